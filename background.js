@@ -4,13 +4,35 @@ import init, { solve } from "./wasm/solver.js";
   await init();
 })();
 
+// What went wrong in the solver, for the page to show. A panic caught by the solver comes back
+// as a JSON report with the message and the board's hash, to attach to a bug report. Builds that
+// abort on panic trap instead, which throws a `WebAssembly.RuntimeError`.
+function describeError(error) {
+  if (typeof error !== "string") {
+    return { message: String(error), panic: error instanceof WebAssembly.RuntimeError };
+  }
+
+  try {
+    const report = JSON.parse(error);
+    if (report.error === "panic") {
+      return { message: report.message, panic: true, boardHash: report.boardHash };
+    }
+  } catch (_) {
+    // Not a report, just a message about the input.
+  }
+  return { message: error, panic: false };
+}
+
 chrome.runtime.onMessage.addListener(async (message, _, sendResponse) => {
   if (message.action !== "solve") {
     return;
   }
 
-  const solution = solve(message.gameJson);
-  sendResponse(solution);
+  try {
+    sendResponse({ solution: solve(message.gameJson) });
+  } catch (error) {
+    sendResponse({ error: describeError(error) });
+  }
 });
 
 chrome.action.onClicked.addListener((tab) => {
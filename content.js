@@ -38,12 +38,22 @@ async function solveGame() {
   const grid = document.getElementById("queens-grid");
   const game = buildGame(grid);
 
-  const solutionJson = await chrome.runtime.sendMessage({
+  const response = await chrome.runtime.sendMessage({
     action: "solve",
     gameJson: JSON.stringify(game),
   });
 
-  const solution = new Set(JSON.parse(solutionJson));
+  if (response.error) {
+    const { message, panic, boardHash } = response.error;
+    if (panic) {
+      console.error(`The solver crashed: ${message}`, boardHash ? `(board ${boardHash})` : "");
+    } else {
+      console.error(`Could not solve the board: ${message}`);
+    }
+    return false;
+  }
+
+  const solution = new Set(JSON.parse(response.solution));
 
   for (const child of grid.children) {
     const idx = parseInt(child.dataset.cellIdx);
@@ -55,11 +65,14 @@ async function solveGame() {
       simulateClick(child);
     }
   }
+
+  return true;
 }
 
 (async () => {
   console.log("Solving...");
   const start = Date.now();
-  await solveGame();
-  console.log(`Solved! (${(Date.now() - start) / 1000.0}s)`);
+  if (await solveGame()) {
+    console.log(`Solved! (${(Date.now() - start) / 1000.0}s)`);
+  }
 })();
@@ -0,0 +1,191 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use solver::corpus::{CorpusBoard, CORPUS};
+
+/// Solves `board`, checking the answer against the one the corpus gives.
+fn solve(board: &CorpusBoard) {
+    let mut solution: Vec<usize> =
+        serde_json::from_str(&solver::solve(board.json.to_string()).unwrap()).unwrap();
+    solution.sort_unstable();
+
+    assert_eq!(
+        solution, board.solution,
+        "wrong solution for {}",
+        board.name
+    );
+}
+
+/// Solves `board` like `solve`, but with the fixed default heuristics rather than those picked
+/// for its size, to measure what picking them gains.
+fn solve_fixed(board: &CorpusBoard) {
+    solve_with(board, "{}");
+}
+
+/// Solves `board` with `options_json`, checking the answer like `solve`.
+fn solve_with(board: &CorpusBoard, options_json: &str) {
+    let output: serde_json::Value = serde_json::from_str(
+        &solver::solve_with_options(board.json.to_string(), options_json.to_string()).unwrap(),
+    )
+    .unwrap();
+    let mut solution: Vec<usize> = serde_json::from_value(output["solution"].clone()).unwrap();
+    solution.sort_unstable();
+
+    assert_eq!(
+        solution, board.solution,
+        "wrong solution for {}",
+        board.name
+    );
+}
+
+fn count_solutions(board: &CorpusBoard) {
+    let count: u64 =
+        serde_json::from_str(&solver::count_solutions(board.json.to_string()).unwrap()).unwrap();
+
+    let expected = !board.solution.is_empty() as u64;
+    assert_eq!(count, expected, "wrong count for {}", board.name);
+}
+
+fn bench_solve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(20);
+
+    for board in CORPUS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(board.name),
+            board,
+            |b, board| b.iter(|| solve(board)),
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_solve_fixed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve_fixed");
+    group.sample_size(20);
+
+    for board in CORPUS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(board.name),
+            board,
+            |b, board| b.iter(|| solve_fixed(board)),
+        );
+    }
+
+    group.finish();
+}
+
+/// Solves every board with the fixed default heuristics and the ordering weights tuned on the
+/// corpus, to compare against `solve_fixed` for what tuning gains.
+fn bench_solve_tuned(c: &mut Criterion) {
+    let corpus: Vec<String> = CORPUS
+        .iter()
+        .map(|board| board.json.split_whitespace().collect())
+        .collect();
+    let tuning: serde_json::Value =
+        serde_json::from_str(&solver::tune_ordering_weights(corpus.join("\n")).unwrap()).unwrap();
+    let options_json = serde_json::json!({ "orderingWeights": tuning["weights"] }).to_string();
+
+    let mut group = c.benchmark_group("solve_tuned");
+    group.sample_size(20);
+
+    for board in CORPUS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(board.name),
+            board,
+            |b, board| b.iter(|| solve_with(board, &options_json)),
+        );
+    }
+
+    group.finish();
+}
+
+/// Solves every board with the fixed default heuristics and `decompose` on, to compare against
+/// `solve_fixed` for what trying to split the board costs, all of it on the boards that do not
+/// split.
+fn bench_solve_decomposed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve_decomposed");
+    group.sample_size(20);
+
+    for board in CORPUS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(board.name),
+            board,
+            |b, board| b.iter(|| solve_with(board, r#"{"decompose":true}"#)),
+        );
+    }
+
+    group.finish();
+}
+
+/// Solves every board with the fixed default heuristics on the bitboard, whose spot counts the
+/// `simd` feature replaces. Criterion runs natively, where `simd` does nothing, so its delta comes
+/// from timing `solve_with_options` with these options on two wasm builds under node 20, both
+/// with `+simd128` and only the feature differing: the simd counts were 10-20% slower, e.g.
+/// hard-10x10 went from 58ms to 71ms and 11x11 from 1.19ms to 1.44ms.
+fn bench_solve_bitboard(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve_bitboard");
+    group.sample_size(20);
+
+    for board in CORPUS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(board.name),
+            board,
+            |b, board| b.iter(|| solve_with(board, r#"{"bitboard":true}"#)),
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_count_solutions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("count_solutions");
+    group.sample_size(20);
+
+    for board in CORPUS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(board.name),
+            board,
+            |b, board| b.iter(|| count_solutions(board)),
+        );
+    }
+
+    group.finish();
+}
+
+/// Generates the candidates of the root of every board, and of the node halfway down to the
+/// solution of those that have one.
+fn bench_candidates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("candidates");
+
+    for board in CORPUS {
+        let halfway = &board.solution[..board.solution.len() / 2];
+        let nodes = [("root", &[][..]), ("halfway", halfway)];
+
+        for (node, queens) in nodes {
+            if node == "halfway" && queens.is_empty() {
+                continue;
+            }
+
+            let id = BenchmarkId::new(node, board.name);
+            solver::bench_candidates(board.json, queens, |generate| {
+                assert!(generate() > 0, "no candidates for {}", board.name);
+                group.bench_function(id, |b| b.iter(&mut *generate));
+            })
+            .unwrap();
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_solve,
+    bench_solve_fixed,
+    bench_solve_tuned,
+    bench_solve_decomposed,
+    bench_solve_bitboard,
+    bench_count_solutions,
+    bench_candidates
+);
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+use crate::options::Options;
+use crate::pack::canonical_hash;
+use crate::strategy::{Outcome, Status};
+use crate::utils::hash_hex;
+use crate::Game;
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+/// How many solves the activity log keeps unless told otherwise.
+const DEFAULT_CAPACITY: usize = 32;
+
+thread_local! {
+    static ACTIVITY: RefCell<VecDeque<Activity>> = const { RefCell::new(VecDeque::new()) };
+    static CAPACITY: Cell<usize> = const { Cell::new(DEFAULT_CAPACITY) };
+}
+
+/// A solve remembered by the activity log, for diagnosing reports of slowness. Holds nothing
+/// that could tell the board itself.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Activity {
+    /// The entry point of the solve: `solve`, `solveWithOptions`, or `solveSession`.
+    pub entry: &'static str,
+    /// The `canonical_hash` of the game, telling repeated solves of one board apart from
+    /// different boards, however their JSON was written.
+    pub board_hash: String,
+    pub rows: usize,
+    pub cols: usize,
+    pub status: Status,
+    pub nodes: usize,
+    /// Milliseconds the solve took, over all its chunks for a session.
+    pub ms: f64,
+    /// The strategy that solved, and the tier that picked the heuristics if they were left to
+    /// one.
+    pub algorithm: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heuristics: Option<&'static str>,
+    /// A hash of the options, in hex, telling solves with the same configuration apart from
+    /// others.
+    pub options_hash: String,
+}
+
+/// Adds the solve of `game` under `options` to the activity log, dropping the oldest entry if
+/// the log is full.
+pub(crate) fn record_solve(
+    entry: &'static str,
+    game: &Game,
+    options: &Options,
+    outcome: &Outcome,
+    ms: f64,
+) {
+    let capacity = CAPACITY.with(Cell::get);
+    if capacity == 0 {
+        return;
+    }
+
+    let activity = Activity {
+        entry,
+        board_hash: canonical_hash(game),
+        rows: game.rows,
+        cols: game.cols,
+        status: outcome.status,
+        nodes: outcome.stats.nodes,
+        ms,
+        algorithm: options
+            .algorithm
+            .clone()
+            .unwrap_or_else(|| "systematic".to_string()),
+        heuristics: outcome.stats.heuristics,
+        options_hash: hash_hex(&format!("{:?}", options)),
+    };
+
+    ACTIVITY.with(|log| {
+        let mut log = log.borrow_mut();
+        while log.len() >= capacity {
+            log.pop_front();
+        }
+        log.push_back(activity);
+    });
+}
+
+/// The solves in the activity log, oldest first.
+pub(crate) fn recent_activity() -> Vec<Activity> {
+    ACTIVITY.with(|log| log.borrow().iter().cloned().collect())
+}
+
+pub(crate) fn clear_activity() {
+    ACTIVITY.with(|log| log.borrow_mut().clear());
+}
+
+/// Keeps at most `capacity` solves from now on, dropping the oldest beyond that. None are kept
+/// with a capacity of 0.
+pub(crate) fn set_activity_capacity(capacity: usize) {
+    CAPACITY.with(|cell| cell.set(capacity));
+    ACTIVITY.with(|log| {
+        let mut log = log.borrow_mut();
+        let excess = log.len().saturating_sub(capacity);
+        log.drain(..excess);
+    });
+}
@@ -0,0 +1,321 @@
+use crate::{AdjacentsLookup, Game};
+use std::rc::Rc;
+
+/// The operations the search needs from its record of which cells can still hold a queen.
+pub(crate) trait CellAvailability {
+    fn adj_lookup(&self) -> &AdjacentsLookup;
+
+    fn is_available(&self, idx: usize) -> bool;
+
+    /// Pushes every available cell onto `out` as `(row, col)`, in row-major order.
+    fn collect_available(&self, game: &Game, out: &mut Vec<(usize, usize)>);
+
+    fn row_spots(&self) -> &[usize];
+
+    fn col_spots(&self) -> &[usize];
+
+    /// Empty when the color rule does not apply.
+    fn color_spots(&self) -> &[usize];
+
+    /// Rules out every cell threatened by a queen on `idx`.
+    fn place(&mut self, game: &Game, idx: usize);
+
+    /// Undoes `place` for the queen on `idx`, which must be the most recent change.
+    fn remove(&mut self, game: &Game, idx: usize);
+
+    /// Rules out the single cell `idx` without placing a queen.
+    fn exclude(&mut self, game: &Game, idx: usize);
+
+    /// Undoes `exclude` for `idx`, which must be the most recent change.
+    fn restore(&mut self, game: &Game, idx: usize);
+}
+
+/// Tracks which cells can still hold a queen, along with the spots left in every row, column,
+/// and color.
+///
+/// Placing or removing a queen only touches the cells it affects (its row, column, color region,
+/// and neighbors) instead of rescanning the board at every node.
+pub(crate) struct Availability {
+    adj_lookup: Rc<AdjacentsLookup>,
+    /// How many placed queens rule out each cell. A cell is available when this is zero.
+    blockers: Vec<u32>,
+    row_spots: Vec<usize>,
+    col_spots: Vec<usize>,
+    color_spots: Vec<usize>,
+    cells_by_color: Vec<Vec<usize>>,
+}
+
+impl Availability {
+    pub fn new(game: &Game) -> Self {
+        let mut cells_by_color = vec![vec![]; game.num_colors()];
+        let mut color_spots = vec![0; game.num_colors()];
+
+        if game.has_color_rule() {
+            for (idx, &color) in game.idx_to_color.iter().enumerate() {
+                cells_by_color[color].push(idx);
+                color_spots[color] += 1;
+            }
+        }
+
+        Self {
+            adj_lookup: AdjacentsLookup::for_game(game),
+            blockers: vec![0; game.rows * game.cols],
+            row_spots: vec![game.cols; game.rows],
+            col_spots: vec![game.rows; game.cols],
+            color_spots,
+            cells_by_color,
+        }
+    }
+
+    fn update(&mut self, game: &Game, idx: usize, placing: bool) {
+        let (row, col) = (idx / game.cols, idx % game.cols);
+
+        for c in 0..game.cols {
+            self.update_cell(game, row * game.cols + c, placing);
+        }
+
+        for r in 0..game.rows {
+            self.update_cell(game, r * game.cols + col, placing);
+        }
+
+        if game.has_color_rule() {
+            let color = game.idx_to_color[idx];
+            for i in 0..self.cells_by_color[color].len() {
+                self.update_cell(game, self.cells_by_color[color][i], placing);
+            }
+        }
+
+        for i in 0..self.adj_lookup.neighbors(idx).len() {
+            self.update_cell(game, self.adj_lookup.neighbors(idx)[i] as usize, placing);
+        }
+    }
+
+    #[inline(always)]
+    fn update_cell(&mut self, game: &Game, idx: usize, placing: bool) {
+        let was_available = self.is_available(idx);
+
+        if placing {
+            self.blockers[idx] += 1;
+        } else {
+            self.blockers[idx] -= 1;
+        }
+
+        if was_available == self.is_available(idx) {
+            return;
+        }
+
+        let (row, col) = (idx / game.cols, idx % game.cols);
+
+        if placing {
+            self.row_spots[row] -= 1;
+            self.col_spots[col] -= 1;
+            if let Some(spots) = self.color_spots.get_mut(game.idx_to_color[idx]) {
+                *spots -= 1;
+            }
+        } else {
+            self.row_spots[row] += 1;
+            self.col_spots[col] += 1;
+            if let Some(spots) = self.color_spots.get_mut(game.idx_to_color[idx]) {
+                *spots += 1;
+            }
+        }
+    }
+}
+
+impl CellAvailability for Availability {
+    #[inline(always)]
+    fn adj_lookup(&self) -> &AdjacentsLookup {
+        &self.adj_lookup
+    }
+
+    #[inline(always)]
+    fn is_available(&self, idx: usize) -> bool {
+        self.blockers[idx] == 0
+    }
+
+    fn collect_available(&self, game: &Game, out: &mut Vec<(usize, usize)>) {
+        for row in 0..game.rows {
+            for col in 0..game.cols {
+                if self.is_available(row * game.cols + col) {
+                    out.push((row, col));
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn row_spots(&self) -> &[usize] {
+        &self.row_spots
+    }
+
+    #[inline(always)]
+    fn col_spots(&self) -> &[usize] {
+        &self.col_spots
+    }
+
+    #[inline(always)]
+    fn color_spots(&self) -> &[usize] {
+        &self.color_spots
+    }
+
+    fn place(&mut self, game: &Game, idx: usize) {
+        self.update(game, idx, true);
+    }
+
+    fn remove(&mut self, game: &Game, idx: usize) {
+        self.update(game, idx, false);
+    }
+
+    fn exclude(&mut self, game: &Game, idx: usize) {
+        self.update_cell(game, idx, true);
+    }
+
+    fn restore(&mut self, game: &Game, idx: usize) {
+        self.update_cell(game, idx, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitAvailability;
+    use crate::corpus::CORPUS;
+    use crate::options::Options;
+    use crate::search::{RunEnd, Search};
+    use crate::utils::Rng;
+    use crate::NoGoods;
+
+    /// Which cells are available with queens on `queens` and `excluded` ruled out, and the spots
+    /// left in every row, column, and color, found by scanning the whole board.
+    fn rescan(game: &Game, queens: &[usize], excluded: &[usize]) -> (Vec<bool>, [Vec<usize>; 3]) {
+        let lookup = AdjacentsLookup::for_game(game);
+        let attacks = |queen: usize, idx: usize| {
+            queen / game.cols == idx / game.cols
+                || queen % game.cols == idx % game.cols
+                || (game.has_color_rule() && game.idx_to_color[queen] == game.idx_to_color[idx])
+                || lookup.neighbors(queen).contains(&(idx as u16))
+        };
+
+        let available: Vec<_> = (0..game.rows * game.cols)
+            .map(|idx| !excluded.contains(&idx) && queens.iter().all(|&queen| !attacks(queen, idx)))
+            .collect();
+
+        let mut spots = [
+            vec![0; game.rows],
+            vec![0; game.cols],
+            vec![0; game.num_colors()],
+        ];
+        for idx in (0..available.len()).filter(|&idx| available[idx]) {
+            spots[0][idx / game.cols] += 1;
+            spots[1][idx % game.cols] += 1;
+            if game.has_color_rule() {
+                spots[2][game.idx_to_color[idx]] += 1;
+            }
+        }
+
+        (available, spots)
+    }
+
+    fn assert_matches(
+        game: &Game,
+        availability: &impl CellAvailability,
+        queens: &[usize],
+        excluded: &[usize],
+    ) {
+        let (available, [rows, cols, colors]) = rescan(game, queens, excluded);
+        for (idx, &available) in available.iter().enumerate() {
+            assert_eq!(availability.is_available(idx), available, "cell {}", idx);
+        }
+        assert_eq!(availability.row_spots(), rows);
+        assert_eq!(availability.col_spots(), cols);
+        assert_eq!(availability.color_spots(), colors);
+    }
+
+    /// Random queens placed, cells ruled out, and both undone again leave the counters as a scan
+    /// of the board finds them, after every change.
+    #[test]
+    fn updates_match_a_rescan() {
+        let mut rng = Rng::new(112);
+        for board in CORPUS {
+            let game = Game::from_json(board.json).unwrap();
+            let mut counted = Availability::new(&game);
+            let mut bits = BitAvailability::new(&game);
+            // Every change as the cell and whether it placed a queen.
+            let mut changes: Vec<(usize, bool)> = vec![];
+
+            for _ in 0..500 {
+                let open: Vec<_> = (0..game.rows * game.cols)
+                    .filter(|&idx| counted.is_available(idx))
+                    .collect();
+
+                if open.is_empty() || (!changes.is_empty() && rng.below(3) == 0) {
+                    let (idx, queen) = changes.pop().unwrap();
+                    if queen {
+                        counted.remove(&game, idx);
+                        bits.remove(&game, idx);
+                    } else {
+                        counted.restore(&game, idx);
+                        bits.restore(&game, idx);
+                    }
+                } else {
+                    let idx = open[rng.below(open.len())];
+                    let queen = rng.below(2) == 0;
+                    if queen {
+                        counted.place(&game, idx);
+                        bits.place(&game, idx);
+                    } else {
+                        counted.exclude(&game, idx);
+                        bits.exclude(&game, idx);
+                    }
+                    changes.push((idx, queen));
+                }
+
+                let queens: Vec<_> = changes
+                    .iter()
+                    .filter(|change| change.1)
+                    .map(|change| change.0)
+                    .collect();
+                let excluded: Vec<_> = changes
+                    .iter()
+                    .filter(|change| !change.1)
+                    .map(|change| change.0)
+                    .collect();
+                assert_matches(&game, &counted, &queens, &excluded);
+                assert_matches(&game, &bits, &queens, &excluded);
+            }
+        }
+    }
+
+    /// The decisions in place at every node the search enters, found by pausing after each one.
+    fn visited<A: CellAvailability>(
+        game: &Game,
+        options: &Options,
+        availability: A,
+    ) -> Vec<Vec<usize>> {
+        let mut search = Search::<_, NoGoods>::with_cache(game, options, availability);
+        let mut visited = vec![];
+        while search.run_more(1) == RunEnd::Stopped {
+            visited.push(search.solution.clone());
+        }
+
+        visited
+    }
+
+    /// The counters and the bitboard lead the search through the same nodes.
+    #[test]
+    fn bitboard_visits_the_same_nodes() {
+        for board in CORPUS {
+            let game = Game::from_json(board.json).unwrap();
+            for json in ["{}", r#"{"propagate":true,"arcConsistency":true}"#] {
+                let options = Options::from_json(json).unwrap();
+                assert_eq!(
+                    visited(&game, &options, Availability::new(&game)),
+                    visited(&game, &options, BitAvailability::new(&game)),
+                    "{} with {}",
+                    board.name,
+                    json
+                );
+            }
+        }
+    }
+}
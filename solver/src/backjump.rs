@@ -0,0 +1,333 @@
+use crate::availability::CellAvailability;
+use crate::profile::Event;
+use crate::propagation::Step;
+use crate::search::{RunEnd, Search, Unit};
+use crate::NogoodCache;
+
+/// The result of searching a subtree.
+enum Outcome {
+    Solved,
+    /// The run used up its node budget, so the subtree was not searched completely.
+    Stopped,
+    /// The subtree has no solution because of the decisions in this set, given as a bitmask over
+    /// the depths at which they were placed.
+    Conflict(u64),
+}
+
+impl<A: CellAvailability, N: NogoodCache> Search<'_, A, N> {
+    /// Backtracking search with conflict-directed backjumping.
+    ///
+    /// Every failed subtree reports the set of earlier placements actually responsible for its
+    /// failure. That set is recorded as the nogood instead of the whole prefix, and the search
+    /// jumps straight back to the most recent placement in it rather than retrying siblings that
+    /// cannot help.
+    ///
+    /// Queens forced by propagation are consequences rather than decisions, so wherever one shows
+    /// up in a conflict it is replaced by the decisions that forced it. Conflicts and nogoods
+    /// therefore only ever name decisions.
+    pub(crate) fn backjump<const COLORS: bool>(&mut self) -> RunEnd {
+        match self.backjump_node::<COLORS>() {
+            Outcome::Solved => RunEnd::Solved,
+            Outcome::Stopped => RunEnd::Stopped,
+            Outcome::Conflict(_) => RunEnd::Exhausted,
+        }
+    }
+
+    fn backjump_node<const COLORS: bool>(&mut self) -> Outcome {
+        if self.out_of_budget() {
+            return Outcome::Stopped;
+        }
+
+        self.stats.nodes += 1;
+        self.profiler.count(self.sorted_solution.len(), Event::Node);
+
+        let steps = self.propagate::<COLORS>();
+
+        if self.is_solved() {
+            return Outcome::Solved;
+        }
+
+        let depth = self.solution.len();
+        let depth_bit = 1u64 << depth;
+
+        // The conflict set of every candidate tried here, minus the candidate itself.
+        let mut child_conflicts = self.scratch.conflict_lists.pop().unwrap_or_default();
+        child_conflicts.clear();
+
+        let candidates = self.get_candidates::<COLORS>();
+        for &(row, col) in &candidates {
+            let idx = row * self.game.cols + col;
+            let sorted_pos = self.push(idx);
+
+            let nogood = &mut self.scratch.nogood;
+            let conflict = if self.nogoods.find_subset(&self.sorted_solution, nogood) {
+                let depth = self.sorted_solution.len() - 1;
+                self.profiler.count(depth, Event::NogoodHit);
+                nogood_depths(nogood, &self.solution)
+            } else {
+                self.set_queen(idx, true);
+
+                let conflict = match self.backjump_node::<COLORS>() {
+                    Outcome::Solved => return Outcome::Solved,
+                    Outcome::Stopped => {
+                        self.set_queen(idx, false);
+                        self.pop(sorted_pos);
+                        self.recycle(candidates);
+                        self.scratch.conflict_lists.push(child_conflicts);
+                        self.unpropagate(steps);
+                        return Outcome::Stopped;
+                    }
+                    Outcome::Conflict(conflict) => conflict,
+                };
+
+                self.set_queen(idx, false);
+                conflict
+            };
+
+            self.pop(sorted_pos);
+
+            // The failure does not involve this candidate, so the current prefix is already
+            // doomed and trying the remaining candidates is pointless.
+            if conflict & depth_bit == 0 {
+                self.recycle(candidates);
+                self.scratch.conflict_lists.push(child_conflicts);
+                return self.retract(steps, conflict);
+            }
+
+            child_conflicts.push((idx, conflict & !depth_bit));
+        }
+
+        self.recycle(candidates);
+
+        let conflict = self.resolve_forced(self.explain_failure::<COLORS>(&child_conflicts));
+        self.scratch.conflict_lists.push(child_conflicts);
+
+        let (nogood, solution) = (&mut self.scratch.nogood, &self.solution);
+        nogood.clear();
+        nogood.extend(
+            (0..depth)
+                .filter(|&d| (conflict >> d) & 1 == 1)
+                .map(|d| solution[d]),
+        );
+        nogood.sort_unstable();
+        self.nogoods.insert(nogood);
+
+        self.retract(steps, conflict)
+    }
+
+    /// Undoes the `steps` propagated at the current node before reporting `conflict`.
+    fn retract(&mut self, steps: usize, conflict: u64) -> Outcome {
+        self.unpropagate(steps);
+
+        Outcome::Conflict(conflict)
+    }
+
+    /// Records the decisions responsible for `step`, which is about to be applied.
+    ///
+    /// A forced queen is explained by whatever eliminated the other cells of its unit, a confined
+    /// cell by whatever eliminated the cells of its colors outside the lines, and a wiped out cell
+    /// by whatever eliminated the cells of the unit it would have emptied.
+    pub(crate) fn record_reason<const COLORS: bool>(&mut self, step: &Step) {
+        let game = self.game;
+
+        match *step {
+            Step::Forced { idx, unit } => {
+                let cells = unit.cells(&self.index).iter().copied();
+                let cells = cells.filter(|&cell| cell != idx);
+                let reason = self.resolve_forced(self.eliminations_of::<COLORS>(cells));
+                self.forced_reasons.push((self.solution.len(), reason));
+            }
+            Step::Confined { idx, color, line } => {
+                let cells = Unit::Color(color).cells(&self.index).iter().copied();
+                let cells = cells.filter(|&cell| !line.contains(game, cell));
+                let reason = self.resolve_forced(self.eliminations_of::<COLORS>(cells));
+                self.excluded_reasons.push((idx, reason));
+            }
+            Step::Subset { idx, colors, lines } => {
+                let index = &self.index;
+                let cells = (0..game.num_colors())
+                    .filter(|&color| {
+                        (colors >> color) & 1 == 1
+                            && lines.excludes_some(index.color_rows[color], index.color_cols[color])
+                    })
+                    .flat_map(|color| index.cells_by_color[color].iter().copied())
+                    .filter(|&cell| !lines.contains(game, cell));
+                let reason = self.resolve_forced(self.eliminations_of::<COLORS>(cells));
+                self.excluded_reasons.push((idx, reason));
+            }
+            Step::Wipeout { idx, unit } => {
+                let cells = unit.cells(&self.index).iter().copied();
+                let cells = cells.filter(|&cell| !self.availability.is_available(cell));
+                let reason = self.resolve_forced(self.eliminations_of::<COLORS>(cells));
+                self.excluded_reasons.push((idx, reason));
+            }
+        }
+    }
+
+    /// Combines the reasons every cell in `cells` is unavailable.
+    fn eliminations_of<const COLORS: bool>(&self, cells: impl Iterator<Item = usize>) -> u64 {
+        cells
+            .filter_map(|cell| self.eliminated_by::<COLORS>(cell))
+            .fold(0, |mask, reason| mask | reason)
+    }
+
+    /// Replaces every forced queen in `conflict` with the decisions that forced it.
+    fn resolve_forced(&self, conflict: u64) -> u64 {
+        self.forced_reasons
+            .iter()
+            .fold(conflict, |conflict, &(depth, reason)| {
+                if (conflict >> depth) & 1 == 1 {
+                    (conflict & !(1 << depth)) | reason
+                } else {
+                    conflict
+                }
+            })
+    }
+
+    /// Computes why no cell of the current node leads to a solution.
+    ///
+    /// Every solution places a queen in each required unit (row, column, or color) that is still
+    /// empty, so any such unit explains the failure: each of its cells was either eliminated by an
+    /// earlier placement or tried as a candidate whose subtree failed. Among all units, the one
+    /// whose explanation reaches back the furthest is chosen to allow the longest jump.
+    fn explain_failure<const COLORS: bool>(&self, child_conflicts: &[(usize, u64)]) -> u64 {
+        let game = self.game;
+        let used = &self.used;
+
+        // Without an explanation the whole prefix is to blame, which is always sound.
+        let mut best = (1u64 << self.solution.len()) - 1;
+
+        let mut consider = |cells: &mut dyn Iterator<Item = usize>| {
+            let mut conflict = 0u64;
+
+            for idx in cells {
+                match child_conflicts.iter().find(|&&(i, _)| i == idx) {
+                    Some(&(_, child)) => conflict |= child,
+                    None => match self.eliminated_by::<COLORS>(idx) {
+                        Some(reason) => conflict |= reason,
+                        None => return,
+                    },
+                }
+            }
+
+            if conflict.leading_zeros() > best.leading_zeros()
+                || (conflict.leading_zeros() == best.leading_zeros()
+                    && conflict.count_ones() < best.count_ones())
+            {
+                best = conflict;
+            }
+        };
+
+        let index = &self.index;
+
+        for row in (0..game.rows).filter(|&row| (used.rows >> row) & 1 == 0) {
+            consider(&mut index.cells_by_row[row].iter().copied());
+        }
+
+        let missing_cols = used.required_cols & !used.cols;
+        for col in (0..game.cols).filter(|&col| (missing_cols >> col) & 1 == 1) {
+            consider(&mut index.cells_by_col[col].iter().copied());
+        }
+
+        if COLORS {
+            let missing_colors = used.required_colors & !used.colors;
+            for color in (0..game.colors.len()).filter(|&color| (missing_colors >> color) & 1 == 1)
+            {
+                consider(&mut index.cells_by_color[color].iter().copied());
+            }
+        }
+
+        best
+    }
+
+    /// Finds why a queen on `idx` is ruled out, as a mask over depths: the earliest placement that
+    /// attacks it, or else the reason propagation ruled it out.
+    fn eliminated_by<const COLORS: bool>(&self, idx: usize) -> Option<u64> {
+        let game = self.game;
+        let adj_lookup = self.availability.adj_lookup();
+        let (row, col) = (idx / game.cols, idx % game.cols);
+
+        let attacker = self.solution.iter().position(|&queen| {
+            (queen / game.cols == row)
+                || (queen % game.cols == col)
+                || (COLORS && game.idx_to_color[queen] == game.idx_to_color[idx])
+                || adj_lookup.neighbors(queen).contains(&(idx as u16))
+        });
+
+        attacker.map(|depth| 1 << depth).or_else(|| {
+            self.excluded_reasons
+                .iter()
+                .find(|&&(cell, _)| cell == idx)
+                .map(|&(_, reason)| reason)
+        })
+    }
+}
+
+/// Maps the indices of a nogood back to the depths at which they were placed.
+fn nogood_depths(nogood: &[usize], solution: &[usize]) -> u64 {
+    solution
+        .iter()
+        .enumerate()
+        .filter(|(_, idx)| nogood.contains(idx))
+        .fold(0, |mask, (depth, _)| mask | (1 << depth))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availability::Availability;
+    use crate::corpus::CORPUS;
+    use crate::options::Options;
+    use crate::{Game, NoGoods};
+
+    /// How the search ends on `game`, with the cells of the solution sorted, and its nodes.
+    fn search(game: &Game, options: &Options) -> (RunEnd, Vec<usize>, usize) {
+        let mut search = Search::<_, NoGoods>::with_cache(game, options, Availability::new(game));
+        let end = search.run();
+        let mut solution = search.solution.clone();
+        solution.sort_unstable();
+
+        (end, solution, search.stats.nodes)
+    }
+
+    /// Backjumping gives the same answers as backtracking on every board of the corpus, never
+    /// entering more nodes, and far fewer where backtracking thrashes.
+    #[test]
+    fn agrees_with_backtracking() {
+        for board in CORPUS {
+            let game = Game::from_json(board.json).unwrap();
+            for json in [
+                "{}",
+                r#"{"branching":"unit"}"#,
+                r#"{"propagate":true}"#,
+                r#"{"propagate":true,"branching":"unit","confinement":true}"#,
+            ] {
+                let mut options = Options::from_json(json).unwrap();
+                let (end, solution, nodes) = search(&game, &options);
+                options.backjumping = true;
+                let (jumped_end, jumped_solution, jumped_nodes) = search(&game, &options);
+
+                let context = format!("{} with {}", board.name, json);
+                let expected = match board.solution {
+                    [] => RunEnd::Exhausted,
+                    _ => RunEnd::Solved,
+                };
+                assert_eq!(end, expected, "{}", context);
+                assert_eq!(jumped_end, expected, "{}", context);
+                assert_eq!(solution, board.solution, "{}", context);
+                assert_eq!(jumped_solution, board.solution, "{}", context);
+                assert!(
+                    jumped_nodes <= nodes,
+                    "{}: {} > {}",
+                    context,
+                    jumped_nodes,
+                    nodes
+                );
+
+                if json == "{}" && ["hard-10x10", "unsolvable-9x9"].contains(&board.name) {
+                    assert!(jumped_nodes * 100 < nodes, "{}", context);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use crate::availability::CellAvailability;
+use crate::options::{Heuristics, NogoodStore, Options, TieBreak, UnitWeights, ValueOrdering};
+use crate::search::Search;
+use crate::strategy::Status;
+use crate::utils::now_ms;
+use crate::{run_solver, Game};
+use serde::{Deserialize, Serialize};
+
+/// The node budget of every run in the default matrix, so that a configuration taking minutes
+/// on some board does not hold up the rest of the corpus.
+const DEFAULT_MAX_NODES: usize = 2_000_000;
+
+/// The node budget of every solve while tuning ordering weights, low enough that a poor table
+/// cannot stall the grid.
+const TUNING_MAX_NODES: usize = 200_000;
+
+/// The depths counting as early in the tables `tune_weights` tries, the rest of the search
+/// being late.
+const EARLY_DEPTHS: usize = 3;
+
+/// The multipliers `tune_weights` tries for the columns and colors, the rows keeping weight 1
+/// since only the ratios between the weights change the order.
+const WEIGHT_GRID: &[f64] = &[0.5, 1.0, 2.0];
+
+/// A set of options to benchmark, named for the report.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    pub name: String,
+    #[serde(default)]
+    pub options: Options,
+}
+
+impl Config {
+    fn new(name: &str, options: Options) -> Self {
+        Self {
+            name: name.to_string(),
+            options,
+        }
+    }
+
+    /// The default options, followed by each of the nogood cache, forward checking, and the
+    /// ordering of candidates turned off alone and then all together, by the nogoods kept in a
+    /// hash set instead of a trie or screened by signature, by each tie-break of the candidates
+    /// other than their index, by the board split into independent parts, and by the heuristics
+    /// left to `Heuristics::Auto`, all giving up after `DEFAULT_MAX_NODES`.
+    pub fn default_matrix() -> Vec<Self> {
+        let baseline = Options {
+            max_nodes: Some(DEFAULT_MAX_NODES),
+            ..Options::default()
+        };
+        let no_nogoods = Options {
+            nogood_store: NogoodStore::Off,
+            ..baseline.clone()
+        };
+        let no_forward_check = Options {
+            forward_checking: Some(false),
+            ..baseline.clone()
+        };
+        let no_sort = Options {
+            value_ordering: ValueOrdering::BoardOrder,
+            ..baseline.clone()
+        };
+        let all_off = Options {
+            nogood_store: NogoodStore::Off,
+            forward_checking: Some(false),
+            value_ordering: ValueOrdering::BoardOrder,
+            ..baseline.clone()
+        };
+        let hash_nogoods = Options {
+            nogood_store: NogoodStore::Hash,
+            ..baseline.clone()
+        };
+
+        let signatures = Options {
+            nogood_signatures: true,
+            ..baseline.clone()
+        };
+        let degree = Options {
+            tie_break: TieBreak::Degree,
+            ..baseline.clone()
+        };
+        let centrality = Options {
+            tie_break: TieBreak::Centrality,
+            ..baseline.clone()
+        };
+        let decompose = Options {
+            decompose: true,
+            ..baseline.clone()
+        };
+        let auto = Options {
+            heuristics: Heuristics::Auto,
+            ..baseline.clone()
+        };
+
+        vec![
+            Self::new("baseline", baseline),
+            Self::new("noNogoods", no_nogoods),
+            Self::new("noForwardCheck", no_forward_check),
+            Self::new("noSort", no_sort),
+            Self::new("allOff", all_off),
+            Self::new("hashNogoods", hash_nogoods),
+            Self::new("signatures", signatures),
+            Self::new("degreeTieBreak", degree),
+            Self::new("centralityTieBreak", centrality),
+            Self::new("decompose", decompose),
+            Self::new("auto", auto),
+        ]
+    }
+}
+
+/// The results of `run_benchmark`, in the order of the configurations and the corpus.
+#[derive(Serialize, Debug)]
+pub(crate) struct Report {
+    pub configs: Vec<Summary>,
+    pub boards: Vec<BoardRuns>,
+}
+
+/// The totals of one configuration over the corpus.
+#[derive(Serialize, Debug)]
+pub(crate) struct Summary {
+    pub name: String,
+    /// Boards the configuration found a solution for.
+    pub solved: usize,
+    /// Boards the configuration gave up on.
+    pub unknown: usize,
+    pub nodes: usize,
+    pub ms: f64,
+    /// Boards the configuration was the fastest to settle.
+    pub wins: usize,
+}
+
+/// Every configuration's run on one board of the corpus.
+#[derive(Serialize, Debug)]
+pub(crate) struct BoardRuns {
+    /// The board's line in the corpus, counting from 1.
+    pub line: usize,
+    pub runs: Vec<Run>,
+    /// The name of the fastest configuration to solve the board or prove it unsolvable, the
+    /// first of them on a tie. Absent if every configuration gave up.
+    pub winner: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct Run {
+    pub status: Status,
+    pub nodes: usize,
+    /// The fastest of the repeated solves, in milliseconds.
+    pub ms: f64,
+}
+
+/// Solves every board of `corpus` under every configuration, `repeats` times each to take the
+/// fastest time, and tallies nodes, time, and wins per configuration.
+pub(crate) fn run_benchmark(
+    corpus: &[(usize, Game)],
+    configs: &[Config],
+    repeats: usize,
+) -> Report {
+    let mut summaries: Vec<_> = configs
+        .iter()
+        .map(|config| Summary {
+            name: config.name.clone(),
+            solved: 0,
+            unknown: 0,
+            nodes: 0,
+            ms: 0.0,
+            wins: 0,
+        })
+        .collect();
+
+    let boards = corpus
+        .iter()
+        .map(|(line, game)| {
+            let runs: Vec<_> = configs
+                .iter()
+                .map(|config| time_run(game, &config.options, repeats))
+                .collect();
+
+            for (summary, run) in summaries.iter_mut().zip(&runs) {
+                summary.solved += (run.status == Status::Solved) as usize;
+                summary.unknown += (run.status == Status::Unknown) as usize;
+                summary.nodes += run.nodes;
+                summary.ms += run.ms;
+            }
+
+            let winner = (0..runs.len())
+                .filter(|&pos| runs[pos].status != Status::Unknown)
+                .reduce(|best, pos| {
+                    if runs[pos].ms < runs[best].ms {
+                        pos
+                    } else {
+                        best
+                    }
+                });
+            if let Some(winner) = winner {
+                summaries[winner].wins += 1;
+            }
+
+            BoardRuns {
+                line: *line,
+                runs,
+                winner: winner.map(|winner| configs[winner].name.clone()),
+            }
+        })
+        .collect();
+
+    Report {
+        configs: summaries,
+        boards,
+    }
+}
+
+/// The best table of ordering weights `tune_weights` found.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Tuning {
+    /// The table, to pass as `orderingWeights`.
+    pub weights: Vec<UnitWeights>,
+    /// The nodes of the corpus with the table.
+    pub nodes: usize,
+    /// The nodes of the corpus without weights, counting every unit the same.
+    pub baseline_nodes: usize,
+    /// Boards given up on with the table.
+    pub unknown: usize,
+    /// How many tables were tried.
+    pub tried: usize,
+}
+
+/// Grid-searches the ordering weights of the default options for the fewest nodes over
+/// `corpus`, trying every pair of weights from `WEIGHT_GRID` for the columns and colors in the
+/// first `EARLY_DEPTHS` depths and again for the rest. Nodes rather than time are compared, so
+/// the result is the same on every run. Ties keep the table tried first, which starts from even
+/// weights.
+pub(crate) fn tune_weights(corpus: &[(usize, Game)]) -> Tuning {
+    let nodes = |weights: Option<Vec<UnitWeights>>| {
+        let options = Options {
+            max_nodes: Some(TUNING_MAX_NODES),
+            ordering_weights: weights,
+            ..Options::default()
+        };
+
+        corpus.iter().fold((0, 0), |(nodes, unknown), (_, game)| {
+            let outcome = run_solver(game, &options);
+            (
+                nodes + outcome.stats.nodes,
+                unknown + (outcome.status == Status::Unknown) as usize,
+            )
+        })
+    };
+
+    let mut pairs: Vec<_> = WEIGHT_GRID
+        .iter()
+        .flat_map(|&col| WEIGHT_GRID.iter().map(move |&color| (col, color)))
+        .map(|(col, color)| UnitWeights {
+            row: 1.0,
+            col,
+            color,
+        })
+        .collect();
+    // Even weights first, so that they win ties.
+    pairs.sort_by_key(|weights| weights != &UnitWeights::default());
+
+    let (baseline_nodes, baseline_unknown) = nodes(None);
+    let mut best = (
+        baseline_nodes,
+        baseline_unknown,
+        vec![UnitWeights::default()],
+    );
+    let mut tried = 0;
+
+    for &early in &pairs {
+        for &late in &pairs {
+            let mut table = vec![early; EARLY_DEPTHS];
+            table.push(late);
+
+            let (total, unknown) = nodes(Some(table.clone()));
+            tried += 1;
+            if (unknown, total) < (best.1, best.0) {
+                best = (total, unknown, table);
+            }
+        }
+    }
+
+    Tuning {
+        weights: best.2,
+        nodes: best.0,
+        baseline_nodes,
+        unknown: best.1,
+        tried,
+    }
+}
+
+/// Solves `game` `repeats` times, which all visit the same nodes, keeping the fastest time.
+fn time_run(game: &Game, options: &Options, repeats: usize) -> Run {
+    let mut best = f64::INFINITY;
+    let mut outcome = None;
+
+    for _ in 0..repeats {
+        let start = now_ms();
+        let solved = run_solver(game, options);
+        best = best.min(now_ms() - start);
+        outcome = Some(solved);
+    }
+
+    let outcome = outcome.unwrap();
+    Run {
+        status: outcome.status,
+        nodes: outcome.stats.nodes,
+        ms: best,
+    }
+}
+
+/// Places `queens` and hands `bench` a function generating the candidates of the node reached,
+/// for `bench_candidates`. Each call leaves the search as it found it.
+pub(crate) fn time_candidates<A: CellAvailability>(
+    game: &Game,
+    options: &Options,
+    availability: A,
+    queens: &[usize],
+    bench: impl FnOnce(&mut dyn FnMut() -> usize),
+) -> Result<(), String> {
+    let mut search = Search::new(game, options, availability);
+    if !search.assume(queens, &[]) {
+        return Err(format!("Queens on {:?} threaten each other", queens));
+    }
+
+    let colors = game.has_color_rule();
+    bench(&mut || {
+        let candidates = if colors {
+            search.get_candidates::<true>()
+        } else {
+            search.get_candidates::<false>()
+        };
+        let count = candidates.len();
+        search.recycle(candidates);
+        count
+    });
+
+    Ok(())
+}
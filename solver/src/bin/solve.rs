@@ -0,0 +1,595 @@
+//! Solves a game from the command line, as `solve_with_options` does in the extension, or runs
+//! one of the analyses taking files:
+//!
+//! ```text
+//! cargo run --bin solve -- game.json [--options options.json] [--algorithm name] \
+//!     [--bundle out.json] [--stats]
+//! cargo run --bin solve -- find-duplicates corpus.jsonl game.json...
+//! cargo run --bin solve -- census game.json [--unit '{"row": 0}']
+//! cargo run --bin solve -- generate --rows 8 --count 10 [--technique name] [--seed n] \
+//!     [--title text]
+//! cargo run --release --bin solve -- bench --compare corpus.jsonl [--configs configs.json] \
+//!     [--repeats n] [--report report.json]
+//! cargo run --release --bin solve -- tune corpus.jsonl
+//! ```
+//!
+//! Prints the output of `solve_with_options`. `--algorithm` picks the strategy by its registered
+//! name, in place of any the options name. With `--bundle`, also writes the `debug_bundle` of the
+//! game to `out.json`, the blob the extension attaches to bug reports. `--stats` follows the
+//! output with a table of the search statistics: the totals, then what the search did at every
+//! depth, which needs a build with the `profile` feature.
+//!
+//! `find-duplicates` prints the `find_duplicates` of each game against the corpus, one game per
+//! line, indexing the corpus once for all of them.
+//!
+//! `census` prints the `branch_census` of the game: how many solutions go through each cell of
+//! the unit, by default the one the counter branches on first.
+//!
+//! `generate` prints a pack for `PuzzlePack` of up to `count` boards from `generate_lesson`, each
+//! taking the technique, `singles` by default, but nothing harder, written by `make_pack` so the
+//! format cannot drift from what the extension reads.
+//!
+//! `bench` solves every game of the corpus under each configuration of `benchmark_options`, the
+//! default matrix unless `--configs` gives a list of `{"name", "options"}`, and prints the totals
+//! of each configuration and the winner on each board. `--report` also writes the full report as
+//! JSON, for tracking the numbers over time.
+//!
+//! `tune` grid-searches the ordering weights over the corpus with `tune_ordering_weights` and
+//! prints the best table, ready to pass as `orderingWeights` in the options, then on stderr how
+//! many nodes it takes against none.
+
+use std::convert::TryInto;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: solve <game.json> [--options <options.json>] [--algorithm <name>] [--bundle <out.json>]
+             [--stats]
+       solve find-duplicates <corpus.jsonl> <game.json>...
+       solve census <game.json> [--unit <unit JSON>]
+       solve generate --rows <n> --count <n> [--technique <name>] [--seed <n>] [--title <text>]
+       solve bench --compare <corpus.jsonl> [--configs <configs.json>] [--repeats <n>]
+             [--report <report.json>]
+       solve tune <corpus.jsonl>";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Solve {
+        game: String,
+        options: Option<String>,
+        algorithm: Option<String>,
+        bundle: Option<String>,
+        stats: bool,
+    },
+    FindDuplicates {
+        corpus: String,
+        games: Vec<String>,
+    },
+    Census {
+        game: String,
+        unit: Option<String>,
+    },
+    Generate {
+        rows: usize,
+        count: usize,
+        technique: String,
+        seed: u64,
+        title: String,
+    },
+    Bench {
+        corpus: String,
+        configs: Option<String>,
+        repeats: u32,
+        report: Option<String>,
+    },
+    Tune {
+        corpus: String,
+    },
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let Some(first) = args.next() else {
+        return Err("Missing the game to solve".to_string());
+    };
+
+    match first.as_str() {
+        "find-duplicates" => {
+            let (mut paths, [], []) = split_args(args, [], [])?;
+            if paths.len() < 2 {
+                return Err("find-duplicates takes a corpus and at least one game".to_string());
+            }
+            let games = paths.split_off(1);
+
+            Ok(Command::FindDuplicates {
+                corpus: paths.remove(0),
+                games,
+            })
+        }
+        "census" => {
+            let (paths, [unit], []) = split_args(args, ["--unit"], [])?;
+            let [game] = exactly(paths, "the game to take the census of")?;
+
+            Ok(Command::Census { game, unit })
+        }
+        "generate" => {
+            let flags = ["--rows", "--count", "--technique", "--seed", "--title"];
+            let (paths, [rows, count, technique, seed, title], []) = split_args(args, flags, [])?;
+            at_most(&paths, 0)?;
+
+            Ok(Command::Generate {
+                rows: number(rows.ok_or("generate takes the size of the boards with --rows")?)?,
+                count: number(count.ok_or("generate takes how many boards with --count")?)?,
+                technique: technique.unwrap_or_else(|| "singles".to_string()),
+                seed: seed.map_or(Ok(0), number)?,
+                title: title.unwrap_or_else(|| "Practice".to_string()),
+            })
+        }
+        "bench" => {
+            let flags = ["--compare", "--configs", "--repeats", "--report"];
+            let (paths, [corpus, configs, repeats, report], []) = split_args(args, flags, [])?;
+            at_most(&paths, 0)?;
+            let repeats = repeats.map_or(Ok(1), number)?;
+
+            Ok(Command::Bench {
+                corpus: corpus.ok_or("bench takes the corpus to compare on with --compare")?,
+                configs,
+                repeats,
+                report,
+            })
+        }
+        "tune" => {
+            let (paths, [], []) = split_args(args, [], [])?;
+            let [corpus] = exactly(paths, "the corpus to tune on")?;
+
+            Ok(Command::Tune { corpus })
+        }
+        _ => {
+            let args = std::iter::once(first).chain(args);
+            let flags = ["--options", "--algorithm", "--bundle"];
+            let (paths, [options, algorithm, bundle], [stats]) =
+                split_args(args, flags, ["--stats"])?;
+            let [game] = exactly(paths, "the game to solve")?;
+
+            Ok(Command::Solve {
+                game,
+                options,
+                algorithm,
+                bundle,
+                stats,
+            })
+        }
+    }
+}
+
+/// The arguments that are not flags, the values of flags taking one, and the switches given.
+type Split<const N: usize, const M: usize> = (Vec<String>, [Option<String>; N], [bool; M]);
+
+/// The arguments of `args` that are not flags, the value given to each of `flags`, which all
+/// take one, and whether each of `switches`, which take none, is there.
+fn split_args<const N: usize, const M: usize>(
+    mut args: impl Iterator<Item = String>,
+    flags: [&str; N],
+    switches: [&str; M],
+) -> Result<Split<N, M>, String> {
+    let mut paths = vec![];
+    let mut values = [(); N].map(|_| None);
+    let mut on = [false; M];
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            paths.push(arg);
+            continue;
+        }
+        if let Some(switch) = switches.iter().position(|&switch| switch == arg) {
+            on[switch] = true;
+            continue;
+        }
+
+        let slot = flags
+            .iter()
+            .position(|&flag| flag == arg)
+            .ok_or_else(|| format!("Unknown flag {}", arg))?;
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} takes a value", arg))?;
+        values[slot] = Some(value);
+    }
+
+    Ok((paths, values, on))
+}
+
+fn number<T: std::str::FromStr>(arg: String) -> Result<T, String> {
+    arg.parse().map_err(|_| format!("Invalid number {}", arg))
+}
+
+/// Fails on any argument in `paths` past the first `n`.
+fn at_most(paths: &[String], n: usize) -> Result<(), String> {
+    match paths.get(n) {
+        Some(extra) => Err(format!("Unexpected argument {}", extra)),
+        None => Ok(()),
+    }
+}
+
+/// The one argument in `paths`, failing when it is missing or followed by others.
+fn exactly(paths: Vec<String>, what: &str) -> Result<[String; 1], String> {
+    at_most(&paths, 1)?;
+
+    paths.try_into().map_err(|_| format!("Missing {}", what))
+}
+
+fn read(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))
+}
+
+fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Solve {
+            game,
+            options,
+            algorithm,
+            bundle,
+            stats,
+        } => solve(&game, options, algorithm, bundle, stats),
+        Command::FindDuplicates { corpus, games } => {
+            let corpus_jsonl = read(&corpus)?;
+            for game in &games {
+                let matches = solver::find_duplicates(corpus_jsonl.clone(), read(game)?)
+                    .map_err(|e| format!("{}: {}", game, e))?;
+                println!("{}", matches);
+            }
+
+            Ok(())
+        }
+        Command::Census { game, unit } => {
+            let unit_json = unit.unwrap_or_else(|| "null".to_string());
+            println!("{}", solver::branch_census(read(&game)?, unit_json)?);
+
+            Ok(())
+        }
+        Command::Generate {
+            rows,
+            count,
+            technique,
+            seed,
+            title,
+        } => {
+            println!("{}", generate(rows, count, technique, seed, title)?);
+
+            Ok(())
+        }
+        Command::Bench {
+            corpus,
+            configs,
+            repeats,
+            report,
+        } => bench(&corpus, configs, repeats, report),
+        Command::Tune { corpus } => {
+            let tuning = solver::tune_ordering_weights(read(&corpus)?)?;
+            let tuning: serde_json::Value = serde_json::from_str(&tuning).unwrap();
+
+            println!("{}", tuning["weights"]);
+            eprintln!(
+                "{} nodes with the weights against {} without, {} boards given up on, {} tables \
+                 tried",
+                tuning["nodes"], tuning["baselineNodes"], tuning["unknown"], tuning["tried"]
+            );
+
+            Ok(())
+        }
+    }
+}
+
+fn solve(
+    game: &str,
+    options: Option<String>,
+    algorithm: Option<String>,
+    bundle: Option<String>,
+    stats: bool,
+) -> Result<(), String> {
+    let game_json = read(game)?;
+    let mut options_json = match &options {
+        Some(path) => read(path)?,
+        None => "{}".to_string(),
+    };
+    if let Some(algorithm) = algorithm {
+        options_json = with_algorithm(&options_json, algorithm)?;
+    }
+
+    let output = solver::solve_with_options(game_json.clone(), options_json.clone())?;
+    println!("{}", output);
+    if stats {
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        print_stats(&output["stats"]);
+    }
+
+    if let Some(path) = &bundle {
+        let bundle = solver::debug_bundle(game_json, options_json)?;
+        std::fs::write(path, bundle).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+    }
+
+    Ok(())
+}
+
+/// A pack of up to `count` boards from `generate_lesson`, noting on stderr when fewer were found.
+fn generate(
+    rows: usize,
+    count: usize,
+    technique: String,
+    seed: u64,
+    title: String,
+) -> Result<String, String> {
+    let lesson = solver::generate_lesson(rows, technique, count, seed)?;
+    let lesson: serde_json::Value = serde_json::from_str(&lesson).unwrap();
+    let games: Vec<_> = lesson["boards"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|board| board["game"].clone())
+        .collect();
+    if games.len() < count {
+        eprintln!("Found only {} of {} boards", games.len(), count);
+    }
+
+    solver::make_pack(title, serde_json::Value::Array(games).to_string())
+}
+
+/// Prints the `stats` of `solve_with_options` as a table, one row per depth of the search when
+/// they have a `profile`.
+fn print_stats(stats: &serde_json::Value) {
+    let count = |key: &str| stats[key].as_u64().unwrap_or(0);
+    println!(
+        "\nnodes {}, forced {}, eliminated {}, nogood hits {} of {} lookups, nogoods stored {}",
+        count("nodes"),
+        count("forced"),
+        count("confined") + count("subsetEliminations") + count("wipeouts"),
+        count("nogoodHits"),
+        count("nogoodLookups"),
+        count("nogoodsStored")
+    );
+
+    let Some(profile) = stats["profile"].as_array() else {
+        println!("Build with --features profile for the counts at every depth");
+        return;
+    };
+
+    println!(
+        "\n{:>5} {:>10} {:>10} {:>12} {:>11} {:>7} {:>10}",
+        "depth", "nodes", "candidates", "fc failures", "nogood hits", "forced", "eliminated"
+    );
+    for (depth, counts) in profile.iter().enumerate() {
+        let count = |key: &str| counts[key].as_u64().unwrap_or(0);
+        println!(
+            "{:>5} {:>10} {:>10} {:>12} {:>11} {:>7} {:>10}",
+            depth,
+            count("nodes"),
+            count("candidates"),
+            count("forwardCheckFailures"),
+            count("nogoodHits"),
+            count("forced"),
+            count("eliminated")
+        );
+    }
+}
+
+fn bench(
+    corpus: &str,
+    configs: Option<String>,
+    repeats: u32,
+    report: Option<String>,
+) -> Result<(), String> {
+    let configs_json = match &configs {
+        Some(path) => read(path)?,
+        None => "null".to_string(),
+    };
+    let report_json = solver::benchmark_options(read(corpus)?, configs_json, repeats)?;
+    let parsed: serde_json::Value = serde_json::from_str(&report_json).unwrap();
+
+    println!(
+        "{:<20} {:>6} {:>7} {:>12} {:>10} {:>4}",
+        "configuration", "solved", "unknown", "nodes", "ms", "wins"
+    );
+    for config in parsed["configs"].as_array().unwrap() {
+        println!(
+            "{:<20} {:>6} {:>7} {:>12} {:>10.1} {:>4}",
+            config["name"].as_str().unwrap(),
+            config["solved"].as_u64().unwrap(),
+            config["unknown"].as_u64().unwrap(),
+            config["nodes"].as_u64().unwrap(),
+            config["ms"].as_f64().unwrap(),
+            config["wins"].as_u64().unwrap()
+        );
+    }
+
+    println!("\n{:>5}  winner", "line");
+    for board in parsed["boards"].as_array().unwrap() {
+        let winner = board["winner"]
+            .as_str()
+            .unwrap_or("none, every configuration gave up");
+        println!("{:>5}  {}", board["line"].as_u64().unwrap(), winner);
+    }
+
+    if let Some(path) = &report {
+        std::fs::write(path, report_json).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+    }
+
+    Ok(())
+}
+
+/// The options of `options_json` with `algorithm` in place of any they name.
+fn with_algorithm(options_json: &str, algorithm: String) -> Result<String, String> {
+    let mut options: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(options_json).map_err(|e| format!("Invalid options JSON: {}", e))?;
+    options.insert("algorithm".to_string(), algorithm.into());
+
+    Ok(serde_json::Value::Object(options).to_string())
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)).and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, String> {
+        parse_args(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn parses_a_solve() {
+        assert_eq!(
+            parse("game.json --bundle out.json --algorithm local"),
+            Ok(Command::Solve {
+                game: "game.json".to_string(),
+                options: None,
+                algorithm: Some("local".to_string()),
+                bundle: Some("out.json".to_string()),
+                stats: false,
+            })
+        );
+        assert_eq!(
+            parse("--stats game.json"),
+            Ok(Command::Solve {
+                game: "game.json".to_string(),
+                options: None,
+                algorithm: None,
+                bundle: None,
+                stats: true,
+            })
+        );
+        assert_eq!(parse(""), Err("Missing the game to solve".to_string()));
+        assert_eq!(
+            parse("a.json b.json"),
+            Err("Unexpected argument b.json".to_string())
+        );
+        assert_eq!(
+            parse("game.json --verbose"),
+            Err("Unknown flag --verbose".to_string())
+        );
+        assert_eq!(
+            parse("game.json --options"),
+            Err("--options takes a value".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_find_duplicates() {
+        assert_eq!(
+            parse("find-duplicates corpus.jsonl a.json b.json"),
+            Ok(Command::FindDuplicates {
+                corpus: "corpus.jsonl".to_string(),
+                games: vec!["a.json".to_string(), "b.json".to_string()],
+            })
+        );
+        assert_eq!(
+            parse("find-duplicates corpus.jsonl"),
+            Err("find-duplicates takes a corpus and at least one game".to_string())
+        );
+        assert_eq!(
+            parse("find-duplicates corpus.jsonl a.json --options o.json"),
+            Err("Unknown flag --options".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_census() {
+        assert_eq!(
+            parse(r#"census game.json --unit {"col":3}"#),
+            Ok(Command::Census {
+                game: "game.json".to_string(),
+                unit: Some(r#"{"col":3}"#.to_string()),
+            })
+        );
+        assert_eq!(
+            parse("census"),
+            Err("Missing the game to take the census of".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_generate() {
+        assert_eq!(
+            parse("generate --count 3 --rows 7 --seed 9"),
+            Ok(Command::Generate {
+                rows: 7,
+                count: 3,
+                technique: "singles".to_string(),
+                seed: 9,
+                title: "Practice".to_string(),
+            })
+        );
+        assert_eq!(
+            parse("generate --count 3"),
+            Err("generate takes the size of the boards with --rows".to_string())
+        );
+        assert_eq!(
+            parse("generate --rows 7 --count -1"),
+            Err("Invalid number -1".to_string())
+        );
+    }
+
+    #[test]
+    fn generates_packs_the_extension_reads() {
+        let pack_json = generate(6, 3, "confinement".to_string(), 1, "Drills".to_string()).unwrap();
+        let pack = solver::PuzzlePack::new(pack_json).unwrap();
+
+        assert_eq!(pack.title(), "Drills");
+        assert_eq!(pack.len(), 3);
+        let verified: serde_json::Value = serde_json::from_str(&pack.verify().unwrap()).unwrap();
+        assert_eq!(verified["passed"], true);
+        for index in 0..pack.len() {
+            let meta: serde_json::Value = serde_json::from_str(&pack.meta(index).unwrap()).unwrap();
+            assert_eq!(meta["difficulty"], "confinement");
+        }
+
+        assert_eq!(
+            generate(3, 1, "singles".to_string(), 0, "Drills".to_string()),
+            Err("Lessons have boards of 4 to 63 rows, not 3".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_bench() {
+        assert_eq!(
+            parse("bench --compare corpus.jsonl --repeats 3 --report report.json"),
+            Ok(Command::Bench {
+                corpus: "corpus.jsonl".to_string(),
+                configs: None,
+                repeats: 3,
+                report: Some("report.json".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("bench --configs configs.json"),
+            Err("bench takes the corpus to compare on with --compare".to_string())
+        );
+        assert_eq!(
+            parse("bench --compare corpus.jsonl --repeats many"),
+            Err("Invalid number many".to_string())
+        );
+        assert_eq!(
+            parse("bench corpus.jsonl"),
+            Err("Unexpected argument corpus.jsonl".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_tune() {
+        assert_eq!(
+            parse("tune corpus.jsonl"),
+            Ok(Command::Tune {
+                corpus: "corpus.jsonl".to_string(),
+            })
+        );
+        assert_eq!(
+            parse("tune"),
+            Err("Missing the corpus to tune on".to_string())
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use crate::availability::CellAvailability;
+use crate::{AdjacentsLookup, Game};
+use std::rc::Rc;
+
+const WORDS: usize = 3;
+
+/// A set of cells packed into machine words.
+type Bits = [u64; WORDS];
+
+#[inline(always)]
+fn and_not(a: &Bits, b: &Bits) -> Bits {
+    [a[0] & !b[0], a[1] & !b[1], a[2] & !b[2]]
+}
+
+/// How many cells `a` and `b` have in common. The simd build keeps it to check its own version
+/// against.
+#[cfg_attr(
+    all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"),
+    allow(dead_code)
+)]
+#[inline(always)]
+fn count_common_scalar(a: &Bits, b: &Bits) -> usize {
+    ((a[0] & b[0]).count_ones() + (a[1] & b[1]).count_ones() + (a[2] & b[2]).count_ones()) as usize
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+use self::count_common_scalar as count_common;
+
+/// The same count as the scalar version, with the first two words handled as one 128-bit vector:
+/// a byte-wise popcount followed by pairwise widening adds.
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+#[inline(always)]
+fn count_common(a: &Bits, b: &Bits) -> usize {
+    use core::arch::wasm32::*;
+
+    let common = v128_and(u64x2(a[0], a[1]), u64x2(b[0], b[1]));
+    let counts = u32x4_extadd_pairwise_u16x8(u16x8_extadd_pairwise_u8x16(u8x16_popcnt(common)));
+
+    let low = u32x4_extract_lane::<0>(counts)
+        + u32x4_extract_lane::<1>(counts)
+        + u32x4_extract_lane::<2>(counts)
+        + u32x4_extract_lane::<3>(counts);
+
+    (low + (a[2] & b[2]).count_ones()) as usize
+}
+
+#[inline(always)]
+fn set_bit(bits: &mut Bits, idx: usize) {
+    bits[idx / 64] |= 1 << (idx % 64);
+}
+
+/// Tracks available cells as a bitset.
+///
+/// Placing a queen clears its precomputed threat mask (its row, column, color, and neighbors) in
+/// a single AND, and spot counts are popcounts of the available cells within each unit's mask.
+/// Previous states are kept on a stack so removing a queen is a pop.
+pub(crate) struct BitAvailability {
+    adj_lookup: Rc<AdjacentsLookup>,
+    available: Bits,
+    history: Vec<Bits>,
+    threats: Vec<Bits>,
+    row_masks: Vec<Bits>,
+    col_masks: Vec<Bits>,
+    color_masks: Vec<Bits>,
+    row_spots: Vec<usize>,
+    col_spots: Vec<usize>,
+    color_spots: Vec<usize>,
+}
+
+impl BitAvailability {
+    /// The most cells the bitset can hold, enough for a 12x12 board.
+    pub const MAX_CELLS: usize = WORDS * 64;
+
+    pub fn new(game: &Game) -> Self {
+        let cells = game.rows * game.cols;
+        let adj_lookup = AdjacentsLookup::for_game(game);
+
+        let mut available = [0; WORDS];
+        let mut row_masks = vec![[0; WORDS]; game.rows];
+        let mut col_masks = vec![[0; WORDS]; game.cols];
+        let mut color_masks = vec![[0; WORDS]; game.num_colors()];
+
+        for idx in 0..cells {
+            set_bit(&mut available, idx);
+            set_bit(&mut row_masks[idx / game.cols], idx);
+            set_bit(&mut col_masks[idx % game.cols], idx);
+            if game.has_color_rule() {
+                set_bit(&mut color_masks[game.idx_to_color[idx]], idx);
+            }
+        }
+
+        let threats = (0..cells)
+            .map(|idx| {
+                let mut threat = row_masks[idx / game.cols];
+                let col_mask = &col_masks[idx % game.cols];
+                let color_mask = color_masks.get(game.idx_to_color[idx]);
+
+                for word in 0..WORDS {
+                    threat[word] |= col_mask[word] | color_mask.map_or(0, |mask| mask[word]);
+                }
+                for &neighbor in adj_lookup.neighbors(idx) {
+                    set_bit(&mut threat, neighbor as usize);
+                }
+
+                threat
+            })
+            .collect();
+
+        let mut bit_availability = Self {
+            adj_lookup,
+            available,
+            history: Vec::with_capacity(game.rows),
+            threats,
+            row_masks,
+            col_masks,
+            color_masks,
+            row_spots: vec![0; game.rows],
+            col_spots: vec![0; game.cols],
+            color_spots: vec![0; game.num_colors()],
+        };
+        bit_availability.count_spots();
+
+        bit_availability
+    }
+
+    fn count_spots(&mut self) {
+        let available = &self.available;
+
+        for (spots, mask) in self.row_spots.iter_mut().zip(&self.row_masks) {
+            *spots = count_common(available, mask);
+        }
+        for (spots, mask) in self.col_spots.iter_mut().zip(&self.col_masks) {
+            *spots = count_common(available, mask);
+        }
+        for (spots, mask) in self.color_spots.iter_mut().zip(&self.color_masks) {
+            *spots = count_common(available, mask);
+        }
+    }
+}
+
+impl CellAvailability for BitAvailability {
+    #[inline(always)]
+    fn adj_lookup(&self) -> &AdjacentsLookup {
+        &self.adj_lookup
+    }
+
+    #[inline(always)]
+    fn is_available(&self, idx: usize) -> bool {
+        (self.available[idx / 64] >> (idx % 64)) & 1 == 1
+    }
+
+    fn collect_available(&self, game: &Game, out: &mut Vec<(usize, usize)>) {
+        for (word, &bits) in self.available.iter().enumerate() {
+            let mut bits = bits;
+
+            while bits != 0 {
+                let idx = word * 64 + bits.trailing_zeros() as usize;
+                out.push((idx / game.cols, idx % game.cols));
+                bits &= bits - 1;
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn row_spots(&self) -> &[usize] {
+        &self.row_spots
+    }
+
+    #[inline(always)]
+    fn col_spots(&self) -> &[usize] {
+        &self.col_spots
+    }
+
+    #[inline(always)]
+    fn color_spots(&self) -> &[usize] {
+        &self.color_spots
+    }
+
+    fn place(&mut self, _game: &Game, idx: usize) {
+        self.history.push(self.available);
+        self.available = and_not(&self.available, &self.threats[idx]);
+        self.count_spots();
+    }
+
+    fn remove(&mut self, _game: &Game, _idx: usize) {
+        self.available = self.history.pop().unwrap();
+        self.count_spots();
+    }
+
+    fn exclude(&mut self, _game: &Game, idx: usize) {
+        self.history.push(self.available);
+        self.available[idx / 64] &= !(1 << (idx % 64));
+        self.count_spots();
+    }
+
+    fn restore(&mut self, _game: &Game, _idx: usize) {
+        self.available = self.history.pop().unwrap();
+        self.count_spots();
+    }
+}
+
+/// Besides `cargo test`, these run in the simd build, where they check its counts against the
+/// scalar ones, e.g.
+/// RUSTFLAGS="-C target-feature=+simd128" CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=\
+///   wasm-bindgen-test-runner cargo test --target wasm32-unknown-unknown --features simd \
+///   --lib bitboard
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availability::Availability;
+    use crate::corpus::CORPUS;
+    use crate::utils::Rng;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn random_bits(rng: &mut Rng) -> Bits {
+        // Sparse, dense, and even words, so runs of set bits cross the byte lanes.
+        let mut word = || match rng.below(4) {
+            0 => rng.next_u64() & rng.next_u64(),
+            1 => rng.next_u64() | rng.next_u64(),
+            2 => !0,
+            _ => rng.next_u64(),
+        };
+        [word(), word(), word()]
+    }
+
+    #[test]
+    fn counts_common_cells_like_the_scalar_version() {
+        let mut rng = Rng::new(7);
+
+        for _ in 0..10_000 {
+            let (a, b) = (random_bits(&mut rng), random_bits(&mut rng));
+            let bit_by_bit = (0..BitAvailability::MAX_CELLS)
+                .filter(|&idx| (a[idx / 64] & b[idx / 64]) >> (idx % 64) & 1 == 1)
+                .count();
+
+            assert_eq!(count_common(&a, &b), bit_by_bit, "{:x?} {:x?}", a, b);
+            assert_eq!(count_common_scalar(&a, &b), bit_by_bit, "{:x?} {:x?}", a, b);
+        }
+    }
+
+    /// The spots the bitboard counts with masks agree with those `Availability` keeps cell by
+    /// cell, through random queens and exclusions undone in turn.
+    #[test]
+    fn keeps_the_spots_of_the_cell_by_cell_tracker() {
+        let mut rng = Rng::new(11);
+
+        for entry in CORPUS {
+            let game = Game::from_json(entry.json).unwrap();
+            let cells = game.rows * game.cols;
+            let mut bits = BitAvailability::new(&game);
+            let mut cell_by_cell = Availability::new(&game);
+            // Whether each change still in effect placed a queen, and on which cell.
+            let mut changes: Vec<(bool, usize)> = vec![];
+
+            for _ in 0..2_000 {
+                let open: Vec<_> = (0..cells)
+                    .filter(|&idx| cell_by_cell.is_available(idx))
+                    .collect();
+
+                if open.is_empty() || (!changes.is_empty() && rng.below(3) == 0) {
+                    let (placed, idx) = changes.pop().unwrap();
+                    if placed {
+                        bits.remove(&game, idx);
+                        cell_by_cell.remove(&game, idx);
+                    } else {
+                        bits.restore(&game, idx);
+                        cell_by_cell.restore(&game, idx);
+                    }
+                } else {
+                    let idx = open[rng.below(open.len())];
+                    let placed = rng.below(2) == 0;
+                    if placed {
+                        bits.place(&game, idx);
+                        cell_by_cell.place(&game, idx);
+                    } else {
+                        bits.exclude(&game, idx);
+                        cell_by_cell.exclude(&game, idx);
+                    }
+                    changes.push((placed, idx));
+                }
+
+                let context = format!("{} after {:?}", entry.name, changes);
+                for idx in 0..cells {
+                    assert_eq!(
+                        bits.is_available(idx),
+                        cell_by_cell.is_available(idx),
+                        "{} at {}",
+                        context,
+                        idx
+                    );
+                }
+                assert_eq!(bits.row_spots(), cell_by_cell.row_spots(), "{}", context);
+                assert_eq!(bits.col_spots(), cell_by_cell.col_spots(), "{}", context);
+                assert_eq!(
+                    bits.color_spots(),
+                    cell_by_cell.color_spots(),
+                    "{}",
+                    context
+                );
+            }
+        }
+    }
+}
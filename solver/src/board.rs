@@ -0,0 +1,121 @@
+use crate::Game;
+
+/// The cells of every row, column, and color, built once per board so that visiting a unit does
+/// not mean scanning the whole grid.
+pub(crate) struct BoardIndex {
+    pub cells_by_row: Vec<Vec<usize>>,
+    pub cells_by_col: Vec<Vec<usize>>,
+    /// Empty when the color rule does not apply.
+    pub cells_by_color: Vec<Vec<usize>>,
+    /// The rows holding cells of each color, as bitmasks.
+    pub color_rows: Vec<u64>,
+    /// The columns holding cells of each color, as bitmasks.
+    pub color_cols: Vec<u64>,
+}
+
+impl BoardIndex {
+    pub fn new(game: &Game) -> Self {
+        let mut cells_by_row = vec![Vec::with_capacity(game.cols); game.rows];
+        let mut cells_by_col = vec![Vec::with_capacity(game.rows); game.cols];
+        let mut cells_by_color = vec![vec![]; game.num_colors()];
+        let mut color_rows = vec![0; game.num_colors()];
+        let mut color_cols = vec![0; game.num_colors()];
+
+        for idx in 0..game.rows * game.cols {
+            let (row, col) = (idx / game.cols, idx % game.cols);
+            cells_by_row[row].push(idx);
+            cells_by_col[col].push(idx);
+
+            if game.has_color_rule() {
+                let color = game.idx_to_color[idx];
+                cells_by_color[color].push(idx);
+                color_rows[color] |= 1 << row;
+                color_cols[color] |= 1 << col;
+            }
+        }
+
+        Self {
+            cells_by_row,
+            cells_by_col,
+            cells_by_color,
+            color_rows,
+            color_cols,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+
+    /// Every list and mask holds what scanning the grid finds, on square boards and on ones wider
+    /// than tall, where `row * cols + col` and `col * rows + row` part ways.
+    #[test]
+    fn indexes_the_cells_of_every_unit() {
+        let wide = [
+            r#"{"rows":3,"cols":5,"colors":[0,1,2],"idxToColor":[
+                0,0,1,1,1, 0,2,2,2,1, 2,2,2,2,2]}"#,
+            r#"{"rows":2,"cols":7,"colors":[0,1],"idxToColor":[
+                1,0,0,0,0,0,0, 1,1,1,1,1,1,0]}"#,
+        ];
+        let boards = CORPUS.iter().map(|entry| entry.json).chain(wide);
+
+        for json in boards {
+            let game = Game::from_json(json).unwrap();
+            let index = BoardIndex::new(&game);
+            let cells = 0..game.rows * game.cols;
+
+            for row in 0..game.rows {
+                let scanned: Vec<_> = cells
+                    .clone()
+                    .filter(|&idx| idx / game.cols == row)
+                    .collect();
+                assert_eq!(index.cells_by_row[row], scanned, "{} row {}", json, row);
+            }
+            for col in 0..game.cols {
+                let scanned: Vec<_> = cells
+                    .clone()
+                    .filter(|&idx| idx % game.cols == col)
+                    .collect();
+                assert_eq!(index.cells_by_col[col], scanned, "{} col {}", json, col);
+            }
+
+            assert_eq!(index.cells_by_color.len(), game.num_colors(), "{}", json);
+            for color in 0..game.num_colors() {
+                let scanned: Vec<_> = cells
+                    .clone()
+                    .filter(|&idx| game.idx_to_color[idx] == color)
+                    .collect();
+                let rows = scanned
+                    .iter()
+                    .fold(0, |rows, &idx| rows | 1 << (idx / game.cols));
+                let cols = scanned
+                    .iter()
+                    .fold(0, |cols, &idx| cols | 1 << (idx % game.cols));
+
+                assert_eq!(
+                    index.cells_by_color[color], scanned,
+                    "{} color {}",
+                    json, color
+                );
+                assert_eq!(index.color_rows[color], rows, "{} color {}", json, color);
+                assert_eq!(index.color_cols[color], cols, "{} color {}", json, color);
+            }
+        }
+    }
+
+    #[test]
+    fn has_no_colors_without_the_color_rule() {
+        let game = Game::from_json(r#"{"rows":3,"cols":5}"#).unwrap();
+        let index = BoardIndex::new(&game);
+
+        assert_eq!(
+            index.cells_by_row,
+            [[0, 1, 2, 3, 4], [5, 6, 7, 8, 9], [10, 11, 12, 13, 14]]
+        );
+        assert_eq!(index.cells_by_col[4], [4, 9, 14]);
+        assert!(index.cells_by_color.is_empty());
+        assert!(index.color_rows.is_empty() && index.color_cols.is_empty());
+    }
+}
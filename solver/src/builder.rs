@@ -0,0 +1,336 @@
+use crate::rules::Rules;
+use crate::Game;
+use std::fmt;
+
+/// Why `GameBuilder::build` could not make a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /// Neither the rows nor a grid were given.
+    MissingSize,
+    /// A row of the grid has another number of cells than the first.
+    RaggedGrid {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+    /// A cell was painted outside the board.
+    CellOutOfBounds { row: usize, col: usize },
+    /// A cell of the board was never painted.
+    Unpainted { row: usize, col: usize },
+    /// The board breaks a rule of well-formed boards, as the JSON entry points would report it.
+    Invalid(String),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSize => write!(f, "Board gives neither rows nor a grid"),
+            Self::RaggedGrid { row, len, expected } => write!(
+                f,
+                "Row {} has {} cells, but row 0 has {}",
+                row, len, expected
+            ),
+            Self::CellOutOfBounds { row, col } => {
+                write!(
+                    f,
+                    "Cell at row {} and column {} is not on the board",
+                    row, col
+                )
+            }
+            Self::Unpainted { row, col } => {
+                write!(f, "Cell at row {} and column {} has no color", row, col)
+            }
+            Self::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// Builds a `Game` in code rather than from game JSON, checked as fully as the JSON entry points
+/// check theirs.
+#[derive(Debug, Clone, Default)]
+pub struct GameBuilder {
+    rows: Option<usize>,
+    cols: Option<usize>,
+    /// The cells painted, in order, a later color of a cell replacing an earlier one.
+    painted: Vec<(usize, usize, usize)>,
+    rules: Rules,
+    /// The first row of the grid not as long as the first, with its length.
+    ragged: Option<(usize, usize)>,
+}
+
+impl GameBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A builder with the size and colors of `grid`, one slice of colors per row.
+    pub fn from_grid(grid: &[&[usize]]) -> Self {
+        let cols = grid.first().map_or(0, |row| row.len());
+        let ragged = grid
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.len() != cols)
+            .map(|(row, cells)| (row, cells.len()));
+
+        let painted = grid
+            .iter()
+            .enumerate()
+            .flat_map(|(row, cells)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .map(move |(col, &color)| (row, col, color))
+            })
+            .collect();
+
+        Self {
+            rows: Some(grid.len()),
+            cols: Some(cols),
+            painted,
+            rules: Rules::default(),
+            ragged,
+        }
+    }
+
+    pub fn rows(mut self, rows: usize) -> Self {
+        self.rows = Some(rows);
+        self
+    }
+
+    /// The number of columns, as many as the rows when not given.
+    pub fn cols(mut self, cols: usize) -> Self {
+        self.cols = Some(cols);
+        self
+    }
+
+    /// Paints the cell at `row` and `col` with `color`. Colors are numbered from 0, and the board
+    /// has as many as the highest painted plus one.
+    pub fn color_at(mut self, row: usize, col: usize, color: usize) -> Self {
+        self.painted.push((row, col, color));
+        self
+    }
+
+    /// The rules of the game, those of the LinkedIn game when not given.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Checks the board and makes the game, with its neighbors spelled out like a game read
+    /// from JSON.
+    pub fn build(self) -> Result<Game, GameError> {
+        let rows = self.rows.ok_or(GameError::MissingSize)?;
+        let cols = self.cols.unwrap_or(rows);
+        if let Some((row, len)) = self.ragged {
+            return Err(GameError::RaggedGrid {
+                row,
+                len,
+                expected: cols,
+            });
+        }
+
+        let mut idx_to_color = vec![None; rows * cols];
+        for &(row, col, color) in &self.painted {
+            if row >= rows || col >= cols {
+                return Err(GameError::CellOutOfBounds { row, col });
+            }
+            idx_to_color[row * cols + col] = Some(color);
+        }
+        // Colorless boards need no colors, so their cells may be left unpainted.
+        let idx_to_color = idx_to_color
+            .into_iter()
+            .enumerate()
+            .map(|(idx, color)| match color {
+                Some(color) => Ok(color),
+                None if self.rules.colorless => Ok(0),
+                None => Err(GameError::Unpainted {
+                    row: idx / cols,
+                    col: idx % cols,
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let colors = if self.rules.colorless {
+            vec![]
+        } else {
+            (0..idx_to_color.iter().max().map_or(0, |&color| color + 1)).collect()
+        };
+
+        Game {
+            rows,
+            cols,
+            colors,
+            idx_to_color,
+            regions: None,
+            rules: self.rules,
+            adjacency: None,
+            cell_ids: None,
+            links: vec![],
+            strict: false,
+        }
+        .prepare()
+        .map_err(GameError::Invalid)
+    }
+}
+
+impl Game {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The number of colors the rules constrain, none on colorless boards.
+    pub fn color_count(&self) -> usize {
+        self.num_colors()
+    }
+
+    /// The color of the cell at `row` and `col`, which must be on the board.
+    pub fn color_at(&self, row: usize, col: usize) -> usize {
+        assert!(
+            row < self.rows && col < self.cols,
+            "cell ({}, {}) is off the board",
+            row,
+            col
+        );
+        self.idx_to_color[row * self.cols + col]
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::run_counter;
+
+    fn grid(game: &Game) -> Vec<Vec<usize>> {
+        game.idx_to_color
+            .chunks(game.cols)
+            .map(<[usize]>::to_vec)
+            .collect()
+    }
+
+    #[test]
+    fn builds_the_corpus_boards_like_their_json() {
+        for entry in CORPUS {
+            let read = Game::from_json(entry.json).unwrap();
+            let rows = grid(&read);
+            let rows: Vec<&[usize]> = rows.iter().map(Vec::as_slice).collect();
+
+            let from_grid = GameBuilder::from_grid(&rows).build().unwrap();
+            let painted = (0..read.rows)
+                .flat_map(|row| (0..read.cols).map(move |col| (row, col)))
+                .fold(GameBuilder::new().rows(read.rows), |builder, (row, col)| {
+                    builder.color_at(row, col, read.color_at(row, col))
+                })
+                .build()
+                .unwrap();
+
+            for built in [from_grid, painted] {
+                assert_eq!(built.to_json(), read.to_json(), "{}", entry.name);
+                assert_eq!((built.rows(), built.cols()), (read.rows, read.cols));
+                assert_eq!(built.color_count(), read.colors.len(), "{}", entry.name);
+                assert_eq!(
+                    run_counter(&built, false),
+                    run_counter(&read, false),
+                    "{}",
+                    entry.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn paints_rectangular_and_colorless_boards() {
+        // A later color of a cell replaces an earlier one.
+        let game = GameBuilder::from_grid(&[&[0, 0, 1], &[2, 2, 2]])
+            .color_at(0, 1, 1)
+            .build()
+            .unwrap();
+        assert_eq!((game.rows(), game.cols(), game.color_count()), (2, 3, 3));
+        assert_eq!(game.idx_to_color, [0, 1, 1, 2, 2, 2]);
+        assert_eq!(game.color_at(1, 2), 2);
+
+        let colorless = GameBuilder::new()
+            .rows(6)
+            .rules(Rules {
+                colorless: true,
+                ..Rules::default()
+            })
+            .build()
+            .unwrap();
+        assert_eq!((colorless.rows(), colorless.cols()), (6, 6));
+        assert_eq!(colorless.color_count(), 0);
+        assert!(colorless.rules().colorless);
+        assert_eq!(run_counter(&colorless, false), 90);
+    }
+
+    #[test]
+    fn names_what_is_wrong_with_the_board() {
+        let errors = [
+            (GameBuilder::new().cols(3).build(), GameError::MissingSize),
+            (
+                GameBuilder::from_grid(&[&[0, 1], &[1], &[0]]).build(),
+                GameError::RaggedGrid {
+                    row: 1,
+                    len: 1,
+                    expected: 2,
+                },
+            ),
+            (
+                GameBuilder::new().rows(2).color_at(0, 2, 0).build(),
+                GameError::CellOutOfBounds { row: 0, col: 2 },
+            ),
+            (
+                GameBuilder::from_grid(&[&[0, 1], &[1, 0]])
+                    .rows(3)
+                    .cols(2)
+                    .build(),
+                GameError::Unpainted { row: 2, col: 0 },
+            ),
+        ];
+        for (built, error) in errors {
+            assert_eq!(built.unwrap_err(), error);
+        }
+
+        let error = GameBuilder::from_grid(&[&[0, 0], &[0, 0]])
+            .build()
+            .unwrap_err();
+        let GameError::Invalid(message) = &error else {
+            panic!("expected an invalid board, got {:?}", error);
+        };
+        let boxed: Box<dyn std::error::Error> = Box::new(error.clone());
+        assert_eq!(boxed.to_string(), *message);
+
+        assert_eq!(
+            GameError::RaggedGrid {
+                row: 1,
+                len: 1,
+                expected: 2
+            }
+            .to_string(),
+            "Row 1 has 1 cells, but row 0 has 2"
+        );
+        assert_eq!(
+            GameError::Unpainted { row: 2, col: 0 }.to_string(),
+            "Cell at row 2 and column 0 has no color"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cell (2, 0) is off the board")]
+    fn refuses_colors_of_cells_off_the_board() {
+        GameBuilder::from_grid(&[&[0, 1], &[1, 0]])
+            .build()
+            .unwrap()
+            .color_at(2, 0);
+    }
+}
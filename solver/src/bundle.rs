@@ -0,0 +1,116 @@
+use crate::options::Options;
+use crate::pack::canonical_hash;
+use crate::self_test::enabled_features;
+use crate::stats::SolveStats;
+use crate::strategy::Status;
+use crate::utils::now_ms;
+use crate::verify::{verify_solution, Violation};
+use crate::{run_solver, Game};
+use serde::Serialize;
+
+/// The version of the bundle format, raised whenever a field is renamed, dropped, or changes
+/// meaning, so that whoever reads a bug report knows how to read its bundle.
+const BUNDLE_VERSION: u32 = 1;
+
+/// Everything needed to reproduce a solve and see what it did, for attaching to bug reports.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Bundle {
+    pub bundle_version: u32,
+    pub crate_version: &'static str,
+    /// The optional features the build was made with.
+    pub features: Vec<&'static str>,
+    /// Whether the build has debug assertions, which change what `verify` defaults to.
+    pub debug: bool,
+    /// The game normalized, as `normalize_game` writes it, which is the board solved.
+    pub board: Game,
+    /// The `canonical_hash` of the board, as in the activity log and panic reports.
+    pub board_hash: String,
+    /// The options searched with, every default spelled out and `Heuristics::Auto` replaced by
+    /// the heuristics of its tier, so they reproduce the solve when passed back to
+    /// `solve_with_options`.
+    pub options: Options,
+    pub solution: Vec<usize>,
+    pub status: Status,
+    /// Whether `solution` keeps every rule, checked whatever the options say, or `None` without
+    /// a solution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    /// The rules `solution` breaks.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
+    pub stats: SolveStats,
+    pub ms: f64,
+}
+
+/// Solves `game` normalized under `options` as `solve_with_options` does and bundles the solve
+/// with the build it ran on. The normalized board is solved rather than `game`, so that the
+/// bundle reproduces its own solve, whatever the JSON of the report looked like.
+pub(crate) fn bundle_solve(game: &Game, options: &Options) -> Bundle {
+    let game = game.normalized();
+    let (tuned, heuristics) = options.for_game(&game);
+    let options = Options {
+        verify: Some(tuned.verifies()),
+        forward_checking: Some(tuned.forward_checks()),
+        ..tuned.into_owned()
+    };
+
+    let started = now_ms();
+    let mut outcome = run_solver(&game, &options);
+    let ms = now_ms() - started;
+    outcome.stats.heuristics = heuristics;
+
+    let solved = outcome.status == Status::Solved;
+    let violations = if solved {
+        verify_solution(&game, &outcome.solution)
+    } else {
+        vec![]
+    };
+    if !violations.is_empty() && options.verifies() {
+        outcome.status = Status::InternalError;
+    }
+
+    Bundle {
+        bundle_version: BUNDLE_VERSION,
+        crate_version: env!("CARGO_PKG_VERSION"),
+        features: enabled_features(),
+        debug: cfg!(debug_assertions),
+        board_hash: canonical_hash(&game),
+        board: game,
+        options,
+        solution: outcome.solution,
+        status: outcome.status,
+        verified: solved.then_some(violations.is_empty()),
+        violations,
+        stats: outcome.stats,
+        ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+
+    /// Two spellings of one board, the second with its colors renamed, bundle the same board
+    /// under the same hash, which passed back reproduces the solve.
+    #[test]
+    fn bundles_the_normalized_board() {
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        let mut renamed = game.clone();
+        for color in &mut renamed.idx_to_color {
+            *color = 7 - *color;
+        }
+
+        let options = Options::default();
+        let bundle = bundle_solve(&game, &options);
+        let other = bundle_solve(&renamed, &options);
+        assert_eq!(bundle.board.to_json(), other.board.to_json());
+        assert_eq!(bundle.board_hash, other.board_hash);
+        assert_eq!(bundle.board_hash, canonical_hash(&renamed));
+
+        let again = bundle_solve(&bundle.board, &bundle.options);
+        assert_eq!(again.solution, bundle.solution);
+        assert_eq!(again.stats.nodes, bundle.stats.nodes);
+    }
+}
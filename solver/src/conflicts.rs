@@ -0,0 +1,159 @@
+use crate::{AdjacentsLookup, Game};
+use serde::Serialize;
+
+/// The rule two queens break together.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PairRule {
+    Row,
+    Column,
+    Color,
+    /// The queens are neighbors by the board's adjacency, beyond sharing a row or column:
+    /// diagonally on square boards.
+    Adjacent,
+}
+
+/// Two of the user's queens breaking a rule, the lower cell first.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Conflict {
+    pub first: usize,
+    pub second: usize,
+    pub rule: PairRule,
+}
+
+/// Every rule broken between two of the queens on `placed`, one conflict per pair and rule, in
+/// order of the pairs and then the rules. Only the queens are looked at, never the solution, so
+/// nothing is given away, and a queen the solution does not have raises no conflict on its own.
+pub(crate) fn find_conflicts(game: &Game, placed: &[usize]) -> Result<Vec<Conflict>, String> {
+    let cells = game.rows * game.cols;
+    let mut placed = placed.to_vec();
+    placed.sort_unstable();
+    if let Some(&idx) = placed.iter().find(|&&idx| idx >= cells) {
+        return Err(format!(
+            "Cell {} is not on the board, which has cells 0..{}",
+            idx, cells
+        ));
+    }
+    if let Some(pair) = placed.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(format!("Cell {} holds more than one queen", pair[0]));
+    }
+
+    let adjacents = AdjacentsLookup::for_game(game);
+    let mut conflicts = vec![];
+
+    for (pos, &first) in placed.iter().enumerate() {
+        for &second in &placed[pos + 1..] {
+            let rules = [
+                (PairRule::Row, first / game.cols == second / game.cols),
+                (PairRule::Column, first % game.cols == second % game.cols),
+                (
+                    PairRule::Color,
+                    game.has_color_rule() && game.idx_to_color[first] == game.idx_to_color[second],
+                ),
+                (
+                    PairRule::Adjacent,
+                    adjacents.neighbors(first).contains(&(second as u16))
+                        || adjacents.neighbors(second).contains(&(first as u16)),
+                ),
+            ];
+
+            conflicts.extend(
+                rules
+                    .iter()
+                    .filter(|&&(_, broken)| broken)
+                    .map(|&(rule, _)| Conflict {
+                        first,
+                        second,
+                        rule,
+                    }),
+            );
+        }
+    }
+
+    Ok(conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conflicts;
+    use crate::corpus::CORPUS;
+    use crate::verify::{verify_solution, Violation};
+
+    #[test]
+    fn names_every_rule_each_pair_breaks() {
+        assert_eq!(
+            conflicts(CORPUS[0].json.to_string(), "[9,0,1,63]".to_string()),
+            Ok(concat!(
+                r#"[{"first":0,"second":1,"rule":"row"},"#,
+                r#"{"first":0,"second":1,"rule":"color"},"#,
+                r#"{"first":0,"second":9,"rule":"adjacent"},"#,
+                r#"{"first":1,"second":9,"rule":"column"}]"#,
+            )
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn agrees_with_verifying_every_pair() {
+        let colorless = r#"{"rows":6,"cols":6,"rules":{"colorless":true}}"#;
+        let hex = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+            0,0,1,1, 0,2,2,1, 3,2,2,1, 3,3,3,3],"rules":{"topology":"hex"}}"#;
+        let boards = CORPUS
+            .iter()
+            .map(|entry| entry.json)
+            .chain([colorless, hex]);
+
+        for game in boards.map(|json| Game::from_json(json).unwrap()) {
+            let cells = game.rows * game.cols;
+            for first in 0..cells {
+                for second in first + 1..cells {
+                    let rules: Vec<_> = find_conflicts(&game, &[second, first])
+                        .unwrap()
+                        .into_iter()
+                        .map(|conflict| {
+                            assert_eq!((conflict.first, conflict.second), (first, second));
+                            conflict.rule
+                        })
+                        .collect();
+                    let broken: Vec<_> = verify_solution(&game, &[first, second])
+                        .into_iter()
+                        .filter_map(|violation| match violation {
+                            Violation::RowTwice { .. } => Some(PairRule::Row),
+                            Violation::ColumnTwice { .. } => Some(PairRule::Column),
+                            Violation::ColorTwice { .. } => Some(PairRule::Color),
+                            Violation::Adjacent { .. } => Some(PairRule::Adjacent),
+                            _ => None,
+                        })
+                        .collect();
+                    assert_eq!(rules, broken, "cells {} and {}", first, second);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finds_nothing_among_the_queens_of_a_solution() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            let conflicts = find_conflicts(&game, entry.solution).unwrap();
+            assert!(conflicts.is_empty(), "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_queens() {
+        let check = |placed: &str| conflicts(CORPUS[0].json.to_string(), placed.to_string());
+        assert_eq!(
+            check("[64]"),
+            Err("Cell 64 is not on the board, which has cells 0..64".to_string())
+        );
+        assert_eq!(
+            check("[3,5,3]"),
+            Err("Cell 3 holds more than one queen".to_string())
+        );
+        let error = check("[true]").unwrap_err();
+        assert!(error.starts_with("Invalid placed JSON: "), "{}", error);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::strategy::Status;
+use crate::verify::{verify_solution, Violation};
+use crate::{enumerate_solutions, Game};
+use serde::Serialize;
+
+/// The outcome of `solve_with_constraints`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConstrainedSolve {
+    /// `solved` or `unsolvable`.
+    pub status: Status,
+    /// The cells of the solution, sorted. Empty unless solved.
+    pub solution: Vec<usize>,
+    /// The cells both required and excluded, sorted.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overlap: Vec<usize>,
+    /// The rules the required cells break among themselves.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<Violation>,
+}
+
+/// A solution of the game with queens on all of `include` and none on `exclude`, its cells
+/// sorted, or `None` if there is no such solution. The one search behind every question about
+/// solutions with some cells fixed.
+pub(crate) fn find_solution(
+    game: &Game,
+    include: &[usize],
+    exclude: &[usize],
+) -> Option<Vec<usize>> {
+    // The search places the required queens before excluding cells, so it would not notice a
+    // cell that is both.
+    if include.iter().any(|idx| exclude.contains(idx)) {
+        return None;
+    }
+
+    enumerate_solutions(game, include, exclude, 1).pop()
+}
+
+/// Solves the game with queens required on `include` and ruled out on `exclude`, telling apart
+/// constraints that contradict each other, which are reported without searching, from those that
+/// only contradict the board. Fails on cells off the board; repeated cells count once.
+pub(crate) fn solve_with_constraints(
+    game: &Game,
+    include: &[usize],
+    exclude: &[usize],
+) -> Result<ConstrainedSolve, String> {
+    let cells = game.rows * game.cols;
+    if let Some(&idx) = include.iter().chain(exclude).find(|&&idx| idx >= cells) {
+        return Err(format!(
+            "Cell {} is not on the board, which has cells 0..{}",
+            idx, cells
+        ));
+    }
+
+    let mut include = include.to_vec();
+    include.sort_unstable();
+    include.dedup();
+    let mut exclude = exclude.to_vec();
+    exclude.sort_unstable();
+    exclude.dedup();
+
+    let overlap: Vec<_> = include
+        .iter()
+        .copied()
+        .filter(|idx| exclude.binary_search(idx).is_ok())
+        .collect();
+    // Some queens are still missing, and a link may yet be completed, so only the rules two of
+    // the required queens break together count.
+    let conflicts: Vec<_> = verify_solution(game, &include)
+        .into_iter()
+        .filter(|violation| {
+            matches!(
+                violation,
+                Violation::RowTwice { .. }
+                    | Violation::ColumnTwice { .. }
+                    | Violation::ColorTwice { .. }
+                    | Violation::Adjacent { .. }
+            )
+        })
+        .collect();
+
+    let solution = if overlap.is_empty() && conflicts.is_empty() {
+        find_solution(game, &include, &exclude)
+    } else {
+        None
+    };
+
+    Ok(ConstrainedSolve {
+        status: if solution.is_some() {
+            Status::Solved
+        } else {
+            Status::Unsolvable
+        },
+        solution: solution.unwrap_or_default(),
+        overlap,
+        conflicts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::solve_constrained;
+
+    fn solve(include: &str, exclude: &str) -> Result<String, String> {
+        let game = CORPUS[0].json.to_string();
+        solve_constrained(game, include.to_string(), exclude.to_string())
+    }
+
+    #[test]
+    fn solves_with_cells_required_and_ruled_out() {
+        let colorless = Game::from_json(r#"{"rows":6,"cols":6,"rules":{"colorless":true}}"#);
+        let mut several = Game::from_json(CORPUS[0].json).unwrap();
+        several.idx_to_color[33] = 1;
+
+        for game in [colorless.unwrap(), several] {
+            let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+            let cells = game.rows * game.cols;
+            for include in 0..cells {
+                for exclude in 0..cells {
+                    let solved = solve_with_constraints(&game, &[include], &[exclude]).unwrap();
+                    let expected = solutions.iter().any(|solution| {
+                        solution.contains(&include) && !solution.contains(&exclude)
+                    });
+
+                    if expected {
+                        assert_eq!(solved.status, Status::Solved);
+                        assert!(solutions.contains(&solved.solution));
+                        assert!(solved.solution.contains(&include));
+                        assert!(!solved.solution.contains(&exclude));
+                    } else {
+                        assert_eq!(solved.status, Status::Unsolvable);
+                        assert!(solved.solution.is_empty());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_constraints_contradicting_each_other() {
+        assert_eq!(
+            solve("[4,4,9]", "[9,3]"),
+            Ok(r#"{"status":"unsolvable","solution":[],"overlap":[9]}"#.to_string())
+        );
+        assert_eq!(
+            solve("[1,8]", "[]"),
+            Ok(concat!(
+                r#"{"status":"unsolvable","solution":[],"#,
+                r#""conflicts":[{"kind":"adjacent","first":1,"second":8}]}"#,
+            )
+            .to_string())
+        );
+        assert_eq!(
+            solve("[0,2]", "[]"),
+            Ok(concat!(
+                r#"{"status":"unsolvable","solution":[],"conflicts":["#,
+                r#"{"kind":"rowTwice","row":0},{"kind":"colorTwice","color":0}]}"#,
+            )
+            .to_string())
+        );
+
+        // Cell 56 is all of color 7, so the constraints only contradict the board.
+        assert_eq!(
+            solve("[4]", "[56]"),
+            Ok(r#"{"status":"unsolvable","solution":[]}"#.to_string())
+        );
+        assert_eq!(
+            solve("[4,9]", "[]"),
+            Ok(r#"{"status":"solved","solution":[4,9,19,29,39,42,54,56]}"#.to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_cells_off_the_board_and_bad_lists() {
+        assert_eq!(
+            solve("[64]", "[]"),
+            Err("Cell 64 is not on the board, which has cells 0..64".to_string())
+        );
+        assert_eq!(
+            solve("[]", "[0,64]"),
+            Err("Cell 64 is not on the board, which has cells 0..64".to_string())
+        );
+        let error = solve("4", "[]").unwrap_err();
+        assert!(error.starts_with("Invalid include JSON: "), "{}", error);
+        let error = solve("[]", "[-1]").unwrap_err();
+        assert!(error.starts_with("Invalid exclude JSON: "), "{}", error);
+    }
+}
@@ -0,0 +1,127 @@
+/// A board of the benchmark corpus, with the answer the solver must give for it.
+pub struct CorpusBoard {
+    pub name: &'static str,
+    /// The game JSON, as taken by `solve`, one row of the board per line.
+    pub json: &'static str,
+    /// The cells of the board's only solution, sorted, or none if it has no solution.
+    pub solution: &'static [usize],
+}
+
+/// Boards for the benchmarks and `run_self_tests`, and for checking answers against: puzzles of
+/// the sizes the game uses, and a couple of generated ones that are much harder to search.
+pub const CORPUS: &[CorpusBoard] = &[
+    // Regions grown around a planted solution and recolored until no other is left, like the
+    // daily puzzles: a few regions pin down the rest quickly.
+    CorpusBoard {
+        name: "8x8",
+        json: r#"{"rows":8,"cols":8,"colors":[0,1,2,3,4,5,6,7],"idxToColor":[
+            0,0,0,0,0,0,0,0,
+            1,1,2,0,0,0,0,0,
+            1,5,2,2,0,3,6,4,
+            5,5,5,5,0,3,6,4,
+            5,5,5,5,5,5,6,4,
+            5,5,5,5,5,6,6,4,
+            5,5,5,5,5,6,6,6,
+            7,5,5,5,5,6,6,6
+        ]}"#,
+        solution: &[4, 9, 19, 29, 39, 42, 54, 56],
+    },
+    CorpusBoard {
+        name: "9x9",
+        json: r#"{"rows":9,"cols":9,"colors":[0,1,2,3,4,5,6,7,8],"idxToColor":[
+            0,0,0,0,0,0,0,0,0,
+            1,1,0,0,0,0,0,0,2,
+            3,0,0,0,0,0,0,4,2,
+            3,0,0,0,0,0,0,4,4,
+            3,0,0,0,0,0,0,4,4,
+            3,3,0,5,5,0,4,4,4,
+            3,3,7,5,5,5,6,4,4,
+            7,7,7,7,5,5,4,4,4,
+            7,7,7,7,8,5,5,4,4
+        ]}"#,
+        solution: &[5, 10, 26, 27, 43, 48, 60, 65, 76],
+    },
+    CorpusBoard {
+        name: "10x10",
+        json: r#"{"rows":10,"cols":10,"colors":[0,1,2,3,4,5,6,7,8,9],"idxToColor":[
+            2,2,0,0,0,0,0,0,0,0,
+            2,2,4,4,4,1,0,0,0,0,
+            2,4,4,4,3,6,6,6,6,5,
+            4,4,4,3,3,6,6,6,6,5,
+            4,4,6,6,6,6,6,6,5,5,
+            4,7,6,6,6,6,6,6,5,5,
+            7,7,6,6,6,6,6,6,6,6,
+            7,7,7,6,6,6,6,6,6,6,
+            7,7,7,7,6,6,6,6,6,8,
+            7,7,7,7,9,9,6,6,8,8
+        ]}"#,
+        solution: &[7, 15, 20, 33, 41, 58, 66, 72, 89, 94],
+    },
+    CorpusBoard {
+        name: "11x11",
+        json: r#"{"rows":11,"cols":11,"colors":[0,1,2,3,4,5,6,7,8,9,10],"idxToColor":[
+            2,2,2,2,2,4,0,0,1,1,1,
+            2,2,2,2,2,4,1,1,1,1,1,
+            2,2,4,4,4,4,4,3,1,6,1,
+            4,4,4,4,4,4,4,3,6,6,6,
+            4,4,4,4,4,4,4,4,4,4,6,
+            5,4,4,4,4,4,4,4,6,6,6,
+            4,4,4,4,4,4,6,6,6,6,6,
+            4,4,4,4,9,4,9,9,6,6,7,
+            4,4,4,8,9,9,9,9,7,6,7,
+            4,4,10,10,9,9,9,9,7,6,7,
+            4,10,10,10,9,9,9,9,7,7,7
+        ]}"#,
+        solution: &[6, 20, 23, 40, 48, 55, 74, 87, 91, 104, 112],
+    },
+    // Regions grown at random, which rarely leaves one solution and then takes a long search to
+    // find it.
+    CorpusBoard {
+        name: "hard-10x10",
+        json: r#"{"rows":10,"cols":10,"colors":[0,1,2,3,4,5,6,7,8,9],"idxToColor":[
+            4,4,4,4,4,4,1,1,1,1,
+            5,5,5,4,4,4,4,1,6,6,
+            9,9,5,5,4,4,4,7,7,7,
+            9,9,8,8,8,8,4,7,7,7,
+            9,9,8,8,8,8,0,7,7,7,
+            3,9,8,8,8,8,0,0,7,7,
+            3,9,8,8,0,0,0,0,0,0,
+            3,3,2,8,8,0,0,0,0,0,
+            2,2,2,0,0,0,0,0,0,0,
+            2,2,2,0,0,0,0,0,0,0
+        ]}"#,
+        solution: &[7, 19, 23, 36, 41, 58, 60, 74, 82, 95],
+    },
+    // Random regions without a solution, which the search has to refute entirely.
+    CorpusBoard {
+        name: "unsolvable-9x9",
+        json: r#"{"rows":9,"cols":9,"colors":[0,1,2,3,4,5,6,7,8],"idxToColor":[
+            0,0,0,0,0,5,5,5,5,
+            0,0,0,0,0,5,5,5,5,
+            0,0,0,0,5,5,5,5,5,
+            0,4,0,0,4,5,5,5,5,
+            4,4,4,4,4,6,6,5,5,
+            4,4,4,4,4,6,6,6,5,
+            2,2,2,4,4,3,3,7,7,
+            2,2,2,8,8,3,3,7,7,
+            2,2,2,2,8,1,1,7,7
+        ]}"#,
+        solution: &[],
+    },
+    CorpusBoard {
+        name: "unsolvable-10x10",
+        json: r#"{"rows":10,"cols":10,"colors":[0,1,2,3,4,5,6,7,8,9],"idxToColor":[
+            9,6,6,6,6,2,2,5,7,7,
+            1,6,6,6,6,2,2,5,2,2,
+            1,6,6,6,2,2,2,2,2,2,
+            1,6,4,4,4,2,2,2,2,2,
+            1,4,4,4,2,2,2,2,2,2,
+            4,4,4,4,2,2,2,2,2,2,
+            4,4,0,0,0,0,2,2,2,2,
+            4,3,3,3,8,0,2,2,2,2,
+            3,3,3,3,8,2,2,2,2,2,
+            3,3,3,3,3,3,2,2,2,2
+        ]}"#,
+        solution: &[],
+    },
+];
@@ -0,0 +1,465 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::options::Options;
+use crate::profile::Event;
+use crate::search::{Search, Unit};
+use crate::topology::Topology;
+use crate::utils::Rng;
+use crate::Game;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The most columns `count_by_rows` takes on.
+const DP_MAX_COLS: usize = 14;
+
+/// The most states `count_by_rows` keeps for one row before leaving the board to the search.
+const DP_MAX_STATES: usize = 1 << 20;
+
+impl<A: CellAvailability> Search<'_, A> {
+    /// Visits every solution below the root's candidates at positions `first`, `first + step`,
+    /// and so on, summing what `visit` returns for each, normally 1, until the sum reaches
+    /// `limit`. Without a limit, searches with `first` running over `0..step` together visit
+    /// every solution exactly once, so they can run independently.
+    ///
+    /// Counting needs nodes to split the solutions between their children, which only holds when
+    /// branching over the cells of a single unit, so `options` must use `Branching::Unit`.
+    /// Nogoods are neither consulted nor recorded.
+    pub(crate) fn count_share<const COLORS: bool>(
+        &mut self,
+        first: usize,
+        step: usize,
+        limit: u64,
+        visit: &mut impl FnMut(&[usize]) -> u64,
+    ) -> u64 {
+        self.count_within::<COLORS>(first, step, limit, visit)
+    }
+
+    fn count_within<const COLORS: bool>(
+        &mut self,
+        first: usize,
+        step: usize,
+        limit: u64,
+        visit: &mut impl FnMut(&[usize]) -> u64,
+    ) -> u64 {
+        self.stats.nodes += 1;
+        self.profiler.count(self.sorted_solution.len(), Event::Node);
+
+        let steps = self.propagate::<COLORS>();
+
+        let count = if self.is_solved() {
+            // A board solved by propagation alone belongs to the first share.
+            if first == 0 {
+                visit(&self.solution)
+            } else {
+                0
+            }
+        } else {
+            let mut count = 0;
+
+            let candidates = self.get_candidates::<COLORS>();
+            for &(row, col) in candidates.iter().skip(first).step_by(step) {
+                if count >= limit {
+                    break;
+                }
+
+                let idx = row * self.game.cols + col;
+                let sorted_pos = self.push(idx);
+                self.set_queen(idx, true);
+
+                count += self.count_within::<COLORS>(0, 1, limit - count, visit);
+
+                self.set_queen(idx, false);
+                self.pop(sorted_pos);
+            }
+            self.recycle(candidates);
+
+            count
+        };
+
+        self.unpropagate(steps);
+        count
+    }
+}
+
+impl<A: CellAvailability> Search<'_, A> {
+    /// Walks one random path from the root of the counting tree down to a solution or a dead
+    /// end, returning the product of the number of candidates at every node on the way, or 0 at
+    /// a dead end. This averages to the number of solutions over many probes, since every leaf
+    /// is reached with the inverse of that product as probability.
+    ///
+    /// Branches like `count_share`, so `options` must use `Branching::Unit`.
+    pub(crate) fn probe<const COLORS: bool>(&mut self, rng: &mut Rng) -> f64 {
+        let mut weight = 1.0;
+        // Every queen placed on the way, with its position in `sorted_solution` and the steps
+        // propagated at the node above it.
+        let mut path = vec![];
+
+        let mut steps = self.propagate::<COLORS>();
+        let leaf = loop {
+            self.stats.nodes += 1;
+            if self.is_solved() {
+                break weight;
+            }
+
+            let candidates = self.get_candidates::<COLORS>();
+            if candidates.is_empty() {
+                self.recycle(candidates);
+                break 0.0;
+            }
+
+            weight *= candidates.len() as f64;
+            let (row, col) = candidates[rng.below(candidates.len())];
+            self.recycle(candidates);
+
+            let idx = row * self.game.cols + col;
+            let sorted_pos = self.push(idx);
+            self.set_queen(idx, true);
+            path.push((idx, sorted_pos, steps));
+
+            steps = self.propagate::<COLORS>();
+        };
+
+        self.unpropagate(steps);
+        while let Some((idx, sorted_pos, steps)) = path.pop() {
+            self.set_queen(idx, false);
+            self.pop(sorted_pos);
+            self.unpropagate(steps);
+        }
+
+        leaf
+    }
+}
+
+/// An estimate of the number of solutions from random probes of the search tree.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Estimate {
+    /// The mean of the probes.
+    pub estimate: f64,
+    pub probes: usize,
+    /// The sample variance of the probes.
+    pub variance: f64,
+    /// The standard error of `estimate`, which only roughly describes its accuracy since a few
+    /// rare probes can dominate the mean.
+    pub standard_error: f64,
+}
+
+/// Estimates the number of solutions with Knuth's random probing of the tree that counting
+/// searches, taking `probes` probes seeded by `seed`.
+pub(crate) fn estimate_count(game: &Game, probes: usize, seed: u64) -> Estimate {
+    let options = Options::for_counting();
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        run_probes(game, &options, BitAvailability::new(game), probes, seed)
+    } else {
+        run_probes(game, &options, Availability::new(game), probes, seed)
+    }
+}
+
+fn run_probes<A: CellAvailability>(
+    game: &Game,
+    options: &Options,
+    availability: A,
+    probes: usize,
+    seed: u64,
+) -> Estimate {
+    let mut search = Search::new(game, options, availability);
+    let mut rng = Rng::new(seed);
+
+    // Welford's running mean and sum of squared deviations, which stay accurate with huge
+    // probe values.
+    let (mut mean, mut deviations) = (0.0, 0.0);
+    for probe in 1..=probes {
+        let value = if game.has_color_rule() {
+            search.probe::<true>(&mut rng)
+        } else {
+            search.probe::<false>(&mut rng)
+        };
+
+        let delta = value - mean;
+        mean += delta / probe as f64;
+        deviations += delta * (value - mean);
+    }
+
+    let variance = if probes > 1 {
+        deviations / (probes - 1) as f64
+    } else {
+        0.0
+    };
+
+    Estimate {
+        estimate: mean,
+        probes,
+        variance,
+        standard_error: (variance / probes as f64).sqrt(),
+    }
+}
+
+/// How the solutions of a board spread over the cells of one unit, from `branch_census`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Census {
+    pub unit: Unit,
+    /// Every cell of the unit in increasing order, with the solutions holding a queen on it.
+    pub cells: Vec<CellCount>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct CellCount {
+    pub idx: usize,
+    pub count: u64,
+}
+
+/// Counts the solutions through every cell of `unit`, or with `None` of the tightest unit left
+/// once the forced queens are placed, the one the counter would branch on first. Each count
+/// is a search of its own with a queen given on the cell, so the cells counting zero are those
+/// no solution uses.
+pub(crate) fn branch_census(game: &Game, unit: Option<Unit>) -> Result<Census, String> {
+    let options = Options::for_counting();
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        census_with(game, &options, unit, || BitAvailability::new(game))
+    } else {
+        census_with(game, &options, unit, || Availability::new(game))
+    }
+}
+
+fn census_with<A: CellAvailability>(
+    game: &Game,
+    options: &Options,
+    unit: Option<Unit>,
+    availability: impl Fn() -> A,
+) -> Result<Census, String> {
+    let unit = match unit {
+        Some(unit) => {
+            let (kind, index, units) = match unit {
+                Unit::Row(row) => ("Row", row, game.rows),
+                Unit::Col(col) => ("Column", col, game.cols),
+                Unit::Color(_) if !game.has_color_rule() => {
+                    return Err("Colorless boards have no colors to count over".to_string());
+                }
+                Unit::Color(color) => ("Color", color, game.num_colors()),
+            };
+            if index >= units {
+                return Err(format!(
+                    "{} {} is not on the board, which has {} of them",
+                    kind, index, units
+                ));
+            }
+            unit
+        }
+        None => {
+            let mut search = Search::new(game, options, availability());
+            let tightest = if game.has_color_rule() {
+                search.propagate::<true>();
+                search.tightest_unit::<true>()
+            } else {
+                search.propagate::<false>();
+                search.tightest_unit::<false>()
+            };
+            // Only a board propagation solves has no unit left to branch on, and then any does.
+            tightest.map_or(Unit::Row(0), |(_, unit)| unit)
+        }
+    };
+
+    let cells = (0..game.rows * game.cols)
+        .filter(|&idx| unit.contains(game, idx))
+        .map(|idx| {
+            let mut search = Search::new(game, options, availability());
+            let count = if !search.assume(&[idx], &[]) {
+                0
+            } else if game.has_color_rule() {
+                search.count_share::<true>(0, 1, u64::MAX, &mut |_| 1)
+            } else {
+                search.count_share::<false>(0, 1, u64::MAX, &mut |_| 1)
+            };
+
+            CellCount { idx, count }
+        })
+        .collect();
+
+    Ok(Census { unit, cells })
+}
+
+/// Counts the solutions one row at a time, without enumerating them, or returns `None` when the
+/// board is too wide or needs too many states, or has custom adjacency, links, or the lines of
+/// `HexLines`.
+///
+/// After each row, the partial placements are merged by the columns and colors they use and the
+/// column of their last queen. Neighbors off the queen's row and column are always in consecutive
+/// rows, so the last queen is all that can still attack the next row's, and placements agreeing
+/// on those three extend in the same ways.
+pub(crate) fn count_by_rows(game: &Game) -> Option<u64> {
+    if game.cols > DP_MAX_COLS
+        || game.adjacency.is_some()
+        || game.has_same_links()
+        || game.rules.topology == Topology::HexLines
+    {
+        return None;
+    }
+
+    // Whether a queen in `col` attacks one in the previous row's `last` column. A hexagon's
+    // neighbor in the row above sits one column to its right.
+    let attacks = |last: usize, col: usize| match game.rules.topology {
+        Topology::Hex => col + 1 == last,
+        _ => last.abs_diff(col) == 1,
+    };
+
+    // Ways to reach each (used columns, used colors, last queen's column), starting from a row
+    // above the board whose queen attacks nothing.
+    let mut states: HashMap<(u64, u64, usize), u64> = HashMap::new();
+    states.insert((0, 0, usize::MAX), 1);
+
+    for row in 0..game.rows {
+        let mut next = HashMap::with_capacity(states.len());
+
+        for (&(cols, colors, last), &ways) in &states {
+            for col in (0..game.cols).filter(|&col| (cols >> col) & 1 == 0) {
+                if attacks(last, col) {
+                    continue;
+                }
+
+                let color_bit = if game.has_color_rule() {
+                    1 << game.idx_to_color[row * game.cols + col]
+                } else {
+                    0
+                };
+                if colors & color_bit != 0 {
+                    continue;
+                }
+
+                *next
+                    .entry((cols | (1 << col), colors | color_bit, col))
+                    .or_insert(0) += ways;
+            }
+        }
+
+        if next.len() > DP_MAX_STATES {
+            return None;
+        }
+        states = next;
+    }
+
+    let required_colors = if game.has_color_rule() && game.rules.all_colors_required {
+        (1 << game.num_colors()) - 1
+    } else {
+        0
+    };
+
+    Some(
+        states
+            .iter()
+            .filter(|&(&(_, colors, _), _)| colors & required_colors == required_colors)
+            .map(|(_, &ways)| ways)
+            .sum(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::enumerate_solutions;
+
+    fn enumerated(game: &Game) -> u64 {
+        enumerate_solutions(game, &[], &[], u64::MAX).len() as u64
+    }
+
+    /// Counting row by row finds as many solutions as enumerating them, on the corpus, on Hex
+    /// boards, and on boards with more columns than rows.
+    #[test]
+    fn counts_by_rows_like_enumerating() {
+        let shapes = [
+            r#"{"rows":6,"cols":6,"rules":{"topology":"hex"}}"#,
+            r#"{"rows":7,"cols":7,"rules":{"topology":"hex"}}"#,
+            r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+                0,0,1,1, 0,2,2,1, 3,2,2,1, 3,3,3,1],"rules":{"topology":"hex"}}"#,
+            r#"{"rows":4,"cols":7}"#,
+            r#"{"rows":5,"cols":8}"#,
+            r#"{"rows":3,"cols":5,"colors":[0,1,2],"idxToColor":[
+                0,0,1,1,1, 0,2,2,2,1, 2,2,2,2,2]}"#,
+        ];
+        let boards = CORPUS.iter().map(|entry| entry.json).chain(shapes);
+
+        for json in boards {
+            let game = Game::from_json(json).unwrap();
+            assert_eq!(count_by_rows(&game), Some(enumerated(&game)), "{}", json);
+        }
+    }
+
+    #[test]
+    fn leaves_what_it_cannot_count_to_the_search() {
+        for json in [
+            r#"{"rows":3,"cols":3,"adjacency":[[8],[],[],[],[],[],[],[],[0]]}"#,
+            r#"{"rows":5,"cols":5,"links":[{"kind":"same","cells":[1,13]}]}"#,
+            r#"{"rows":5,"cols":5,"rules":{"topology":"hexLines"}}"#,
+            r#"{"rows":2,"cols":15}"#,
+        ] {
+            let game = Game::from_json(json).unwrap();
+            assert_eq!(count_by_rows(&game), None, "{}", json);
+        }
+    }
+
+    /// Knuth's estimate is unbiased, so with enough probes it lands well within an order of
+    /// magnitude of the exact count on boards with thousands to hundreds of thousands of
+    /// solutions, whatever the seed.
+    #[test]
+    fn estimates_within_an_order_of_magnitude() {
+        for side in [8, 10] {
+            let game = Game::from_json(&format!(r#"{{"rows":{},"cols":{}}}"#, side, side)).unwrap();
+            let exact = count_by_rows(&game).unwrap() as f64;
+
+            for seed in 0..3 {
+                let estimate = estimate_count(&game, 2000, seed);
+                assert_eq!(estimate.probes, 2000);
+                assert!(
+                    exact / 10.0 < estimate.estimate && estimate.estimate < exact * 10.0,
+                    "side {} seed {}: {:?} for {}",
+                    side,
+                    seed,
+                    estimate,
+                    exact
+                );
+                assert!(estimate.variance > 0.0);
+            }
+        }
+    }
+
+    /// Every probe of a board deductions solve sees the one solution, and no probe of a board
+    /// without solutions finds one, so neither estimate varies.
+    #[test]
+    fn estimates_settled_boards_exactly() {
+        let singles = Game::from_json(
+            r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+                1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#,
+        )
+        .unwrap();
+        let estimate = estimate_count(&singles, 50, 1);
+        assert_eq!((estimate.estimate, estimate.variance), (1.0, 0.0));
+
+        for entry in CORPUS.iter().filter(|entry| entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            let estimate = estimate_count(&game, 50, 1);
+            assert_eq!(
+                (estimate.estimate, estimate.standard_error),
+                (0.0, 0.0),
+                "{}",
+                entry.name
+            );
+        }
+    }
+
+    #[test]
+    fn estimating_takes_a_probe() {
+        assert_eq!(
+            crate::estimate_solution_count(CORPUS[0].json.to_string(), 0, 1).unwrap_err(),
+            "Estimating the solution count takes at least one probe"
+        );
+        let output: serde_json::Value = serde_json::from_str(
+            &crate::estimate_solution_count(CORPUS[0].json.to_string(), 10, 1).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(output["probes"], 10);
+        assert!(output["standardError"].is_number());
+    }
+}
@@ -0,0 +1,455 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::options::Options;
+use crate::search::Search;
+use crate::{split_shares, AdjacentsLookup, Game};
+
+/// An independent part of a board from `split_board`, as a board of its own.
+pub(crate) struct Part {
+    pub game: Game,
+    /// The cells of `game` that cannot hold a queen, to be ruled out before searching it.
+    pub excluded: Vec<usize>,
+    /// The cell of the whole board for every cell of `game`.
+    pub cells: Vec<usize>,
+}
+
+impl Part {
+    /// The cells of the whole board for `queens` of the part.
+    pub fn to_board<'a>(&'a self, queens: &'a [usize]) -> impl Iterator<Item = usize> + 'a {
+        queens.iter().map(move |&idx| self.cells[idx])
+    }
+}
+
+/// Splits the board into parts whose solutions combine freely, or `None` if it does not split.
+///
+/// A full board never splits, since every cell joins its row and its column, so the cells that
+/// cannot hold a queen are ruled out first: those of a color whose queen would leave some set of
+/// colors fewer rows or columns than it has colors, and the other way around. The cells left are
+/// then grouped by the rows, columns, and colors they share and the neighbors they attack.
+///
+/// Only square boards whose colors all need a queen, without links, are split, since the parts of
+/// others would not keep their rules.
+pub(crate) fn split_board(game: &Game) -> Option<Vec<Part>> {
+    let n = game.rows;
+    if game.cols != n
+        || !game.has_color_rule()
+        || !game.rules.all_colors_required
+        || game.num_colors() != n
+        || !game.links.is_empty()
+    {
+        return None;
+    }
+
+    let cells = n * n;
+    let adjacency = AdjacentsLookup::for_game(game);
+    let mut open = vec![true; cells];
+    // Ruling out cells for the columns can leave a color fewer rows, or a queen forced, so the
+    // rules take turns until none rules out more. A board that fails to match has no solution,
+    // which the search is left to prove.
+    loop {
+        let by_rows = prune_lines(game, &mut open, |idx| idx / n)?;
+        let by_cols = prune_lines(game, &mut open, |idx| idx % n)?;
+        let by_singles = prune_neighbors(game, &adjacency, &mut open);
+        if !by_rows && !by_cols && !by_singles {
+            break;
+        }
+    }
+
+    // Rows are 0..n, columns n..2n, and colors 2n..3n.
+    let mut groups = Groups::new(3 * n);
+    for idx in (0..cells).filter(|&idx| open[idx]) {
+        let color = 2 * n + game.idx_to_color[idx];
+        groups.join(idx / n, color);
+        groups.join(n + idx % n, color);
+        for &neighbor in adjacency.neighbors(idx) {
+            if open[neighbor as usize] {
+                groups.join(color, 2 * n + game.idx_to_color[neighbor as usize]);
+            }
+        }
+    }
+
+    let root_of: Vec<_> = (0..3 * n).map(|unit| groups.root(unit)).collect();
+    let mut roots = root_of[..n].to_vec();
+    roots.sort_unstable();
+    roots.dedup();
+    if roots.len() < 2 {
+        return None;
+    }
+
+    roots
+        .iter()
+        .map(|&root| {
+            let of_part = |unit: usize| root_of[unit] == root;
+            let rows: Vec<_> = (0..n).filter(|&row| of_part(row)).collect();
+            let cols: Vec<_> = (0..n).filter(|&col| of_part(n + col)).collect();
+            let colors: Vec<_> = (0..n).filter(|&color| of_part(2 * n + color)).collect();
+            if cols.len() != rows.len() || colors.len() != rows.len() {
+                return None;
+            }
+
+            Some(cut_part(game, &adjacency, &open, &rows, &cols, &colors))
+        })
+        .collect()
+}
+
+/// The board of the cells in `rows` and `cols`, with the colors and neighbors they have on the
+/// whole board. Cells of other colors, or not `open`, are excluded.
+fn cut_part(
+    game: &Game,
+    adjacency: &AdjacentsLookup,
+    open: &[bool],
+    rows: &[usize],
+    cols: &[usize],
+    colors: &[usize],
+) -> Part {
+    let cells: Vec<_> = rows
+        .iter()
+        .flat_map(|&row| cols.iter().map(move |&col| row * game.cols + col))
+        .collect();
+    let mut position = vec![None; game.rows * game.cols];
+    for (part_idx, &idx) in cells.iter().enumerate() {
+        position[idx] = Some(part_idx);
+    }
+
+    let mut idx_to_color = Vec::with_capacity(cells.len());
+    let mut excluded = vec![];
+    for (part_idx, &idx) in cells.iter().enumerate() {
+        match colors
+            .iter()
+            .position(|&color| color == game.idx_to_color[idx])
+        {
+            Some(color) if open[idx] => idx_to_color.push(color),
+            // The cell is excluded, so its color only has to be one the part has.
+            Some(color) => {
+                idx_to_color.push(color);
+                excluded.push(part_idx);
+            }
+            None => {
+                idx_to_color.push(0);
+                excluded.push(part_idx);
+            }
+        }
+    }
+
+    let neighbors = cells
+        .iter()
+        .map(|&idx| {
+            adjacency
+                .neighbors(idx)
+                .iter()
+                .filter_map(|&neighbor| position[neighbor as usize])
+                .collect()
+        })
+        .collect();
+
+    Part {
+        game: Game {
+            rows: rows.len(),
+            cols: cols.len(),
+            colors: (0..colors.len()).collect(),
+            idx_to_color,
+            regions: None,
+            rules: game.rules.clone(),
+            adjacency: Some(neighbors),
+            cell_ids: None,
+            links: vec![],
+            strict: false,
+        },
+        excluded,
+        cells,
+    }
+}
+
+/// Rules out the `open` cells no matching of colors to the lines numbered by `line` can use, as
+/// with the all-different constraint: a cell is kept if its line goes to its color in some way
+/// of giving every color a line of its own. Returns whether any cell was ruled out, or `None` if
+/// there is no such way.
+fn prune_lines(game: &Game, open: &mut [bool], line: impl Fn(usize) -> usize) -> Option<bool> {
+    let n = game.rows;
+    let mut lines_of = vec![0u64; n];
+    for idx in (0..n * n).filter(|&idx| open[idx]) {
+        lines_of[game.idx_to_color[idx]] |= 1 << line(idx);
+    }
+
+    // Kuhn's augmenting paths, which are plenty for at most `MAX_UNITS` colors, after giving
+    // every color it can a free line straight away.
+    let mut color_of_line = vec![usize::MAX; n];
+    let mut taken = 0u64;
+    let mut unmatched = vec![];
+    for (color, &lines) in lines_of.iter().enumerate() {
+        let free = lines & !taken;
+        if free == 0 {
+            unmatched.push(color);
+            continue;
+        }
+        let line = free.trailing_zeros() as usize;
+        color_of_line[line] = color;
+        taken |= 1 << line;
+    }
+    for color in unmatched {
+        let mut seen = 0u64;
+        if !augment(color, &lines_of, &mut color_of_line, &mut seen) {
+            return None;
+        }
+    }
+
+    // A color points at the colors holding its other lines. Cells on a line held by a color in
+    // another strongly connected component are on no matching.
+    let successors: Vec<u64> = (0..n)
+        .map(|color| {
+            (0..n)
+                .filter(|&line| (lines_of[color] >> line) & 1 == 1)
+                .fold(0, |mask, line| mask | 1 << color_of_line[line])
+                & !(1 << color)
+        })
+        .collect();
+    let component = strong_components(&successors);
+
+    let mut pruned = false;
+    for idx in 0..n * n {
+        let holder = color_of_line[line(idx)];
+        if open[idx] && component[holder] != component[game.idx_to_color[idx]] {
+            open[idx] = false;
+            pruned = true;
+        }
+    }
+
+    Some(pruned)
+}
+
+/// Rules out the `open` neighbors of every cell left alone in its row, column, or color, whose
+/// queen is forced. Returns whether any cell was ruled out.
+fn prune_neighbors(game: &Game, adjacency: &AdjacentsLookup, open: &mut [bool]) -> bool {
+    let n = game.rows;
+    let mut spots = vec![0; 3 * n];
+    for idx in (0..n * n).filter(|&idx| open[idx]) {
+        spots[idx / n] += 1;
+        spots[n + idx % n] += 1;
+        spots[2 * n + game.idx_to_color[idx]] += 1;
+    }
+
+    let mut pruned = false;
+    for idx in 0..n * n {
+        let units = [idx / n, n + idx % n, 2 * n + game.idx_to_color[idx]];
+        if open[idx] && units.iter().any(|&unit| spots[unit] == 1) {
+            for &neighbor in adjacency.neighbors(idx) {
+                pruned |= std::mem::replace(&mut open[neighbor as usize], false);
+            }
+        }
+    }
+
+    pruned
+}
+
+/// Looks for a line for `color` along an augmenting path, taking lines from colors that can move
+/// to another. `seen` holds the lines already tried.
+fn augment(color: usize, lines_of: &[u64], color_of_line: &mut [usize], seen: &mut u64) -> bool {
+    for line in 0..lines_of.len() {
+        // Deeper paths may have tried some of the lines since.
+        if (lines_of[color] >> line) & 1 == 0 || (*seen >> line) & 1 == 1 {
+            continue;
+        }
+        *seen |= 1 << line;
+
+        if color_of_line[line] == usize::MAX
+            || augment(color_of_line[line], lines_of, color_of_line, seen)
+        {
+            color_of_line[line] = color;
+            return true;
+        }
+    }
+
+    false
+}
+
+/// The strongly connected component of every node of the graph given by the mask of the
+/// successors of each node, by Tarjan's algorithm. Nodes in the same component get the same
+/// number.
+fn strong_components(successors: &[u64]) -> Vec<usize> {
+    struct Tarjan<'a> {
+        successors: &'a [u64],
+        index: Vec<usize>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        component: Vec<usize>,
+        next_index: usize,
+        next_component: usize,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = self.next_index;
+            self.low[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+
+            let mut nexts = self.successors[node];
+            while nexts != 0 {
+                let next = nexts.trailing_zeros() as usize;
+                nexts &= nexts - 1;
+                if self.index[next] == usize::MAX {
+                    self.visit(next);
+                    self.low[node] = self.low[node].min(self.low[next]);
+                } else if self.on_stack[next] {
+                    self.low[node] = self.low[node].min(self.index[next]);
+                }
+            }
+
+            if self.low[node] == self.index[node] {
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    self.component[member] = self.next_component;
+                    if member == node {
+                        break;
+                    }
+                }
+                self.next_component += 1;
+            }
+        }
+    }
+
+    let nodes = successors.len();
+    let mut tarjan = Tarjan {
+        successors,
+        index: vec![usize::MAX; nodes],
+        low: vec![0; nodes],
+        on_stack: vec![false; nodes],
+        stack: vec![],
+        component: vec![0; nodes],
+        next_index: 0,
+        next_component: 0,
+    };
+    for node in 0..nodes {
+        if tarjan.index[node] == usize::MAX {
+            tarjan.visit(node);
+        }
+    }
+
+    tarjan.component
+}
+
+/// Disjoint sets of units, joined as cells link them.
+struct Groups {
+    parent: Vec<usize>,
+}
+
+impl Groups {
+    fn new(units: usize) -> Self {
+        Self {
+            parent: (0..units).collect(),
+        }
+    }
+
+    /// The unit standing for the set of `unit`, halving the path to it on the way.
+    fn root(&mut self, mut unit: usize) -> usize {
+        while self.parent[unit] != unit {
+            self.parent[unit] = self.parent[self.parent[unit]];
+            unit = self.parent[unit];
+        }
+        unit
+    }
+
+    fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.root(a), self.root(b));
+        self.parent[a.max(b)] = a.min(b);
+    }
+}
+
+/// The number of solutions of the board the parts were split from: the product of theirs.
+pub(crate) fn count_parts(parts: &[Part]) -> u64 {
+    parts
+        .iter()
+        .map(|part| {
+            split_shares(|first, step| count_part(part, first, step))
+                .into_iter()
+                .sum::<u64>()
+        })
+        .product()
+}
+
+/// Counts the solutions of `part` below the root candidates at positions `first`, `first + step`,
+/// and so on.
+fn count_part(part: &Part, first: usize, step: usize) -> u64 {
+    let options = Options::for_counting();
+    let game = &part.game;
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        count_with(part, &options, BitAvailability::new(game), first, step)
+    } else {
+        count_with(part, &options, Availability::new(game), first, step)
+    }
+}
+
+fn count_with<A: CellAvailability>(
+    part: &Part,
+    options: &Options,
+    availability: A,
+    first: usize,
+    step: usize,
+) -> u64 {
+    let mut search = Search::new(&part.game, options, availability);
+    search.assume(&[], &part.excluded);
+
+    // Only boards with the color rule are split.
+    search.count_share::<true>(first, step, u64::MAX, &mut |_| 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{enumerate_solutions, run_counter};
+
+    /// Two 4 by 4 blocks of row colors on the diagonal of a 9 by 9 board, joined by a ninth color
+    /// covering the rest, whose queen can only go on the center. Each block has the two solutions
+    /// of four queens that do not touch.
+    fn blocks() -> Game {
+        let idx_to_color: Vec<_> = (0..81)
+            .map(|idx| match (idx / 9, idx % 9) {
+                (row, col) if row < 4 && col < 4 => row,
+                (row, col) if row > 4 && col > 4 => row - 1,
+                _ => 8,
+            })
+            .collect();
+        let json = format!(
+            r#"{{"rows":9,"cols":9,"colors":[0,1,2,3,4,5,6,7,8],"idxToColor":{:?}}}"#,
+            idx_to_color
+        );
+
+        Game::from_json(&json).unwrap()
+    }
+
+    /// In counting mode the parts' counts multiply to the count of the whole board.
+    #[test]
+    fn counts_of_parts_multiply() {
+        let game = blocks();
+        let parts = split_board(&game).unwrap();
+        assert!(parts.len() > 1);
+
+        let counts: Vec<_> = parts
+            .iter()
+            .map(|part| count_parts(std::slice::from_ref(part)))
+            .collect();
+        assert_eq!(counts.iter().filter(|&&count| count == 2).count(), 2);
+        assert_eq!(counts.iter().product::<u64>(), 4);
+
+        assert_eq!(count_parts(&parts), 4);
+        assert_eq!(enumerate_solutions(&game, &[], &[], u64::MAX).len(), 4);
+        assert_eq!(run_counter(&game, false), 4);
+    }
+
+    /// Every solution of the whole board is one solution of each part put together.
+    #[test]
+    fn parts_cover_the_board() {
+        let game = blocks();
+        let parts = split_board(&game).unwrap();
+
+        let mut cells: Vec<_> = parts.iter().flat_map(|part| part.cells.clone()).collect();
+        cells.sort_unstable();
+        cells.dedup();
+        for solution in enumerate_solutions(&game, &[], &[], u64::MAX) {
+            assert!(solution.iter().all(|idx| cells.binary_search(idx).is_ok()));
+        }
+    }
+}
@@ -0,0 +1,592 @@
+use crate::recolor::{judge, Recolor, Verdict};
+use crate::regions::{color_pieces, edge_neighbors};
+use crate::Game;
+use serde::Serialize;
+
+/// How many times the average region size a region must exceed to look merged. Published boards
+/// stay under 4.25.
+const OVERSIZED: f64 = 4.5;
+
+/// The fraction of the average region size below which a region looks split off, on boards with
+/// too many regions. Boards with the right count often have single cells as regions.
+const UNDERSIZED: f64 = 0.25;
+
+/// How far a cell's color may lie towards a neighboring region's before the cell is suspected of
+/// belonging there: 0 at its own region's average color, 1 at the neighbor's.
+const BOUNDARY: f64 = 0.4;
+
+/// How many suspect cells and how many merges or splits get a fix proposed and judged.
+const MAX_CELL_FIXES: usize = 8;
+const MAX_REGION_FIXES: usize = 3;
+
+/// What looks wrong with a scraped board, and with the colors of its cells, ways to fix it.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Diagnosis {
+    pub anomalies: Vec<Anomaly>,
+    /// Proposed fixes, most promising first: those leaving a unique solution, then several, then
+    /// none, then an invalid board. Only offered when the colors of the cells are known.
+    pub fixes: Vec<Fix>,
+}
+
+/// Something real boards do not have, hinting that the clustering of colors went wrong.
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum Anomaly {
+    /// The colors with cells do not match the rows: fewer means two regions were merged, more
+    /// means one was split.
+    RegionCount { regions: usize, rows: usize },
+    /// A region far larger than the average, or far smaller on a board with too many regions.
+    SizeOutlier {
+        color: usize,
+        cells: usize,
+        average: f64,
+    },
+    /// A region in several pieces, each with its cells in increasing order.
+    Disconnected {
+        color: usize,
+        pieces: Vec<Vec<usize>>,
+    },
+}
+
+/// A change of colors that may undo a mis-scrape.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Fix {
+    pub kind: FixKind,
+    /// The cells to move, as for `what_if_recolor_batch`. A split moves cells to a new color,
+    /// numbered `colors.len()`, and a merge empties a color, which the fixed board drops.
+    pub recolors: Vec<Recolor>,
+    /// The fixed board, judged with the rules of the original.
+    pub verdict: Verdict,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FixKind {
+    /// One cell whose color lies towards a neighboring region's, moved there.
+    Cell,
+    /// Two neighboring regions of close colors, made one.
+    Merge,
+    /// A region whose cells fall into two groups of colors, made two.
+    Split,
+}
+
+/// Looks for the marks of a mis-scrape on `game`: a region count off from the rows, regions of
+/// outlying sizes, and regions in pieces. With `rgb`, the scraped color of every cell, also
+/// proposes fixes: cells that look closer to a neighboring region, and when the region count is
+/// off, the merges or splits of regions that the colors suggest.
+///
+/// `game` has colors, and was read without requiring enough of them for the rows, since that is
+/// what this looks for. Its rules still require them, and the fixes are judged with them.
+pub(crate) fn diagnose(game: &Game, rgb: Option<&[[f64; 3]]>) -> Result<Diagnosis, String> {
+    let cells = game.rows * game.cols;
+    if let Some(rgb) = rgb {
+        if rgb.len() != cells {
+            return Err(format!(
+                "Expected {} RGB values for a {}x{} board, found {}",
+                cells,
+                game.rows,
+                game.cols,
+                rgb.len()
+            ));
+        }
+    }
+
+    let mut sizes = vec![0; game.colors.len()];
+    for &color in &game.idx_to_color {
+        sizes[color] += 1;
+    }
+
+    let regions = sizes.iter().filter(|&&size| size > 0).count();
+    let too_few = regions < game.rows;
+    let too_many = regions > game.rows && game.rules.all_colors_required;
+    let average = cells as f64 / regions as f64;
+
+    let mut anomalies = vec![];
+    if too_few || too_many {
+        anomalies.push(Anomaly::RegionCount {
+            regions,
+            rows: game.rows,
+        });
+    }
+
+    for (color, &size) in sizes.iter().enumerate() {
+        let size_f = size as f64;
+        if size > 0 && (size_f > OVERSIZED * average || (too_many && size_f < UNDERSIZED * average))
+        {
+            anomalies.push(Anomaly::SizeOutlier {
+                color,
+                cells: size,
+                average,
+            });
+        }
+    }
+
+    for (color, pieces) in color_pieces(game).into_iter().enumerate() {
+        if pieces.len() > 1 {
+            anomalies.push(Anomaly::Disconnected { color, pieces });
+        }
+    }
+
+    let Some(rgb) = rgb else {
+        return Ok(Diagnosis {
+            anomalies,
+            fixes: vec![],
+        });
+    };
+
+    let palette = Palette::new(game, rgb);
+    let mut candidates = if too_few {
+        splits(game, rgb)
+    } else if too_many {
+        palette.merges(game)
+    } else {
+        vec![]
+    };
+    candidates.extend(palette.suspect_cells(game));
+
+    let mut fixes: Vec<_> = candidates
+        .into_iter()
+        .map(|(kind, recolors)| Fix {
+            kind,
+            verdict: judge(&recolored(game, &recolors), false),
+            recolors,
+        })
+        .collect();
+    fixes.sort_by_key(|fix| match fix.verdict {
+        Verdict::Unique { .. } => 0,
+        Verdict::Multiple => 1,
+        Verdict::Unsolvable => 2,
+        Verdict::Invalid { .. } => 3,
+    });
+
+    Ok(Diagnosis { anomalies, fixes })
+}
+
+/// The total and count of the scraped colors of every color's cells.
+struct Palette<'a> {
+    rgb: &'a [[f64; 3]],
+    sums: Vec<[f64; 3]>,
+    sizes: Vec<usize>,
+}
+
+impl<'a> Palette<'a> {
+    fn new(game: &Game, rgb: &'a [[f64; 3]]) -> Self {
+        let mut sums = vec![[0.0; 3]; game.colors.len()];
+        let mut sizes = vec![0; game.colors.len()];
+
+        for (&color, value) in game.idx_to_color.iter().zip(rgb) {
+            sizes[color] += 1;
+            for (sum, channel) in sums[color].iter_mut().zip(value) {
+                *sum += channel;
+            }
+        }
+
+        Self { rgb, sums, sizes }
+    }
+
+    /// The average scraped color of `color`, leaving out the cell `without` if given.
+    fn mean(&self, color: usize, without: Option<usize>) -> [f64; 3] {
+        let mut sum = self.sums[color];
+        let mut size = self.sizes[color] as f64;
+
+        if let Some(idx) = without {
+            for (sum, channel) in sum.iter_mut().zip(&self.rgb[idx]) {
+                *sum -= channel;
+            }
+            size -= 1.0;
+        }
+
+        sum.map(|total| total / size)
+    }
+
+    /// Single-cell moves for the most suspect cells: those whose scraped color lies furthest
+    /// towards the average of a region they touch, if at least `BOUNDARY` of the way. A cell's
+    /// own region is averaged without it, and cells alone in their region are left be.
+    fn suspect_cells(&self, game: &Game) -> Vec<(FixKind, Vec<Recolor>)> {
+        let mut suspects = vec![];
+
+        for (idx, &color) in game.idx_to_color.iter().enumerate() {
+            if self.sizes[color] < 2 {
+                continue;
+            }
+
+            let own = self.mean(color, Some(idx));
+            let mut best: Option<(f64, usize)> = None;
+
+            for next in edge_neighbors(game, idx) {
+                let other = game.idx_to_color[next];
+                if other == color {
+                    continue;
+                }
+
+                let along = progress(own, self.mean(other, None), self.rgb[idx]);
+                if along >= BOUNDARY && best.is_none_or(|(most, _)| along > most) {
+                    best = Some((along, other));
+                }
+            }
+
+            if let Some((along, other)) = best {
+                suspects.push((along, idx, other));
+            }
+        }
+
+        suspects.sort_by(|a, b| b.0.total_cmp(&a.0));
+        suspects
+            .into_iter()
+            .take(MAX_CELL_FIXES)
+            .map(|(_, idx, color)| (FixKind::Cell, vec![Recolor { idx, color }]))
+            .collect()
+    }
+
+    /// Merges of the touching regions with the closest average colors, the smaller region
+    /// folded into the larger.
+    fn merges(&self, game: &Game) -> Vec<(FixKind, Vec<Recolor>)> {
+        let mut pairs = vec![];
+        for (idx, &color) in game.idx_to_color.iter().enumerate() {
+            for next in edge_neighbors(game, idx) {
+                let other = game.idx_to_color[next];
+                if color < other && !pairs.contains(&(color, other)) {
+                    pairs.push((color, other));
+                }
+            }
+        }
+
+        let mut scored: Vec<_> = pairs
+            .into_iter()
+            .map(|(a, b)| (distance(self.mean(a, None), self.mean(b, None)), a, b))
+            .collect();
+        scored.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+        scored
+            .into_iter()
+            .take(MAX_REGION_FIXES)
+            .map(|(_, a, b)| {
+                let (from, into) = if self.sizes[a] < self.sizes[b] {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                let recolors = (0..game.idx_to_color.len())
+                    .filter(|&idx| game.idx_to_color[idx] == from)
+                    .map(|idx| Recolor { idx, color: into })
+                    .collect();
+                (FixKind::Merge, recolors)
+            })
+            .collect()
+    }
+}
+
+/// Splits of the regions whose scraped colors fall most clearly into two groups, found by two-means
+/// clustering, moving the smaller group to a new color.
+fn splits(game: &Game, rgb: &[[f64; 3]]) -> Vec<(FixKind, Vec<Recolor>)> {
+    let new_color = game.colors.len();
+    let mut scored = vec![];
+
+    for color in 0..game.colors.len() {
+        let cells: Vec<_> = (0..game.idx_to_color.len())
+            .filter(|&idx| game.idx_to_color[idx] == color)
+            .collect();
+        if let Some((separation, group)) = two_means(&cells, rgb) {
+            scored.push((separation, group));
+        }
+    }
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+        .into_iter()
+        .take(MAX_REGION_FIXES)
+        .map(|(_, group)| {
+            let recolors = group
+                .into_iter()
+                .map(|idx| Recolor {
+                    idx,
+                    color: new_color,
+                })
+                .collect();
+            (FixKind::Split, recolors)
+        })
+        .collect()
+}
+
+/// Splits `cells` in two by their scraped colors. Returns how far apart the two groups' averages
+/// are against the spread within them, and the smaller group, or nothing if the cells all look
+/// alike.
+fn two_means(cells: &[usize], rgb: &[[f64; 3]]) -> Option<(f64, Vec<usize>)> {
+    if cells.len() < 2 {
+        return None;
+    }
+
+    // Seeds as far apart as the cells allow: the cell furthest from the average, then the cell
+    // furthest from that one.
+    let furthest = |from: [f64; 3]| {
+        *cells
+            .iter()
+            .max_by(|&&a, &&b| distance(from, rgb[a]).total_cmp(&distance(from, rgb[b])))
+            .unwrap()
+    };
+    let first = furthest(average(cells, rgb));
+    let second = furthest(rgb[first]);
+    if distance(rgb[first], rgb[second]) == 0.0 {
+        return None;
+    }
+
+    let mut centers = [rgb[first], rgb[second]];
+    let mut in_first = vec![];
+    for _ in 0..16 {
+        let assignment: Vec<_> = cells
+            .iter()
+            .map(|&idx| distance(rgb[idx], centers[0]) <= distance(rgb[idx], centers[1]))
+            .collect();
+        if assignment == in_first {
+            break;
+        }
+        in_first = assignment;
+
+        let (a, b) = split(cells, &in_first);
+        centers = [average(&a, rgb), average(&b, rgb)];
+    }
+
+    let (a, b) = split(cells, &in_first);
+    let spread = cells
+        .iter()
+        .zip(&in_first)
+        .map(|(&idx, &first)| distance(rgb[idx], centers[if first { 0 } else { 1 }]))
+        .sum::<f64>()
+        / cells.len() as f64;
+
+    let separation = distance(centers[0], centers[1]) / (spread + 1.0);
+    Some((separation, if a.len() < b.len() { a } else { b }))
+}
+
+/// The cells marked in `in_first`, then the others.
+fn split(cells: &[usize], in_first: &[bool]) -> (Vec<usize>, Vec<usize>) {
+    let (a, b): (Vec<_>, Vec<_>) = cells.iter().zip(in_first).partition(|(_, &first)| first);
+    (
+        a.into_iter().map(|(&idx, _)| idx).collect(),
+        b.into_iter().map(|(&idx, _)| idx).collect(),
+    )
+}
+
+/// The board after `recolors`, with colors left without cells dropped and the others renamed in
+/// order. A new color takes the label after the largest.
+fn recolored(game: &Game, recolors: &[Recolor]) -> Game {
+    let mut labels = game.colors.clone();
+    labels.push(game.colors.iter().max().map_or(0, |&label| label + 1));
+
+    let mut idx_to_color = game.idx_to_color.clone();
+    for recolor in recolors {
+        idx_to_color[recolor.idx] = recolor.color;
+    }
+
+    let mut used = vec![false; labels.len()];
+    for &color in &idx_to_color {
+        used[color] = true;
+    }
+    let kept: Vec<_> = (0..labels.len()).filter(|&color| used[color]).collect();
+    let mut names = vec![0; labels.len()];
+    for (name, &color) in kept.iter().enumerate() {
+        names[color] = name;
+    }
+
+    Game {
+        rows: game.rows,
+        cols: game.cols,
+        colors: kept.iter().map(|&color| labels[color]).collect(),
+        idx_to_color: idx_to_color.iter().map(|&color| names[color]).collect(),
+        regions: None,
+        rules: game.rules.clone(),
+        adjacency: game.adjacency.clone(),
+        cell_ids: None,
+        links: game.links.clone(),
+        strict: false,
+    }
+}
+
+/// How far `point` lies along the way from `from` to `to`, projected onto that line.
+fn progress(from: [f64; 3], to: [f64; 3], point: [f64; 3]) -> f64 {
+    let way: Vec<_> = (0..3).map(|i| to[i] - from[i]).collect();
+    let length: f64 = way.iter().map(|d| d * d).sum();
+    if length == 0.0 {
+        return 0.0;
+    }
+
+    (0..3).map(|i| (point[i] - from[i]) * way[i]).sum::<f64>() / length
+}
+
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt()
+}
+
+fn average(cells: &[usize], rgb: &[[f64; 3]]) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    for &idx in cells {
+        for (total, channel) in sum.iter_mut().zip(&rgb[idx]) {
+            *total += channel;
+        }
+    }
+
+    sum.map(|total| total / cells.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::diagnose_scrape;
+    use serde_json::{json, Value};
+
+    /// A distinct shade for each color of the 9x9 corpus board, and one spare.
+    const SHADES: [[f64; 3]; 10] = [
+        [230.0, 100.0, 90.0],
+        [250.0, 200.0, 120.0],
+        [150.0, 190.0, 250.0],
+        [180.0, 230.0, 150.0],
+        [220.0, 220.0, 220.0],
+        [200.0, 160.0, 230.0],
+        [250.0, 250.0, 150.0],
+        [120.0, 200.0, 200.0],
+        [240.0, 150.0, 200.0],
+        [160.0, 120.0, 90.0],
+    ];
+
+    const SOLUTION: [usize; 9] = [5, 10, 26, 27, 43, 48, 60, 65, 76];
+
+    /// The colors of the 9x9 corpus board, and the shades of its cells.
+    fn scraped() -> (Vec<usize>, Vec<[f64; 3]>) {
+        let colors = Game::from_json(CORPUS[1].json).unwrap().idx_to_color;
+        let rgb = colors.iter().map(|&color| SHADES[color]).collect();
+        (colors, rgb)
+    }
+
+    fn diagnosis(colors: &[usize], rgb: Option<&[[f64; 3]]>) -> Value {
+        let count = colors.iter().max().unwrap() + 1;
+        let game = json!({
+            "rows": 9,
+            "cols": 9,
+            "colors": (0..count).collect::<Vec<_>>(),
+            "idxToColor": colors,
+        });
+        let rgb = rgb.map(|rgb| serde_json::to_string(rgb).unwrap());
+        serde_json::from_str(&diagnose_scrape(game.to_string(), rgb).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn finds_nothing_wrong_with_corpus_boards() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let diagnosis = diagnose_scrape(entry.json.to_string(), None).unwrap();
+            assert_eq!(
+                diagnosis, r#"{"anomalies":[],"fixes":[]}"#,
+                "{}",
+                entry.name
+            );
+        }
+
+        let (colors, rgb) = scraped();
+        assert_eq!(
+            diagnosis(&colors, Some(&rgb)),
+            json!({"anomalies": [], "fixes": []})
+        );
+    }
+
+    #[test]
+    fn splits_merged_regions() {
+        let (colors, rgb) = scraped();
+        let merged: Vec<_> = colors
+            .iter()
+            .map(|&color| color.saturating_sub(1))
+            .collect();
+
+        assert_eq!(
+            diagnosis(&merged, None),
+            json!({
+                "anomalies": [{"kind": "regionCount", "regions": 8, "rows": 9}],
+                "fixes": [],
+            })
+        );
+        let fixes = diagnosis(&merged, Some(&rgb))["fixes"].clone();
+        assert_eq!(
+            fixes[0],
+            json!({
+                "kind": "split",
+                "recolors": [{"idx": 9, "color": 8}, {"idx": 10, "color": 8}],
+                "verdict": {"status": "unique", "solution": SOLUTION},
+            })
+        );
+    }
+
+    #[test]
+    fn merges_split_regions() {
+        let (mut colors, mut rgb) = scraped();
+        // The first three cells are scraped as a shade of their own, barely off color 0's.
+        for idx in 0..3 {
+            colors[idx] = 9;
+            rgb[idx][0] += 4.0;
+        }
+
+        let diagnosis = diagnosis(&colors, Some(&rgb));
+        assert_eq!(
+            diagnosis["anomalies"][0],
+            json!({"kind": "regionCount", "regions": 10, "rows": 9})
+        );
+        assert_eq!(
+            diagnosis["fixes"][0],
+            json!({
+                "kind": "merge",
+                "recolors": [
+                    {"idx": 0, "color": 0},
+                    {"idx": 1, "color": 0},
+                    {"idx": 2, "color": 0},
+                ],
+                "verdict": {"status": "unique", "solution": SOLUTION},
+            })
+        );
+    }
+
+    #[test]
+    fn moves_cells_towards_the_region_they_look_like() {
+        let (mut colors, rgb) = scraped();
+        // Cell 16 has color 0's shade, but was put in color 4 below it.
+        colors[16] = 4;
+
+        let diagnosis = diagnosis(&colors, Some(&rgb));
+        assert_eq!(diagnosis["anomalies"], json!([]));
+        assert_eq!(
+            diagnosis["fixes"][0],
+            json!({
+                "kind": "cell",
+                "recolors": [{"idx": 16, "color": 0}],
+                "verdict": {"status": "unique", "solution": SOLUTION},
+            })
+        );
+    }
+
+    #[test]
+    fn finds_regions_in_pieces() {
+        let (mut colors, _) = scraped();
+        colors[0] = 8;
+
+        assert_eq!(
+            diagnosis(&colors, None)["anomalies"],
+            json!([{"kind": "disconnected", "color": 8, "pieces": [[0], [76]]}])
+        );
+    }
+
+    #[test]
+    fn rejects_colorless_boards_and_bad_colors() {
+        let colorless = r#"{"rows":4,"cols":4,"rules":{"colorless":true}}"#;
+        assert_eq!(
+            diagnose_scrape(colorless.to_string(), None),
+            Err("Colorless boards have no regions to diagnose".to_string())
+        );
+
+        let game = CORPUS[1].json.to_string();
+        assert_eq!(
+            diagnose_scrape(game.clone(), Some("[[0,0,0]]".to_string())),
+            Err("Expected 81 RGB values for a 9x9 board, found 1".to_string())
+        );
+        let error = diagnose_scrape(game, Some("[[0,0]]".to_string())).unwrap_err();
+        assert!(error.starts_with("Invalid RGB JSON: "), "{}", error);
+    }
+}
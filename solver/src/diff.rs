@@ -0,0 +1,266 @@
+use crate::Game;
+use serde::Serialize;
+
+/// What tells two scrapes of a board apart, from `diff_boards`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BoardDiff {
+    /// The rows and columns of each board.
+    pub size_a: [usize; 2],
+    pub size_b: [usize; 2],
+    /// The color of `b` matched with each color of `a`, or `None` for colors left over when the
+    /// boards have different numbers of them.
+    pub alignment: Vec<Option<usize>>,
+    /// The cells, in row-major order, of the rows and columns both boards have, whose color in
+    /// `b` is not the one matched with their color in `a`.
+    pub cells: Vec<CellDiff>,
+    /// Whether the boards have the same size and the same regions, however their colors are
+    /// numbered.
+    pub same: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct CellDiff {
+    pub row: usize,
+    pub col: usize,
+    /// The color of the cell on each board.
+    pub a: usize,
+    pub b: usize,
+}
+
+/// Compares the regions of two boards, matching the colors of `a` with those of `b` so that the
+/// matched regions share as many cells as possible, since scrapes can number the same regions in
+/// different orders. Only the cells whose region really differs are reported. Boards of
+/// different sizes are compared on the rows and columns they both have.
+pub(crate) fn diff_boards(a: &Game, b: &Game) -> BoardDiff {
+    let (rows, cols) = (a.rows.min(b.rows), a.cols.min(b.cols));
+    let shared = (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col)));
+    let colors = |row: usize, col: usize| {
+        (
+            a.idx_to_color[row * a.cols + col],
+            b.idx_to_color[row * b.cols + col],
+        )
+    };
+
+    let mut overlap = vec![vec![0; palette(b)]; palette(a)];
+    for (row, col) in shared.clone() {
+        let (color_a, color_b) = colors(row, col);
+        overlap[color_a][color_b] += 1;
+    }
+    let alignment = max_weight_matching(&overlap);
+
+    let cells: Vec<_> = shared
+        .filter_map(|(row, col)| {
+            let (color_a, color_b) = colors(row, col);
+            (alignment[color_a] != Some(color_b)).then_some(CellDiff {
+                row,
+                col,
+                a: color_a,
+                b: color_b,
+            })
+        })
+        .collect();
+
+    BoardDiff {
+        size_a: [a.rows, a.cols],
+        size_b: [b.rows, b.cols],
+        same: a.rows == b.rows && a.cols == b.cols && cells.is_empty(),
+        alignment,
+        cells,
+    }
+}
+
+/// How many colors the cells of `game` are numbered from, which colorless boards leave all 0.
+fn palette(game: &Game) -> usize {
+    let used = game.idx_to_color.iter().max().map_or(0, |&color| color + 1);
+    used.max(game.colors.len())
+}
+
+/// The column matched with every row of `weights` so that the matched weights sum to the most,
+/// by the Hungarian method in O(n³) for n the larger side. Rows left over when there are more
+/// rows than columns get `None`.
+fn max_weight_matching(weights: &[Vec<i64>]) -> Vec<Option<usize>> {
+    let rows = weights.len();
+    let cols = weights.first().map_or(0, Vec::len);
+    let n = rows.max(cols);
+    // Minimizes the cost of the square matrix padded with zeros, one-based with row and column 0
+    // standing for "none yet".
+    let cost = |row: usize, col: usize| -> i64 {
+        if row <= rows && col <= cols {
+            -weights[row - 1][col - 1]
+        } else {
+            0
+        }
+    };
+
+    let mut row_potential = vec![0; n + 1];
+    let mut col_potential = vec![0; n + 1];
+    let mut row_of_col = vec![0; n + 1];
+    let mut way = vec![0; n + 1];
+
+    for row in 1..=n {
+        row_of_col[0] = row;
+        let mut col = 0;
+        let mut slack = vec![i64::MAX; n + 1];
+        let mut used = vec![false; n + 1];
+
+        loop {
+            used[col] = true;
+            let current = row_of_col[col];
+            let mut delta = i64::MAX;
+            let mut next = 0;
+
+            for other in 1..=n {
+                if used[other] {
+                    continue;
+                }
+                let reduced = cost(current, other) - row_potential[current] - col_potential[other];
+                if reduced < slack[other] {
+                    slack[other] = reduced;
+                    way[other] = col;
+                }
+                if slack[other] < delta {
+                    delta = slack[other];
+                    next = other;
+                }
+            }
+
+            for other in 0..=n {
+                if used[other] {
+                    row_potential[row_of_col[other]] += delta;
+                    col_potential[other] -= delta;
+                } else {
+                    slack[other] -= delta;
+                }
+            }
+
+            col = next;
+            if row_of_col[col] == 0 {
+                break;
+            }
+        }
+
+        // Flip the augmenting path back to the root.
+        while col != 0 {
+            let previous = way[col];
+            row_of_col[col] = row_of_col[previous];
+            col = previous;
+        }
+    }
+
+    let mut matching = vec![None; rows];
+    for col in 1..=cols {
+        if (1..=rows).contains(&row_of_col[col]) {
+            matching[row_of_col[col] - 1] = Some(col - 1);
+        }
+    }
+
+    matching
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::utils::Rng;
+
+    /// The weight of the best matching, trying every way to match each row.
+    fn brute_force(weights: &[Vec<i64>], row: usize, taken: &mut Vec<bool>) -> i64 {
+        if row == weights.len() {
+            return 0;
+        }
+
+        let mut best = brute_force(weights, row + 1, taken);
+        for col in 0..taken.len() {
+            if !taken[col] {
+                taken[col] = true;
+                best = best.max(weights[row][col] + brute_force(weights, row + 1, taken));
+                taken[col] = false;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn matches_for_the_most_weight() {
+        let mut rng = Rng::new(11);
+        for _ in 0..200 {
+            let (rows, cols) = (1 + rng.below(5), 1 + rng.below(5));
+            let weights: Vec<Vec<i64>> = (0..rows)
+                .map(|_| (0..cols).map(|_| rng.below(10) as i64).collect())
+                .collect();
+
+            let matching = max_weight_matching(&weights);
+            let mut matched: Vec<_> = matching.iter().flatten().collect();
+            assert_eq!(matched.len(), rows.min(cols), "{:?}", weights);
+            matched.sort_unstable();
+            matched.dedup();
+            assert_eq!(matched.len(), rows.min(cols), "{:?}", weights);
+
+            let weight: i64 = (0..rows)
+                .filter_map(|row| matching[row].map(|col| weights[row][col]))
+                .sum();
+            let best = brute_force(&weights, 0, &mut vec![false; cols]);
+            assert_eq!(weight, best, "{:?}", weights);
+        }
+    }
+
+    #[test]
+    fn sees_through_renumbered_colors() {
+        for entry in CORPUS {
+            let a = Game::from_json(entry.json).unwrap();
+            let count = a.colors.len();
+            let mut b = a.clone();
+            for color in &mut b.idx_to_color {
+                *color = (*color + 3) % count;
+            }
+
+            let diff = diff_boards(&a, &b);
+            let shifted: Vec<_> = (0..count).map(|color| Some((color + 3) % count)).collect();
+            assert!(diff.same, "{}", entry.name);
+            assert!(diff.cells.is_empty(), "{}", entry.name);
+            assert_eq!(diff.alignment, shifted, "{}", entry.name);
+
+            // Moving a cell to another region is the one difference, however they are numbered.
+            b.idx_to_color[0] = b.idx_to_color[a.cols * a.rows - 1];
+            let diff = diff_boards(&a, &b);
+            assert!(!diff.same, "{}", entry.name);
+            assert_eq!(diff.alignment, shifted, "{}", entry.name);
+            assert_eq!(diff.cells.len(), 1, "{}", entry.name);
+            assert_eq!(
+                (diff.cells[0].row, diff.cells[0].col),
+                (0, 0),
+                "{}",
+                entry.name
+            );
+        }
+    }
+
+    #[test]
+    fn compares_boards_of_different_sizes_where_they_overlap() {
+        let a = r#"{"rows":3,"cols":3,"colors":[0,1,2],"idxToColor":[0,0,1, 0,2,1, 2,2,1]}"#;
+        let b = r#"{"rows":3,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+            2,2,0,3, 1,1,0,3, 1,1,0,3]}"#;
+        assert_eq!(
+            crate::diff_boards(a.to_string(), b.to_string()),
+            Ok(concat!(
+                r#"{"sizeA":[3,3],"sizeB":[3,4],"alignment":[2,0,1],"#,
+                r#""cells":[{"row":1,"col":0,"a":0,"b":1}],"same":false}"#,
+            )
+            .to_string())
+        );
+
+        // The same regions on a board with a column more are still not the same board.
+        let a = Game::from_json(a).unwrap();
+        let mut b = Game::from_json(b).unwrap();
+        b.idx_to_color[4] = 2;
+        let diff = diff_boards(&a, &b);
+        assert!(diff.cells.is_empty());
+        assert!(!diff.same);
+
+        assert_eq!(
+            crate::diff_boards(CORPUS[0].json.to_string(), "{".to_string()),
+            Err("Invalid game JSON: EOF while parsing an object at line 1 column 1".to_string())
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::options::Options;
+use crate::propagation::Step;
+use crate::search::{RunEnd, Search};
+use crate::Game;
+use serde::{Deserialize, Serialize};
+
+/// A way of making progress on a board, from the easiest to the hardest.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Technique {
+    /// A row, column, or color with a single spot left.
+    Singles,
+    /// A color whose spots lie in one row or column, ruling out the rest of that line.
+    Confinement,
+    /// Two colors whose spots lie in two rows or columns.
+    Pairs,
+    /// Three colors whose spots lie in three rows or columns.
+    Triples,
+    /// Trying a queen and backtracking, once no deduction applies.
+    Guessing,
+}
+
+impl Technique {
+    const NAMES: &'static [(&'static str, Technique)] = &[
+        ("singles", Technique::Singles),
+        ("confinement", Technique::Confinement),
+        ("pairs", Technique::Pairs),
+        ("triples", Technique::Triples),
+        ("guessing", Technique::Guessing),
+    ];
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::NAMES
+            .iter()
+            .find(|&&(registered, _)| registered == name)
+            .map(|&(_, technique)| technique)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::NAMES.iter().map(|&(name, _)| name).collect();
+                format!(
+                    "Unknown technique {:?}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// The technique of a step of propagation, which must not be a wipeout.
+    pub fn of_step(step: Step) -> Self {
+        match step {
+            Step::Forced { .. } => Technique::Singles,
+            Step::Confined { .. } => Technique::Confinement,
+            Step::Subset { colors, .. } if colors.count_ones() == 2 => Technique::Pairs,
+            Step::Subset { .. } => Technique::Triples,
+            Step::Wipeout { .. } => unreachable!("arc consistency is off"),
+        }
+    }
+}
+
+/// How often each technique was used, read back from the propagation trail.
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Usage {
+    /// Deductions made with each technique. A deduction ruling out several cells counts once.
+    pub singles: usize,
+    pub confinements: usize,
+    pub pairs: usize,
+    pub triples: usize,
+    /// The hardest technique used. Absent only when there was nothing to do.
+    pub hardest: Option<Technique>,
+}
+
+impl Usage {
+    /// Tallies the deductions in `trail`.
+    pub fn of_trail(trail: &[Step]) -> Self {
+        let mut usage = Self::default();
+
+        let mut last = None;
+        for &step in trail {
+            let technique = Technique::of_step(step);
+            usage.hardest = usage.hardest.max(Some(technique));
+
+            if !last.is_some_and(|last| same_deduction(last, step)) {
+                match technique {
+                    Technique::Singles => usage.singles += 1,
+                    Technique::Confinement => usage.confinements += 1,
+                    Technique::Pairs => usage.pairs += 1,
+                    Technique::Triples => usage.triples += 1,
+                    Technique::Guessing => {}
+                }
+            }
+            last = Some(step);
+        }
+
+        usage
+    }
+}
+
+/// Which techniques solving a board takes, from `assess_difficulty`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Difficulty {
+    /// The deductions made before guessing, with `hardest` raised to `Guessing` if the board
+    /// needed it.
+    #[serde(flatten)]
+    pub usage: Usage,
+    /// Whether the deductions alone solve the board.
+    pub solved_by_deduction: bool,
+    /// Nodes of the search finishing the board once the deductions stall, or 0.
+    pub search_nodes: usize,
+}
+
+/// Makes every deduction it can on the empty board, trying easier techniques before harder
+/// ones, and finishes with search if that leaves the board unsolved. Fails when the board has no
+/// solution.
+pub(crate) fn assess_difficulty(game: &Game) -> Result<Difficulty, String> {
+    let options = Options::for_deduction();
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        assess(game, &options, BitAvailability::new(game))
+    } else {
+        assess(game, &options, Availability::new(game))
+    }
+}
+
+fn assess<A: CellAvailability>(
+    game: &Game,
+    options: &Options,
+    availability: A,
+) -> Result<Difficulty, String> {
+    let mut search = Search::new(game, options, availability);
+    if game.has_color_rule() {
+        search.propagate::<true>();
+    } else {
+        search.propagate::<false>();
+    }
+
+    let mut difficulty = Difficulty {
+        usage: Usage::of_trail(&search.trail),
+        solved_by_deduction: search.is_solved(),
+        search_nodes: 0,
+    };
+
+    if !difficulty.solved_by_deduction {
+        if search.run() != RunEnd::Solved {
+            return Err("Board has no solution".to_string());
+        }
+
+        difficulty.search_nodes = search.stats.nodes;
+        difficulty.usage.hardest = Some(Technique::Guessing);
+    }
+
+    Ok(difficulty)
+}
+
+/// Whether `step` rules out another cell for the same reason as `last`, the step before it.
+pub(crate) fn same_deduction(last: Step, step: Step) -> bool {
+    match (last, step) {
+        (
+            Step::Confined { color, line, .. },
+            Step::Confined {
+                color: next_color,
+                line: next_line,
+                ..
+            },
+        ) => color == next_color && line == next_line,
+        (
+            Step::Subset { colors, lines, .. },
+            Step::Subset {
+                colors: next_colors,
+                lines: next_lines,
+                ..
+            },
+        ) => colors == next_colors && lines == next_lines,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::difficulty_breakdown;
+
+    fn corpus_game(name: &str) -> Game {
+        let entry = CORPUS.iter().find(|entry| entry.name == name).unwrap();
+        Game::from_json(entry.json).unwrap()
+    }
+
+    /// Color 0 is a single cell, and each queen leaves a single spot for the next.
+    #[test]
+    fn rates_a_board_of_singles() {
+        let game = Game::from_json(
+            r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+                1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#,
+        )
+        .unwrap();
+        let difficulty = assess_difficulty(&game).unwrap();
+
+        assert_eq!(difficulty.usage.singles, 4);
+        assert_eq!(
+            (
+                difficulty.usage.confinements,
+                difficulty.usage.pairs,
+                difficulty.usage.triples
+            ),
+            (0, 0, 0)
+        );
+        assert_eq!(difficulty.usage.hardest, Some(Technique::Singles));
+        assert!(difficulty.solved_by_deduction);
+        assert_eq!(difficulty.search_nodes, 0);
+    }
+
+    /// The 11x11 corpus board takes a pair: deductions solve it, but not without subsets.
+    #[test]
+    fn rates_a_board_needing_pairs() {
+        let game = corpus_game("11x11");
+        let difficulty = assess_difficulty(&game).unwrap();
+        assert!(difficulty.usage.pairs > 0);
+        assert_eq!(difficulty.usage.triples, 0);
+        assert_eq!(difficulty.usage.hardest, Some(Technique::Pairs));
+        assert!(difficulty.solved_by_deduction);
+        assert_eq!(difficulty.search_nodes, 0);
+
+        let options = Options {
+            subsets: false,
+            ..Options::for_deduction()
+        };
+        let mut search = Search::new(&game, &options, Availability::new(&game));
+        search.propagate::<true>();
+        assert!(!search.is_solved());
+    }
+
+    #[test]
+    fn rates_guessing_when_deductions_stall() {
+        let difficulty = assess_difficulty(&corpus_game("8x8")).unwrap();
+
+        assert!(!difficulty.solved_by_deduction);
+        assert_eq!(difficulty.usage.hardest, Some(Technique::Guessing));
+        assert!(difficulty.search_nodes > 0);
+    }
+
+    #[test]
+    fn fails_on_boards_without_solutions() {
+        for name in ["unsolvable-9x9", "unsolvable-10x10"] {
+            let json = CORPUS.iter().find(|entry| entry.name == name).unwrap().json;
+            assert_eq!(
+                difficulty_breakdown(json.to_string()),
+                Err("Board has no solution".to_string())
+            );
+        }
+        assert!(difficulty_breakdown("{".to_string()).is_err());
+    }
+
+    #[test]
+    fn reports_the_breakdown_in_camel_case() {
+        let json = CORPUS
+            .iter()
+            .find(|entry| entry.name == "9x9")
+            .unwrap()
+            .json;
+        let output: serde_json::Value =
+            serde_json::from_str(&difficulty_breakdown(json.to_string()).unwrap()).unwrap();
+
+        assert_eq!(
+            output,
+            serde_json::json!({
+                "singles": 9, "confinements": 1, "pairs": 0, "triples": 0,
+                "hardest": "confinement", "solvedByDeduction": true, "searchNodes": 0
+            })
+        );
+    }
+}
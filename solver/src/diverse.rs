@@ -0,0 +1,165 @@
+use crate::nearest::missing_from;
+use crate::{enumerate_solutions, Game};
+use serde::Serialize;
+
+/// How far apart the solutions of a board can be, from `find_most_different`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub(crate) enum Spread {
+    Unsolvable,
+    /// The board has only one solution, so there is nothing to compare it with.
+    Unique {
+        solution: Vec<usize>,
+    },
+    Multiple(Pair),
+}
+
+/// The two solutions differing the most among those compared.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Pair {
+    /// The cells of each solution, sorted.
+    pub first: Vec<usize>,
+    pub second: Vec<usize>,
+    /// How many cells hold a queen in one solution but not the other.
+    pub distance: usize,
+    /// The queens of `first` missing from `second`, sorted.
+    pub only_first: Vec<usize>,
+    /// The queens of `second` missing from `first`, sorted.
+    pub only_second: Vec<usize>,
+    /// How many solutions were compared.
+    pub compared: usize,
+    /// Whether those were every solution of the board, and so no other pair differs more.
+    pub exact: bool,
+}
+
+/// Compares every pair among up to `cap` solutions of `game`, keeping the pair differing in the
+/// most cells. Of pairs differing as much the first in sorted order wins, so the answer is the
+/// same every time.
+pub(crate) fn find_most_different(game: &Game, cap: u64) -> Result<Spread, String> {
+    if cap < 2 {
+        return Err("Comparing solutions takes a cap of at least 2".to_string());
+    }
+
+    let mut solutions = enumerate_solutions(game, &[], &[], cap.saturating_add(1));
+    let exact = solutions.len() as u64 <= cap;
+    solutions.truncate(cap as usize);
+
+    match solutions.len() {
+        0 => return Ok(Spread::Unsolvable),
+        1 => {
+            return Ok(Spread::Unique {
+                solution: solutions.pop().unwrap(),
+            })
+        }
+        _ => {}
+    }
+
+    let mut best = (0, 1, 0);
+    for (i, first) in solutions.iter().enumerate() {
+        for (j, second) in solutions.iter().enumerate().skip(i + 1) {
+            // Every solution has as many queens, so the cells of one missing from the other are
+            // half of those they differ in.
+            let distance = 2 * missing_from(first, second).len();
+            if distance > best.2 {
+                best = (i, j, distance);
+            }
+        }
+    }
+
+    let (i, j, distance) = best;
+    let (first, second) = (&solutions[i], &solutions[j]);
+    Ok(Spread::Multiple(Pair {
+        only_first: missing_from(first, second),
+        only_second: missing_from(second, first),
+        first: first.clone(),
+        second: second.clone(),
+        distance,
+        compared: solutions.len(),
+        exact,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::max_diff_solutions;
+
+    /// The 8x8 corpus board with cell 33 moved to color 1, which gives it five solutions.
+    fn several() -> Game {
+        let mut game = Game::from_json(CORPUS[0].json).unwrap();
+        game.idx_to_color[33] = 1;
+        game
+    }
+
+    fn differing(first: &[usize], second: &[usize]) -> usize {
+        first.iter().filter(|idx| !second.contains(idx)).count()
+            + second.iter().filter(|idx| !first.contains(idx)).count()
+    }
+
+    #[test]
+    fn finds_the_two_most_different_solutions() {
+        assert_eq!(
+            max_diff_solutions(several().to_json(), 10),
+            Ok(concat!(
+                r#"{"status":"multiple","first":[4,9,19,29,39,42,54,56],"#,
+                r#""second":[4,10,21,31,33,46,51,56],"distance":12,"#,
+                r#""onlyFirst":[9,19,29,39,42,54],"onlySecond":[10,21,31,33,46,51],"#,
+                r#""compared":5,"exact":true}"#,
+            )
+            .to_string())
+        );
+
+        let colorless = Game::from_json(r#"{"rows":6,"cols":6,"rules":{"colorless":true}}"#);
+        for game in [several(), colorless.unwrap()] {
+            let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+            let most = solutions
+                .iter()
+                .flat_map(|first| solutions.iter().map(move |second| differing(first, second)))
+                .max()
+                .unwrap();
+
+            let Ok(Spread::Multiple(pair)) = find_most_different(&game, 1000) else {
+                panic!("expected several solutions");
+            };
+            assert_eq!(pair.distance, most);
+            assert_eq!(differing(&pair.first, &pair.second), most);
+            assert!(pair.first < pair.second);
+            assert!(solutions.contains(&pair.first) && solutions.contains(&pair.second));
+            assert_eq!(pair.only_first, missing_from(&pair.first, &pair.second));
+            assert_eq!(pair.only_second, missing_from(&pair.second, &pair.first));
+            assert_eq!((pair.compared, pair.exact), (solutions.len(), true));
+        }
+    }
+
+    #[test]
+    fn compares_only_up_to_the_cap() {
+        let game = Game::from_json(r#"{"rows":6,"cols":6,"rules":{"colorless":true}}"#).unwrap();
+        let compared = enumerate_solutions(&game, &[], &[], 10);
+
+        let Ok(Spread::Multiple(pair)) = find_most_different(&game, 10) else {
+            panic!("expected several solutions");
+        };
+        assert_eq!((pair.compared, pair.exact), (10, false));
+        assert!(compared.contains(&pair.first) && compared.contains(&pair.second));
+    }
+
+    #[test]
+    fn tells_unique_and_unsolvable_boards_apart() {
+        assert_eq!(
+            max_diff_solutions(CORPUS[0].json.to_string(), 2),
+            Ok(r#"{"status":"unique","solution":[4,9,19,29,39,42,54,56]}"#.to_string())
+        );
+        for entry in CORPUS.iter().filter(|entry| entry.solution.is_empty()) {
+            assert_eq!(
+                max_diff_solutions(entry.json.to_string(), 2),
+                Ok(r#"{"status":"unsolvable"}"#.to_string())
+            );
+        }
+        assert_eq!(
+            max_diff_solutions(CORPUS[0].json.to_string(), 1),
+            Err("Comparing solutions takes a cap of at least 2".to_string())
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::marks::BoardState;
+use crate::options::Options;
+use crate::propagation::Step;
+use crate::search::{Search, Unit};
+use crate::Game;
+use serde::Serialize;
+
+/// How much reasoning `find_domains` applies, each level adding a rule to the one before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Level {
+    /// Only what the queens attack and the marks rule out.
+    Basic,
+    /// Queens forced by a row, column, or color with a single spot left.
+    Singles,
+    Confinement,
+    Pairs,
+    Triples,
+}
+
+impl Level {
+    const NAMES: &'static [(&'static str, Level)] = &[
+        ("basic", Level::Basic),
+        ("singles", Level::Singles),
+        ("confinement", Level::Confinement),
+        ("pairs", Level::Pairs),
+        ("triples", Level::Triples),
+    ];
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::NAMES
+            .iter()
+            .find(|&&(registered, _)| registered == name)
+            .map(|&(_, level)| level)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::NAMES.iter().map(|&(name, _)| name).collect();
+                format!(
+                    "Unknown level {:?}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    fn options(self) -> Options {
+        Options {
+            bitboard: true,
+            propagate: self >= Level::Singles,
+            confinement: self >= Level::Confinement,
+            subsets: self >= Level::Pairs,
+            ..Options::default()
+        }
+    }
+}
+
+/// What ruled a cell out.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Cause {
+    /// The user marked the cell.
+    Mark,
+    /// One of the user's queens attacks the cell.
+    Queen,
+    /// A queen forced by a single attacks the cell.
+    Singles,
+    Confinement,
+    Pairs,
+    Triples,
+}
+
+/// A cell that cannot hold a queen, and why.
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Elimination {
+    pub cell: usize,
+    pub cause: Cause,
+    /// The queen attacking the cell, for `queen` and `singles`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queen: Option<usize>,
+}
+
+/// The cells of a board that could still hold a queen, from `find_domains`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Domains {
+    /// For every cell in row-major order, whether it could still hold a queen. Cells holding one
+    /// count as possible.
+    pub possible: Vec<bool>,
+    /// Every cell that is not possible, in increasing order, with what ruled it out first.
+    pub eliminations: Vec<Elimination>,
+    /// The queens placed by singles, in the order they were found.
+    pub forced: Vec<usize>,
+    /// A row, column, or color left without any spot, so that no solution agrees with the
+    /// state. Since deductions stop there, the grid is the one they had reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emptied: Option<Unit>,
+    /// Whether the user's queens attack each other, in which case nothing is deduced from them.
+    pub conflicting: bool,
+}
+
+/// The cells of the board the rules of `level` leave open given the queens and marks of `state`,
+/// like pencil marks, with the cause of every elimination. Takes no search.
+pub(crate) fn find_domains(
+    game: &Game,
+    state: &BoardState,
+    level: Level,
+) -> Result<Domains, String> {
+    state.validate(game)?;
+
+    Ok(deduce(game, &state.queens, &state.marks, level))
+}
+
+/// The domains of the board with `queens` placed and `marks` ruled out, which must be on the
+/// board and distinct.
+pub(crate) fn deduce(game: &Game, queens: &[usize], marks: &[usize], level: Level) -> Domains {
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        deduce_with(game, queens, marks, level, BitAvailability::new(game))
+    } else {
+        deduce_with(game, queens, marks, level, Availability::new(game))
+    }
+}
+
+fn deduce_with<A: CellAvailability>(
+    game: &Game,
+    queens: &[usize],
+    marks: &[usize],
+    level: Level,
+    availability: A,
+) -> Domains {
+    let options = level.options();
+    let mut search = Search::new(game, &options, availability);
+    if level == Level::Pairs {
+        search.subset_limit = 2;
+    }
+
+    let cells = game.rows * game.cols;
+    let mut causes: Vec<Option<Elimination>> = vec![None; cells];
+    let mut conflicting = false;
+
+    for &queen in queens {
+        conflicting |= !search.availability.is_available(queen);
+        let open: Vec<_> = (0..cells)
+            .map(|idx| search.availability.is_available(idx))
+            .collect();
+
+        search.push(queen);
+        search.set_queen(queen, true);
+        for idx in (0..cells).filter(|&idx| open[idx] && idx != queen) {
+            if !search.availability.is_available(idx) {
+                causes[idx].get_or_insert(Elimination {
+                    cell: idx,
+                    cause: Cause::Queen,
+                    queen: Some(queen),
+                });
+            }
+        }
+    }
+
+    for &mark in marks {
+        if search.availability.is_available(mark) {
+            search.availability.exclude(game, mark);
+            causes[mark] = Some(Elimination {
+                cell: mark,
+                cause: Cause::Mark,
+                queen: None,
+            });
+        }
+    }
+
+    let mut forced = vec![];
+    let mut emptied = None;
+    if !conflicting {
+        let mut log = |step: Step, ruled_out: &[usize]| {
+            let (cause, queen) = match step {
+                Step::Forced { idx, .. } => {
+                    forced.push(idx);
+                    (Cause::Singles, Some(idx))
+                }
+                Step::Confined { .. } => (Cause::Confinement, None),
+                Step::Subset { colors, .. } if colors.count_ones() == 2 => (Cause::Pairs, None),
+                Step::Subset { .. } => (Cause::Triples, None),
+                Step::Wipeout { .. } => unreachable!("arc consistency is off"),
+            };
+
+            for &cell in ruled_out {
+                causes[cell].get_or_insert(Elimination { cell, cause, queen });
+            }
+        };
+
+        emptied = if game.has_color_rule() {
+            search.propagate_logged::<true>(&mut log);
+            search.tightest_unit::<true>()
+        } else {
+            search.propagate_logged::<false>(&mut log);
+            search.tightest_unit::<false>()
+        }
+        .filter(|&(spots, _)| spots == 0)
+        .map(|(_, unit)| unit);
+    }
+
+    let possible: Vec<_> = (0..cells)
+        .map(|idx| search.availability.is_available(idx) || search.solution.contains(&idx))
+        .collect();
+
+    Domains {
+        eliminations: causes
+            .into_iter()
+            .zip(&possible)
+            .filter(|&(_, &possible)| !possible)
+            .filter_map(|(cause, _)| cause)
+            .collect(),
+        possible,
+        forced,
+        emptied,
+        conflicting,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::domains;
+
+    /// Color 0 is the single cell 1, and the only solution is [1, 7, 8, 14].
+    const SINGLES: &str = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+        1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#;
+
+    fn grid(state_json: &str, level: &str) -> String {
+        domains(
+            SINGLES.to_string(),
+            state_json.to_string(),
+            level.to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn names_the_cause_of_every_elimination() {
+        // Cell 3 is attacked by the queen before the mark gets to it.
+        assert_eq!(
+            grid(r#"{"queens":[7],"marks":[3]}"#, "basic"),
+            concat!(
+                r#"{"possible":[false,true,false,false,false,false,false,true,"#,
+                r#"true,true,false,false,true,true,true,false],"eliminations":["#,
+                r#"{"cell":0,"cause":"queen","queen":7},{"cell":2,"cause":"queen","queen":7},"#,
+                r#"{"cell":3,"cause":"queen","queen":7},{"cell":4,"cause":"queen","queen":7},"#,
+                r#"{"cell":5,"cause":"queen","queen":7},{"cell":6,"cause":"queen","queen":7},"#,
+                r#"{"cell":10,"cause":"queen","queen":7},{"cell":11,"cause":"queen","queen":7},"#,
+                r#"{"cell":15,"cause":"queen","queen":7}],"forced":[],"conflicting":false}"#,
+            )
+        );
+        assert_eq!(
+            grid(r#"{"queens":[],"marks":[]}"#, "singles"),
+            concat!(
+                r#"{"possible":[false,true,false,false,false,false,false,true,"#,
+                r#"true,false,false,false,false,false,true,false],"eliminations":["#,
+                r#"{"cell":0,"cause":"singles","queen":1},{"cell":2,"cause":"singles","queen":1},"#,
+                r#"{"cell":3,"cause":"singles","queen":1},{"cell":4,"cause":"singles","queen":1},"#,
+                r#"{"cell":5,"cause":"singles","queen":1},{"cell":6,"cause":"singles","queen":1},"#,
+                r#"{"cell":9,"cause":"singles","queen":1},"#,
+                r#"{"cell":10,"cause":"singles","queen":7},"#,
+                r#"{"cell":11,"cause":"singles","queen":7},"#,
+                r#"{"cell":12,"cause":"singles","queen":8},"#,
+                r#"{"cell":13,"cause":"singles","queen":1},"#,
+                r#"{"cell":15,"cause":"singles","queen":7}],"#,
+                r#""forced":[1,7,8,14],"conflicting":false}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn stops_at_emptied_units_and_conflicting_queens() {
+        assert_eq!(
+            grid(r#"{"queens":[],"marks":[1]}"#, "singles"),
+            concat!(
+                r#"{"possible":[true,false,true,true,true,true,true,true,"#,
+                r#"true,true,true,true,true,true,true,true],"#,
+                r#""eliminations":[{"cell":1,"cause":"mark"}],"forced":[],"#,
+                r#""emptied":{"color":0},"conflicting":false}"#,
+            )
+        );
+
+        // Queens 0 and 5 touch, so singles are not tried on what they leave.
+        let domains = deduce(
+            &Game::from_json(SINGLES).unwrap(),
+            &[0, 5],
+            &[],
+            Level::Triples,
+        );
+        assert!(domains.conflicting);
+        assert!(domains.forced.is_empty());
+        assert_eq!(domains.emptied, None);
+        assert!(domains.possible[0] && domains.possible[5]);
+    }
+
+    #[test]
+    fn keeps_the_solution_open_at_every_level() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            for queens in [&[][..], &entry.solution[..2]] {
+                let mut wider: Option<Vec<bool>> = None;
+                for &(name, level) in Level::NAMES {
+                    let domains = deduce(&game, queens, &[], level);
+                    let message = format!("{} at {} from {:?}", entry.name, name, queens);
+
+                    assert!(!domains.conflicting, "{}", message);
+                    assert_eq!(domains.emptied, None, "{}", message);
+                    assert!(
+                        entry.solution.iter().all(|&idx| domains.possible[idx]),
+                        "{}",
+                        message
+                    );
+                    assert!(
+                        domains
+                            .forced
+                            .iter()
+                            .all(|idx| entry.solution.contains(idx)),
+                        "{}",
+                        message
+                    );
+                    let ruled_out = domains.possible.iter().filter(|&&open| !open).count();
+                    assert_eq!(domains.eliminations.len(), ruled_out, "{}", message);
+                    if let Some(wider) = &wider {
+                        assert!(
+                            (0..wider.len()).all(|idx| wider[idx] || !domains.possible[idx]),
+                            "{}",
+                            message
+                        );
+                    }
+                    wider = Some(domains.possible);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_levels_and_bad_states() {
+        let empty = r#"{"queens":[],"marks":[]}"#.to_string();
+        assert_eq!(
+            domains(SINGLES.to_string(), empty, "hard".to_string()),
+            Err(concat!(
+                r#"Unknown level "hard", expected one of "#,
+                "basic, singles, confinement, pairs, triples",
+            )
+            .to_string())
+        );
+        assert_eq!(
+            domains(
+                SINGLES.to_string(),
+                r#"{"queens":[16]}"#.to_string(),
+                "basic".to_string()
+            ),
+            Err("Cell 16 is not on the board, which has cells 0..16".to_string())
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use crate::panics::catch_panics;
+use crate::recolor::Verdict;
+use crate::regions::disconnected_colors;
+use crate::rules::Rules;
+use crate::verify::verify_solution;
+use crate::{enumerate_solutions, Game};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// What `BoardEditor::status` reports about the board being edited.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct EditorStatus<'a> {
+    #[serde(flatten)]
+    verdict: &'a Verdict,
+    /// Colors without any cells, which leave the board unsolvable.
+    empty_colors: Vec<usize>,
+    /// Colors whose cells are split into several pieces, which published boards avoid.
+    split_colors: Vec<usize>,
+}
+
+/// A board being painted cell by cell, judged after every edit.
+///
+/// Most edits leave the solutions away from the edited cells alone, since whether a set of
+/// queens solves the board only depends on the colors under them. So the editor keeps up to two
+/// solutions from the last status: if both still hold the board has several, and if they were
+/// all of them then only solutions with a queen on an edited cell need looking for. Adjacency
+/// tables are shared between searches by board size anyway.
+#[wasm_bindgen]
+pub struct BoardEditor {
+    game: Game,
+    /// The cell and its previous color for every edit, most recent last.
+    undo: Vec<(usize, usize)>,
+    /// Up to two solutions of the board when the status was last worked out.
+    witnesses: Vec<Vec<usize>>,
+    /// Whether `witnesses` were every solution of the board back then.
+    complete: bool,
+    /// The cells recolored since, without repeats.
+    changed: Vec<usize>,
+    /// The last status, while the board has not changed since.
+    status: Option<String>,
+}
+
+#[wasm_bindgen]
+impl BoardEditor {
+    /// An editor for a `rows` by `cols` board with one color per row, every cell starting out in
+    /// color 0.
+    #[wasm_bindgen(constructor)]
+    pub fn new(rows: usize, cols: usize) -> Result<BoardEditor, String> {
+        catch_panics(None, || {
+            if rows == 0 {
+                return Err("Board needs at least one row".to_string());
+            }
+
+            let game = Game {
+                rows,
+                cols,
+                colors: (0..rows).collect(),
+                idx_to_color: vec![0; rows * cols],
+                regions: None,
+                rules: Rules::default(),
+                adjacency: None,
+                cell_ids: None,
+                links: vec![],
+                strict: false,
+            };
+            game.validate()?;
+
+            Ok(Self {
+                game,
+                undo: vec![],
+                witnesses: vec![],
+                complete: false,
+                changed: vec![],
+                status: None,
+            })
+        })
+    }
+
+    /// Paints the cell at `row` and `col` with `color`, which can be undone.
+    pub fn set_color(&mut self, row: usize, col: usize, color: usize) -> Result<(), String> {
+        if row >= self.game.rows || col >= self.game.cols {
+            return Err(format!(
+                "Cell ({}, {}) is not on the {}x{} board",
+                row, col, self.game.rows, self.game.cols
+            ));
+        }
+
+        if color >= self.game.colors.len() {
+            return Err(format!(
+                "Color {} is not one of the board's colors 0..{}",
+                color,
+                self.game.colors.len()
+            ));
+        }
+
+        let idx = row * self.game.cols + col;
+        let previous = self.game.idx_to_color[idx];
+        if previous != color {
+            self.undo.push((idx, previous));
+            self.recolor(idx, color);
+        }
+
+        Ok(())
+    }
+
+    /// Takes back the last edit not yet undone, returning whether there was one.
+    pub fn undo(&mut self) -> bool {
+        let Some((idx, color)) = self.undo.pop() else {
+            return false;
+        };
+
+        self.recolor(idx, color);
+        true
+    }
+
+    /// The board as game JSON, as taken by `solve` and the other entry points.
+    pub fn get_board_json(&self) -> String {
+        self.game.to_json()
+    }
+
+    /// Judges the board as it stands: `invalid`, `unsolvable`, `unique` with the solution, or
+    /// `multiple`, along with the colors that are empty or split into pieces.
+    pub fn status(&mut self) -> Result<String, String> {
+        let board = self.game.to_json();
+        let status = catch_panics(Some(&board), || {
+            if let Some(status) = &self.status {
+                return Ok(status.clone());
+            }
+
+            let verdict = self.judge();
+            let mut empty_colors = vec![true; self.game.colors.len()];
+            for &color in &self.game.idx_to_color {
+                empty_colors[color] = false;
+            }
+
+            let status = serde_json::to_string(&EditorStatus {
+                verdict: &verdict,
+                empty_colors: (0..empty_colors.len())
+                    .filter(|&color| empty_colors[color])
+                    .collect(),
+                split_colors: disconnected_colors(&self.game),
+            })
+            .unwrap();
+
+            self.status = Some(status.clone());
+            Ok(status)
+        });
+
+        // Judging can panic with the witnesses half updated, so the next status searches the
+        // whole board again.
+        if status.is_err() {
+            self.witnesses.clear();
+            self.complete = false;
+        }
+        status
+    }
+}
+
+impl BoardEditor {
+    fn recolor(&mut self, idx: usize, color: usize) {
+        self.game.idx_to_color[idx] = color;
+        self.status = None;
+
+        if !self.changed.contains(&idx) {
+            self.changed.push(idx);
+        }
+    }
+
+    /// Works out the verdict from the solutions known at the last status, searching again only
+    /// as far as the edits since require.
+    fn judge(&mut self) -> Verdict {
+        if let Err(error) = self.game.validate() {
+            self.witnesses.clear();
+            self.complete = false;
+            return Verdict::Invalid { error };
+        }
+
+        let game = &self.game;
+        let mut solutions = std::mem::take(&mut self.witnesses);
+        solutions.retain(|solution| verify_solution(game, solution).is_empty());
+
+        // Searching through every edited cell costs about as much as searching the whole board
+        // once there are as many of them as rows.
+        if solutions.len() < 2 && self.complete && self.changed.len() < game.rows {
+            for &idx in &self.changed {
+                for solution in enumerate_solutions(game, &[idx], &[], 2) {
+                    if solutions.len() < 2 && !solutions.contains(&solution) {
+                        solutions.push(solution);
+                    }
+                }
+            }
+        } else if solutions.len() < 2 {
+            solutions = enumerate_solutions(game, &[], &[], 2);
+        }
+
+        self.changed.clear();
+        self.complete = solutions.len() < 2;
+        self.witnesses = solutions;
+
+        match self.witnesses.as_slice() {
+            [] => Verdict::Unsolvable,
+            [solution] => Verdict::Unique {
+                solution: solution.clone(),
+            },
+            _ => Verdict::Multiple,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::recolor::judge;
+    use serde_json::{json, Value};
+
+    /// The status of `editor`, checking that it matches judging the board from scratch.
+    fn status(editor: &mut BoardEditor) -> Value {
+        let status: Value = serde_json::from_str(&editor.status().unwrap()).unwrap();
+
+        let mut expected = serde_json::to_value(judge(&editor.game, false)).unwrap();
+        let empty: Vec<_> = (0..editor.game.colors.len())
+            .filter(|color| !editor.game.idx_to_color.contains(color))
+            .collect();
+        expected["emptyColors"] = json!(empty);
+        expected["splitColors"] = json!(disconnected_colors(&editor.game));
+        assert_eq!(status, expected, "{}", editor.get_board_json());
+
+        status
+    }
+
+    #[test]
+    fn starts_with_one_color_everywhere() {
+        let mut editor = BoardEditor::new(4, 4).unwrap();
+        assert_eq!(
+            editor.get_board_json(),
+            Game::from_json(
+                r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+                0,0,0,0, 0,0,0,0, 0,0,0,0, 0,0,0,0]}"#
+            )
+            .unwrap()
+            .to_json()
+        );
+        assert_eq!(
+            status(&mut editor),
+            json!({"status": "unsolvable", "emptyColors": [1, 2, 3], "splitColors": []})
+        );
+        assert!(!editor.undo());
+    }
+
+    /// Painting a corpus board cell by cell, every status agrees with judging the board afresh,
+    /// however many solutions the editor kept, and the board ends with its only solution.
+    #[test]
+    fn judges_every_edit_like_a_fresh_board() {
+        for entry in CORPUS.iter().take(2) {
+            let game = Game::from_json(entry.json).unwrap();
+            let mut editor = BoardEditor::new(game.rows, game.cols).unwrap();
+
+            for idx in 0..game.rows * game.cols {
+                let (row, col) = (idx / game.cols, idx % game.cols);
+                editor.set_color(row, col, game.idx_to_color[idx]).unwrap();
+                status(&mut editor);
+            }
+
+            assert_eq!(editor.get_board_json(), game.to_json());
+            let solution = entry.solution.to_vec();
+            assert_eq!(
+                status(&mut editor),
+                json!({"status": "unique", "solution": solution,
+                    "emptyColors": [], "splitColors": []})
+            );
+
+            // Undoing every edit brings back the blank board.
+            let mut undone = 0;
+            while editor.undo() {
+                undone += 1;
+                if undone % 7 == 0 {
+                    status(&mut editor);
+                }
+            }
+            assert_eq!(
+                editor.get_board_json(),
+                BoardEditor::new(game.rows, game.cols)
+                    .unwrap()
+                    .get_board_json()
+            );
+        }
+    }
+
+    /// Edits near the solution and away from it, tried and taken back, keep the status right.
+    #[test]
+    fn keeps_up_with_edits_and_undos() {
+        let entry = &CORPUS[0];
+        let game = Game::from_json(entry.json).unwrap();
+        let mut editor = BoardEditor::new(game.rows, game.cols).unwrap();
+        for idx in 0..game.rows * game.cols {
+            editor
+                .set_color(idx / game.cols, idx % game.cols, game.idx_to_color[idx])
+                .unwrap();
+        }
+        status(&mut editor);
+
+        for idx in (0..game.rows * game.cols).step_by(3) {
+            let color = (game.idx_to_color[idx] + 1) % game.rows;
+            editor
+                .set_color(idx / game.cols, idx % game.cols, color)
+                .unwrap();
+            status(&mut editor);
+            if idx % 2 == 0 {
+                assert!(editor.undo());
+                status(&mut editor);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_edits_off_the_board() {
+        assert_eq!(
+            BoardEditor::new(0, 4).err().unwrap(),
+            "Board needs at least one row"
+        );
+        assert!(BoardEditor::new(5, 4).is_err());
+
+        let mut editor = BoardEditor::new(4, 5).unwrap();
+        assert_eq!(
+            editor.set_color(4, 0, 1).unwrap_err(),
+            "Cell (4, 0) is not on the 4x5 board"
+        );
+        assert_eq!(
+            editor.set_color(0, 0, 4).unwrap_err(),
+            "Color 4 is not one of the board's colors 0..4"
+        );
+        // Painting a cell its own color is not an edit.
+        editor.set_color(0, 0, 0).unwrap();
+        assert!(!editor.undo());
+    }
+}
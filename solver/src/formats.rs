@@ -0,0 +1,240 @@
+use crate::{Game, GameBuilder};
+use serde::Serialize;
+use serde_json::Value;
+
+/// A way of writing a board down that `game_from_any` reads.
+struct Format {
+    /// The name reported as `format`.
+    name: &'static str,
+    /// What the input looks like, for the error when no format recognizes it.
+    looks_like: &'static str,
+    /// Whether the input looks like it is in this format, from a glance at it.
+    sniff: fn(&str) -> bool,
+    read: fn(&str) -> Result<Game, String>,
+}
+
+/// Every format `game_from_any` knows, tried in order.
+const FORMATS: &[Format] = &[
+    Format {
+        name: "game",
+        looks_like: "a JSON object with `idxToColor`, or no colors at all, as taken by `solve`",
+        sniff: |input| {
+            input.starts_with('{')
+                && (input.contains("\"idxToColor\"")
+                    || !input.contains("\"regions\"") && !input.contains("\"colorGrid\""))
+        },
+        read: Game::from_json,
+    },
+    Format {
+        name: "regions",
+        looks_like: "a JSON object with `regions` listing the cells of every color",
+        sniff: |input| input.starts_with('{') && input.contains("\"regions\""),
+        read: Game::from_json,
+    },
+    Format {
+        name: "linkedin",
+        looks_like: "a JSON object with LinkedIn's `gridSize` and `colorGrid`",
+        sniff: |input| input.starts_with('{') && input.contains("\"colorGrid\""),
+        read: read_linkedin,
+    },
+    Format {
+        name: "grid",
+        looks_like: "lines of letters or digits, one symbol per cell and region",
+        sniff: |input| {
+            !input.is_empty()
+                && input.lines().all(|line| {
+                    line.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '\t')
+                })
+        },
+        read: read_grid,
+    },
+];
+
+/// A board read by `game_from_any`.
+#[derive(Serialize, Debug)]
+pub(crate) struct Imported {
+    /// The name of the format the input was in.
+    pub format: &'static str,
+    pub game: Game,
+}
+
+/// Reads a board written in any format of `FORMATS`, telling which it was in. Fails when no
+/// format, or more than one, recognizes the input, naming the formats tried, and when the one
+/// that does cannot read it.
+pub(crate) fn game_from_any(input: &str) -> Result<Imported, String> {
+    let input = input.trim();
+    let matching: Vec<_> = FORMATS
+        .iter()
+        .filter(|format| (format.sniff)(input))
+        .collect();
+
+    match matching.as_slice() {
+        [format] => {
+            let game =
+                (format.read)(input).map_err(|e| format!("Reading a {}: {}", format.name, e))?;
+            Ok(Imported {
+                format: format.name,
+                game: game.normalized(),
+            })
+        }
+        [] => Err(format!(
+            "Input is in no known format, having tried {}",
+            tried()
+        )),
+        several => {
+            let names: Vec<_> = several.iter().map(|format| format.name).collect();
+            Err(format!(
+                "Input could be in any of the formats {}, having tried {}",
+                names.join(", "),
+                tried()
+            ))
+        }
+    }
+}
+
+/// Every format of `FORMATS` with what it looks like, for the errors of `game_from_any`.
+fn tried() -> String {
+    let tried: Vec<_> = FORMATS
+        .iter()
+        .map(|format| format!("{} ({})", format.name, format.looks_like))
+        .collect();
+
+    tried.join("; ")
+}
+
+/// Reads a board under the field names of LinkedIn's puzzle data, `gridSize` for the rows and
+/// columns and `colorGrid` for the color of every cell, row by row, by renaming them to those of
+/// game JSON. Other fields are kept, so rules and the like read as in a game.
+fn read_linkedin(input: &str) -> Result<Game, String> {
+    let mut value: Value = serde_json::from_str(input).map_err(|e| e.to_string())?;
+    let object = value.as_object_mut().ok_or("Expected a JSON object")?;
+
+    // The sniff only saw the name somewhere in the text.
+    let grid = object.remove("colorGrid").ok_or("Missing colorGrid")?;
+    let grid: Vec<Vec<usize>> =
+        serde_json::from_value(grid).map_err(|e| format!("Invalid colorGrid: {}", e))?;
+    let size = match object.remove("gridSize") {
+        Some(size) => {
+            serde_json::from_value(size).map_err(|e| format!("Invalid gridSize: {}", e))?
+        }
+        None => grid.len(),
+    };
+    if grid.len() != size || grid.iter().any(|row| row.len() != size) {
+        return Err(format!(
+            "colorGrid is not {} rows of {} colors, as gridSize says",
+            size, size
+        ));
+    }
+
+    let mut colors: Vec<_> = grid.iter().flatten().copied().collect();
+    colors.sort_unstable();
+    colors.dedup();
+    object.insert("rows".into(), size.into());
+    object.insert("cols".into(), size.into());
+    object.insert("colors".into(), colors.into());
+    object.insert("idxToColor".into(), grid.concat().into());
+
+    Game::from_json(&value.to_string())
+}
+
+/// Reads a board drawn as lines of symbols, every line a row and every symbol a cell, cells
+/// with the same symbol sharing a region. Spaces between the symbols are ignored. Regions are
+/// numbered by first appearance.
+fn read_grid(input: &str) -> Result<Game, String> {
+    let mut symbols = vec![];
+    let rows: Vec<Vec<usize>> = input
+        .lines()
+        .map(|line| {
+            line.chars()
+                .filter(|c| !c.is_whitespace())
+                .map(
+                    |symbol| match symbols.iter().position(|&seen| seen == symbol) {
+                        Some(color) => color,
+                        None => {
+                            symbols.push(symbol);
+                            symbols.len() - 1
+                        }
+                    },
+                )
+                .collect()
+        })
+        .filter(|row: &Vec<usize>| !row.is_empty())
+        .collect();
+    let grid: Vec<&[usize]> = rows.iter().map(Vec::as_slice).collect();
+
+    GameBuilder::from_grid(&grid)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: &str = "AABB\nABBC\nDDCC\nDDDC";
+
+    /// The format found for `input` and the game read, normalized.
+    fn import(input: &str) -> (&'static str, String) {
+        let imported = game_from_any(input).unwrap();
+        (imported.format, imported.game.to_json())
+    }
+
+    /// The same board in every format reads the same, each telling its format apart.
+    #[test]
+    fn tells_formats_apart() {
+        let (format, game) = import(GRID);
+        assert_eq!(format, "grid");
+
+        let linkedin = r#"{"gridSize":4,"colorGrid":[[0,0,1,1],[0,1,1,2],[3,3,2,2],[3,3,3,2]]}"#;
+        let regions =
+            r#"{"rows":4,"cols":4,"regions":[[0,1,4],[2,3,5,6],[7,10,11,15],[8,9,12,13,14]]}"#;
+        for (input, expected) in [
+            (game.as_str(), "game"),
+            (linkedin, "linkedin"),
+            (regions, "regions"),
+        ] {
+            assert_eq!(import(input), (expected, game.clone()), "{}", input);
+        }
+
+        // Without colors, only the game format reads it.
+        assert_eq!(import(r#"{"rows":4,"cols":4}"#).0, "game");
+    }
+
+    #[test]
+    fn rejects_bad_linkedin_grids() {
+        for (input, error) in [
+            (
+                r#"{"gridSize":3,"colorGrid":[[0,0],[1,1]]}"#,
+                "Reading a linkedin: colorGrid is not 3 rows of 3 colors, as gridSize says",
+            ),
+            (
+                r#"{"colorGrid":[[0,"a"],[1,1]]}"#,
+                "Reading a linkedin: Invalid colorGrid: invalid type: string \"a\", expected usize",
+            ),
+            (
+                r#"{"name":"colorGrid"}"#,
+                "Reading a linkedin: Missing colorGrid",
+            ),
+        ] {
+            assert_eq!(game_from_any(input).unwrap_err(), error);
+        }
+    }
+
+    /// Both errors name every format, not only those that matched.
+    #[test]
+    fn names_every_format_tried() {
+        let every = FORMATS.iter().map(|format| format.name);
+
+        let unknown = game_from_any("[1, 2]").unwrap_err();
+        assert!(unknown.starts_with("Input is in no known format"));
+        let ambiguous = game_from_any(r#"{"idxToColor":[0],"regions":[[0]]}"#).unwrap_err();
+        assert!(ambiguous.starts_with("Input could be in any of the formats game, regions, "));
+
+        for error in [unknown, ambiguous] {
+            for name in every.clone() {
+                assert!(error.contains(&format!("{} (", name)), "{}", error);
+            }
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::board::BoardIndex;
+use crate::difficulty::{same_deduction, Technique, Usage};
+use crate::marks::BoardState;
+use crate::options::Options;
+use crate::propagation::Step;
+use crate::safe::find_safe_cells;
+use crate::search::Search;
+use crate::strategy::Status;
+use crate::{run_solver, Game};
+use serde::Serialize;
+
+/// The most givens `minimal_hints` tries every combination of. Past that it settles for a
+/// greedy choice.
+const MAX_EXACT_GIVENS: usize = 3;
+
+/// Queens to place before a solver only allowed some techniques can finish the board.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Hints {
+    /// The cells of the givens, all from one solution, in increasing order.
+    pub givens: Vec<usize>,
+    /// Whether no fewer givens would do, which is only known for up to `MAX_EXACT_GIVENS`.
+    pub minimal: bool,
+    /// The deductions that finish the board from the givens.
+    pub usage: Usage,
+}
+
+/// How far the deductions got from some givens.
+struct Trial {
+    /// Queens on the board, then cells ruled out, more being better.
+    progress: (usize, usize),
+    /// The deductions made, if they solved the board.
+    usage: Option<Usage>,
+}
+
+/// Finds a smallest set of givens from a solution of the game such that deductions with
+/// `techniques` alone finish the board. Singles are always allowed, since no other technique
+/// places queens, and triples bring pairs with them.
+///
+/// Every combination of up to `MAX_EXACT_GIVENS` givens is tried first. Beyond that the givens
+/// are added greedily, each time the one that gets the deductions furthest, and then any that
+/// turn out to be unnecessary are dropped.
+pub(crate) fn find_minimal_hints(game: &Game, techniques: &[Technique]) -> Result<Hints, String> {
+    if techniques.contains(&Technique::Guessing) {
+        return Err("Guessing is not a deduction technique".to_string());
+    }
+
+    let outcome = run_solver(game, &Options::default());
+    if outcome.status != Status::Solved {
+        return Err("Board has no solution".to_string());
+    }
+    let mut solution = outcome.solution;
+    solution.sort_unstable();
+
+    let options = Options {
+        bitboard: true,
+        propagate: true,
+        confinement: techniques.contains(&Technique::Confinement),
+        subsets: techniques.contains(&Technique::Pairs) || techniques.contains(&Technique::Triples),
+        ..Options::default()
+    };
+    let subset_limit = if techniques.contains(&Technique::Triples) {
+        3
+    } else {
+        2
+    };
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        let mut search = Search::new(game, &options, BitAvailability::new(game));
+        search.subset_limit = subset_limit;
+        Ok(hints_for(&mut search, &solution))
+    } else {
+        let mut search = Search::new(game, &options, Availability::new(game));
+        search.subset_limit = subset_limit;
+        Ok(hints_for(&mut search, &solution))
+    }
+}
+
+fn hints_for<A: CellAvailability>(search: &mut Search<A>, solution: &[usize]) -> Hints {
+    if search.game.has_color_rule() {
+        exact_hints::<A, true>(search, solution)
+            .unwrap_or_else(|| greedy_hints::<A, true>(search, solution))
+    } else {
+        exact_hints::<A, false>(search, solution)
+            .unwrap_or_else(|| greedy_hints::<A, false>(search, solution))
+    }
+}
+
+/// Tries every combination of up to `MAX_EXACT_GIVENS` cells of `solution`, fewest first.
+fn exact_hints<A: CellAvailability, const COLORS: bool>(
+    search: &mut Search<A>,
+    solution: &[usize],
+) -> Option<Hints> {
+    let mut givens = vec![];
+
+    for size in 0..=MAX_EXACT_GIVENS.min(solution.len()) {
+        if let Some(usage) = try_combinations::<A, COLORS>(search, solution, size, &mut givens) {
+            return Some(Hints {
+                givens,
+                minimal: true,
+                usage,
+            });
+        }
+    }
+
+    None
+}
+
+/// Extends `givens` by `size` more cells of `candidates` in every way, in increasing order,
+/// stopping at the first that solves the board. The solving givens are left in `givens`.
+fn try_combinations<A: CellAvailability, const COLORS: bool>(
+    search: &mut Search<A>,
+    candidates: &[usize],
+    size: usize,
+    givens: &mut Vec<usize>,
+) -> Option<Usage> {
+    if size == 0 {
+        return try_givens::<A, COLORS>(search, givens).usage;
+    }
+
+    for (pos, &idx) in candidates.iter().enumerate() {
+        givens.push(idx);
+        let usage = try_combinations::<A, COLORS>(search, &candidates[pos + 1..], size - 1, givens);
+        if usage.is_some() {
+            return usage;
+        }
+        givens.pop();
+    }
+
+    None
+}
+
+fn greedy_hints<A: CellAvailability, const COLORS: bool>(
+    search: &mut Search<A>,
+    solution: &[usize],
+) -> Hints {
+    let mut givens = vec![];
+
+    // The board is solved once every queen is given, so this ends.
+    while try_givens::<A, COLORS>(search, &givens).usage.is_none() {
+        let mut best: Option<((usize, usize), usize)> = None;
+
+        for &idx in solution {
+            if givens.contains(&idx) {
+                continue;
+            }
+
+            givens.push(idx);
+            let progress = try_givens::<A, COLORS>(search, &givens).progress;
+            givens.pop();
+
+            if best.is_none_or(|(best_progress, _)| progress > best_progress) {
+                best = Some((progress, idx));
+            }
+        }
+
+        givens.push(best.unwrap().1);
+    }
+
+    // Givens placed early may have become redundant once later ones were added.
+    let mut pos = 0;
+    while pos < givens.len() {
+        let given = givens.remove(pos);
+        if try_givens::<A, COLORS>(search, &givens).usage.is_none() {
+            givens.insert(pos, given);
+            pos += 1;
+        }
+    }
+
+    givens.sort_unstable();
+    let usage = try_givens::<A, COLORS>(search, &givens).usage.unwrap();
+
+    Hints {
+        givens,
+        minimal: false,
+        usage,
+    }
+}
+
+/// Places `givens`, makes every deduction that follows, and takes it all back.
+fn try_givens<A: CellAvailability, const COLORS: bool>(
+    search: &mut Search<A>,
+    givens: &[usize],
+) -> Trial {
+    let mut placed = Vec::with_capacity(givens.len());
+    for &idx in givens {
+        placed.push((idx, search.push(idx)));
+        search.set_queen(idx, true);
+    }
+
+    let steps = search.propagate::<COLORS>();
+
+    let game = search.game;
+    let available: usize = search.availability.row_spots().iter().sum();
+    let trial = Trial {
+        progress: (search.solution.len(), game.rows * game.cols - available),
+        usage: search.is_solved().then(|| Usage::of_trail(&search.trail)),
+    };
+
+    search.unpropagate(steps);
+    for (idx, sorted_pos) in placed.into_iter().rev() {
+        search.set_queen(idx, false);
+        search.pop(sorted_pos);
+    }
+
+    trial
+}
+
+/// A suggestion for the user's next move, from `rank_hints`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Hint {
+    /// `guessing` for a safe cell no deduction from the state reaches, which needs lookahead.
+    pub technique: Technique,
+    /// The cell to put a queen on, for singles and cells needing lookahead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queen: Option<usize>,
+    /// The cells to mark, for confinements and subsets, in increasing order.
+    pub marks: Vec<usize>,
+    /// The cells the reasoning rests on: the row, column, or color of a single, or the spots
+    /// left for the colors of a confinement or subset. Empty for cells needing lookahead.
+    pub reason: Vec<usize>,
+}
+
+/// Up to `limit` different suggestions for the next move from `state`, simplest first: the
+/// deductions that apply right away in the order of their techniques, then the safe cells that
+/// take lookahead. Deductions with the same effect are collapsed into the simplest. Empty when
+/// no solution agrees with the state.
+pub(crate) fn rank_hints(
+    game: &Game,
+    state: &BoardState,
+    limit: usize,
+) -> Result<Vec<Hint>, String> {
+    let safe = find_safe_cells(game, state)?;
+    if !safe.completable {
+        return Ok(vec![]);
+    }
+    let safe: Vec<_> = safe.cells.iter().map(|safe| safe.cell).collect();
+
+    let mut hints = if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        next_moves(game, state, &safe, BitAvailability::new(game))
+    } else {
+        next_moves(game, state, &safe, Availability::new(game))
+    };
+
+    hints.truncate(limit);
+    Ok(hints)
+}
+
+fn next_moves<A: CellAvailability>(
+    game: &Game,
+    state: &BoardState,
+    safe: &[usize],
+    availability: A,
+) -> Vec<Hint> {
+    let options = Options::for_deduction();
+    let mut search = Search::new(game, &options, availability);
+    // Some solution agrees with the state, so its queens leave each other alone.
+    search.assume(&state.queens, &state.marks);
+
+    let available: Vec<_> = (0..game.rows * game.cols)
+        .map(|idx| search.availability.is_available(idx))
+        .collect();
+
+    if game.has_color_rule() {
+        search.propagate::<true>();
+    } else {
+        search.propagate::<false>();
+    }
+
+    let mut hints: Vec<_> = search
+        .trail
+        .iter()
+        .enumerate()
+        .filter(|&(pos, &step)| pos == 0 || !same_deduction(search.trail[pos - 1], step))
+        .filter_map(|(_, &step)| applies_now(game, &search.index, &available, step))
+        .collect();
+    hints.sort_by_key(|hint| hint.technique);
+
+    let mut seen = vec![];
+    hints.retain(|hint| {
+        let effect = (hint.queen, hint.marks.clone());
+        let new = !seen.contains(&effect);
+        seen.push(effect);
+        new
+    });
+
+    hints.extend(
+        safe.iter()
+            .filter(|idx| !search.solution.contains(idx))
+            .map(|&idx| Hint {
+                technique: Technique::Guessing,
+                queen: Some(idx),
+                marks: vec![],
+                reason: vec![],
+            }),
+    );
+
+    hints
+}
+
+/// The deduction `step` as a hint, if its reasoning already holds on the board before any
+/// deductions, where `available` tells the cells still open.
+///
+/// Propagation makes each deduction on the board left by the ones before it, so a later step may
+/// rest on earlier ones. Those are left out, since the user cannot see them yet.
+fn applies_now(game: &Game, index: &BoardIndex, available: &[bool], step: Step) -> Option<Hint> {
+    let open = |cells: &[usize]| -> Vec<usize> {
+        cells
+            .iter()
+            .copied()
+            .filter(|&idx| available[idx])
+            .collect()
+    };
+
+    let (technique, spots, marks) = match step {
+        Step::Forced { idx, unit } => {
+            let cells = unit.cells(index);
+            if open(cells) != [idx] {
+                return None;
+            }
+
+            return Some(Hint {
+                technique: Technique::Singles,
+                queen: Some(idx),
+                marks: vec![],
+                reason: cells.to_vec(),
+            });
+        }
+        Step::Confined { color, line, .. } => {
+            let spots = open(&index.cells_by_color[color]);
+            if !spots.iter().all(|&idx| line.contains(game, idx)) {
+                return None;
+            }
+
+            let marks = open(line.cells(index))
+                .into_iter()
+                .filter(|&idx| game.idx_to_color[idx] != color)
+                .collect();
+            (Technique::Confinement, spots, marks)
+        }
+        Step::Subset { colors, lines, .. } => {
+            let in_set = |idx: usize| (colors >> game.idx_to_color[idx]) & 1 == 1;
+            let spots: Vec<_> = (0..game.rows * game.cols)
+                .filter(|&idx| available[idx] && in_set(idx))
+                .collect();
+            if !spots.iter().all(|&idx| lines.contains(game, idx)) {
+                return None;
+            }
+
+            let mut marks: Vec<_> = lines
+                .cells(index)
+                .filter(|&idx| available[idx] && !in_set(idx))
+                .collect();
+            marks.sort_unstable();
+
+            let technique = if colors.count_ones() == 2 {
+                Technique::Pairs
+            } else {
+                Technique::Triples
+            };
+            (technique, spots, marks)
+        }
+        Step::Wipeout { .. } => unreachable!("arc consistency is off"),
+    };
+
+    if spots.is_empty() || marks.is_empty() {
+        return None;
+    }
+
+    Some(Hint {
+        technique,
+        queen: None,
+        marks,
+        reason: spots,
+    })
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::domains::{deduce, Level};
+    use crate::{enumerate_solutions, hints, minimal_hints};
+    use serde_json::{json, Value};
+
+    fn game(name: &str) -> Game {
+        let entry = CORPUS.iter().find(|entry| entry.name == name).unwrap();
+        Game::from_json(entry.json).unwrap()
+    }
+
+    /// Whether deductions up to `level` finish the board from the queens `givens`.
+    fn finishes(game: &Game, givens: &[usize], level: Level) -> bool {
+        givens.len() + deduce(game, givens, &[], level).forced.len() == game.rows
+    }
+
+    #[test]
+    fn gives_nothing_away_when_deductions_finish() {
+        let hints = find_minimal_hints(&game("9x9"), &[Technique::Confinement]).unwrap();
+        assert!(hints.givens.is_empty());
+        assert!(hints.minimal);
+        assert_eq!((hints.usage.singles, hints.usage.confinements), (9, 1));
+        assert_eq!(hints.usage.hardest, Some(Technique::Confinement));
+    }
+
+    /// The givens are queens of the solution after which the deductions allowed finish the
+    /// board, and no fewer of them would do.
+    #[test]
+    fn finds_the_fewest_givens_on_corpus_boards() {
+        let cases: [(&str, &[Technique], Level, &[usize]); 6] = [
+            ("8x8", &[], Level::Singles, &[39]),
+            ("9x9", &[], Level::Singles, &[5]),
+            ("10x10", &[], Level::Singles, &[7]),
+            (
+                "11x11",
+                &[Technique::Confinement],
+                Level::Confinement,
+                &[20],
+            ),
+            ("hard-10x10", &[], Level::Singles, &[7, 23, 60]),
+            (
+                "hard-10x10",
+                &[Technique::Confinement],
+                Level::Confinement,
+                &[41],
+            ),
+        ];
+
+        for (name, techniques, level, expected) in cases {
+            let game = game(name);
+            let hints = find_minimal_hints(&game, techniques).unwrap();
+            assert_eq!(hints.givens, expected, "{} {:?}", name, techniques);
+            assert!(hints.minimal);
+            assert!(finishes(&game, &hints.givens, level), "{}", name);
+
+            // Dropping any given leaves the deductions stuck, and so does every smaller set.
+            let solution = enumerate_solutions(&game, &[], &[], 2).remove(0);
+            let mut fewer = vec![];
+            assert!(!fewer_finish(
+                &game,
+                &solution,
+                expected.len() - 1,
+                &mut fewer,
+                level
+            ));
+        }
+    }
+
+    /// Whether some `size` more queens of `solution` after those in `givens` finish the board.
+    fn fewer_finish(
+        game: &Game,
+        solution: &[usize],
+        size: usize,
+        givens: &mut Vec<usize>,
+        level: Level,
+    ) -> bool {
+        if size == 0 {
+            return finishes(game, givens, level);
+        }
+
+        (0..solution.len()).any(|pos| {
+            givens.push(solution[pos]);
+            let finished = fewer_finish(game, &solution[pos + 1..], size - 1, givens, level);
+            givens.pop();
+            finished
+        })
+    }
+
+    /// Past `MAX_EXACT_GIVENS` the givens are chosen greedily, and none of them is redundant.
+    #[test]
+    fn settles_for_greedy_givens_on_open_boards() {
+        let game = Game::from_json(r#"{"rows":8,"cols":8}"#).unwrap();
+        let hints = find_minimal_hints(&game, &[]).unwrap();
+        assert!(!hints.minimal);
+        assert!(hints.givens.len() > MAX_EXACT_GIVENS);
+        assert!(finishes(&game, &hints.givens, Level::Singles));
+        assert_eq!(hints.usage.singles, 8 - hints.givens.len());
+
+        for pos in 0..hints.givens.len() {
+            let mut fewer = hints.givens.clone();
+            fewer.remove(pos);
+            assert!(
+                !finishes(&game, &fewer, Level::Singles),
+                "without {}",
+                hints.givens[pos]
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_guessing_and_boards_without_solutions() {
+        assert_eq!(
+            find_minimal_hints(&game("9x9"), &[Technique::Guessing]).unwrap_err(),
+            "Guessing is not a deduction technique"
+        );
+        assert_eq!(
+            find_minimal_hints(&game("unsolvable-9x9"), &[]).unwrap_err(),
+            "Board has no solution"
+        );
+
+        let json = CORPUS[0].json.to_string();
+        let error = minimal_hints(json.clone(), r#"["xWing"]"#.to_string()).unwrap_err();
+        assert!(error.starts_with("Invalid techniques JSON"), "{}", error);
+        let hints: Value =
+            serde_json::from_str(&minimal_hints(json, r#"["singles"]"#.to_string()).unwrap())
+                .unwrap();
+        let usage = json!({"singles": 7, "confinements": 0, "pairs": 0, "triples": 0,
+            "hardest": "singles"});
+        assert_eq!(
+            hints,
+            json!({"givens": [39], "minimal": true, "usage": usage})
+        );
+    }
+
+    fn ranked(game: &Game, queens: &[usize], marks: &[usize], limit: usize) -> Vec<Hint> {
+        let state = BoardState {
+            queens: queens.to_vec(),
+            marks: marks.to_vec(),
+        };
+        rank_hints(game, &state, limit).unwrap()
+    }
+
+    #[test]
+    fn ranks_the_hints_of_the_8x8_board() {
+        let hints = ranked(&game("8x8"), &[], &[], 10);
+        let expected = json!([
+            {"technique": "singles", "queen": 56, "marks": [], "reason": [56]},
+            {"technique": "confinement", "marks": [7, 15, 55, 63], "reason": [23, 31, 39, 47]},
+            {"technique": "guessing", "queen": 39, "marks": [], "reason": []},
+            {"technique": "guessing", "queen": 42, "marks": [], "reason": []},
+            {"technique": "guessing", "queen": 54, "marks": [], "reason": []},
+        ]);
+        assert_eq!(serde_json::to_value(&hints).unwrap(), expected);
+
+        // Fewer hints are the first of more.
+        let fewer = ranked(&game("8x8"), &[], &[], 2);
+        assert_eq!(
+            serde_json::to_value(&fewer).unwrap(),
+            json!([expected[0], expected[1]])
+        );
+        assert!(ranked(&game("8x8"), &[], &[], 0).is_empty());
+    }
+
+    /// From states made of queens of the solution and marks off it, the hints come simplest
+    /// first, do different things, and only place queens of the solution or mark cells off it
+    /// that are still open. Deductions rest on what the board shows.
+    #[test]
+    fn ranks_hints_that_apply_to_the_state() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            let off: Vec<_> = (0..game.rows * game.cols)
+                .filter(|idx| !entry.solution.contains(idx))
+                .collect();
+
+            for given in 0..entry.solution.len() {
+                let queens = &entry.solution[..given];
+                let marks: Vec<_> = off.iter().copied().step_by(given + 2).collect();
+                let open = deduce(&game, queens, &marks, Level::Basic).possible;
+                let open = |idx: &usize| open[*idx] && !queens.contains(idx);
+                let hints = ranked(&game, queens, &marks, usize::MAX);
+
+                assert!(hints
+                    .windows(2)
+                    .all(|pair| pair[0].technique <= pair[1].technique));
+                for (pos, hint) in hints.iter().enumerate() {
+                    let same =
+                        |other: &Hint| (other.queen, &other.marks) == (hint.queen, &hint.marks);
+                    assert!(!hints[..pos].iter().any(same), "{} {:?}", entry.name, hint);
+
+                    if let Some(queen) = hint.queen {
+                        assert!(entry.solution.contains(&queen) && open(&queen));
+                    }
+                    assert!(hint
+                        .marks
+                        .iter()
+                        .all(|idx| !entry.solution.contains(idx) && open(idx)));
+                    match hint.technique {
+                        Technique::Singles => {
+                            assert_eq!(hint.reason.iter().filter(|&idx| open(idx)).count(), 1);
+                        }
+                        Technique::Guessing => assert!(hint.reason.is_empty()),
+                        _ => assert!(!hint.marks.is_empty() && hint.reason.iter().all(open)),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gives_no_hints_without_a_completion() {
+        assert!(ranked(&game("8x8"), &[0, 4], &[], 5).is_empty());
+        assert!(ranked(&game("8x8"), &[], &[56], 5).is_empty());
+        assert!(ranked(&game("unsolvable-9x9"), &[], &[], 5).is_empty());
+
+        let json = CORPUS[0].json.to_string();
+        let error = hints(json.clone(), r#"{"marks":[1,1]}"#.to_string(), 3).unwrap_err();
+        assert_eq!(
+            error,
+            "Cell 1 appears more than once among the queens and marks"
+        );
+        assert!(hints(json, "{".to_string(), 3).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::symmetry::{sorted_pair, Transform};
+use crate::topology::Topology;
+use crate::Game;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+thread_local! {
+    static CORPUS_CACHE: RefCell<Option<Rc<Corpus>>> = const { RefCell::new(None) };
+}
+
+/// A board with its colors renamed by order of first appearance, so that boards differing only
+/// in their color labels have the same form.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Form {
+    rows: usize,
+    cols: usize,
+    colorless: bool,
+    all_colors_required: bool,
+    topology: Topology,
+    /// How many colors the board lists, including any without cells.
+    colors: usize,
+    /// The renamed color of every cell, in row-major order.
+    cells: Vec<usize>,
+    /// The sorted neighbors of every cell, if the board has custom adjacency.
+    adjacency: Option<Vec<Vec<usize>>>,
+    /// The cells of every `Same` link, lowest first, in increasing order. Other links are part
+    /// of the adjacency.
+    links: Vec<[usize; 2]>,
+}
+
+impl Form {
+    /// The form of the board `transform` carries `game` onto, which must fit it.
+    fn of_image(game: &Game, transform: Transform) -> Self {
+        let cells = game.rows * game.cols;
+
+        let mut colors = vec![0; cells];
+        for idx in 0..cells {
+            colors[transform.apply(game, idx)] = game.idx_to_color[idx];
+        }
+
+        let mut names = vec![None; game.colors.len().max(1)];
+        let mut next = 0;
+        for color in colors.iter_mut() {
+            *color = *names[*color].get_or_insert_with(|| {
+                next += 1;
+                next - 1
+            });
+        }
+
+        let adjacency = game.adjacency.as_ref().map(|adjacency| {
+            let mut image = vec![vec![]; cells];
+            for (idx, neighbors) in adjacency.iter().enumerate() {
+                let list = &mut image[transform.apply(game, idx)];
+                list.extend(
+                    neighbors
+                        .iter()
+                        .map(|&neighbor| transform.apply(game, neighbor)),
+                );
+                list.sort_unstable();
+            }
+            image
+        });
+
+        let mut links: Vec<_> = game
+            .same_links()
+            .map(|[a, b]| sorted_pair([transform.apply(game, a), transform.apply(game, b)]))
+            .collect();
+        links.sort_unstable();
+
+        Self {
+            rows: game.rows,
+            cols: game.cols,
+            colorless: game.rules.colorless,
+            all_colors_required: game.rules.all_colors_required,
+            topology: game.rules.topology,
+            colors: game.colors.len(),
+            cells: colors,
+            adjacency,
+            links,
+        }
+    }
+
+    /// The smallest form of any image of `game` under a transform fitting it, which is the same
+    /// for every board isomorphic to it.
+    pub fn canonical(game: &Game) -> Self {
+        let game = &game.normalized();
+
+        Transform::ALL
+            .iter()
+            .filter(|transform| transform.fits(game))
+            .map(|&transform| Self::of_image(game, transform))
+            .min()
+            .unwrap()
+    }
+}
+
+/// How one board maps onto another.
+#[derive(Serialize, Debug)]
+pub(crate) struct Isomorphism {
+    /// The transform carrying the cells of the first board onto the second's.
+    pub transform: Transform,
+    /// The second board's color for each color of the first. Empty on colorless boards.
+    pub colors: Vec<usize>,
+}
+
+/// How `a` maps onto `b` by a rotation or reflection and a renaming of the colors, or `None` if
+/// the boards are not isomorphic.
+pub(crate) fn isomorphism(a: &Game, b: &Game) -> Option<Isomorphism> {
+    let (normal_a, normal_b) = (a.normalized(), b.normalized());
+    let target = Form::of_image(&normal_b, Transform::Identity);
+
+    let transform = Transform::ALL.iter().copied().find(|&transform| {
+        a.rows == b.rows
+            && a.cols == b.cols
+            && transform.fits(a)
+            && Form::of_image(&normal_a, transform) == target
+    })?;
+
+    let mut colors = vec![0; a.num_colors()];
+    if a.has_color_rule() {
+        for idx in 0..a.rows * a.cols {
+            colors[a.idx_to_color[idx]] = b.idx_to_color[transform.apply(a, idx)];
+        }
+    }
+
+    Some(Isomorphism { transform, colors })
+}
+
+/// A board of a corpus isomorphic to the board looked up.
+#[derive(Serialize, Debug)]
+pub(crate) struct Duplicate {
+    /// The board's line in the corpus, counting from 1.
+    pub line: usize,
+    /// How the corpus board maps onto the board looked up.
+    #[serde(flatten)]
+    pub isomorphism: Isomorphism,
+}
+
+/// The boards of a corpus, indexed by canonical form.
+struct Corpus {
+    /// A hash of the corpus text, to tell whether a cached corpus is the one asked about.
+    hash: u64,
+    boards: Vec<(usize, Game)>,
+    by_form: HashMap<Form, Vec<usize>>,
+}
+
+impl Corpus {
+    fn parse(jsonl: &str, hash: u64) -> Result<Self, String> {
+        let boards = Game::from_jsonl(jsonl)?;
+
+        let mut by_form: HashMap<Form, Vec<usize>> = HashMap::new();
+        for (pos, (_, game)) in boards.iter().enumerate() {
+            by_form.entry(Form::canonical(game)).or_default().push(pos);
+        }
+
+        Ok(Self {
+            hash,
+            boards,
+            by_form,
+        })
+    }
+
+    /// The corpus in `jsonl`, parsed again only if the last corpus asked about was different.
+    fn cached(jsonl: &str) -> Result<Rc<Self>, String> {
+        let mut hasher = DefaultHasher::new();
+        jsonl.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        CORPUS_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+
+            if let Some(corpus) = cache.as_ref().filter(|corpus| corpus.hash == hash) {
+                return Ok(Rc::clone(corpus));
+            }
+
+            let corpus = Rc::new(Self::parse(jsonl, hash)?);
+            *cache = Some(Rc::clone(&corpus));
+            Ok(corpus)
+        })
+    }
+}
+
+/// Every board of the JSONL corpus `jsonl` isomorphic to `game`, in corpus order. The corpus is
+/// indexed once and kept for repeated lookups.
+pub(crate) fn find_in_corpus(jsonl: &str, game: &Game) -> Result<Vec<Duplicate>, String> {
+    let corpus = Corpus::cached(jsonl)?;
+
+    let Some(matches) = corpus.by_form.get(&Form::canonical(game)) else {
+        return Ok(vec![]);
+    };
+
+    Ok(matches
+        .iter()
+        .map(|&pos| {
+            let (line, board) = &corpus.boards[pos];
+            Duplicate {
+                line: *line,
+                // Equal canonical forms guarantee an isomorphism.
+                isomorphism: isomorphism(board, game).unwrap(),
+            }
+        })
+        .collect())
+}
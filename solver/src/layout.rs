@@ -0,0 +1,320 @@
+use crate::topology::Topology;
+use crate::Game;
+use serde::{Deserialize, Serialize};
+
+/// Where a board sits on the page, from the `layout_json` of `click_plan`. Lengths are in CSS
+/// pixels.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct Layout {
+    /// The bounding box of the cells, without any border or padding around them.
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+    /// The space between neighboring cells.
+    pub gap: f64,
+    /// The size of a cell. Absent sizes are what the box leaves after the gaps, shared equally.
+    pub cell_width: Option<f64>,
+    pub cell_height: Option<f64>,
+    /// Device pixels per CSS pixel, multiplying every coordinate of the plan.
+    pub device_pixel_ratio: f64,
+    /// Whether placing a queen takes a double click, as on boards where a single click only
+    /// marks the cell.
+    pub double_click: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            left: 0.0,
+            top: 0.0,
+            width: 0.0,
+            height: 0.0,
+            gap: 0.0,
+            cell_width: None,
+            cell_height: None,
+            device_pixel_ratio: 1.0,
+            double_click: false,
+        }
+    }
+}
+
+impl Layout {
+    pub fn from_json(layout_json: &str) -> Result<Self, String> {
+        serde_json::from_str(layout_json).map_err(|e| format!("Invalid layout JSON: {}", e))
+    }
+
+    /// The width and height of a cell on `game`, checking that the cells fit the box.
+    fn cell_size(&self, game: &Game) -> Result<(f64, f64), String> {
+        let lengths = [
+            ("left", self.left),
+            ("top", self.top),
+            ("width", self.width),
+            ("height", self.height),
+            ("gap", self.gap),
+            ("devicePixelRatio", self.device_pixel_ratio),
+        ];
+        if let Some((name, _)) = lengths.iter().find(|(_, length)| !length.is_finite()) {
+            return Err(format!("Layout {} must be a finite number", name));
+        }
+
+        if self.gap < 0.0 {
+            return Err(format!(
+                "Layout gap must not be negative, found {}",
+                self.gap
+            ));
+        }
+
+        if self.device_pixel_ratio <= 0.0 {
+            return Err(format!(
+                "Layout devicePixelRatio must be positive, found {}",
+                self.device_pixel_ratio
+            ));
+        }
+
+        let width = fit(self.width, self.cell_width, self.gap, game.cols, "width")?;
+        let height = fit(self.height, self.cell_height, self.gap, game.rows, "height")?;
+
+        Ok((width, height))
+    }
+}
+
+/// The size of each of `cells` cells along a side of length `side`, `gap` apart, which is
+/// `given` when present and must then fit.
+fn fit(side: f64, given: Option<f64>, gap: f64, cells: usize, name: &str) -> Result<f64, String> {
+    let gaps = gap * cells.saturating_sub(1) as f64;
+    let size = given.unwrap_or((side - gaps) / cells as f64);
+
+    if !size.is_finite() || size <= 0.0 {
+        return Err(format!(
+            "Layout leaves cells a {} of {}, which must be positive",
+            name, size
+        ));
+    }
+
+    // Sizes measured on the page rarely add up exactly, so allow for rounding.
+    if given.is_some() && size * cells as f64 + gaps > side + 0.5 {
+        return Err(format!(
+            "{} cells of {} {} with gaps of {} exceed the box {} of {}",
+            cells, name, size, gap, name, side
+        ));
+    }
+
+    Ok(size)
+}
+
+/// A click placing one queen.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Click {
+    pub cell: usize,
+    /// The center of the cell, in device pixels.
+    pub x: f64,
+    pub y: f64,
+    pub double_click: bool,
+}
+
+/// The click placing each queen of `solution`, in order, on `game` laid out as `layout`.
+pub(crate) fn plan_clicks(
+    game: &Game,
+    solution: &[usize],
+    layout: &Layout,
+) -> Result<Vec<Click>, String> {
+    if game.rules.topology != Topology::Square {
+        return Err("Click plans are only available for boards of square cells".to_string());
+    }
+
+    let (cell_width, cell_height) = layout.cell_size(game)?;
+    let cells = game.rows * game.cols;
+
+    solution
+        .iter()
+        .map(|&cell| {
+            if cell >= cells {
+                return Err(format!(
+                    "Cell {} is not on the board, which has cells 0..{}",
+                    cell, cells
+                ));
+            }
+
+            let (row, col) = ((cell / game.cols) as f64, (cell % game.cols) as f64);
+            let x = layout.left + col * (cell_width + layout.gap) + cell_width / 2.0;
+            let y = layout.top + row * (cell_height + layout.gap) + cell_height / 2.0;
+
+            Ok(Click {
+                cell,
+                x: x * layout.device_pixel_ratio,
+                y: y * layout.device_pixel_ratio,
+                double_click: layout.double_click,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::click_plan;
+    use crate::corpus::CORPUS;
+
+    fn plan(game_json: &str, solution: &[usize], layout_json: &str) -> Result<Vec<Click>, String> {
+        let game = Game::from_json(game_json).unwrap();
+        plan_clicks(&game, solution, &Layout::from_json(layout_json).unwrap())
+    }
+
+    fn points(clicks: &[Click]) -> Vec<(usize, f64, f64)> {
+        clicks
+            .iter()
+            .map(|click| (click.cell, click.x, click.y))
+            .collect()
+    }
+
+    #[test]
+    fn clicks_the_centers_of_the_solution_in_order() {
+        let entry = &CORPUS[0];
+        let layout = r#"{"left":100,"top":50,"width":400,"height":400}"#;
+        let clicks = plan(entry.json, entry.solution, layout).unwrap();
+
+        assert_eq!(clicks.len(), entry.solution.len());
+        for (click, &cell) in clicks.iter().zip(entry.solution) {
+            assert_eq!(click.cell, cell);
+            assert_eq!(click.x, 125.0 + 50.0 * (cell % 8) as f64);
+            assert_eq!(click.y, 75.0 + 50.0 * (cell / 8) as f64);
+            assert!(!click.double_click);
+        }
+    }
+
+    /// The first and last cells of a line are where padding or a missing gap shows: their centers
+    /// must sit half a cell in from either side of the box.
+    #[test]
+    fn centers_the_edge_cells() {
+        let game = r#"{"rows":4,"cols":4}"#;
+        let layout = r#"{"left":10,"top":20,"width":230,"height":230,"gap":10}"#;
+        let clicks = plan(game, &[0, 3, 12, 15], layout).unwrap();
+
+        assert_eq!(
+            points(&clicks),
+            [
+                (0, 35.0, 45.0),
+                (3, 215.0, 45.0),
+                (12, 35.0, 225.0),
+                (15, 215.0, 225.0)
+            ]
+        );
+    }
+
+    /// Boxes measured on the page have fractional sizes, which must not be rounded per cell, or
+    /// the error builds up across the row.
+    #[test]
+    fn keeps_fractional_cell_sizes() {
+        let game = r#"{"rows":8,"cols":8}"#;
+        // Cells of 40.375 by 30.25, with gaps of 1.5.
+        let layout = r#"{"left":10.25,"top":20.5,"width":333.5,"height":252.5,"gap":1.5}"#;
+        let clicks = plan(game, &[0, 7, 56, 63], layout).unwrap();
+
+        assert_eq!(
+            points(&clicks),
+            [
+                (0, 30.4375, 35.625),
+                (7, 323.5625, 35.625),
+                (56, 30.4375, 257.875),
+                (63, 323.5625, 257.875)
+            ]
+        );
+        // The last cell ends on the edge of the box.
+        assert_eq!(clicks[3].x + 40.375 / 2.0, 10.25 + 333.5);
+    }
+
+    /// Sizes given outright win over the box, which may be off by the rounding of the page.
+    #[test]
+    fn takes_given_cell_sizes() {
+        let game = r#"{"rows":2,"cols":3}"#;
+        let layout = r#"{"width":100.3,"height":60,"cellWidth":33.5,"cellHeight":20}"#;
+        let clicks = plan(game, &[2, 3], layout).unwrap();
+        assert_eq!(points(&clicks), [(2, 83.75, 10.0), (3, 16.75, 30.0)]);
+    }
+
+    #[test]
+    fn scales_by_the_device_pixel_ratio() {
+        let game = r#"{"rows":4,"cols":4}"#;
+        let layout = r#"{"left":10,"top":20,"width":230,"height":230,"gap":10,
+            "devicePixelRatio":1.5,"doubleClick":true}"#;
+        let clicks = plan(game, &[0, 15], layout).unwrap();
+
+        // The offset of the box scales along with the cells.
+        assert_eq!(points(&clicks), [(0, 52.5, 67.5), (15, 322.5, 337.5)]);
+        assert!(clicks.iter().all(|click| click.double_click));
+    }
+
+    #[test]
+    fn reports_clicks_in_camel_case() {
+        let plan = click_plan(
+            r#"{"rows":4,"cols":4}"#.to_string(),
+            "[5]".to_string(),
+            r#"{"width":40,"height":40,"doubleClick":true}"#.to_string(),
+        )
+        .unwrap();
+        assert_eq!(plan, r#"[{"cell":5,"x":15.0,"y":15.0,"doubleClick":true}]"#);
+    }
+
+    #[test]
+    fn rejects_layouts_that_do_not_fit() {
+        let game = r#"{"rows":4,"cols":4}"#;
+        let cases = [
+            (
+                r#"{"width":0,"height":40}"#,
+                "Layout leaves cells a width of 0",
+            ),
+            (
+                r#"{"width":40,"height":40,"gap":20}"#,
+                "Layout leaves cells a width of -5",
+            ),
+            (
+                r#"{"width":40,"height":40,"gap":-1}"#,
+                "Layout gap must not be negative",
+            ),
+            (
+                r#"{"width":40,"height":40,"devicePixelRatio":0}"#,
+                "Layout devicePixelRatio must be positive",
+            ),
+            (
+                r#"{"width":40,"height":40,"cellWidth":11}"#,
+                "4 cells of width 11 with gaps of 0 exceed the box width of 40",
+            ),
+        ];
+        for (layout, error) in cases {
+            let result = plan(game, &[0], layout);
+            assert!(result.unwrap_err().starts_with(error), "{}", layout);
+        }
+
+        assert!(Layout::from_json(r#"{"width":40,"padding":2}"#)
+            .unwrap_err()
+            .starts_with("Invalid layout JSON: unknown field `padding`"));
+    }
+
+    #[test]
+    fn rejects_cells_off_the_board_and_hex_boards() {
+        let layout = r#"{"width":40,"height":40}"#;
+        assert_eq!(
+            plan(r#"{"rows":4,"cols":4}"#, &[3, 16], layout).unwrap_err(),
+            "Cell 16 is not on the board, which has cells 0..16"
+        );
+        assert_eq!(
+            plan(
+                r#"{"rows":4,"cols":4,"rules":{"topology":"hex"}}"#,
+                &[0],
+                layout
+            )
+            .unwrap_err(),
+            "Click plans are only available for boards of square cells"
+        );
+        assert!(click_plan(
+            r#"{"rows":4,"cols":4}"#.to_string(),
+            "[1,".to_string(),
+            layout.to_string()
+        )
+        .unwrap_err()
+        .starts_with("Invalid solution JSON"));
+    }
+}
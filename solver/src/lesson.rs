@@ -0,0 +1,358 @@
+use crate::difficulty::{assess_difficulty, Difficulty, Technique};
+use crate::domains::{deduce, Level};
+use crate::isomorphism::Form;
+use crate::regions::{disconnected_colors, edge_neighbors};
+use crate::rules::Rules;
+use crate::utils::Rng;
+use crate::{enumerate_solutions, Game, MAX_UNITS};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// How many boards `generate_lesson` grows for every board asked for before settling for fewer.
+const ATTEMPTS_PER_BOARD: usize = 200;
+
+/// A board of a lesson, with the proof that it takes the technique taught.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LessonBoard {
+    pub game: Game,
+    /// The cells of its only solution, sorted.
+    pub solution: Vec<usize>,
+    /// The deductions solving it, the hardest being the technique taught.
+    pub difficulty: Difficulty,
+}
+
+/// Boards for practicing one technique, from `generate_lesson`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Lesson {
+    pub technique: Technique,
+    pub boards: Vec<LessonBoard>,
+    /// How many boards were grown to find them.
+    pub attempts: usize,
+}
+
+/// Up to `count` square boards of `rows` rows that take `technique` and nothing harder,
+/// reproducible from `seed`.
+///
+/// Each board is grown from queens placed at random, every color spreading from one queen, and
+/// recolored until they are its only solution. It is kept if `teaches` accepts it and it is not
+/// isomorphic to one kept before. Fewer come back when `ATTEMPTS_PER_BOARD` boards for every one
+/// asked for run out first.
+pub(crate) fn generate_lesson(
+    rows: usize,
+    technique: Technique,
+    count: usize,
+    seed: u64,
+) -> Result<Lesson, String> {
+    if !(4..=MAX_UNITS).contains(&rows) {
+        return Err(format!(
+            "Lessons have boards of 4 to {} rows, not {}",
+            MAX_UNITS, rows
+        ));
+    }
+
+    let mut rng = Rng::new(seed);
+    let mut seen = HashSet::new();
+    let mut boards = vec![];
+    let mut attempts = 0;
+
+    while boards.len() < count && attempts < count.saturating_mul(ATTEMPTS_PER_BOARD) {
+        attempts += 1;
+
+        let Some((game, solution)) = grow_board(rows, &mut rng) else {
+            continue;
+        };
+        let Some(difficulty) = teaches(&game, technique) else {
+            continue;
+        };
+        if !seen.insert(Form::canonical(&game)) {
+            continue;
+        }
+
+        boards.push(LessonBoard {
+            game,
+            solution,
+            difficulty,
+        });
+    }
+
+    Ok(Lesson {
+        technique,
+        boards,
+        attempts,
+    })
+}
+
+/// The difficulty of `game`, which must have exactly one solution, if it takes `technique` and
+/// nothing harder: its deductions, easiest first, reach for `technique` and nothing above it,
+/// and the techniques below `technique` alone do not solve it.
+///
+/// The second half is checked by deducing again with only the weaker techniques. Reaching for
+/// `technique` already says they had stalled at that point, but not that they stall for good,
+/// and a board they solve teaches nothing new.
+pub(crate) fn teaches(game: &Game, technique: Technique) -> Option<Difficulty> {
+    let difficulty = assess_difficulty(game).ok()?;
+    if difficulty.usage.hardest != Some(technique) {
+        return None;
+    }
+
+    let weaker = match technique {
+        Technique::Singles => None,
+        Technique::Confinement => Some(Level::Singles),
+        Technique::Pairs => Some(Level::Confinement),
+        Technique::Triples => Some(Level::Pairs),
+        Technique::Guessing => Some(Level::Triples),
+    };
+    // Every queen of a board deduced from scratch is forced by a single, so the board is solved
+    // once there are as many as rows.
+    if weaker.is_some_and(|level| deduce(game, &[], &[], level).forced.len() == game.rows) {
+        return None;
+    }
+
+    Some(difficulty)
+}
+
+/// A board of side `side` with every color in one piece and exactly one solution, which is
+/// returned with it, or `None` if recoloring got stuck.
+fn grow_board(side: usize, rng: &mut Rng) -> Option<(Game, Vec<usize>)> {
+    let queens = plant_queens(side, rng);
+
+    let mut idx_to_color = vec![usize::MAX; side * side];
+    for (color, &queen) in queens.iter().enumerate() {
+        idx_to_color[queen] = color;
+    }
+    let mut game = Game {
+        rows: side,
+        cols: side,
+        colors: (0..side).collect(),
+        idx_to_color,
+        regions: None,
+        rules: Rules::default(),
+        adjacency: None,
+        cell_ids: None,
+        links: vec![],
+        strict: false,
+    };
+
+    // The cells left join a neighboring color one at a time, so every color grows from its
+    // queen in one piece.
+    loop {
+        let frontier: Vec<_> = (0..side * side)
+            .filter(|&idx| game.idx_to_color[idx] == usize::MAX)
+            .flat_map(|idx| {
+                let game = &game;
+                edge_neighbors(game, idx)
+                    .into_iter()
+                    .filter(move |&neighbor| game.idx_to_color[neighbor] != usize::MAX)
+                    .map(move |neighbor| (idx, game.idx_to_color[neighbor]))
+            })
+            .collect();
+        if frontier.is_empty() {
+            break;
+        }
+        let (idx, color) = frontier[rng.below(frontier.len())];
+        game.idx_to_color[idx] = color;
+    }
+
+    // Any other solution has a queen on a cell outside `queens`. Moving that cell to a
+    // neighboring color leaves its old color without a queen in that solution, which rules it
+    // out and keeps `queens`.
+    for _ in 0..side * side {
+        let Some(other) = enumerate_solutions(&game, &[], &[], 2)
+            .into_iter()
+            .find(|solution| *solution != queens)
+        else {
+            return Some((game, queens));
+        };
+
+        let mut moves: Vec<_> = other
+            .iter()
+            .filter(|idx| !queens.contains(idx))
+            .flat_map(|&idx| {
+                let game = &game;
+                edge_neighbors(game, idx)
+                    .into_iter()
+                    .map(move |neighbor| game.idx_to_color[neighbor])
+                    .filter(move |&color| color != game.idx_to_color[idx])
+                    .map(move |color| (idx, color))
+            })
+            .collect();
+
+        loop {
+            if moves.is_empty() {
+                return None;
+            }
+            let (idx, color) = moves.swap_remove(rng.below(moves.len()));
+            let old = std::mem::replace(&mut game.idx_to_color[idx], color);
+            if disconnected_colors(&game).is_empty() {
+                break;
+            }
+            game.idx_to_color[idx] = old;
+        }
+    }
+
+    None
+}
+
+/// One queen per row and column, none touching another, at random, in increasing order of cell.
+fn plant_queens(side: usize, rng: &mut Rng) -> Vec<usize> {
+    let mut cols = vec![];
+    assert!(
+        place_row(side, &mut cols, rng),
+        "queens fit on 4 rows or more"
+    );
+
+    cols.iter()
+        .enumerate()
+        .map(|(row, &col)| row * side + col)
+        .collect()
+}
+
+/// Places queens on the rows after those of `cols` in columns tried at random, backtracking when
+/// a row has no room. Returns whether every row got one.
+fn place_row(side: usize, cols: &mut Vec<usize>, rng: &mut Rng) -> bool {
+    if cols.len() == side {
+        return true;
+    }
+
+    let mut order: Vec<_> = (0..side).collect();
+    for pos in (1..order.len()).rev() {
+        order.swap(pos, rng.below(pos + 1));
+    }
+
+    for col in order {
+        let touching = cols
+            .last()
+            .is_some_and(|&last: &usize| last.abs_diff(col) <= 1);
+        if touching || cols.contains(&col) {
+            continue;
+        }
+
+        cols.push(col);
+        if place_row(side, cols, rng) {
+            return true;
+        }
+        cols.pop();
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+
+    const TECHNIQUES: [(Technique, Option<Level>); 5] = [
+        (Technique::Singles, Some(Level::Singles)),
+        (Technique::Confinement, Some(Level::Confinement)),
+        (Technique::Pairs, Some(Level::Pairs)),
+        (Technique::Triples, Some(Level::Triples)),
+        (Technique::Guessing, None),
+    ];
+
+    fn solves(game: &Game, level: Level) -> bool {
+        deduce(game, &[], &[], level).forced.len() == game.rows
+    }
+
+    /// A board teaches only the hardest technique its deductions take.
+    #[test]
+    fn teaches_the_hardest_technique_of_corpus_boards() {
+        for (name, taught) in [
+            ("8x8", Technique::Guessing),
+            ("9x9", Technique::Confinement),
+            ("11x11", Technique::Pairs),
+            ("hard-10x10", Technique::Guessing),
+        ] {
+            let entry = CORPUS.iter().find(|entry| entry.name == name).unwrap();
+            let game = Game::from_json(entry.json).unwrap();
+            for (technique, _) in TECHNIQUES {
+                let difficulty = teaches(&game, technique);
+                assert_eq!(
+                    difficulty.is_some(),
+                    technique == taught,
+                    "{} {:?}",
+                    name,
+                    technique
+                );
+            }
+        }
+
+        let unsolvable = CORPUS
+            .iter()
+            .find(|entry| entry.name == "unsolvable-9x9")
+            .unwrap();
+        let game = Game::from_json(unsolvable.json).unwrap();
+        assert!(TECHNIQUES
+            .iter()
+            .all(|&(technique, _)| teaches(&game, technique).is_none()));
+    }
+
+    /// Every board has one solution, which the deductions up to the technique reach, except for
+    /// guessing, and which those below it alone do not.
+    #[test]
+    fn generates_boards_needing_exactly_the_technique() {
+        for (pos, &(technique, level)) in TECHNIQUES.iter().enumerate() {
+            let lesson = generate_lesson(6, technique, 2, 7).unwrap();
+            assert_eq!(lesson.technique, technique);
+            assert_eq!(lesson.boards.len(), 2, "{:?}", technique);
+            assert!(lesson.attempts <= 2 * ATTEMPTS_PER_BOARD);
+
+            for board in &lesson.boards {
+                let game = &board.game;
+                assert_eq!(game.rows, 6);
+                let solutions = enumerate_solutions(game, &[], &[], u64::MAX);
+                assert_eq!(solutions, std::slice::from_ref(&board.solution));
+                assert!(disconnected_colors(game).is_empty());
+
+                assert_eq!(board.difficulty.usage.hardest, Some(technique));
+                assert_eq!(board.difficulty.solved_by_deduction, level.is_some());
+                if let Some(level) = level {
+                    assert!(solves(game, level), "{:?}", technique);
+                }
+                if let Some(&(_, Some(weaker))) = pos.checked_sub(1).map(|pos| &TECHNIQUES[pos]) {
+                    assert!(!solves(game, weaker), "{:?}", technique);
+                }
+            }
+
+            let forms: HashSet<_> = lesson
+                .boards
+                .iter()
+                .map(|board| Form::canonical(&board.game))
+                .collect();
+            assert_eq!(forms.len(), lesson.boards.len());
+        }
+    }
+
+    #[test]
+    fn reproduces_lessons_from_the_seed() {
+        let json = |seed| {
+            serde_json::to_string(&generate_lesson(5, Technique::Confinement, 3, seed).unwrap())
+                .unwrap()
+        };
+        assert_eq!(json(11), json(11));
+        assert_ne!(json(11), json(12));
+    }
+
+    /// Boards too small to hold a queen per row, too big for the search, or asked for by an
+    /// unknown name are refused, and asking for none grows none.
+    #[test]
+    fn rejects_lessons_it_cannot_grow() {
+        assert_eq!(
+            generate_lesson(3, Technique::Singles, 1, 0).unwrap_err(),
+            format!("Lessons have boards of 4 to {} rows, not 3", MAX_UNITS)
+        );
+        assert!(generate_lesson(MAX_UNITS + 1, Technique::Singles, 1, 0).is_err());
+
+        let lesson = generate_lesson(5, Technique::Singles, 0, 0).unwrap();
+        assert_eq!((lesson.boards.len(), lesson.attempts), (0, 0));
+
+        let error = crate::generate_lesson(5, "xWing".to_string(), 1, 0).unwrap_err();
+        assert!(
+            error.starts_with("Unknown technique \"xWing\""),
+            "{}",
+            error
+        );
+    }
+}
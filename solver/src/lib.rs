@@ -1,50 +1,553 @@
+// Declared first, so that its logging macros are in scope in every module after it.
+#[macro_use]
+mod trace;
+mod activity;
+mod availability;
+mod backjump;
+mod bench;
+mod bitboard;
+mod board;
+mod builder;
+mod bundle;
+mod conflicts;
+mod constrained;
+pub mod corpus;
+mod count;
+mod decompose;
+mod diagnose;
+mod diff;
+mod difficulty;
+mod diverse;
+mod domains;
+mod editor;
+mod formats;
+mod hints;
+mod isomorphism;
+mod layout;
+mod lesson;
+mod links;
+mod local;
+mod marks;
+mod nearest;
+mod no_nogoods;
+mod nogood_set;
+mod normalize;
+mod options;
+mod pack;
+mod panics;
+mod preview;
+mod profile;
+mod propagation;
+mod quiz;
+mod rebalance;
+mod recolor;
+mod regions;
+mod replay;
+mod resize;
+mod rules;
+mod safe;
+mod search;
+mod self_test;
+mod session;
+mod solutions;
+mod stats;
+mod strategy;
+mod symmetry;
+mod technique;
+#[cfg(feature = "tiny-json")]
+mod tiny_json;
+mod topology;
+mod trie;
 mod utils;
+mod variants;
+mod verify;
+mod walkthrough;
+mod worker;
 
-use serde::Deserialize;
-use serde_json;
-use std::collections::HashMap;
-use utils::set_panic_hook;
+use activity::record_solve;
+use availability::{Availability, CellAvailability};
+use bench::{run_benchmark, time_candidates, tune_weights, Config};
+use bitboard::BitAvailability;
+pub use builder::{GameBuilder, GameError};
+use bundle::bundle_solve;
+use conflicts::find_conflicts;
+use constrained::solve_with_constraints;
+use count::{branch_census as census_of, count_by_rows, estimate_count};
+use decompose::{count_parts, split_board};
+use diagnose::diagnose;
+use diff::diff_boards as board_diff;
+use difficulty::{assess_difficulty, Technique};
+use diverse::find_most_different;
+use domains::{find_domains, Level};
+pub use editor::BoardEditor;
+use formats::game_from_any as import_game;
+use hints::{find_minimal_hints, rank_hints};
+use isomorphism::{find_in_corpus, isomorphism};
+use layout::{plan_clicks, Layout};
+use lesson::generate_lesson as lesson_for;
+use links::Link;
+use marks::{judge_marks, BoardState};
+use nearest::find_nearest_solution;
+use options::{Options, SolutionOrder};
+use pack::write_pack;
+pub use pack::PuzzlePack;
+use panics::catch_panics;
+use preview::preview_queen;
+use quiz::make_quiz as quiz_of;
+use rebalance::rebalance_regions;
+use recolor::{judge_recolors, Recolor};
+use regions::{analyze_regions, colors_of_regions, split_regions, RegionCell, SplitRegion};
+use replay::{grade_replay as replay_grades, Event};
+use resize::{resize_board, Corner};
+pub use rules::Rules;
+use safe::find_safe_cells;
+use search::{RunEnd, Search, Unit};
+use self_test::run_self_tests as self_tests;
+use serde::{Deserialize, Serialize};
+pub use session::SolveSession;
+pub use solutions::SolutionIterator;
+use stats::SolveStats;
+use std::cell::RefCell;
+use std::rc::Rc;
+use strategy::{find_strategy, Outcome, Status};
+use symmetry::{
+    board_symmetries, is_canonical, set_symmetries, solution_symmetries, Pin, Transform,
+};
+use technique::find_technique as technique_in;
+pub use topology::Topology;
+use trie::{NodeId, Trie};
+use utils::{now_ms, warn};
+use variants::derive_variants as variants_of;
+use verify::{verify_solution, Violation};
+use walkthrough::{deduction_order, walk_through, Placement};
 use wasm_bindgen::prelude::*;
+/// Starts the pool of `num_threads` web workers that counting and enumeration are split across,
+/// as `initThreadPool` in JS. The promise it returns must settle before anything is solved.
+#[cfg(all(feature = "wasm-threads", target_arch = "wasm32"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
+use worker::{
+    decode_reply as decode_worker_reply, encode_request as encode_worker_request,
+    handle_request as handle_worker_request, Kind as WorkerKind,
+};
 
-#[derive(Deserialize, Debug)]
-struct Game {
+#[cfg(all(feature = "small-alloc", target_arch = "wasm32"))]
+#[global_allocator]
+static ALLOCATOR: utils::SmallAlloc = utils::SmallAlloc::new();
+
+/// Sends the debug logs of the `trace` feature to `console.debug`, or to stderr natively, from
+/// now on. With the package built by `wasm-pack build -- --features trace`:
+///
+/// ```js
+/// import init, { initTracing, solve } from "./pkg/solver.js";
+///
+/// await init();
+/// initTracing();
+/// // Logs lines like `backtrack resumed=false: propagate depth=3: Forced { idx: 42, .. }`.
+/// solve(gameJson);
+/// ```
+#[cfg(feature = "trace")]
+#[wasm_bindgen(js_name = initTracing)]
+pub fn init_tracing() {
+    trace::init_tracing();
+}
+
+/// The largest number of rows, columns, or colors that fit in the `UsedTracker` bitmasks.
+const MAX_UNITS: usize = 63;
+
+/// A board and the rules it is played by, read from game JSON or made with `GameBuilder`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Game {
+    /// Either side may be left out when the cell colors tell it, and both on a square board.
+    #[serde(default)]
     rows: usize,
+    /// May exceed `rows`, in which case each column holds at most one queen.
+    #[serde(default)]
     cols: usize,
+    /// The color labels present on the board. An absent or empty list implies the colorless rule.
+    #[serde(default)]
     colors: Vec<usize>,
-    #[serde(rename = "idxToColor")]
+    #[serde(rename = "idxToColor", default)]
     idx_to_color: Vec<usize>,
+    /// The cells of every color, region `i` taking color `i`, as cell indices or `[row, col]`
+    /// pairs, in place of `idx_to_color`. Only read: preparing the game turns it into
+    /// `idx_to_color`.
+    #[serde(default, skip_serializing)]
+    regions: Option<Vec<Vec<RegionCell>>>,
+    #[serde(default)]
+    rules: Rules,
+    /// Optional neighbors of every cell, replacing the neighbors of the topology.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    adjacency: Option<Vec<Vec<usize>>>,
+    /// Optional caller-chosen names of the cells, such as DOM ids. The solver ignores them and
+    /// only echoes those of the queens in `solve_with_options`.
+    #[serde(rename = "cellIds", default, skip_serializing_if = "Option::is_none")]
+    cell_ids: Option<Vec<String>>,
+    /// Optional side constraints on pairs of cells, beyond the rules of the game.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    links: Vec<Link>,
+    /// Reject a board with a color split into several pieces instead of warning about it. Only
+    /// read, since it says how to take the board rather than what the board is.
+    #[serde(default, skip_serializing)]
+    strict: bool,
+}
+
+impl Game {
+    fn from_json(game_json: &str) -> Result<Self, String> {
+        Self::parse(game_json)?.prepare()
+    }
+
+    /// Reads the game as given, without filling anything in or checking it.
+    fn parse(game_json: &str) -> Result<Self, String> {
+        #[cfg(not(feature = "tiny-json"))]
+        let game = serde_json::from_str(game_json).map_err(|e| e.to_string());
+        #[cfg(feature = "tiny-json")]
+        let game = tiny_json::read_game(game_json);
+
+        game.map_err(|e| format!("Invalid game JSON: {}", e))
+    }
+
+    /// The game as JSON, as taken by `solve` and the other entry points.
+    fn to_json(&self) -> String {
+        #[cfg(not(feature = "tiny-json"))]
+        return serde_json::to_string(self).unwrap();
+        #[cfg(feature = "tiny-json")]
+        return tiny_json::write_game(self);
+    }
+
+    /// Fills in what the JSON may leave out, checks the board, and spells out its neighbors.
+    fn prepare(mut self) -> Result<Self, String> {
+        if self.rules.colorless && !self.colors.is_empty() {
+            return Err("Colorless rules cannot be combined with a list of colors".to_string());
+        }
+
+        if let Some(regions) = &self.regions {
+            if !self.idx_to_color.is_empty() {
+                return Err("Board gives both idxToColor and regions".to_string());
+            }
+            if self.colors.is_empty() && !self.rules.colorless {
+                self.colors = (0..regions.len()).collect();
+            }
+        }
+
+        if self.colors.is_empty() {
+            self.rules.colorless = true;
+        }
+
+        // A cell in several regions is only counted once, so that the error names it rather than
+        // the size it throws off.
+        let cells = match &self.regions {
+            Some(regions) => regions
+                .iter()
+                .flatten()
+                .collect::<std::collections::HashSet<_>>()
+                .len(),
+            None => self.idx_to_color.len(),
+        };
+        match (self.rows, self.cols) {
+            (0, 0) => {
+                let side = (cells as f64).sqrt().round() as usize;
+                if cells == 0 || side * side != cells {
+                    return Err(format!(
+                        "Board gives neither rows nor cols, and its {} cells do not make a square",
+                        cells
+                    ));
+                }
+                self.rows = side;
+                self.cols = side;
+            }
+            (0, cols) => self.rows = cells / cols,
+            (rows, 0) => self.cols = cells / rows,
+            _ => {}
+        }
+
+        if let Some(regions) = self.regions.take() {
+            self.idx_to_color = colors_of_regions(self.rows, self.cols, &regions)?;
+        }
+
+        // Colorless boards may omit the color of each cell entirely.
+        if self.idx_to_color.is_empty() {
+            self.idx_to_color = vec![0; self.rows * self.cols];
+        }
+
+        self.validate()?;
+        self.check_contiguity()?;
+        for (idx, neighbor) in self.symmetrize_adjacency() {
+            warn(&format!(
+                "Adjacency is not symmetric: adding cell {} as a neighbor of cell {}",
+                neighbor, idx
+            ));
+        }
+        self.adjoin_links();
+
+        Ok(self)
+    }
+
+    /// Reads a corpus of one game per line, skipping blank lines. Each game comes with its line,
+    /// counting from 1.
+    fn from_jsonl(jsonl: &str) -> Result<Vec<(usize, Self)>, String> {
+        jsonl
+            .lines()
+            .enumerate()
+            .filter(|(_, json)| !json.trim().is_empty())
+            .map(|(line, json)| {
+                Self::from_json(json)
+                    .map(|game| (line + 1, game))
+                    .map_err(|e| format!("Corpus line {}: {}", line + 1, e))
+            })
+            .collect()
+    }
+
+    /// Warns about every color split into several pieces, or rejects the board for them when it
+    /// is `strict`.
+    fn check_contiguity(&self) -> Result<(), String> {
+        let split = split_regions(self);
+        if self.strict && !split.is_empty() {
+            let descriptions: Vec<_> = split.iter().map(SplitRegion::describe).collect();
+            return Err(format!(
+                "Board is not contiguous: {}",
+                descriptions.join("; ")
+            ));
+        }
+
+        for region in &split {
+            warn(&region.describe());
+        }
+
+        Ok(())
+    }
+
+    /// Makes a custom adjacency relation symmetric, since a queen threatens its neighbors exactly
+    /// when they threaten it. Returns every neighbor that had to be added, as the cell and the
+    /// neighbor added to its list, for `prepare` to warn about.
+    fn symmetrize_adjacency(&mut self) -> Vec<(usize, usize)> {
+        let Some(adjacency) = &mut self.adjacency else {
+            return vec![];
+        };
+
+        let mut missing = vec![];
+
+        for (idx, neighbors) in adjacency.iter().enumerate() {
+            for &neighbor in neighbors {
+                if !adjacency[neighbor].contains(&idx) {
+                    missing.push((neighbor, idx));
+                }
+            }
+        }
+
+        let mut added = vec![];
+        for (idx, neighbor) in missing {
+            if !adjacency[idx].contains(&neighbor) {
+                adjacency[idx].push(neighbor);
+                added.push((idx, neighbor));
+            }
+        }
+
+        added
+    }
+
+    /// Checks that the board is well formed before it reaches the solver.
+    fn validate(&self) -> Result<(), String> {
+        self.rules.validate()?;
+
+        if self.rows > MAX_UNITS || self.cols > MAX_UNITS {
+            return Err(format!(
+                "Board of {}x{} exceeds the maximum of {} rows and columns",
+                self.rows, self.cols, MAX_UNITS
+            ));
+        }
+
+        // Every row needs a queen, so at most one per column only works with enough columns.
+        if self.rows > self.cols {
+            return Err(format!(
+                "Board of {}x{} has more rows than columns, so not every row can hold a queen",
+                self.rows, self.cols
+            ));
+        }
+
+        if self.idx_to_color.len() != self.rows * self.cols {
+            return Err(format!(
+                "Expected {} cell colors for a {}x{} board, found {}",
+                self.rows * self.cols,
+                self.rows,
+                self.cols,
+                self.idx_to_color.len()
+            ));
+        }
+
+        if let Some(cell_ids) = &self.cell_ids {
+            if cell_ids.len() != self.rows * self.cols {
+                return Err(format!(
+                    "Expected {} cell ids for a {}x{} board, found {}",
+                    self.rows * self.cols,
+                    self.rows,
+                    self.cols,
+                    cell_ids.len()
+                ));
+            }
+        }
+
+        if let Some(link) = self.links.iter().find(|link| {
+            let [a, b] = link.cells;
+            a == b || a >= self.rows * self.cols || b >= self.rows * self.cols
+        }) {
+            return Err(format!(
+                "Link between cells {} and {} is invalid: links must join two different cells \
+                 in 0..{}",
+                link.cells[0],
+                link.cells[1],
+                self.rows * self.cols
+            ));
+        }
+
+        if let Some(adjacency) = &self.adjacency {
+            if adjacency.len() != self.rows * self.cols {
+                return Err(format!(
+                    "Expected adjacency lists for {} cells, found {}",
+                    self.rows * self.cols,
+                    adjacency.len()
+                ));
+            }
+
+            for (idx, neighbors) in adjacency.iter().enumerate() {
+                if let Some(&neighbor) = neighbors
+                    .iter()
+                    .find(|&&neighbor| neighbor >= adjacency.len() || neighbor == idx)
+                {
+                    return Err(format!(
+                        "Cell {} has invalid neighbor {}: neighbors must be other cells in 0..{}",
+                        idx,
+                        neighbor,
+                        adjacency.len()
+                    ));
+                }
+            }
+        }
+
+        if self.has_color_rule() {
+            if self.colors.len() > MAX_UNITS {
+                return Err(format!(
+                    "Board has {} colors, exceeding the maximum of {}",
+                    self.colors.len(),
+                    MAX_UNITS
+                ));
+            }
+
+            if let Some(idx) = self
+                .idx_to_color
+                .iter()
+                .position(|&color| color >= self.colors.len())
+            {
+                return Err(format!(
+                    "Cell {} has color {}, but colors must be in 0..{}",
+                    idx,
+                    self.idx_to_color[idx],
+                    self.colors.len()
+                ));
+            }
+
+            if self.rules.all_colors_required && self.colors.len() < self.rows {
+                return Err(format!(
+                    "Board has {} colors but {} rows, so not every color can hold a queen",
+                    self.colors.len(),
+                    self.rows
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn has_color_rule(&self) -> bool {
+        !self.rules.colorless
+    }
+
+    /// The number of colors constrained by the rules.
+    #[inline(always)]
+    fn num_colors(&self) -> usize {
+        if self.has_color_rule() {
+            self.colors.len()
+        } else {
+            0
+        }
+    }
+}
+
+/// How many board shapes `AdjacentsLookup::for_game` keeps tables for. The extension only ever
+/// sees a few sizes.
+const ADJACENCY_CACHE_SIZE: usize = 4;
+
+/// Adjacency tables by topology and board size, most recently used last.
+type AdjacencyCache = Vec<((Topology, usize, usize), Rc<AdjacentsLookup>)>;
+
+thread_local! {
+    static ADJACENCY_CACHE: RefCell<AdjacencyCache> = const { RefCell::new(vec![]) };
 }
 
-/// Pre-computed table of adjacent indices.
+/// Pre-computed table of adjacent indices. Tables are immutable, so boards of the same shape share
+/// one.
 struct AdjacentsLookup {
-    adjacents: Vec<Vec<usize>>,
-    counts: Vec<usize>,
+    /// The neighbors of every cell back to back, in one allocation.
+    neighbors: Vec<u16>,
+    /// Where the neighbors of each cell start in `neighbors`, followed by the end of the last.
+    offsets: Vec<u32>,
 }
 
 impl AdjacentsLookup {
-    fn new(rows: usize, cols: usize) -> Self {
-        let mut adjacents = vec![Vec::with_capacity(4); rows * cols];
-        let counts = vec![0; rows * cols];
-
-        for row in 0..rows {
-            for col in 0..cols {
-                for (dr, dc) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
-                    let new_row = (row as i32) + dr;
-                    let new_col = (col as i32) + dc;
-
-                    if new_row >= 0
-                        && new_row < (rows as i32)
-                        && new_col >= 0
-                        && new_col < (cols as i32)
-                    {
-                        adjacents[row * cols + col]
-                            .push((new_row as usize) * cols + (new_col as usize));
-                    }
-                }
+    /// Uses the game's custom adjacency when present, and the neighbors of its topology otherwise.
+    fn for_game(game: &Game) -> Rc<Self> {
+        match &game.adjacency {
+            Some(adjacency) => Rc::new(Self::from_lists(
+                adjacency.iter().map(|list| list.iter().copied()),
+            )),
+            None => Self::cached(game.rules.topology, game.rows, game.cols),
+        }
+    }
+
+    /// The adjacency of a `rows` by `cols` board of `topology`, built only if no recent solve
+    /// used the same shape.
+    fn cached(topology: Topology, rows: usize, cols: usize) -> Rc<Self> {
+        ADJACENCY_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let shape = (topology, rows, cols);
+
+            let lookup = match cache.iter().position(|&(key, _)| key == shape) {
+                Some(pos) => cache.remove(pos).1,
+                None => Rc::new(Self::new(topology, rows, cols)),
+            };
+
+            if cache.len() == ADJACENCY_CACHE_SIZE {
+                cache.remove(0);
             }
+            cache.push((shape, Rc::clone(&lookup)));
+
+            lookup
+        })
+    }
+
+    fn new(topology: Topology, rows: usize, cols: usize) -> Self {
+        Self::from_lists(
+            (0..rows * cols).map(|idx| topology.neighbors(rows, cols, idx).into_iter()),
+        )
+    }
+
+    /// Packs one list of neighbors per cell. Boards have at most `MAX_UNITS` squared cells, so
+    /// every index fits in a `u16`.
+    fn from_lists<L: Iterator<Item = usize>>(lists: impl Iterator<Item = L>) -> Self {
+        let mut neighbors = vec![];
+        let mut offsets = vec![0];
+
+        for list in lists {
+            neighbors.extend(list.map(|idx| idx as u16));
+            offsets.push(neighbors.len() as u32);
         }
 
-        Self { adjacents, counts }
+        Self { neighbors, offsets }
+    }
+
+    #[inline(always)]
+    fn neighbors(&self, idx: usize) -> &[u16] {
+        &self.neighbors[self.offsets[idx] as usize..self.offsets[idx + 1] as usize]
     }
 }
 
@@ -52,108 +555,422 @@ struct UsedTracker {
     rows: u64,
     cols: u64,
     colors: u64,
+    /// All ones when the color rule applies, zero otherwise, so that colors are never marked used.
+    color_mask: u64,
     required_rows: u64,
     required_cols: u64,
     required_colors: u64,
 }
 
 impl UsedTracker {
-    fn new(num_rows: usize, num_cols: usize, num_colors: usize) -> Self {
+    fn new(num_rows: usize, num_cols: usize, num_colors: usize, all_colors_required: bool) -> Self {
         Self {
             rows: 0,
             cols: 0,
             colors: 0,
+            color_mask: if num_colors == 0 { 0 } else { !0 },
             required_rows: (1 << num_rows) - 1,
-            required_cols: (1 << num_cols) - 1,
-            required_colors: (1 << num_colors) - 1,
+            // With more columns than rows some columns necessarily stay empty.
+            required_cols: if num_cols > num_rows {
+                0
+            } else {
+                (1 << num_cols) - 1
+            },
+            required_colors: if all_colors_required {
+                (1 << num_colors) - 1
+            } else {
+                0
+            },
         }
     }
 
-    #[inline(always)]
-    fn is_used(&self, row: usize, col: usize, color: usize) -> bool {
-        ((self.rows >> row) & 1 == 1)
-            || ((self.cols >> col) & 1 == 1)
-            || ((self.colors >> color) & 1 == 1)
-    }
-
     #[inline(always)]
     fn set(&mut self, row: usize, col: usize, color: usize, value: bool) {
         let bit: u64 = if value { 1 } else { 0 };
         self.rows = (self.rows & !(1 << row)) | (bit << row);
         self.cols = (self.cols & !(1 << col)) | (bit << col);
-        self.colors = (self.colors & !(1 << color)) | (bit << color);
+        self.colors = ((self.colors & !(1 << color)) | (bit << color)) & self.color_mask;
     }
 
     #[inline(always)]
     fn is_solved(&self) -> bool {
         (self.rows == self.required_rows)
-            && (self.cols == self.required_cols)
-            && (self.colors == self.required_colors)
+            && (self.cols & self.required_cols == self.required_cols)
+            && (self.colors & self.required_colors == self.required_colors)
     }
 }
 
-struct TrieNode {
-    pub children: HashMap<usize, TrieNode>,
-    pub is_leaf: bool,
+/// The operations the search needs from its cache of nogoods: sets of decisions, each given as
+/// sorted indices, that cannot lead to a solution.
+///
+/// A stored nogood prunes every prefix of the search containing it. `NoGoods` keeps them in a
+/// trie and `NoGoodSet` in a hash set, while `NoNogoods` keeps none, selected by
+/// `Options::nogood_store`.
+pub(crate) trait NogoodCache {
+    type Cursor<'a>: NogoodCursor
+    where
+        Self: 'a;
+
+    /// An empty cache, keeping at most `Options::max_nogoods` nogoods of at most
+    /// `Options::max_nogood_len` decisions each.
+    fn new(options: &Options) -> Self;
+
+    /// Records the nogood `sorted_solution`, returning whether it was stored.
+    fn insert(&mut self, sorted_solution: &[usize]) -> bool;
+
+    /// Finds a stored nogood that is a subset of `sorted_solution`, leaving it in `path`.
+    fn find_subset(&mut self, sorted_solution: &[usize], path: &mut Vec<usize>) -> bool;
+
+    /// A cursor answering for each candidate whether adding it to the decisions
+    /// `sorted_solution` would make a stored nogood a prefix of them.
+    fn cursor<'a>(&'a self, sorted_solution: &'a [usize]) -> Self::Cursor<'a>;
+
+    /// Counts the lookups made through a cursor, now that it is done.
+    fn count_lookups(&mut self, lookups: Lookups);
+
+    /// The nogoods of one or two decisions stored so far.
+    fn short(&self) -> &ShortNogoods;
+
+    /// Fills in the nogood counters of `stats`, along with the size of the cache.
+    fn report(&self, stats: &mut SolveStats);
+}
+
+/// The candidate checks of one node, from `NogoodCache::cursor`.
+pub(crate) trait NogoodCursor {
+    /// Whether some stored nogood is a prefix of the decisions with `idx` added.
+    fn hits(&mut self, idx: usize) -> bool;
+
+    fn counts(&self) -> Lookups;
 }
 
-impl TrieNode {
-    fn new() -> Self {
-        Self {
-            children: HashMap::new(),
-            is_leaf: false,
+/// Every nogood of one or two decisions a cache has stored, indexed by cell, so that the
+/// candidates completing one can be dropped before the search tries them.
+///
+/// Nogoods hold whatever decisions they come in, so a pair rules out its partner whichever of
+/// the two sorts first, unlike a cursor, which only finds nogoods at the start of the sorted
+/// decisions. Pairs stay listed when the cache lets them go, since they remain true.
+#[derive(Default)]
+pub(crate) struct ShortNogoods {
+    /// Cells no queen may be decided on.
+    singles: Vec<usize>,
+    /// The cells that cannot both be decided on with each cell.
+    partners: Vec<Vec<usize>>,
+}
+
+impl ShortNogoods {
+    /// Records `sorted_solution` if it has at most two decisions.
+    fn record(&mut self, sorted_solution: &[usize]) {
+        match *sorted_solution {
+            [idx] => self.singles.push(idx),
+            [a, b] => {
+                let cells = a.max(b) + 1;
+                if self.partners.len() < cells {
+                    self.partners.resize(cells, vec![]);
+                }
+                self.partners[a].push(b);
+                self.partners[b].push(a);
+            }
+            _ => {}
         }
     }
+
+    /// Sets the bit in `mask` of every cell that would complete one of the nogoods when added to
+    /// `sorted_solution`. Returns false, leaving `mask` alone, when there are none to complete.
+    pub fn mark_completions(&self, sorted_solution: &[usize], mask: &mut Vec<u64>) -> bool {
+        if self.singles.is_empty() && self.partners.is_empty() {
+            return false;
+        }
+
+        mask.clear();
+        let partners = sorted_solution
+            .iter()
+            .filter_map(|&idx| self.partners.get(idx))
+            .flatten();
+        for &idx in self.singles.iter().chain(partners) {
+            if mask.len() <= idx / 64 {
+                mask.resize(idx / 64 + 1, 0);
+            }
+            mask[idx / 64] |= 1 << (idx % 64);
+        }
+
+        true
+    }
+}
+
+/// The lookups made through a cursor.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Lookups {
+    pub lookups: usize,
+    /// Lookups that found a nogood.
+    pub hits: usize,
+    /// Lookups answered by the signature prefilter without walking the trie.
+    pub avoided: usize,
+}
+
+/// The bit standing for `idx` in the signature of a set of decisions, spread by a multiplicative
+/// hash so that nearby cells land on different bits.
+#[inline(always)]
+fn signature_bit(idx: usize) -> u64 {
+    1 << ((idx as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 58)
+}
+
+/// The signature of a set of decisions: the bits of all of them, so that a subset's signature is
+/// covered by the signature of the set.
+fn signature(sorted_solution: &[usize]) -> u64 {
+    sorted_solution
+        .iter()
+        .fold(0, |signature, &idx| signature | signature_bit(idx))
 }
 
 /// The `NoGoods` cache stores combinations of indices that _cannot_ lead to a valid solution.
 ///
 /// The cache allows efficient checking of potential solutions against known bad partial
-/// solutions using a Trie implementation.
+/// solutions using a Trie implementation, kept in an arena.
+///
+/// The cache can be capped to a number of stored nogoods. Once full, the longest nogoods are
+/// evicted first since short ones prune the most. Nogoods can also be bounded in length, leaving
+/// out the long ones that rarely prune anything.
+///
+/// With `Options::nogood_signatures`, lookups are first screened by signature. Every nogood
+/// stored under a first decision adds its bits to the decision's bucket by intersection, leaving
+/// the bits common to all of them. A lookup whose signature misses one of those bits cannot find
+/// any of them, so the walk is skipped. Buckets are not widened back when nogoods leave the
+/// trie, which only lets more lookups through.
 struct NoGoods {
-    root: TrieNode,
+    trie: Trie,
+    limit: Option<usize>,
+    max_len: Option<usize>,
+    /// The bits common to the signatures of the nogoods starting with each cell, all ones for
+    /// cells no nogood has started with. Only kept with signatures on.
+    signatures: Option<Vec<u64>>,
+    len: usize,
+    /// Copies of the stored nogoods bucketed by length, only kept when the cache is capped so
+    /// that the longest can be found for eviction.
+    by_len: Vec<Vec<Vec<usize>>>,
+    insertions: usize,
+    evictions: usize,
+    subsumed: usize,
+    duplicates: usize,
+    too_long: usize,
+    pruned: usize,
+    short: ShortNogoods,
+    /// Lookups by the search, how many of them found a nogood, and how many the signatures
+    /// answered.
+    lookups: usize,
+    hits: usize,
+    avoided: usize,
 }
 
-impl NoGoods {
-    fn new() -> Self {
+impl NogoodCache for NoGoods {
+    type Cursor<'a> = NoGoodsCursor<'a>;
+
+    fn new(options: &Options) -> Self {
         Self {
-            root: TrieNode::new(),
+            trie: Trie::new(),
+            limit: options.max_nogoods,
+            max_len: options.max_nogood_len,
+            signatures: options.nogood_signatures.then(Vec::new),
+            len: 0,
+            by_len: vec![],
+            insertions: 0,
+            evictions: 0,
+            subsumed: 0,
+            duplicates: 0,
+            too_long: 0,
+            pruned: 0,
+            short: ShortNogoods::default(),
+            lookups: 0,
+            hits: 0,
+            avoided: 0,
         }
     }
 
-    /// Inserts a bad partial-solution into the no goods cache.
-    pub fn insert(&mut self, mut solution: Vec<usize>) {
-        solution.sort_unstable();
+    /// Inserts a bad partial-solution, given as sorted indices, into the no goods cache. Returns
+    /// whether it was stored.
+    ///
+    /// Nothing is stored when the same nogood or a known prefix of it is already there, since
+    /// that nogood prunes everything the new one would. Conversely, stored nogoods extending the
+    /// new one are dropped. The check and the insertion share one descent of the trie.
+    fn insert(&mut self, sorted_solution: &[usize]) -> bool {
+        if self
+            .max_len
+            .is_some_and(|max_len| sorted_solution.len() > max_len)
+        {
+            self.too_long += 1;
+            return false;
+        }
+
+        let mut current = Trie::ROOT;
+
+        for (depth, &idx) in sorted_solution.iter().enumerate() {
+            current = self.trie.child_or_insert(current, idx);
+
+            // Nodes made on the way are not nogoods, so one met here was stored before, and
+            // nothing has been added yet.
+            if self.trie.is_leaf(current) {
+                if depth + 1 == sorted_solution.len() {
+                    self.duplicates += 1;
+                } else {
+                    self.subsumed += 1;
+                }
+                return false;
+            }
+        }
+
+        // Only the empty nogood ends on a node the loop did not check.
+        if self.trie.is_leaf(current) {
+            self.duplicates += 1;
+            return false;
+        }
+
+        let pruned = self.trie.mark_leaf(current);
+        self.len = self.len + 1 - pruned;
+        self.pruned += pruned;
+
+        if let Some(limit) = self.limit {
+            if self.len > limit && !self.evict_longer_than(sorted_solution.len()) {
+                // The new nogood would be the first to go, so take it back out.
+                self.trie.remove(sorted_solution);
+                self.len -= 1;
+                self.evictions += 1;
+                return false;
+            }
 
-        let mut current = &mut self.root;
+            if self.by_len.len() <= sorted_solution.len() {
+                self.by_len.resize(sorted_solution.len() + 1, vec![]);
+            }
+            self.by_len[sorted_solution.len()].push(sorted_solution.to_vec());
+        }
 
-        for idx in solution {
-            current = current.children.entry(idx).or_insert(TrieNode::new());
+        if let (Some(signatures), Some(&first)) = (&mut self.signatures, sorted_solution.first()) {
+            if signatures.len() <= first {
+                signatures.resize(first + 1, !0);
+            }
+            signatures[first] &= signature(sorted_solution);
         }
 
-        current.is_leaf = true;
+        self.short.record(sorted_solution);
+        self.insertions += 1;
+        true
     }
 
-    /// Searches the cache to see if the current solution contains any bad combination of elements.
+    /// Finds a stored nogood that is a subset of `sorted_solution`, wherever its elements appear.
     ///
-    /// If any known bad partial solution set is a subset of `solution`, then we know `solution` cannot be valid.
-    /// This is because it's known that a bad partial solution cannot lead to an eventually correct solution.
-    /// In other words the `solution` is futile.
-    pub fn search(&self, mut solution: Vec<usize>) -> bool {
-        solution.sort_unstable();
+    /// Unlike a `cursor`, which only detects nogoods forming a prefix of the sorted solution, this
+    /// explores every matching branch of the trie, which is needed once nogoods are minimal.
+    /// The nogood found is left in `path`.
+    fn find_subset(&mut self, sorted_solution: &[usize], path: &mut Vec<usize>) -> bool {
+        path.clear();
+        let found = if self.screens_out(sorted_solution) {
+            self.avoided += 1;
+            false
+        } else {
+            Self::find_subset_from(&self.trie, Trie::ROOT, sorted_solution, path)
+        };
+
+        self.lookups += 1;
+        self.hits += found as usize;
+        found
+    }
+
+    /// A cursor over the trie for the decisions `sorted_solution`, answering for each candidate
+    /// whether adding it would make the search hit a nogood.
+    ///
+    /// The trie must not change while the cursor is in use. The lookups it makes are only counted
+    /// once they are handed back to `count_lookups`.
+    fn cursor<'a>(&'a self, sorted_solution: &'a [usize]) -> NoGoodsCursor<'a> {
+        NoGoodsCursor::new(self, sorted_solution)
+    }
+
+    fn count_lookups(&mut self, lookups: Lookups) {
+        self.lookups += lookups.lookups;
+        self.hits += lookups.hits;
+        self.avoided += lookups.avoided;
+    }
+
+    fn short(&self) -> &ShortNogoods {
+        &self.short
+    }
+
+    /// The trie nodes and bytes come from walking the whole trie.
+    fn report(&self, stats: &mut SolveStats) {
+        stats.nogood_insertions = self.insertions;
+        stats.nogood_evictions = self.evictions;
+        stats.nogood_subsumed = self.subsumed;
+        stats.nogood_duplicates = self.duplicates;
+        stats.nogood_too_long = self.too_long;
+        stats.nogood_pruned = self.pruned;
+        stats.nogood_lookups = self.lookups;
+        stats.nogood_hits = self.hits;
+        stats.nogood_walks_avoided = self.avoided;
+        stats.nogoods_stored = self.len;
+        (stats.nogood_trie_nodes, stats.nogood_bytes) = self.footprint();
+    }
+}
+
+impl NoGoods {
+    /// Whether the signatures show that no stored nogood is a subset of `sorted_solution`.
+    fn screens_out(&self, sorted_solution: &[usize]) -> bool {
+        let Some(signatures) = &self.signatures else {
+            return false;
+        };
+        // The empty nogood is a subset of everything, but belongs to no bucket.
+        if self.trie.is_leaf(Trie::ROOT) {
+            return false;
+        }
+
+        let query = signature(sorted_solution);
+        sorted_solution.iter().all(|&first| {
+            signatures
+                .get(first)
+                .is_none_or(|&common| common & !query != 0)
+        })
+    }
+
+    /// The trie nodes in use and how many bytes the cache takes up.
+    fn footprint(&self) -> (usize, usize) {
+        let (nodes, bytes) = self.trie.footprint();
+        let copies: usize = self
+            .by_len
+            .iter()
+            .flatten()
+            .map(|nogood| nogood.capacity() * std::mem::size_of::<usize>())
+            .sum();
+
+        (nodes, std::mem::size_of::<Self>() + bytes + copies)
+    }
+
+    /// Evicts one of the longest stored nogoods if it is longer than `len`.
+    fn evict_longer_than(&mut self, len: usize) -> bool {
+        while let Some(longest) = self.by_len.iter().rposition(|bucket| !bucket.is_empty()) {
+            if longest <= len {
+                return false;
+            }
+
+            // Buckets still hold copies of nogoods that were pruned as supersets.
+            let evicted = self.by_len[longest].pop().unwrap();
+            if self.trie.remove(&evicted) {
+                self.len -= 1;
+                self.evictions += 1;
+                return true;
+            }
+        }
 
-        let mut current = &self.root;
+        false
+    }
 
-        for idx in solution {
-            if let Some(child) = current.children.get(&idx) {
-                current = child;
+    fn find_subset_from(trie: &Trie, node: NodeId, rest: &[usize], path: &mut Vec<usize>) -> bool {
+        for (pos, &idx) in rest.iter().enumerate() {
+            if let Some(child) = trie.child(node, idx) {
+                path.push(idx);
 
-                // A bad partial solution is a subset of `solution`.
-                if current.is_leaf {
+                if trie.is_leaf(child)
+                    || Self::find_subset_from(trie, child, &rest[pos + 1..], path)
+                {
                     return true;
                 }
-            } else {
-                return false;
+
+                path.pop();
             }
         }
 
@@ -161,141 +978,1826 @@ impl NoGoods {
     }
 }
 
-#[wasm_bindgen]
-pub fn solve(game_json: String) -> String {
-    set_panic_hook();
-    let game: Game = serde_json::from_str(&game_json).unwrap();
+/// Checks the candidates of one node against the `NoGoods` cache without walking the trie from the
+/// root for each of them.
+///
+/// Adding a candidate to the decisions of the node inserts it somewhere in their sorted order,
+/// so its prefix lookup shares the descent only as far as the decisions below it. The cursor keeps
+/// the node reached after every decision of that descent, and each candidate resumes from the one
+/// where it slots in, walking only the decisions above it. The descent itself is only extended as
+/// far as some candidate needs, so a node whose first candidate is taken costs one plain lookup.
+pub(crate) struct NoGoodsCursor<'a> {
+    trie: &'a Trie,
+    sorted_solution: &'a [usize],
+    /// `path[depth]` is the node reached after the first `depth` decisions, up to `reached`.
+    path: [NodeId; MAX_UNITS + 1],
+    reached: usize,
+    /// Whether the descent can go no further, having left the trie or reached a nogood.
+    stopped: bool,
+    /// Whether the descent reached a nogood, which is then a prefix of the decisions.
+    at_nogood: bool,
+    /// The signature buckets of the cache and the signature of the decisions, when screening.
+    signatures: Option<(&'a [u64], u64)>,
+    lookups: Lookups,
+}
 
-    let mut used = UsedTracker::new(game.rows, game.cols, game.colors.len());
-    let mut adj_lookup = AdjacentsLookup::new(game.rows, game.cols);
-    let mut nogoods = NoGoods::new();
-    let mut solution = vec![];
+impl NogoodCursor for NoGoodsCursor<'_> {
+    /// Like walking the sorted decisions with `idx` added down the trie from the root.
+    #[inline(always)]
+    fn hits(&mut self, idx: usize) -> bool {
+        let found = if self.screens_out(idx) {
+            self.lookups.avoided += 1;
+            false
+        } else {
+            self.find(idx)
+        };
 
-    solve_backtracking(
-        &game,
-        &mut used,
-        &mut adj_lookup,
-        &mut nogoods,
-        &mut solution,
-    );
+        self.lookups.lookups += 1;
+        self.lookups.hits += found as usize;
+        found
+    }
 
-    serde_json::to_string(&solution).unwrap()
+    fn counts(&self) -> Lookups {
+        self.lookups
+    }
 }
 
-fn solve_backtracking(
-    game: &Game,
-    used: &mut UsedTracker,
-    adj_lookup: &mut AdjacentsLookup,
-    nogoods: &mut NoGoods,
-    solution: &mut Vec<usize>,
-) -> bool {
-    if used.is_solved() {
-        return true;
+impl<'a> NoGoodsCursor<'a> {
+    fn new(nogoods: &'a NoGoods, sorted_solution: &'a [usize]) -> Self {
+        let trie = &nogoods.trie;
+        // The empty nogood is a prefix of everything, but belongs to no bucket.
+        let signatures = match &nogoods.signatures {
+            Some(signatures) if !trie.is_leaf(Trie::ROOT) => {
+                Some((signatures.as_slice(), signature(sorted_solution)))
+            }
+            _ => None,
+        };
+
+        Self {
+            trie,
+            sorted_solution,
+            path: [Trie::ROOT; MAX_UNITS + 1],
+            reached: 0,
+            stopped: false,
+            at_nogood: false,
+            signatures,
+            lookups: Lookups::default(),
+        }
     }
 
-    for (row, col) in get_candidates(game, used, adj_lookup) {
-        let idx = row * game.cols + col;
-        let color = game.idx_to_color[idx];
+    /// Whether the signatures show that no stored nogood starts the decisions with `idx` added.
+    /// Only the bucket of the first of them can hold one.
+    #[inline(always)]
+    fn screens_out(&self, idx: usize) -> bool {
+        let Some((signatures, decisions)) = self.signatures else {
+            return false;
+        };
 
-        // No goods optimization.
-        solution.push(idx);
-        if nogoods.search(solution.clone()) {
-            solution.pop();
-            continue;
-        }
+        let first = self
+            .sorted_solution
+            .first()
+            .map_or(idx, |&first| first.min(idx));
+        let common = signatures.get(first).copied().unwrap_or(!0);
+        common & !(decisions | signature_bit(idx)) != 0
+    }
 
-        // Put a queen on this square.
-        used.set(row, col, color, true);
-        for &i in &adj_lookup.adjacents[idx] {
-            adj_lookup.counts[i] += 1;
-        }
+    #[inline(always)]
+    fn find(&mut self, idx: usize) -> bool {
+        let pos = self.sorted_solution.partition_point(|&i| i < idx);
+        self.descend(pos);
 
-        if solve_backtracking(game, used, adj_lookup, nogoods, solution) {
+        // The decisions below `idx` come first either way, so the descent through them stands.
+        if self.at_nogood && self.reached <= pos {
             return true;
         }
-
-        // Backtrack and continue.
-        used.set(row, col, color, false);
-        for &i in &adj_lookup.adjacents[idx] {
-            adj_lookup.counts[i] -= 1;
+        if self.reached < pos {
+            return false;
         }
-        solution.pop();
-    }
 
-    // Add this combination of indices to the no goods cache.
-    nogoods.insert(solution.clone());
+        let trie = self.trie;
+        let Some(mut current) = trie.child(self.path[pos], idx) else {
+            return false;
+        };
 
-    false
-}
+        for &next in &self.sorted_solution[pos..] {
+            if trie.is_leaf(current) {
+                return true;
+            }
 
-#[inline(always)]
-fn get_candidates(
-    game: &Game,
-    used: &UsedTracker,
-    adj_lookup: &AdjacentsLookup,
-) -> Vec<(usize, usize)> {
-    let mut row_to_spots = vec![0usize; game.rows];
-    let mut col_to_spots = vec![0usize; game.cols];
-    let mut color_to_spots = vec![0usize; game.colors.len()];
-
-    let mut candidates = vec![];
-
-    for row in 0..game.rows {
-        for col in 0..game.cols {
-            let idx = row * game.cols + col;
-            let color = game.idx_to_color[idx];
-
-            if !used.is_used(row, col, color) && (adj_lookup.counts[idx] == 0) {
-                row_to_spots[row] += 1;
-                col_to_spots[col] += 1;
-                color_to_spots[color] += 1;
-                candidates.push((row, col));
+            match trie.child(current, next) {
+                Some(child) => current = child,
+                None => return false,
             }
         }
+
+        trie.is_leaf(current)
     }
 
-    // Forward checking optimization.
-    if forward_check_failure(used, &row_to_spots, &col_to_spots, &color_to_spots) {
-        return vec![];
+    /// Extends the descent through the decisions until it covers the first `depth` of them or
+    /// stops.
+    #[inline(always)]
+    fn descend(&mut self, depth: usize) {
+        while self.reached < depth && !self.stopped {
+            let idx = self.sorted_solution[self.reached];
+            let Some(child) = self.trie.child(self.path[self.reached], idx) else {
+                self.stopped = true;
+                break;
+            };
+
+            self.reached += 1;
+            self.path[self.reached] = child;
+            if self.trie.is_leaf(child) {
+                self.at_nogood = true;
+                self.stopped = true;
+            }
+        }
     }
+}
 
-    // Variable ordering heuristic optimization.
-    candidates.sort_unstable_by_key(|&(row, col)| {
-        vec![
-            row_to_spots[row],
-            col_to_spots[col],
-            color_to_spots[game.idx_to_color[row * game.cols + col]],
-        ]
-        .into_iter()
-        .min()
-    });
+#[derive(Serialize)]
+struct SolveOutput {
+    solution: Vec<usize>,
+    status: Status,
+    /// The rules broken by `solution` when `status` is `InternalError`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    violations: Vec<Violation>,
+    /// The ids of the queens' cells in the order of `solution`, if the game named its cells.
+    #[serde(rename = "cellIds", skip_serializing_if = "Option::is_none")]
+    cell_ids: Option<Vec<String>>,
+    /// With `order: "deduction"`, how every queen of `solution` is found, in the same order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    placements: Option<Vec<Placement>>,
+    stats: SolveStats,
+}
+
+#[wasm_bindgen]
+pub fn solve(game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let options = Options::for_solve();
+        let started = now_ms();
+        let mut outcome = run_solver(&game, &options);
+
+        let violations = check_outcome(&game, &options, &mut outcome);
+        record_solve("solve", &game, &options, &outcome, now_ms() - started);
+        if !violations.is_empty() {
+            let violations: Vec<_> = violations.iter().map(ToString::to_string).collect();
+            return Err(format!(
+                "Internal error: the solver returned {:?}, but {}",
+                outcome.solution,
+                violations.join(", ")
+            ));
+        }
 
-    candidates
+        #[cfg(not(feature = "tiny-json"))]
+        return Ok(serde_json::to_string(&outcome.solution).unwrap());
+        #[cfg(feature = "tiny-json")]
+        return Ok(tiny_json::write_solution(&outcome.solution));
+    })
 }
 
-#[inline(always)]
-fn forward_check_failure(
-    used: &UsedTracker,
-    row_to_spots: &[usize],
-    col_to_spots: &[usize],
-    color_to_spots: &[usize],
-) -> bool {
-    let rows = row_to_spots.len();
-    let cols = col_to_spots.len();
-    let colors = color_to_spots.len();
+/// Solves the game like `solve`, tuned by `options_json`, returning the solution together with
+/// statistics about the search.
+#[wasm_bindgen]
+pub fn solve_with_options(game_json: String, options_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let options = Options::from_json(&options_json)?;
+        let started = now_ms();
+        let outcome = run_solver(&game, &options);
+        let solved = ("solveWithOptions", now_ms() - started);
 
-    if (0..rows).any(|row| (((used.rows >> row) & 1) == 0) && (row_to_spots[row] == 0)) {
-        return true;
-    }
+        Ok(solve_output(&game, &options, outcome, Some(solved)))
+    })
+}
 
-    if (0..cols).any(|col| (((used.cols >> col) & 1) == 0) && (col_to_spots[col] == 0)) {
-        return true;
+/// The JSON `solve_with_options` returns for `outcome`, checked when the options ask for it. With
+/// `solved`, the entry point that solved and the milliseconds it took, the solve is recorded in
+/// the activity log.
+fn solve_output(
+    game: &Game,
+    options: &Options,
+    mut outcome: Outcome,
+    solved: Option<(&'static str, f64)>,
+) -> String {
+    let violations = check_outcome(game, options, &mut outcome);
+    if let Some((entry, ms)) = solved {
+        record_solve(entry, game, options, &outcome, ms);
     }
+    let Outcome {
+        mut solution,
+        status,
+        stats,
+    } = outcome;
 
-    if (0..colors).any(|color| (((used.colors >> color) & 1) == 0) && (color_to_spots[color] == 0))
-    {
-        return true;
+    let placements = (options.order == SolutionOrder::Deduction && status == Status::Solved)
+        .then(|| deduction_order(game, &solution));
+    if let Some(placements) = &placements {
+        solution = placements.iter().map(|placement| placement.queen).collect();
     }
 
-    false
+    let cell_ids = game
+        .cell_ids
+        .as_ref()
+        .map(|cell_ids| solution.iter().map(|&idx| cell_ids[idx].clone()).collect());
+
+    serde_json::to_string(&SolveOutput {
+        solution,
+        status,
+        violations,
+        cell_ids,
+        placements,
+        stats,
+    })
+    .unwrap()
+}
+
+/// Lists the last solves made by `solve`, `solve_with_options`, and finished `SolveSession`s,
+/// oldest first, for diagnosing slowness: for each the entry point, a hash of the board, its
+/// size, the status, nodes, and milliseconds, and the algorithm, heuristics tier, and a hash of
+/// the options. Kept in memory only, 32 solves unless `set_activity_capacity` says otherwise.
+#[wasm_bindgen]
+pub fn recent_activity() -> String {
+    serde_json::to_string(&activity::recent_activity()).unwrap()
+}
+
+/// Forgets every solve in the activity log.
+#[wasm_bindgen]
+pub fn clear_activity() {
+    activity::clear_activity();
+}
+
+/// Keeps at most `capacity` solves in the activity log, dropping the oldest beyond that, or none
+/// with a capacity of 0.
+#[wasm_bindgen]
+pub fn set_activity_capacity(capacity: usize) {
+    activity::set_activity_capacity(capacity);
+}
+
+/// Counts every solution of the game.
+#[wasm_bindgen]
+pub fn count_solutions(game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(serde_json::to_string(&run_counter(&game, false)).unwrap())
+    })
+}
+
+/// Counts the solutions of the game up to its symmetries: solutions carried into each other by a
+/// rotation or reflection mapping the board onto itself count once.
+#[wasm_bindgen]
+pub fn count_solutions_mod_symmetry(game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(serde_json::to_string(&run_counter(&game, true)).unwrap())
+    })
+}
+
+/// The most solutions `classify_symmetry` lists.
+const MAX_CLASSIFIED_SOLUTIONS: usize = 1000;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SymmetryOutput {
+    /// The transforms mapping the board onto itself, colors up to relabeling.
+    board_symmetries: Vec<Transform>,
+    /// Every solution, sorted, with the transforms mapping it onto itself.
+    solutions: Vec<SolutionSymmetry>,
+    /// The transforms mapping the set of solutions onto itself.
+    set_symmetries: Vec<Transform>,
+}
+
+#[derive(Serialize)]
+struct SolutionSymmetry {
+    solution: Vec<usize>,
+    symmetries: Vec<Transform>,
+}
+
+/// Reports which rotations and reflections map the board, each of its solutions, and its whole
+/// set of solutions onto themselves. A board with several solutions can be symmetric as a set
+/// even when no single solution is.
+#[wasm_bindgen]
+pub fn classify_symmetry(game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        let count = run_counter(&game, false);
+        if count > MAX_CLASSIFIED_SOLUTIONS as u64 {
+            return Err(format!(
+                "Board has {} solutions, more than the {} that can be classified",
+                count, MAX_CLASSIFIED_SOLUTIONS
+            ));
+        }
+
+        let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+
+        Ok(serde_json::to_string(&SymmetryOutput {
+            board_symmetries: board_symmetries(&game),
+            set_symmetries: set_symmetries(&game, &solutions),
+            solutions: solutions
+                .into_iter()
+                .map(|solution| SolutionSymmetry {
+                    symmetries: solution_symmetries(&game, &solution),
+                    solution,
+                })
+                .collect(),
+        })
+        .unwrap())
+    })
+}
+
+/// Up to `limit` solutions of the game with queens on all of `givens` and none on `excluded`,
+/// with their cells sorted, in sorted order.
+fn enumerate_solutions(
+    game: &Game,
+    givens: &[usize],
+    excluded: &[usize],
+    limit: u64,
+) -> Vec<Vec<usize>> {
+    // Which solutions a limit keeps depends on the order they are found in, so only enumerating
+    // them all is split up, leaving every build with the same answers.
+    let mut solutions = if limit == u64::MAX {
+        split_shares(|first, step| enumerate_share(game, givens, excluded, limit, first, step))
+            .into_iter()
+            .flatten()
+            .collect()
+    } else {
+        enumerate_share(game, givens, excluded, limit, 0, 1)
+    };
+
+    solutions.sort_unstable();
+    solutions
+}
+
+/// The solutions of `enumerate_solutions` below the root candidates at positions `first`,
+/// `first + step`, and so on.
+fn enumerate_share(
+    game: &Game,
+    givens: &[usize],
+    excluded: &[usize],
+    limit: u64,
+    first: usize,
+    step: usize,
+) -> Vec<Vec<usize>> {
+    let options = Options::for_counting();
+    let share = (first, step);
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        let availability = BitAvailability::new(game);
+        collect_solutions(game, &options, availability, givens, excluded, limit, share)
+    } else {
+        let availability = Availability::new(game);
+        collect_solutions(game, &options, availability, givens, excluded, limit, share)
+    }
+}
+
+fn collect_solutions<A: CellAvailability>(
+    game: &Game,
+    options: &Options,
+    availability: A,
+    givens: &[usize],
+    excluded: &[usize],
+    limit: u64,
+    (first, step): (usize, usize),
+) -> Vec<Vec<usize>> {
+    let mut search = Search::new(game, options, availability);
+    let mut solutions = vec![];
+
+    if !search.assume(givens, excluded) {
+        return solutions;
+    }
+
+    let mut visit = |solution: &[usize]| {
+        let mut solution = solution.to_vec();
+        solution.sort_unstable();
+        solutions.push(solution);
+        1
+    };
+
+    if game.has_color_rule() {
+        search.count_share::<true>(first, step, limit, &mut visit);
+    } else {
+        search.count_share::<false>(first, step, limit, &mut visit);
+    }
+
+    solutions
+}
+
+/// Describes where the cells of every color lie: their rows, columns, and bounding box, and
+/// whether they are confined to one or two rows or columns. A quick analysis for overlays, which
+/// does not search.
+#[wasm_bindgen]
+pub fn region_analysis(game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(serde_json::to_string(&analyze_regions(&game)).unwrap())
+    })
+}
+
+/// What `validate_game` finds suspicious about a board that the rules allow.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GameWarnings {
+    split_regions: Vec<SplitRegion>,
+}
+
+/// Checks the game like every other entry point does, and lists what is suspicious about it
+/// without breaking the rules: under `splitRegions`, every color whose cells fall apart into
+/// several pieces through shared edges, with the cells of each piece for highlighting. A game
+/// with `"strict": true` is rejected for those instead.
+#[wasm_bindgen]
+pub fn validate_game(game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let warnings = GameWarnings {
+            split_regions: split_regions(&game),
+        };
+
+        Ok(serde_json::to_string(&warnings).unwrap())
+    })
+}
+
+/// Reports which deduction techniques solving the game takes and how often, and whether it
+/// still needs guessing once they run out.
+#[wasm_bindgen]
+pub fn difficulty_breakdown(game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(serde_json::to_string(&assess_difficulty(&game)?).unwrap())
+    })
+}
+
+/// Estimates the number of solutions of boards too large to count, from `probes` random paths
+/// down the counting search's tree. The result holds the estimate with its variance and standard
+/// error, which are only rough guides since the estimate can be far off with few probes.
+#[wasm_bindgen]
+pub fn estimate_solution_count(
+    game_json: String,
+    probes: u32,
+    seed: u32,
+) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        if probes == 0 {
+            return Err("Estimating the solution count takes at least one probe".to_string());
+        }
+
+        Ok(serde_json::to_string(&estimate_count(&game, probes as usize, seed as u64)).unwrap())
+    })
+}
+
+/// Finds the fewest queens to give away so that deductions with only the techniques listed in
+/// `techniques_json`, such as `["singles", "confinement"]`, finish the board. Returns the givens
+/// with the deductions that follow them.
+#[wasm_bindgen]
+pub fn minimal_hints(game_json: String, techniques_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let techniques: Vec<Technique> = serde_json::from_str(&techniques_json)
+            .map_err(|e| format!("Invalid techniques JSON: {}", e))?;
+
+        Ok(serde_json::to_string(&find_minimal_hints(&game, &techniques)?).unwrap())
+    })
+}
+
+/// Whether board `b` is board `a` rotated or reflected, with its colors renamed. Returns the
+/// transform and color renaming carrying `a` onto `b`, or `null`.
+#[wasm_bindgen]
+pub fn is_isomorphic(a_json: String, b_json: String) -> Result<String, String> {
+    catch_panics(None, || {
+        let a = Game::from_json(&a_json)?;
+        let b = Game::from_json(&b_json)?;
+
+        Ok(serde_json::to_string(&isomorphism(&a, &b)).unwrap())
+    })
+}
+
+/// Compares two scrapes of a board, matching the colors of `a` with those of `b` by the most
+/// cells their regions share, so that numbering the same regions differently is not a
+/// difference. Returns the size of each board, the `alignment` giving the color of `b` matched
+/// with every color of `a`, the `cells` whose region differs with their color on each board,
+/// and whether the boards are the `same`.
+#[wasm_bindgen]
+pub fn diff_boards(a_json: String, b_json: String) -> Result<String, String> {
+    catch_panics(None, || {
+        let a = Game::from_json(&a_json)?;
+        let b = Game::from_json(&b_json)?;
+
+        Ok(serde_json::to_string(&board_diff(&a, &b)).unwrap())
+    })
+}
+
+/// Lists the boards of `corpus_jsonl`, one game per line, that are the game rotated or reflected
+/// with its colors renamed, and how each maps onto it. Looking up several games in the same
+/// corpus indexes it only once.
+#[wasm_bindgen]
+pub fn find_duplicates(corpus_jsonl: String, game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(serde_json::to_string(&find_in_corpus(&corpus_jsonl, &game)?).unwrap())
+    })
+}
+
+/// Lists every rule the queens in `placed_json`, a list of cells, break against each other: each
+/// pair sharing a row, column, or color, or placed on neighboring cells, as `{"first",
+/// "second", "rule"}`. Never looks at the solution, so it gives nothing away.
+#[wasm_bindgen]
+pub fn conflicts(game_json: String, placed_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let placed: Vec<usize> = serde_json::from_str(&placed_json)
+            .map_err(|e| format!("Invalid placed JSON: {}", e))?;
+
+        Ok(serde_json::to_string(&find_conflicts(&game, &placed)?).unwrap())
+    })
+}
+
+/// Replays the user's moves in `events_json`, a list of `{"kind", "cell", "time"}` with `kind`
+/// one of `place`, `remove`, `mark`, and `unmark`, for a review of the game. Grades each queen
+/// and mark put down as `forced`, `safe`, `guess`, or `mistake` against the board it went on, or
+/// `unsolvable` once no solution agreed with the board, flags the queens later taken off as
+/// wasted, and gives the first event leaving the board unsolvable with a summary of the counts
+/// and accuracy. Events that do not fit the board come back with a warning and are skipped.
+/// Fails when the board has no solution.
+#[wasm_bindgen]
+pub fn grade_replay(game_json: String, events_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let events: Vec<Event> = serde_json::from_str(&events_json)
+            .map_err(|e| format!("Invalid events JSON: {}", e))?;
+
+        Ok(serde_json::to_string(&replay_grades(&game, &events)?).unwrap())
+    })
+}
+
+/// Checks the user's X marks in `state_json`, `{"queens", "marks"}` as lists of cells, against
+/// every solution. Returns the marks that are wrong, since the cell holds a queen in every
+/// solution, those the user's queens already imply, those the deductions from those queens
+/// reach, and the premature rest. Fails when the board has no solution.
+#[wasm_bindgen]
+pub fn check_marks(game_json: String, state_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let state = BoardState::from_json(&state_json)?;
+
+        Ok(serde_json::to_string(&judge_marks(&game, &state)?).unwrap())
+    })
+}
+
+/// Suggests up to `n` different next moves for the user's board in `state_json`, `{"queens",
+/// "marks"}` as lists of cells, simplest first: forced singles, then eliminations by confinement,
+/// pairs, and triples, then safe cells that take lookahead. Each names its technique, the queen
+/// to place or cells to mark, and the cells its reasoning rests on. Empty when no solution agrees
+/// with the state.
+#[wasm_bindgen]
+pub fn hints(game_json: String, state_json: String, n: usize) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let state = BoardState::from_json(&state_json)?;
+
+        Ok(serde_json::to_string(&rank_hints(&game, &state, n)?).unwrap())
+    })
+}
+
+/// Spells out a way from the user's board in `state_json`, `{"queens", "marks"}` as lists of
+/// cells, to a solution agreeing with it, one step at a time. Each step names its technique, a
+/// key and arguments for the sentence describing it, the queen it places or cells it marks, and
+/// the cells its reasoning rests on. Deductions come first; when they run out the step tries a
+/// queen of the solution, with whether deductions alone refute each alternative spot.
+#[wasm_bindgen]
+pub fn walkthrough(game_json: String, state_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let state = BoardState::from_json(&state_json)?;
+
+        Ok(serde_json::to_string(&walk_through(&game, &state)?).unwrap())
+    })
+}
+
+/// Lists the empty cells of the user's board in `state_json`, `{"queens", "marks"}` as lists of
+/// cells, that hold a queen in every solution agreeing with those queens and marks. Each comes
+/// with whether filling in singles from the state finds it. Meant to run after every move: the
+/// solutions of the board are kept between calls.
+#[wasm_bindgen]
+pub fn safe_cells(game_json: String, state_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let state = BoardState::from_json(&state_json)?;
+
+        Ok(serde_json::to_string(&find_safe_cells(&game, &state)?).unwrap())
+    })
+}
+
+/// Shows which cells of the user's board in `state_json`, `{"queens", "marks"}` as lists of
+/// cells, could still hold a queen once `level` has been applied: `basic` for what the queens
+/// attack and the marks rule out, then `singles`, `confinement`, `pairs`, and `triples`, each
+/// adding its technique to the ones before. Every ruled-out cell names the technique, or mark or
+/// queen, that ruled it out, and a unit left without any spot is named as `emptied`.
+#[wasm_bindgen]
+pub fn domains(game_json: String, state_json: String, level: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let state = BoardState::from_json(&state_json)?;
+        let level = Level::from_name(&level)?;
+
+        Ok(serde_json::to_string(&find_domains(&game, &state, level)?).unwrap())
+    })
+}
+
+/// Points at one place `technique`, one of `singles`, `confinement`, `pairs`, and `triples`,
+/// applies to the user's board in `state_json`, `{"queens", "marks"}` as lists of cells, right
+/// now, without making it or any other deduction. Comes back `found` with the cells the
+/// reasoning rests on, the `units` it is about, and the `queen` it places or `eliminations` it
+/// makes; `absent` if the technique applies nowhere, even if an easier one does; or
+/// `contradiction` if no solution agrees with the board.
+#[wasm_bindgen]
+pub fn find_technique(
+    game_json: String,
+    state_json: String,
+    technique: String,
+) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let state = BoardState::from_json(&state_json)?;
+        let technique = Technique::from_name(&technique)?;
+
+        Ok(serde_json::to_string(&technique_in(&game, &state, technique)?).unwrap())
+    })
+}
+
+/// Makes a drill from a deduction using `technique`, one of `singles`, `confinement`, `pairs`,
+/// and `triples`, among those solving the game, picked reproducibly from `seed`. Comes back
+/// `ready` with the `position` just before it, `{"queens", "marks"}` as taken by `hints`, and the
+/// `answer` as from `find_technique` to check the user's against, along with which `deduction`
+/// it is out of how many `deductions`; or `unused` if solving the game never takes the technique.
+#[wasm_bindgen]
+pub fn make_quiz(game_json: String, technique: String, seed: u64) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let technique = Technique::from_name(&technique)?;
+
+        Ok(serde_json::to_string(&quiz_of(&game, technique, seed)?).unwrap())
+    })
+}
+
+/// Previews placing a queen on cell `idx` of the user's board in `state_json`, `{"queens",
+/// "marks"}` as lists of cells, without changing it: the queens singles would force, the cells
+/// deductions up to triples would newly rule out and why, and whether that leaves a row, column,
+/// or color without any spot. A cell the queens already hold or attack comes back `illegal` with
+/// the reason. With `deep`, also searches for whether a solution still agrees with the board.
+#[wasm_bindgen]
+pub fn what_if(
+    game_json: String,
+    state_json: String,
+    idx: usize,
+    deep: bool,
+) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let state = BoardState::from_json(&state_json)?;
+
+        Ok(serde_json::to_string(&preview_queen(&game, &state, idx, deep)?).unwrap())
+    })
+}
+
+/// Finds the solution closest to the queens in `placed_json`, a list of cells, with the user's
+/// queens to remove and the queens to add to reach it. Ties between solutions are broken the
+/// same way every time, so the correction is stable. Boards with many solutions are only
+/// compared against some of them, as `exact` tells.
+#[wasm_bindgen]
+pub fn nearest_solution(game_json: String, placed_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let placed: Vec<usize> = serde_json::from_str(&placed_json)
+            .map_err(|e| format!("Invalid placed JSON: {}", e))?;
+
+        Ok(serde_json::to_string(&find_nearest_solution(&game, &placed)?).unwrap())
+    })
+}
+
+/// Counts the solutions through every cell of one unit, given by `unit_json` as `{"row": 0}`,
+/// `{"col": 0}`, or `{"color": 0}`, or with `null` the tightest unit once forced queens are
+/// placed, the first the counter branches on. Returns the `unit` and its `cells`, each with its
+/// `idx` and `count`. The cells counting zero are the ones no solution uses.
+#[wasm_bindgen]
+pub fn branch_census(game_json: String, unit_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let unit: Option<Unit> =
+            serde_json::from_str(&unit_json).map_err(|e| format!("Invalid unit JSON: {}", e))?;
+
+        Ok(serde_json::to_string(&census_of(&game, unit)?).unwrap())
+    })
+}
+
+/// Finds the two solutions of the game differing in the most cells among up to `cap` of them,
+/// at least 2, with the `distance` between them, the cells only each holds, how many solutions
+/// were `compared`, and whether they were all of them. The `status` is `multiple`, or `unique`
+/// with the only solution, or `unsolvable`.
+#[wasm_bindgen]
+pub fn max_diff_solutions(game_json: String, cap: u32) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(serde_json::to_string(&find_most_different(&game, cap as u64)?).unwrap())
+    })
+}
+
+/// Solves the game with queens on every cell of `include_json` and none on `exclude_json`, both
+/// lists of cells. Returns the `status`, `solved` or `unsolvable`, and the `solution` with its
+/// cells sorted. When the constraints contradict each other the status is `unsolvable` without
+/// any search, listing the cells both included and excluded as `overlap` and the rules the
+/// included cells break together as `conflicts`.
+#[wasm_bindgen]
+pub fn solve_constrained(
+    game_json: String,
+    include_json: String,
+    exclude_json: String,
+) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let include: Vec<usize> = serde_json::from_str(&include_json)
+            .map_err(|e| format!("Invalid include JSON: {}", e))?;
+        let exclude: Vec<usize> = serde_json::from_str(&exclude_json)
+            .map_err(|e| format!("Invalid exclude JSON: {}", e))?;
+
+        Ok(serde_json::to_string(&solve_with_constraints(&game, &include, &exclude)?).unwrap())
+    })
+}
+
+/// Looks for signs that scraping `game_json` merged or split regions: a region count off from the
+/// rows, regions of outlying sizes, and regions in pieces. With `rgb_json`, the scraped `[r, g, b]`
+/// of every cell, also proposes fixes, each a list of `{"idx", "color"}` changes judged like
+/// `what_if_recolor_batch`, best first. Boards short of colors are read anyway, as they are what
+/// this is for.
+#[wasm_bindgen]
+pub fn diagnose_scrape(game_json: String, rgb_json: Option<String>) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let mut game = Game::parse(&game_json)?;
+        if game.colors.is_empty() {
+            return Err("Colorless boards have no regions to diagnose".to_string());
+        }
+
+        // Too few colors for the rows is the most common mis-scrape, which validation would reject.
+        let all_colors_required = game.rules.all_colors_required;
+        game.rules.all_colors_required = false;
+        let mut game = game.prepare()?;
+        game.rules.all_colors_required = all_colors_required;
+
+        let rgb: Option<Vec<[f64; 3]>> = rgb_json
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| format!("Invalid RGB JSON: {}", e))?;
+
+        Ok(serde_json::to_string(&diagnose(&game, rgb.as_deref())?).unwrap())
+    })
+}
+
+/// Reads a board pasted in any format the crate knows, working out which from the input: game
+/// JSON as taken by `solve` (`game`), the same with `regions` listing the cells of every color
+/// (`regions`), LinkedIn's puzzle data with its `gridSize` and `colorGrid` (`linkedin`), or an
+/// ASCII grid of one letter or digit per cell, cells with the same symbol sharing a region
+/// (`grid`). Returns the `format` found and the `game`, normalized as by `normalize_game`. Fails
+/// when no format or several recognize the input, naming every format tried.
+#[wasm_bindgen]
+pub fn game_from_any(input: String) -> Result<String, String> {
+    catch_panics(None, || {
+        Ok(serde_json::to_string(&import_game(&input)?).unwrap())
+    })
+}
+
+/// Writes the boards of `games_json`, a JSON array of games as taken by `solve`, as a pack file
+/// titled `title` for `PuzzlePack`, working out the difficulty, hash, and solution of each. Fails
+/// on a board without exactly one solution, which a pack has no use for.
+#[wasm_bindgen]
+pub fn make_pack(title: String, games_json: String) -> Result<String, String> {
+    catch_panics(None, || {
+        let games: Vec<Game> =
+            serde_json::from_str(&games_json).map_err(|e| format!("Invalid games JSON: {}", e))?;
+
+        write_pack(title, games)
+    })
+}
+
+/// Rewrites `game_json` in one canonical form, for hashing, caching, or comparing boards: sizes
+/// filled in, unknown fields dropped, colors renamed by first appearance, neighbor lists and
+/// links sorted, and keys in a fixed order. Normalizing the result again returns it unchanged.
+#[wasm_bindgen]
+pub fn normalize_game(game_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(game.normalized().to_json())
+    })
+}
+
+/// Turns the cells of `solution_json`, such as a solution from `solve`, into the points to click
+/// to place their queens, in the same order. `layout_json` gives the bounding box of the cells on
+/// the page, the gap between them, and optionally their size, the device pixel ratio, and whether
+/// queens take a double click. Returns each cell with the device-pixel center of its cell.
+#[wasm_bindgen]
+pub fn click_plan(
+    game_json: String,
+    solution_json: String,
+    layout_json: String,
+) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let solution: Vec<usize> = serde_json::from_str(&solution_json)
+            .map_err(|e| format!("Invalid solution JSON: {}", e))?;
+        let layout = Layout::from_json(&layout_json)?;
+
+        Ok(serde_json::to_string(&plan_clicks(&game, &solution, &layout)?).unwrap())
+    })
+}
+
+/// Checks that the build answers right: solves every board of the corpus bundled with the crate
+/// like `solve` and counts its solutions, comparing against the known answers and checking every
+/// solution against the rules. Returns whether everything `passed`, the crate `version` and
+/// optional `features` of the build, and per board whether it passed, what failed, and the
+/// milliseconds taken. Takes a few milliseconds.
+#[wasm_bindgen]
+pub fn run_self_tests() -> Result<String, String> {
+    catch_panics(None, || Ok(serde_json::to_string(&self_tests()).unwrap()))
+}
+
+/// Solves the game like `solve_with_options` and returns a bundle for attaching to bug reports:
+/// the `bundleVersion` of its format, the crate version, features, and whether it is a debug
+/// build, the game normalized with its `boardHash`, the options searched with, every default
+/// spelled out and automatic heuristics replaced by their tier's, the solution, its status,
+/// whether it was `verified` to keep every rule with any `violations`, the statistics, and the
+/// milliseconds taken. Passing the board and options back to `solve_with_options` reproduces the
+/// solve. `solve --bundle out.json` writes the same bundle from the command line.
+#[wasm_bindgen]
+pub fn debug_bundle(game_json: String, options_json: String) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let options = Options::from_json(&options_json)?;
+
+        Ok(serde_json::to_string(&bundle_solve(&game, &options)).unwrap())
+    })
+}
+
+/// Packs a request for a worker running the solver, to post to it as is: `kind` is `"solve"`,
+/// `"hint"`, `"validate"`, or `"count"`, run as `solve_with_options` (or `solve` when
+/// `options_json` is empty), `hints` for the first hint (`options_json` then being the board
+/// state, the empty board when empty), `validate_game`, and `count_solutions`. The envelope starts
+/// with a protocol version, so that a worker from another build answers with an error instead of
+/// misreading it.
+#[wasm_bindgen]
+pub fn encode_request(
+    kind: String,
+    game_json: String,
+    options_json: String,
+) -> Result<Vec<u8>, String> {
+    catch_panics(Some(&game_json), || {
+        let kind = WorkerKind::from_name(&kind)?;
+
+        Ok(encode_worker_request(kind, &game_json, &options_json))
+    })
+}
+
+/// Runs a request from `encode_request` inside the worker and packs the reply to post back, for
+/// `decode_reply` on the other side. Never fails: requests of another protocol version, cut
+/// short, or of a kind this build does not know get a reply carrying the error.
+#[wasm_bindgen]
+pub fn handle_request(bytes: Vec<u8>) -> Vec<u8> {
+    handle_worker_request(&bytes)
+}
+
+/// The JSON the request returned from a reply of `handle_request`, or the error it carries.
+#[wasm_bindgen]
+pub fn decode_reply(bytes: Vec<u8>) -> Result<String, String> {
+    catch_panics(None, || decode_worker_reply(&bytes))
+}
+
+/// Solves every game of `corpus_jsonl`, one per line, under each configuration of `configs_json`,
+/// a list of `{"name", "options"}` with options as for `solve_with_options`. With `null` the
+/// nogood cache, forward checking, and candidate ordering are each turned off alone and then
+/// together, the nogoods are kept in a hash set instead or screened by signature, ties between
+/// candidates are broken by degree or centrality, the board is split into independent parts, and
+/// the heuristics are left to `"auto"`, every run giving up after two million nodes. Each solve is
+/// repeated `repeats` times to take the fastest. Returns per configuration the boards solved and
+/// given up on, the total nodes and milliseconds, and the boards it settled fastest, followed by
+/// every run.
+#[wasm_bindgen]
+pub fn benchmark_options(
+    corpus_jsonl: String,
+    configs_json: String,
+    repeats: u32,
+) -> Result<String, String> {
+    catch_panics(None, || {
+        let corpus = Game::from_jsonl(&corpus_jsonl)?;
+        let configs: Option<Vec<Config>> = serde_json::from_str(&configs_json)
+            .map_err(|e| format!("Invalid configurations JSON: {}", e))?;
+        let configs = configs.unwrap_or_else(Config::default_matrix);
+
+        if configs.is_empty() {
+            return Err("Benchmarking takes at least one configuration".to_string());
+        }
+
+        if repeats == 0 {
+            return Err("Benchmarking takes at least one repeat".to_string());
+        }
+
+        for config in &configs {
+            config
+                .options
+                .validate()
+                .map_err(|e| format!("Configuration {}: {}", config.name, e))?;
+        }
+
+        Ok(serde_json::to_string(&run_benchmark(&corpus, &configs, repeats as usize)).unwrap())
+    })
+}
+
+/// Grid-searches the `orderingWeights` of `solve_with_options` for the fewest search nodes over
+/// the games of `corpus_jsonl`, one per line, with the other options left at their defaults.
+/// Returns the best table as `weights`, ready to pass as `orderingWeights`, with its total
+/// `nodes`, the `baselineNodes` without weights, the boards still given up on, and how many
+/// tables were tried.
+#[wasm_bindgen]
+pub fn tune_ordering_weights(corpus_jsonl: String) -> Result<String, String> {
+    catch_panics(None, || {
+        let corpus = Game::from_jsonl(&corpus_jsonl)?;
+        if corpus.is_empty() {
+            return Err("Tuning takes at least one game".to_string());
+        }
+
+        Ok(serde_json::to_string(&tune_weights(&corpus)).unwrap())
+    })
+}
+
+/// Hands `bench` a function generating the candidates of the node of `game_json` reached by
+/// placing `queens`, under the default options, and returning how many there are. This lets the
+/// criterion benchmarks time candidate generation apart from the rest of the search.
+#[doc(hidden)]
+pub fn bench_candidates(
+    game_json: &str,
+    queens: &[usize],
+    bench: impl FnOnce(&mut dyn FnMut() -> usize),
+) -> Result<(), String> {
+    let game = Game::from_json(game_json)?;
+    let options = Options::default();
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        let availability = BitAvailability::new(&game);
+        time_candidates(&game, &options, availability, queens, bench)
+    } else {
+        let availability = Availability::new(&game);
+        time_candidates(&game, &options, availability, queens, bench)
+    }
+}
+
+/// Judges the game with cell `idx` moved to color `new_color`: `invalid` with the reason, or
+/// `unsolvable`, `unique` with the solution, or `multiple`. With `contiguous`, a color split into
+/// pieces makes the board invalid.
+#[wasm_bindgen]
+pub fn what_if_recolor(
+    game_json: String,
+    idx: usize,
+    new_color: usize,
+    contiguous: bool,
+) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let mut game = Game::from_json(&game_json)?;
+        let recolor = Recolor {
+            idx,
+            color: new_color,
+        };
+
+        Ok(serde_json::to_string(&judge_recolors(&mut game, &[recolor], contiguous)).unwrap())
+    })
+}
+
+/// Like `what_if_recolor` for several tentative edits, each a list of `{"idx", "color"}` changes
+/// applied together, judged one by one against the unchanged game. Returns a verdict per edit.
+#[wasm_bindgen]
+pub fn what_if_recolor_batch(
+    game_json: String,
+    edits_json: String,
+    contiguous: bool,
+) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let mut game = Game::from_json(&game_json)?;
+        let edits: Vec<Vec<Recolor>> =
+            serde_json::from_str(&edits_json).map_err(|e| format!("Invalid edits JSON: {}", e))?;
+
+        let verdicts: Vec<_> = edits
+            .iter()
+            .map(|edit| judge_recolors(&mut game, edit, contiguous))
+            .collect();
+
+        Ok(serde_json::to_string(&verdicts).unwrap())
+    })
+}
+
+/// Derives up to `count` practice boards from the game, reproducibly from `seed`: each moves a
+/// few cells between neighboring colors, keeping the only solution and the hardest technique it
+/// takes, then is rotated or reflected and has its colors renamed. Each comes with its solution
+/// and the operations that produced it. No two are isomorphic, nor any to the game. Fails unless
+/// the game has exactly one solution.
+#[wasm_bindgen]
+pub fn derive_variants(game_json: String, count: usize, seed: u64) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(serde_json::to_string(&variants_of(&game, count, seed)?).unwrap())
+    })
+}
+
+/// Grows the square game by a row and a column at the corner `direction`, one of `topLeft`,
+/// `topRight`, `bottomLeft`, and `bottomRight`, reproducibly from `seed`. The new cells mostly
+/// extend the colors beside them, and a stretch of them makes up the new color. Returns the new
+/// `game` with its only `solution`, or fails when no extension tried has exactly one solution.
+#[wasm_bindgen]
+pub fn resize(game_json: String, direction: String, seed: u64) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let corner = Corner::from_name(&direction)?;
+
+        Ok(serde_json::to_string(&resize_board(&game, corner, false, seed)?).unwrap())
+    })
+}
+
+/// Shrinks the square game by the row and column at the corner `direction`, as for `resize`,
+/// keeping most queens of its solution and the colors around them, and giving the cells cut off
+/// from their color to a neighboring one. Returns the new `game` with its only `solution`, or
+/// fails when no reduction tried has exactly one solution.
+#[wasm_bindgen]
+pub fn shrink(game_json: String, direction: String, seed: u64) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+        let corner = Corner::from_name(&direction)?;
+
+        Ok(serde_json::to_string(&resize_board(&game, corner, true, seed)?).unwrap())
+    })
+}
+
+/// Evens out the sizes of the colors, reproducibly from `seed`, by moving cells on their borders
+/// from larger colors to smaller ones until the largest has at most `target_spread` cells more
+/// than the smallest. The cells of the solution never move, and a move is only kept if the board
+/// stays valid, in one piece, and with the same only solution. Returns the `game`, the `shifts`
+/// made, the `spread` reached, and whether it `met` the target. Fails unless the game has exactly
+/// one solution.
+#[wasm_bindgen]
+pub fn rebalance(game_json: String, seed: u64, target_spread: usize) -> Result<String, String> {
+    catch_panics(Some(&game_json), || {
+        let game = Game::from_json(&game_json)?;
+
+        Ok(serde_json::to_string(&rebalance_regions(&game, seed, target_spread)?).unwrap())
+    })
+}
+
+/// Generates up to `count` square boards of `rows` rows for a lesson on `technique`, one of
+/// `singles`, `confinement`, `pairs`, `triples`, and `guessing`, reproducibly from `seed`. Every
+/// board has one solution and takes the technique but nothing harder: deductions up to it solve
+/// the board, or stall for `guessing`, while those below it alone stall. Each comes with its
+/// solution and, as proof, its `difficulty` as from `difficulty_breakdown`. Fewer come back when
+/// the boards grown run out, `attempts` telling how many were.
+#[wasm_bindgen]
+pub fn generate_lesson(
+    rows: usize,
+    technique: String,
+    count: usize,
+    seed: u64,
+) -> Result<String, String> {
+    catch_panics(None, || {
+        let technique = Technique::from_name(&technique)?;
+
+        Ok(serde_json::to_string(&lesson_for(rows, technique, count, seed)?).unwrap())
+    })
+}
+
+/// Counts the solutions, or with `mod_symmetry` only the canonical one of every orbit under the
+/// board's symmetries.
+fn run_counter(game: &Game, mod_symmetry: bool) -> u64 {
+    // Colored boards are rarely symmetric, and without symmetries every orbit is one solution.
+    let mod_symmetry = mod_symmetry && board_symmetries(game).len() > 1;
+
+    // Counting by rows loses track of the solutions themselves, which the symmetric count needs.
+    if !mod_symmetry {
+        if let Some(count) = count_by_rows(game) {
+            return count;
+        }
+        if let Some(parts) = split_board(game) {
+            return count_parts(&parts);
+        }
+    }
+
+    split_shares(|first, step| count_share(game, mod_symmetry, first, step))
+        .into_iter()
+        .sum()
+}
+
+/// Runs `share(first, step)` for every `first` in `0..step`, one share of the root's candidates
+/// per thread of rayon's pool: native threads with the `parallel` feature, the workers started by
+/// `init_thread_pool` with `wasm-threads`. Otherwise only the calling thread runs, with one share.
+fn split_shares<T: Send>(share: impl Fn(usize, usize) -> T + Sync) -> Vec<T> {
+    #[cfg(any(
+        all(feature = "parallel", not(target_arch = "wasm32")),
+        all(feature = "wasm-threads", target_arch = "wasm32")
+    ))]
+    {
+        use rayon::prelude::*;
+
+        let threads = rayon::current_num_threads();
+        (0..threads)
+            .into_par_iter()
+            .map(|first| share(first, threads))
+            .collect()
+    }
+
+    #[cfg(not(any(
+        all(feature = "parallel", not(target_arch = "wasm32")),
+        all(feature = "wasm-threads", target_arch = "wasm32")
+    )))]
+    vec![share(0, 1)]
+}
+
+/// Counts the solutions below the root candidates at positions `first`, `first + step`, and so
+/// on, each share with a search of its own.
+fn count_share(game: &Game, mod_symmetry: bool, first: usize, step: usize) -> u64 {
+    let options = Options::for_counting();
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        let availability = BitAvailability::new(game);
+        run_count(game, &options, availability, mod_symmetry, first, step)
+    } else {
+        let availability = Availability::new(game);
+        run_count(game, &options, availability, mod_symmetry, first, step)
+    }
+}
+
+fn run_count<A: CellAvailability>(
+    game: &Game,
+    options: &Options,
+    availability: A,
+    mod_symmetry: bool,
+    first: usize,
+    step: usize,
+) -> u64 {
+    let mut search = Search::new(game, options, availability);
+
+    // A reflection of the board reflects its solutions too, so only the solutions with a queen in
+    // one half of the line it pins need visiting.
+    let symmetries = board_symmetries(game);
+    let pin = Pin::for_symmetries(game, &symmetries);
+    if let Some(pin) = pin {
+        for idx in pin.excluded_cells(game) {
+            search.availability.exclude(game, idx);
+        }
+    }
+
+    let mut visit = |solution: &[usize]| {
+        if mod_symmetry {
+            is_canonical(game, &symmetries, pin, solution) as u64
+        } else {
+            pin.map_or(1, |pin| pin.weight(game, solution))
+        }
+    };
+
+    if game.has_color_rule() {
+        search.count_share::<true>(first, step, u64::MAX, &mut visit)
+    } else {
+        search.count_share::<false>(first, step, u64::MAX, &mut visit)
+    }
+}
+
+/// Runs the strategy selected by the options.
+fn run_solver(game: &Game, options: &Options) -> Outcome {
+    let (options, heuristics) = options.for_game(game);
+    // Options from `Options::from_json` have already been checked to name a strategy.
+    let mut outcome = find_strategy(options.algorithm.as_deref())
+        .unwrap()
+        .solve(game, &options);
+    outcome.stats.heuristics = heuristics;
+
+    outcome
+}
+
+/// Checks a claimed solution when the options ask for it, turning the status into an internal
+/// error if it breaks any rule. Returns the rules broken.
+fn check_outcome(game: &Game, options: &Options, outcome: &mut Outcome) -> Vec<Violation> {
+    if !options.verifies() || outcome.status != Status::Solved {
+        return vec![];
+    }
+
+    let violations = verify_solution(game, &outcome.solution);
+    if !violations.is_empty() {
+        outcome.status = Status::InternalError;
+    }
+
+    violations
+}
+
+/// Searches `game` with the cells of `excluded` ruled out from the start.
+fn run_search<A: CellAvailability, N: NogoodCache>(
+    game: &Game,
+    options: &Options,
+    availability: A,
+    excluded: &[usize],
+) -> (Vec<usize>, SolveStats, RunEnd) {
+    let mut search = Search::<A, N>::with_cache(game, options, availability);
+    search.assume(&[], excluded);
+    let end = search.run();
+
+    let mut stats = search.stats;
+    search.nogoods.report(&mut stats);
+    #[cfg(feature = "profile")]
+    {
+        stats.profile = search.profiler.into_depths();
+    }
+
+    (search.solution, stats, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A board of `side` rows and columns without colors.
+    fn colorless(side: usize) -> Game {
+        Game::from_json(&format!(r#"{{"rows":{},"cols":{}}}"#, side, side)).unwrap()
+    }
+
+    /// Queens on every row and column, no two touching, are the permutations counted by OEIS
+    /// A002464.
+    #[test]
+    fn counts_colorless_solutions() {
+        for (side, count) in [(4, 2), (5, 14), (6, 90), (7, 646), (8, 5242)] {
+            let game = colorless(side);
+            assert!(!game.has_color_rule());
+            // Counted by rows, and by the search.
+            assert_eq!(run_counter(&game, false), count, "side {}", side);
+            assert_eq!(count_share(&game, false, 0, 1), count, "side {}", side);
+        }
+    }
+
+    /// However the root is split into shares, they count and enumerate what one search does, on
+    /// the corpus and colorless boards. `split_shares` uses threads with `--features parallel`.
+    #[test]
+    fn shares_count_like_one_search() {
+        let colorless = [r#"{"rows":6,"cols":6}"#, r#"{"rows":7,"cols":7}"#];
+        let boards = corpus::CORPUS
+            .iter()
+            .map(|entry| entry.json)
+            .chain(colorless);
+
+        for json in boards {
+            let game = Game::from_json(json).unwrap();
+            for mod_symmetry in [false, true] {
+                let one = count_share(&game, mod_symmetry, 0, 1);
+                let split: u64 =
+                    split_shares(|first, step| count_share(&game, mod_symmetry, first, step))
+                        .into_iter()
+                        .sum();
+                let thirds: u64 = (0..3)
+                    .map(|first| count_share(&game, mod_symmetry, first, 3))
+                    .sum();
+                assert_eq!(split, one, "{}", json);
+                assert_eq!(thirds, one, "{}", json);
+            }
+
+            // Any limit keeps enumeration on one thread.
+            assert_eq!(
+                enumerate_solutions(&game, &[], &[], u64::MAX),
+                enumerate_solutions(&game, &[], &[], u64::MAX - 1),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn solves_colorless_boards() {
+        let game = colorless(8);
+        let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+        assert_eq!(solutions.len(), 5242);
+        assert!(solutions
+            .iter()
+            .all(|solution| verify_solution(&game, solution).is_empty()));
+
+        // Dropping the colors of a board keeps its solution and adds others.
+        let colored = Game::from_json(corpus::CORPUS[0].json).unwrap();
+        let mut game = colored.clone();
+        game.colors.clear();
+        game.rules.colorless = true;
+        let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+        assert_eq!(solutions.len(), 5242);
+        assert!(solutions.contains(&corpus::CORPUS[0].solution.to_vec()));
+        assert_eq!(
+            enumerate_solutions(&colored, &[], &[], u64::MAX),
+            [corpus::CORPUS[0].solution.to_vec()]
+        );
+    }
+
+    /// The 9 by 9 board of the corpus with the bottom right of its color 4 made a tenth color.
+    fn ten_colors(all_colors_required: bool) -> Game {
+        let mut game = Game::from_json(corpus::CORPUS[1].json).unwrap();
+        for idx in [69, 70, 71, 79, 80] {
+            assert_eq!(game.idx_to_color[idx], 4);
+            game.idx_to_color[idx] = 9;
+        }
+        game.colors.push(9);
+        game.rules.all_colors_required = all_colors_required;
+        game
+    }
+
+    #[test]
+    fn solves_boards_with_more_colors_than_rows() {
+        let solution = corpus::CORPUS[1].solution.to_vec();
+
+        // Nine queens cannot fill ten colors.
+        let game = ten_colors(true);
+        assert!(Game::from_json(&game.to_json()).is_ok());
+        assert!(enumerate_solutions(&game, &[], &[], u64::MAX).is_empty());
+        assert_eq!(
+            run_solver(&game, &Options::default()).status,
+            Status::Unsolvable
+        );
+
+        // Leaving a color empty is allowed once not every color needs a queen, and the queens of
+        // the nine colors still solve it.
+        let game = ten_colors(false);
+        let game = Game::from_json(&game.to_json()).unwrap();
+        let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+        assert!(solutions.contains(&solution));
+        for solution in &solutions {
+            assert!(verify_solution(&game, solution).is_empty());
+            let mut colors: Vec<_> = solution.iter().map(|&idx| game.idx_to_color[idx]).collect();
+            colors.sort_unstable();
+            colors.dedup();
+            assert_eq!(colors.len(), 9);
+        }
+        for options in [Options::default(), Options::for_deduction()] {
+            let outcome = run_solver(&game, &options);
+            assert_eq!(outcome.status, Status::Solved);
+            assert!(verify_solution(&game, &outcome.solution).is_empty());
+        }
+    }
+
+    #[test]
+    fn rejects_fewer_colors_than_rows_when_all_are_required() {
+        let mut game = Game::from_json(corpus::CORPUS[1].json).unwrap();
+        for color in &mut game.idx_to_color {
+            *color = (*color).min(7);
+        }
+        game.colors.pop();
+        assert_eq!(
+            Game::from_json(&game.to_json()).unwrap_err(),
+            "Board has 8 colors but 9 rows, so not every color can hold a queen"
+        );
+
+        game.rules.all_colors_required = false;
+        assert!(Game::from_json(&game.to_json()).is_ok());
+    }
+
+    /// Boards with more columns than rows, where some columns stay empty.
+    const WIDE: &[&str] = &[
+        r#"{"rows":4,"cols":7}"#,
+        r#"{"rows":3,"cols":5,"colors":[0,1,2],"idxToColor":[
+            0,0,1,1,1, 0,2,2,2,1, 2,2,2,2,2]}"#,
+        r#"{"rows":3,"cols":5,"colors":[0,1,2],"idxToColor":[
+            0,0,1,1,1, 0,0,1,1,1, 2,2,2,2,2],"rules":{"topology":"hex"}}"#,
+    ];
+
+    /// Only rows must all hold a queen, so every column is optional once columns outnumber them.
+    #[test]
+    fn requires_columns_only_on_boards_as_wide_as_tall() {
+        assert_eq!(UsedTracker::new(3, 5, 3, true).required_cols, 0);
+        assert_eq!(UsedTracker::new(4, 4, 4, true).required_cols, 0b1111);
+
+        let mut used = UsedTracker::new(3, 5, 0, true);
+        for (row, col) in [(0, 4), (1, 0), (2, 2)] {
+            assert!(!used.is_solved());
+            used.set(row, col, 0, true);
+        }
+        assert!(used.is_solved());
+    }
+
+    #[test]
+    fn solves_and_counts_boards_wider_than_tall() {
+        for json in WIDE {
+            let game = Game::from_json(json).unwrap();
+            let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+            assert!(!solutions.is_empty(), "{}", json);
+            for solution in &solutions {
+                assert_eq!(verify_solution(&game, solution), vec![], "{}", json);
+            }
+
+            let count = solutions.len() as u64;
+            assert_eq!(run_counter(&game, false), count, "{}", json);
+            assert_eq!(count_share(&game, false, 0, 1), count, "{}", json);
+            assert_eq!(count_solutions(json.to_string()), Ok(count.to_string()));
+
+            let solution: Vec<usize> =
+                serde_json::from_str(&solve(json.to_string()).unwrap()).unwrap();
+            assert_eq!(solution.len(), game.rows, "{}", json);
+            assert_eq!(verify_solution(&game, &solution), vec![], "{}", json);
+        }
+    }
+
+    #[test]
+    fn rejects_boards_taller_than_wide() {
+        let error = "Board of 7x4 has more rows than columns, so not every row can hold a queen";
+        let json = r#"{"rows":7,"cols":4}"#;
+        assert_eq!(Game::from_json(json).unwrap_err(), error);
+        assert_eq!(solve(json.to_string()).unwrap_err(), error);
+        assert_eq!(count_solutions(json.to_string()).unwrap_err(), error);
+    }
+
+    /// A colorless 4 by 4 board where queens only threaten each other through `adjacency`.
+    fn with_adjacency(adjacency: &[&[usize]]) -> Game {
+        let mut game = colorless(4);
+        game.adjacency = Some(adjacency.iter().map(|list| list.to_vec()).collect());
+        game
+    }
+
+    #[test]
+    fn symmetrizes_custom_adjacency() {
+        // Cell 0 lists cell 5, and cell 6 lists cells 5 and 9, but neither is listed back.
+        let mut adjacency: Vec<&[usize]> = vec![&[]; 16];
+        adjacency[0] = &[5];
+        adjacency[6] = &[5, 9];
+        let mut game = with_adjacency(&adjacency);
+
+        assert_eq!(game.symmetrize_adjacency(), [(5, 0), (5, 6), (9, 6)]);
+        let symmetric = game.adjacency.clone().unwrap();
+        assert_eq!(symmetric[5], [0, 6]);
+        assert_eq!(symmetric[9], [6]);
+        assert!(game.symmetrize_adjacency().is_empty());
+
+        // Preparing the board does the same, so that a queen on 5 rules out 0 just as a queen on
+        // 0 rules out 5. Of the 24 ways to fill the rows and columns, the 2 with queens on 0 and
+        // 5 and the 2 with queens on 6 and 9 are lost.
+        let game = Game::from_json(&with_adjacency(&adjacency).to_json()).unwrap();
+        assert_eq!(game.adjacency.as_ref(), Some(&symmetric));
+        let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+        assert_eq!(solutions.len(), 20);
+        for solution in &solutions {
+            assert!(!(solution.contains(&0) && solution.contains(&5)));
+            assert!(verify_solution(&game, solution).is_empty());
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_adjacency() {
+        let mut adjacency: Vec<&[usize]> = vec![&[]; 16];
+        adjacency[3] = &[16];
+        assert_eq!(
+            Game::from_json(&with_adjacency(&adjacency).to_json()).unwrap_err(),
+            "Cell 3 has invalid neighbor 16: neighbors must be other cells in 0..16"
+        );
+
+        adjacency[3] = &[3];
+        assert!(Game::from_json(&with_adjacency(&adjacency).to_json()).is_err());
+        assert!(Game::from_json(&with_adjacency(&adjacency[1..]).to_json()).is_err());
+    }
+
+    /// The neighbors of `idx` in `lookup`, sorted.
+    fn neighbors_of(lookup: &AdjacentsLookup, idx: usize) -> Vec<u16> {
+        let mut neighbors = lookup.neighbors(idx).to_vec();
+        neighbors.sort_unstable();
+        neighbors
+    }
+
+    #[test]
+    fn packs_corner_and_edge_neighbors() {
+        // A 3 by 4 board:
+        //  0  1  2  3
+        //  4  5  6  7
+        //  8  9 10 11
+        let lookup = AdjacentsLookup::new(Topology::Square, 3, 4);
+        let expected: [&[u16]; 12] = [
+            &[5],
+            &[4, 6],
+            &[5, 7],
+            &[6],
+            &[1, 9],
+            &[0, 2, 8, 10],
+            &[1, 3, 9, 11],
+            &[2, 10],
+            &[5],
+            &[4, 6],
+            &[5, 7],
+            &[6],
+        ];
+        for (idx, expected) in expected.iter().enumerate() {
+            assert_eq!(neighbors_of(&lookup, idx), *expected, "cell {}", idx);
+        }
+        assert_eq!(lookup.offsets.len(), 13);
+        assert_eq!(lookup.neighbors.len(), 24);
+
+        // A single row or column has no diagonal neighbors at all, and the largest board keeps
+        // every index within a u16.
+        let row = AdjacentsLookup::new(Topology::Square, 1, 5);
+        assert!((0..5).all(|idx| row.neighbors(idx).is_empty()));
+        let column = AdjacentsLookup::new(Topology::Square, 5, 1);
+        assert!((0..5).all(|idx| column.neighbors(idx).is_empty()));
+        let last = MAX_UNITS * MAX_UNITS - 1;
+        let largest = AdjacentsLookup::new(Topology::Square, MAX_UNITS, MAX_UNITS);
+        assert_eq!(
+            neighbors_of(&largest, last),
+            [(last - MAX_UNITS - 1) as u16]
+        );
+    }
+
+    #[test]
+    fn shares_lookups_between_boards_of_one_shape() {
+        let game = colorless(6);
+        let first = AdjacentsLookup::for_game(&game);
+        assert!(Rc::ptr_eq(
+            &first,
+            &AdjacentsLookup::for_game(&colorless(6))
+        ));
+        assert!(!Rc::ptr_eq(
+            &first,
+            &AdjacentsLookup::for_game(&colorless(7))
+        ));
+
+        // A custom adjacency gets a lookup of its own.
+        let mut adjacency: Vec<&[usize]> = vec![&[]; 16];
+        adjacency[0] = &[15];
+        adjacency[15] = &[0];
+        let lookup = AdjacentsLookup::for_game(&with_adjacency(&adjacency));
+        assert_eq!(lookup.neighbors(0), [15]);
+        assert_eq!(lookup.neighbors(15), [0]);
+        assert!((1..15).all(|idx| lookup.neighbors(idx).is_empty()));
+    }
+
+    /// A nogood cache holding at most `limit` nogoods, and its counters.
+    fn nogoods(limit: Option<usize>) -> NoGoods {
+        let options = Options {
+            max_nogoods: limit,
+            ..Options::default()
+        };
+        NoGoods::new(&options)
+    }
+
+    fn nogood_stats(nogoods: &NoGoods) -> SolveStats {
+        let mut stats = SolveStats::default();
+        nogoods.report(&mut stats);
+        stats
+    }
+
+    #[test]
+    fn skips_duplicate_nogoods() {
+        let mut nogoods = nogoods(None);
+        assert!(nogoods.insert(&[1, 5, 9]));
+        assert!(!nogoods.insert(&[1, 5, 9]));
+        // Extending a stored nogood adds nothing either, but is told apart.
+        assert!(!nogoods.insert(&[1, 5, 9, 12]));
+        assert!(nogoods.insert(&[1, 5, 10]));
+
+        let stats = nogood_stats(&nogoods);
+        assert_eq!(stats.nogood_insertions, 2);
+        assert_eq!(stats.nogood_duplicates, 1);
+        assert_eq!(stats.nogood_subsumed, 1);
+        assert_eq!(stats.nogoods_stored, 2);
+        // The root, 1, 5, and the two leaves: the subsumed nogood left no node behind.
+        assert_eq!(stats.nogood_trie_nodes, 5);
+
+        // The empty nogood ends on the root, which the descent never checks.
+        assert!(nogoods.insert(&[]));
+        assert!(!nogoods.insert(&[]));
+        let stats = nogood_stats(&nogoods);
+        assert_eq!(stats.nogood_duplicates, 2);
+        assert_eq!(stats.nogood_pruned, 2);
+        assert_eq!(stats.nogoods_stored, 1);
+    }
+
+    /// Subset lookups count when they are made, and the lookups of a cursor once they are handed
+    /// back, with or without the signatures screening them.
+    #[test]
+    fn counts_lookups_and_hits() {
+        for signatures in [false, true] {
+            let options = Options {
+                nogood_signatures: signatures,
+                ..Options::default()
+            };
+            let mut nogoods = NoGoods::new(&options);
+            let empty = nogood_stats(&nogoods).nogood_bytes;
+            assert!(nogoods.insert(&[1, 5, 9]));
+            assert!(nogoods.insert(&[2, 7]));
+            assert!(nogood_stats(&nogoods).nogood_bytes > empty);
+
+            let mut path = vec![];
+            assert!(nogoods.find_subset(&[1, 5, 9, 12], &mut path));
+            assert!(!nogoods.find_subset(&[3, 4], &mut path));
+            let stats = nogood_stats(&nogoods);
+            assert_eq!((stats.nogood_lookups, stats.nogood_hits), (2, 1));
+
+            let mut cursor = nogoods.cursor(&[1, 5]);
+            assert!(cursor.hits(9));
+            assert!(!cursor.hits(10));
+            let lookups = cursor.counts();
+            assert_eq!((lookups.lookups, lookups.hits), (2, 1));
+            // Nothing is counted until the cursor's lookups come back.
+            assert_eq!(nogood_stats(&nogoods).nogood_lookups, 2);
+            nogoods.count_lookups(lookups);
+
+            let stats = nogood_stats(&nogoods);
+            assert_eq!((stats.nogood_lookups, stats.nogood_hits), (4, 2));
+            assert!(stats.nogood_walks_avoided <= stats.nogood_lookups - stats.nogood_hits);
+            if !signatures {
+                assert_eq!(stats.nogood_walks_avoided, 0);
+            }
+        }
+    }
+
+    /// The counters a real search reports add up: every stored nogood was inserted and neither
+    /// pruned nor evicted, and only lookups can hit.
+    #[test]
+    fn reports_consistent_nogood_stats() {
+        let entry = corpus::CORPUS
+            .iter()
+            .find(|entry| entry.name == "11x11")
+            .unwrap();
+        let game = Game::from_json(entry.json).unwrap();
+        for max_nogoods in [None, Some(4)] {
+            let options = Options {
+                max_nogoods,
+                ..Options::default()
+            };
+            let availability = Availability::new(&game);
+            let (_, stats, _) = run_search::<_, NoGoods>(&game, &options, availability, &[]);
+            assert!(stats.nogood_insertions > 0);
+            assert!(stats.nogood_lookups > 0);
+            assert!(0 < stats.nogood_hits && stats.nogood_hits <= stats.nogood_lookups);
+            // Evictions also count nogoods taken back out right after going in, which were never
+            // counted as inserted.
+            let kept = stats.nogood_insertions - stats.nogood_pruned;
+            if let Some(limit) = max_nogoods {
+                assert!(stats.nogood_evictions > 0);
+                assert!(stats.nogoods_stored <= limit);
+                assert!(stats.nogoods_stored + stats.nogood_evictions >= kept);
+                assert!(stats.nogoods_stored <= kept);
+            } else {
+                assert_eq!(stats.nogood_evictions, 0);
+                assert_eq!(stats.nogoods_stored, kept);
+            }
+            assert!(stats.nogood_trie_nodes > stats.nogoods_stored);
+            assert!(stats.nogood_bytes > 0);
+        }
+    }
+
+    /// A nogood ending on an inner node of the trie turns it into a leaf. Later inserts must then
+    /// meet that leaf on the way down, and not the nodes that were below it.
+    #[test]
+    fn marks_inner_nodes_as_leaves() {
+        let mut nogoods = nogoods(None);
+        assert!(nogoods.insert(&[1, 5, 9]));
+        assert!(nogoods.insert(&[1, 5, 10]));
+        assert!(nogoods.insert(&[1, 6]));
+        assert!(nogoods.insert(&[1, 5]));
+
+        let stats = nogood_stats(&nogoods);
+        assert_eq!(stats.nogood_pruned, 2);
+        assert_eq!(stats.nogoods_stored, 2);
+        assert_eq!(stats.nogood_trie_nodes, 4);
+
+        assert!(!nogoods.insert(&[1, 5]));
+        assert!(!nogoods.insert(&[1, 5, 9]));
+        assert!(!nogoods.insert(&[1, 5, 11]));
+        let stats = nogood_stats(&nogoods);
+        assert_eq!(stats.nogood_duplicates, 1);
+        assert_eq!(stats.nogood_subsumed, 2);
+        assert_eq!(stats.nogood_trie_nodes, 4);
+
+        let mut path = vec![];
+        assert!(nogoods.find_subset(&[0, 1, 5, 9], &mut path));
+        assert_eq!(path, [1, 5]);
+        assert!(nogoods.find_subset(&[1, 3, 6], &mut path));
+        assert_eq!(path, [1, 6]);
+        assert!(!nogoods.find_subset(&[1, 9, 10], &mut path));
+
+        // The prefix lookups of the search stop on the inner leaf too.
+        let mut cursor = nogoods.cursor(&[1]);
+        assert!(cursor.hits(5));
+        assert!(!cursor.hits(9));
+    }
+
+    /// The new nogood goes in before anything is evicted, so the nogoods it prunes make room for
+    /// it, and one that would be the first to go is taken back out.
+    #[test]
+    fn evicts_after_inserting() {
+        let mut nogoods = nogoods(Some(2));
+        assert!(nogoods.insert(&[1, 5, 9]));
+        assert!(nogoods.insert(&[2, 7, 11]));
+
+        // Full, but pruning [1, 5, 9] leaves room.
+        assert!(nogoods.insert(&[1, 5]));
+        let stats = nogood_stats(&nogoods);
+        assert_eq!(stats.nogood_evictions, 0);
+        assert_eq!(stats.nogood_pruned, 1);
+        assert_eq!(stats.nogoods_stored, 2);
+
+        // A shorter nogood evicts the longest one.
+        assert!(nogoods.insert(&[3]));
+        let mut path = vec![];
+        assert!(!nogoods.find_subset(&[2, 7, 11], &mut path));
+        assert!(nogoods.find_subset(&[1, 5], &mut path));
+
+        // One longer than every stored nogood leaves no trace.
+        let before = nogood_stats(&nogoods).nogood_trie_nodes;
+        assert!(!nogoods.insert(&[4, 8, 12]));
+        assert!(!nogoods.find_subset(&[4, 8, 12], &mut path));
+        let stats = nogood_stats(&nogoods);
+        assert_eq!(stats.nogood_evictions, 2);
+        assert_eq!(stats.nogood_insertions, 4);
+        assert_eq!(stats.nogoods_stored, 2);
+        assert_eq!(stats.nogood_trie_nodes, before);
+    }
+
+    /// Colors 0 to 3 of a 4 by 4 board, solved by singles alone: the lone cell of color 1 takes
+    /// the first queen, which leaves one spot in row 1, then in row 2, then in row 3.
+    const STAIRS: &str = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+        0,1,2,2,
+        0,0,0,2,
+        0,3,3,2,
+        3,3,3,3
+    ]}"#;
+
+    #[test]
+    fn orders_queens_by_deduction() {
+        let output = solve_with_options(STAIRS.into(), r#"{"order":"deduction"}"#.into()).unwrap();
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(output["solution"], serde_json::json!([1, 7, 8, 14]));
+        let placements: Vec<_> = output["placements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|placement| {
+                assert_eq!(placement["technique"], "singles");
+                (
+                    placement["queen"].as_u64().unwrap(),
+                    placement["step"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(placements, [(1, 0), (7, 1), (8, 2), (14, 3)]);
+
+        // The steps the placements point at.
+        let game = Game::from_json(STAIRS).unwrap();
+        let steps: Vec<_> = walk_through(&game, &BoardState::default())
+            .unwrap()
+            .into_iter()
+            .map(|step| (step.key, step.args, step.queen))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                ("singleInColor", vec![1], Some(1)),
+                ("singleInRow", vec![1], Some(7)),
+                ("singleInRow", vec![2], Some(8)),
+                ("singleInRow", vec![3], Some(14)),
+            ]
+        );
+
+        // Only the deduction order comes with placements.
+        let output = solve_with_options(STAIRS.into(), "{}".into()).unwrap();
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(output.get("placements").is_none());
+    }
+
+    /// Runs in a dedicated worker, as rayon may not block the main thread of a page, with the
+    /// nightly build of `wasm-threads`, e.g.
+    /// RUSTFLAGS="-C target-feature=+atomics,+bulk-memory" rustup run nightly \
+    ///   wasm-pack test --headless --chrome -- --features wasm-threads -Z build-std=panic_abort,std
+    #[cfg(all(feature = "wasm-threads", target_arch = "wasm32"))]
+    mod threads {
+        use super::*;
+        use wasm_bindgen_futures::JsFuture;
+        use wasm_bindgen_test::*;
+
+        wasm_bindgen_test_configure!(run_in_dedicated_worker);
+
+        /// The workers split the search, so they must find what one thread finds.
+        #[wasm_bindgen_test]
+        async fn counts_on_workers_like_one_thread() {
+            JsFuture::from(init_thread_pool(4)).await.unwrap();
+
+            for entry in corpus::CORPUS {
+                let game = Game::from_json(entry.json).unwrap();
+                let parallel: u64 =
+                    split_shares(|first, step| count_share(&game, false, first, step))
+                        .into_iter()
+                        .sum();
+                assert_eq!(parallel, count_share(&game, false, 0, 1));
+
+                // Any limit keeps enumeration on one thread.
+                assert_eq!(
+                    enumerate_solutions(&game, &[], &[], u64::MAX),
+                    enumerate_solutions(&game, &[], &[], u64::MAX - 1)
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,247 @@
+use crate::availability::CellAvailability;
+use crate::search::Search;
+use crate::{Game, NogoodCache};
+use serde::{Deserialize, Serialize};
+
+/// A side constraint tying two cells together, from the game's optional `links`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct Link {
+    pub kind: LinkKind,
+    pub cells: [usize; 2],
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum LinkKind {
+    /// Both cells hold queens or neither does.
+    Same,
+    /// At most one of the cells holds a queen. Such links make the cells neighbors, so they
+    /// are honored wherever adjacency is.
+    AtMostOne,
+}
+
+impl Game {
+    /// The cells of every link asking for both queens or neither.
+    pub(crate) fn same_links(&self) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.links
+            .iter()
+            .filter(|link| link.kind == LinkKind::Same)
+            .map(|link| link.cells)
+    }
+
+    pub(crate) fn has_same_links(&self) -> bool {
+        self.same_links().next().is_some()
+    }
+
+    /// Makes the cells of every `AtMostOne` link neighbors, spelling out the neighbors of the
+    /// topology first if the board has no custom adjacency.
+    pub(crate) fn adjoin_links(&mut self) {
+        let mut pairs = self
+            .links
+            .iter()
+            .filter(|link| link.kind == LinkKind::AtMostOne)
+            .map(|link| link.cells)
+            .peekable();
+        if pairs.peek().is_none() {
+            return;
+        }
+
+        let (rows, cols) = (self.rows, self.cols);
+        let topology = self.rules.topology;
+        let adjacency = self.adjacency.get_or_insert_with(|| {
+            (0..rows * cols)
+                .map(|idx| topology.neighbors(rows, cols, idx))
+                .collect()
+        });
+
+        for [a, b] in pairs {
+            if !adjacency[a].contains(&b) {
+                adjacency[a].push(b);
+            }
+            if !adjacency[b].contains(&a) {
+                adjacency[b].push(a);
+            }
+        }
+    }
+}
+
+impl<A: CellAvailability, N: NogoodCache> Search<'_, A, N> {
+    /// Whether every row, column, and color has its queen, and no link is broken.
+    #[inline(always)]
+    pub fn is_solved(&self) -> bool {
+        self.used.is_solved() && !self.breaks_link()
+    }
+
+    /// Whether some `Same` link has a queen on one cell while the other is ruled out, so that no
+    /// solution can follow.
+    #[inline(always)]
+    pub fn breaks_link(&self) -> bool {
+        self.game.same_links().any(|[a, b]| {
+            let (queen_a, queen_b) = (self.solution.contains(&a), self.solution.contains(&b));
+            (queen_a && !queen_b && !self.availability.is_available(b))
+                || (queen_b && !queen_a && !self.availability.is_available(a))
+        })
+    }
+
+    /// Whether a queen on the candidate `idx` would break a `Same` link at once, its partner
+    /// already being ruled out.
+    #[inline(always)]
+    pub fn strands_partner(&self, idx: usize) -> bool {
+        self.game.same_links().any(|[a, b]| {
+            let partner = if idx == a {
+                b
+            } else if idx == b {
+                a
+            } else {
+                return false;
+            };
+
+            !self.availability.is_available(partner) && !self.solution.contains(&partner)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Options;
+    use crate::strategy::Status;
+    use crate::verify::{verify_solution, Violation};
+    use crate::{enumerate_solutions, run_counter, run_solver};
+
+    /// A colorless 4x4 board with `links`, whose only solutions without them are [1, 7, 8, 14]
+    /// and [2, 4, 11, 13].
+    fn linked(links: &str) -> Game {
+        Game::from_json(&format!(r#"{{"rows":4,"cols":4,"links":[{}]}}"#, links)).unwrap()
+    }
+
+    /// The solutions of `game` found by every way of solving and counting it, checking that they
+    /// agree.
+    fn solutions(game: &Game) -> Vec<Vec<usize>> {
+        let solutions = enumerate_solutions(game, &[], &[], u64::MAX);
+        assert_eq!(run_counter(game, false), solutions.len() as u64);
+
+        let presets = [
+            Options::default(),
+            Options::for_solve(),
+            Options::for_deduction(),
+            Options::for_counting(),
+        ];
+        for options in &presets {
+            let outcome = run_solver(game, options);
+            if solutions.is_empty() {
+                assert_eq!(outcome.status, Status::Unsolvable);
+            } else {
+                assert_eq!(outcome.status, Status::Solved);
+                let mut solution = outcome.solution.clone();
+                solution.sort_unstable();
+                assert!(solutions.contains(&solution), "{:?}", outcome.solution);
+            }
+        }
+
+        solutions
+    }
+
+    #[test]
+    fn same_links_decide_the_solution() {
+        assert_eq!(solutions(&linked("")).len(), 2);
+
+        // The first solution has a queen on 1 but not on 0, and the second on neither.
+        let game = linked(r#"{"kind":"same","cells":[1,0]}"#);
+        assert_eq!(solutions(&game), [vec![2, 4, 11, 13]]);
+        let game = linked(r#"{"kind":"same","cells":[3,13]}"#);
+        assert_eq!(solutions(&game), [vec![1, 7, 8, 14]]);
+        // Both cells hold queens in the first solution, and neither does in the second.
+        let game = linked(r#"{"kind":"same","cells":[1,7]}"#);
+        assert_eq!(solutions(&game).len(), 2);
+
+        // Each solution has a queen on just one of the cells.
+        let game = linked(r#"{"kind":"same","cells":[1,4]}"#);
+        assert!(solutions(&game).is_empty());
+    }
+
+    #[test]
+    fn at_most_one_links_decide_the_solution() {
+        let game = linked(r#"{"kind":"atMostOne","cells":[2,11]}"#);
+        assert_eq!(solutions(&game), [vec![1, 7, 8, 14]]);
+        // No solution has queens on both cells, so nothing changes.
+        let game = linked(r#"{"kind":"atMostOne","cells":[1,4]}"#);
+        assert_eq!(solutions(&game).len(), 2);
+    }
+
+    /// On a bigger board, linking two cells keeps exactly the solutions that honor the link.
+    #[test]
+    fn links_keep_the_solutions_honoring_them() {
+        let unlinked = Game::from_json(r#"{"rows":6,"cols":6}"#).unwrap();
+        let all = solutions(&unlinked);
+        assert_eq!(all.len(), 90);
+
+        for [a, b] in [[0, 35], [1, 8], [7, 22], [14, 21], [3, 33]] {
+            for (kind, honors) in [
+                (
+                    "same",
+                    (|a: bool, b: bool| a == b) as fn(bool, bool) -> bool,
+                ),
+                ("atMostOne", |a, b| !(a && b)),
+            ] {
+                let game = Game::from_json(&format!(
+                    r#"{{"rows":6,"cols":6,"links":[{{"kind":"{}","cells":[{},{}]}}]}}"#,
+                    kind, a, b
+                ))
+                .unwrap();
+                let expected: Vec<_> = all
+                    .iter()
+                    .filter(|solution| honors(solution.contains(&a), solution.contains(&b)))
+                    .cloned()
+                    .collect();
+                assert_eq!(solutions(&game), expected, "{} {} {}", kind, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn verifying_reports_broken_links() {
+        let game = linked(r#"{"kind":"same","cells":[1,4]}"#);
+        assert_eq!(
+            verify_solution(&game, &[1, 7, 8, 14]),
+            [Violation::Unlinked {
+                first: 1,
+                second: 4
+            }]
+        );
+        assert_eq!(
+            verify_solution(&game, &[2, 4, 11, 13]),
+            [Violation::Unlinked {
+                first: 1,
+                second: 4
+            }]
+        );
+
+        let game = linked(r#"{"kind":"atMostOne","cells":[2,11]}"#);
+        assert_eq!(
+            verify_solution(&game, &[2, 4, 11, 13]),
+            [Violation::Adjacent {
+                first: 2,
+                second: 11
+            }]
+        );
+        assert!(verify_solution(&game, &[1, 7, 8, 14]).is_empty());
+    }
+
+    #[test]
+    fn rejects_links_off_the_board_or_to_themselves() {
+        for cells in ["[3,3]", "[0,16]", "[16,2]"] {
+            let json = format!(
+                r#"{{"rows":4,"cols":4,"links":[{{"kind":"same","cells":{}}}]}}"#,
+                cells
+            );
+            let error = Game::from_json(&json).unwrap_err();
+            assert!(error.starts_with("Link between cells"), "{}", error);
+            assert!(error.ends_with("two different cells in 0..16"), "{}", error);
+        }
+
+        let json = r#"{"rows":4,"cols":4,"links":[{"kind":"never","cells":[0,1]}]}"#;
+        assert!(Game::from_json(json).is_err());
+    }
+}
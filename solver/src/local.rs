@@ -0,0 +1,282 @@
+use crate::options::Options;
+use crate::stats::SolveStats;
+use crate::utils::Rng;
+use crate::{AdjacentsLookup, Game};
+use std::rc::Rc;
+
+/// Min-conflicts local search, which tends to find solutions of large boards with many of them
+/// quickly, but cannot prove that a board has none.
+///
+/// Every row holds one queen throughout. Each step takes a random row whose queen is attacked and
+/// moves it to the column where it is attacked least, or now and then to a random column so the
+/// search can walk out of local minima. A run that has not found a solution within its step
+/// budget starts over from a fresh random placement. Returns `None` once every run gave up.
+pub(crate) fn min_conflicts(
+    game: &Game,
+    options: &Options,
+    stats: &mut SolveStats,
+) -> Option<Vec<usize>> {
+    let local = &options.local;
+
+    // One queen per color cannot cover more colors than rows.
+    if game.has_color_rule() && game.rules.all_colors_required && game.colors.len() > game.rows {
+        return None;
+    }
+
+    // Moves go by attacks, which say nothing about queens that need a partner.
+    if game.has_same_links() {
+        return None;
+    }
+
+    let mut rng = Rng::new(options.seed);
+    let mut board = LocalBoard::new(game);
+    let mut conflicted = vec![];
+
+    for run in 0..=local.restarts {
+        if run > 0 {
+            stats.restarts += 1;
+        }
+        board.scatter(&mut rng);
+
+        for _ in 0..local.steps {
+            conflicted.clear();
+            conflicted.extend((0..game.rows).filter(|&row| board.conflicts(row) > 0));
+
+            if conflicted.is_empty() {
+                return Some(board.solution());
+            }
+
+            stats.local_steps += 1;
+            let row = conflicted[rng.below(conflicted.len())];
+            let col = if rng.next_f64() < local.walk {
+                rng.below(game.cols)
+            } else {
+                board.least_attacked_col(row, &mut rng)
+            };
+            board.lift(row);
+            board.put(row, col);
+        }
+    }
+
+    if board.is_solved() {
+        return Some(board.solution());
+    }
+
+    None
+}
+
+/// One queen per row, with counts of the queens in every column and color so that how often a
+/// cell is attacked takes only a look at its neighbors.
+struct LocalBoard<'a> {
+    game: &'a Game,
+    adj_lookup: Rc<AdjacentsLookup>,
+    /// The column of every row's queen.
+    queens: Vec<usize>,
+    col_queens: Vec<usize>,
+    color_queens: Vec<usize>,
+    occupied: Vec<bool>,
+}
+
+impl<'a> LocalBoard<'a> {
+    fn new(game: &'a Game) -> Self {
+        Self {
+            game,
+            adj_lookup: AdjacentsLookup::for_game(game),
+            queens: vec![0; game.rows],
+            col_queens: vec![0; game.cols],
+            color_queens: vec![0; game.num_colors()],
+            occupied: vec![false; game.rows * game.cols],
+        }
+    }
+
+    /// Puts every row's queen on a random column.
+    fn scatter(&mut self, rng: &mut Rng) {
+        self.col_queens.iter_mut().for_each(|count| *count = 0);
+        self.color_queens.iter_mut().for_each(|count| *count = 0);
+        self.occupied
+            .iter_mut()
+            .for_each(|occupied| *occupied = false);
+
+        for row in 0..self.game.rows {
+            self.put(row, rng.below(self.game.cols));
+        }
+    }
+
+    fn put(&mut self, row: usize, col: usize) {
+        let idx = row * self.game.cols + col;
+        self.queens[row] = col;
+        self.col_queens[col] += 1;
+        if self.game.has_color_rule() {
+            self.color_queens[self.game.idx_to_color[idx]] += 1;
+        }
+        self.occupied[idx] = true;
+    }
+
+    fn lift(&mut self, row: usize) {
+        let col = self.queens[row];
+        let idx = row * self.game.cols + col;
+        self.col_queens[col] -= 1;
+        if self.game.has_color_rule() {
+            self.color_queens[self.game.idx_to_color[idx]] -= 1;
+        }
+        self.occupied[idx] = false;
+    }
+
+    /// How many queens attack `idx`, which must not hold one.
+    fn attacks(&self, idx: usize) -> usize {
+        let game = self.game;
+
+        let mut attacks = self.col_queens[idx % game.cols];
+        if game.has_color_rule() {
+            attacks += self.color_queens[game.idx_to_color[idx]];
+        }
+
+        attacks
+            + self
+                .adj_lookup
+                .neighbors(idx)
+                .iter()
+                .filter(|&&neighbor| self.occupied[neighbor as usize])
+                .count()
+    }
+
+    /// How many queens attack the queen of `row`.
+    fn conflicts(&mut self, row: usize) -> usize {
+        let col = self.queens[row];
+        self.lift(row);
+        let conflicts = self.attacks(row * self.game.cols + col);
+        self.put(row, col);
+
+        conflicts
+    }
+
+    /// The column where the queen of `row` would be attacked least, choosing at random among
+    /// ties.
+    fn least_attacked_col(&mut self, row: usize, rng: &mut Rng) -> usize {
+        let col = self.queens[row];
+        self.lift(row);
+
+        let (mut best, mut best_attacks, mut ties) = (0, usize::MAX, 0);
+        for candidate in 0..self.game.cols {
+            let attacks = self.attacks(row * self.game.cols + candidate);
+            if attacks < best_attacks {
+                best = candidate;
+                best_attacks = attacks;
+                ties = 1;
+            } else if attacks == best_attacks {
+                // Keeps each tied column with equal probability.
+                ties += 1;
+                if rng.below(ties) == 0 {
+                    best = candidate;
+                }
+            }
+        }
+
+        self.put(row, col);
+        best
+    }
+
+    fn is_solved(&mut self) -> bool {
+        (0..self.game.rows).all(|row| self.conflicts(row) == 0)
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        self.queens
+            .iter()
+            .enumerate()
+            .map(|(row, &col)| row * self.game.cols + col)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::options::LocalSearch;
+    use crate::verify::verify_solution;
+
+    fn seeded(seed: u64, steps: usize, restarts: usize) -> Options {
+        Options {
+            seed,
+            local: LocalSearch {
+                steps,
+                restarts,
+                ..LocalSearch::default()
+            },
+            ..Options::default()
+        }
+    }
+
+    /// Whatever the seed, a solution found is one the rules allow, so on the small corpus
+    /// boards it is their only one, and none is ever found on a board without any.
+    #[test]
+    fn only_returns_valid_solutions() {
+        for entry in CORPUS {
+            let game = Game::from_json(entry.json).unwrap();
+            if game.rows > 10 {
+                continue;
+            }
+
+            for seed in 0..4 {
+                let mut stats = SolveStats::default();
+                let Some(mut solution) = min_conflicts(&game, &seeded(seed, 2000, 4), &mut stats)
+                else {
+                    continue;
+                };
+                assert_eq!(verify_solution(&game, &solution), vec![], "{}", entry.name);
+                solution.sort_unstable();
+                assert_eq!(solution, entry.solution, "{} seed {}", entry.name, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn solves_small_boards() {
+        for json in [CORPUS[0].json, r#"{"rows":6,"cols":6}"#] {
+            let game = Game::from_json(json).unwrap();
+            let found = (0..4).find_map(|seed| {
+                min_conflicts(&game, &seeded(seed, 10_000, 20), &mut SolveStats::default())
+            });
+            let solution = found.unwrap_or_else(|| panic!("no solution for {}", json));
+            assert_eq!(verify_solution(&game, &solution), vec![], "{}", json);
+        }
+    }
+
+    /// Attacks say nothing about a queen's partner, so a board with Same links gets no answer
+    /// rather than one that may break them.
+    #[test]
+    fn gives_up_on_same_links() {
+        let game =
+            Game::from_json(r#"{"rows":6,"cols":6,"links":[{"kind":"same","cells":[1,9]}]}"#)
+                .unwrap();
+        let mut stats = SolveStats::default();
+
+        assert_eq!(min_conflicts(&game, &seeded(0, 2000, 4), &mut stats), None);
+        assert_eq!(stats.local_steps, 0);
+    }
+
+    #[test]
+    fn gives_up_on_unsatisfiable_colors() {
+        // More colors than rows: no search at all.
+        let game = Game::from_json(
+            r#"{"rows":4,"cols":4,"colors":[0,1,2,3,4],"idxToColor":[
+                0,0,1,1, 0,0,1,1, 2,2,3,3, 2,2,3,4]}"#,
+        )
+        .unwrap();
+        let mut stats = SolveStats::default();
+        assert_eq!(min_conflicts(&game, &seeded(0, 2000, 4), &mut stats), None);
+        assert_eq!(stats.local_steps, 0);
+
+        // A color only on a corner, which no 4x4 placement of queens uses, so the budget runs
+        // out.
+        let game = Game::from_json(
+            r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+                0,1,1,1, 1,1,1,2, 2,2,2,3, 3,3,3,3]}"#,
+        )
+        .unwrap();
+        let mut stats = SolveStats::default();
+        assert_eq!(min_conflicts(&game, &seeded(0, 200, 2), &mut stats), None);
+        assert_eq!(stats.restarts, 2);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::constrained::find_solution;
+use crate::options::Options;
+use crate::search::Search;
+use crate::{enumerate_solutions, Game};
+use serde::{Deserialize, Serialize};
+
+/// What the user has put on the board, from the `state_json` of `check_marks`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct BoardState {
+    /// The cells holding the user's queens.
+    pub queens: Vec<usize>,
+    /// The cells the user has marked with an X, as holding no queen.
+    pub marks: Vec<usize>,
+}
+
+impl BoardState {
+    pub fn from_json(state_json: &str) -> Result<Self, String> {
+        serde_json::from_str(state_json).map_err(|e| format!("Invalid state JSON: {}", e))
+    }
+
+    pub fn validate(&self, game: &Game) -> Result<(), String> {
+        let cells = game.rows * game.cols;
+        let mut seen = vec![false; cells];
+
+        for &idx in self.queens.iter().chain(&self.marks) {
+            if idx >= cells {
+                return Err(format!(
+                    "Cell {} is not on the board, which has cells 0..{}",
+                    idx, cells
+                ));
+            }
+
+            if std::mem::replace(&mut seen[idx], true) {
+                return Err(format!(
+                    "Cell {} appears more than once among the queens and marks",
+                    idx
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The user's X marks sorted by how they stand, each in exactly one list, in the order given.
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MarkCheck {
+    /// Marks on cells holding a queen in every solution.
+    pub wrong: Vec<usize>,
+    /// Marks on cells one of the user's queens threatens.
+    pub implied: Vec<usize>,
+    /// Marks on cells the deductions rule out, given the user's queens.
+    pub deduced: Vec<usize>,
+    /// The other marks, which nothing on the board shows yet. On boards with several solutions
+    /// these include cells only some of the solutions need.
+    pub premature: Vec<usize>,
+}
+
+/// Sorts the marks of `state` into wrong, implied, deduced, and premature ones. Fails when the
+/// board has no solution.
+pub(crate) fn judge_marks(game: &Game, state: &BoardState) -> Result<MarkCheck, String> {
+    state.validate(game)?;
+
+    if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        check(game, state, BitAvailability::new(game))
+    } else {
+        check(game, state, Availability::new(game))
+    }
+}
+
+fn check<A: CellAvailability>(
+    game: &Game,
+    state: &BoardState,
+    availability: A,
+) -> Result<MarkCheck, String> {
+    let solutions = enumerate_solutions(game, &[], &[], 2);
+    if solutions.is_empty() {
+        return Err("Board has no solution".to_string());
+    }
+
+    // A cell missing from some solution is not needed by all of them. When there are several,
+    // the cells the two found share are only needed if no solution can do without them.
+    let is_needed = |idx: usize| {
+        solutions.iter().all(|solution| solution.contains(&idx))
+            && (solutions.len() == 1 || find_solution(game, &[], &[idx]).is_none())
+    };
+
+    let deduction = Options::for_deduction();
+    let mut search = Search::new(game, &deduction, availability);
+
+    // Deductions from queens that threaten each other would rule out the whole board.
+    let mut consistent = true;
+    for &idx in &state.queens {
+        consistent &= search.availability.is_available(idx);
+        search.push(idx);
+        search.set_queen(idx, true);
+    }
+
+    let threatened: Vec<_> = state
+        .marks
+        .iter()
+        .map(|&idx| !search.availability.is_available(idx))
+        .collect();
+
+    if consistent {
+        if game.has_color_rule() {
+            search.propagate::<true>();
+        } else {
+            search.propagate::<false>();
+        }
+    }
+
+    let mut check = MarkCheck::default();
+    for (&idx, &threatened) in state.marks.iter().zip(&threatened) {
+        let list = if is_needed(idx) {
+            &mut check.wrong
+        } else if threatened {
+            &mut check.implied
+        } else if consistent
+            && !search.availability.is_available(idx)
+            && !search.solution.contains(&idx)
+        {
+            &mut check.deduced
+        } else {
+            &mut check.premature
+        };
+        list.push(idx);
+    }
+
+    Ok(check)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check_marks;
+    use crate::corpus::CORPUS;
+
+    fn checked(game: &Game, queens: &[usize], marks: &[usize]) -> MarkCheck {
+        let state = BoardState {
+            queens: queens.to_vec(),
+            marks: marks.to_vec(),
+        };
+        judge_marks(game, &state).unwrap()
+    }
+
+    /// Marking every other cell of the 8x8 board with the queen on 9 placed: the queens of the
+    /// solution are wrong, the cells 9 attacks implied, and the rest deduced but for four.
+    #[test]
+    fn sorts_the_marks_of_a_board_with_one_solution() {
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        let marks: Vec<_> = (0..64).filter(|&idx| idx != 9).collect();
+        let check = checked(&game, &[9], &marks);
+
+        assert_eq!(check.wrong, [4, 19, 29, 39, 42, 54, 56]);
+        assert_eq!(
+            check.implied,
+            [0, 1, 2, 8, 10, 11, 12, 13, 14, 15, 16, 17, 18, 25, 33, 41, 49, 57]
+        );
+        assert_eq!(check.premature, [34, 46, 47, 50]);
+        assert_eq!(
+            check.wrong.len() + check.implied.len() + check.deduced.len() + check.premature.len(),
+            marks.len()
+        );
+
+        // Without queens nothing is implied, and marks keep the order given.
+        let check = checked(&game, &[], &[8, 56, 0, 4]);
+        assert_eq!(check.wrong, [56, 4]);
+        assert!(check.implied.is_empty());
+    }
+
+    /// With several solutions only the cells all of them need are wrong to mark.
+    #[test]
+    fn sorts_the_marks_of_a_board_with_several_solutions() {
+        let mut game = Game::from_json(CORPUS[0].json).unwrap();
+        // Five solutions, all with queens on 4 and 56 only.
+        game.idx_to_color[33] = 1;
+        assert_eq!(enumerate_solutions(&game, &[], &[], u64::MAX).len(), 5);
+
+        let check = checked(&game, &[], &[4, 9, 10, 33, 56]);
+        assert_eq!(check.wrong, [4, 56]);
+        assert!(check.implied.is_empty());
+        for idx in [9, 10, 33] {
+            assert!(check.deduced.contains(&idx) || check.premature.contains(&idx));
+        }
+
+        // On the colorless 4x4 no cell is in both solutions.
+        let game = Game::from_json(r#"{"rows":4,"cols":4}"#).unwrap();
+        let check = checked(&game, &[], &[1, 2, 7]);
+        assert!(check.wrong.is_empty());
+        assert_eq!(check.premature, [1, 2, 7]);
+    }
+
+    /// Queens attacking each other still imply marks, but nothing is deduced from them.
+    #[test]
+    fn deduces_nothing_from_conflicting_queens() {
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        let marks: Vec<_> = (16..64).collect();
+        let check = checked(&game, &[0, 1], &marks);
+        assert!(check.deduced.is_empty());
+        assert!(check.implied.contains(&16) && check.implied.contains(&17));
+    }
+
+    #[test]
+    fn rejects_bad_states_and_boards_without_solutions() {
+        let json = CORPUS[0].json.to_string();
+        let error = check_marks(json.clone(), r#"{"marks":[64]}"#.to_string()).unwrap_err();
+        assert_eq!(error, "Cell 64 is not on the board, which has cells 0..64");
+        let error = check_marks(json.clone(), r#"{"marks":[3,3]}"#.to_string()).unwrap_err();
+        assert_eq!(
+            error,
+            "Cell 3 appears more than once among the queens and marks"
+        );
+        let error = check_marks(json.clone(), r#"{"xs":[]}"#.to_string()).unwrap_err();
+        assert!(error.starts_with("Invalid state JSON"), "{}", error);
+
+        let unsolvable = CORPUS
+            .iter()
+            .find(|entry| entry.name == "unsolvable-9x9")
+            .unwrap();
+        let error = check_marks(unsolvable.json.to_string(), "{}".to_string()).unwrap_err();
+        assert_eq!(error, "Board has no solution");
+
+        assert_eq!(
+            check_marks(json, r#"{"queens":[9],"marks":[8,56]}"#.to_string()).unwrap(),
+            r#"{"wrong":[56],"implied":[8],"deduced":[],"premature":[]}"#
+        );
+    }
+}
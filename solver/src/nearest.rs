@@ -0,0 +1,207 @@
+use crate::constrained::find_solution;
+use crate::{enumerate_solutions, Game};
+use serde::Serialize;
+
+/// How many solutions `find_nearest_solution` compares the user's queens against when none of
+/// them has all the queens. Boards with more are rare, and far from published ones.
+const MAX_COMPARED_SOLUTIONS: u64 = 1000;
+
+/// The solution closest to the user's queens, and how to get there from them.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Correction {
+    /// The cells of the solution, sorted.
+    pub solution: Vec<usize>,
+    /// The user's queens missing from the solution, sorted.
+    pub remove: Vec<usize>,
+    /// The queens of the solution the user has not placed, sorted.
+    pub add: Vec<usize>,
+    /// Whether no solution shares more cells with the user's queens. False only when the board
+    /// has more solutions than were compared.
+    pub exact: bool,
+}
+
+/// Finds the solution sharing the most cells with the queens on `placed`, which means moving the
+/// fewest queens since every solution has as many. Ties are broken the same way every time, so
+/// the same queens always get the same correction: toward the first solution the search finds
+/// with all of them, or else the least in sorted order.
+pub(crate) fn find_nearest_solution(game: &Game, placed: &[usize]) -> Result<Correction, String> {
+    let cells = game.rows * game.cols;
+    let mut placed = placed.to_vec();
+    placed.sort_unstable();
+    if let Some(&idx) = placed.iter().find(|&&idx| idx >= cells) {
+        return Err(format!(
+            "Cell {} is not on the board, which has cells 0..{}",
+            idx, cells
+        ));
+    }
+    if let Some(pair) = placed.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(format!("Cell {} holds more than one queen", pair[0]));
+    }
+
+    // A solution with every queen already placed only asks for more, which no other beats.
+    // Otherwise the queens are compared against the solutions one by one.
+    let (solution, exact) = match find_solution(game, &placed, &[]) {
+        Some(solution) => (solution, true),
+        None => {
+            let solutions = enumerate_solutions(game, &[], &[], MAX_COMPARED_SOLUTIONS + 1);
+            let exact = solutions.len() as u64 <= MAX_COMPARED_SOLUTIONS;
+            let shared = |solution: &Vec<usize>| {
+                solution
+                    .iter()
+                    .filter(|idx| placed.binary_search(idx).is_ok())
+                    .count()
+            };
+
+            // `max_by_key` keeps the last of equals, so the solutions go in reverse.
+            let solution = solutions
+                .into_iter()
+                .take(MAX_COMPARED_SOLUTIONS as usize)
+                .rev()
+                .max_by_key(shared)
+                .ok_or_else(|| "Board has no solution".to_string())?;
+            (solution, exact)
+        }
+    };
+
+    Ok(Correction {
+        remove: missing_from(&placed, &solution),
+        add: missing_from(&solution, &placed),
+        solution,
+        exact,
+    })
+}
+
+/// The cells of `cells` that `other` lacks, both sorted.
+pub(crate) fn missing_from(cells: &[usize], other: &[usize]) -> Vec<usize> {
+    cells
+        .iter()
+        .copied()
+        .filter(|idx| other.binary_search(idx).is_err())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::nearest_solution;
+
+    fn correction(game_json: &str, placed: &str) -> Result<String, String> {
+        nearest_solution(game_json.to_string(), placed.to_string())
+    }
+
+    /// The 8x8 corpus board with cell 33 moved to color 1, which gives it five solutions.
+    fn several() -> Game {
+        let mut game = Game::from_json(CORPUS[0].json).unwrap();
+        game.idx_to_color[33] = 1;
+        game
+    }
+
+    /// Checks the correction of `placed` against every solution of `game`.
+    fn check(game: &Game, placed: &[usize]) {
+        let solutions = enumerate_solutions(game, &[], &[], u64::MAX);
+        let correction = find_nearest_solution(game, placed).unwrap();
+        let shared =
+            |solution: &Vec<usize>| solution.iter().filter(|idx| placed.contains(idx)).count();
+        let most = solutions.iter().map(shared).max().unwrap();
+
+        assert!(solutions.contains(&correction.solution), "{:?}", placed);
+        assert_eq!(shared(&correction.solution), most, "{:?}", placed);
+        assert_eq!(correction.remove.len(), placed.len() - most, "{:?}", placed);
+        assert_eq!(correction.add.len(), game.rows - most, "{:?}", placed);
+        assert!(correction.exact);
+        if most < placed.len() {
+            let least = solutions
+                .iter()
+                .filter(|&solution| shared(solution) == most)
+                .min();
+            assert_eq!(Some(&correction.solution), least, "{:?}", placed);
+        }
+
+        let mut shuffled = placed.to_vec();
+        shuffled.reverse();
+        let again = find_nearest_solution(game, &shuffled).unwrap();
+        assert_eq!(again.solution, correction.solution, "{:?}", placed);
+    }
+
+    #[test]
+    fn moves_the_misplaced_queens_of_a_unique_board() {
+        assert_eq!(
+            correction(CORPUS[0].json, "[57,4,9,19,29,39,42,54]"),
+            Ok(
+                r#"{"solution":[4,9,19,29,39,42,54,56],"remove":[57],"add":[56],"exact":true}"#
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            correction(CORPUS[0].json, "[]"),
+            Ok(concat!(
+                r#"{"solution":[4,9,19,29,39,42,54,56],"remove":[],"#,
+                r#""add":[4,9,19,29,39,42,54,56],"exact":true}"#,
+            )
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn finds_the_closest_of_several_solutions() {
+        let colorless = Game::from_json(r#"{"rows":6,"cols":6,"rules":{"colorless":true}}"#);
+        let boards = [
+            Game::from_json(CORPUS[0].json).unwrap(),
+            Game::from_json(CORPUS[1].json).unwrap(),
+            several(),
+            colorless.unwrap(),
+        ];
+
+        for game in &boards {
+            let cells = game.rows * game.cols;
+            for solution in enumerate_solutions(game, &[], &[], 3) {
+                check(game, &solution);
+                check(game, &solution[1..]);
+                for idx in (0..cells).filter(|idx| !solution.contains(idx)) {
+                    let mut placed = solution[1..].to_vec();
+                    placed.push(idx);
+                    check(game, &placed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn compares_against_some_solutions_of_open_boards() {
+        // The 8x8 board without colors has thousands of solutions, none with both queens.
+        let colorless = r#"{"rows":8,"cols":8,"rules":{"colorless":true}}"#;
+        assert_eq!(
+            correction(colorless, "[0,1]"),
+            Ok(concat!(
+                r#"{"solution":[0,10,20,25,37,47,51,62],"remove":[1],"#,
+                r#""add":[10,20,25,37,47,51,62],"exact":false}"#,
+            )
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_queens_and_boards_without_solutions() {
+        assert_eq!(
+            correction(CORPUS[0].json, "[64]"),
+            Err("Cell 64 is not on the board, which has cells 0..64".to_string())
+        );
+        assert_eq!(
+            correction(CORPUS[0].json, "[4,9,4]"),
+            Err("Cell 4 holds more than one queen".to_string())
+        );
+        let error = correction(CORPUS[0].json, "4").unwrap_err();
+        assert!(error.starts_with("Invalid placed JSON: "), "{}", error);
+
+        let unsolvable = CORPUS
+            .iter()
+            .find(|entry| entry.solution.is_empty())
+            .unwrap();
+        assert_eq!(
+            correction(unsolvable.json, "[]"),
+            Err("Board has no solution".to_string())
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use crate::options::Options;
+use crate::stats::SolveStats;
+use crate::{Lookups, NogoodCache, NogoodCursor, ShortNogoods};
+
+/// The cache of `NogoodStore::Off`, which records nothing and finds nothing, so that a search
+/// without nogoods pays neither for storing failures nor for looking candidates up.
+#[derive(Default)]
+pub(crate) struct NoNogoods {
+    /// Always empty.
+    short: ShortNogoods,
+}
+
+impl NogoodCache for NoNogoods {
+    type Cursor<'a> = NoNogoodsCursor;
+
+    fn new(_options: &Options) -> Self {
+        Self::default()
+    }
+
+    #[inline(always)]
+    fn insert(&mut self, _sorted_solution: &[usize]) -> bool {
+        false
+    }
+
+    fn find_subset(&mut self, _sorted_solution: &[usize], path: &mut Vec<usize>) -> bool {
+        path.clear();
+        false
+    }
+
+    #[inline(always)]
+    fn cursor<'a>(&'a self, _sorted_solution: &'a [usize]) -> NoNogoodsCursor {
+        NoNogoodsCursor
+    }
+
+    #[inline(always)]
+    fn count_lookups(&mut self, _lookups: Lookups) {}
+
+    fn short(&self) -> &ShortNogoods {
+        &self.short
+    }
+
+    /// Every counter stays at zero.
+    fn report(&self, _stats: &mut SolveStats) {}
+}
+
+/// The cursor of `NoNogoods`, which never hits.
+pub(crate) struct NoNogoodsCursor;
+
+impl NogoodCursor for NoNogoodsCursor {
+    #[inline(always)]
+    fn hits(&mut self, _idx: usize) -> bool {
+        false
+    }
+
+    fn counts(&self) -> Lookups {
+        Lookups::default()
+    }
+}
@@ -0,0 +1,270 @@
+use crate::options::Options;
+use crate::stats::SolveStats;
+use crate::{Lookups, NogoodCache, NogoodCursor, ShortNogoods};
+use std::collections::HashSet;
+
+/// A nogood as sorted cell indices. Boards have at most `MAX_UNITS` squared cells, so every index
+/// fits in a `u16`.
+type Key = Box<[u16]>;
+
+/// A nogood cache keeping every nogood as its own key of a hash set, to measure the trie of
+/// `NoGoods` against.
+///
+/// The search only ever asks whether its decisions with one candidate added start with a stored
+/// nogood, which takes one hash lookup per prefix containing the candidate, up to the longest
+/// stored nogood. Unlike the trie, a shorter nogood arriving does not drop the stored ones it
+/// covers, since finding them would take a scan of the whole set.
+pub(crate) struct NoGoodSet {
+    set: HashSet<Key>,
+    limit: Option<usize>,
+    max_len: Option<usize>,
+    /// The length of the longest nogood stored so far, past which no prefix needs looking up.
+    longest: usize,
+    /// Copies of the stored nogoods bucketed by length, only kept when the cache is capped so
+    /// that the longest can be found for eviction.
+    by_len: Vec<Vec<Key>>,
+    /// A nogood being matched against the set.
+    key: Vec<u16>,
+    insertions: usize,
+    evictions: usize,
+    subsumed: usize,
+    duplicates: usize,
+    too_long: usize,
+    short: ShortNogoods,
+    /// Lookups by the search, and how many of them found a nogood.
+    lookups: usize,
+    hits: usize,
+}
+
+impl NogoodCache for NoGoodSet {
+    type Cursor<'a> = NoGoodSetCursor<'a>;
+
+    /// Signatures are left to the trie.
+    fn new(options: &Options) -> Self {
+        Self {
+            set: HashSet::new(),
+            limit: options.max_nogoods,
+            max_len: options.max_nogood_len,
+            longest: 0,
+            by_len: vec![],
+            key: vec![],
+            insertions: 0,
+            evictions: 0,
+            subsumed: 0,
+            duplicates: 0,
+            too_long: 0,
+            short: ShortNogoods::default(),
+            lookups: 0,
+            hits: 0,
+        }
+    }
+
+    /// Like the trie, nothing is stored when the same nogood or a prefix of it is already there.
+    fn insert(&mut self, sorted_solution: &[usize]) -> bool {
+        if self
+            .max_len
+            .is_some_and(|max_len| sorted_solution.len() > max_len)
+        {
+            self.too_long += 1;
+            return false;
+        }
+
+        let key: Key = sorted_solution.iter().map(|&idx| idx as u16).collect();
+        if self.set.contains(&key) {
+            self.duplicates += 1;
+            return false;
+        }
+
+        if (0..key.len().min(self.longest + 1)).any(|len| self.set.contains(&key[..len])) {
+            self.subsumed += 1;
+            return false;
+        }
+
+        if let Some(limit) = self.limit {
+            if self.set.len() >= limit && !self.evict_longer_than(key.len()) {
+                // The new nogood would be the first to go, so don't store it at all.
+                self.evictions += 1;
+                return false;
+            }
+
+            if self.by_len.len() <= key.len() {
+                self.by_len.resize(key.len() + 1, vec![]);
+            }
+            self.by_len[key.len()].push(key.clone());
+        }
+
+        self.longest = self.longest.max(key.len());
+        self.set.insert(key);
+        self.short.record(sorted_solution);
+        self.insertions += 1;
+        true
+    }
+
+    /// Looks up every subset of `sorted_solution` up to the longest stored nogood, which only
+    /// stays cheap while nogoods are short, as with backjumping.
+    fn find_subset(&mut self, sorted_solution: &[usize], path: &mut Vec<usize>) -> bool {
+        let mut key = std::mem::take(&mut self.key);
+        key.clear();
+        let found = self.find_subset_from(sorted_solution, &mut key);
+
+        path.clear();
+        if found {
+            path.extend(key.iter().map(|&idx| idx as usize));
+        }
+        self.key = key;
+
+        self.lookups += 1;
+        self.hits += found as usize;
+        found
+    }
+
+    fn cursor<'a>(&'a self, sorted_solution: &'a [usize]) -> NoGoodSetCursor<'a> {
+        NoGoodSetCursor::new(self, sorted_solution)
+    }
+
+    fn count_lookups(&mut self, lookups: Lookups) {
+        self.lookups += lookups.lookups;
+        self.hits += lookups.hits;
+    }
+
+    fn short(&self) -> &ShortNogoods {
+        &self.short
+    }
+
+    /// There is no trie, so no trie nodes. The bytes count the keys and the slots of the table.
+    fn report(&self, stats: &mut SolveStats) {
+        stats.nogood_insertions = self.insertions;
+        stats.nogood_evictions = self.evictions;
+        stats.nogood_subsumed = self.subsumed;
+        stats.nogood_duplicates = self.duplicates;
+        stats.nogood_too_long = self.too_long;
+        stats.nogood_lookups = self.lookups;
+        stats.nogood_hits = self.hits;
+        stats.nogoods_stored = self.set.len();
+        stats.nogood_bytes = self.footprint();
+    }
+}
+
+impl NoGoodSet {
+    /// Roughly how many bytes the cache takes up, going through every key.
+    fn footprint(&self) -> usize {
+        let slots = self.set.capacity() * (std::mem::size_of::<Key>() + 1);
+        let keys: usize = self
+            .set
+            .iter()
+            .chain(self.by_len.iter().flatten())
+            .map(|key| key.len() * std::mem::size_of::<u16>())
+            .sum();
+
+        std::mem::size_of::<Self>() + slots + keys
+    }
+
+    /// Evicts one of the longest stored nogoods if it is longer than `len`.
+    fn evict_longer_than(&mut self, len: usize) -> bool {
+        let Some(longest) = self.by_len.iter().rposition(|bucket| !bucket.is_empty()) else {
+            return false;
+        };
+        if longest <= len {
+            return false;
+        }
+
+        let evicted = self.by_len[longest].pop().unwrap();
+        self.set.remove(&evicted);
+        self.evictions += 1;
+        true
+    }
+
+    /// Whether `key` followed by some of `rest`, in order, is a stored nogood, extending `key` to
+    /// the first one found.
+    fn find_subset_from(&self, rest: &[usize], key: &mut Vec<u16>) -> bool {
+        if self.set.contains(key.as_slice()) {
+            return true;
+        }
+        if key.len() >= self.longest {
+            return false;
+        }
+
+        for (pos, &idx) in rest.iter().enumerate() {
+            key.push(idx as u16);
+
+            if self.find_subset_from(&rest[pos + 1..], key) {
+                return true;
+            }
+
+            key.pop();
+        }
+
+        false
+    }
+}
+
+/// Checks the candidates of one node against a `NoGoodSet`.
+///
+/// The prefixes made only of decisions below a candidate are the same for every candidate above
+/// them, so the cursor looks each of those up once, and only as far as some candidate needs.
+pub(crate) struct NoGoodSetCursor<'a> {
+    nogoods: &'a NoGoodSet,
+    sorted_solution: &'a [usize],
+    /// The decisions with room for one candidate, which is slotted in and taken out again.
+    key: Vec<u16>,
+    /// How many of the shortest prefixes of the decisions are known not to be stored.
+    checked: usize,
+    /// The length of the shortest prefix of the decisions that is stored, once one is found.
+    stored_prefix: Option<usize>,
+    lookups: Lookups,
+}
+
+impl<'a> NoGoodSetCursor<'a> {
+    fn new(nogoods: &'a NoGoodSet, sorted_solution: &'a [usize]) -> Self {
+        let mut key = Vec::with_capacity(sorted_solution.len() + 1);
+        key.extend(sorted_solution.iter().map(|&idx| idx as u16));
+
+        Self {
+            nogoods,
+            sorted_solution,
+            key,
+            checked: 0,
+            stored_prefix: None,
+            lookups: Lookups::default(),
+        }
+    }
+
+    #[inline(always)]
+    fn find(&mut self, idx: usize) -> bool {
+        let pos = self.sorted_solution.partition_point(|&i| i < idx);
+        let longest = self.nogoods.longest;
+
+        // Prefixes of up to `pos` decisions stay prefixes once `idx` is slotted in after them.
+        while self.stored_prefix.is_none() && self.checked <= pos.min(longest) {
+            if self.nogoods.set.contains(&self.key[..self.checked]) {
+                self.stored_prefix = Some(self.checked);
+            }
+            self.checked += 1;
+        }
+        if self.stored_prefix.is_some_and(|len| len <= pos) {
+            return true;
+        }
+
+        self.key.insert(pos, idx as u16);
+        let found = (pos + 1..=self.key.len().min(longest))
+            .any(|len| self.nogoods.set.contains(&self.key[..len]));
+        self.key.remove(pos);
+
+        found
+    }
+}
+
+impl NogoodCursor for NoGoodSetCursor<'_> {
+    #[inline(always)]
+    fn hits(&mut self, idx: usize) -> bool {
+        let found = self.find(idx);
+
+        self.lookups.lookups += 1;
+        self.lookups.hits += found as usize;
+        found
+    }
+
+    fn counts(&self) -> Lookups {
+        self.lookups
+    }
+}
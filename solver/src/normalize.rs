@@ -0,0 +1,216 @@
+use crate::links::{Link, LinkKind};
+use crate::symmetry::sorted_pair;
+use crate::Game;
+
+impl Game {
+    /// The board in one canonical shape among the equivalent ways of writing it, so that boards
+    /// can be hashed and compared as values. Normalizing a normalized board changes nothing.
+    ///
+    /// Colors are renamed `0..k` by first appearance, dropping those without cells unless every
+    /// color must hold a queen, since they then leave the board unsolvable. Neighbor lists and
+    /// links are sorted without repeats, and an adjacency the topology and links imply anyway is
+    /// left out.
+    pub(crate) fn normalized(&self) -> Self {
+        let (colors, idx_to_color) = self.dense_colors();
+
+        let mut links: Vec<_> = self
+            .links
+            .iter()
+            .map(|link| Link {
+                kind: link.kind,
+                cells: sorted_pair(link.cells),
+            })
+            .collect();
+        links.sort_unstable();
+        links.dedup();
+
+        let adjacency = self
+            .adjacency
+            .as_ref()
+            .map(|adjacency| {
+                adjacency
+                    .iter()
+                    .map(|neighbors| sorted_set(neighbors.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|adjacency| *adjacency != self.implied_adjacency());
+
+        Self {
+            rows: self.rows,
+            cols: self.cols,
+            colors,
+            idx_to_color,
+            regions: None,
+            rules: self.rules.clone(),
+            adjacency,
+            cell_ids: self.cell_ids.clone(),
+            links,
+            strict: false,
+        }
+    }
+
+    /// The color labels and the color of every cell, renamed by first appearance.
+    fn dense_colors(&self) -> (Vec<usize>, Vec<usize>) {
+        if !self.has_color_rule() {
+            return (vec![], vec![0; self.rows * self.cols]);
+        }
+
+        let mut names = vec![None; self.colors.len()];
+        let mut next = 0;
+        let idx_to_color = self
+            .idx_to_color
+            .iter()
+            .map(|&color| {
+                *names[color].get_or_insert_with(|| {
+                    next += 1;
+                    next - 1
+                })
+            })
+            .collect();
+
+        let count = if self.rules.all_colors_required {
+            self.colors.len()
+        } else {
+            next
+        };
+
+        ((0..count).collect(), idx_to_color)
+    }
+
+    /// The sorted neighbors of every cell by the topology and the `AtMostOne` links alone, as
+    /// `Game::from_json` spells them out.
+    fn implied_adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency: Vec<_> = (0..self.rows * self.cols)
+            .map(|idx| self.rules.topology.neighbors(self.rows, self.cols, idx))
+            .collect();
+
+        for link in self
+            .links
+            .iter()
+            .filter(|link| link.kind == LinkKind::AtMostOne)
+        {
+            let [a, b] = link.cells;
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+
+        adjacency.into_iter().map(sorted_set).collect()
+    }
+}
+
+fn sorted_set(mut cells: Vec<usize>) -> Vec<usize> {
+    cells.sort_unstable();
+    cells.dedup();
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::corpus::CORPUS;
+    use crate::normalize_game;
+    use crate::pack::canonical_hash;
+    use crate::Game;
+
+    /// Boards written in every way `normalized` has to straighten out.
+    const UNTIDY: &[&str] = &[
+        // Colors named out of order, and a label without cells.
+        r#"{"rows":4,"cols":4,"colors":[0,1,2,3,4,5],"idxToColor":[
+            3,3,1,1, 3,0,0,1, 4,0,0,2, 4,4,2,2],"rules":{"allColorsRequired":false}}"#,
+        // Sizes left out, regions instead of cell colors, and a field the solver does not know.
+        r#"{"regions":[[0,1,4],[2,3,7],[5,6,9,10],[8,12,13],[11,14,15]],"comment":"hand made"}"#,
+        // Links repeated and reversed.
+        r#"{"rows":4,"cols":4,"links":[{"kind":"same","cells":[9,2]},
+            {"kind":"atMostOne","cells":[3,12]},{"kind":"same","cells":[2,9]}]}"#,
+        // Neighbor lists unsorted and repeated.
+        r#"{"rows":2,"cols":3,"adjacency":[[5,5],[3],[],[1],[],[0,0]]}"#,
+        // Neighbors the topology implies anyway.
+        r#"{"rows":2,"cols":2,"adjacency":[[3],[2],[1],[0]]}"#,
+    ];
+
+    #[test]
+    fn normalizing_twice_changes_nothing() {
+        let boards = CORPUS
+            .iter()
+            .map(|board| board.json)
+            .chain(UNTIDY.iter().copied());
+        for json in boards {
+            let once = normalize_game(json.to_string()).unwrap();
+            assert_eq!(normalize_game(once.clone()).unwrap(), once, "{}", json);
+
+            let game = Game::from_json(json).unwrap();
+            assert_eq!(
+                canonical_hash(&Game::from_json(&once).unwrap()),
+                canonical_hash(&game)
+            );
+        }
+    }
+
+    #[test]
+    fn spells_out_untidy_boards_in_one_way() {
+        let normalized: Vec<_> = UNTIDY
+            .iter()
+            .map(|json| normalize_game(json.to_string()).unwrap())
+            .collect();
+        assert_eq!(
+            normalized,
+            [
+                concat!(
+                    r#"{"rows":4,"cols":4,"colors":[0,1,2,3,4],"#,
+                    r#""idxToColor":[0,0,1,1,0,2,2,1,3,2,2,4,3,3,4,4],"#,
+                    r#""rules":{"colorless":false,"allColorsRequired":false,"topology":"square"}}"#,
+                ),
+                concat!(
+                    r#"{"rows":4,"cols":4,"colors":[0,1,2,3,4],"#,
+                    r#""idxToColor":[0,0,1,1,0,2,2,1,3,2,2,4,3,3,4,4],"#,
+                    r#""rules":{"colorless":false,"allColorsRequired":true,"topology":"square"}}"#,
+                ),
+                concat!(
+                    r#"{"rows":4,"cols":4,"colors":[],"#,
+                    r#""idxToColor":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],"#,
+                    r#""rules":{"colorless":true,"allColorsRequired":true,"topology":"square"},"#,
+                    r#""links":[{"kind":"same","cells":[2,9]},"#,
+                    r#"{"kind":"atMostOne","cells":[3,12]}]}"#,
+                ),
+                concat!(
+                    r#"{"rows":2,"cols":3,"colors":[],"idxToColor":[0,0,0,0,0,0],"#,
+                    r#""rules":{"colorless":true,"allColorsRequired":true,"topology":"square"},"#,
+                    r#""adjacency":[[5],[3],[],[1],[],[0]]}"#,
+                ),
+                concat!(
+                    r#"{"rows":2,"cols":2,"colors":[],"idxToColor":[0,0,0,0],"#,
+                    r#""rules":{"colorless":true,"allColorsRequired":true,"topology":"square"}}"#,
+                ),
+            ]
+        );
+    }
+
+    /// Boards differing only in how they are written normalize to the same JSON and hash.
+    #[test]
+    fn equivalent_boards_normalize_alike() {
+        let pairs = [
+            (
+                r#"{"rows":2,"cols":2,"colors":[0,1],"idxToColor":[0,0,1,1]}"#,
+                r#"{"colors":[1,0],"idxToColor":[1,1,0,0]}"#,
+            ),
+            (
+                r#"{"rows":4,"cols":4,"links":[{"kind":"same","cells":[9,2]}]}"#,
+                concat!(
+                    r#"{"rows":4,"cols":4,"links":[{"kind":"same","cells":[2,9]},"#,
+                    r#"{"kind":"same","cells":[9,2]}]}"#,
+                ),
+            ),
+            (
+                r#"{"rows":2,"cols":2,"adjacency":[[3],[2],[1],[0]]}"#,
+                r#"{"rows":2,"cols":2}"#,
+            ),
+        ];
+
+        for (a, b) in pairs {
+            assert_eq!(normalize_game(a.to_string()), normalize_game(b.to_string()));
+            assert_eq!(
+                canonical_hash(&Game::from_json(a).unwrap()),
+                canonical_hash(&Game::from_json(b).unwrap())
+            );
+        }
+    }
+}
@@ -0,0 +1,547 @@
+use crate::strategy::find_strategy;
+use crate::Game;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Tuning knobs for `solve_with_options`. Absent fields keep the fixed defaults, where `solve`
+/// leaves the heuristics to `Heuristics::Auto`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct Options {
+    /// The most nogoods kept in the cache at once. Unlimited when absent.
+    pub max_nogoods: Option<usize>,
+    /// The most decisions in a nogood worth storing. Failures deeper than this are not recorded,
+    /// since the search rarely comes back to a superset of them. Unlimited when absent.
+    pub max_nogood_len: Option<usize>,
+    pub nogood_store: NogoodStore,
+    /// Screen nogood lookups with 64-bit signatures of the decisions, skipping the walks of the
+    /// trie that cannot find a nogood. Only the trie store screens.
+    pub nogood_signatures: bool,
+    /// Use conflict-directed backjumping, learning only the decisions responsible for each
+    /// failure as nogoods.
+    pub backjumping: bool,
+    /// Track available cells as a bitset, for boards of at most 192 cells. Larger boards use the
+    /// default counters.
+    pub bitboard: bool,
+    pub branching: Branching,
+    pub value_ordering: ValueOrdering,
+    /// Multipliers of the row, column, and color spot counts in the key of
+    /// `ValueOrdering::TightestUnit`, one entry per depth of the search, the last applying to every
+    /// depth past the table. Every unit counts the same when absent.
+    pub ordering_weights: Option<Vec<UnitWeights>>,
+    /// How candidates with equal keys under `value_ordering` are ordered.
+    pub tie_break: TieBreak,
+    /// Before branching, place every queen forced by a row, column, or color with a single spot
+    /// left, repeating until none remain.
+    pub propagate: bool,
+    /// While propagating, rule out every cell whose queen would attack all the spots left in
+    /// some unused row, column, or color.
+    pub arc_consistency: bool,
+    /// While propagating, rule out the other cells of any row or column holding every spot left
+    /// for a color.
+    pub confinement: bool,
+    /// While propagating, rule out the cells of other colors in any two or three rows or columns
+    /// holding every spot left for as many colors.
+    pub subsets: bool,
+    /// Abandon a run of the search once it has used up its node budget and start over, keeping
+    /// the nogoods learned so far. Never restarts when absent.
+    pub restarts: Option<Restarts>,
+    /// Before searching, split the board into parts sharing no row, column, or color, and no
+    /// neighbors, once the cells no solution uses are ruled out, and search each alone. The parts
+    /// share `max_nodes`, each searched with what the parts before it left of it.
+    pub decompose: bool,
+    /// Give up with an unknown status once the backtracking search has entered this many nodes.
+    /// Unlimited when absent.
+    pub max_nodes: Option<usize>,
+    /// Seeds the random tie-breaking of candidates after a restart, and local search.
+    pub seed: u64,
+    /// The name of the strategy to solve with, `"systematic"` backtracking search when absent or
+    /// `"local"` min-conflicts search. See `strategy::STRATEGIES`.
+    pub algorithm: Option<String>,
+    /// Budgets of local search.
+    pub local: LocalSearch,
+    /// Check every solution against the rules before returning it, reporting an internal error
+    /// instead of a wrong answer. Defaults to on in debug builds only.
+    pub verify: Option<bool>,
+    /// Fail a node as soon as some unused row, required column, or required color has no spot
+    /// left, instead of only once no candidates remain. Defaults to on.
+    pub forward_checking: Option<bool>,
+    pub heuristics: Heuristics,
+    /// The order of the queens in the output.
+    pub order: SolutionOrder,
+}
+
+/// How `solve_with_options` orders the queens of a solution.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SolutionOrder {
+    /// The order the search placed them in.
+    #[default]
+    Search,
+    /// The order the deductions find them in from the empty board, each with the walkthrough
+    /// step placing it and its technique. See `walkthrough::deduction_order`.
+    Deduction,
+}
+
+/// Where the heuristics of the search come from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Heuristics {
+    /// The fields of the options, as given.
+    #[default]
+    Fixed,
+    /// The tier of `AUTO_TIERS` for the size of the board, in place of the fields it sets.
+    Auto,
+}
+
+/// The heuristics `Heuristics::Auto` picks for boards up to a size.
+pub(crate) struct AutoTier {
+    /// The name reported in `SolveStats::heuristics`.
+    pub name: &'static str,
+    /// The most cells and colors of the boards the tier takes.
+    pub max_cells: usize,
+    pub max_colors: usize,
+    pub confinement: bool,
+    pub subsets: bool,
+    pub decompose: bool,
+}
+
+/// The tiers of `Heuristics::Auto`, the first to take a board picking its heuristics.
+///
+/// Measured with `benchmark_options` on the corpus of `corpus::CORPUS`, random regions, and
+/// regions grown around a planted solution, from 5 by 5 to 15 by 15. Every tier tracks cells in a
+/// bitboard where they fit, places forced queens, and branches over the tightest unit, which cuts
+/// the nodes of the fixed defaults by one to three orders of magnitude from 8 by 8 up and was
+/// never slower on smaller boards. With branching that tight nogoods never paid for their lookups
+/// and sorting the few candidates gained nothing, so every tier turns the nogood store off and
+/// leaves the candidates in board order. Confinement and
+/// naked subsets cost more than they save until the boards grow past 12 by 12, beyond which they
+/// cut the nodes twentyfold. Splitting the board into independent parts takes the boards of the
+/// corpus apart but doubles the time of their few dozen nodes, while from 16 by 16 up it costs a
+/// tenth of a solve that does not split at most, so only the large tier splits.
+///
+/// On every board of the corpus the small tier enters at most as many nodes as the fixed
+/// defaults, which `tests::auto_never_searches_more_than_fixed` holds it to, and in release
+/// builds it settles each in 0.02 to 0.04 ms against 0.03 to 80 ms for the fixed defaults, as
+/// the `solve` and `solve_fixed` benches compare.
+pub(crate) const AUTO_TIERS: &[AutoTier] = &[
+    AutoTier {
+        name: "small",
+        max_cells: 144,
+        max_colors: 12,
+        confinement: false,
+        subsets: false,
+        decompose: false,
+    },
+    AutoTier {
+        name: "large",
+        max_cells: usize::MAX,
+        max_colors: usize::MAX,
+        confinement: true,
+        subsets: true,
+        decompose: true,
+    },
+];
+
+/// Budgets of min-conflicts local search.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct LocalSearch {
+    /// Queen moves per run before starting over from a fresh random placement.
+    pub steps: usize,
+    /// How many times to start over before giving up.
+    pub restarts: usize,
+    /// The probability of moving a queen to a random column rather than the least attacked one.
+    pub walk: f64,
+}
+
+impl Default for LocalSearch {
+    fn default() -> Self {
+        Self {
+            steps: 10_000,
+            restarts: 20,
+            walk: 0.1,
+        }
+    }
+}
+
+/// When to restart, from `Options::restarts`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct Restarts {
+    /// The node budget of the first run, which later runs scale by the schedule.
+    pub nodes: usize,
+    pub schedule: Schedule,
+    /// How much each run's budget grows over the last with the geometric schedule.
+    pub factor: f64,
+}
+
+impl Default for Restarts {
+    fn default() -> Self {
+        Self {
+            nodes: 100,
+            schedule: Schedule::default(),
+            factor: 1.5,
+        }
+    }
+}
+
+impl Restarts {
+    /// Rejects schedules whose budgets could stop growing, since the search might then never
+    /// finish a run.
+    fn validate(&self) -> Result<(), String> {
+        if self.nodes == 0 {
+            return Err("Restarts need a node budget of at least 1".to_string());
+        }
+
+        if self.schedule == Schedule::Geometric && self.factor <= 1.0 {
+            return Err(format!(
+                "Geometric restarts need a factor above 1, found {}",
+                self.factor
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The node budget of run number `run`, counting from zero.
+    pub fn budget(&self, run: usize) -> usize {
+        let scale = match self.schedule {
+            Schedule::Luby => luby(run + 1) as f64,
+            // Repeated multiplication rounds the same everywhere, unlike `powi`, so budgets and
+            // with them the search agree between the wasm and native builds.
+            Schedule::Geometric => (0..run).fold(1.0, |scale, _| scale * self.factor),
+        };
+
+        ((self.nodes as f64) * scale).max(1.0) as usize
+    }
+}
+
+/// How node budgets grow from one run to the next.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Schedule {
+    /// 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, ... times the first budget: mostly short runs, with a long
+    /// one now and then.
+    #[default]
+    Luby,
+    /// Each budget `factor` times the one before.
+    Geometric,
+}
+
+/// The `i`-th term of the Luby sequence, counting from one.
+fn luby(mut i: usize) -> usize {
+    loop {
+        // The smallest k with i <= 2^k - 1.
+        let k = usize::BITS - i.leading_zeros();
+        if i == (1 << k) - 1 {
+            return 1 << (k - 1);
+        }
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+/// How the search keeps its nogoods.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NogoodStore {
+    /// A trie sharing common prefixes, where shorter nogoods drop the longer ones they cover.
+    #[default]
+    Trie,
+    /// A hash set of whole nogoods, looked up once per prefix, for measuring what the trie gains.
+    Hash,
+    /// No nogoods at all: failures are not recorded and candidates are not looked up, for
+    /// searches that never come back to the same decisions.
+    Off,
+}
+
+/// Which cells each search node branches over.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Branching {
+    /// Every available cell, ordered by the tightest unit it belongs to.
+    #[default]
+    Cells,
+    /// Only the available cells of the tightest unused row, column, or color.
+    Unit,
+}
+
+/// The order in which a node tries its candidate cells.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ValueOrdering {
+    /// Cells in the row, column, or color with the fewest spots first.
+    #[default]
+    TightestUnit,
+    /// Cells that rule out the fewest other cells first, which tends to find a solution sooner.
+    LeastConstraining,
+    /// Cells that rule out the most other cells first, which tends to fail faster when there is
+    /// no solution.
+    MostConstraining,
+    /// Cells in row-major order, or shuffled after a restart, for measuring what the other
+    /// orderings gain.
+    BoardOrder,
+}
+
+/// The secondary key of the candidates, ordering those the `ValueOrdering` ties on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TieBreak {
+    /// Cells in row-major order, or shuffled after a restart.
+    #[default]
+    Index,
+    /// Cells conflicting with the most available cells first, through their row, column, color,
+    /// and neighbors, which tends to settle a board with one solution sooner.
+    Degree,
+    /// Cells nearest the center of the board first.
+    Centrality,
+}
+
+/// How much the spots of each kind of unit count in the tightest-unit key, from
+/// `Options::ordering_weights`. A cell's key is the least of its units' spot counts, each
+/// multiplied by its weight, so a lower weight makes a kind of unit look tighter.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct UnitWeights {
+    pub row: f64,
+    pub col: f64,
+    pub color: f64,
+}
+
+impl Default for UnitWeights {
+    fn default() -> Self {
+        Self {
+            row: 1.0,
+            col: 1.0,
+            color: 1.0,
+        }
+    }
+}
+
+impl UnitWeights {
+    /// Keys are weighted spot counts in this many parts of a spot, so that fractional weights
+    /// still tell counts apart.
+    const SCALE: f64 = 256.0;
+
+    /// The key of a cell whose row, column, and color have these spots left, without a color on
+    /// colorless boards. Scaled to fit in 32 bits, as `sort_by_cached_key` asks.
+    pub fn key(self, row_spots: usize, col_spots: usize, color_spots: Option<usize>) -> usize {
+        let weigh = |spots: usize, weight: f64| {
+            (spots as f64 * weight * Self::SCALE).min(u32::MAX as f64) as usize
+        };
+
+        let key = weigh(row_spots, self.row).min(weigh(col_spots, self.col));
+        match color_spots {
+            Some(spots) => key.min(weigh(spots, self.color)),
+            None => key,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, weight) in [("row", self.row), ("col", self.col), ("color", self.color)] {
+            if !weight.is_finite() || weight <= 0.0 {
+                return Err(format!(
+                    "Ordering weights must be positive numbers, found {} for {}",
+                    weight, name
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Options {
+    pub fn from_json(options_json: &str) -> Result<Self, String> {
+        let options: Self = serde_json::from_str(options_json)
+            .map_err(|e| format!("Invalid options JSON: {}", e))?;
+        options.validate()?;
+
+        Ok(options)
+    }
+
+    /// Rejects options that would not describe a search, for options not read by `from_json`.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(restarts) = &self.restarts {
+            restarts.validate()?;
+        }
+
+        if let Some(weights) = &self.ordering_weights {
+            if weights.is_empty() {
+                return Err("Ordering weights need at least one depth".to_string());
+            }
+            for weights in weights {
+                weights.validate()?;
+            }
+        }
+
+        find_strategy(self.algorithm.as_deref())?;
+
+        Ok(())
+    }
+
+    /// The options to search `game` with: these, or with `Heuristics::Auto` the heuristics of the
+    /// tier taking the board in their place, along with the tier's name.
+    pub fn for_game(&self, game: &Game) -> (Cow<'_, Self>, Option<&'static str>) {
+        if self.heuristics == Heuristics::Fixed {
+            return (Cow::Borrowed(self), None);
+        }
+
+        let cells = game.rows * game.cols;
+        let tier = AUTO_TIERS
+            .iter()
+            .find(|tier| cells <= tier.max_cells && game.num_colors() <= tier.max_colors)
+            .unwrap();
+
+        let options = Self {
+            nogood_store: NogoodStore::Off,
+            bitboard: true,
+            branching: Branching::Unit,
+            value_ordering: ValueOrdering::BoardOrder,
+            propagate: true,
+            confinement: tier.confinement,
+            subsets: tier.subsets,
+            decompose: tier.decompose,
+            heuristics: Heuristics::Fixed,
+            ..self.clone()
+        };
+
+        (Cow::Owned(options), Some(tier.name))
+    }
+
+    /// Whether solutions are checked before they are returned.
+    pub fn verifies(&self) -> bool {
+        self.verify.unwrap_or(cfg!(debug_assertions))
+    }
+
+    /// The ordering weights of the candidates at `depth`, if any are given.
+    pub fn weights_at(&self, depth: usize) -> Option<UnitWeights> {
+        let table = self.ordering_weights.as_deref()?;
+        table.get(depth).or(table.last()).copied()
+    }
+
+    /// Whether nodes fail early on a unit without spots.
+    pub fn forward_checks(&self) -> bool {
+        self.forward_checking.unwrap_or(true)
+    }
+
+    /// The options `solve` uses, which leave the heuristics to `Heuristics::Auto`.
+    pub fn for_solve() -> Self {
+        Self {
+            heuristics: Heuristics::Auto,
+            ..Self::default()
+        }
+    }
+
+    /// The options used when grading a board by the deductions it takes, which leave out arc
+    /// consistency since it subsumes confinement.
+    pub fn for_deduction() -> Self {
+        Self {
+            bitboard: true,
+            propagate: true,
+            confinement: true,
+            subsets: true,
+            ..Self::default()
+        }
+    }
+
+    /// The options used when counting solutions, which must branch over a single unit.
+    pub fn for_counting() -> Self {
+        Self {
+            bitboard: true,
+            branching: Branching::Unit,
+            propagate: true,
+            confinement: true,
+            subsets: true,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::run_solver;
+
+    /// Auto never does meaningfully worse than the fixed defaults on the corpus: it settles every
+    /// board the same way, through no more nodes.
+    #[test]
+    fn auto_never_searches_more_than_fixed() {
+        let auto = Options {
+            heuristics: Heuristics::Auto,
+            ..Options::default()
+        };
+
+        for entry in CORPUS {
+            let game = Game::from_json(entry.json).unwrap();
+            let fixed = run_solver(&game, &Options::default());
+            let picked = run_solver(&game, &auto);
+
+            assert_eq!(picked.status, fixed.status, "{}", entry.name);
+            assert!(
+                picked.stats.nodes <= fixed.stats.nodes,
+                "{}: {} nodes against {}",
+                entry.name,
+                picked.stats.nodes,
+                fixed.stats.nodes
+            );
+            assert_eq!(picked.stats.heuristics, Some("small"));
+            assert_eq!(fixed.stats.heuristics, None);
+        }
+    }
+
+    #[test]
+    fn auto_turns_nogoods_off() {
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        let solve = Options::for_solve();
+        let (options, tier) = solve.for_game(&game);
+
+        assert_eq!(options.nogood_store, NogoodStore::Off);
+        assert_eq!(options.heuristics, Heuristics::Fixed);
+        assert_eq!(tier, Some("small"));
+    }
+
+    /// Without a nogood store nothing is recorded, evicted, or looked up, while the search still
+    /// settles every board.
+    #[test]
+    fn off_store_keeps_no_nogoods() {
+        let off = Options {
+            nogood_store: NogoodStore::Off,
+            bitboard: true,
+            branching: Branching::Unit,
+            propagate: true,
+            ..Options::default()
+        };
+
+        for entry in CORPUS {
+            let game = Game::from_json(entry.json).unwrap();
+            let outcome = run_solver(&game, &off);
+            let stats = &outcome.stats;
+
+            let mut solution = outcome.solution.clone();
+            solution.sort_unstable();
+            assert_eq!(solution, entry.solution, "{}", entry.name);
+            assert_eq!(stats.nogood_insertions, 0);
+            assert_eq!(stats.nogood_evictions, 0);
+            assert_eq!(stats.nogood_lookups, 0);
+            assert_eq!(stats.nogoods_stored, 0);
+        }
+    }
+
+    #[test]
+    fn reads_every_tie_break() {
+        for (name, tie_break) in [
+            ("index", TieBreak::Index),
+            ("degree", TieBreak::Degree),
+            ("centrality", TieBreak::Centrality),
+        ] {
+            let json = format!(r#"{{"tieBreak":"{}"}}"#, name);
+            assert_eq!(Options::from_json(&json).unwrap().tie_break, tie_break);
+        }
+        assert_eq!(Options::default().tie_break, TieBreak::Index);
+
+        let error = Options::from_json(r#"{"tieBreak":"random"}"#).unwrap_err();
+        assert!(error.contains("unknown variant `random`"), "{}", error);
+    }
+}
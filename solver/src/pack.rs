@@ -0,0 +1,284 @@
+use crate::difficulty::{assess_difficulty, Technique};
+use crate::panics::catch_panics;
+use crate::utils::hash_hex;
+use crate::verify::verify_solution;
+use crate::{enumerate_solutions, Game};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// The version of the pack format this build reads and writes.
+pub(crate) const PACK_VERSION: u32 = 1;
+
+/// A pack file as written to disk, entries holding their boards as given.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct PackFile {
+    pub version: u32,
+    pub title: String,
+    pub entries: Vec<PackEntry>,
+}
+
+/// A puzzle of a pack with what is known about it.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct PackEntry {
+    pub board: Game,
+    /// The hardest technique solving the board takes.
+    pub difficulty: Technique,
+    /// The `canonical_hash` of the board, which a pack is checked against when read.
+    pub hash: String,
+    /// The cells of its solution, if the pack gives it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution: Option<Vec<usize>>,
+}
+
+/// What `PuzzlePack::meta` tells about an entry besides its board.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct EntryMeta<'a> {
+    index: usize,
+    difficulty: Technique,
+    hash: &'a str,
+    rows: usize,
+    cols: usize,
+    has_solution: bool,
+}
+
+/// What `PuzzlePack::verify` found for an entry.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct EntryCheck {
+    index: usize,
+    passed: bool,
+    /// What went wrong, in words. Empty if the entry passed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failures: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PackCheck {
+    passed: bool,
+    entries: Vec<EntryCheck>,
+}
+
+/// A hash of `game` that only depends on the board and not how its JSON was written, the same
+/// on every build and platform, in hex. Boards equal after normalizing have equal hashes.
+pub(crate) fn canonical_hash(game: &Game) -> String {
+    // The serde form of the game, which does not change with the tiny-json feature.
+    hash_hex(&serde_json::to_string(&game.normalized()).unwrap())
+}
+
+/// A pack of puzzles for the practice mode, read from a pack file.
+///
+/// A pack file is JSON with the format `version`, a `title`, and `entries`, each holding a
+/// `board` as game JSON, its `difficulty` as the hardest technique it takes, its `hash` as
+/// computed by the crate, and optionally its `solution`. Reading checks every board and that
+/// every hash matches its board, so that a pack edited by hand cannot pass off one board as
+/// another.
+#[wasm_bindgen]
+pub struct PuzzlePack {
+    title: String,
+    entries: Vec<PackEntry>,
+}
+
+#[wasm_bindgen]
+impl PuzzlePack {
+    #[wasm_bindgen(constructor)]
+    pub fn new(pack_json: String) -> Result<PuzzlePack, String> {
+        catch_panics(None, || {
+            let pack: PackFile = serde_json::from_str(&pack_json)
+                .map_err(|e| format!("Invalid pack JSON: {}", e))?;
+            if pack.version != PACK_VERSION {
+                return Err(format!(
+                    "Pack has version {}, but this build reads version {}",
+                    pack.version, PACK_VERSION
+                ));
+            }
+
+            let entries = pack
+                .entries
+                .into_iter()
+                .enumerate()
+                .map(|(index, mut entry)| {
+                    entry.board = entry
+                        .board
+                        .prepare()
+                        .map_err(|e| format!("Entry {}: {}", index, e))?;
+
+                    let hash = canonical_hash(&entry.board);
+                    if entry.hash != hash {
+                        return Err(format!(
+                            "Entry {} has hash {}, but its board hashes to {}",
+                            index, entry.hash, hash
+                        ));
+                    }
+
+                    Ok(entry)
+                })
+                .collect::<Result<_, String>>()?;
+
+            Ok(Self {
+                title: pack.title,
+                entries,
+            })
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The board of entry `index` as game JSON.
+    pub fn get(&self, index: usize) -> Result<String, String> {
+        Ok(self.entry(index)?.board.to_json())
+    }
+
+    /// What the pack says about entry `index`: its `difficulty`, `hash`, size, and whether it
+    /// comes with a solution.
+    pub fn meta(&self, index: usize) -> Result<String, String> {
+        let entry = self.entry(index)?;
+
+        Ok(serde_json::to_string(&EntryMeta {
+            index,
+            difficulty: entry.difficulty,
+            hash: &entry.hash,
+            rows: entry.board.rows,
+            cols: entry.board.cols,
+            has_solution: entry.solution.is_some(),
+        })
+        .unwrap())
+    }
+
+    /// The index of the first entry with hash `hash`, if any.
+    pub fn find_by_hash(&self, hash: String) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.hash == hash)
+    }
+
+    /// Checks that every board has exactly one solution, and that the solution given with an
+    /// entry, if any, keeps the rules and is that one. Returns whether every entry `passed`,
+    /// and per entry whether it passed and what failed.
+    pub fn verify(&self) -> Result<String, String> {
+        catch_panics(None, || {
+            let entries: Vec<_> = self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| check_entry(index, entry))
+                .collect();
+
+            Ok(serde_json::to_string(&PackCheck {
+                passed: entries.iter().all(|entry| entry.passed),
+                entries,
+            })
+            .unwrap())
+        })
+    }
+}
+
+impl PuzzlePack {
+    fn entry(&self, index: usize) -> Result<&PackEntry, String> {
+        self.entries.get(index).ok_or_else(|| {
+            format!(
+                "Entry {} is not in the pack, which has entries 0..{}",
+                index,
+                self.entries.len()
+            )
+        })
+    }
+}
+
+/// Writes `games` as a pack titled `title`, each with its difficulty, hash, and solution, in the
+/// format `PuzzlePack` reads. Every board must have exactly one solution.
+pub(crate) fn write_pack(title: String, games: Vec<Game>) -> Result<String, String> {
+    let entries = games
+        .into_iter()
+        .enumerate()
+        .map(|(index, game)| {
+            let board = game
+                .prepare()
+                .map_err(|e| format!("Board {}: {}", index, e))?;
+
+            let mut solutions = enumerate_solutions(&board, &[], &[], 2);
+            if solutions.len() != 1 {
+                return Err(format!(
+                    "Board {} has {} solutions, but a pack only holds boards with one",
+                    index,
+                    if solutions.is_empty() {
+                        "no"
+                    } else {
+                        "several"
+                    }
+                ));
+            }
+
+            let difficulty = assess_difficulty(&board)
+                .map_err(|e| format!("Board {}: {}", index, e))?
+                .usage
+                .hardest
+                .unwrap_or(Technique::Singles);
+
+            Ok(PackEntry {
+                hash: canonical_hash(&board),
+                difficulty,
+                solution: solutions.pop(),
+                board,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(serde_json::to_string(&PackFile {
+        version: PACK_VERSION,
+        title,
+        entries,
+    })
+    .unwrap())
+}
+
+fn check_entry(index: usize, entry: &PackEntry) -> EntryCheck {
+    let game = &entry.board;
+    let mut failures = vec![];
+
+    let solutions = enumerate_solutions(game, &[], &[], 2);
+    match solutions.len() {
+        0 => failures.push("The board has no solution".to_string()),
+        1 => {}
+        _ => failures.push("The board has more than one solution".to_string()),
+    }
+
+    if let Some(solution) = &entry.solution {
+        let violations: Vec<_> = verify_solution(game, solution)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let mut sorted = solution.clone();
+        sorted.sort_unstable();
+
+        if !violations.is_empty() {
+            failures.push(format!(
+                "The solution breaks the rules: {}",
+                violations.join(", ")
+            ));
+        } else if solutions.len() == 1 && solutions[0] != sorted {
+            failures.push(format!(
+                "The pack gives the solution {:?}, but the board's is {:?}",
+                sorted, solutions[0]
+            ));
+        }
+    }
+
+    EntryCheck {
+        index,
+        passed: failures.is_empty(),
+        failures,
+    }
+}
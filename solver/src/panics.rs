@@ -0,0 +1,124 @@
+use crate::pack::canonical_hash;
+use crate::utils::{hash_hex, set_panic_hook};
+use crate::Game;
+use serde::Serialize;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// What an entry point returns as its error when the solver panics, as JSON, so that the page can
+/// tell a bug apart from a bad input and attach the report to a bug report.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PanicReport {
+    /// Always `panic`, as no other error is JSON.
+    error: &'static str,
+    /// What the panic said.
+    message: String,
+    /// The hash of the board, as in the activity log, if the entry point was given one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    board_hash: Option<String>,
+}
+
+/// Runs the body of an entry point, turning a panic inside it into an error carrying the panic
+/// message and the hash of `board`, the game JSON it was given, instead of a trap. The panic hook
+/// still logs the panic to the console first.
+///
+/// The bodies are unwind safe even though the compiler cannot tell: the caches only ever store
+/// values they finished building, a session or iterator whose search panicked has lost it, so
+/// that every later call fails the same way rather than going on from a broken state, and the
+/// board editor forgets the solutions it kept.
+///
+/// Only builds that unwind can catch anything, so the promise that entry points never trap does
+/// not hold for the wasm package the extension ships: stable wasm builds abort on panic, which
+/// traps, and the page sees a `WebAssembly.RuntimeError` rather than this report. Only a nightly
+/// build with `-C panic=unwind` and the exception-handling target feature keeps it, e.g.
+/// RUSTFLAGS="-C panic=unwind -C target-feature=+exception-handling" rustup run nightly \
+///   wasm-pack build -- -Z build-std=std,panic_unwind
+/// Native builds unwind by default.
+pub(crate) fn catch_panics<T>(
+    board: Option<&str>,
+    body: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    set_panic_hook();
+
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let report = PanicReport {
+            error: "panic",
+            message: panic_message(payload.as_ref()),
+            board_hash: board.map(board_hash),
+        };
+        Err(serde_json::to_string(&report).unwrap())
+    })
+}
+
+/// The message a panic was raised with, which `panic!` makes a `&str` or a `String`.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Panicked without a message".to_string()
+    }
+}
+
+/// The hash of the board as the activity log takes it, the `canonical_hash` of the game read out
+/// of `game_json`, or a hash of the JSON as given if reading it fails or panics in turn.
+fn board_hash(game_json: &str) -> String {
+    let hash = panic::catch_unwind(|| Game::from_json(game_json).map(|game| canonical_hash(&game)));
+
+    match hash {
+        Ok(Ok(hash)) => hash,
+        _ => hash_hex(game_json),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use serde_json::Value;
+
+    fn report<T>(result: Result<T, String>) -> Value {
+        let Err(error) = result else {
+            panic!("the panic was not caught");
+        };
+        serde_json::from_str(&error).unwrap()
+    }
+
+    #[test]
+    fn reports_a_panic_with_the_board_hash() {
+        let json = CORPUS[0].json;
+        let result: Result<(), _> = catch_panics(Some(json), || panic!("broken at {}", 3));
+
+        let report = report(result);
+        let hash = canonical_hash(&Game::from_json(json).unwrap());
+        assert_eq!(report["error"], "panic");
+        assert_eq!(report["message"], "broken at 3");
+        assert_eq!(report["boardHash"], hash.as_str());
+    }
+
+    #[test]
+    fn hashes_the_json_of_an_unreadable_board() {
+        let result: Result<(), _> = catch_panics(Some("{\"rows\""), || panic!("broken"));
+        assert_eq!(report(result)["boardHash"], hash_hex("{\"rows\"").as_str());
+
+        let result: Result<(), _> = catch_panics(None, || panic!("broken"));
+        assert_eq!(report(result).get("boardHash"), None);
+    }
+
+    #[test]
+    fn passes_results_through() {
+        assert_eq!(catch_panics(None, || Ok(1)), Ok(1));
+        assert_eq!(
+            catch_panics::<()>(Some(CORPUS[0].json), || Err("bad".to_string())),
+            Err("bad".to_string())
+        );
+    }
+
+    #[test]
+    fn entry_points_err_plainly_on_bad_input() {
+        let error = crate::solve("{".to_string()).unwrap_err();
+        assert!(serde_json::from_str::<Value>(&error).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use crate::constrained::find_solution;
+use crate::domains::{deduce, Elimination, Level};
+use crate::marks::BoardState;
+use crate::search::Unit;
+use crate::Game;
+use serde::Serialize;
+
+/// Why a queen cannot go on the previewed cell.
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", tag = "reason")]
+pub(crate) enum Illegal {
+    /// The cell holds one of the user's queens already.
+    Occupied,
+    /// One of the user's queens attacks the cell.
+    Attacked { queen: usize },
+}
+
+/// What placing a queen would lead to, from `preview_queen`.
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Preview {
+    /// Why the queen cannot be placed at all, in which case nothing is simulated and the rest
+    /// is empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub illegal: Option<Illegal>,
+    /// The queens singles place after it, in the order they were found.
+    pub forced: Vec<usize>,
+    /// The cells ruled out that the user's queens and marks alone leave open, in increasing
+    /// order, with what ruled them out first.
+    pub eliminations: Vec<Elimination>,
+    /// Whether the queen contradicts the board: it is illegal, the user's queens attack each
+    /// other, or deductions leave a unit without any spot.
+    pub contradiction: bool,
+    /// The row, column, or color left without any spot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emptied: Option<Unit>,
+    /// With the deep check, whether some solution agrees with the state and the queen. Deductions
+    /// can miss a contradiction a search finds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completable: Option<bool>,
+}
+
+/// Simulates placing a queen on `idx` on top of the queens and marks of `state`, running every
+/// deduction up to triples to a fixpoint. A mark on `idx` is lifted, as a queen replaces it. With
+/// `deep`, also searches for a solution agreeing with the outcome. `state` itself is left alone.
+pub(crate) fn preview_queen(
+    game: &Game,
+    state: &BoardState,
+    idx: usize,
+    deep: bool,
+) -> Result<Preview, String> {
+    state.validate(game)?;
+    let cells = game.rows * game.cols;
+    if idx >= cells {
+        return Err(format!(
+            "Cell {} is not on the board, which has cells 0..{}",
+            idx, cells
+        ));
+    }
+
+    let mut marks = state.marks.clone();
+    marks.retain(|&mark| mark != idx);
+    let before = deduce(game, &state.queens, &marks, Level::Basic);
+
+    let illegal = if state.queens.contains(&idx) {
+        Some(Illegal::Occupied)
+    } else if before.possible[idx] {
+        None
+    } else {
+        // With the mark lifted, only a queen can rule the cell out.
+        let queen = before
+            .eliminations
+            .iter()
+            .find(|elimination| elimination.cell == idx)
+            .and_then(|elimination| elimination.queen)
+            .unwrap();
+        Some(Illegal::Attacked { queen })
+    };
+    if illegal.is_some() {
+        return Ok(Preview {
+            illegal,
+            contradiction: true,
+            completable: if deep { Some(false) } else { None },
+            ..Preview::default()
+        });
+    }
+
+    let mut queens = state.queens.clone();
+    queens.push(idx);
+    let after = deduce(game, &queens, &marks, Level::Triples);
+    let contradiction = after.conflicting || after.emptied.is_some();
+    let completable = if deep {
+        Some(!contradiction && find_solution(game, &queens, &marks).is_some())
+    } else {
+        None
+    };
+
+    Ok(Preview {
+        illegal: None,
+        forced: after.forced,
+        eliminations: after
+            .eliminations
+            .into_iter()
+            .filter(|elimination| before.possible[elimination.cell])
+            .collect(),
+        contradiction,
+        emptied: after.emptied,
+        completable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::{enumerate_solutions, what_if};
+    use serde_json::{json, Value};
+
+    /// Color 0 is the single cell 1, and the only solution is [1, 7, 8, 14].
+    const SINGLES: &str = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+        1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#;
+
+    fn preview(state_json: &str, idx: usize) -> Value {
+        let preview = what_if(SINGLES.to_string(), state_json.to_string(), idx, true).unwrap();
+        serde_json::from_str(&preview).unwrap()
+    }
+
+    fn queen(cell: usize, queen: usize) -> Value {
+        json!({"cell": cell, "cause": "queen", "queen": queen})
+    }
+
+    fn singles(cell: usize, queen: usize) -> Value {
+        json!({"cell": cell, "cause": "singles", "queen": queen})
+    }
+
+    #[test]
+    fn previews_the_fallout_of_a_queen() {
+        // Only the cells the marks leave open are listed, and the mark under the queen is lifted.
+        assert_eq!(
+            preview(r#"{"queens":[],"marks":[1,9]}"#, 1),
+            json!({
+                "forced": [7, 8, 14],
+                "eliminations": [
+                    queen(0, 1),
+                    queen(2, 1),
+                    queen(3, 1),
+                    queen(4, 1),
+                    queen(5, 1),
+                    queen(6, 1),
+                    singles(10, 7),
+                    singles(11, 7),
+                    singles(12, 8),
+                    queen(13, 1),
+                    singles(15, 7),
+                ],
+                "contradiction": false,
+                "completable": true,
+            })
+        );
+        assert_eq!(
+            preview(r#"{"queens":[],"marks":[]}"#, 0),
+            json!({
+                "forced": [],
+                "eliminations": [
+                    queen(1, 0),
+                    queen(2, 0),
+                    queen(3, 0),
+                    queen(4, 0),
+                    queen(5, 0),
+                    queen(6, 0),
+                    queen(7, 0),
+                    queen(8, 0),
+                    queen(12, 0),
+                ],
+                "contradiction": true,
+                "emptied": {"row": 1},
+                "completable": false,
+            })
+        );
+    }
+
+    #[test]
+    fn refuses_cells_the_queens_hold_or_attack() {
+        let state = r#"{"queens":[1],"marks":[]}"#;
+        assert_eq!(
+            preview(state, 1),
+            json!({
+                "illegal": {"reason": "occupied"},
+                "forced": [],
+                "eliminations": [],
+                "contradiction": true,
+                "completable": false,
+            })
+        );
+        assert_eq!(
+            preview(state, 0)["illegal"],
+            json!({"reason": "attacked", "queen": 1})
+        );
+    }
+
+    #[test]
+    fn completes_exactly_the_cells_of_some_solution() {
+        for entry in CORPUS {
+            let game = Game::from_json(entry.json).unwrap();
+            let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+            let state = BoardState {
+                queens: vec![],
+                marks: vec![],
+            };
+
+            for idx in 0..game.rows * game.cols {
+                let preview = preview_queen(&game, &state, idx, true).unwrap();
+                let completable = solutions.iter().any(|solution| solution.contains(&idx));
+                assert_eq!(
+                    preview.completable,
+                    Some(completable),
+                    "{} cell {}",
+                    entry.name,
+                    idx
+                );
+                if completable {
+                    assert!(!preview.contradiction, "{} cell {}", entry.name, idx);
+                }
+                if solutions.len() == 1 && completable {
+                    assert!(
+                        preview.forced.iter().all(|idx| solutions[0].contains(idx)),
+                        "{} cell {}",
+                        entry.name,
+                        idx
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_cells_off_the_board_and_bad_states() {
+        let empty = r#"{"queens":[],"marks":[]}"#.to_string();
+        assert_eq!(
+            what_if(SINGLES.to_string(), empty, 16, false),
+            Err("Cell 16 is not on the board, which has cells 0..16".to_string())
+        );
+        assert_eq!(
+            what_if(
+                SINGLES.to_string(),
+                r#"{"queens":[1,1]}"#.to_string(),
+                0,
+                false
+            ),
+            Err("Cell 1 appears more than once among the queens and marks".to_string())
+        );
+    }
+}
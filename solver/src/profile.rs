@@ -0,0 +1,129 @@
+#[cfg(feature = "profile")]
+use serde::Serialize;
+
+/// What the search did at one depth, the number of decisions above the nodes concerned. Only
+/// gathered with the `profile` feature.
+#[cfg(feature = "profile")]
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthProfile {
+    /// Search nodes entered.
+    pub nodes: usize,
+    /// Candidate cells generated for branching.
+    pub candidates: usize,
+    /// Nodes abandoned because some required unit had no spots left.
+    pub forward_check_failures: usize,
+    /// Candidates skipped because they completed a stored nogood.
+    pub nogood_hits: usize,
+    /// Queens placed by propagation.
+    pub forced: usize,
+    /// Cells ruled out by confinement or naked subsets.
+    pub eliminated: usize,
+    /// Milliseconds spent propagating.
+    pub propagate_ms: f64,
+    /// Milliseconds spent generating and ordering candidates.
+    pub candidates_ms: f64,
+}
+
+/// Something the search did, counted towards the current depth.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(feature = "profile"), allow(dead_code))]
+pub(crate) enum Event {
+    Node,
+    Candidates(usize),
+    ForwardCheckFailure,
+    NogoodHit,
+    Forced,
+    Eliminated,
+}
+
+/// A timed part of the work done at a node.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Phase {
+    Propagate,
+    Candidates,
+}
+
+/// When a phase started. Empty without the `profile` feature.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Stamp {
+    #[cfg(feature = "profile")]
+    ms: f64,
+}
+
+/// Gathers a `DepthProfile` per depth. Without the `profile` feature it holds nothing and every
+/// method is empty, so the calls in the search compile away.
+#[derive(Clone)]
+pub(crate) struct Profiler {
+    /// One profile for every depth a board of the size can reach, allocated up front so that
+    /// counting never allocates during the search.
+    #[cfg(feature = "profile")]
+    depths: Vec<DepthProfile>,
+}
+
+#[cfg_attr(not(feature = "profile"), allow(unused_variables))]
+impl Profiler {
+    /// A profiler for a board of `rows` rows, whose searches place at most one queen per row.
+    pub fn new(rows: usize) -> Self {
+        Self {
+            #[cfg(feature = "profile")]
+            depths: vec![DepthProfile::default(); rows + 1],
+        }
+    }
+
+    #[inline(always)]
+    pub fn count(&mut self, depth: usize, event: Event) {
+        #[cfg(feature = "profile")]
+        {
+            let profile = self.at(depth);
+            match event {
+                Event::Node => profile.nodes += 1,
+                Event::Candidates(candidates) => profile.candidates += candidates,
+                Event::ForwardCheckFailure => profile.forward_check_failures += 1,
+                Event::NogoodHit => profile.nogood_hits += 1,
+                Event::Forced => profile.forced += 1,
+                Event::Eliminated => profile.eliminated += 1,
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn start(&self) -> Stamp {
+        Stamp {
+            #[cfg(feature = "profile")]
+            ms: crate::utils::now_ms(),
+        }
+    }
+
+    /// Adds the time since `start` to `phase` at `depth`.
+    #[inline(always)]
+    pub fn finish(&mut self, depth: usize, phase: Phase, start: Stamp) {
+        #[cfg(feature = "profile")]
+        {
+            let elapsed = crate::utils::now_ms() - start.ms;
+            let profile = self.at(depth);
+            match phase {
+                Phase::Propagate => profile.propagate_ms += elapsed,
+                Phase::Candidates => profile.candidates_ms += elapsed,
+            }
+        }
+    }
+
+    /// The profile of every depth reached, shallowest first.
+    #[cfg(feature = "profile")]
+    pub fn into_depths(mut self) -> Vec<DepthProfile> {
+        let reached = self
+            .depths
+            .iter()
+            .rposition(|profile| profile.nodes > 0 || profile.nogood_hits > 0)
+            .map_or(0, |depth| depth + 1);
+        self.depths.truncate(reached);
+        self.depths
+    }
+
+    #[cfg(feature = "profile")]
+    #[inline(always)]
+    fn at(&mut self, depth: usize) -> &mut DepthProfile {
+        &mut self.depths[depth]
+    }
+}
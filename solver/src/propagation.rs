@@ -0,0 +1,830 @@
+use crate::availability::CellAvailability;
+use crate::board::BoardIndex;
+use crate::profile::{Event, Phase};
+use crate::search::{Search, SearchScratch, Unit};
+use crate::{Game, NogoodCache};
+
+/// A deduction made by propagation. Deductions are kept on a trail so they can be undone, and so
+/// they can be read back to see how the board was narrowed.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Step {
+    /// `idx` was the only spot left in `unit`, so its queen went there.
+    Forced { idx: usize, unit: Unit },
+    /// Every spot left for `color` lies in `line`, so the queen of `line` has that color and
+    /// `idx`, a cell of `line` with another color, was ruled out.
+    Confined {
+        idx: usize,
+        color: usize,
+        line: Unit,
+    },
+    /// Every spot left for the colors in `colors` lies in `lines`, one line per color, so those
+    /// lines' queens have those colors and `idx`, a cell of `lines` with another color, was ruled
+    /// out.
+    Subset {
+        idx: usize,
+        colors: u64,
+        lines: Lines,
+    },
+    /// A queen on `idx` would attack every spot left in `unit`, leaving it nowhere to go, so
+    /// `idx` was ruled out.
+    Wipeout { idx: usize, unit: Unit },
+}
+
+impl Step {
+    /// The same deduction about `idx` instead: the queen of `Forced`, or the cell ruled out.
+    pub fn at(self, idx: usize) -> Self {
+        match self {
+            Step::Forced { unit, .. } => Step::Forced { idx, unit },
+            Step::Confined { color, line, .. } => Step::Confined { idx, color, line },
+            Step::Subset { colors, lines, .. } => Step::Subset { idx, colors, lines },
+            Step::Wipeout { unit, .. } => Step::Wipeout { idx, unit },
+        }
+    }
+}
+
+/// One of the rules `propagate` applies, which `Search::find_rule` also looks for on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Rule {
+    /// A unit with a single spot left, where its queen goes.
+    Singles,
+    /// Cells whose queen would leave some unit without a spot.
+    Wipeouts,
+    /// A color whose spots lie in one row or column.
+    Confinement,
+    /// `smallest` to `largest` colors, out of two or three, whose spots lie in as many rows or
+    /// columns.
+    Subsets { smallest: usize, largest: usize },
+}
+
+/// A set of rows or of columns, as a bitmask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Lines {
+    Rows(u64),
+    Cols(u64),
+}
+
+impl Lines {
+    pub fn contains(self, game: &Game, idx: usize) -> bool {
+        match self {
+            Lines::Rows(rows) => (rows >> (idx / game.cols)) & 1 == 1,
+            Lines::Cols(cols) => (cols >> (idx % game.cols)) & 1 == 1,
+        }
+    }
+
+    pub fn cells(self, index: &BoardIndex) -> impl Iterator<Item = usize> + '_ {
+        let (mask, lists) = match self {
+            Lines::Rows(rows) => (rows, &index.cells_by_row),
+            Lines::Cols(cols) => (cols, &index.cells_by_col),
+        };
+
+        lists
+            .iter()
+            .enumerate()
+            .filter(move |&(line, _)| (mask >> line) & 1 == 1)
+            .flat_map(|(_, cells)| cells.iter().copied())
+    }
+
+    /// Whether a color spanning the rows `color_rows` and the columns `color_cols` has cells
+    /// outside these lines.
+    pub fn excludes_some(self, color_rows: u64, color_cols: u64) -> bool {
+        match self {
+            Lines::Rows(rows) => color_rows & !rows != 0,
+            Lines::Cols(cols) => color_cols & !cols != 0,
+        }
+    }
+}
+
+impl<A: CellAvailability, N: NogoodCache> Search<'_, A, N> {
+    /// Applies the enabled propagation rules until none of them makes progress or some unit has
+    /// no spots at all. Returns how many steps were pushed onto the trail.
+    pub fn propagate<const COLORS: bool>(&mut self) -> usize {
+        if !self.options.propagate
+            && !self.options.arc_consistency
+            && !self.options.confinement
+            && !self.options.subsets
+        {
+            return 0;
+        }
+
+        trace_span!(_span, "propagate", depth = self.sorted_solution.len());
+        let start = self.trail.len();
+        let started = self.profiler.start();
+        let mut scratch = std::mem::take(&mut self.scratch);
+
+        loop {
+            match self.tightest_unit::<COLORS>() {
+                None | Some((0, _)) => break,
+                Some((1, unit)) if self.options.propagate => {
+                    if let Some(step) = self.forced_in(unit) {
+                        self.apply::<COLORS>(step);
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+
+            let rules = [
+                (self.options.arc_consistency, Rule::Wipeouts),
+                (COLORS && self.options.confinement, Rule::Confinement),
+                (
+                    COLORS && self.options.subsets,
+                    Rule::Subsets {
+                        smallest: 2,
+                        largest: self.subset_limit,
+                    },
+                ),
+            ];
+            let found = rules
+                .iter()
+                .filter(|&&(enabled, _)| enabled)
+                .find_map(|&(_, rule)| self.find_rule::<COLORS>(rule, &mut scratch));
+            let Some(step) = found else {
+                break;
+            };
+
+            for &idx in &scratch.cells {
+                self.apply::<COLORS>(step.at(idx));
+            }
+        }
+
+        self.scratch = scratch;
+        self.profiler
+            .finish(self.sorted_solution.len(), Phase::Propagate, started);
+        self.trail.len() - start
+    }
+
+    /// Propagates like `propagate`, then hands every step taken to `log` in order, with the cells
+    /// it ruled out: the cells a forced queen attacks, or the one cell an elimination is about.
+    /// Returns how many steps were taken.
+    pub fn propagate_logged<const COLORS: bool>(
+        &mut self,
+        mut log: impl FnMut(Step, &[usize]),
+    ) -> usize {
+        let start = self.trail.len();
+        let taken = self.propagate::<COLORS>();
+        let steps = self.trail[start..].to_vec();
+        // Steps are replayed one by one to see what each rules out, but only counted once.
+        let stats = self.stats.clone();
+        self.unpropagate(taken);
+
+        let cells = self.game.rows * self.game.cols;
+        let mut ruled_out = vec![];
+        for step in steps {
+            let open: Vec<_> = (0..cells)
+                .map(|idx| self.availability.is_available(idx))
+                .collect();
+            self.apply::<COLORS>(step);
+
+            ruled_out.clear();
+            ruled_out.extend(
+                (0..cells).filter(|&idx| open[idx] && !self.availability.is_available(idx)),
+            );
+            if let Step::Forced { idx, .. } = step {
+                ruled_out.retain(|&cell| cell != idx);
+            }
+            log(step, &ruled_out);
+        }
+        self.stats = stats;
+
+        taken
+    }
+
+    /// Undoes the last `steps` steps of the trail.
+    pub fn unpropagate(&mut self, steps: usize) {
+        for _ in 0..steps {
+            match self.trail.pop().unwrap() {
+                Step::Forced { .. } => {
+                    let idx = self.solution.pop().unwrap();
+                    self.set_queen(idx, false);
+                    self.forced_reasons.pop();
+                }
+                Step::Confined { idx, .. }
+                | Step::Subset { idx, .. }
+                | Step::Wipeout { idx, .. } => {
+                    self.availability.restore(self.game, idx);
+                    self.excluded_reasons.pop();
+                }
+            }
+        }
+    }
+
+    fn apply<const COLORS: bool>(&mut self, step: Step) {
+        trace_event!(?step);
+        if self.options.backjumping {
+            self.record_reason::<COLORS>(&step);
+        }
+
+        match step {
+            Step::Forced { idx, .. } => {
+                self.solution.push(idx);
+                self.set_queen(idx, true);
+                self.stats.forced += 1;
+                self.profiler
+                    .count(self.sorted_solution.len(), Event::Forced);
+            }
+            Step::Confined { idx, .. } => {
+                self.availability.exclude(self.game, idx);
+                self.stats.confined += 1;
+                self.profiler
+                    .count(self.sorted_solution.len(), Event::Eliminated);
+            }
+            Step::Subset { idx, .. } => {
+                self.availability.exclude(self.game, idx);
+                self.stats.subset_eliminations += 1;
+                self.profiler
+                    .count(self.sorted_solution.len(), Event::Eliminated);
+            }
+            Step::Wipeout { idx, .. } => {
+                self.availability.exclude(self.game, idx);
+                self.stats.wipeouts += 1;
+                self.profiler
+                    .count(self.sorted_solution.len(), Event::Eliminated);
+            }
+        }
+
+        self.trail.push(step);
+    }
+
+    /// Looks for one instance of `rule` on the board as it stands, without applying it. Returns
+    /// the first step applying it takes, and leaves the cells of all of them in `scratch.cells`:
+    /// the queen of a single, or the cells ruled out, each step being the first `at` one of them.
+    ///
+    /// `propagate` applies the rules through this, but for singles, which it finds from the
+    /// tightest unit it looks at anyway. Confinement and subsets only apply with `COLORS`.
+    pub fn find_rule<const COLORS: bool>(
+        &self,
+        rule: Rule,
+        scratch: &mut SearchScratch,
+    ) -> Option<Step> {
+        let game = self.game;
+        scratch.cells.clear();
+
+        let step = match rule {
+            Rule::Singles => {
+                let step = match self.tightest_unit::<COLORS>() {
+                    Some((1, unit)) => self.forced_in(unit)?,
+                    _ => return None,
+                };
+                if let Step::Forced { idx, .. } = step {
+                    scratch.cells.push(idx);
+                }
+                step
+            }
+            Rule::Wipeouts => {
+                let unit = self.find_wipeouts::<COLORS>(scratch)?;
+                Step::Wipeout {
+                    idx: scratch.cells[0],
+                    unit,
+                }
+            }
+            Rule::Confinement if COLORS => {
+                let (color, line) = self.find_confinement(scratch)?;
+                scratch
+                    .cells
+                    .extend(line.cells(&self.index).iter().filter(|&&idx| {
+                        game.idx_to_color[idx] != color && self.availability.is_available(idx)
+                    }));
+                Step::Confined {
+                    idx: scratch.cells[0],
+                    color,
+                    line,
+                }
+            }
+            Rule::Subsets { smallest, largest } if COLORS => {
+                let (colors, lines) = self.find_naked_subset(scratch, smallest, largest)?;
+                scratch
+                    .cells
+                    .extend(lines.cells(&self.index).filter(|&idx| {
+                        (colors >> game.idx_to_color[idx]) & 1 == 0
+                            && self.availability.is_available(idx)
+                    }));
+                Step::Subset {
+                    idx: scratch.cells[0],
+                    colors,
+                    lines,
+                }
+            }
+            Rule::Confinement | Rule::Subsets { .. } => return None,
+        };
+
+        Some(step)
+    }
+
+    /// The queen forced on the one spot left in `unit`, if it has one.
+    fn forced_in(&self, unit: Unit) -> Option<Step> {
+        let is_available = |&&idx: &&usize| self.availability.is_available(idx);
+        let &idx = unit.cells(&self.index).iter().find(is_available)?;
+
+        Some(Step::Forced { idx, unit })
+    }
+
+    /// Finds an unused row, required column, or required color with spots left, and the
+    /// available cells outside it whose queen would attack all of them. Those cells are left in
+    /// `scratch.cells`.
+    ///
+    /// A unit with a single spot left loses it to any attacking queen, so this rules out every
+    /// cell attacking a forced queen before it is placed, and it covers confinement too: a color
+    /// whose spots all lie in one line is wiped out by every other cell of that line.
+    fn find_wipeouts<const COLORS: bool>(&self, scratch: &mut SearchScratch) -> Option<Unit> {
+        let game = self.game;
+        let used = &self.used;
+
+        let rows = (0..game.rows)
+            .filter(|&row| (used.rows >> row) & 1 == 0)
+            .map(Unit::Row);
+        let missing_cols = used.required_cols & !used.cols;
+        let cols = (0..game.cols)
+            .filter(|&col| (missing_cols >> col) & 1 == 1)
+            .map(Unit::Col);
+        let missing_colors = if COLORS {
+            used.required_colors & !used.colors
+        } else {
+            0
+        };
+        let colors = (0..game.num_colors())
+            .filter(|&color| (missing_colors >> color) & 1 == 1)
+            .map(Unit::Color);
+
+        for unit in rows.chain(cols).chain(colors) {
+            scratch.spots.clear();
+            scratch.spots.extend(
+                unit.cells(&self.index)
+                    .iter()
+                    .filter(|&&idx| self.availability.is_available(idx)),
+            );
+            let spots = &scratch.spots;
+            let Some(&first) = spots.first() else {
+                continue;
+            };
+
+            // Any cell attacking every spot attacks the first one in particular.
+            scratch.cells.clear();
+            scratch
+                .cells
+                .extend(self.attackers_of::<COLORS>(first).filter(|&idx| {
+                    !unit.contains(game, idx)
+                        && self.availability.is_available(idx)
+                        && spots.iter().all(|&spot| self.attacks::<COLORS>(idx, spot))
+                }));
+
+            if !scratch.cells.is_empty() {
+                return Some(unit);
+            }
+        }
+
+        None
+    }
+
+    /// Every cell a queen on `idx` attacks, each once.
+    fn attackers_of<const COLORS: bool>(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let game = self.game;
+        let (row, col) = (idx / game.cols, idx % game.cols);
+        let color = game.idx_to_color[idx];
+
+        let row_cells = self.index.cells_by_row[row].iter().copied();
+        let col_cells = self.index.cells_by_col[col].iter().copied();
+        let color_cells = if COLORS {
+            &self.index.cells_by_color[color][..]
+        } else {
+            &[]
+        };
+        let color_cells = color_cells.iter().copied();
+        let neighbors = self.availability.adj_lookup().neighbors(idx).iter();
+
+        row_cells
+            .filter(move |&cell| cell != idx)
+            .chain(col_cells.filter(move |&cell| cell / game.cols != row))
+            .chain(
+                color_cells.filter(move |&cell| cell / game.cols != row && cell % game.cols != col),
+            )
+            .chain(neighbors.map(|&cell| cell as usize).filter(move |&cell| {
+                cell / game.cols != row
+                    && cell % game.cols != col
+                    && !(COLORS && game.idx_to_color[cell] == color)
+            }))
+    }
+
+    /// Whether a queen on `idx` attacks `other`.
+    fn attacks<const COLORS: bool>(&self, idx: usize, other: usize) -> bool {
+        let game = self.game;
+
+        idx != other
+            && (idx / game.cols == other / game.cols
+                || idx % game.cols == other % game.cols
+                || (COLORS && game.idx_to_color[idx] == game.idx_to_color[other])
+                || self
+                    .availability
+                    .adj_lookup()
+                    .neighbors(idx)
+                    .contains(&(other as u16)))
+    }
+
+    /// The rows and the columns holding spots of each color, as bitmasks.
+    /// Fills `rows_of` and `cols_of` in `scratch`.
+    fn color_lines(&self, scratch: &mut SearchScratch) {
+        let game = self.game;
+        let (rows_of, cols_of) = (&mut scratch.rows_of, &mut scratch.cols_of);
+        rows_of.clear();
+        rows_of.resize(game.num_colors(), 0);
+        cols_of.clear();
+        cols_of.resize(game.num_colors(), 0);
+
+        for (color, cells) in self.index.cells_by_color.iter().enumerate() {
+            for &idx in cells
+                .iter()
+                .filter(|&&idx| self.availability.is_available(idx))
+            {
+                rows_of[color] |= 1 << (idx / game.cols);
+                cols_of[color] |= 1 << (idx % game.cols);
+            }
+        }
+    }
+
+    /// Finds a required color still missing its queen whose spots all lie in one row or column
+    /// that also has spots of other colors.
+    fn find_confinement(&self, scratch: &mut SearchScratch) -> Option<(usize, Unit)> {
+        let game = self.game;
+        self.color_lines(scratch);
+        let (rows_of, cols_of) = (&scratch.rows_of, &scratch.cols_of);
+
+        let confines = |color: usize, line: Unit| {
+            line.cells(&self.index)
+                .iter()
+                .any(|&idx| game.idx_to_color[idx] != color && self.availability.is_available(idx))
+        };
+
+        let missing_colors = self.used.required_colors & !self.used.colors;
+        for color in (0..game.num_colors()).filter(|&color| (missing_colors >> color) & 1 == 1) {
+            if rows_of[color].count_ones() == 1 {
+                let line = Unit::Row(rows_of[color].trailing_zeros() as usize);
+                if confines(color, line) {
+                    return Some((color, line));
+                }
+            }
+
+            if cols_of[color].count_ones() == 1 {
+                let line = Unit::Col(cols_of[color].trailing_zeros() as usize);
+                if confines(color, line) {
+                    return Some((color, line));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Finds two or three required colors still missing their queens whose spots together lie in
+    /// as many rows (or columns), where those lines also have spots of other colors. A single
+    /// color is the confinement rule. Only looks for sets of `smallest` to `largest` colors.
+    fn find_naked_subset(
+        &self,
+        scratch: &mut SearchScratch,
+        smallest: usize,
+        largest: usize,
+    ) -> Option<(u64, Lines)> {
+        let game = self.game;
+        self.color_lines(scratch);
+        let SearchScratch {
+            rows_of,
+            cols_of,
+            colors_in_row,
+            colors_in_col,
+            colors: candidates,
+            ..
+        } = scratch;
+
+        colors_in_row.clear();
+        colors_in_row.resize(game.rows, 0);
+        colors_in_col.clear();
+        colors_in_col.resize(game.cols, 0);
+        for (color, (&rows, &cols)) in rows_of.iter().zip(cols_of.iter()).enumerate() {
+            for row in (0..game.rows).filter(|&row| (rows >> row) & 1 == 1) {
+                colors_in_row[row] |= 1 << color;
+            }
+            for col in (0..game.cols).filter(|&col| (cols >> col) & 1 == 1) {
+                colors_in_col[col] |= 1 << color;
+            }
+        }
+
+        let missing_colors = self.used.required_colors & !self.used.colors;
+        candidates.clear();
+        candidates.extend(
+            (0..game.num_colors())
+                .filter(|&color| (missing_colors >> color) & 1 == 1 && rows_of[color] != 0),
+        );
+
+        let check = |colors: &[usize]| {
+            let set = colors.iter().fold(0u64, |set, &color| set | (1 << color));
+            let k = colors.len() as u32;
+
+            let rows = colors.iter().fold(0, |rows, &color| rows | rows_of[color]);
+            if rows.count_ones() == k
+                && (0..game.rows)
+                    .any(|row| (rows >> row) & 1 == 1 && colors_in_row[row] & !set != 0)
+            {
+                return Some((set, Lines::Rows(rows)));
+            }
+
+            let cols = colors.iter().fold(0, |cols, &color| cols | cols_of[color]);
+            if cols.count_ones() == k
+                && (0..game.cols)
+                    .any(|col| (cols >> col) & 1 == 1 && colors_in_col[col] & !set != 0)
+            {
+                return Some((set, Lines::Cols(cols)));
+            }
+
+            None
+        };
+
+        // Every pair before any triple, so a triple is only ever reported where no pair applies.
+        if smallest <= 2 {
+            for (i, &a) in candidates.iter().enumerate() {
+                for &b in &candidates[i + 1..] {
+                    if let Some(subset) = check(&[a, b]) {
+                        return Some(subset);
+                    }
+                }
+            }
+        }
+
+        if largest < 3 {
+            return None;
+        }
+
+        for (i, &a) in candidates.iter().enumerate() {
+            for (j, &b) in candidates.iter().enumerate().skip(i + 1) {
+                for &c in &candidates[j + 1..] {
+                    if let Some(subset) = check(&[a, b, c]) {
+                        return Some(subset);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availability::Availability;
+    use crate::bitboard::BitAvailability;
+    use crate::options::Options;
+    use crate::NoGoods;
+
+    /// Everything propagating changes: the available cells, the spots left in every unit, the
+    /// queens placed, and the units they use.
+    fn snapshot<A: CellAvailability>(
+        search: &Search<A, NoGoods>,
+    ) -> (Vec<usize>, Vec<Vec<usize>>, Vec<usize>, [u64; 3]) {
+        let game = search.game;
+        let available = (0..game.rows * game.cols)
+            .filter(|&idx| search.availability.is_available(idx))
+            .collect();
+        let spots = vec![
+            search.availability.row_spots().to_vec(),
+            search.availability.col_spots().to_vec(),
+            search.availability.color_spots().to_vec(),
+        ];
+        let used = [search.used.rows, search.used.cols, search.used.colors];
+
+        (available, spots, search.solution.clone(), used)
+    }
+
+    /// Propagates on the empty board with both availability trackers, checking that undoing it
+    /// leaves the board as it was. Returns the steps taken and the cells left available.
+    fn propagated(json: &str, options: &Options) -> (Vec<Step>, Vec<usize>) {
+        let game = Game::from_json(json).unwrap();
+
+        let mut bits =
+            Search::<_, NoGoods>::with_cache(&game, options, BitAvailability::new(&game));
+        let before = snapshot(&bits);
+        let taken = bits.propagate::<true>();
+        let bit_available = snapshot(&bits).0;
+        bits.unpropagate(taken);
+        assert_eq!(snapshot(&bits), before, "{}", json);
+
+        let mut search = Search::<_, NoGoods>::with_cache(&game, options, Availability::new(&game));
+        let before = snapshot(&search);
+        let taken = search.propagate::<true>();
+        let steps = search.trail.clone();
+        let available = snapshot(&search).0;
+        assert_eq!(steps.len(), taken);
+        assert_eq!(available, bit_available, "{}", json);
+        search.unpropagate(taken);
+        assert_eq!(snapshot(&search), before, "{}", json);
+        assert!(search.trail.is_empty());
+
+        (steps, available)
+    }
+
+    /// Color 0 has a single cell, and each queen placed leaves one spot in some other unit, so
+    /// singles alone solve the board.
+    #[test]
+    fn singles_solve_a_board_without_branching() {
+        let json = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+            1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#;
+        let options = Options {
+            propagate: true,
+            ..Options::default()
+        };
+
+        let (steps, available) = propagated(json, &options);
+        let mut placed: Vec<_> = steps
+            .iter()
+            .map(|step| match *step {
+                Step::Forced { idx, .. } => idx,
+                _ => panic!("{:?} is not a single", step),
+            })
+            .collect();
+        assert!(matches!(
+            steps[0],
+            Step::Forced {
+                idx: 1,
+                unit: Unit::Color(0)
+            }
+        ));
+        placed.sort_unstable();
+        assert_eq!(placed, [1, 7, 8, 14]);
+        assert!(available.is_empty());
+    }
+
+    #[test]
+    fn singles_are_off_by_default() {
+        let json = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+            1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#;
+
+        let (steps, available) = propagated(json, &Options::default());
+        assert!(steps.is_empty());
+        assert_eq!(available.len(), 16);
+    }
+
+    /// Color 0 lies in row 0, so the rest of row 0 goes; that leaves color 1 in row 1, so the
+    /// rest of row 1 goes too.
+    const CONFINED: &str = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+        0,0,1,1, 2,2,1,1, 2,2,3,3, 2,2,3,3]}"#;
+
+    #[test]
+    fn confinement_rules_out_the_rest_of_the_line() {
+        let options = Options {
+            confinement: true,
+            ..Options::default()
+        };
+
+        let (steps, available) = propagated(CONFINED, &options);
+        let eliminated: Vec<_> = steps
+            .iter()
+            .map(|step| match *step {
+                Step::Confined { idx, color, line } => (idx, color, line),
+                _ => panic!("{:?} is not a confinement", step),
+            })
+            .collect();
+        assert_eq!(
+            eliminated,
+            [
+                (2, 0, Unit::Row(0)),
+                (3, 0, Unit::Row(0)),
+                (4, 1, Unit::Row(1)),
+                (5, 1, Unit::Row(1)),
+            ]
+        );
+        assert_eq!(available, [0, 1, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+    }
+
+    /// The first `rule` found on the empty board, with the cells it applies to.
+    fn found(json: &str, rule: Rule) -> Option<(Step, Vec<usize>)> {
+        let game = Game::from_json(json).unwrap();
+        let options = Options::default();
+        let search = Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+        let mut scratch = SearchScratch::default();
+
+        let step = search.find_rule::<true>(rule, &mut scratch)?;
+        Some((step, scratch.cells))
+    }
+
+    /// Colors 0 and 1 lie in rows 0 and 1 between them, without either lying in one line.
+    const PAIR: &str = r#"{"rows":5,"cols":5,"colors":[0,1,2,3,4],"idxToColor":[
+        0,1,2,2,2, 1,0,2,2,3, 2,2,2,3,3, 4,4,4,3,3, 4,4,4,4,4]}"#;
+
+    /// Colors 0, 1 and 2 lie in rows 0 to 2 between them, while any two of them span more.
+    const TRIPLE: &str = r#"{"rows":5,"cols":5,"colors":[0,1,2,3,4],"idxToColor":[
+        0,3,3,3,2, 3,0,1,3,3, 3,3,2,1,3, 3,3,4,4,4, 4,4,4,4,4]}"#;
+
+    const ALL_SUBSETS: Rule = Rule::Subsets {
+        smallest: 2,
+        largest: 3,
+    };
+
+    #[test]
+    fn subsets_need_no_confinement() {
+        for json in [PAIR, TRIPLE] {
+            assert!(found(json, Rule::Confinement).is_none(), "{}", json);
+        }
+        let pairs = Rule::Subsets {
+            smallest: 2,
+            largest: 2,
+        };
+        assert!(found(TRIPLE, pairs).is_none());
+    }
+
+    #[test]
+    fn a_pair_rules_out_other_colors_in_its_rows() {
+        let (step, cells) = found(PAIR, ALL_SUBSETS).unwrap();
+        assert!(matches!(
+            step,
+            Step::Subset {
+                idx: 2,
+                colors: 0b11,
+                lines: Lines::Rows(0b11)
+            }
+        ));
+        assert_eq!(cells, [2, 3, 4, 7, 8, 9]);
+
+        let options = Options {
+            subsets: true,
+            ..Options::default()
+        };
+        let (steps, _) = propagated(PAIR, &options);
+        let first: Vec<_> = steps[..cells.len()]
+            .iter()
+            .map(|step| match *step {
+                Step::Subset {
+                    idx,
+                    colors: 0b11,
+                    lines: Lines::Rows(0b11),
+                } => idx,
+                _ => panic!("{:?} is not the pair's", step),
+            })
+            .collect();
+        assert_eq!(first, cells);
+    }
+
+    #[test]
+    fn a_triple_rules_out_other_colors_in_its_rows() {
+        let (step, cells) = found(TRIPLE, ALL_SUBSETS).unwrap();
+        assert!(matches!(
+            step,
+            Step::Subset {
+                idx: 1,
+                colors: 0b111,
+                lines: Lines::Rows(0b111)
+            }
+        ));
+        assert_eq!(cells, [1, 2, 3, 5, 8, 9, 10, 11, 14]);
+
+        let options = Options {
+            subsets: true,
+            ..Options::default()
+        };
+        let (steps, available) = propagated(TRIPLE, &options);
+        assert!(steps.len() >= cells.len());
+        assert!(cells.iter().all(|idx| !available.contains(idx)));
+    }
+
+    /// Wipeouts alone narrow this board down to its solution. The first goes to cell 5, next to
+    /// cell 0 and sharing color 2 with the rest of column 0.
+    #[test]
+    fn wipeouts_rule_out_cells_attacking_a_whole_unit() {
+        let (step, cells) = found(CONFINED, Rule::Wipeouts).unwrap();
+        assert!(matches!(
+            step,
+            Step::Wipeout {
+                idx: 5,
+                unit: Unit::Col(0)
+            }
+        ));
+        assert_eq!(cells, [5]);
+
+        let options = Options {
+            arc_consistency: true,
+            ..Options::default()
+        };
+        let (steps, available) = propagated(CONFINED, &options);
+        assert_eq!(available, [1, 7, 8, 14]);
+
+        // Each cell ruled out attacked every spot its unit had left at the time.
+        let game = Game::from_json(CONFINED).unwrap();
+        let mut search =
+            Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+        for step in steps {
+            let Step::Wipeout { idx, unit } = step else {
+                panic!("{:?} is not a wipeout", step);
+            };
+            let spots: Vec<_> = unit
+                .cells(&search.index)
+                .iter()
+                .copied()
+                .filter(|&spot| search.availability.is_available(spot))
+                .collect();
+            assert!(!spots.is_empty(), "{:?}", step);
+            assert!(
+                spots.iter().all(|&spot| search.attacks::<true>(idx, spot)),
+                "{:?} leaves some of {:?}",
+                step,
+                spots
+            );
+            search.apply::<true>(step);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::difficulty::{same_deduction, Technique};
+use crate::marks::BoardState;
+use crate::options::Options;
+use crate::propagation::Step;
+use crate::search::Search;
+use crate::technique::{find_technique, Finding, Instance};
+use crate::utils::Rng;
+use crate::Game;
+use serde::Serialize;
+
+/// A drill from `make_quiz`.
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub(crate) enum Quiz {
+    /// `position` is the board just before the deduction numbered `deduction` out of
+    /// `deductions`, which is `answer`.
+    Ready {
+        position: BoardState,
+        answer: Instance,
+        deduction: usize,
+        deductions: usize,
+    },
+    /// The deductions solving the board never use the technique.
+    Unused,
+}
+
+/// A position to find a deduction using `technique` in, picked reproducibly from `seed` among
+/// those the deductions solving `game` make, with that deduction as the answer. Fails for
+/// guessing, which is not a deduction.
+///
+/// The position is rebuilt by replaying the steps of the trail before the deduction, and the
+/// answer is found again on it with `find_technique`, which must come back with the very
+/// deduction picked, or the quiz fails rather than asking about a different one. Easier
+/// techniques never apply there, since the deductions try them first.
+pub(crate) fn make_quiz(game: &Game, technique: Technique, seed: u64) -> Result<Quiz, String> {
+    if technique == Technique::Guessing {
+        return Err("Guessing is not a deduction, so it makes no quiz".to_string());
+    }
+
+    let trail = if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        deduction_trail(game, BitAvailability::new(game))
+    } else {
+        deduction_trail(game, Availability::new(game))
+    };
+
+    // Where each deduction starts on the trail, one ruling out several cells taking a step per
+    // cell.
+    let starts: Vec<_> = (0..trail.len())
+        .filter(|&pos| pos == 0 || !same_deduction(trail[pos - 1], trail[pos]))
+        .collect();
+    let picks: Vec<_> = (0..starts.len())
+        .filter(|&deduction| Technique::of_step(trail[starts[deduction]]) == technique)
+        .collect();
+    if picks.is_empty() {
+        return Ok(Quiz::Unused);
+    }
+
+    let deduction = picks[Rng::new(seed).below(picks.len())];
+    let start = starts[deduction];
+    let end = starts.get(deduction + 1).copied().unwrap_or(trail.len());
+
+    let position = position_after(&trail[..start]);
+    let Finding::Found(answer) = find_technique(game, &position, technique)? else {
+        return Err(format!(
+            "The replayed position disagrees with deduction {}: {:?} is not found on it",
+            deduction, technique
+        ));
+    };
+
+    let mut effect: Vec<_> = trail[start..end]
+        .iter()
+        .map(|&step| cell_of(step))
+        .collect();
+    effect.sort_unstable();
+    let found = match answer.queen {
+        Some(queen) => vec![queen],
+        None => answer.eliminations.clone(),
+    };
+    if found != effect {
+        return Err(format!(
+            "The replayed position disagrees with deduction {}: it finds {:?} instead of {:?}",
+            deduction, found, effect
+        ));
+    }
+
+    Ok(Quiz::Ready {
+        position,
+        answer,
+        deduction,
+        deductions: starts.len(),
+    })
+}
+
+/// The steps of every deduction `game` allows from the empty board, easier techniques first.
+fn deduction_trail<A: CellAvailability>(game: &Game, availability: A) -> Vec<Step> {
+    let options = Options::for_deduction();
+    let mut search = Search::new(game, &options, availability);
+    if game.has_color_rule() {
+        search.propagate::<true>();
+    } else {
+        search.propagate::<false>();
+    }
+
+    search.trail
+}
+
+/// The board after `steps` from the empty board: the queens forced and, as marks, the cells
+/// ruled out, each in the order of the steps.
+fn position_after(steps: &[Step]) -> BoardState {
+    let mut position = BoardState::default();
+    for &step in steps {
+        match step {
+            Step::Forced { idx, .. } => position.queens.push(idx),
+            _ => position.marks.push(cell_of(step)),
+        }
+    }
+
+    position
+}
+
+/// The queen a step places, or the cell it rules out.
+fn cell_of(step: Step) -> usize {
+    match step {
+        Step::Forced { idx, .. }
+        | Step::Confined { idx, .. }
+        | Step::Subset { idx, .. }
+        | Step::Wipeout { idx, .. } => idx,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+
+    /// Color 0 is the single cell 1, and the only solution is [1, 7, 8, 14].
+    const SINGLES: &str = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+        1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#;
+
+    /// Takes confinement, pairs and triples: colors 0, 1 and 2 lie in rows 0 to 2 between them,
+    /// while any two of them span more. No corpus board needs triples.
+    const TRIPLE: &str = r#"{"rows":5,"cols":5,"colors":[0,1,2,3,4],"idxToColor":[
+        0,3,3,3,2, 3,0,1,3,3, 3,3,2,1,3, 3,3,4,4,4, 4,4,4,4,4]}"#;
+
+    const TECHNIQUES: [Technique; 4] = [
+        Technique::Singles,
+        Technique::Confinement,
+        Technique::Pairs,
+        Technique::Triples,
+    ];
+
+    fn quiz(game_json: &str, technique: &str, seed: u64) -> Result<String, String> {
+        crate::make_quiz(game_json.to_string(), technique.to_string(), seed)
+    }
+
+    #[test]
+    fn asks_about_a_deduction_of_a_small_board() {
+        assert_eq!(
+            quiz(SINGLES, "singles", 1),
+            Ok(concat!(
+                r#"{"status":"ready","position":{"queens":[1,7],"marks":[]},"#,
+                r#""answer":{"technique":"singles","cells":[8,9,10,11],"units":[{"row":2}],"#,
+                r#""queen":8,"eliminations":[]},"deduction":2,"deductions":4}"#,
+            )
+            .to_string())
+        );
+        // Singles alone solve it.
+        for technique in ["confinement", "pairs", "triples"] {
+            assert_eq!(
+                quiz(SINGLES, technique, 0),
+                Ok(r#"{"status":"unused"}"#.to_string())
+            );
+        }
+    }
+
+    /// No easier technique applies at the position of any quiz, `find_technique` finds the
+    /// answer there, and on corpus boards the position is on the way to the solution.
+    #[test]
+    fn quizzes_every_technique_on_corpus_boards() {
+        let triple = (TRIPLE, "triple", &[][..]);
+        let boards = CORPUS
+            .iter()
+            .map(|entry| (entry.json, entry.name, entry.solution))
+            .chain(std::iter::once(triple));
+
+        let mut asked = vec![];
+        for (json, name, solution) in boards {
+            let game = Game::from_json(json).unwrap();
+            for technique in TECHNIQUES {
+                for seed in 0..4 {
+                    let context = format!("{} {:?} seed {}", name, technique, seed);
+                    let (position, answer, deduction, deductions) =
+                        match make_quiz(&game, technique, seed).unwrap() {
+                            Quiz::Ready {
+                                position,
+                                answer,
+                                deduction,
+                                deductions,
+                            } => (position, answer, deduction, deductions),
+                            Quiz::Unused => continue,
+                        };
+                    asked.push(technique);
+
+                    assert!(deduction < deductions, "{}", context);
+                    assert_eq!(answer.technique, technique, "{}", context);
+                    let Finding::Found(found) =
+                        find_technique(&game, &position, technique).unwrap()
+                    else {
+                        panic!("{}", context);
+                    };
+                    assert_eq!(
+                        serde_json::to_value(&found).unwrap(),
+                        serde_json::to_value(&answer).unwrap(),
+                        "{}",
+                        context
+                    );
+                    for &easier in TECHNIQUES.iter().filter(|&&easier| easier < technique) {
+                        assert!(
+                            matches!(
+                                find_technique(&game, &position, easier).unwrap(),
+                                Finding::Absent
+                            ),
+                            "{} {:?}",
+                            context,
+                            easier
+                        );
+                    }
+                    if !solution.is_empty() {
+                        let on_the_way =
+                            position.queens.iter().all(|queen| solution.contains(queen))
+                                && position.marks.iter().all(|mark| !solution.contains(mark));
+                        assert!(on_the_way, "{}", context);
+                    }
+                }
+            }
+        }
+
+        for technique in TECHNIQUES {
+            assert!(asked.contains(&technique), "{:?}", technique);
+        }
+    }
+
+    #[test]
+    fn reproduces_quizzes_from_the_seed() {
+        let entry = CORPUS.iter().find(|entry| entry.name == "11x11").unwrap();
+        let mut deductions = vec![];
+        for seed in 0..8 {
+            let first = quiz(entry.json, "singles", seed).unwrap();
+            assert_eq!(quiz(entry.json, "singles", seed).unwrap(), first);
+
+            let value: serde_json::Value = serde_json::from_str(&first).unwrap();
+            deductions.push(value["deduction"].as_u64().unwrap());
+        }
+        deductions.sort_unstable();
+        deductions.dedup();
+        assert!(deductions.len() > 1, "{:?}", deductions);
+    }
+
+    #[test]
+    fn rejects_guessing_and_unknown_techniques() {
+        assert_eq!(
+            quiz(SINGLES, "guessing", 0),
+            Err("Guessing is not a deduction, so it makes no quiz".to_string())
+        );
+        assert_eq!(
+            quiz(SINGLES, "x", 0),
+            Err(concat!(
+                r#"Unknown technique "x", expected one of "#,
+                "singles, confinement, pairs, triples, guessing",
+            )
+            .to_string())
+        );
+        assert!(quiz("{", "singles", 0).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::recolor::{judge_recolors, Recolor, Verdict};
+use crate::regions::{disconnected_colors, edge_neighbors};
+use crate::utils::Rng;
+use crate::{enumerate_solutions, Game};
+use serde::Serialize;
+
+/// A board with its colors evened out by `rebalance_regions`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Rebalanced {
+    pub game: Game,
+    /// The cells moved to another color, in order.
+    pub shifts: Vec<Recolor>,
+    /// The difference in cells between the largest and the smallest color at the end.
+    pub spread: usize,
+    /// Whether `spread` is within the target.
+    pub met: bool,
+}
+
+/// Moves cells on the borders between colors from larger colors to smaller ones until the
+/// largest has at most `target_spread` cells more than the smallest, reproducibly from `seed`.
+///
+/// The cells of the solution's queens never move. A shift is kept only if the board stays valid,
+/// keeps every color in one piece if it was, and still has the same one solution; otherwise it is
+/// undone and another tried. Stops short of the target when no shift passes. Fails unless the
+/// board has exactly one solution.
+pub(crate) fn rebalance_regions(
+    game: &Game,
+    seed: u64,
+    target_spread: usize,
+) -> Result<Rebalanced, String> {
+    if !game.has_color_rule() {
+        return Err("Colorless boards have no colors to rebalance".to_string());
+    }
+
+    let mut solutions = enumerate_solutions(game, &[], &[], 2);
+    if solutions.len() != 1 {
+        return Err("Only boards with exactly one solution can be rebalanced".to_string());
+    }
+    let solution = solutions.pop().unwrap();
+    let contiguous = disconnected_colors(game).is_empty();
+
+    let mut game = game.clone();
+    let mut rng = Rng::new(seed);
+    let mut shifts = vec![];
+    let cells = game.rows * game.cols;
+
+    // Every shift lowers the sum of the squared sizes, so shifts never undo each other, but their
+    // number is bounded all the same.
+    for _ in 0..cells {
+        let sizes = color_sizes(&game);
+        if spread(&sizes) <= target_spread {
+            break;
+        }
+
+        // Any shift from a color to one at least two cells smaller evens the sizes out, but only
+        // those out of the largest colors or into the smallest ones narrow the spread, so they
+        // are tried first, each group in random order.
+        let (min, max) = (*sizes.iter().min().unwrap(), *sizes.iter().max().unwrap());
+        let mut candidates: Vec<_> = (0..cells)
+            .filter(|idx| !solution.contains(idx))
+            .flat_map(|idx| {
+                let game = &game;
+                let sizes = &sizes;
+                let from = game.idx_to_color[idx];
+                edge_neighbors(game, idx)
+                    .into_iter()
+                    .map(move |neighbor| game.idx_to_color[neighbor])
+                    .filter(move |&to| sizes[from] > sizes[to] + 1)
+                    .map(move |color| Recolor { idx, color })
+            })
+            .collect();
+        candidates.sort_unstable_by_key(|recolor| (recolor.idx, recolor.color));
+        candidates.dedup();
+        for pos in (1..candidates.len()).rev() {
+            candidates.swap(pos, rng.below(pos + 1));
+        }
+        candidates.sort_by_key(|recolor| {
+            let from = game.idx_to_color[recolor.idx];
+            sizes[from] != max && sizes[recolor.color] != min
+        });
+
+        let shift = candidates.into_iter().find(|&recolor| {
+            matches!(
+                judge_recolors(&mut game, &[recolor], contiguous),
+                Verdict::Unique { solution: ref kept } if *kept == solution
+            )
+        });
+        let Some(shift) = shift else {
+            break;
+        };
+
+        game.idx_to_color[shift.idx] = shift.color;
+        shifts.push(shift);
+    }
+
+    let spread = spread(&color_sizes(&game));
+    Ok(Rebalanced {
+        game,
+        shifts,
+        spread,
+        met: spread <= target_spread,
+    })
+}
+
+/// The number of cells of every color.
+fn color_sizes(game: &Game) -> Vec<usize> {
+    let mut sizes = vec![0; game.num_colors()];
+    for &color in &game.idx_to_color {
+        sizes[color] += 1;
+    }
+    sizes
+}
+
+fn spread(sizes: &[usize]) -> usize {
+    sizes.iter().max().unwrap_or(&0) - sizes.iter().min().unwrap_or(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::rebalance;
+
+    fn squares(game: &Game) -> usize {
+        color_sizes(game).iter().map(|size| size * size).sum()
+    }
+
+    #[test]
+    fn evens_out_corpus_boards_keeping_the_solution() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            let before = spread(&color_sizes(&game));
+
+            for target in [0, 12, before] {
+                let rebalanced = rebalance_regions(&game, 1, target).unwrap();
+                assert_eq!(rebalanced.spread, spread(&color_sizes(&rebalanced.game)));
+                assert_eq!(
+                    rebalanced.met,
+                    rebalanced.spread <= target,
+                    "{}",
+                    entry.name
+                );
+                assert!(rebalanced.spread <= before, "{}", entry.name);
+                if target == before {
+                    assert!(rebalanced.shifts.is_empty(), "{}", entry.name);
+                }
+
+                // Replaying the shifts only ever evens the sizes out, and leaves the board.
+                let mut replayed = game.clone();
+                for shift in &rebalanced.shifts {
+                    assert!(!entry.solution.contains(&shift.idx), "{}", entry.name);
+                    let squares_before = squares(&replayed);
+                    replayed.idx_to_color[shift.idx] = shift.color;
+                    assert!(squares(&replayed) < squares_before, "{}", entry.name);
+                }
+                assert_eq!(replayed.idx_to_color, rebalanced.game.idx_to_color);
+
+                assert!(
+                    disconnected_colors(&rebalanced.game).is_empty(),
+                    "{}",
+                    entry.name
+                );
+                assert_eq!(
+                    enumerate_solutions(&rebalanced.game, &[], &[], 2),
+                    [entry.solution],
+                    "{}",
+                    entry.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stops_at_the_target() {
+        // From seed 1, the shifts found take the 8x8 board from a spread of 24 down to 9.
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        let best = rebalance_regions(&game, 1, 0).unwrap();
+        assert_eq!((best.spread, best.met), (9, false));
+
+        let met = rebalance_regions(&game, 1, 12).unwrap();
+        assert!(met.met && met.spread <= 12 && met.spread > 9);
+        assert!(met.shifts.len() < best.shifts.len());
+    }
+
+    #[test]
+    fn reproduces_shifts_from_the_seed() {
+        let game = CORPUS[1].json.to_string();
+        assert_eq!(
+            rebalance(game.clone(), 3, 0).unwrap(),
+            rebalance(game.clone(), 3, 0).unwrap()
+        );
+        assert_ne!(
+            rebalance(game.clone(), 3, 0).unwrap(),
+            rebalance(game, 4, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_boards_without_exactly_one_solution() {
+        let colorless = r#"{"rows":6,"cols":6,"rules":{"colorless":true}}"#;
+        assert_eq!(
+            rebalance(colorless.to_string(), 0, 0),
+            Err("Colorless boards have no colors to rebalance".to_string())
+        );
+
+        let mut several = Game::from_json(CORPUS[0].json).unwrap();
+        several.idx_to_color[33] = 1;
+        let unsolvable = CORPUS
+            .iter()
+            .find(|entry| entry.solution.is_empty())
+            .unwrap();
+        for game in [several, Game::from_json(unsolvable.json).unwrap()] {
+            assert_eq!(
+                rebalance_regions(&game, 0, 0).err(),
+                Some("Only boards with exactly one solution can be rebalanced".to_string())
+            );
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::regions::disconnected_colors;
+use crate::{enumerate_solutions, Game};
+use serde::{Deserialize, Serialize};
+
+/// Moving one cell to another color, as tried by `what_if_recolor`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Recolor {
+    pub idx: usize,
+    pub color: usize,
+}
+
+/// What a board turns into after recoloring some cells.
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub(crate) enum Verdict {
+    /// The recolored board breaks a rule of well-formed boards.
+    Invalid {
+        error: String,
+    },
+    Unsolvable,
+    Unique {
+        solution: Vec<usize>,
+    },
+    Multiple,
+}
+
+/// Applies `recolors` to `game` together and judges the result: whether it is still a valid
+/// board, with every color in one piece if `contiguous` asks for it, and whether it has no, one,
+/// or several solutions. `game` is left as it was.
+pub(crate) fn judge_recolors(game: &mut Game, recolors: &[Recolor], contiguous: bool) -> Verdict {
+    let cells = game.rows * game.cols;
+
+    if !game.has_color_rule() {
+        return Verdict::Invalid {
+            error: "Colorless boards have no colors to change".to_string(),
+        };
+    }
+
+    if let Some(recolor) = recolors.iter().find(|recolor| recolor.idx >= cells) {
+        return Verdict::Invalid {
+            error: format!(
+                "Cell {} is not on the board, which has cells 0..{}",
+                recolor.idx, cells
+            ),
+        };
+    }
+
+    let previous: Vec<_> = recolors
+        .iter()
+        .map(|recolor| std::mem::replace(&mut game.idx_to_color[recolor.idx], recolor.color))
+        .collect();
+
+    let verdict = judge(game, contiguous);
+
+    for (recolor, color) in recolors.iter().zip(previous).rev() {
+        game.idx_to_color[recolor.idx] = color;
+    }
+
+    verdict
+}
+
+/// Judges `game` as it stands, like `judge_recolors` without recoloring anything.
+pub(crate) fn judge(game: &Game, contiguous: bool) -> Verdict {
+    if let Err(error) = game.validate() {
+        return Verdict::Invalid { error };
+    }
+
+    if contiguous {
+        if let Some(color) = disconnected_colors(game).first() {
+            return Verdict::Invalid {
+                error: format!("Color {} is split into several pieces", color),
+            };
+        }
+    }
+
+    let mut solutions = enumerate_solutions(game, &[], &[], 2);
+    match solutions.len() {
+        0 => Verdict::Unsolvable,
+        1 => Verdict::Unique {
+            solution: solutions.pop().unwrap(),
+        },
+        _ => Verdict::Multiple,
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::{run_counter, what_if_recolor, what_if_recolor_batch};
+
+    /// Color 0 is the single cell 1, and the only solution is [1, 7, 8, 14].
+    const SINGLES: &str = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+        1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#;
+
+    fn verdict(game_json: &str, idx: usize, color: usize, contiguous: bool) -> String {
+        let mut game = Game::from_json(game_json).unwrap();
+        let verdict = judge_recolors(&mut game, &[Recolor { idx, color }], contiguous);
+        serde_json::to_string(&verdict).unwrap()
+    }
+
+    #[test]
+    fn judges_every_outcome() {
+        let unique = r#"{"status":"unique","solution":[1,7,8,14]}"#;
+        assert_eq!(verdict(SINGLES, 0, 1, true), unique);
+        // Color 0 gains a second spot in the first row.
+        assert_eq!(verdict(SINGLES, 2, 0, true), r#"{"status":"multiple"}"#);
+        // Color 0 loses its only cell.
+        assert_eq!(verdict(SINGLES, 1, 1, true), r#"{"status":"unsolvable"}"#);
+        assert_eq!(verdict(SINGLES, 14, 2, true), r#"{"status":"unsolvable"}"#);
+
+        // Cell 0 joins color 2 without touching it, which only matters when asked.
+        assert_eq!(
+            verdict(SINGLES, 0, 2, true),
+            r#"{"status":"invalid","error":"Color 2 is split into several pieces"}"#
+        );
+        assert_eq!(verdict(SINGLES, 0, 2, false), unique);
+    }
+
+    #[test]
+    fn rejects_recolors_breaking_the_board() {
+        let invalid = |json: &str| -> String {
+            let value: serde_json::Value = serde_json::from_str(json).unwrap();
+            assert_eq!(value["status"], "invalid");
+            value["error"].as_str().unwrap().to_string()
+        };
+
+        assert_eq!(
+            invalid(&verdict(SINGLES, 16, 0, false)),
+            "Cell 16 is not on the board, which has cells 0..16"
+        );
+        assert_eq!(
+            invalid(&verdict(SINGLES, 3, 4, false)),
+            "Cell 3 has color 4, but colors must be in 0..4"
+        );
+        assert_eq!(
+            invalid(&verdict(r#"{"rows":4,"cols":4}"#, 3, 0, false)),
+            "Colorless boards have no colors to change"
+        );
+    }
+
+    /// Each recolored corpus board gets the verdict its solution count calls for, and the game
+    /// is left as it was.
+    #[test]
+    fn judges_like_counting_the_recolored_board() {
+        for entry in CORPUS.iter().take(2) {
+            let mut game = Game::from_json(entry.json).unwrap();
+            let before = game.to_json();
+
+            for idx in 0..game.rows * game.cols {
+                let color = game.idx_to_color[(idx + 1) % (game.rows * game.cols)];
+                let verdict = judge_recolors(&mut game, &[Recolor { idx, color }], false);
+
+                let mut recolored = game.clone();
+                recolored.idx_to_color[idx] = color;
+                match (verdict, run_counter(&recolored, false)) {
+                    (Verdict::Unsolvable, 0) | (Verdict::Multiple, 2..) => {}
+                    (Verdict::Unique { solution }, 1) => {
+                        assert_eq!(enumerate_solutions(&recolored, &[], &[], 2), [solution]);
+                    }
+                    (verdict, count) => {
+                        panic!("{} {}: {:?} for {}", entry.name, idx, verdict, count)
+                    }
+                }
+            }
+
+            assert_eq!(game.to_json(), before);
+        }
+    }
+
+    /// Edits in a batch apply their recolors together, each against the unchanged game.
+    #[test]
+    fn judges_batches_of_edits() {
+        let edits = r#"[[{"idx":2,"color":0}], [{"idx":2,"color":0},{"idx":1,"color":1}], []]"#;
+        let verdicts = what_if_recolor_batch(SINGLES.to_string(), edits.to_string(), true);
+        assert_eq!(
+            verdicts.unwrap(),
+            r#"[{"status":"multiple"},{"status":"unique","solution":[2,4,11,13]},"#.to_string()
+                + r#"{"status":"unique","solution":[1,7,8,14]}]"#
+        );
+
+        let single = what_if_recolor(SINGLES.to_string(), 2, 0, true).unwrap();
+        assert_eq!(single, r#"{"status":"multiple"}"#);
+
+        let error = what_if_recolor_batch(SINGLES.to_string(), r#"[{"idx":2}]"#.to_string(), true);
+        assert!(error.unwrap_err().starts_with("Invalid edits JSON"));
+        assert!(what_if_recolor("{".to_string(), 0, 0, true).is_err());
+    }
+}
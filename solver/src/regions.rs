@@ -0,0 +1,486 @@
+use crate::board::BoardIndex;
+use crate::topology::Topology;
+use crate::Game;
+use serde::{Deserialize, Serialize};
+
+/// A cell of a region in the game's optional `regions`: its index, or its row and column.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub(crate) enum RegionCell {
+    Idx(usize),
+    At([usize; 2]),
+}
+
+/// The color of every cell of a `rows` by `cols` board given as lists of cells, region `i`
+/// taking color `i`. Fails naming the cells off the board, those in no region, and those in
+/// several.
+pub(crate) fn colors_of_regions(
+    rows: usize,
+    cols: usize,
+    regions: &[Vec<RegionCell>],
+) -> Result<Vec<usize>, String> {
+    let cells = rows * cols;
+    let mut idx_to_color = vec![None; cells];
+    let mut doubled = vec![];
+
+    for (color, region) in regions.iter().enumerate() {
+        for &cell in region {
+            let idx = match cell {
+                RegionCell::Idx(idx) if idx < cells => idx,
+                RegionCell::At([row, col]) if row < rows && col < cols => row * cols + col,
+                _ => {
+                    return Err(format!(
+                        "Region {} has cell {}, which is not on the {}x{} board",
+                        color,
+                        describe_cell(cell),
+                        rows,
+                        cols
+                    ))
+                }
+            };
+
+            if idx_to_color[idx].replace(color).is_some() {
+                doubled.push(idx);
+            }
+        }
+    }
+
+    let uncovered: Vec<_> = (0..cells)
+        .filter(|&idx| idx_to_color[idx].is_none())
+        .collect();
+    doubled.sort_unstable();
+    doubled.dedup();
+
+    let mut problems = vec![];
+    if !uncovered.is_empty() {
+        problems.push(format!("cells {:?} are in no region", uncovered));
+    }
+    if !doubled.is_empty() {
+        problems.push(format!("cells {:?} are in more than one region", doubled));
+    }
+    if !problems.is_empty() {
+        return Err(format!(
+            "Regions do not tile the board: {}",
+            problems.join(", and ")
+        ));
+    }
+
+    Ok(idx_to_color.into_iter().flatten().collect())
+}
+
+fn describe_cell(cell: RegionCell) -> String {
+    match cell {
+        RegionCell::Idx(idx) => idx.to_string(),
+        RegionCell::At([row, col]) => format!("[{}, {}]", row, col),
+    }
+}
+
+/// Where one color's cells lie, for showing the deductions available before any search.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Region {
+    /// The color's position in `Game::colors`, as used by `idxToColor`.
+    pub color: usize,
+    /// The color's label from `Game::colors`.
+    pub label: usize,
+    pub cells: usize,
+    /// The rows and the columns holding the color's cells, in increasing order. Lists rather than
+    /// bitmasks, since masks of boards past 53 rows would not survive as JS numbers.
+    pub rows: Vec<usize>,
+    pub cols: Vec<usize>,
+    /// All cells in one row or column, which then holds the color's queen, so the rest of that
+    /// line can be ruled out at once.
+    pub pinned_to_row: bool,
+    pub pinned_to_col: bool,
+    /// All cells in two rows or columns: together with another such color on the same two lines,
+    /// those lines hold both their queens.
+    pub spans_two_rows: bool,
+    pub spans_two_cols: bool,
+    /// The smallest rectangle holding the color's cells. Absent for a color without cells.
+    pub bounds: Option<Bounds>,
+}
+
+/// A rectangle of cells, with inclusive edges.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub(crate) struct Bounds {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+}
+
+/// Describes the region of every color, or nothing on colorless boards.
+pub(crate) fn analyze_regions(game: &Game) -> Vec<Region> {
+    let index = BoardIndex::new(game);
+
+    (0..game.num_colors())
+        .map(|color| {
+            let rows = bits(index.color_rows[color]);
+            let cols = bits(index.color_cols[color]);
+
+            Region {
+                color,
+                label: game.colors[color],
+                cells: index.cells_by_color[color].len(),
+                pinned_to_row: rows.len() == 1,
+                pinned_to_col: cols.len() == 1,
+                spans_two_rows: rows.len() == 2,
+                spans_two_cols: cols.len() == 2,
+                bounds: match (rows.first(), rows.last(), cols.first(), cols.last()) {
+                    (Some(&top), Some(&bottom), Some(&left), Some(&right)) => Some(Bounds {
+                        top,
+                        left,
+                        bottom,
+                        right,
+                    }),
+                    _ => None,
+                },
+                rows,
+                cols,
+            }
+        })
+        .collect()
+}
+
+/// The positions of the set bits of `mask`, lowest first.
+pub(crate) fn bits(mut mask: u64) -> Vec<usize> {
+    let mut positions = vec![];
+
+    while mask != 0 {
+        positions.push(mask.trailing_zeros() as usize);
+        mask &= mask - 1;
+    }
+
+    positions
+}
+
+/// The colors whose cells do not form one piece through shared edges, in increasing order. Colors
+/// without cells count as connected.
+pub(crate) fn disconnected_colors(game: &Game) -> Vec<usize> {
+    color_pieces(game)
+        .iter()
+        .enumerate()
+        .filter(|(_, pieces)| pieces.len() > 1)
+        .map(|(color, _)| color)
+        .collect()
+}
+
+/// A color whose cells do not form one piece, which real boards never have, so it usually means
+/// the board was scraped wrong.
+#[derive(Serialize, Debug)]
+pub(crate) struct SplitRegion {
+    pub color: usize,
+    /// The color's pieces as from `color_pieces`.
+    pub pieces: Vec<Vec<usize>>,
+}
+
+impl SplitRegion {
+    pub fn describe(&self) -> String {
+        let pieces: Vec<_> = self
+            .pieces
+            .iter()
+            .map(|piece| format!("{:?}", piece))
+            .collect();
+
+        format!(
+            "Color {} is split into {} pieces: {}",
+            self.color,
+            self.pieces.len(),
+            pieces.join(", ")
+        )
+    }
+}
+
+/// Every color split into several pieces, in increasing order.
+pub(crate) fn split_regions(game: &Game) -> Vec<SplitRegion> {
+    color_pieces(game)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, pieces)| pieces.len() > 1)
+        .map(|(color, pieces)| SplitRegion { color, pieces })
+        .collect()
+}
+
+/// The pieces every color's cells form through shared edges, each with its cells in increasing
+/// order, ordered by their first cell. Colors without cells have no pieces.
+pub(crate) fn color_pieces(game: &Game) -> Vec<Vec<Vec<usize>>> {
+    let index = BoardIndex::new(game);
+    let mut reached = vec![false; game.rows * game.cols];
+    let mut stack = vec![];
+
+    (0..game.num_colors())
+        .map(|color| {
+            let mut pieces = vec![];
+
+            for &start in &index.cells_by_color[color] {
+                if std::mem::replace(&mut reached[start], true) {
+                    continue;
+                }
+
+                stack.push(start);
+                let mut piece = vec![];
+
+                while let Some(idx) = stack.pop() {
+                    piece.push(idx);
+
+                    for next in edge_neighbors(game, idx) {
+                        if !reached[next] && game.idx_to_color[next] == color {
+                            reached[next] = true;
+                            stack.push(next);
+                        }
+                    }
+                }
+
+                piece.sort_unstable();
+                pieces.push(piece);
+            }
+
+            pieces
+        })
+        .collect()
+}
+
+/// The cells sharing an edge with `idx`: the four beside it on square boards, and on hex boards
+/// also the two neighbors off its row and column.
+pub(crate) fn edge_neighbors(game: &Game, idx: usize) -> Vec<usize> {
+    let (row, col) = (idx / game.cols, idx % game.cols);
+
+    let up = row.checked_sub(1).map(|row| row * game.cols + col);
+    let down = (row + 1 < game.rows).then(|| idx + game.cols);
+    let left = col.checked_sub(1).map(|_| idx - 1);
+    let right = (col + 1 < game.cols).then(|| idx + 1);
+
+    let mut neighbors: Vec<_> = [up, down, left, right].iter().flatten().copied().collect();
+    if game.rules.topology != Topology::Square {
+        neighbors.extend(Topology::Hex.neighbors(game.rows, game.cols, idx));
+    }
+
+    neighbors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::difficulty::Technique;
+    use crate::lesson::generate_lesson;
+    use crate::{region_analysis, validate_game};
+    use serde_json::{json, Value};
+
+    /// Color 1 comes in two pieces, color 5 is a single cell, and color 6 has no cells, on a board
+    /// wider than tall with labels that are not the color positions.
+    const MIXED: &str = r#"{"rows":4,"cols":5,"colors":[10,11,12,13,14,15,16],"idxToColor":[
+        0,0,1,1,1, 2,0,1,3,3, 2,4,4,4,3, 1,4,5,4,3],"rules":{"allColorsRequired":false}}"#;
+
+    fn analyzed(json: &str) -> Value {
+        serde_json::from_str(&region_analysis(json.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn describes_every_region() {
+        let expected = json!([
+            {"color": 0, "label": 10, "cells": 3, "rows": [0, 1], "cols": [0, 1],
+             "pinnedToRow": false, "pinnedToCol": false,
+             "spansTwoRows": true, "spansTwoCols": true,
+             "bounds": {"top": 0, "left": 0, "bottom": 1, "right": 1}},
+            {"color": 1, "label": 11, "cells": 5, "rows": [0, 1, 3], "cols": [0, 2, 3, 4],
+             "pinnedToRow": false, "pinnedToCol": false,
+             "spansTwoRows": false, "spansTwoCols": false,
+             "bounds": {"top": 0, "left": 0, "bottom": 3, "right": 4}},
+            {"color": 2, "label": 12, "cells": 2, "rows": [1, 2], "cols": [0],
+             "pinnedToRow": false, "pinnedToCol": true,
+             "spansTwoRows": true, "spansTwoCols": false,
+             "bounds": {"top": 1, "left": 0, "bottom": 2, "right": 0}},
+            {"color": 3, "label": 13, "cells": 4, "rows": [1, 2, 3], "cols": [3, 4],
+             "pinnedToRow": false, "pinnedToCol": false,
+             "spansTwoRows": false, "spansTwoCols": true,
+             "bounds": {"top": 1, "left": 3, "bottom": 3, "right": 4}},
+            {"color": 4, "label": 14, "cells": 5, "rows": [2, 3], "cols": [1, 2, 3],
+             "pinnedToRow": false, "pinnedToCol": false,
+             "spansTwoRows": true, "spansTwoCols": false,
+             "bounds": {"top": 2, "left": 1, "bottom": 3, "right": 3}},
+            {"color": 5, "label": 15, "cells": 1, "rows": [3], "cols": [2],
+             "pinnedToRow": true, "pinnedToCol": true,
+             "spansTwoRows": false, "spansTwoCols": false,
+             "bounds": {"top": 3, "left": 2, "bottom": 3, "right": 2}},
+            {"color": 6, "label": 16, "cells": 0, "rows": [], "cols": [],
+             "pinnedToRow": false, "pinnedToCol": false,
+             "spansTwoRows": false, "spansTwoCols": false,
+             "bounds": null},
+        ]);
+
+        assert_eq!(analyzed(MIXED), expected);
+    }
+
+    #[test]
+    fn has_no_regions_without_colors() {
+        assert_eq!(analyzed(r#"{"rows":4,"cols":4}"#), json!([]));
+    }
+
+    #[test]
+    fn rejects_invalid_games() {
+        let error = region_analysis(r#"{"rows":4,"cols":4,"colors":[0]}"#.to_string());
+        assert_eq!(
+            error.unwrap_err(),
+            "Board has 1 colors but 4 rows, so not every color can hold a queen"
+        );
+        assert!(region_analysis("not json".to_string()).is_err());
+    }
+
+    /// Cells touching only at a corner are separate pieces on square cells, but neighbors on
+    /// hexagons where they touch along the third direction.
+    #[test]
+    fn splits_colors_by_shared_edges() {
+        let game = Game::from_json(MIXED).unwrap();
+        let pieces = color_pieces(&game);
+        assert_eq!(pieces[1], [vec![2, 3, 4, 7], vec![15]]);
+        assert_eq!(pieces[6], Vec::<Vec<usize>>::new());
+        assert_eq!(disconnected_colors(&game), [1]);
+
+        let diagonal = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+            1,0,2,2, 0,1,2,2, 3,3,3,3, 3,3,3,3]}"#;
+        let game = Game::from_json(diagonal).unwrap();
+        let split = split_regions(&game);
+        assert_eq!(split.len(), 2);
+        assert_eq!(
+            (split[0].color, &split[0].pieces),
+            (0, &vec![vec![1], vec![4]])
+        );
+        assert_eq!(
+            (split[1].color, &split[1].pieces),
+            (1, &vec![vec![0], vec![5]])
+        );
+
+        // On hexagons, 1 and 4 touch, while 0 and 5 are still apart.
+        let hex = diagonal.replace("]}", r#"],"rules":{"topology":"hex"}}"#);
+        let game = Game::from_json(&hex).unwrap();
+        assert_eq!(disconnected_colors(&game), [1]);
+    }
+
+    #[test]
+    fn lists_split_regions_when_validating() {
+        let json: Value = serde_json::from_str(&validate_game(MIXED.to_string()).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            json!({"splitRegions": [{"color": 1, "pieces": [[2, 3, 4, 7], [15]]}]})
+        );
+
+        for entry in CORPUS {
+            let json = validate_game(entry.json.to_string()).unwrap();
+            assert_eq!(json, r#"{"splitRegions":[]}"#, "{}", entry.name);
+        }
+    }
+
+    /// Split regions are only a warning unless the game is strict.
+    #[test]
+    fn rejects_split_regions_when_strict() {
+        assert!(Game::from_json(MIXED).is_ok());
+
+        let strict = MIXED.replace(r#""rules""#, r#""strict":true,"rules""#);
+        assert_eq!(
+            Game::from_json(&strict).unwrap_err(),
+            "Board is not contiguous: Color 1 is split into 2 pieces: [2, 3, 4, 7], [15]"
+        );
+        assert!(region_analysis(strict.clone()).is_err());
+        assert!(validate_game(strict).is_err());
+
+        let strict = CORPUS[0].json.replacen('{', r#"{"strict":true,"#, 1);
+        assert!(Game::from_json(&strict).is_ok());
+    }
+
+    /// The boards grown for lessons keep every color in one piece.
+    #[test]
+    fn generates_contiguous_boards() {
+        for (rows, seed) in [(5, 1), (6, 2), (7, 3)] {
+            let lesson = generate_lesson(rows, Technique::Singles, 3, seed).unwrap();
+            assert!(!lesson.boards.is_empty());
+            for board in &lesson.boards {
+                assert!(split_regions(&board.game).is_empty(), "{:?}", board.game);
+                let strict = board.game.to_json().replacen('{', r#"{"strict":true,"#, 1);
+                assert!(Game::from_json(&strict).is_ok());
+            }
+        }
+    }
+
+    /// The corpus boards with their colors given as lists of cells, by index with the size left
+    /// out, and by row and column.
+    fn as_regions(game: &Game) -> [String; 2] {
+        let regions: Vec<Vec<usize>> = (0..game.colors.len())
+            .map(|color| {
+                (0..game.idx_to_color.len())
+                    .filter(|&idx| game.idx_to_color[idx] == color)
+                    .collect()
+            })
+            .collect();
+        let pairs: Vec<Vec<[usize; 2]>> = regions
+            .iter()
+            .map(|cells| {
+                cells
+                    .iter()
+                    .map(|&idx| [idx / game.cols, idx % game.cols])
+                    .collect()
+            })
+            .collect();
+
+        [
+            json!({"regions": regions}).to_string(),
+            json!({"rows": game.rows, "cols": game.cols, "regions": pairs}).to_string(),
+        ]
+    }
+
+    #[test]
+    fn takes_regions_as_lists_of_cells_everywhere() {
+        type EntryPoint = fn(String) -> Result<String, String>;
+        let entry_points: [(&str, EntryPoint); 6] = [
+            ("solve", crate::solve),
+            ("count_solutions", crate::count_solutions),
+            ("validate_game", validate_game),
+            ("region_analysis", region_analysis),
+            ("normalize_game", crate::normalize_game),
+            ("difficulty_breakdown", crate::difficulty_breakdown),
+        ];
+
+        for entry in CORPUS {
+            let game = Game::from_json(entry.json).unwrap();
+            for json in as_regions(&game) {
+                assert_eq!(
+                    Game::from_json(&json).unwrap().idx_to_color,
+                    game.idx_to_color
+                );
+                for (name, entry_point) in entry_points {
+                    assert_eq!(
+                        entry_point(json.clone()),
+                        entry_point(entry.json.to_string()),
+                        "{} on {}",
+                        name,
+                        entry.name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn names_the_cells_regions_miss_or_share() {
+        let read = |json: &str| Game::from_json(json).unwrap_err();
+        assert_eq!(
+            read(r#"{"rows":2,"cols":2,"regions":[[0,1,2],[2,1]]}"#),
+            concat!(
+                "Regions do not tile the board: cells [3] are in no region, ",
+                "and cells [1, 2] are in more than one region",
+            )
+        );
+        assert_eq!(
+            read(r#"{"rows":2,"cols":2,"regions":[[0,1],[[1,0],[1,2]]]}"#),
+            "Region 1 has cell [1, 2], which is not on the 2x2 board"
+        );
+        assert_eq!(
+            read(r#"{"rows":2,"cols":2,"regions":[[0,1],[2,4]]}"#),
+            "Region 1 has cell 4, which is not on the 2x2 board"
+        );
+        assert_eq!(
+            read(r#"{"rows":2,"cols":2,"idxToColor":[0,0,1,1],"regions":[[0,1],[2,3]]}"#),
+            "Board gives both idxToColor and regions"
+        );
+    }
+}
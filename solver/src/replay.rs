@@ -0,0 +1,452 @@
+use crate::constrained::find_solution;
+use crate::marks::BoardState;
+use crate::safe::find_safe_cells;
+use crate::Game;
+use serde::{Deserialize, Serialize};
+
+/// A move of the user, from the `events_json` of `grade_replay`.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(tag = "kind", rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) enum Event {
+    /// A queen put on `cell`, replacing any X mark there.
+    Place {
+        cell: usize,
+        #[serde(default)]
+        time: f64,
+    },
+    /// The queen on `cell` taken off.
+    Remove {
+        cell: usize,
+        #[serde(default)]
+        time: f64,
+    },
+    /// An X mark put on `cell`.
+    Mark {
+        cell: usize,
+        #[serde(default)]
+        time: f64,
+    },
+    /// The X mark on `cell` taken off.
+    Unmark {
+        cell: usize,
+        #[serde(default)]
+        time: f64,
+    },
+}
+
+impl Event {
+    fn cell(self) -> usize {
+        match self {
+            Self::Place { cell, .. }
+            | Self::Remove { cell, .. }
+            | Self::Mark { cell, .. }
+            | Self::Unmark { cell, .. } => cell,
+        }
+    }
+
+    fn time(self) -> f64 {
+        match self {
+            Self::Place { time, .. }
+            | Self::Remove { time, .. }
+            | Self::Mark { time, .. }
+            | Self::Unmark { time, .. } => time,
+        }
+    }
+}
+
+/// How a queen or X mark stood against the solutions when it was put down.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Grade {
+    /// A queen that filling in singles from the board places.
+    Forced,
+    /// A queen every completion of the board has, or a mark none of them has a queen on.
+    Safe,
+    /// A move some completions of the board agree with and others do not.
+    Guess,
+    /// A move no completion of the board agrees with.
+    Mistake,
+    /// A move made once the board already had no completion.
+    Unsolvable,
+}
+
+/// What became of one event of the replay.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplayedEvent {
+    pub index: usize,
+    pub cell: usize,
+    pub time: f64,
+    /// The grade of a queen or mark put down. Absent for removals and skipped events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<Grade>,
+    /// Whether a queen placed here is taken off again later.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub wasted: bool,
+    /// Why the event was skipped, if it does not fit the board it was made on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// The counts of the replay.
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplaySummary {
+    /// Queens and marks put down, graded or not.
+    pub moves: usize,
+    pub forced: usize,
+    pub safe: usize,
+    pub guesses: usize,
+    pub mistakes: usize,
+    /// Queens placed and taken off again later.
+    pub wasted: usize,
+    /// Events skipped with a warning.
+    pub skipped: usize,
+    /// The share of the moves made while the board could still be completed that were forced or
+    /// safe, from 0 to 1. 1 when there were none.
+    pub accuracy: f64,
+    /// The time from the first event to the last.
+    pub duration: f64,
+    /// Whether the queens at the end make a solution.
+    pub solved: bool,
+}
+
+/// The game review of `grade_replay`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Replay {
+    pub events: Vec<ReplayedEvent>,
+    /// The index of the first event after which no solution agreed with the board, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_unsolvable: Option<usize>,
+    pub summary: ReplaySummary,
+}
+
+/// Replays `events` from the empty board, grading every queen and mark put down against the
+/// board it was put on. Events that do not fit the board, such as removing a queen that is not
+/// there, are skipped with a warning rather than ending the replay. Fails when the board has no
+/// solution.
+pub(crate) fn grade_replay(game: &Game, events: &[Event]) -> Result<Replay, String> {
+    let cells = game.rows * game.cols;
+    if find_solution(game, &[], &[]).is_none() {
+        return Err("Board has no solution".to_string());
+    }
+
+    let mut state = BoardState::default();
+    // The solution found agreeing with the board, or none once it has no completion.
+    let mut completion = Some(vec![]);
+    let mut first_unsolvable = None;
+    // The event that placed each queen on the board, to mark it wasted if it is taken off.
+    let mut placed_by = vec![None; cells];
+    let mut replayed: Vec<ReplayedEvent> = Vec::with_capacity(events.len());
+
+    for (index, &event) in events.iter().enumerate() {
+        let cell = event.cell();
+        let mut grade = None;
+
+        let warning = if cell >= cells {
+            Some(format!(
+                "Cell {} is not on the board, which has cells 0..{}",
+                cell, cells
+            ))
+        } else {
+            let queen = state.queens.contains(&cell);
+            let marked = state.marks.contains(&cell);
+
+            match event {
+                Event::Place { .. } if queen => {
+                    Some(format!("Cell {} already holds a queen", cell))
+                }
+                Event::Place { .. } => {
+                    state.marks.retain(|&idx| idx != cell);
+                    grade = Some(grade_queen(game, &state, cell, completion.is_some())?);
+                    state.queens.push(cell);
+                    placed_by[cell] = Some(index);
+                    None
+                }
+                Event::Remove { .. } if !queen => {
+                    Some(format!("Cell {} holds no queen to remove", cell))
+                }
+                Event::Remove { .. } => {
+                    state.queens.retain(|&idx| idx != cell);
+                    if let Some(placer) = placed_by[cell].take() {
+                        replayed[placer].wasted = true;
+                    }
+                    None
+                }
+                Event::Mark { .. } if queen || marked => Some(format!(
+                    "Cell {} already holds a {}",
+                    cell,
+                    if queen { "queen" } else { "mark" }
+                )),
+                Event::Mark { .. } => {
+                    grade = Some(grade_mark(game, &state, cell, completion.is_some()));
+                    state.marks.push(cell);
+                    None
+                }
+                Event::Unmark { .. } if !marked => {
+                    Some(format!("Cell {} holds no mark to remove", cell))
+                }
+                Event::Unmark { .. } => {
+                    state.marks.retain(|&idx| idx != cell);
+                    None
+                }
+            }
+        };
+
+        if warning.is_none() {
+            completion = find_solution(game, &state.queens, &state.marks);
+            if completion.is_none() && first_unsolvable.is_none() {
+                first_unsolvable = Some(index);
+            }
+        }
+
+        replayed.push(ReplayedEvent {
+            index,
+            cell,
+            time: event.time(),
+            grade,
+            wasted: false,
+            warning,
+        });
+    }
+
+    let mut summary = ReplaySummary::default();
+    for event in &replayed {
+        summary.skipped += event.warning.is_some() as usize;
+        summary.wasted += event.wasted as usize;
+
+        if event.grade.is_some() {
+            summary.moves += 1;
+        }
+        match event.grade {
+            Some(Grade::Forced) => summary.forced += 1,
+            Some(Grade::Safe) => summary.safe += 1,
+            Some(Grade::Guess) => summary.guesses += 1,
+            Some(Grade::Mistake) => summary.mistakes += 1,
+            Some(Grade::Unsolvable) | None => {}
+        }
+    }
+
+    let graded = summary.forced + summary.safe + summary.guesses + summary.mistakes;
+    summary.accuracy = if graded == 0 {
+        1.0
+    } else {
+        (summary.forced + summary.safe) as f64 / graded as f64
+    };
+    summary.duration = match (events.first(), events.last()) {
+        (Some(first), Some(last)) => last.time() - first.time(),
+        _ => 0.0,
+    };
+    summary.solved = completion.is_some_and(|solution| solution.len() == state.queens.len());
+
+    Ok(Replay {
+        events: replayed,
+        first_unsolvable,
+        summary,
+    })
+}
+
+/// Grades a queen put on `cell` of `state`, which holds no queen or mark there.
+fn grade_queen(
+    game: &Game,
+    state: &BoardState,
+    cell: usize,
+    completable: bool,
+) -> Result<Grade, String> {
+    if !completable {
+        return Ok(Grade::Unsolvable);
+    }
+
+    let safe = find_safe_cells(game, state)?;
+    if let Some(safe) = safe.cells.iter().find(|safe| safe.cell == cell) {
+        return Ok(if safe.easy {
+            Grade::Forced
+        } else {
+            Grade::Safe
+        });
+    }
+
+    let mut queens = state.queens.clone();
+    queens.push(cell);
+
+    Ok(if find_solution(game, &queens, &state.marks).is_some() {
+        Grade::Guess
+    } else {
+        Grade::Mistake
+    })
+}
+
+/// Grades a mark put on `cell` of `state`, which holds no queen or mark there.
+fn grade_mark(game: &Game, state: &BoardState, cell: usize, completable: bool) -> Grade {
+    if !completable {
+        return Grade::Unsolvable;
+    }
+
+    let mut queens = state.queens.clone();
+    queens.push(cell);
+    let mut marks = state.marks.clone();
+    marks.push(cell);
+
+    match (
+        find_solution(game, &queens, &state.marks).is_some(),
+        find_solution(game, &state.queens, &marks).is_some(),
+    ) {
+        (false, _) => Grade::Safe,
+        (true, true) => Grade::Guess,
+        (true, false) => Grade::Mistake,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::enumerate_solutions;
+    use serde_json::{json, Value};
+
+    /// Color 0 is the single cell 1, and the only solution is [1, 7, 8, 14].
+    const SINGLES: &str = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+        1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#;
+
+    fn replay(game_json: &str, events: Value) -> Result<Value, String> {
+        let replay = crate::grade_replay(game_json.to_string(), events.to_string())?;
+        Ok(serde_json::from_str(&replay).unwrap())
+    }
+
+    #[test]
+    fn reviews_a_game_with_mistakes_and_bad_events() {
+        let events = json!([
+            {"kind": "place", "cell": 1, "time": 0},
+            {"kind": "mark", "cell": 0, "time": 1.5},
+            {"kind": "place", "cell": 2, "time": 2},
+            {"kind": "mark", "cell": 12, "time": 3},
+            {"kind": "remove", "cell": 2, "time": 4},
+            {"kind": "remove", "cell": 5, "time": 5},
+            {"kind": "unmark", "cell": 3, "time": 6},
+            {"kind": "place", "cell": 16, "time": 7},
+            {"kind": "place", "cell": 7, "time": 8},
+            {"kind": "place", "cell": 8, "time": 9},
+            {"kind": "mark", "cell": 8, "time": 9.5},
+            {"kind": "place", "cell": 14, "time": 10},
+        ]);
+
+        assert_eq!(
+            replay(SINGLES, events),
+            Ok(json!({
+                "events": [
+                    {"index": 0, "cell": 1, "time": 0.0, "grade": "forced"},
+                    {"index": 1, "cell": 0, "time": 1.5, "grade": "safe"},
+                    {"index": 2, "cell": 2, "time": 2.0, "grade": "mistake", "wasted": true},
+                    {"index": 3, "cell": 12, "time": 3.0, "grade": "unsolvable"},
+                    {"index": 4, "cell": 2, "time": 4.0},
+                    {
+                        "index": 5,
+                        "cell": 5,
+                        "time": 5.0,
+                        "warning": "Cell 5 holds no queen to remove",
+                    },
+                    {
+                        "index": 6,
+                        "cell": 3,
+                        "time": 6.0,
+                        "warning": "Cell 3 holds no mark to remove",
+                    },
+                    {
+                        "index": 7,
+                        "cell": 16,
+                        "time": 7.0,
+                        "warning": "Cell 16 is not on the board, which has cells 0..16",
+                    },
+                    {"index": 8, "cell": 7, "time": 8.0, "grade": "forced"},
+                    {"index": 9, "cell": 8, "time": 9.0, "grade": "forced"},
+                    {
+                        "index": 10,
+                        "cell": 8,
+                        "time": 9.5,
+                        "warning": "Cell 8 already holds a queen",
+                    },
+                    {"index": 11, "cell": 14, "time": 10.0, "grade": "forced"},
+                ],
+                "firstUnsolvable": 2,
+                "summary": {
+                    "moves": 7,
+                    "forced": 4,
+                    "safe": 1,
+                    "guesses": 0,
+                    "mistakes": 1,
+                    "wasted": 1,
+                    "skipped": 4,
+                    "accuracy": 5.0 / 6.0,
+                    "duration": 10.0,
+                    "solved": true,
+                },
+            }))
+        );
+    }
+
+    #[test]
+    fn grades_first_moves_against_every_solution() {
+        let mut several = Game::from_json(CORPUS[0].json).unwrap();
+        several.idx_to_color[33] = 1;
+        let colorless = Game::from_json(r#"{"rows":6,"cols":6,"rules":{"colorless":true}}"#);
+
+        for game in [
+            Game::from_json(CORPUS[0].json).unwrap(),
+            several,
+            colorless.unwrap(),
+        ] {
+            let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+            for cell in 0..game.rows * game.cols {
+                let holding = solutions.iter().filter(|solution| solution.contains(&cell));
+                let grades = (holding.count(), solutions.len());
+                let place = grade_replay(&game, &[Event::Place { cell, time: 0.0 }]).unwrap();
+                let mark = grade_replay(&game, &[Event::Mark { cell, time: 0.0 }]).unwrap();
+
+                let (queen_grades, mark_grade) = match grades {
+                    (0, _) => (&[Grade::Mistake][..], Grade::Safe),
+                    (all, total) if all == total => {
+                        (&[Grade::Forced, Grade::Safe][..], Grade::Mistake)
+                    }
+                    _ => (&[Grade::Guess][..], Grade::Guess),
+                };
+                assert!(
+                    queen_grades.contains(&place.events[0].grade.unwrap()),
+                    "cell {}",
+                    cell
+                );
+                assert_eq!(mark.events[0].grade, Some(mark_grade), "cell {}", cell);
+                assert_eq!(
+                    place.first_unsolvable.is_some(),
+                    grades.0 == 0,
+                    "cell {}",
+                    cell
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_bad_events_and_boards_without_solutions() {
+        let error = replay(SINGLES, json!([{"kind": "jump", "cell": 1}])).unwrap_err();
+        assert!(
+            error.starts_with(concat!(
+                "Invalid events JSON: unknown variant `jump`, ",
+                "expected one of `place`, `remove`, `mark`, `unmark`",
+            )),
+            "{}",
+            error
+        );
+        let error = replay(SINGLES, json!([{"kind": "place", "cell": 1, "at": 3}])).unwrap_err();
+        assert!(error.contains("unknown field `at`"), "{}", error);
+
+        let unsolvable = CORPUS
+            .iter()
+            .find(|entry| entry.solution.is_empty())
+            .unwrap();
+        assert_eq!(
+            replay(unsolvable.json, json!([])),
+            Err("Board has no solution".to_string())
+        );
+    }
+}
@@ -0,0 +1,539 @@
+use crate::recolor::{judge, Verdict};
+use crate::regions::{color_pieces, edge_neighbors};
+use crate::rules::Rules;
+use crate::topology::Topology;
+use crate::utils::Rng;
+use crate::{enumerate_solutions, Game};
+use serde::Serialize;
+
+/// How many extensions or reductions `resize_board` tries before giving up.
+const MAX_ATTEMPTS: usize = 200;
+
+/// The most cells `resize_board` moves to a neighboring color after shrinking a board.
+const MAX_RECOLORS: usize = 3;
+
+/// How many ways to fill in the queens of a shrunk board `resize_board` picks among.
+const MAX_PLACEMENTS: u64 = 16;
+
+/// The corner of the board where `resize_board` adds or removes a row and a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    const NAMES: &'static [(&'static str, Corner)] = &[
+        ("topLeft", Corner::TopLeft),
+        ("topRight", Corner::TopRight),
+        ("bottomLeft", Corner::BottomLeft),
+        ("bottomRight", Corner::BottomRight),
+    ];
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::NAMES
+            .iter()
+            .find(|&&(registered, _)| registered == name)
+            .map(|&(_, corner)| corner)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::NAMES.iter().map(|&(name, _)| name).collect();
+                format!(
+                    "Unknown direction {:?}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    fn top(self) -> bool {
+        matches!(self, Self::TopLeft | Self::TopRight)
+    }
+
+    fn left(self) -> bool {
+        matches!(self, Self::TopLeft | Self::BottomLeft)
+    }
+}
+
+/// A board made from another by `resize_board`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Resized {
+    pub game: Game,
+    /// The cells of its only solution, sorted.
+    pub solution: Vec<usize>,
+}
+
+/// Grows the square board `game` by a row and a column at `corner`, or with `shrink` removes the
+/// row and column there, reproducibly from `seed`.
+///
+/// Growing gives the new cells the colors of the old cells beside them, except for a stretch of
+/// them, and maybe a few old cells next to it, that makes up the one new color. Shrinking keeps
+/// most of the queens of the old solution and fits the colors around them. Either way the board
+/// is kept only if every color is in one piece and it has exactly one solution, and other choices
+/// are tried until one is or `MAX_ATTEMPTS` run out.
+pub(crate) fn resize_board(
+    game: &Game,
+    corner: Corner,
+    shrink: bool,
+    seed: u64,
+) -> Result<Resized, String> {
+    if !game.has_color_rule() {
+        return Err("Colorless boards have no colors to extend".to_string());
+    }
+    if game.rows != game.cols || game.colors.len() != game.rows {
+        return Err("Only square boards with one color per row can be resized".to_string());
+    }
+    if game.rules.topology != Topology::Square || game.adjacency.is_some() || !game.links.is_empty()
+    {
+        return Err(
+            "Only square boards without custom adjacency or links can be resized".to_string(),
+        );
+    }
+    if shrink && game.rows < 2 {
+        return Err("A board of one cell cannot shrink".to_string());
+    }
+
+    let side = if shrink { game.rows - 1 } else { game.rows + 1 };
+    let solution = match enumerate_solutions(game, &[], &[], 1).pop() {
+        Some(solution) => solution,
+        None => return Err("Board has no solution".to_string()),
+    };
+    let mut rng = Rng::new(seed);
+
+    for _ in 0..MAX_ATTEMPTS {
+        let idx_to_color = if shrink {
+            match reduce(game, &solution, corner, &mut rng) {
+                Some(idx_to_color) => idx_to_color,
+                None => continue,
+            }
+        } else {
+            extend(game, corner, &mut rng)
+        };
+
+        let resized = Game {
+            rows: side,
+            cols: side,
+            colors: (0..side).collect(),
+            idx_to_color,
+            regions: None,
+            rules: game.rules.clone(),
+            adjacency: None,
+            cell_ids: None,
+            links: vec![],
+            strict: game.strict,
+        };
+
+        if let Verdict::Unique { solution } = judge(&resized, true) {
+            return Ok(Resized {
+                game: resized,
+                solution,
+            });
+        }
+    }
+
+    Err(format!(
+        "Found no {}x{} board with one solution in {} tries",
+        side, side, MAX_ATTEMPTS
+    ))
+}
+
+/// The colors of `game` grown by a row and a column at `corner`, the new color numbered last.
+fn extend(game: &Game, corner: Corner, rng: &mut Rng) -> Vec<usize> {
+    let n = game.rows;
+    let side = n + 1;
+    let (row_off, col_off) = (corner.top() as usize, corner.left() as usize);
+    let new_row = if corner.top() { 0 } else { n };
+    let new_col = if corner.left() { 0 } else { n };
+
+    let mut idx_to_color = vec![usize::MAX; side * side];
+    for idx in 0..n * n {
+        let (row, col) = (idx / n + row_off, idx % n + col_off);
+        idx_to_color[row * side + col] = game.idx_to_color[idx];
+    }
+
+    // The new cells in a line of shared edges: along the new row to the corner, then along the
+    // new column away from it.
+    let mut path: Vec<_> = (0..side)
+        .filter(|&col| col != new_col)
+        .map(|col| new_row * side + col)
+        .collect();
+    if corner.left() {
+        path.reverse();
+    }
+    path.push(new_row * side + new_col);
+    let column = (0..side)
+        .filter(|&row| row != new_row)
+        .map(|row| row * side + new_col);
+    if corner.top() {
+        path.extend(column);
+    } else {
+        path.extend(column.rev());
+    }
+
+    let new_color = n;
+    let len = 1 + rng.below(n);
+    let start = rng.below(path.len() - len + 1);
+    let stretch = &path[start..start + len];
+    for &idx in stretch {
+        idx_to_color[idx] = new_color;
+    }
+
+    // Every other new cell but the corner lies beside exactly one old cell.
+    let inward = |idx: usize| {
+        if idx / side == new_row {
+            if corner.top() {
+                idx + side
+            } else {
+                idx - side
+            }
+        } else if corner.left() {
+            idx + 1
+        } else {
+            idx - 1
+        }
+    };
+    let corner_idx = new_row * side + new_col;
+    for &idx in &path {
+        if idx != corner_idx && idx_to_color[idx] == usize::MAX {
+            idx_to_color[idx] = idx_to_color[inward(idx)];
+        }
+    }
+    if idx_to_color[corner_idx] == usize::MAX {
+        let neighbors: Vec<_> = path
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [a, b] if *a == corner_idx => Some(*b),
+                [a, b] if *b == corner_idx => Some(*a),
+                _ => None,
+            })
+            .collect();
+        idx_to_color[corner_idx] = idx_to_color[neighbors[rng.below(neighbors.len())]];
+    }
+
+    // A few old cells beside the new color join it, so that it reaches into the board.
+    for _ in 0..rng.below(3) {
+        let borders: Vec<_> = (0..side * side)
+            .filter(|&idx| idx_to_color[idx] != new_color)
+            .filter(|&idx| {
+                sides(side, idx)
+                    .into_iter()
+                    .any(|neighbor| idx_to_color[neighbor] == new_color)
+            })
+            .collect();
+        if borders.is_empty() {
+            break;
+        }
+        idx_to_color[borders[rng.below(borders.len())]] = new_color;
+    }
+
+    idx_to_color
+}
+
+/// The colors of `game` with the row and column at `corner` removed, or `None` if no queens
+/// fit on what is left.
+///
+/// The queens of `solution` outside the removed cells stay, but for a few dropped at random, and
+/// the rows and columns left empty are filled in by queens anywhere they fit. Each queen then
+/// gets a color of its own, numbered in the order of the queens: the old color of its cell if it
+/// holds no other queen, grown into the cells of the colors holding none or several. Pieces cut
+/// off from their queen go to a neighboring color.
+fn reduce(game: &Game, solution: &[usize], corner: Corner, rng: &mut Rng) -> Option<Vec<usize>> {
+    let n = game.rows;
+    let side = n - 1;
+    let (row_off, col_off) = (corner.top() as usize, corner.left() as usize);
+    let kept = |idx: usize| {
+        let (row, col) = (idx / n, idx % n);
+        (row >= row_off && row - row_off < side && col >= col_off && col - col_off < side)
+            .then(|| (row - row_off) * side + col - col_off)
+    };
+
+    let mut old_colors = vec![0; side * side];
+    for idx in 0..n * n {
+        if let Some(kept) = kept(idx) {
+            old_colors[kept] = game.idx_to_color[idx];
+        }
+    }
+
+    let mut givens: Vec<_> = solution.iter().filter_map(|&idx| kept(idx)).collect();
+    for _ in 0..rng.below(3).min(givens.len()) {
+        givens.swap_remove(rng.below(givens.len()));
+    }
+    let shape = Game {
+        rows: side,
+        cols: side,
+        colors: vec![],
+        idx_to_color: vec![0; side * side],
+        regions: None,
+        rules: Rules {
+            colorless: true,
+            ..game.rules.clone()
+        },
+        adjacency: None,
+        cell_ids: None,
+        links: vec![],
+        strict: false,
+    };
+    let mut placements = enumerate_solutions(&shape, &givens, &[], MAX_PLACEMENTS);
+    if placements.is_empty() {
+        return None;
+    }
+    let queens = placements.swap_remove(rng.below(placements.len()));
+
+    let mut idx_to_color = vec![usize::MAX; side * side];
+    for (color, &queen) in queens.iter().enumerate() {
+        idx_to_color[queen] = color;
+    }
+    for idx in 0..side * side {
+        let mut holding = queens
+            .iter()
+            .enumerate()
+            .filter(|&(_, &queen)| old_colors[queen] == old_colors[idx]);
+        if let (Some((color, _)), None) = (holding.next(), holding.next()) {
+            idx_to_color[idx] = color;
+        }
+    }
+
+    let mut reduced = Game {
+        colors: (0..side).collect(),
+        rules: game.rules.clone(),
+        ..shape
+    };
+
+    // The cells left over join a neighboring color one at a time, so the colors grow into them.
+    loop {
+        let frontier: Vec<_> = (0..side * side)
+            .filter(|&idx| idx_to_color[idx] == usize::MAX)
+            .flat_map(|idx| {
+                let colors = &idx_to_color;
+                sides(side, idx)
+                    .into_iter()
+                    .filter(move |&neighbor| colors[neighbor] != usize::MAX)
+                    .map(move |neighbor| (idx, colors[neighbor]))
+            })
+            .collect();
+        if frontier.is_empty() {
+            break;
+        }
+        let (idx, color) = frontier[rng.below(frontier.len())];
+        idx_to_color[idx] = color;
+    }
+    reduced.idx_to_color = idx_to_color;
+
+    // The pieces a color was cut into, but the one holding its queen, go to neighboring colors,
+    // until every color is in one piece.
+    for _ in 0..side * side {
+        let split = color_pieces(&reduced)
+            .into_iter()
+            .find(|pieces| pieces.len() > 1);
+        let Some(pieces) = split else {
+            break;
+        };
+
+        let piece = pieces
+            .iter()
+            .find(|piece| !queens.iter().any(|queen| piece.contains(queen)))?;
+        let target = neighbor_color(&reduced, piece, rng)?;
+        for &idx in piece {
+            reduced.idx_to_color[idx] = target;
+        }
+    }
+
+    // Other boards the queens fit on, so that one of them has no other solution: a few cells on
+    // the borders between colors, none holding a queen, move to a neighboring color.
+    for _ in 0..rng.below(MAX_RECOLORS + 1) {
+        let borders: Vec<_> = (0..side * side)
+            .filter(|idx| !queens.contains(idx))
+            .flat_map(|idx| {
+                let reduced = &reduced;
+                edge_neighbors(reduced, idx)
+                    .into_iter()
+                    .filter(move |&neighbor| {
+                        reduced.idx_to_color[neighbor] != reduced.idx_to_color[idx]
+                    })
+                    .map(move |neighbor| (idx, reduced.idx_to_color[neighbor]))
+            })
+            .collect();
+        if borders.is_empty() {
+            break;
+        }
+        let (idx, color) = borders[rng.below(borders.len())];
+        reduced.idx_to_color[idx] = color;
+    }
+
+    Some(reduced.idx_to_color)
+}
+
+/// A color other than that of `cells` on a cell sharing an edge with one of them, at random.
+fn neighbor_color(game: &Game, cells: &[usize], rng: &mut Rng) -> Option<usize> {
+    let color = game.idx_to_color[cells[0]];
+    let mut colors: Vec<_> = cells
+        .iter()
+        .flat_map(|&idx| edge_neighbors(game, idx))
+        .map(|neighbor| game.idx_to_color[neighbor])
+        .filter(|&neighbor| neighbor != color)
+        .collect();
+    colors.sort_unstable();
+    colors.dedup();
+
+    (!colors.is_empty()).then(|| colors[rng.below(colors.len())])
+}
+
+/// The cells sharing an edge with `idx` on a square board of side `side`.
+fn sides(side: usize, idx: usize) -> Vec<usize> {
+    let (row, col) = (idx / side, idx % side);
+
+    [
+        (row > 0).then(|| idx - side),
+        (row + 1 < side).then(|| idx + side),
+        (col > 0).then(|| idx - 1),
+        (col + 1 < side).then(|| idx + 1),
+    ]
+    .iter()
+    .flatten()
+    .copied()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::regions::disconnected_colors;
+    use crate::{resize, shrink};
+
+    fn check(resized: &Resized, side: usize) {
+        let game = &resized.game;
+        assert_eq!((game.rows, game.cols), (side, side));
+        assert_eq!(game.colors, (0..side).collect::<Vec<_>>());
+        assert!((0..side).all(|color| game.idx_to_color.contains(&color)));
+        assert!(disconnected_colors(game).is_empty());
+        assert_eq!(
+            enumerate_solutions(game, &[], &[], 2),
+            std::slice::from_ref(&resized.solution)
+        );
+    }
+
+    /// Resizes `game` at every corner with a few seeds, checking every board that comes back.
+    /// Not every try finds one: keeping its queens, the 8x8 board grown at the bottom left would
+    /// need a queen on the new corner, which touches the queen on the old one.
+    fn resize_everywhere(game: &Game, shrink: bool) -> Vec<(Corner, Resized)> {
+        let side = if shrink { game.rows - 1 } else { game.rows + 1 };
+        let mut resized = vec![];
+
+        for &(_, corner) in Corner::NAMES {
+            for seed in 0..3 {
+                match resize_board(game, corner, shrink, seed) {
+                    Ok(board) => {
+                        check(&board, side);
+                        resized.push((corner, board));
+                    }
+                    Err(error) => assert_eq!(
+                        error,
+                        format!(
+                            "Found no {}x{} board with one solution in {} tries",
+                            side, side, MAX_ATTEMPTS
+                        )
+                    ),
+                }
+            }
+        }
+
+        resized
+    }
+
+    #[test]
+    fn grows_corpus_boards_keeping_their_colors() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            let n = game.rows;
+            let grown = resize_everywhere(&game, false);
+            assert!(!grown.is_empty(), "{}", entry.name);
+
+            // Old cells keep their color or join the new one.
+            for (corner, grown) in grown {
+                let (row_off, col_off) = (corner.top() as usize, corner.left() as usize);
+                for idx in 0..n * n {
+                    let moved = (idx / n + row_off) * (n + 1) + idx % n + col_off;
+                    let color = grown.game.idx_to_color[moved];
+                    assert!(
+                        color == game.idx_to_color[idx] || color == n,
+                        "{} {:?} cell {}",
+                        entry.name,
+                        corner,
+                        idx
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shrinks_corpus_boards() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            assert!(!resize_everywhere(&game, true).is_empty(), "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn reproduces_boards_from_the_seed() {
+        let game = CORPUS[0].json.to_string();
+        for resizing in [resize, shrink] {
+            let first = resizing(game.clone(), "topLeft".to_string(), 1).unwrap();
+            let again = resizing(game.clone(), "topLeft".to_string(), 1).unwrap();
+            assert_eq!(first, again);
+        }
+    }
+
+    #[test]
+    fn rejects_boards_it_cannot_resize() {
+        let check = |game_json: &str, direction: &str| {
+            resize(game_json.to_string(), direction.to_string(), 0).unwrap_err()
+        };
+        assert_eq!(
+            check(CORPUS[0].json, "left"),
+            concat!(
+                r#"Unknown direction "left", expected one of "#,
+                "topLeft, topRight, bottomLeft, bottomRight",
+            )
+        );
+        assert_eq!(
+            check(
+                r#"{"rows":4,"cols":4,"rules":{"colorless":true}}"#,
+                "topLeft"
+            ),
+            "Colorless boards have no colors to extend"
+        );
+        assert_eq!(
+            check(
+                r#"{"rows":2,"cols":3,"colors":[0,1],"idxToColor":[0,0,0,1,1,1]}"#,
+                "topLeft"
+            ),
+            "Only square boards with one color per row can be resized"
+        );
+        let hex = r#"{"rows":2,"cols":2,"colors":[0,1],"idxToColor":[0,0,1,1],
+            "rules":{"topology":"hex"}}"#;
+        assert_eq!(
+            check(hex, "topLeft"),
+            "Only square boards without custom adjacency or links can be resized"
+        );
+        let unsolvable = CORPUS
+            .iter()
+            .find(|entry| entry.solution.is_empty())
+            .unwrap();
+        assert_eq!(check(unsolvable.json, "topLeft"), "Board has no solution");
+
+        // No 2x2 board has a solution, since its two queens always touch.
+        let one = r#"{"rows":1,"cols":1,"colors":[0],"idxToColor":[0]}"#;
+        assert_eq!(
+            check(one, "topLeft"),
+            "Found no 2x2 board with one solution in 200 tries"
+        );
+        assert_eq!(
+            shrink(one.to_string(), "topLeft".to_string(), 0),
+            Err("A board of one cell cannot shrink".to_string())
+        );
+    }
+}
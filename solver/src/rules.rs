@@ -0,0 +1,140 @@
+use crate::topology::Topology;
+use serde::{Deserialize, Serialize};
+
+/// The variant rules of a game, read from its optional `rules` object.
+///
+/// Every entry point consumes this one struct, so a flag means the same thing everywhere it is
+/// honored. Absent fields take the rules of the standard LinkedIn game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct Rules {
+    /// Drops the one-queen-per-color rule, leaving only rows, columns, and adjacency.
+    pub colorless: bool,
+    /// Whether every color must hold a queen. When false, colors only forbid a second queen,
+    /// which allows boards with more regions than rows.
+    pub all_colors_required: bool,
+    /// The shape of the cells, square unless the board is made of hexagons.
+    pub topology: Topology,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            colorless: false,
+            all_colors_required: true,
+            topology: Topology::Square,
+        }
+    }
+}
+
+impl Rules {
+    /// Rejects combinations of flags that contradict each other.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.colorless && !self.all_colors_required {
+            return Err(
+                "Rules colorless and allColorsRequired = false cannot be combined: \
+                 colorless boards have no colors to require"
+                    .to_string(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::verify_solution;
+    use crate::{count_solutions, enumerate_solutions, hints, solve, Game};
+
+    /// 6x6 boards with as many colors as rows, none of them a whole row or column, and with one
+    /// more, which only boards not requiring every color allow.
+    const BOARDS: &[&str] = &[
+        r#"{"rows":6,"cols":6,"colors":[0,1,2,3,4,5],"idxToColor":[
+            0,0,0,1,1,1, 0,2,2,2,1,1, 0,2,3,3,3,1, 4,4,3,5,5,5, 4,4,4,4,5,5, 4,4,4,4,4,5]}"#,
+        r#"{"rows":6,"cols":6,"colors":[0,1,2,3,4,5,6],"idxToColor":[
+            0,0,0,1,1,1, 0,2,2,2,1,1, 0,2,3,3,3,1, 4,4,3,5,5,5, 6,6,4,4,5,5, 6,6,6,4,4,5]}"#,
+    ];
+
+    /// Every entry point honors every combination of the rules the same way: the solutions
+    /// `solve` finds and `count_solutions` counts are those enumeration finds, each keeping the
+    /// rules, and hints only place queens every solution has and mark cells none has. The
+    /// combinations `validate` rejects, and boards the rules do not allow, fail everywhere with
+    /// the same message.
+    #[test]
+    fn entry_points_agree_under_every_rule() {
+        let topologies = [Topology::Square, Topology::Hex, Topology::HexLines];
+
+        for (board, colorless) in BOARDS
+            .iter()
+            .flat_map(|&board| [(board, false), (board, true)])
+        {
+            for all_colors_required in [false, true] {
+                for topology in topologies {
+                    let rules = Rules {
+                        colorless,
+                        all_colors_required,
+                        topology,
+                    };
+                    let mut game = Game::from_json(board).unwrap();
+                    game.rules = rules.clone();
+                    if colorless {
+                        game.colors.clear();
+                        game.idx_to_color.iter_mut().for_each(|color| *color = 0);
+                    }
+                    let json = game.to_json();
+
+                    if let Err(error) = Game::from_json(&json) {
+                        if let Err(invalid) = rules.validate() {
+                            assert_eq!(error, invalid);
+                        }
+                        assert_eq!(solve(json.clone()).unwrap_err(), error);
+                        assert_eq!(count_solutions(json.clone()).unwrap_err(), error);
+                        assert_eq!(hints(json, "{}".into(), 3).unwrap_err(), error);
+                        continue;
+                    }
+
+                    let game = Game::from_json(&json).unwrap();
+                    let solutions = enumerate_solutions(&game, &[], &[], u64::MAX);
+                    for solution in &solutions {
+                        assert_eq!(verify_solution(&game, solution), vec![], "{:?}", rules);
+                    }
+                    let count = count_solutions(json.clone()).unwrap();
+                    assert_eq!(count, solutions.len().to_string(), "{:?}", rules);
+
+                    let mut solved: Vec<usize> =
+                        serde_json::from_str(&solve(json.clone()).unwrap()).unwrap();
+                    solved.sort_unstable();
+                    assert_eq!(solved.is_empty(), solutions.is_empty(), "{:?}", rules);
+                    if !solved.is_empty() {
+                        assert!(solutions.contains(&solved), "{:?}", rules);
+                    }
+
+                    let hinted: serde_json::Value =
+                        serde_json::from_str(&hints(json, "{}".into(), 10).unwrap()).unwrap();
+                    for hint in hinted.as_array().unwrap() {
+                        if let Some(queen) = hint["queen"].as_u64() {
+                            let queen = queen as usize;
+                            assert!(
+                                solutions.iter().all(|solution| solution.contains(&queen)),
+                                "{:?}: {}",
+                                rules,
+                                hint
+                            );
+                        }
+                        for mark in hint["marks"].as_array().unwrap() {
+                            let mark = mark.as_u64().unwrap() as usize;
+                            assert!(
+                                solutions.iter().all(|solution| !solution.contains(&mark)),
+                                "{:?}: {}",
+                                rules,
+                                hint
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
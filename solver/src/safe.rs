@@ -0,0 +1,285 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::constrained::find_solution;
+use crate::marks::BoardState;
+use crate::options::Options;
+use crate::search::Search;
+use crate::{enumerate_solutions, Game};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+/// How many solutions of the blank board `find_safe_cells` keeps between calls. Published boards
+/// have one, so the completions of every state are usually all among them.
+const MAX_KNOWN_SOLUTIONS: u64 = 64;
+
+thread_local! {
+    static KNOWN_SOLUTIONS: RefCell<Option<Rc<KnownSolutions>>> = const { RefCell::new(None) };
+}
+
+/// Solutions of the last board asked about, found once per board rather than after every move.
+struct KnownSolutions {
+    /// A hash of the game, to tell whether these are the solutions of the board asked about.
+    hash: u64,
+    /// Up to `MAX_KNOWN_SOLUTIONS` solutions, their cells sorted.
+    solutions: Vec<Vec<usize>>,
+    /// Whether `solutions` are all of them.
+    complete: bool,
+}
+
+impl KnownSolutions {
+    /// The solutions of `game`, looked for again only if the last board asked about was different.
+    fn cached(game: &Game) -> Rc<Self> {
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(game).unwrap().hash(&mut hasher);
+        let hash = hasher.finish();
+
+        KNOWN_SOLUTIONS.with(|cache| {
+            let mut cache = cache.borrow_mut();
+
+            if let Some(known) = cache.as_ref().filter(|known| known.hash == hash) {
+                return Rc::clone(known);
+            }
+
+            let solutions = enumerate_solutions(game, &[], &[], MAX_KNOWN_SOLUTIONS + 1);
+            let complete = solutions.len() as u64 <= MAX_KNOWN_SOLUTIONS;
+            let known = Rc::new(Self {
+                hash,
+                solutions: solutions
+                    .into_iter()
+                    .take(MAX_KNOWN_SOLUTIONS as usize)
+                    .collect(),
+                complete,
+            });
+            *cache = Some(Rc::clone(&known));
+            known
+        })
+    }
+}
+
+/// The empty cells holding a queen in every completion of the user's board, from
+/// `find_safe_cells`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SafeCells {
+    /// Whether some solution agrees with the user's queens and marks. There are no safe cells
+    /// otherwise.
+    pub completable: bool,
+    /// In increasing order.
+    pub cells: Vec<SafeCell>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SafeCell {
+    pub cell: usize,
+    /// Whether filling in singles from the user's queens and marks alone places this queen, so
+    /// that it is easy to see.
+    pub easy: bool,
+}
+
+/// The cells without a queen or mark in `state` that every solution agreeing with it has a
+/// queen on.
+///
+/// The solutions of the blank board are kept between calls, so while there are few of them
+/// each move only filters them. Boards with more search for two completions of the state and
+/// check each cell they share by ruling it out.
+pub(crate) fn find_safe_cells(game: &Game, state: &BoardState) -> Result<SafeCells, String> {
+    state.validate(game)?;
+
+    let agrees = |solution: &[usize]| {
+        state.queens.iter().all(|queen| solution.contains(queen))
+            && !state.marks.iter().any(|mark| solution.contains(mark))
+    };
+
+    let known = KnownSolutions::cached(game);
+    let completions: Vec<_> = if known.complete {
+        known
+            .solutions
+            .iter()
+            .filter(|solution| agrees(solution))
+            .cloned()
+            .collect()
+    } else {
+        enumerate_solutions(game, &state.queens, &state.marks, 2)
+    };
+
+    let mut cells: Vec<_> = match completions.split_first() {
+        Some((first, rest)) => first
+            .iter()
+            .copied()
+            .filter(|idx| !state.queens.contains(idx))
+            .filter(|idx| rest.iter().all(|solution| solution.contains(idx)))
+            .collect(),
+        None => vec![],
+    };
+
+    if !known.complete && completions.len() > 1 {
+        let mut excluded = state.marks.clone();
+        cells.retain(|&idx| {
+            excluded.push(idx);
+            let needed = find_solution(game, &state.queens, &excluded).is_none();
+            excluded.pop();
+            needed
+        });
+    }
+
+    let easy = if cells.is_empty() {
+        vec![]
+    } else if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        singles(game, state, BitAvailability::new(game))
+    } else {
+        singles(game, state, Availability::new(game))
+    };
+
+    Ok(SafeCells {
+        completable: !completions.is_empty(),
+        cells: cells
+            .into_iter()
+            .map(|cell| SafeCell {
+                cell,
+                easy: easy.contains(&cell),
+            })
+            .collect(),
+    })
+}
+
+/// The queens that filling in singles places from `state`.
+fn singles<A: CellAvailability>(game: &Game, state: &BoardState, availability: A) -> Vec<usize> {
+    let options = Options {
+        bitboard: true,
+        propagate: true,
+        ..Options::default()
+    };
+    let mut search = Search::new(game, &options, availability);
+
+    if !search.assume(&state.queens, &state.marks) {
+        return vec![];
+    }
+    if game.has_color_rule() {
+        search.propagate::<true>();
+    } else {
+        search.propagate::<false>();
+    }
+
+    search.solution.split_off(state.queens.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::domains::{deduce, Level};
+    use crate::safe_cells;
+
+    /// The safe cells of `state` found by intersecting every completion, each easy when singles
+    /// from the state place it.
+    fn brute_force(game: &Game, state: &BoardState) -> (bool, Vec<(usize, bool)>) {
+        let completions = enumerate_solutions(game, &state.queens, &state.marks, u64::MAX);
+        let Some((first, rest)) = completions.split_first() else {
+            return (false, vec![]);
+        };
+
+        let singles = deduce(game, &state.queens, &state.marks, Level::Singles).forced;
+        let cells = first
+            .iter()
+            .copied()
+            .filter(|idx| !state.queens.contains(idx))
+            .filter(|idx| rest.iter().all(|solution| solution.contains(idx)))
+            .map(|idx| (idx, singles.contains(&idx)))
+            .collect();
+        (true, cells)
+    }
+
+    fn check(game: &Game, queens: &[usize], marks: &[usize]) -> Vec<(usize, bool)> {
+        let state = BoardState {
+            queens: queens.to_vec(),
+            marks: marks.to_vec(),
+        };
+        let safe = find_safe_cells(game, &state).unwrap();
+        let cells: Vec<_> = safe
+            .cells
+            .iter()
+            .map(|safe| (safe.cell, safe.easy))
+            .collect();
+        assert_eq!(
+            (safe.completable, cells.clone()),
+            brute_force(game, &state),
+            "{:?}",
+            state
+        );
+        cells
+    }
+
+    /// States made of queens of a solution and marks off it, some of them leaving several
+    /// completions, on boards with one solution, five, and more than are kept between calls.
+    #[test]
+    fn finds_the_cells_of_every_completion() {
+        let mut recolored = Game::from_json(CORPUS[0].json).unwrap();
+        recolored.idx_to_color[33] = 1;
+        let colorless = Game::from_json(r#"{"rows":7,"cols":7}"#).unwrap();
+        assert!(
+            enumerate_solutions(&colorless, &[], &[], u64::MAX).len() as u64 > MAX_KNOWN_SOLUTIONS
+        );
+
+        let mut games: Vec<_> = CORPUS
+            .iter()
+            .take(3)
+            .map(|entry| Game::from_json(entry.json).unwrap())
+            .collect();
+        games.extend([recolored, colorless]);
+
+        for game in &games {
+            let solution = enumerate_solutions(game, &[], &[], 1).remove(0);
+            for given in 0..=solution.len() {
+                let queens = &solution[..given];
+                let marks: Vec<_> = (0..game.rows * game.cols)
+                    .filter(|idx| !solution.contains(idx))
+                    .step_by(given + 3)
+                    .collect();
+                check(game, queens, &[]);
+                check(game, queens, &marks);
+            }
+        }
+
+        // A blank board with one solution is all safe.
+        let game = &games[1];
+        let cells = check(game, &[], &[]);
+        assert_eq!(
+            cells.iter().map(|&(cell, _)| cell).collect::<Vec<_>>(),
+            CORPUS[1].solution
+        );
+        assert_eq!(check(&games[3], &[], &[]), [(4, false), (56, true)]);
+    }
+
+    /// Switching boards between calls does not answer for the board asked about before.
+    #[test]
+    fn answers_for_the_board_asked_about() {
+        let first = Game::from_json(CORPUS[0].json).unwrap();
+        let second = Game::from_json(CORPUS[1].json).unwrap();
+        for game in [&first, &second, &first] {
+            check(game, &[], &[]);
+        }
+    }
+
+    #[test]
+    fn has_nothing_safe_on_boards_that_cannot_be_completed() {
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        // Two queens in the first row, and a mark on a queen of the only solution.
+        assert!(check(&game, &[0, 4], &[]).is_empty());
+        assert!(check(&game, &[], &[56]).is_empty());
+
+        let json = CORPUS[0].json.to_string();
+        assert_eq!(
+            safe_cells(json.clone(), r#"{"marks":[56]}"#.to_string()).unwrap(),
+            r#"{"completable":false,"cells":[]}"#
+        );
+        assert_eq!(
+            safe_cells(json.clone(), r#"{"queens":[64]}"#.to_string()).unwrap_err(),
+            "Cell 64 is not on the board, which has cells 0..64"
+        );
+        assert!(safe_cells(json, r#"{"queens":3}"#.to_string()).is_err());
+    }
+}
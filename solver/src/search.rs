@@ -0,0 +1,1271 @@
+use crate::availability::CellAvailability;
+use crate::board::BoardIndex;
+use crate::options::{Branching, Options, TieBreak, ValueOrdering};
+use crate::profile::{Event, Phase, Profiler};
+use crate::propagation::Step;
+use crate::stats::SolveStats;
+use crate::utils::Rng;
+use crate::{Game, NoGoods, NogoodCache, NogoodCursor, UsedTracker};
+use serde::{Deserialize, Serialize};
+
+/// The state of one search over a game, keeping its nogoods in a trie unless told otherwise.
+pub(crate) struct Search<'a, A: CellAvailability, N: NogoodCache = NoGoods> {
+    pub game: &'a Game,
+    pub options: &'a Options,
+    pub index: BoardIndex,
+    pub used: UsedTracker,
+    pub availability: A,
+    pub nogoods: N,
+    /// Queens in the order they were placed.
+    pub solution: Vec<usize>,
+    /// The decisions in `solution` in sorted order, maintained incrementally so the no goods
+    /// cache can be queried without sorting a copy at every node. Queens forced by propagation
+    /// follow from the decisions before them, so they are left out.
+    pub sorted_solution: Vec<usize>,
+    /// Every propagation step currently applied, oldest first.
+    pub trail: Vec<Step>,
+    /// The depth of every forced queen on the board with the decisions that forced it, as a mask
+    /// over depths. Only kept when backjumping.
+    pub forced_reasons: Vec<(usize, u64)>,
+    /// Every cell ruled out by propagation with the decisions responsible, as a mask over
+    /// depths. Only kept when backjumping.
+    pub excluded_reasons: Vec<(usize, u64)>,
+    pub scratch: SearchScratch,
+    pub stats: SolveStats,
+    pub profiler: Profiler,
+    /// The value of `stats.nodes` at which the current run stops, if it has a budget.
+    pub node_limit: Option<usize>,
+    /// The most colors in a naked subset, 2 or 3.
+    pub subset_limit: usize,
+    /// Breaks ties between candidates at random once the search has restarted. Before that, and
+    /// between candidates drawing the same number, ties go to the first in row-major order.
+    pub tiebreak: Option<Rng>,
+    /// Whether a run of `backtrack` that uses up its budget or finds a solution keeps its stack in
+    /// `paused` to go on from, instead of undoing its placements.
+    pub pausing: bool,
+    /// The stack of the last run of `backtrack` if it was paused, its top frame having placed a
+    /// queen whose node has not been entered yet, or solved if `solved_steps` is set.
+    paused: Option<Vec<Frame>>,
+    /// The propagation steps taken by the node the last run of `backtrack` solved, if it was
+    /// pausing, to undo when going on to the next solution.
+    solved_steps: Option<usize>,
+}
+
+/// A search paused between runs, without the game and options it borrows, so that it can be kept
+/// while they are not borrowed and taken up again by `Search::resume`.
+pub(crate) struct Suspended<A: CellAvailability, N: NogoodCache> {
+    index: BoardIndex,
+    used: UsedTracker,
+    availability: A,
+    nogoods: N,
+    solution: Vec<usize>,
+    sorted_solution: Vec<usize>,
+    trail: Vec<Step>,
+    forced_reasons: Vec<(usize, u64)>,
+    excluded_reasons: Vec<(usize, u64)>,
+    scratch: SearchScratch,
+    stats: SolveStats,
+    profiler: Profiler,
+    node_limit: Option<usize>,
+    subset_limit: usize,
+    tiebreak: Option<Rng>,
+    pausing: bool,
+    paused: Option<Vec<Frame>>,
+    solved_steps: Option<usize>,
+}
+
+/// How a run of the search ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RunEnd {
+    Solved,
+    /// Every branch failed, so there is no solution.
+    Exhausted,
+    /// The run used up its node budget and undid all its placements, unless it was pausing.
+    Stopped,
+}
+
+/// Buffers reused from node to node so the search does not allocate at every step.
+#[derive(Default)]
+pub(crate) struct SearchScratch {
+    /// Candidate lists whose nodes are done, kept to be refilled.
+    pub candidate_lists: Vec<Vec<(usize, usize)>>,
+    /// Candidates paired with their sort keys.
+    pub keyed: Vec<(u128, (usize, usize))>,
+    /// The rows and the columns holding spots of each color, as bitmasks.
+    pub rows_of: Vec<u64>,
+    pub cols_of: Vec<u64>,
+    /// The colors with spots in each row and in each column, as bitmasks.
+    pub colors_in_row: Vec<u64>,
+    pub colors_in_col: Vec<u64>,
+    /// The colors considered by the naked subset rule.
+    pub colors: Vec<usize>,
+    /// The cells a propagation rule is about to rule out.
+    pub cells: Vec<usize>,
+    /// The spots left in a unit.
+    pub spots: Vec<usize>,
+    /// Conflict lists of `backjump` nodes that are done, kept to be refilled.
+    pub conflict_lists: Vec<Vec<(usize, u64)>>,
+    /// A nogood being matched against or added to the store.
+    pub nogood: Vec<usize>,
+    /// The cells completing a short nogood with the decisions, as a bitmask over cells.
+    pub completions: Vec<u64>,
+}
+
+/// A node of `backtrack` on its explicit stack.
+struct Frame {
+    candidates: Vec<(usize, usize)>,
+    /// The position in `candidates` of the next cell to try.
+    next: usize,
+    /// How many propagation steps were applied on entering the node.
+    steps: usize,
+    /// The candidate whose subtree is being searched, with its position in `sorted_solution`.
+    placed: Option<(usize, usize)>,
+}
+
+impl<A: CellAvailability, N: NogoodCache> Suspended<A, N> {
+    /// The queens on the board, in the order they were placed.
+    pub fn solution(&self) -> &[usize] {
+        &self.solution
+    }
+
+    /// What the search did over all its runs so far, with the work of its nogood cache.
+    pub fn stats(&self) -> SolveStats {
+        let mut stats = self.stats.clone();
+        self.nogoods.report(&mut stats);
+        #[cfg(feature = "profile")]
+        {
+            stats.profile = self.profiler.clone().into_depths();
+        }
+
+        stats
+    }
+}
+
+/// A row, column, or color: a set of cells that must hold exactly one queen.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Unit {
+    Row(usize),
+    Col(usize),
+    Color(usize),
+}
+
+impl Unit {
+    pub fn cells(self, index: &BoardIndex) -> &[usize] {
+        match self {
+            Unit::Row(row) => &index.cells_by_row[row],
+            Unit::Col(col) => &index.cells_by_col[col],
+            Unit::Color(color) => &index.cells_by_color[color],
+        }
+    }
+
+    pub fn contains(self, game: &Game, idx: usize) -> bool {
+        match self {
+            Unit::Row(row) => idx / game.cols == row,
+            Unit::Col(col) => idx % game.cols == col,
+            Unit::Color(color) => game.idx_to_color[idx] == color,
+        }
+    }
+}
+
+impl<'a, A: CellAvailability> Search<'a, A> {
+    pub fn new(game: &'a Game, options: &'a Options, availability: A) -> Self {
+        Self::with_cache(game, options, availability)
+    }
+}
+
+impl<'a, A: CellAvailability, N: NogoodCache> Search<'a, A, N> {
+    /// A search keeping its nogoods in a cache of type `N`.
+    pub fn with_cache(game: &'a Game, options: &'a Options, availability: A) -> Self {
+        Self {
+            game,
+            options,
+            index: BoardIndex::new(game),
+            used: UsedTracker::new(
+                game.rows,
+                game.cols,
+                game.num_colors(),
+                game.rules.all_colors_required,
+            ),
+            availability,
+            nogoods: N::new(options),
+            solution: vec![],
+            sorted_solution: vec![],
+            trail: vec![],
+            forced_reasons: vec![],
+            excluded_reasons: vec![],
+            scratch: SearchScratch::default(),
+            stats: SolveStats::default(),
+            profiler: Profiler::new(game.rows),
+            node_limit: None,
+            subset_limit: 3,
+            tiebreak: None,
+            pausing: false,
+            paused: None,
+            solved_steps: None,
+        }
+    }
+
+    /// Sets the search aside, to be resumed with the same game and options.
+    pub fn suspend(self) -> Suspended<A, N> {
+        let Self {
+            game: _,
+            options: _,
+            index,
+            used,
+            availability,
+            nogoods,
+            solution,
+            sorted_solution,
+            trail,
+            forced_reasons,
+            excluded_reasons,
+            scratch,
+            stats,
+            profiler,
+            node_limit,
+            subset_limit,
+            tiebreak,
+            pausing,
+            paused,
+            solved_steps,
+        } = self;
+
+        Suspended {
+            index,
+            used,
+            availability,
+            nogoods,
+            solution,
+            sorted_solution,
+            trail,
+            forced_reasons,
+            excluded_reasons,
+            scratch,
+            stats,
+            profiler,
+            node_limit,
+            subset_limit,
+            tiebreak,
+            pausing,
+            paused,
+            solved_steps,
+        }
+    }
+
+    /// Takes up a search set aside by `suspend`, which must be given the game and options it was
+    /// started with.
+    pub fn resume(game: &'a Game, options: &'a Options, suspended: Suspended<A, N>) -> Self {
+        let Suspended {
+            index,
+            used,
+            availability,
+            nogoods,
+            solution,
+            sorted_solution,
+            trail,
+            forced_reasons,
+            excluded_reasons,
+            scratch,
+            stats,
+            profiler,
+            node_limit,
+            subset_limit,
+            tiebreak,
+            pausing,
+            paused,
+            solved_steps,
+        } = suspended;
+
+        Self {
+            game,
+            options,
+            index,
+            used,
+            availability,
+            nogoods,
+            solution,
+            sorted_solution,
+            trail,
+            forced_reasons,
+            excluded_reasons,
+            scratch,
+            stats,
+            profiler,
+            node_limit,
+            subset_limit,
+            tiebreak,
+            pausing,
+            paused,
+            solved_steps,
+        }
+    }
+
+    /// Runs the configured search, leaving the solution in `solution` (empty if there is none).
+    /// Ends `Stopped` if it gave up on reaching `Options::max_nodes`.
+    ///
+    /// With restarts, every run that uses up its budget is abandoned for a fresh one with the
+    /// next budget of the schedule and candidates reshuffled among ties. Nogoods only record
+    /// subtrees that were completely searched, so they stay valid and carry over.
+    pub fn run(&mut self) -> RunEnd {
+        let options = self.options;
+        let restarts = match &options.restarts {
+            Some(restarts) => restarts,
+            None => {
+                self.node_limit = options.max_nodes;
+                return self.run_once();
+            }
+        };
+
+        for run in 0.. {
+            let budget = self.stats.nodes.saturating_add(restarts.budget(run));
+            let last = options
+                .max_nodes
+                .is_some_and(|max_nodes| max_nodes <= budget);
+            self.node_limit = Some(options.max_nodes.map_or(budget, |max| max.min(budget)));
+
+            match self.run_once() {
+                RunEnd::Stopped if !last => {
+                    self.stats.restarts += 1;
+                    self.tiebreak.get_or_insert_with(|| Rng::new(options.seed));
+                }
+                end => return end,
+            }
+        }
+
+        unreachable!()
+    }
+
+    /// Searches for up to `extra_nodes` more nodes, at least one, going on from where the last run
+    /// paused, or from the start if there was none. The run pauses instead of giving up if it
+    /// uses them up, so that running in several chunks visits the same nodes as one long run.
+    ///
+    /// Only backtracking can pause, and restarts and `Options::max_nodes` are left out.
+    pub fn run_more(&mut self, extra_nodes: usize) -> RunEnd {
+        self.node_limit = Some(self.stats.nodes.saturating_add(extra_nodes));
+        self.pausing = true;
+        self.run_once()
+    }
+
+    fn run_once(&mut self) -> RunEnd {
+        match (self.game.has_color_rule(), self.options.backjumping) {
+            (true, false) => self.backtrack::<true>(),
+            (false, false) => self.backtrack::<false>(),
+            (true, true) => self.backjump::<true>(),
+            (false, true) => self.backjump::<false>(),
+        }
+    }
+
+    /// Whether the current run has used up its node budget.
+    #[inline(always)]
+    pub fn out_of_budget(&self) -> bool {
+        self.node_limit
+            .is_some_and(|limit| self.stats.nodes >= limit)
+    }
+
+    /// Appends the decision `idx` to the solution without placing its queen yet, returning its
+    /// position in `sorted_solution`.
+    #[inline(always)]
+    pub fn push(&mut self, idx: usize) -> usize {
+        self.solution.push(idx);
+        let sorted_pos = self.sorted_solution.partition_point(|&i| i < idx);
+        self.sorted_solution.insert(sorted_pos, idx);
+
+        sorted_pos
+    }
+
+    /// Undoes `push`.
+    #[inline(always)]
+    pub fn pop(&mut self, sorted_pos: usize) {
+        self.solution.pop();
+        self.sorted_solution.remove(sorted_pos);
+    }
+
+    /// Places queens on `queens` and rules out `excluded`, before any search. Returns false,
+    /// leaving the search unusable, if the queens threaten each other.
+    pub fn assume(&mut self, queens: &[usize], excluded: &[usize]) -> bool {
+        for &idx in queens {
+            if !self.availability.is_available(idx) {
+                return false;
+            }
+            self.push(idx);
+            self.set_queen(idx, true);
+        }
+
+        for &idx in excluded {
+            if self.availability.is_available(idx) {
+                self.availability.exclude(self.game, idx);
+            }
+        }
+
+        true
+    }
+
+    /// Puts a queen on `idx`, or removes it when `value` is false.
+    #[inline(always)]
+    pub fn set_queen(&mut self, idx: usize, value: bool) {
+        let game = self.game;
+        let (row, col) = (idx / game.cols, idx % game.cols);
+
+        self.used.set(row, col, game.idx_to_color[idx], value);
+        if value {
+            self.availability.place(game, idx);
+        } else {
+            self.availability.remove(game, idx);
+        }
+    }
+
+    /// Backtracking search. `COLORS` selects whether the one-queen-per-color rule is enforced, so
+    /// the colorless mode costs nothing on the normal path.
+    ///
+    /// The search keeps its own stack of frames rather than recursing once per queen, so its depth
+    /// is not limited by the native (or wasm) stack, and a paused run can be picked up again. A
+    /// run that paused on a solution goes on to the next one.
+    fn backtrack<const COLORS: bool>(&mut self) -> RunEnd {
+        trace_span!(_span, "backtrack", resumed = self.paused.is_some());
+        let mut stack = match (self.paused.take(), self.solved_steps.take()) {
+            // Leave the solution found last, trying the next candidate of its parent.
+            (Some(stack), Some(steps)) => {
+                self.unpropagate(steps);
+                stack
+            }
+            // The queen placed last was never entered, so go on from there.
+            (Some(mut stack), None) => match self.enter::<COLORS>() {
+                Ok(child) => {
+                    stack.push(child);
+                    stack
+                }
+                Err(steps) => return self.solved(stack, steps),
+            },
+            (None, _) => match self.enter::<COLORS>() {
+                Ok(frame) => vec![frame],
+                Err(steps) => return self.solved(vec![], steps),
+            },
+        };
+
+        while let Some(frame) = stack.last_mut() {
+            // Backtrack and continue.
+            if let Some((idx, sorted_pos)) = frame.placed.take() {
+                self.set_queen(idx, false);
+                self.pop(sorted_pos);
+            }
+
+            // No goods optimization. The cache stays the same until the next queen is placed, so
+            // one cursor serves every candidate skipped on the way.
+            let mut cursor = self.nogoods.cursor(&self.sorted_solution);
+            let mut chosen = None;
+            while let Some(&(row, col)) = frame.candidates.get(frame.next) {
+                frame.next += 1;
+                let idx = row * self.game.cols + col;
+
+                if cursor.hits(idx) {
+                    self.profiler
+                        .count(self.sorted_solution.len(), Event::NogoodHit);
+                    trace_event!(depth = self.sorted_solution.len(), idx, "nogood hit");
+                    continue;
+                }
+
+                chosen = Some(idx);
+                break;
+            }
+            let lookups = cursor.counts();
+            drop(cursor);
+            self.nogoods.count_lookups(lookups);
+
+            // Put a queen on this square.
+            if let Some(idx) = chosen {
+                let sorted_pos = self.push(idx);
+                self.set_queen(idx, true);
+                frame.placed = Some((idx, sorted_pos));
+            }
+
+            if frame.placed.is_some() {
+                if self.out_of_budget() {
+                    if self.pausing {
+                        self.paused = Some(stack);
+                    } else {
+                        self.abandon(stack);
+                    }
+                    return RunEnd::Stopped;
+                }
+
+                match self.enter::<COLORS>() {
+                    Ok(child) => stack.push(child),
+                    Err(steps) => return self.solved(stack, steps),
+                }
+                continue;
+            }
+
+            // Add this combination of indices to the no goods cache.
+            self.nogoods.insert(&self.sorted_solution);
+            let steps = frame.steps;
+            self.unpropagate(steps);
+            if let Some(frame) = stack.pop() {
+                self.recycle(frame.candidates);
+            }
+        }
+
+        RunEnd::Exhausted
+    }
+
+    /// Undoes every node on `stack` without recording nogoods, since their subtrees were not
+    /// searched completely.
+    fn abandon(&mut self, stack: Vec<Frame>) {
+        for mut frame in stack.into_iter().rev() {
+            if let Some((idx, sorted_pos)) = frame.placed.take() {
+                self.set_queen(idx, false);
+                self.pop(sorted_pos);
+            }
+            self.unpropagate(frame.steps);
+            self.recycle(frame.candidates);
+        }
+    }
+
+    /// Ends a run of `backtrack` on a solution, which a pausing run keeps `stack` to go on from,
+    /// with the `steps` the solved node took.
+    fn solved(&mut self, stack: Vec<Frame>, steps: usize) -> RunEnd {
+        if self.pausing {
+            self.paused = Some(stack);
+            self.solved_steps = Some(steps);
+        }
+
+        RunEnd::Solved
+    }
+
+    /// Starts a node of `backtrack`, returning its frame, or the propagation steps it took if the
+    /// board is solved.
+    fn enter<const COLORS: bool>(&mut self) -> Result<Frame, usize> {
+        self.stats.nodes += 1;
+        self.profiler.count(self.sorted_solution.len(), Event::Node);
+
+        let steps = self.propagate::<COLORS>();
+
+        if self.is_solved() {
+            trace_event!(depth = self.sorted_solution.len(), "solved");
+            return Err(steps);
+        }
+
+        let frame = Frame {
+            candidates: self.get_candidates::<COLORS>(),
+            next: 0,
+            steps,
+            placed: None,
+        };
+        trace_event!(
+            depth = self.sorted_solution.len(),
+            candidates = frame.candidates.len(),
+            "node"
+        );
+
+        Ok(frame)
+    }
+
+    /// Returns the cells to branch on at the current node, in the order to try them. The list
+    /// comes from the scratch pool and can be handed back with `recycle` once the node is done.
+    ///
+    /// The order depends only on the state of the search, so a given game and options visit the
+    /// same nodes on every platform: see `sort_by_cached_key` for the contract.
+    #[inline(always)]
+    pub fn get_candidates<const COLORS: bool>(&mut self) -> Vec<(usize, usize)> {
+        let start = self.profiler.start();
+        let candidates = self.collect_candidates::<COLORS>();
+
+        let depth = self.sorted_solution.len();
+        self.profiler.finish(depth, Phase::Candidates, start);
+        self.profiler
+            .count(depth, Event::Candidates(candidates.len()));
+
+        candidates
+    }
+
+    #[inline(always)]
+    fn collect_candidates<const COLORS: bool>(&mut self) -> Vec<(usize, usize)> {
+        let game = self.game;
+        let row_to_spots = self.availability.row_spots();
+        let col_to_spots = self.availability.col_spots();
+        let color_to_spots = self.availability.color_spots();
+
+        // Forward checking optimization.
+        let starved = self.options.forward_checks()
+            && forward_check_failure(&self.used, row_to_spots, col_to_spots, color_to_spots);
+        if starved || self.breaks_link() {
+            self.profiler
+                .count(self.sorted_solution.len(), Event::ForwardCheckFailure);
+            trace_event!(depth = self.sorted_solution.len(), starved, "dead end");
+            return vec![];
+        }
+
+        let mut candidates = self.scratch.candidate_lists.pop().unwrap_or_default();
+        candidates.clear();
+        match self.options.branching {
+            Branching::Cells => self.availability.collect_available(game, &mut candidates),
+            Branching::Unit => self.collect_tightest_unit::<COLORS>(&mut candidates),
+        }
+        if game.has_same_links() {
+            candidates.retain(|&(row, col)| !self.strands_partner(row * game.cols + col));
+        }
+
+        // Backjumping explains a failure by what happened to every cell of some unit, which a
+        // cell dropped here would leave unexplained.
+        let completions = &mut self.scratch.completions;
+        if !self.options.backjumping
+            && self
+                .nogoods
+                .short()
+                .mark_completions(&self.sorted_solution, completions)
+        {
+            let before = candidates.len();
+            candidates.retain(|&(row, col)| {
+                let idx = row * game.cols + col;
+                completions
+                    .get(idx / 64)
+                    .is_none_or(|&word| (word >> (idx % 64)) & 1 == 0)
+            });
+            self.stats.nogood_prefiltered += before - candidates.len();
+        }
+
+        let mut keyed = std::mem::take(&mut self.scratch.keyed);
+        let mut tiebreak = self.tiebreak.take();
+        let rng = tiebreak.as_mut();
+        let tie_key = |(row, col): (usize, usize)| self.tie_key::<COLORS>(row, col);
+        match self.options.value_ordering {
+            // Variable ordering heuristic optimization.
+            ValueOrdering::TightestUnit => {
+                match self.options.weights_at(self.sorted_solution.len()) {
+                    Some(weights) => sort_by_cached_key(
+                        &mut candidates,
+                        &mut keyed,
+                        rng,
+                        tie_key,
+                        |(row, col)| {
+                            let color_spots = COLORS
+                                .then(|| color_to_spots[game.idx_to_color[row * game.cols + col]]);
+                            weights.key(row_to_spots[row], col_to_spots[col], color_spots)
+                        },
+                    ),
+                    None => sort_by_cached_key(
+                        &mut candidates,
+                        &mut keyed,
+                        rng,
+                        tie_key,
+                        |(row, col)| {
+                            let spots = row_to_spots[row].min(col_to_spots[col]);
+                            if COLORS {
+                                spots.min(color_to_spots[game.idx_to_color[row * game.cols + col]])
+                            } else {
+                                spots
+                            }
+                        },
+                    ),
+                }
+            }
+            ValueOrdering::LeastConstraining => {
+                sort_by_cached_key(&mut candidates, &mut keyed, rng, tie_key, |(row, col)| {
+                    self.eliminations::<COLORS>(row * game.cols + col)
+                })
+            }
+            // Keys are flipped so the cells ruling out the most sort first.
+            ValueOrdering::MostConstraining => {
+                sort_by_cached_key(&mut candidates, &mut keyed, rng, tie_key, |(row, col)| {
+                    u32::MAX as usize - self.eliminations::<COLORS>(row * game.cols + col)
+                })
+            }
+            // Equal keys leave the cells in the order the tiebreak puts them in.
+            ValueOrdering::BoardOrder => {
+                sort_by_cached_key(&mut candidates, &mut keyed, rng, tie_key, |_| 0)
+            }
+        }
+        self.scratch.keyed = keyed;
+        self.tiebreak = tiebreak;
+
+        candidates
+    }
+
+    /// Hands a candidate list from `get_candidates` back to the scratch pool.
+    #[inline(always)]
+    pub fn recycle(&mut self, candidates: Vec<(usize, usize)>) {
+        self.scratch.candidate_lists.push(candidates);
+    }
+
+    /// The secondary key of the candidate at `row` and `col` under `Options::tie_break`, from the
+    /// same spot counts as the primary key.
+    #[inline(always)]
+    fn tie_key<const COLORS: bool>(&self, row: usize, col: usize) -> usize {
+        let game = self.game;
+        match self.options.tie_break {
+            TieBreak::Index => 0,
+            // Keys are flipped so the cells ruling out the most sort first.
+            TieBreak::Degree => {
+                u32::MAX as usize - self.eliminations::<COLORS>(row * game.cols + col)
+            }
+            // The squared distance from the center, doubled so that it stays whole.
+            TieBreak::Centrality => {
+                (2 * row).abs_diff(game.rows - 1).pow(2) + (2 * col).abs_diff(game.cols - 1).pow(2)
+            }
+        }
+    }
+
+    /// Roughly how many available cells a queen on `idx` would rule out, from the spot counts of
+    /// its row, column, and color plus its available neighbors. Cells shared by two of those units
+    /// are counted twice, which is fine for ordering.
+    #[inline(always)]
+    fn eliminations<const COLORS: bool>(&self, idx: usize) -> usize {
+        let game = self.game;
+        let availability = &self.availability;
+
+        let mut count = availability.row_spots()[idx / game.cols] - 1;
+        count += availability.col_spots()[idx % game.cols] - 1;
+        if COLORS {
+            count += availability.color_spots()[game.idx_to_color[idx]] - 1;
+        }
+
+        count
+            + availability
+                .adj_lookup()
+                .neighbors(idx)
+                .iter()
+                .filter(|&&neighbor| availability.is_available(neighbor as usize))
+                .count()
+    }
+
+    /// Finds the unused row, required column, or required color with the fewest spots, along
+    /// with that count. Ties go to rows, then columns, then colors, and then to the lowest index.
+    pub fn tightest_unit<const COLORS: bool>(&self) -> Option<(usize, Unit)> {
+        let mut best: Option<(usize, Unit)> = None;
+        let mut consider = |spots: usize, unit: Unit| {
+            if best
+                .as_ref()
+                .is_none_or(|(best_spots, _)| spots < *best_spots)
+            {
+                best = Some((spots, unit));
+            }
+        };
+
+        for (row, &spots) in self.availability.row_spots().iter().enumerate() {
+            if (self.used.rows >> row) & 1 == 0 {
+                consider(spots, Unit::Row(row));
+            }
+        }
+
+        let missing_cols = self.used.required_cols & !self.used.cols;
+        for (col, &spots) in self.availability.col_spots().iter().enumerate() {
+            if (missing_cols >> col) & 1 == 1 {
+                consider(spots, Unit::Col(col));
+            }
+        }
+
+        if COLORS {
+            let missing_colors = self.used.required_colors & !self.used.colors;
+            for (color, &spots) in self.availability.color_spots().iter().enumerate() {
+                if (missing_colors >> color) & 1 == 1 {
+                    consider(spots, Unit::Color(color));
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Pushes the available cells of the tightest unit, since one of them must hold a queen.
+    fn collect_tightest_unit<const COLORS: bool>(&self, out: &mut Vec<(usize, usize)>) {
+        let game = self.game;
+        let Some((_, unit)) = self.tightest_unit::<COLORS>() else {
+            return;
+        };
+        trace_event!(?unit, "tightest");
+
+        for &idx in unit.cells(&self.index) {
+            if self.availability.is_available(idx) {
+                out.push((idx / game.cols, idx % game.cols));
+            }
+        }
+    }
+}
+
+/// Sorts `candidates` by `key`, computing it once per candidate instead of at every comparison.
+/// `keyed` is scratch space.
+///
+/// The order is fully determined, whatever order the candidates come in and however the sort
+/// treats equal elements: by `key`, then by `tie_key`, then by a random draw from `tiebreak` if
+/// there is one, then by position on the board in row-major order. Every candidate gets a
+/// distinct combined key, with `key` in bits 64 to 96, `tie_key` in bits 32 to 64, the draw in the
+/// next 20, and the row and column in the low 12.
+///
+/// Keys must stay below 2^32.
+#[inline(always)]
+fn sort_by_cached_key(
+    candidates: &mut Vec<(usize, usize)>,
+    keyed: &mut Vec<(u128, (usize, usize))>,
+    tiebreak: Option<&mut Rng>,
+    tie_key: impl Fn((usize, usize)) -> usize,
+    key: impl Fn((usize, usize)) -> usize,
+) {
+    keyed.clear();
+    keyed.extend(candidates.iter().map(|&(row, col)| {
+        // Rows and columns are below `MAX_UNITS`, so each fits in 6 bits.
+        let position = ((row << 6) | col) as u128;
+        let keys = ((key((row, col)) as u128) << 64) | ((tie_key((row, col)) as u128) << 32);
+        (keys | position, (row, col))
+    }));
+    if let Some(rng) = tiebreak {
+        for (key, _) in keyed.iter_mut() {
+            *key |= ((rng.next_u64() >> 44) << 12) as u128;
+        }
+    }
+    keyed.sort_unstable_by_key(|&(key, _)| key);
+
+    candidates.clear();
+    candidates.extend(keyed.iter().map(|&(_, cell)| cell));
+}
+
+#[inline(always)]
+fn forward_check_failure(
+    used: &UsedTracker,
+    row_to_spots: &[usize],
+    col_to_spots: &[usize],
+    color_to_spots: &[usize],
+) -> bool {
+    let rows = row_to_spots.len();
+    let cols = col_to_spots.len();
+    let colors = color_to_spots.len();
+
+    if (0..rows).any(|row| (((used.rows >> row) & 1) == 0) && (row_to_spots[row] == 0)) {
+        return true;
+    }
+
+    let missing_cols = used.required_cols & !used.cols;
+    if (0..cols).any(|col| (((missing_cols >> col) & 1) == 1) && (col_to_spots[col] == 0)) {
+        return true;
+    }
+
+    // Colors that are not required may legitimately run out of spots.
+    let missing_colors = used.required_colors & !used.colors;
+    if (0..colors)
+        .any(|color| (((missing_colors >> color) & 1) == 1) && (color_to_spots[color] == 0))
+    {
+        return true;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availability::Availability;
+    use crate::bitboard::BitAvailability;
+    use crate::corpus::CORPUS;
+    use crate::MAX_UNITS;
+
+    /// The decisions in place at every node the search enters, in order, found by pausing after
+    /// each one.
+    fn visited<A: CellAvailability>(
+        game: &Game,
+        options: &Options,
+        availability: A,
+    ) -> Vec<Vec<usize>> {
+        let mut search = Search::<_, NoGoods>::with_cache(game, options, availability);
+        let mut visited = vec![];
+        loop {
+            let end = search.run_more(1);
+            visited.push(search.solution.clone());
+            if end != RunEnd::Stopped {
+                assert_eq!(end, RunEnd::Solved);
+                return visited;
+            }
+        }
+    }
+
+    /// The nodes of the first corpus board under the default options, as recorded when the
+    /// candidate order was pinned down. Any change to the order, or to anything it is built
+    /// from, shows up here before it shows up as a diff between the traces of two builds.
+    #[test]
+    fn visits_the_recorded_nodes() {
+        let recorded: Vec<&[usize]> = vec![
+            &[56],
+            &[56, 9],
+            &[56, 9, 19],
+            &[56, 9, 19, 29],
+            &[56, 9, 19, 29, 34],
+            &[56, 9, 19, 29, 34, 4],
+            &[56, 9, 19, 29, 34, 4, 47],
+            &[56, 9, 19, 29, 34, 4, 46],
+            &[56, 9, 19, 29, 34, 4, 54],
+            &[56, 9, 19, 29, 34, 4, 55],
+            &[56, 9, 19, 29, 34, 47],
+            &[56, 9, 19, 29, 34, 46],
+            &[56, 9, 19, 29, 34, 54],
+            &[56, 9, 19, 29, 34, 55],
+            &[56, 9, 19, 29, 34, 6],
+            &[56, 9, 19, 29, 34, 7],
+            &[56, 9, 19, 29, 39],
+            &[56, 9, 19, 29, 39, 54],
+            &[56, 9, 19, 29, 39, 54, 4],
+            &[56, 9, 19, 29, 39, 54, 4, 42],
+            &[56, 9, 19, 29, 39, 54, 4, 42],
+        ];
+
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        let options = Options::default();
+        assert_eq!(visited(&game, &options, Availability::new(&game)), recorded);
+        assert_eq!(
+            visited(&game, &options, BitAvailability::new(&game)),
+            recorded
+        );
+
+        let mut search =
+            Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+        assert_eq!(search.run(), RunEnd::Solved);
+        assert_eq!(search.stats.nodes, recorded.len());
+    }
+
+    /// The order of the candidates at the root: the tightest unit first, ties in row-major order.
+    #[test]
+    fn orders_the_root_candidates() {
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        let options = Options::default();
+        let mut search =
+            Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+        let candidates: Vec<_> = search
+            .get_candidates::<true>()
+            .into_iter()
+            .map(|(row, col)| row * game.cols + col)
+            .collect();
+
+        // Color 7 has one spot, color 3 two, colors 1 and 2 three, and color 4 four, while every
+        // other cell has more in each of its units.
+        assert_eq!(
+            candidates[..13],
+            [56, 21, 29, 8, 9, 10, 16, 18, 19, 23, 31, 39, 47]
+        );
+        let mut rest = candidates[13..].to_vec();
+        rest.sort_unstable();
+        assert_eq!(candidates[13..], rest[..]);
+    }
+
+    /// Ties between root candidates on the tightest unit are broken by the secondary key, worked
+    /// out here from the sizes of the units and the neighbors of each cell.
+    #[test]
+    fn breaks_ties_by_the_secondary_key() {
+        for board in CORPUS {
+            let game = Game::from_json(board.json).unwrap();
+            let adjacents = crate::AdjacentsLookup::for_game(&game);
+            let mut sizes = vec![0; game.colors.len()];
+            for &color in &game.idx_to_color {
+                sizes[color] += 1;
+            }
+
+            for tie_break in [TieBreak::Index, TieBreak::Degree, TieBreak::Centrality] {
+                let options = Options {
+                    tie_break,
+                    ..Options::default()
+                };
+                let mut search =
+                    Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+                let candidates: Vec<_> = search
+                    .get_candidates::<true>()
+                    .into_iter()
+                    .map(|(row, col)| row * game.cols + col)
+                    .collect();
+
+                let mut expected: Vec<_> = (0..game.rows * game.cols).collect();
+                expected.sort_by_key(|&idx| {
+                    let (row, col) = (idx / game.cols, idx % game.cols);
+                    let size = sizes[game.idx_to_color[idx]];
+                    let tie = match tie_break {
+                        TieBreak::Index => 0,
+                        TieBreak::Degree => {
+                            let degree =
+                                game.rows + game.cols + size - 3 + adjacents.neighbors(idx).len();
+                            usize::MAX - degree
+                        }
+                        TieBreak::Centrality => {
+                            (2 * row).abs_diff(game.rows - 1).pow(2)
+                                + (2 * col).abs_diff(game.cols - 1).pow(2)
+                        }
+                    };
+                    (game.rows.min(game.cols).min(size), tie, idx)
+                });
+                assert_eq!(candidates, expected, "{} by {:?}", board.name, tie_break);
+            }
+        }
+    }
+
+    /// Every tie-break settles every corpus board the same way.
+    #[test]
+    fn every_tie_break_settles_the_corpus() {
+        for board in CORPUS {
+            let game = Game::from_json(board.json).unwrap();
+            for tie_break in [TieBreak::Index, TieBreak::Degree, TieBreak::Centrality] {
+                let options = Options {
+                    tie_break,
+                    ..Options::default()
+                };
+                let (end, solution, _) = search(&game, &options);
+                let context = format!("{} by {:?}", board.name, tie_break);
+                if board.solution.is_empty() {
+                    assert_eq!(end, RunEnd::Exhausted, "{}", context);
+                } else {
+                    assert_eq!((end, solution), (RunEnd::Solved, board.solution.to_vec()));
+                }
+            }
+        }
+    }
+
+    /// The node counts of other options on the same board.
+    #[test]
+    fn visits_the_recorded_number_of_nodes() {
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        for (json, nodes) in [
+            (r#"{"tieBreak":"degree"}"#, 22),
+            (r#"{"tieBreak":"centrality"}"#, 21),
+            (r#"{"valueOrdering":"leastConstraining"}"#, 14),
+            (r#"{"branching":"unit"}"#, 12),
+            (r#"{"restarts":{"nodes":4},"seed":7}"#, 89),
+        ] {
+            let options = Options::from_json(json).unwrap();
+            let mut search =
+                Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+            assert_eq!(search.run(), RunEnd::Solved, "{}", json);
+            assert_eq!(search.stats.nodes, nodes, "{}", json);
+        }
+    }
+
+    /// How the search ends on `game`, with the cells of the solution sorted, and its nodes.
+    fn search(game: &Game, options: &Options) -> (RunEnd, Vec<usize>, usize) {
+        let mut search = Search::<_, NoGoods>::with_cache(game, options, Availability::new(game));
+        let end = search.run();
+        let mut solution = search.solution.clone();
+        solution.sort_unstable();
+
+        (end, solution, search.stats.nodes)
+    }
+
+    /// Branching over the tightest unit gives the same answers as branching over every cell on
+    /// the corpus, never entering more nodes.
+    #[test]
+    fn unit_branching_agrees_with_cells() {
+        for board in CORPUS {
+            let game = Game::from_json(board.json).unwrap();
+            for json in ["{}", r#"{"propagate":true}"#] {
+                let mut options = Options::from_json(json).unwrap();
+                let (end, solution, nodes) = search(&game, &options);
+                options.branching = Branching::Unit;
+                let (unit_end, unit_solution, unit_nodes) = search(&game, &options);
+
+                let context = format!("{} with {}", board.name, json);
+                assert_eq!(unit_end, end, "{}", context);
+                assert_eq!(solution, board.solution, "{}", context);
+                assert_eq!(unit_solution, board.solution, "{}", context);
+                assert!(
+                    unit_nodes <= nodes,
+                    "{}: {} > {}",
+                    context,
+                    unit_nodes,
+                    nodes
+                );
+            }
+        }
+    }
+
+    /// The tightest unit has as few spots as any unit still needing a queen, counted cell by
+    /// cell, with every queen of the solution placed in turn.
+    #[test]
+    fn finds_the_tightest_unit() {
+        for board in CORPUS.iter().filter(|board| !board.solution.is_empty()) {
+            let game = Game::from_json(board.json).unwrap();
+            let options = Options::default();
+            let mut search =
+                Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+
+            for &queen in board.solution {
+                let open = |unit: Unit| {
+                    unit.cells(&search.index)
+                        .iter()
+                        .filter(|&&idx| search.availability.is_available(idx))
+                        .count()
+                };
+                let empty = |unit: Unit| {
+                    !unit
+                        .cells(&search.index)
+                        .iter()
+                        .any(|idx| search.solution.contains(idx))
+                };
+                let fewest = (0..game.rows)
+                    .map(Unit::Row)
+                    .chain((0..game.cols).map(Unit::Col))
+                    .chain((0..game.num_colors()).map(Unit::Color))
+                    .filter(|&unit| empty(unit))
+                    .map(open)
+                    .min();
+
+                let tightest = search.tightest_unit::<true>();
+                assert_eq!(tightest.map(|(spots, _)| spots), fewest, "{}", board.name);
+                assert!(tightest.is_some_and(|(spots, unit)| empty(unit) && open(unit) == spots));
+
+                search.push(queen);
+                search.set_queen(queen, true);
+            }
+            assert_eq!(search.tightest_unit::<true>(), None);
+        }
+    }
+
+    /// Backtracking written as recursion, one call per node, the way the search was before it
+    /// kept its own stack. Returns whether it found a solution, leaving it in place.
+    fn recurse<const COLORS: bool>(search: &mut Search<Availability>) -> bool {
+        search.stats.nodes += 1;
+        let steps = search.propagate::<COLORS>();
+        if search.is_solved() {
+            return true;
+        }
+
+        let candidates = search.get_candidates::<COLORS>();
+        for &(row, col) in &candidates {
+            let idx = row * search.game.cols + col;
+            if search.nogoods.cursor(&search.sorted_solution).hits(idx) {
+                continue;
+            }
+
+            let sorted_pos = search.push(idx);
+            search.set_queen(idx, true);
+            if recurse::<COLORS>(search) {
+                return true;
+            }
+            search.set_queen(idx, false);
+            search.pop(sorted_pos);
+        }
+
+        search.nogoods.insert(&search.sorted_solution);
+        search.unpropagate(steps);
+        search.recycle(candidates);
+        false
+    }
+
+    /// The explicit stack finds the same solution as recursion on every corpus board, through
+    /// the same number of nodes, placing the queens in the same order.
+    #[test]
+    fn stack_agrees_with_recursion() {
+        for board in CORPUS {
+            let game = Game::from_json(board.json).unwrap();
+            for json in ["{}", r#"{"propagate":true}"#, r#"{"branching":"unit"}"#] {
+                let options = Options::from_json(json).unwrap();
+                let mut stacked =
+                    Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+                let end = stacked.run();
+                let mut recursive =
+                    Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+                let solved = recurse::<true>(&mut recursive);
+
+                let context = format!("{} with {}", board.name, json);
+                assert_eq!(end == RunEnd::Solved, solved, "{}", context);
+                assert_eq!(stacked.solution, recursive.solution, "{}", context);
+                assert_eq!(stacked.stats.nodes, recursive.stats.nodes, "{}", context);
+            }
+        }
+    }
+
+    /// The order from `sort_by_cached_key` is the one a stable sort of the candidates in
+    /// row-major order gets by comparing the keys of the contract, whatever order they come in.
+    #[test]
+    fn cached_keys_sort_like_comparing_keys() {
+        let mut rng = Rng::new(121);
+        let mut keyed = vec![];
+
+        for _ in 0..200 {
+            let mut sorted: Vec<_> = (0..1 + rng.below(40))
+                .map(|_| (rng.below(MAX_UNITS), rng.below(MAX_UNITS)))
+                .collect();
+            sorted.sort_unstable();
+            sorted.dedup();
+            let keys: Vec<_> = sorted
+                .iter()
+                .map(|_| (rng.below(4), rng.below(3)))
+                .collect();
+            let key_of = |cell| keys[sorted.binary_search(&cell).unwrap()];
+
+            let mut expected = sorted.clone();
+            expected.sort_by_key(|&cell| key_of(cell));
+
+            let mut cells = sorted.clone();
+            for pos in (1..cells.len()).rev() {
+                cells.swap(pos, rng.below(pos + 1));
+            }
+            let mut candidates = cells.clone();
+            sort_by_cached_key(
+                &mut candidates,
+                &mut keyed,
+                None,
+                |cell| key_of(cell).1,
+                |cell| key_of(cell).0,
+            );
+            assert_eq!(candidates, expected);
+
+            // A random draw only reorders candidates whose keys are equal, the same way for the
+            // same seed.
+            let mut shuffled = cells.clone();
+            sort_by_cached_key(
+                &mut shuffled,
+                &mut keyed,
+                Some(&mut Rng::new(7)),
+                |cell| key_of(cell).1,
+                |cell| key_of(cell).0,
+            );
+            assert!(shuffled
+                .windows(2)
+                .all(|pair| key_of(pair[0]) <= key_of(pair[1])));
+            let mut again = cells.clone();
+            sort_by_cached_key(
+                &mut again,
+                &mut keyed,
+                Some(&mut Rng::new(7)),
+                |cell| key_of(cell).1,
+                |cell| key_of(cell).0,
+            );
+            assert_eq!(again, shuffled);
+            shuffled.sort_unstable();
+            assert_eq!(shuffled, sorted);
+        }
+    }
+
+    /// The candidates with every queen of the solution placed in turn are the available cells in
+    /// row-major order, sorted stably by the fewest spots among their row, column, and color, as
+    /// before keys were cached.
+    #[test]
+    fn orders_candidates_by_their_tightest_unit() {
+        for board in CORPUS {
+            let game = Game::from_json(board.json).unwrap();
+            let options = Options::default();
+            let mut search =
+                Search::<_, NoGoods>::with_cache(&game, &options, Availability::new(&game));
+
+            for &queen in board.solution {
+                let mut expected = vec![];
+                search.availability.collect_available(&game, &mut expected);
+                let (rows, cols, colors) = (
+                    search.availability.row_spots(),
+                    search.availability.col_spots(),
+                    search.availability.color_spots(),
+                );
+                expected.sort_by_key(|&(row, col)| {
+                    *[
+                        rows[row],
+                        cols[col],
+                        colors[game.idx_to_color[row * game.cols + col]],
+                    ]
+                    .iter()
+                    .min()
+                    .unwrap()
+                });
+
+                let candidates = search.get_candidates::<true>();
+                if !candidates.is_empty() {
+                    assert_eq!(candidates, expected, "{}", board.name);
+                }
+                search.recycle(candidates);
+
+                search.push(queen);
+                search.set_queen(queen, true);
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::corpus::{CorpusBoard, CORPUS};
+use crate::options::Options;
+use crate::strategy::Status;
+use crate::utils::now_ms;
+use crate::verify::verify_solution;
+use crate::worker::{decode_reply, encode_request, handle_request, Kind};
+use crate::{count_solutions, run_counter, run_solver, Game};
+use serde::Serialize;
+
+/// What `run_self_tests` found, for attaching to bug reports.
+#[derive(Serialize, Debug)]
+pub(crate) struct SelfTestReport {
+    /// Whether every board passed.
+    pub passed: bool,
+    pub version: &'static str,
+    /// The optional features the build was made with.
+    pub features: Vec<&'static str>,
+    pub boards: Vec<BoardCheck>,
+    /// Milliseconds taken by all the boards together.
+    pub ms: f64,
+}
+
+/// The checks of one board of the corpus.
+#[derive(Serialize, Debug)]
+pub(crate) struct BoardCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    /// What went wrong, in words. Empty if the board passed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+    pub ms: f64,
+}
+
+/// Solves every board of `CORPUS` as `solve` does and counts its solutions, checking each answer
+/// against the corpus and every solution against the rules with `verify_solution`, which does not
+/// share any code with the search.
+pub(crate) fn run_self_tests() -> SelfTestReport {
+    let start = now_ms();
+    let boards: Vec<_> = CORPUS.iter().map(check_board).collect();
+
+    SelfTestReport {
+        passed: boards.iter().all(|board| board.passed),
+        version: env!("CARGO_PKG_VERSION"),
+        features: enabled_features(),
+        boards,
+        ms: now_ms() - start,
+    }
+}
+
+fn check_board(board: &CorpusBoard) -> BoardCheck {
+    let start = now_ms();
+    let mut failures = vec![];
+
+    match Game::from_json(board.json) {
+        Ok(game) => check_game(&game, board.solution, &mut failures),
+        Err(e) => failures.push(format!("The board does not load: {}", e)),
+    }
+    check_worker(board.json, &mut failures);
+
+    BoardCheck {
+        name: board.name,
+        passed: failures.is_empty(),
+        failures,
+        ms: now_ms() - start,
+    }
+}
+
+/// Checks the answers for `game` against its only solution, if it has one.
+fn check_game(game: &Game, expected: &[usize], failures: &mut Vec<String>) {
+    let outcome = run_solver(game, &Options::for_solve());
+    let expected_status = if expected.is_empty() {
+        Status::Unsolvable
+    } else {
+        Status::Solved
+    };
+
+    if outcome.status != expected_status {
+        failures.push(format!(
+            "Solving gave status {:?}, expected {:?}",
+            outcome.status, expected_status
+        ));
+    } else if outcome.status == Status::Solved {
+        let violations = verify_solution(game, &outcome.solution);
+        if !violations.is_empty() {
+            let violations: Vec<_> = violations.iter().map(ToString::to_string).collect();
+            failures.push(format!(
+                "Solving gave {:?}, which breaks the rules: {}",
+                outcome.solution,
+                violations.join(", ")
+            ));
+        }
+
+        let mut solution = outcome.solution.clone();
+        solution.sort_unstable();
+        if solution != expected {
+            failures.push(format!(
+                "Solving gave {:?}, expected {:?}",
+                solution, expected
+            ));
+        }
+    }
+
+    let count = run_counter(game, false);
+    let expected_count = !expected.is_empty() as u64;
+    if count != expected_count {
+        failures.push(format!(
+            "Counting gave {} solutions, expected {}",
+            count, expected_count
+        ));
+    }
+}
+
+/// Checks that a count sent through the worker envelope comes back as `count_solutions` answers
+/// it directly.
+fn check_worker(game_json: &str, failures: &mut Vec<String>) {
+    let request = encode_request(Kind::Count, game_json, "");
+    let through_worker = decode_reply(&handle_request(&request));
+    let direct = count_solutions(game_json.to_string());
+
+    if through_worker != direct {
+        failures.push(format!(
+            "Counting through the worker gave {:?}, directly {:?}",
+            through_worker, direct
+        ));
+    }
+}
+
+/// The features of Cargo.toml the build was made with, beyond the default panic hook.
+pub(crate) fn enabled_features() -> Vec<&'static str> {
+    [
+        ("simd", cfg!(feature = "simd")),
+        ("parallel", cfg!(feature = "parallel")),
+        ("wasm-threads", cfg!(feature = "wasm-threads")),
+        ("profile", cfg!(feature = "profile")),
+        ("trace", cfg!(feature = "trace")),
+        ("tiny-json", cfg!(feature = "tiny-json")),
+    ]
+    .iter()
+    .filter(|&&(_, enabled)| enabled)
+    .map(|&(feature, _)| feature)
+    .collect()
+}
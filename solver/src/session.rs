@@ -0,0 +1,257 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::no_nogoods::NoNogoods;
+use crate::nogood_set::NoGoodSet;
+use crate::options::{NogoodStore, Options, SolutionOrder};
+use crate::panics::catch_panics;
+use crate::search::{RunEnd, Search, Suspended};
+use crate::stats::SolveStats;
+use crate::strategy::Outcome;
+use crate::utils::now_ms;
+use crate::{solve_output, Game, NoGoods, NogoodCache};
+use wasm_bindgen::prelude::*;
+
+/// What a paused search panics with once an earlier run of it panicked midway.
+const LOST: &str = "The search was lost to an earlier panic";
+
+/// A search set aside between chunks, whatever kind of availability and nogood cache it uses.
+pub(crate) trait PausedSearch {
+    /// Runs the search for up to `extra_nodes` more nodes, going on from where it paused.
+    fn run_more(&mut self, game: &Game, options: &Options, extra_nodes: usize) -> RunEnd;
+    fn solution(&self) -> &[usize];
+    fn stats(&self) -> SolveStats;
+}
+
+/// Empty while the search is running, and for good if it panicked.
+impl<A: CellAvailability, N: NogoodCache> PausedSearch for Option<Suspended<A, N>> {
+    fn run_more(&mut self, game: &Game, options: &Options, extra_nodes: usize) -> RunEnd {
+        let mut search = Search::resume(game, options, self.take().expect(LOST));
+        let end = search.run_more(extra_nodes);
+        *self = Some(search.suspend());
+
+        end
+    }
+
+    fn solution(&self) -> &[usize] {
+        self.as_ref().expect(LOST).solution()
+    }
+
+    fn stats(&self) -> SolveStats {
+        self.as_ref().expect(LOST).stats()
+    }
+}
+
+/// A solve run a chunk of nodes at a time, so that a page can show progress, or give up, without
+/// blocking on one long search.
+///
+/// Every chunk takes the search up exactly where the last one paused, with the same stack,
+/// nogoods, and statistics, so solving in chunks finds the same solution after the same number
+/// of nodes as solving in one go. Only backtracking can pause, so the options must leave out
+/// backjumping, restarts, and a node budget of their own, and name no other algorithm.
+#[wasm_bindgen]
+pub struct SolveSession {
+    game: Game,
+    /// The options searched with, after `Heuristics::Auto` has picked its tier.
+    options: Options,
+    /// The name of that tier, if the options left the heuristics to it.
+    heuristics: Option<&'static str>,
+    search: Box<dyn PausedSearch>,
+    /// How the last chunk ended, if one has run since the search started.
+    end: Option<RunEnd>,
+    /// Milliseconds taken by the chunks since the search started.
+    ms: f64,
+}
+
+#[wasm_bindgen]
+impl SolveSession {
+    /// A session solving the game, tuned by `options_json` as for `solve_with_options`. Nothing
+    /// is searched until `continue_solve`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(game_json: String, options_json: String) -> Result<SolveSession, String> {
+        catch_panics(Some(&game_json), || {
+            let game = Game::from_json(&game_json)?;
+            let (options, heuristics) = tuned_options(&game, &options_json)?;
+
+            let search = start(&game, &options);
+            Ok(Self {
+                game,
+                options,
+                heuristics,
+                search,
+                end: None,
+                ms: 0.0,
+            })
+        })
+    }
+
+    /// Searches for up to `extra_nodes` more nodes, at least one, returning what
+    /// `solve_with_options` would with the statistics of every chunk so far. The status stays
+    /// `unknown` until the search finds a solution or proves there is none, after which calling
+    /// again returns the same result without searching.
+    pub fn continue_solve(&mut self, extra_nodes: usize) -> Result<String, String> {
+        let board = self.game.to_json();
+        catch_panics(Some(&board), || {
+            let mut finished = false;
+            if self.end.is_none_or(|end| end == RunEnd::Stopped) {
+                let started = now_ms();
+                let end = self.search.run_more(&self.game, &self.options, extra_nodes);
+                self.ms += now_ms() - started;
+                self.end = Some(end);
+                finished = end != RunEnd::Stopped;
+            }
+
+            let end = self.end.unwrap();
+            let solution = match end {
+                RunEnd::Solved => self.search.solution().to_vec(),
+                RunEnd::Exhausted | RunEnd::Stopped => vec![],
+            };
+            let mut outcome = Outcome {
+                solution,
+                status: end.into(),
+                stats: self.search.stats(),
+            };
+            outcome.stats.heuristics = self.heuristics;
+
+            // Only the chunk finishing the search records it, so a session counts as one solve.
+            let solved = finished.then_some(("solveSession", self.ms));
+            Ok(solve_output(&self.game, &self.options, outcome, solved))
+        })
+    }
+
+    /// Changes the options of the session. The paused search only carries on if nothing but
+    /// `verify` and `order` changed, since it would not visit the same nodes under any other
+    /// options: it starts over, statistics and all, otherwise.
+    pub fn set_options(&mut self, options_json: String) -> Result<(), String> {
+        let board = self.game.to_json();
+        catch_panics(Some(&board), || {
+            let (options, heuristics) = tuned_options(&self.game, &options_json)?;
+
+            let searched = |options: &Options| Options {
+                verify: None,
+                order: SolutionOrder::Search,
+                ..options.clone()
+            };
+            if searched(&options) != searched(&self.options) {
+                self.search = start(&self.game, &options);
+                self.end = None;
+                self.ms = 0.0;
+            }
+            self.options = options;
+            self.heuristics = heuristics;
+
+            Ok(())
+        })
+    }
+}
+
+/// The options of `options_json` with their heuristics picked for `game`, and the name of the tier
+/// that picked them. Rejects options under which the search could not pause.
+fn tuned_options(
+    game: &Game,
+    options_json: &str,
+) -> Result<(Options, Option<&'static str>), String> {
+    let options = Options::from_json(options_json)?;
+    let (options, heuristics) = options.for_game(game);
+    let options = options.into_owned();
+    check_pausable(&options)?;
+
+    Ok((options, heuristics))
+}
+
+/// Rejects options under which the search could not pause.
+fn check_pausable(options: &Options) -> Result<(), String> {
+    if options
+        .algorithm
+        .as_deref()
+        .is_some_and(|algorithm| algorithm != "systematic")
+    {
+        return Err("Only the systematic algorithm can be solved in chunks".to_string());
+    }
+    if options.backjumping {
+        return Err("Backjumping cannot be solved in chunks".to_string());
+    }
+    if options.restarts.is_some() {
+        return Err("Restarts cannot be solved in chunks".to_string());
+    }
+    if options.max_nodes.is_some() {
+        return Err("Chunks take their budgets from continue_solve, not maxNodes".to_string());
+    }
+
+    Ok(())
+}
+
+/// A search at its start, picked like the systematic strategy picks one.
+pub(crate) fn start(game: &Game, options: &Options) -> Box<dyn PausedSearch> {
+    match options.nogood_store {
+        NogoodStore::Trie => start_with::<NoGoods>(game, options),
+        NogoodStore::Hash => start_with::<NoGoodSet>(game, options),
+        NogoodStore::Off => start_with::<NoNogoods>(game, options),
+    }
+}
+
+fn start_with<N: NogoodCache + 'static>(game: &Game, options: &Options) -> Box<dyn PausedSearch> {
+    if options.bitboard && game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        let search = Search::<_, N>::with_cache(game, options, BitAvailability::new(game));
+        Box::new(Some(search.suspend()))
+    } else {
+        let search = Search::<_, N>::with_cache(game, options, Availability::new(game));
+        Box::new(Some(search.suspend()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::solve_with_options;
+    use serde_json::Value;
+
+    /// The output of the chunk finishing a session, and how many chunks it took.
+    fn solve_in_chunks(json: &str, options: &str, chunk: usize) -> (Value, usize) {
+        let mut session = SolveSession::new(json.to_string(), options.to_string()).unwrap();
+        for chunks in 1.. {
+            let output: Value =
+                serde_json::from_str(&session.continue_solve(chunk).unwrap()).unwrap();
+            if output["status"] != "unknown" {
+                return (output, chunks);
+            }
+        }
+
+        unreachable!()
+    }
+
+    /// However the nodes are split into chunks, the search finds the solution solving in one go
+    /// finds, after the same nodes.
+    #[test]
+    fn solves_in_chunks_like_in_one_go() {
+        // The corpus boards taking the fewest nodes, which chunks of one node still get through
+        // quickly.
+        let colorless = r#"{"rows":9,"cols":9}"#;
+        let boards = CORPUS[..4]
+            .iter()
+            .map(|entry| entry.json)
+            .chain([colorless]);
+
+        for json in boards {
+            for options in ["{}", r#"{"nogoodStore":"hash","bitboard":false}"#] {
+                let whole: Value =
+                    serde_json::from_str(&solve_with_options(json.into(), options.into()).unwrap())
+                        .unwrap();
+                let nodes = whole["stats"]["nodes"].as_u64().unwrap() as usize;
+
+                for chunk in [1, 3, 1000] {
+                    let (output, chunks) = solve_in_chunks(json, options, chunk);
+                    assert_eq!(output["status"], whole["status"], "{}", json);
+                    assert_eq!(output["solution"], whole["solution"], "{}", json);
+                    assert_eq!(
+                        output["stats"]["nodes"], whole["stats"]["nodes"],
+                        "{}",
+                        json
+                    );
+                    // Every chunk but the last uses up its budget.
+                    assert_eq!(chunks, 1.max(nodes.div_ceil(chunk)), "{}", json);
+                }
+            }
+        }
+    }
+}
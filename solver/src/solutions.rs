@@ -0,0 +1,104 @@
+use crate::options::{NogoodStore, Options};
+use crate::panics::catch_panics;
+use crate::search::RunEnd;
+use crate::session::{start, PausedSearch};
+use crate::Game;
+use wasm_bindgen::prelude::*;
+
+/// The solutions of a game, found one at a time as they are asked for.
+///
+/// Every call goes on with the same backtracking search from the solution found last, so asking
+/// for a few solutions only pays for those, and the solutions come in the order the search finds
+/// them, each once. The iterator owns its search, which is freed once the solutions run out or it
+/// is dropped, so other calls cannot disturb it.
+#[wasm_bindgen]
+pub struct SolutionIterator {
+    game: Game,
+    options: Options,
+    /// Empty once every solution has been found.
+    search: Option<Box<dyn PausedSearch>>,
+}
+
+#[wasm_bindgen]
+impl SolutionIterator {
+    /// An iterator over the solutions of the game. Nothing is searched until `next`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(game_json: String) -> Result<SolutionIterator, String> {
+        catch_panics(Some(&game_json), || {
+            let game = Game::from_json(&game_json)?;
+            // Branching over a single unit never comes back to the same decisions, so nogoods would
+            // only take up memory.
+            let options = Options {
+                nogood_store: NogoodStore::Off,
+                ..Options::for_counting()
+            };
+
+            let search = Some(start(&game, &options));
+            Ok(Self {
+                game,
+                options,
+                search,
+            })
+        })
+    }
+
+    /// The next solution as JSON, a list of its cells sorted, or `None` once there are no more.
+    #[wasm_bindgen(js_name = next)]
+    pub fn next_solution(&mut self) -> Result<Option<String>, String> {
+        let board = self.game.to_json();
+        catch_panics(Some(&board), || {
+            let Some(search) = self.search.as_mut() else {
+                return Ok(None);
+            };
+
+            match search.run_more(&self.game, &self.options, usize::MAX) {
+                RunEnd::Solved => {
+                    let mut solution = search.solution().to_vec();
+                    solution.sort_unstable();
+                    Ok(Some(serde_json::to_string(&solution).unwrap()))
+                }
+                RunEnd::Exhausted | RunEnd::Stopped => {
+                    self.search = None;
+                    Ok(None)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::enumerate_solutions;
+
+    /// Asked for one at a time, the solutions are those enumerating them all finds, each once.
+    #[test]
+    fn finds_every_solution_once() {
+        let colorless = [r#"{"rows":6,"cols":6}"#, r#"{"rows":7,"cols":7}"#];
+        let boards = CORPUS.iter().map(|entry| entry.json).chain(colorless);
+
+        for json in boards {
+            let mut solutions = SolutionIterator::new(json.to_string()).unwrap();
+            let mut found = vec![];
+            while let Some(solution) = solutions.next_solution().unwrap() {
+                found.push(serde_json::from_str::<Vec<usize>>(&solution).unwrap());
+            }
+            // Exhausted for good.
+            assert_eq!(solutions.next_solution(), Ok(None));
+
+            let count = found.len();
+            found.sort_unstable();
+            found.dedup();
+            assert_eq!(found.len(), count, "{}", json);
+
+            let game = Game::from_json(json).unwrap();
+            assert_eq!(
+                found,
+                enumerate_solutions(&game, &[], &[], u64::MAX),
+                "{}",
+                json
+            );
+        }
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(feature = "profile")]
+use crate::profile::DepthProfile;
+use serde::Serialize;
+
+/// Counters describing the work done by a single solve.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SolveStats {
+    /// Search nodes entered.
+    pub nodes: usize,
+    /// Queens placed by propagation because a unit had a single spot left.
+    pub forced: usize,
+    /// Cells ruled out because a color's spots were confined to their row or column.
+    pub confined: usize,
+    /// Cells ruled out because a few colors' spots were confined to as many rows or columns.
+    pub subset_eliminations: usize,
+    /// Cells ruled out because a queen there would attack every spot left in some unit.
+    pub wipeouts: usize,
+    /// Nogoods added to the cache.
+    pub nogood_insertions: usize,
+    /// Nogoods dropped, or never stored, because the cache was full.
+    pub nogood_evictions: usize,
+    /// Insertions skipped because a shorter stored nogood already covered them.
+    pub nogood_subsumed: usize,
+    /// Insertions skipped because the same nogood was already stored.
+    pub nogood_duplicates: usize,
+    /// Insertions skipped because the nogood was longer than `Options::max_nogood_len`.
+    pub nogood_too_long: usize,
+    /// Stored nogoods dropped because a shorter nogood covering them arrived.
+    pub nogood_pruned: usize,
+    /// Times the search looked for a nogood covering its placements, and how many of those found
+    /// one and pruned the branch.
+    pub nogood_lookups: usize,
+    pub nogood_hits: usize,
+    /// Lookups that `Options::nogood_signatures` answered without walking the trie.
+    pub nogood_walks_avoided: usize,
+    /// Candidates dropped before the search tried them, since with the decisions they completed
+    /// a nogood of one or two decisions.
+    pub nogood_prefiltered: usize,
+    /// Nogoods in the cache when the search ended, and the trie nodes holding them.
+    pub nogoods_stored: usize,
+    pub nogood_trie_nodes: usize,
+    /// Roughly how many bytes the cache took up when the search ended.
+    pub nogood_bytes: usize,
+    /// Runs abandoned for a fresh start after using up their node or step budget.
+    pub restarts: usize,
+    /// Queens moved by local search.
+    pub local_steps: usize,
+    /// The independent parts the board was split into and searched apart, with
+    /// `Options::decompose`. Zero when it was searched whole.
+    pub parts: usize,
+    /// The tier of `options::AUTO_TIERS` that picked the heuristics, when they were left to it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heuristics: Option<&'static str>,
+    /// What the search did at every depth, shallowest first.
+    #[cfg(feature = "profile")]
+    pub profile: Vec<DepthProfile>,
+}
+
+impl SolveStats {
+    /// Adds the counters of another search, such as one of another part of the board.
+    pub fn add(&mut self, other: &Self) {
+        self.nodes += other.nodes;
+        self.forced += other.forced;
+        self.confined += other.confined;
+        self.subset_eliminations += other.subset_eliminations;
+        self.wipeouts += other.wipeouts;
+        self.nogood_insertions += other.nogood_insertions;
+        self.nogood_evictions += other.nogood_evictions;
+        self.nogood_subsumed += other.nogood_subsumed;
+        self.nogood_duplicates += other.nogood_duplicates;
+        self.nogood_too_long += other.nogood_too_long;
+        self.nogood_pruned += other.nogood_pruned;
+        self.nogood_lookups += other.nogood_lookups;
+        self.nogood_hits += other.nogood_hits;
+        self.nogood_walks_avoided += other.nogood_walks_avoided;
+        self.nogood_prefiltered += other.nogood_prefiltered;
+        self.nogoods_stored += other.nogoods_stored;
+        self.nogood_trie_nodes += other.nogood_trie_nodes;
+        self.nogood_bytes += other.nogood_bytes;
+        self.restarts += other.restarts;
+        self.local_steps += other.local_steps;
+    }
+}
@@ -0,0 +1,312 @@
+use crate::availability::Availability;
+use crate::bitboard::BitAvailability;
+use crate::decompose::split_board;
+use crate::local::min_conflicts;
+use crate::no_nogoods::NoNogoods;
+use crate::nogood_set::NoGoodSet;
+use crate::options::{NogoodStore, Options};
+use crate::search::RunEnd;
+use crate::stats::SolveStats;
+use crate::{run_search, Game, NoGoods, NogoodCache};
+use serde::Serialize;
+
+/// An algorithm for finding a solution, selected by name through `Options::algorithm`.
+///
+/// Adding an algorithm takes an implementation and an entry in `STRATEGIES`. Every strategy gets
+/// the same validated game and options, and must only report solutions that satisfy the rules.
+pub(crate) trait Strategy {
+    fn solve(&self, game: &Game, options: &Options) -> Outcome;
+}
+
+/// What a strategy found.
+pub(crate) struct Outcome {
+    /// Empty unless `status` is `Solved`.
+    pub solution: Vec<usize>,
+    pub status: Status,
+    pub stats: SolveStats,
+}
+
+/// What a solve established about the board.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Status {
+    Solved,
+    /// The strategy proved that there is no solution.
+    Unsolvable,
+    /// The strategy gave up, which says nothing about whether there is a solution.
+    Unknown,
+    /// The strategy claimed a solution that breaks the rules, which is a bug in the solver.
+    InternalError,
+}
+
+impl From<RunEnd> for Status {
+    fn from(end: RunEnd) -> Self {
+        match end {
+            RunEnd::Solved => Status::Solved,
+            RunEnd::Exhausted => Status::Unsolvable,
+            RunEnd::Stopped => Status::Unknown,
+        }
+    }
+}
+
+/// Every strategy by name. The first one is the default.
+const STRATEGIES: &[(&str, &dyn Strategy)] = &[("systematic", &Systematic), ("local", &Local)];
+
+/// The strategy registered as `name`, or the default one when there is no name.
+pub(crate) fn find_strategy(name: Option<&str>) -> Result<&'static dyn Strategy, String> {
+    let Some(name) = name else {
+        return Ok(STRATEGIES[0].1);
+    };
+
+    STRATEGIES
+        .iter()
+        .find(|&&(registered, _)| registered == name)
+        .map(|&(_, strategy)| strategy)
+        .ok_or_else(|| {
+            let names: Vec<_> = STRATEGIES.iter().map(|&(name, _)| name).collect();
+            format!(
+                "Unknown algorithm {:?}, expected one of {}",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// Backtracking search, which finds a solution or proves there is none.
+struct Systematic;
+
+impl Strategy for Systematic {
+    fn solve(&self, game: &Game, options: &Options) -> Outcome {
+        let parts = if options.decompose {
+            split_board(game)
+        } else {
+            None
+        };
+        let Some(parts) = parts else {
+            let (solution, stats, end) = search(game, options, &[]);
+            return Outcome {
+                solution,
+                status: end.into(),
+                stats,
+            };
+        };
+
+        // The queens of the parts together make a solution, and any part without one leaves the
+        // whole board without one.
+        let mut outcome = Outcome {
+            solution: vec![],
+            status: Status::Solved,
+            stats: SolveStats {
+                parts: parts.len(),
+                ..SolveStats::default()
+            },
+        };
+        for part in &parts {
+            // The parts share the node budget, each getting what those before it left.
+            let budget = options
+                .max_nodes
+                .map(|max_nodes| max_nodes.saturating_sub(outcome.stats.nodes));
+            let part_options = Options {
+                max_nodes: budget,
+                ..options.clone()
+            };
+            let (solution, stats, end) = search(&part.game, &part_options, &part.excluded);
+            outcome.stats.add(&stats);
+            if end != RunEnd::Solved {
+                outcome.solution.clear();
+                outcome.status = end.into();
+                break;
+            }
+            outcome.solution.extend(part.to_board(&solution));
+        }
+
+        outcome
+    }
+}
+
+/// Runs the backtracking search with the nogood store of the options, `excluded` ruled out.
+fn search(game: &Game, options: &Options, excluded: &[usize]) -> (Vec<usize>, SolveStats, RunEnd) {
+    match options.nogood_store {
+        NogoodStore::Trie => search_with::<NoGoods>(game, options, excluded),
+        NogoodStore::Hash => search_with::<NoGoodSet>(game, options, excluded),
+        NogoodStore::Off => search_with::<NoNogoods>(game, options, excluded),
+    }
+}
+
+/// Runs the backtracking search with its nogoods kept in `N`.
+fn search_with<N: NogoodCache>(
+    game: &Game,
+    options: &Options,
+    excluded: &[usize],
+) -> (Vec<usize>, SolveStats, RunEnd) {
+    if options.bitboard && game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        run_search::<_, N>(game, options, BitAvailability::new(game), excluded)
+    } else {
+        run_search::<_, N>(game, options, Availability::new(game), excluded)
+    }
+}
+
+/// Min-conflicts local search, which is often faster on large boards with many solutions but
+/// gives up with an unknown status instead of proving there is none.
+struct Local;
+
+impl Strategy for Local {
+    fn solve(&self, game: &Game, options: &Options) -> Outcome {
+        let mut stats = SolveStats::default();
+
+        match min_conflicts(game, options, &mut stats) {
+            Some(solution) => Outcome {
+                solution,
+                status: Status::Solved,
+                stats,
+            },
+            None => Outcome {
+                solution: vec![],
+                status: Status::Unknown,
+                stats,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::options::LocalSearch;
+    use crate::verify::verify_solution;
+
+    fn decomposing(decompose: bool) -> Options {
+        Options {
+            decompose,
+            ..Options::default()
+        }
+    }
+
+    /// A board that does not split searches the same nodes with or without decomposing, so
+    /// trying to split it adds no search. The `solve_decomposed` bench times the try itself.
+    #[test]
+    fn decomposing_costs_nothing_without_parts() {
+        // Every color a row, which leaves every cell to some solution.
+        let striped: Vec<_> = (0..81).map(|idx| idx / 9).collect();
+        let striped = format!(
+            r#"{{"rows":9,"cols":9,"colors":[0,1,2,3,4,5,6,7,8],"idxToColor":{:?}}}"#,
+            striped
+        );
+        // The unsolvable boards of the corpus, which fail to split.
+        let boards = CORPUS
+            .iter()
+            .filter(|entry| entry.solution.is_empty())
+            .map(|entry| entry.json)
+            .chain([striped.as_str()]);
+
+        for json in boards {
+            let game = Game::from_json(json).unwrap();
+            assert!(split_board(&game).is_none());
+
+            let whole = Systematic.solve(&game, &decomposing(false));
+            let outcome = Systematic.solve(&game, &decomposing(true));
+            assert_eq!(outcome.status, whole.status);
+            assert_eq!(outcome.solution, whole.solution);
+            assert_eq!(outcome.stats.nodes, whole.stats.nodes);
+            assert_eq!(outcome.stats.parts, 0);
+        }
+    }
+
+    /// However many parts a board splits into, the search gives up within `max_nodes` in all.
+    #[test]
+    fn shares_the_node_budget_between_parts() {
+        let game = Game::from_json(CORPUS[4].json).unwrap();
+        let solved = Systematic.solve(&game, &decomposing(true));
+        assert!(solved.stats.parts > 1);
+        let nodes = solved.stats.nodes;
+
+        for max_nodes in [1, nodes / 2, nodes - 1] {
+            let options = Options {
+                max_nodes: Some(max_nodes),
+                ..decomposing(true)
+            };
+            let outcome = Systematic.solve(&game, &options);
+            assert_eq!(outcome.status, Status::Unknown);
+            assert!(outcome.stats.nodes <= max_nodes, "{}", max_nodes);
+        }
+
+        let options = Options {
+            max_nodes: Some(nodes),
+            ..decomposing(true)
+        };
+        assert_eq!(Systematic.solve(&game, &options).solution, solved.solution);
+    }
+
+    /// The contract every registered strategy is held to, so that a new entry in `STRATEGIES` is
+    /// checked without a test of its own: on every board of the corpus, a solution it reports
+    /// passes `verify_solution` and is the board's, it only calls a board unsolvable that is,
+    /// and it reports nothing else with a status that is not `Solved`.
+    #[test]
+    fn every_strategy_keeps_the_contract() {
+        // Budgets small enough that strategies which cannot prove anything give up quickly.
+        let options = Options {
+            local: LocalSearch {
+                steps: 2_000,
+                restarts: 4,
+                ..LocalSearch::default()
+            },
+            ..Options::default()
+        };
+
+        for &(name, strategy) in STRATEGIES {
+            assert!(find_strategy(Some(name)).is_ok());
+
+            for entry in CORPUS {
+                let game = Game::from_json(entry.json).unwrap();
+                let outcome = strategy.solve(&game, &options);
+                let context = format!("{} on {}", name, entry.name);
+
+                match outcome.status {
+                    Status::Solved => {
+                        assert!(
+                            verify_solution(&game, &outcome.solution).is_empty(),
+                            "{}",
+                            context
+                        );
+                        let mut solution = outcome.solution.clone();
+                        solution.sort_unstable();
+                        assert_eq!(solution, entry.solution, "{}", context);
+                    }
+                    Status::Unsolvable => {
+                        assert!(entry.solution.is_empty(), "{}", context);
+                        assert!(outcome.solution.is_empty(), "{}", context);
+                    }
+                    Status::Unknown => assert!(outcome.solution.is_empty(), "{}", context),
+                    Status::InternalError => panic!("{}: internal error", context),
+                }
+            }
+        }
+    }
+
+    /// Systematic search is complete, so it settles every board of the corpus.
+    #[test]
+    fn systematic_settles_the_corpus() {
+        for entry in CORPUS {
+            let game = Game::from_json(entry.json).unwrap();
+            let outcome = Systematic.solve(&game, &Options::default());
+            let expected = if entry.solution.is_empty() {
+                Status::Unsolvable
+            } else {
+                Status::Solved
+            };
+            assert_eq!(outcome.status, expected, "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn finds_strategies_by_name() {
+        assert!(find_strategy(None).is_ok());
+        assert!(find_strategy(Some("local")).is_ok());
+        let error = find_strategy(Some("dlx")).err().unwrap();
+        assert_eq!(
+            error,
+            "Unknown algorithm \"dlx\", expected one of systematic, local"
+        );
+    }
+}
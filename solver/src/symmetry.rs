@@ -0,0 +1,485 @@
+use crate::topology::Topology;
+use crate::Game;
+use serde::Serialize;
+
+/// One of the eight rotations and reflections of a square, as a map between cells.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Transform {
+    Identity,
+    /// Reflects each row, swapping the left and right columns.
+    MirrorCols,
+    /// Reflects each column, swapping the top and bottom rows.
+    MirrorRows,
+    Rotate180,
+    /// Reflects across the main diagonal.
+    Transpose,
+    /// Reflects across the anti-diagonal.
+    AntiTranspose,
+    /// Rotates a quarter turn clockwise.
+    Rotate90,
+    Rotate270,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::MirrorCols,
+        Transform::MirrorRows,
+        Transform::Rotate180,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+        Transform::Rotate90,
+        Transform::Rotate270,
+    ];
+
+    /// Whether the transform maps the board's grid onto itself, which for those swapping rows and
+    /// columns means the board is square. Hex grids only map onto themselves by the transforms
+    /// keeping the direction of the third lines, which carry every hexagon's other two neighbors.
+    pub fn fits(self, game: &Game) -> bool {
+        let square_cells = game.rules.topology == Topology::Square;
+
+        match self {
+            Transform::Identity | Transform::Rotate180 => true,
+            Transform::MirrorCols | Transform::MirrorRows => square_cells,
+            Transform::Transpose | Transform::AntiTranspose => game.rows == game.cols,
+            Transform::Rotate90 | Transform::Rotate270 => square_cells && game.rows == game.cols,
+        }
+    }
+
+    /// The cell `idx` is carried to. Only meaningful when the transform `fits` the board.
+    pub fn apply(self, game: &Game, idx: usize) -> usize {
+        let (row, col) = (idx / game.cols, idx % game.cols);
+        let (last_row, last_col) = (game.rows - 1, game.cols - 1);
+
+        let (row, col) = match self {
+            Transform::Identity => (row, col),
+            Transform::MirrorCols => (row, last_col - col),
+            Transform::MirrorRows => (last_row - row, col),
+            Transform::Rotate180 => (last_row - row, last_col - col),
+            Transform::Transpose => (col, row),
+            Transform::AntiTranspose => (last_col - col, last_row - row),
+            Transform::Rotate90 => (col, last_row - row),
+            Transform::Rotate270 => (last_col - col, row),
+        };
+
+        row * game.cols + col
+    }
+}
+
+/// The transforms mapping the board onto itself, always starting with `Identity`.
+///
+/// A transform qualifies when it carries every region onto a single region, so the colors only
+/// get relabeled, and every pair of neighbors onto neighbors. It then carries solutions onto
+/// solutions.
+pub(crate) fn board_symmetries(game: &Game) -> Vec<Transform> {
+    Transform::ALL
+        .iter()
+        .copied()
+        .filter(|&transform| transform.fits(game) && maps_onto_itself(game, transform))
+        .collect()
+}
+
+fn maps_onto_itself(game: &Game, transform: Transform) -> bool {
+    let cells = game.rows * game.cols;
+
+    if game.has_color_rule() {
+        // The relabeling of the colors, which must be one to one.
+        let mut image = vec![None; game.colors.len()];
+        let mut preimage = vec![None; game.colors.len()];
+
+        for idx in 0..cells {
+            let from = game.idx_to_color[idx];
+            let to = game.idx_to_color[transform.apply(game, idx)];
+
+            if *image[from].get_or_insert(to) != to || *preimage[to].get_or_insert(from) != from {
+                return false;
+            }
+        }
+    }
+
+    let same: Vec<_> = game.same_links().map(sorted_pair).collect();
+    if !game.same_links().all(|[a, b]| {
+        same.contains(&sorted_pair([
+            transform.apply(game, a),
+            transform.apply(game, b),
+        ]))
+    }) {
+        return false;
+    }
+
+    // The neighbors of the topology are preserved by every transform fitting it, so only custom
+    // ones need a look.
+    match &game.adjacency {
+        Some(adjacency) => (0..cells).all(|idx| {
+            let neighbors = &adjacency[transform.apply(game, idx)];
+            adjacency[idx]
+                .iter()
+                .all(|&neighbor| neighbors.contains(&transform.apply(game, neighbor)))
+        }),
+        None => true,
+    }
+}
+
+/// A line whose queen a counting search may confine to one half, because some symmetry of the
+/// board reflects the line onto itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Pin {
+    /// The first row, reflected by `MirrorCols`.
+    FirstRow,
+    /// The first column, reflected by `MirrorRows`. Only used on square boards, where every
+    /// column holds a queen.
+    FirstCol,
+}
+
+impl Pin {
+    pub fn for_symmetries(game: &Game, symmetries: &[Transform]) -> Option<Self> {
+        if symmetries.contains(&Transform::MirrorCols) {
+            Some(Pin::FirstRow)
+        } else if symmetries.contains(&Transform::MirrorRows) && game.rows == game.cols {
+            Some(Pin::FirstCol)
+        } else {
+            None
+        }
+    }
+
+    /// The cells of the pinned line past its middle, which the search leaves out.
+    pub fn excluded_cells(self, game: &Game) -> Vec<usize> {
+        match self {
+            Pin::FirstRow => (game.cols / 2 + game.cols % 2..game.cols).collect(),
+            Pin::FirstCol => (game.rows / 2 + game.rows % 2..game.rows)
+                .map(|row| row * game.cols)
+                .collect(),
+        }
+    }
+
+    /// How many solutions `solution` stands for once the excluded half is added back: itself and
+    /// its reflection, unless its queen sits on the middle of the line and is its own reflection.
+    pub fn weight(self, game: &Game, solution: &[usize]) -> u64 {
+        let (pos, len) = match self {
+            Pin::FirstRow => {
+                let queen = solution.iter().find(|&&idx| idx < game.cols).unwrap();
+                (queen % game.cols, game.cols)
+            }
+            Pin::FirstCol => {
+                let queen = solution.iter().find(|&&idx| idx % game.cols == 0).unwrap();
+                (queen / game.cols, game.rows)
+            }
+        };
+
+        if 2 * pos + 1 == len {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// The order in which a solution's cells are compared to pick the canonical one of its orbit.
+    /// Sorting this way puts the queen of the pinned line first, so the canonical solution always
+    /// has it in the half the search keeps.
+    fn key(self, game: &Game, idx: usize) -> (usize, usize) {
+        match self {
+            Pin::FirstRow => (idx / game.cols, idx % game.cols),
+            Pin::FirstCol => (idx % game.cols, idx / game.cols),
+        }
+    }
+}
+
+/// Whether `solution` is the smallest of its images under `symmetries`, comparing cells in the
+/// order given by `pin` and otherwise in row-major order. Exactly one solution of every orbit is
+/// canonical.
+pub(crate) fn is_canonical(
+    game: &Game,
+    symmetries: &[Transform],
+    pin: Option<Pin>,
+    solution: &[usize],
+) -> bool {
+    let key = |idx: usize| pin.unwrap_or(Pin::FirstRow).key(game, idx);
+    let sorted_keys = |transform: Transform| {
+        let mut keys: Vec<_> = solution
+            .iter()
+            .map(|&idx| key(transform.apply(game, idx)))
+            .collect();
+        keys.sort_unstable();
+        keys
+    };
+
+    let own = sorted_keys(Transform::Identity);
+    symmetries
+        .iter()
+        .all(|&transform| sorted_keys(transform) >= own)
+}
+
+/// The transforms carrying the placement `solution` onto itself, ignoring the board's colors.
+pub(crate) fn solution_symmetries(game: &Game, solution: &[usize]) -> Vec<Transform> {
+    let mut own = solution.to_vec();
+    own.sort_unstable();
+
+    Transform::ALL
+        .iter()
+        .copied()
+        .filter(|&transform| transform.fits(game) && image(game, transform, &own) == own)
+        .collect()
+}
+
+/// The transforms carrying the set of `solutions`, each sorted and the list sorted too, onto
+/// itself. A transform may permute the solutions without fixing any of them, and every transform
+/// fitting the board fixes an empty set.
+pub(crate) fn set_symmetries(game: &Game, solutions: &[Vec<usize>]) -> Vec<Transform> {
+    Transform::ALL
+        .iter()
+        .copied()
+        .filter(|&transform| {
+            transform.fits(game)
+                && solutions.iter().all(|solution| {
+                    solutions
+                        .binary_search(&image(game, transform, solution))
+                        .is_ok()
+                })
+        })
+        .collect()
+}
+
+/// The sorted cells `solution` is carried to by `transform`.
+fn image(game: &Game, transform: Transform, solution: &[usize]) -> Vec<usize> {
+    let mut image: Vec<_> = solution
+        .iter()
+        .map(|&idx| transform.apply(game, idx))
+        .collect();
+    image.sort_unstable();
+    image
+}
+
+/// The two cells of a link, lowest first.
+pub(crate) fn sorted_pair([a, b]: [usize; 2]) -> [usize; 2] {
+    [a.min(b), a.max(b)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::verify_solution;
+    use crate::{run_counter, AdjacentsLookup, Game};
+    use std::collections::BTreeSet;
+
+    /// Every solution of `game`, found by trying each way of putting one queen in every row and
+    /// column, which suits small square boards.
+    fn brute_force(game: &Game) -> Vec<Vec<usize>> {
+        fn place(game: &Game, cols: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+            if cols.len() == game.rows {
+                let solution: Vec<_> = (0..game.rows)
+                    .map(|row| row * game.cols + cols[row])
+                    .collect();
+                if verify_solution(game, &solution).is_empty() {
+                    out.push(solution);
+                }
+                return;
+            }
+            for col in 0..game.cols {
+                if !cols.contains(&col) {
+                    cols.push(col);
+                    place(game, cols, out);
+                    cols.pop();
+                }
+            }
+        }
+
+        let mut solutions = vec![];
+        place(game, &mut vec![], &mut solutions);
+        solutions
+    }
+
+    /// How many orbits `solutions` fall into under the symmetries of `game`.
+    fn orbits(game: &Game, solutions: &[Vec<usize>]) -> u64 {
+        let symmetries = board_symmetries(game);
+        let canonical: BTreeSet<_> = solutions
+            .iter()
+            .map(|solution| {
+                symmetries
+                    .iter()
+                    .map(|&transform| image(game, transform, solution))
+                    .min()
+                    .unwrap()
+            })
+            .collect();
+
+        canonical.len() as u64
+    }
+
+    /// A `side` by `side` board whose colors are the orbits of the cells under `transform`, so
+    /// that the transform maps it onto itself. There are more colors than rows, so not every one
+    /// needs a queen.
+    fn orbit_colored(side: usize, transform: Transform) -> Game {
+        let mut game = Game::from_json(&format!(r#"{{"rows":{},"cols":{}}}"#, side, side)).unwrap();
+        let mut idx_to_color = vec![usize::MAX; side * side];
+        let mut colors = 0;
+        for idx in 0..side * side {
+            if idx_to_color[idx] == usize::MAX {
+                let mut cell = idx;
+                while idx_to_color[cell] == usize::MAX {
+                    idx_to_color[cell] = colors;
+                    cell = transform.apply(&game, cell);
+                }
+                colors += 1;
+            }
+        }
+
+        game.idx_to_color = idx_to_color;
+        game.colors = (0..colors).collect();
+        game.rules.colorless = false;
+        game.rules.all_colors_required = false;
+        Game::from_json(&game.to_json()).unwrap()
+    }
+
+    /// Colorless boards have every symmetry of the square, and some of their solutions are fixed
+    /// by a rotation or reflection, so orbits come in several sizes.
+    #[test]
+    fn counts_colorless_orbits_like_brute_force() {
+        for side in 4..=7 {
+            let game = Game::from_json(&format!(r#"{{"rows":{},"cols":{}}}"#, side, side)).unwrap();
+            assert_eq!(board_symmetries(&game), Transform::ALL);
+
+            let solutions = brute_force(&game);
+            assert!(solutions
+                .iter()
+                .any(|solution| solution_symmetries(&game, solution).len() > 1));
+            assert_eq!(
+                run_counter(&game, false),
+                solutions.len() as u64,
+                "side {}",
+                side
+            );
+            assert_eq!(
+                run_counter(&game, true),
+                orbits(&game, &solutions),
+                "side {}",
+                side
+            );
+        }
+    }
+
+    /// Boards with a single reflection or rotation, pinning the first row or column with a
+    /// reflection and leaving the count to canonical solutions otherwise.
+    #[test]
+    fn counts_orbits_under_one_symmetry_like_brute_force() {
+        for transform in [
+            Transform::MirrorCols,
+            Transform::MirrorRows,
+            Transform::Rotate180,
+            Transform::Transpose,
+            Transform::AntiTranspose,
+            Transform::Rotate90,
+        ] {
+            let mut total = 0;
+            for side in 4..=6 {
+                let game = orbit_colored(side, transform);
+                let symmetries = board_symmetries(&game);
+                assert!(symmetries.contains(&transform));
+
+                let solutions = brute_force(&game);
+                let context = format!("{:?} on side {}", transform, side);
+                assert_eq!(
+                    run_counter(&game, false),
+                    solutions.len() as u64,
+                    "{}",
+                    context
+                );
+                assert_eq!(
+                    run_counter(&game, true),
+                    orbits(&game, &solutions),
+                    "{}",
+                    context
+                );
+                total += solutions.len();
+            }
+            assert!(total > 0, "{:?}", transform);
+        }
+    }
+
+    /// The 8x8 board of the game without colors. Queens only attack their neighbors off their
+    /// row and column, so it has the 5242 solutions of OEIS A002464, not the 92 of the eight
+    /// queens puzzle. They fall into 691 classes under the symmetries of the square, which
+    /// Burnside's lemma confirms from the solutions each transform fixes: the identity 5242, the
+    /// half turn 122, each diagonal reflection 74, each quarter turn 8, and the mirrors none.
+    #[test]
+    fn counts_the_colorless_8x8_orbits() {
+        let game = Game::from_json(r#"{"rows":8,"cols":8}"#).unwrap();
+        let solutions = crate::enumerate_solutions(&game, &[], &[], u64::MAX);
+
+        assert_eq!(solutions.len(), 5242);
+        assert_eq!(run_counter(&game, false), 5242);
+        assert_eq!(orbits(&game, &solutions), 691);
+        assert_eq!(run_counter(&game, true), 691);
+    }
+
+    /// Hex boards keep the transforms `fits` claims: each carries every pair of neighbors onto
+    /// neighbors and every solution onto a solution, while the others break some neighbors.
+    #[test]
+    fn keeps_hex_neighbors() {
+        for topology in ["hex", "hexLines"] {
+            for side in 4..=6 {
+                let json = format!(
+                    r#"{{"rows":{},"cols":{},"rules":{{"topology":"{}"}}}}"#,
+                    side, side, topology
+                );
+                let game = Game::from_json(&json).unwrap();
+                let lookup = AdjacentsLookup::for_game(&game);
+                let keeps_neighbors = |transform: Transform| {
+                    (0..side * side).all(|idx| {
+                        let image = &lookup.neighbors(transform.apply(&game, idx));
+                        lookup.neighbors(idx).iter().all(|&neighbor| {
+                            image.contains(&(transform.apply(&game, neighbor as usize) as u16))
+                        })
+                    })
+                };
+
+                let symmetries = board_symmetries(&game);
+                assert_eq!(
+                    symmetries,
+                    [
+                        Transform::Identity,
+                        Transform::Rotate180,
+                        Transform::Transpose,
+                        Transform::AntiTranspose
+                    ],
+                    "{}",
+                    json
+                );
+                for transform in Transform::ALL {
+                    assert_eq!(
+                        keeps_neighbors(transform),
+                        symmetries.contains(&transform),
+                        "{:?} on {}",
+                        transform,
+                        json
+                    );
+                }
+
+                let solutions = brute_force(&game);
+                let set: BTreeSet<_> = solutions.iter().cloned().collect();
+                for &transform in &symmetries {
+                    for solution in &solutions {
+                        assert!(
+                            set.contains(&image(&game, transform, solution)),
+                            "{:?} on {}",
+                            transform,
+                            json
+                        );
+                    }
+                }
+                assert_eq!(
+                    run_counter(&game, false),
+                    solutions.len() as u64,
+                    "{}",
+                    json
+                );
+                assert_eq!(
+                    run_counter(&game, true),
+                    orbits(&game, &solutions),
+                    "{}",
+                    json
+                );
+            }
+        }
+    }
+}
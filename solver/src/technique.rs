@@ -0,0 +1,303 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::difficulty::Technique;
+use crate::marks::BoardState;
+use crate::options::Options;
+use crate::propagation::{Lines, Rule, Step};
+use crate::search::{Search, SearchScratch, Unit};
+use crate::Game;
+use serde::Serialize;
+
+/// A place a technique applies, from `find_technique`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Instance {
+    pub technique: Technique,
+    /// The cells the reasoning rests on: the row, column, or color of a single, or the spots left
+    /// for the colors of a confinement or subset, in increasing order.
+    pub cells: Vec<usize>,
+    /// The units it is about: the unit of a single, or the colors of a confinement or subset
+    /// followed by the rows or columns their spots lie in.
+    pub units: Vec<Unit>,
+    /// The cell a single puts a queen on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queen: Option<usize>,
+    /// The cells a confinement or subset rules out, in increasing order.
+    pub eliminations: Vec<usize>,
+}
+
+/// What `find_technique` found.
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub(crate) enum Finding {
+    Found(Instance),
+    /// The technique applies nowhere on the board as it stands. Others may, but are not looked
+    /// for.
+    Absent,
+    /// The queens of the state attack each other, or leave a row, column, or color without a
+    /// spot, so no solution agrees with it.
+    Contradiction,
+}
+
+/// One place `technique` applies to `state` right now, as the propagation rule for it finds it,
+/// without applying it or any other deduction first. Fails for guessing, which is not a
+/// deduction.
+pub(crate) fn find_technique(
+    game: &Game,
+    state: &BoardState,
+    technique: Technique,
+) -> Result<Finding, String> {
+    state.validate(game)?;
+
+    let rule = match technique {
+        Technique::Singles => Rule::Singles,
+        Technique::Confinement => Rule::Confinement,
+        Technique::Pairs => Rule::Subsets {
+            smallest: 2,
+            largest: 2,
+        },
+        Technique::Triples => Rule::Subsets {
+            smallest: 3,
+            largest: 3,
+        },
+        Technique::Guessing => {
+            return Err("Guessing is not a deduction, so it has no place to be found".to_string())
+        }
+    };
+
+    Ok(if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        find_with(game, state, technique, rule, BitAvailability::new(game))
+    } else {
+        find_with(game, state, technique, rule, Availability::new(game))
+    })
+}
+
+fn find_with<A: CellAvailability>(
+    game: &Game,
+    state: &BoardState,
+    technique: Technique,
+    rule: Rule,
+    availability: A,
+) -> Finding {
+    let options = Options::for_deduction();
+    let mut search = Search::new(game, &options, availability);
+    if !search.assume(&state.queens, &state.marks) {
+        return Finding::Contradiction;
+    }
+
+    let mut scratch = SearchScratch::default();
+    let (tightest, step) = if game.has_color_rule() {
+        (
+            search.tightest_unit::<true>(),
+            search.find_rule::<true>(rule, &mut scratch),
+        )
+    } else {
+        (
+            search.tightest_unit::<false>(),
+            search.find_rule::<false>(rule, &mut scratch),
+        )
+    };
+    if matches!(tightest, Some((0, _))) {
+        return Finding::Contradiction;
+    }
+    let Some(step) = step else {
+        return Finding::Absent;
+    };
+
+    let index = &search.index;
+    let open = |in_set: &dyn Fn(usize) -> bool| -> Vec<usize> {
+        (0..game.rows * game.cols)
+            .filter(|&idx| search.availability.is_available(idx) && in_set(idx))
+            .collect()
+    };
+    let mut eliminations = scratch.cells.clone();
+    eliminations.sort_unstable();
+
+    let instance = match step {
+        Step::Forced { idx, unit } => Instance {
+            technique,
+            cells: unit.cells(index).to_vec(),
+            units: vec![unit],
+            queen: Some(idx),
+            eliminations: vec![],
+        },
+        Step::Confined { color, line, .. } => Instance {
+            technique,
+            cells: open(&|idx| game.idx_to_color[idx] == color),
+            units: vec![Unit::Color(color), line],
+            queen: None,
+            eliminations,
+        },
+        Step::Subset { colors, lines, .. } => {
+            let mut units: Vec<_> = (0..game.num_colors())
+                .filter(|&color| (colors >> color) & 1 == 1)
+                .map(Unit::Color)
+                .collect();
+            units.extend(match lines {
+                Lines::Rows(rows) => (0..game.rows)
+                    .filter(|&row| (rows >> row) & 1 == 1)
+                    .map(Unit::Row)
+                    .collect::<Vec<_>>(),
+                Lines::Cols(cols) => (0..game.cols)
+                    .filter(|&col| (cols >> col) & 1 == 1)
+                    .map(Unit::Col)
+                    .collect(),
+            });
+
+            Instance {
+                technique,
+                cells: open(&|idx| (colors >> game.idx_to_color[idx]) & 1 == 1),
+                units,
+                queen: None,
+                eliminations,
+            }
+        }
+        Step::Wipeout { .. } => unreachable!("no technique is found by wipeouts"),
+    };
+
+    Finding::Found(instance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::domains::{deduce, Level};
+
+    /// Color 0 is the single cell 1, and the only solution is [1, 7, 8, 14].
+    const SINGLES: &str = r#"{"rows":4,"cols":4,"colors":[0,1,2,3],"idxToColor":[
+        1,0,1,1, 1,1,1,1, 2,2,3,3, 2,2,3,3]}"#;
+
+    const TECHNIQUES: [Technique; 4] = [
+        Technique::Singles,
+        Technique::Confinement,
+        Technique::Pairs,
+        Technique::Triples,
+    ];
+
+    fn find(state_json: &str, technique: &str) -> Result<String, String> {
+        crate::find_technique(
+            SINGLES.to_string(),
+            state_json.to_string(),
+            technique.to_string(),
+        )
+    }
+
+    #[test]
+    fn points_at_each_technique() {
+        let empty = r#"{"queens":[],"marks":[]}"#;
+        assert_eq!(
+            find(empty, "singles"),
+            Ok(concat!(
+                r#"{"status":"found","technique":"singles","cells":[1],"#,
+                r#""units":[{"color":0}],"queen":1,"eliminations":[]}"#,
+            )
+            .to_string())
+        );
+        assert_eq!(
+            find(empty, "confinement"),
+            Ok(concat!(
+                r#"{"status":"found","technique":"confinement","cells":[1],"#,
+                r#""units":[{"color":0},{"row":0}],"eliminations":[0,2,3]}"#,
+            )
+            .to_string())
+        );
+        assert_eq!(
+            find(empty, "pairs"),
+            Ok(concat!(
+                r#"{"status":"found","technique":"pairs","cells":[1,8,9,12,13],"#,
+                r#""units":[{"color":0},{"color":2},{"col":0},{"col":1}],"#,
+                r#""eliminations":[0,4,5]}"#,
+            )
+            .to_string())
+        );
+    }
+
+    /// Every instance found along the way to the solution of a corpus board is sound: a single's
+    /// queen is the only spot of its unit and part of the solution, and the eliminations are
+    /// open cells the solution leaves empty.
+    #[test]
+    fn finds_sound_instances_on_corpus_boards() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            let index = crate::board::BoardIndex::new(&game);
+
+            for placed in 0..entry.solution.len() {
+                let state = BoardState {
+                    queens: entry.solution[..placed].to_vec(),
+                    marks: vec![],
+                };
+                let possible = deduce(&game, &state.queens, &[], Level::Basic).possible;
+
+                for technique in TECHNIQUES {
+                    let context = format!("{} {:?} after {}", entry.name, technique, placed);
+                    let instance = match find_technique(&game, &state, technique).unwrap() {
+                        Finding::Found(instance) => instance,
+                        Finding::Absent => {
+                            if technique == Technique::Singles {
+                                let forced = deduce(&game, &state.queens, &[], Level::Singles);
+                                assert!(forced.forced.is_empty(), "{}", context);
+                            }
+                            continue;
+                        }
+                        Finding::Contradiction => panic!("{}", context),
+                    };
+
+                    assert_eq!(instance.technique, technique, "{}", context);
+                    if let Some(queen) = instance.queen {
+                        let open: Vec<_> = instance.units[0]
+                            .cells(&index)
+                            .iter()
+                            .copied()
+                            .filter(|&idx| possible[idx] && !state.queens.contains(&idx))
+                            .collect();
+                        assert_eq!(open, [queen], "{}", context);
+                        assert!(entry.solution.contains(&queen), "{}", context);
+                    } else {
+                        assert!(!instance.eliminations.is_empty(), "{}", context);
+                    }
+                    for &idx in &instance.eliminations {
+                        assert!(possible[idx], "{}", context);
+                        assert!(!entry.solution.contains(&idx), "{}", context);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_absent_techniques_and_contradictions() {
+        // Only the queens of the solution are left, so nothing applies.
+        for technique in ["singles", "confinement", "pairs", "triples"] {
+            assert_eq!(
+                find(r#"{"queens":[1,7,8,14]}"#, technique),
+                Ok(r#"{"status":"absent"}"#.to_string())
+            );
+        }
+
+        let contradiction = Ok(r#"{"status":"contradiction"}"#.to_string());
+        assert_eq!(find(r#"{"queens":[0,5]}"#, "singles"), contradiction);
+        assert_eq!(find(r#"{"marks":[1]}"#, "pairs"), contradiction);
+    }
+
+    #[test]
+    fn rejects_guessing_and_unknown_techniques() {
+        let empty = r#"{"queens":[],"marks":[]}"#;
+        assert_eq!(
+            find(empty, "guessing"),
+            Err("Guessing is not a deduction, so it has no place to be found".to_string())
+        );
+        assert_eq!(
+            find(empty, "x"),
+            Err(concat!(
+                r#"Unknown technique "x", expected one of "#,
+                "singles, confinement, pairs, triples, guessing",
+            )
+            .to_string())
+        );
+        assert_eq!(
+            find(r#"{"queens":[16]}"#, "singles"),
+            Err("Cell 16 is not on the board, which has cells 0..16".to_string())
+        );
+    }
+}
@@ -0,0 +1,1569 @@
+use crate::links::{Link, LinkKind};
+use crate::regions::RegionCell;
+use crate::rules::Rules;
+use crate::topology::Topology;
+use crate::Game;
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+/// Reads a game from `json` without serde, for the `tiny-json` feature. Takes the same texts as
+/// serde_json reading the serde derivation of `Game`, fields and defaults as well as the rarer
+/// forms serde allows, such as a struct given as the list of its fields, and fails on the others
+/// with the same error.
+pub(crate) fn read_game(json: &str) -> Result<Game, String> {
+    let mut reader = Reader {
+        json,
+        pos: 0,
+        depth: 128,
+    };
+
+    let game = reader.game().and_then(|game| reader.end().map(|()| game));
+    game.map_err(|e| reader.message(e))
+}
+
+/// Writes `game` the way serde does, keys in declaration order and empty optionals left out.
+pub(crate) fn write_game(game: &Game) -> String {
+    let mut out = String::new();
+
+    write!(
+        out,
+        "{{\"rows\":{},\"cols\":{},\"colors\":",
+        game.rows, game.cols
+    )
+    .unwrap();
+    write_indices(&mut out, &game.colors);
+    out.push_str(",\"idxToColor\":");
+    write_indices(&mut out, &game.idx_to_color);
+
+    let Rules {
+        colorless,
+        all_colors_required,
+        topology,
+    } = &game.rules;
+    let topology = match topology {
+        Topology::Square => "square",
+        Topology::Hex => "hex",
+        Topology::HexLines => "hexLines",
+    };
+    write!(
+        out,
+        ",\"rules\":{{\"colorless\":{},\"allColorsRequired\":{},\"topology\":\"{}\"}}",
+        colorless, all_colors_required, topology
+    )
+    .unwrap();
+
+    if let Some(adjacency) = &game.adjacency {
+        out.push_str(",\"adjacency\":[");
+        for (pos, neighbors) in adjacency.iter().enumerate() {
+            if pos > 0 {
+                out.push(',');
+            }
+            write_indices(&mut out, neighbors);
+        }
+        out.push(']');
+    }
+
+    if let Some(cell_ids) = &game.cell_ids {
+        out.push_str(",\"cellIds\":[");
+        for (pos, id) in cell_ids.iter().enumerate() {
+            if pos > 0 {
+                out.push(',');
+            }
+            write_string(&mut out, id);
+        }
+        out.push(']');
+    }
+
+    if !game.links.is_empty() {
+        out.push_str(",\"links\":[");
+        for (pos, link) in game.links.iter().enumerate() {
+            if pos > 0 {
+                out.push(',');
+            }
+            let kind = match link.kind {
+                LinkKind::Same => "same",
+                LinkKind::AtMostOne => "atMostOne",
+            };
+            write!(
+                out,
+                "{{\"kind\":\"{}\",\"cells\":[{},{}]}}",
+                kind, link.cells[0], link.cells[1]
+            )
+            .unwrap();
+        }
+        out.push(']');
+    }
+
+    out.push('}');
+    out
+}
+
+/// Writes a solution, or any list of cells, as a JSON array.
+pub(crate) fn write_solution(solution: &[usize]) -> String {
+    let mut out = String::new();
+    write_indices(&mut out, solution);
+    out
+}
+
+fn write_indices(out: &mut String, indices: &[usize]) {
+    out.push('[');
+    for (pos, idx) in indices.iter().enumerate() {
+        if pos > 0 {
+            out.push(',');
+        }
+        write!(out, "{}", idx).unwrap();
+    }
+    out.push(']');
+}
+
+/// Writes `s` quoted, escaping exactly the characters serde_json escapes.
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// An error and the byte it points at. Errors about the data rather than the syntax only get a
+/// position once the list or object around them has been read, as with serde_json.
+struct Error {
+    message: String,
+    at: Option<usize>,
+}
+
+type Parsed<T> = Result<T, Error>;
+
+/// A number as serde_json reads it before the type wanting it looks at it.
+enum Number {
+    U64(u64),
+    I64(i64),
+    F64(f64),
+}
+
+impl Number {
+    /// How serde_json names the number in an error.
+    fn unexpected(&self) -> String {
+        match *self {
+            Number::U64(n) => format!("integer `{}`", n),
+            Number::I64(n) => format!("integer `{}`", n),
+            Number::F64(f) => format!("floating point `{}`", format_float(f)),
+        }
+    }
+}
+
+/// Writes `f` the way serde_json does in errors: its shortest digits, spelled out for decimal
+/// exponents from -5 to 15 and in scientific notation otherwise.
+fn format_float(f: f64) -> String {
+    let scientific = format!("{:e}", f);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    if (-5..=15).contains(&exponent) {
+        let fixed = f.to_string();
+        if fixed.contains('.') {
+            fixed
+        } else {
+            fixed + ".0"
+        }
+    } else {
+        format!("{}e{:+}", mantissa, exponent)
+    }
+}
+
+/// What serde buffers of a value whose type is only known after reading it, as far as telling
+/// a region cell goes.
+enum Content {
+    Unsigned(u64),
+    List(Vec<Content>),
+    Other,
+}
+
+const GAME_FIELDS: [&str; 10] = [
+    "rows",
+    "cols",
+    "colors",
+    "idxToColor",
+    "regions",
+    "rules",
+    "adjacency",
+    "cellIds",
+    "links",
+    "strict",
+];
+const RULES_FIELDS: [&str; 3] = ["colorless", "allColorsRequired", "topology"];
+const LINK_FIELDS: [&str; 2] = ["kind", "cells"];
+const TOPOLOGIES: [&str; 3] = ["square", "hex", "hexLines"];
+const LINK_KINDS: [&str; 2] = ["same", "atMostOne"];
+
+/// A cursor over the JSON text, following the steps of serde_json's `Deserializer` for the
+/// types making up a game, so that both read the same texts and fail on the others with the
+/// same error at the same position.
+struct Reader<'a> {
+    json: &'a str,
+    pos: usize,
+    /// How many more lists, objects, and enums may be opened.
+    depth: u8,
+}
+
+impl<'a> Reader<'a> {
+    /// An error at the position reached.
+    fn error(&self, message: &str) -> Error {
+        Error {
+            message: message.to_string(),
+            at: Some(self.pos),
+        }
+    }
+
+    /// An error about the byte ahead, which points just past it.
+    fn peek_error(&self, message: &str) -> Error {
+        Error {
+            message: message.to_string(),
+            at: Some(self.json.len().min(self.pos + 1)),
+        }
+    }
+
+    /// An error about the data, to be placed later.
+    fn data(message: String) -> Error {
+        Error { message, at: None }
+    }
+
+    /// Places `error` at the position reached, unless it already has one.
+    fn fix(&self, mut error: Error) -> Error {
+        error.at.get_or_insert(self.pos);
+        error
+    }
+
+    /// Spells out `error` with its line and column, the column counting the bytes of the line
+    /// before its position.
+    fn message(&self, error: Error) -> String {
+        let Some(pos) = error.at else {
+            return error.message;
+        };
+        let before = &self.json.as_bytes()[..pos];
+        let start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |newline| newline + 1);
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+
+        format!("{} at line {} column {}", error.message, line, pos - start)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.json.as_bytes().get(self.pos).copied()
+    }
+
+    fn eat(&mut self) {
+        self.pos += 1;
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let next = self.peek();
+        self.pos += next.is_some() as usize;
+        next
+    }
+
+    /// Skips whitespace, returning the byte after it without reading it.
+    fn whitespace(&mut self) -> Option<u8> {
+        while let Some(b' ' | b'\n' | b'\t' | b'\r') = self.peek() {
+            self.eat();
+        }
+        self.peek()
+    }
+
+    /// Reads the rest of a keyword whose first letter was read.
+    fn ident(&mut self, rest: &[u8]) -> Parsed<()> {
+        for &expected in rest {
+            match self.next() {
+                None => return Err(self.error("EOF while parsing a value")),
+                Some(next) if next != expected => return Err(self.error("expected ident")),
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rejects anything after the game but whitespace.
+    fn end(&mut self) -> Parsed<()> {
+        match self.whitespace() {
+            Some(_) => Err(self.peek_error("trailing characters")),
+            None => Ok(()),
+        }
+    }
+
+    /// Opens a list, object, or enum, failing once 127 are open.
+    fn enter(&mut self) -> Parsed<()> {
+        self.depth -= 1;
+        if self.depth == 0 {
+            return Err(self.peek_error("recursion limit exceeded"));
+        }
+
+        self.eat();
+        Ok(())
+    }
+
+    /// The error for the value ahead not being `expected`, naming what it is instead. Text that
+    /// is no value at all gets the syntax error instead.
+    fn invalid_type(&mut self, expected: &str) -> Error {
+        let found = match self.peek().unwrap_or(0) {
+            b'n' => {
+                self.eat();
+                if let Err(e) = self.ident(b"ull") {
+                    return e;
+                }
+                "null".to_string()
+            }
+            b't' => {
+                self.eat();
+                if let Err(e) = self.ident(b"rue") {
+                    return e;
+                }
+                "boolean `true`".to_string()
+            }
+            b'f' => {
+                self.eat();
+                if let Err(e) = self.ident(b"alse") {
+                    return e;
+                }
+                "boolean `false`".to_string()
+            }
+            b'-' => {
+                self.eat();
+                match self.integer(false) {
+                    Ok(number) => number.unexpected(),
+                    Err(e) => return e,
+                }
+            }
+            b'0'..=b'9' => match self.integer(true) {
+                Ok(number) => number.unexpected(),
+                Err(e) => return e,
+            },
+            b'"' => {
+                self.eat();
+                match self.string_body() {
+                    Ok(string) => format!("string {:?}", string),
+                    Err(e) => return e,
+                }
+            }
+            b'[' => "sequence".to_string(),
+            b'{' => "map".to_string(),
+            _ => return self.peek_error("expected value"),
+        };
+
+        self.fix(Self::data(format!(
+            "invalid type: {}, expected {}",
+            found, expected
+        )))
+    }
+
+    /// A number whose sign was read.
+    fn integer(&mut self, positive: bool) -> Parsed<Number> {
+        let significand = match self.next() {
+            None => return Err(self.error("EOF while parsing a value")),
+            Some(b'0') => {
+                if self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                    return Err(self.peek_error("invalid number"));
+                }
+                0
+            }
+            Some(first @ b'1'..=b'9') => {
+                let mut significand = (first - b'0') as u64;
+                while let Some(next @ b'0'..=b'9') = self.peek() {
+                    let digit = (next - b'0') as u64;
+                    let Some(more) = significand
+                        .checked_mul(10)
+                        .and_then(|tens| tens.checked_add(digit))
+                    else {
+                        return self.long_integer(positive, significand).map(Number::F64);
+                    };
+                    self.eat();
+                    significand = more;
+                }
+                significand
+            }
+            Some(_) => return Err(self.error("invalid number")),
+        };
+
+        Ok(match self.peek() {
+            Some(b'.') => Number::F64(self.decimal(positive, significand, 0)?),
+            Some(b'e' | b'E') => Number::F64(self.exponent(positive, significand, 0)?),
+            _ if positive => Number::U64(significand),
+            _ => match (significand as i64).wrapping_neg() {
+                // Zero, or too large for an i64.
+                negated if negated >= 0 => Number::F64(-(significand as f64)),
+                negated => Number::I64(negated),
+            },
+        })
+    }
+
+    /// The digits of an integer past those fitting a u64, which only scale it.
+    fn long_integer(&mut self, positive: bool, significand: u64) -> Parsed<f64> {
+        let mut exponent = 0;
+        loop {
+            match self.peek() {
+                Some(b'0'..=b'9') => {
+                    self.eat();
+                    exponent += 1;
+                }
+                Some(b'.') => return self.decimal(positive, significand, exponent),
+                Some(b'e' | b'E') => return self.exponent(positive, significand, exponent),
+                _ => return self.scale(positive, significand, exponent),
+            }
+        }
+    }
+
+    fn decimal(&mut self, positive: bool, mut significand: u64, exponent: i32) -> Parsed<f64> {
+        self.eat();
+
+        let mut fraction_digits = 0;
+        while let Some(next @ b'0'..=b'9') = self.peek() {
+            let digit = (next - b'0') as u64;
+            let Some(more) = significand
+                .checked_mul(10)
+                .and_then(|tens| tens.checked_add(digit))
+            else {
+                // Digits past those fitting a u64 are dropped.
+                while let Some(b'0'..=b'9') = self.peek() {
+                    self.eat();
+                }
+                break;
+            };
+            self.eat();
+            significand = more;
+            fraction_digits += 1;
+        }
+
+        if fraction_digits == 0 && self.peek().is_some_and(|b| !b.is_ascii_digit()) {
+            return Err(self.peek_error("invalid number"));
+        }
+        if fraction_digits == 0 && self.peek().is_none() {
+            return Err(self.peek_error("EOF while parsing a value"));
+        }
+
+        match self.peek() {
+            Some(b'e' | b'E') => self.exponent(positive, significand, exponent - fraction_digits),
+            _ => self.scale(positive, significand, exponent - fraction_digits),
+        }
+    }
+
+    fn exponent(&mut self, positive: bool, significand: u64, starting: i32) -> Parsed<f64> {
+        self.eat();
+
+        let positive_exponent = match self.peek() {
+            Some(b'+') => {
+                self.eat();
+                true
+            }
+            Some(b'-') => {
+                self.eat();
+                false
+            }
+            _ => true,
+        };
+
+        let mut exponent = match self.next() {
+            None => return Err(self.error("EOF while parsing a value")),
+            Some(digit @ b'0'..=b'9') => (digit - b'0') as i32,
+            Some(_) => return Err(self.error("invalid number")),
+        };
+        while let Some(next @ b'0'..=b'9') = self.peek() {
+            self.eat();
+            let digit = (next - b'0') as i32;
+            let Some(more) = exponent
+                .checked_mul(10)
+                .and_then(|tens| tens.checked_add(digit))
+            else {
+                if significand != 0 && positive_exponent {
+                    return Err(self.error("number out of range"));
+                }
+                while let Some(b'0'..=b'9') = self.peek() {
+                    self.eat();
+                }
+                return Ok(if positive { 0.0 } else { -0.0 });
+            };
+            exponent = more;
+        }
+
+        let exponent = if positive_exponent {
+            starting.saturating_add(exponent)
+        } else {
+            starting.saturating_sub(exponent)
+        };
+        self.scale(positive, significand, exponent)
+    }
+
+    /// `significand` times ten to the `exponent`, scaled the way serde_json does without its
+    /// `float_roundtrip` feature, which is not always the nearest float.
+    fn scale(&self, positive: bool, significand: u64, mut exponent: i32) -> Parsed<f64> {
+        let pow10 = |exponent: u32| -> Option<f64> {
+            (exponent <= 308).then(|| format!("1e{}", exponent).parse().unwrap())
+        };
+
+        let mut f = significand as f64;
+        loop {
+            match pow10(exponent.unsigned_abs()) {
+                Some(pow) => {
+                    if exponent >= 0 {
+                        f *= pow;
+                        if f.is_infinite() {
+                            return Err(self.error("number out of range"));
+                        }
+                    } else {
+                        f /= pow;
+                    }
+                    break;
+                }
+                None => {
+                    if f == 0.0 {
+                        break;
+                    }
+                    if exponent >= 0 {
+                        return Err(self.error("number out of range"));
+                    }
+                    f /= 1e308;
+                    exponent += 308;
+                }
+            }
+        }
+
+        Ok(if positive { f } else { -f })
+    }
+
+    /// The rest of a string whose opening quote was read.
+    fn string_body(&mut self) -> Parsed<String> {
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            while self
+                .peek()
+                .is_some_and(|b| b != b'"' && b != b'\\' && b >= b' ')
+            {
+                self.eat();
+            }
+            out.push_str(&self.json[start..self.pos]);
+
+            match self.next() {
+                None => return Err(self.error("EOF while parsing a string")),
+                Some(b'"') => return Ok(out),
+                Some(b'\\') => self.escape(&mut out)?,
+                Some(_) => {
+                    return Err(self
+                        .error("control character (\\u0000-\\u001F) found while parsing a string"))
+                }
+            }
+        }
+    }
+
+    /// An escape whose backslash was read.
+    fn escape(&mut self, out: &mut String) -> Parsed<()> {
+        out.push(match self.next() {
+            None => return Err(self.error("EOF while parsing a string")),
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => self.unicode_escape()?,
+            Some(_) => return Err(self.error("invalid escape")),
+        });
+
+        Ok(())
+    }
+
+    /// The character of a `\u` escape whose four hex digits come next, reading the second half
+    /// of a surrogate pair as well.
+    fn unicode_escape(&mut self) -> Parsed<char> {
+        let first = self.hex4()?;
+        if (0xDC00..=0xDFFF).contains(&first) {
+            return Err(self.error("lone leading surrogate in hex escape"));
+        }
+        if !(0xD800..=0xDBFF).contains(&first) {
+            return Ok(char::from_u32(first).unwrap());
+        }
+
+        for expected in [b'\\', b'u'] {
+            match self.peek() {
+                None => return Err(self.error("EOF while parsing a string")),
+                Some(next) => {
+                    self.eat();
+                    if next != expected {
+                        return Err(self.error("unexpected end of hex escape"));
+                    }
+                }
+            }
+        }
+        let second = self.hex4()?;
+        if !(0xDC00..=0xDFFF).contains(&second) {
+            return Err(self.error("lone leading surrogate in hex escape"));
+        }
+
+        Ok(char::from_u32(0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00)).unwrap())
+    }
+
+    fn hex4(&mut self) -> Parsed<u32> {
+        let Some(digits) = self.json.as_bytes().get(self.pos..self.pos + 4) else {
+            self.pos = self.json.len();
+            return Err(self.error("EOF while parsing a string"));
+        };
+        self.pos += 4;
+
+        digits
+            .iter()
+            .try_fold(0, |code, &b| Some(code * 16 + (b as char).to_digit(16)?))
+            .ok_or_else(|| self.error("invalid escape"))
+    }
+
+    /// Steps over the rest of a string whose opening quote was read, without keeping it.
+    fn skip_string(&mut self) -> Parsed<()> {
+        loop {
+            while self
+                .peek()
+                .is_some_and(|b| b != b'"' && b != b'\\' && b >= b' ')
+            {
+                self.eat();
+            }
+
+            match self.peek() {
+                None => return Err(self.error("EOF while parsing a string")),
+                Some(b'"') => {
+                    self.eat();
+                    return Ok(());
+                }
+                Some(b'\\') => {
+                    self.eat();
+                    match self.next() {
+                        None => return Err(self.error("EOF while parsing a string")),
+                        Some(b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') => {}
+                        Some(b'u') => drop(self.hex4()?),
+                        Some(_) => return Err(self.error("invalid escape")),
+                    }
+                }
+                Some(_) => {
+                    return Err(self
+                        .error("control character (\\u0000-\\u001F) found while parsing a string"))
+                }
+            }
+        }
+    }
+
+    /// Steps over a number whose sign was read, only checking its syntax.
+    fn skip_number(&mut self) -> Parsed<()> {
+        match self.next() {
+            Some(b'0') => {
+                if self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                    return Err(self.peek_error("invalid number"));
+                }
+            }
+            Some(b'1'..=b'9') => {
+                while let Some(b'0'..=b'9') = self.peek() {
+                    self.eat();
+                }
+            }
+            _ => return Err(self.error("invalid number")),
+        }
+
+        if self.peek() == Some(b'.') {
+            self.eat();
+            if !self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                return Err(self.peek_error("invalid number"));
+            }
+            while let Some(b'0'..=b'9') = self.peek() {
+                self.eat();
+            }
+        }
+
+        if let Some(b'e' | b'E') = self.peek() {
+            self.eat();
+            if let Some(b'+' | b'-') = self.peek() {
+                self.eat();
+            }
+            if !self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                self.pos += self.peek().is_some() as usize;
+                return Err(self.error("invalid number"));
+            }
+            while let Some(b'0'..=b'9') = self.peek() {
+                self.eat();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Steps over a value of any shape, for the fields of the game that are not known. Lists
+    /// and objects are walked without recursion and with looser checks, as serde_json does.
+    fn skip(&mut self) -> Parsed<()> {
+        let mut frames = vec![];
+        let mut enclosing = None;
+
+        loop {
+            let Some(peek) = self.whitespace() else {
+                return Err(self.peek_error("EOF while parsing a value"));
+            };
+
+            let frame = match peek {
+                b'n' => {
+                    self.eat();
+                    self.ident(b"ull")?;
+                    None
+                }
+                b't' => {
+                    self.eat();
+                    self.ident(b"rue")?;
+                    None
+                }
+                b'f' => {
+                    self.eat();
+                    self.ident(b"alse")?;
+                    None
+                }
+                b'-' => {
+                    self.eat();
+                    self.skip_number()?;
+                    None
+                }
+                b'0'..=b'9' => {
+                    self.skip_number()?;
+                    None
+                }
+                b'"' => {
+                    self.eat();
+                    self.skip_string()?;
+                    None
+                }
+                b'[' | b'{' => {
+                    frames.extend(enclosing.take());
+                    self.eat();
+                    Some(peek)
+                }
+                _ => return Err(self.peek_error("expected value")),
+            };
+
+            let (mut accept_comma, mut frame) = match frame {
+                Some(frame) => (false, frame),
+                None => match enclosing.take().or_else(|| frames.pop()) {
+                    Some(frame) => (true, frame),
+                    None => return Ok(()),
+                },
+            };
+
+            loop {
+                match self.whitespace() {
+                    Some(b',') if accept_comma => {
+                        self.eat();
+                        break;
+                    }
+                    Some(b']') if frame == b'[' => {}
+                    Some(b'}') if frame == b'{' => {}
+                    Some(_) if accept_comma && frame == b'[' => {
+                        return Err(self.peek_error("expected `,` or `]`"))
+                    }
+                    Some(_) if accept_comma => return Err(self.peek_error("expected `,` or `}`")),
+                    Some(_) => break,
+                    None if frame == b'[' => {
+                        return Err(self.peek_error("EOF while parsing a list"))
+                    }
+                    None => return Err(self.peek_error("EOF while parsing an object")),
+                }
+
+                self.eat();
+                let Some(outer) = frames.pop() else {
+                    return Ok(());
+                };
+                frame = outer;
+                accept_comma = true;
+            }
+
+            if frame == b'{' {
+                match self.whitespace() {
+                    Some(b'"') => self.eat(),
+                    Some(_) => return Err(self.peek_error("key must be a string")),
+                    None => return Err(self.peek_error("EOF while parsing an object")),
+                }
+                self.skip_string()?;
+                self.colon()?;
+            }
+
+            enclosing = Some(frame);
+        }
+    }
+
+    /// Whether another element of a list follows, reading the comma before it.
+    fn has_next_element(&mut self, first: &mut bool) -> Parsed<bool> {
+        let Some(peek) = self.whitespace() else {
+            return Err(self.peek_error("EOF while parsing a list"));
+        };
+
+        if peek == b']' {
+            Ok(false)
+        } else if std::mem::replace(first, false) {
+            Ok(true)
+        } else if peek == b',' {
+            self.eat();
+            match self.whitespace() {
+                Some(b']') => Err(self.peek_error("trailing comma")),
+                Some(_) => Ok(true),
+                None => Err(self.peek_error("EOF while parsing a value")),
+            }
+        } else {
+            Err(self.peek_error("expected `,` or `]`"))
+        }
+    }
+
+    /// Whether another key of an object follows, reading the comma before it.
+    fn has_next_key(&mut self, first: &mut bool) -> Parsed<bool> {
+        let Some(peek) = self.whitespace() else {
+            return Err(self.peek_error("EOF while parsing an object"));
+        };
+
+        if peek == b'}' {
+            Ok(false)
+        } else if std::mem::replace(first, false) {
+            match peek {
+                b'"' => Ok(true),
+                _ => Err(self.peek_error("key must be a string")),
+            }
+        } else if peek == b',' {
+            self.eat();
+            match self.whitespace() {
+                Some(b'"') => Ok(true),
+                Some(b'}') => Err(self.peek_error("trailing comma")),
+                Some(_) => Err(self.peek_error("key must be a string")),
+                None => Err(self.peek_error("EOF while parsing a value")),
+            }
+        } else {
+            Err(self.peek_error("expected `,` or `}`"))
+        }
+    }
+
+    /// The key `has_next_key` found.
+    fn key(&mut self) -> Parsed<String> {
+        self.eat();
+        self.string_body()
+    }
+
+    fn colon(&mut self) -> Parsed<()> {
+        match self.whitespace() {
+            Some(b':') => {
+                self.eat();
+                Ok(())
+            }
+            Some(_) => Err(self.peek_error("expected `:`")),
+            None => Err(self.peek_error("EOF while parsing an object")),
+        }
+    }
+
+    /// Closes a list whose elements `value` holds. The list is closed even if they failed.
+    fn end_seq<T>(&mut self, value: Parsed<T>) -> Parsed<T> {
+        self.depth += 1;
+        let end = match self.whitespace() {
+            Some(b']') => {
+                self.eat();
+                Ok(())
+            }
+            Some(b',') => {
+                self.eat();
+                match self.whitespace() {
+                    Some(b']') => Err(self.peek_error("trailing comma")),
+                    _ => Err(self.peek_error("trailing characters")),
+                }
+            }
+            Some(_) => Err(self.peek_error("trailing characters")),
+            None => Err(self.peek_error("EOF while parsing a list")),
+        };
+
+        let value = value?;
+        end.map(|()| value)
+    }
+
+    /// Closes an object whose fields `value` holds. The object is closed even if they failed.
+    fn end_map<T>(&mut self, value: Parsed<T>) -> Parsed<T> {
+        self.depth += 1;
+        let end = match self.whitespace() {
+            Some(b'}') => {
+                self.eat();
+                Ok(())
+            }
+            Some(b',') => Err(self.peek_error("trailing comma")),
+            Some(_) => Err(self.peek_error("trailing characters")),
+            None => Err(self.peek_error("EOF while parsing an object")),
+        };
+
+        let value = value?;
+        end.map(|()| value)
+    }
+
+    /// A list, whose elements `visit` reads.
+    fn seq<T>(&mut self, expected: &str, visit: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<T> {
+        let value = match self.whitespace() {
+            None => return Err(self.peek_error("EOF while parsing a value")),
+            Some(b'[') => {
+                self.enter()?;
+                let value = visit(self);
+                self.end_seq(value)
+            }
+            Some(_) => Err(self.invalid_type(expected)),
+        };
+
+        value.map_err(|e| self.fix(e))
+    }
+
+    /// A struct, either as a list of its fields in order, which `by_position` reads, or as an
+    /// object, which `by_name` reads.
+    fn structure<T>(
+        &mut self,
+        expected: &str,
+        by_position: impl FnOnce(&mut Self) -> Parsed<T>,
+        by_name: impl FnOnce(&mut Self) -> Parsed<T>,
+    ) -> Parsed<T> {
+        let value = match self.whitespace() {
+            None => return Err(self.peek_error("EOF while parsing a value")),
+            Some(b'[') => {
+                self.enter()?;
+                let value = by_position(self);
+                self.end_seq(value)
+            }
+            Some(b'{') => {
+                self.enter()?;
+                let value = by_name(self);
+                self.end_map(value)
+            }
+            Some(_) => Err(self.invalid_type(expected)),
+        };
+
+        value.map_err(|e| self.fix(e))
+    }
+
+    /// A unit variant out of `names`, given by name or as an object from its name to `null`.
+    /// Returns its position in `names`.
+    fn variant(&mut self, names: &[&str]) -> Parsed<usize> {
+        match self.whitespace() {
+            Some(b'{') => {
+                self.enter()?;
+                let variant = self.variant_entry(names);
+                self.depth += 1;
+                let variant = variant?;
+
+                match self.whitespace() {
+                    Some(b'}') => {
+                        self.eat();
+                        Ok(variant)
+                    }
+                    Some(_) => Err(self.error("expected value")),
+                    None => Err(self.error("EOF while parsing an object")),
+                }
+            }
+            Some(b'"') => {
+                self.eat();
+                let name = self.string_body()?;
+                Self::lookup(names, "variant", &name).map_err(|e| self.fix(e))
+            }
+            Some(_) => Err(self.peek_error("expected value")),
+            None => Err(self.peek_error("EOF while parsing a value")),
+        }
+    }
+
+    /// The one entry of a variant given as an object.
+    fn variant_entry(&mut self, names: &[&str]) -> Parsed<usize> {
+        match self.whitespace() {
+            Some(b'"') => {}
+            Some(b'}') => return Err(self.peek_error("expected value")),
+            Some(_) => return Err(self.peek_error("key must be a string")),
+            None => return Err(self.peek_error("EOF while parsing an object")),
+        }
+
+        let name = self.key()?;
+        let variant = Self::lookup(names, "variant", &name).map_err(|e| self.fix(e))?;
+        self.colon()?;
+        self.unit()?;
+        Ok(variant)
+    }
+
+    /// The position of `name` among the field or variant names `names`.
+    fn lookup(names: &[&str], what: &str, name: &str) -> Parsed<usize> {
+        names
+            .iter()
+            .position(|&known| known == name)
+            .ok_or_else(|| {
+                let quoted: Vec<_> = names.iter().map(|name| format!("`{}`", name)).collect();
+                let expected = match &quoted[..] {
+                    [only] => format!("expected {}", only),
+                    [first, second] => format!("expected {} or {}", first, second),
+                    _ => format!("expected one of {}", quoted.join(", ")),
+                };
+                Self::data(format!("unknown {} `{}`, {}", what, name, expected))
+            })
+    }
+
+    fn unit(&mut self) -> Parsed<()> {
+        let value = match self.whitespace() {
+            None => return Err(self.peek_error("EOF while parsing a value")),
+            Some(b'n') => {
+                self.eat();
+                self.ident(b"ull")
+            }
+            Some(_) => Err(self.invalid_type("unit")),
+        };
+
+        value.map_err(|e| self.fix(e))
+    }
+
+    fn boolean(&mut self) -> Parsed<bool> {
+        let value = match self.whitespace() {
+            None => return Err(self.peek_error("EOF while parsing a value")),
+            Some(b't') => {
+                self.eat();
+                self.ident(b"rue").map(|()| true)
+            }
+            Some(b'f') => {
+                self.eat();
+                self.ident(b"alse").map(|()| false)
+            }
+            Some(_) => Err(self.invalid_type("a boolean")),
+        };
+
+        value.map_err(|e| self.fix(e))
+    }
+
+    fn index(&mut self) -> Parsed<usize> {
+        let number = match self.whitespace() {
+            None => return Err(self.peek_error("EOF while parsing a value")),
+            Some(b'-') => {
+                self.eat();
+                self.integer(false)?
+            }
+            Some(b'0'..=b'9') => self.integer(true)?,
+            Some(_) => return Err(self.invalid_type("usize")),
+        };
+
+        let index = match number {
+            Number::U64(n) => usize::try_from(n).ok(),
+            _ => None,
+        };
+        index.ok_or_else(|| {
+            let message = match number {
+                Number::F64(_) => format!("invalid type: {}, expected usize", number.unexpected()),
+                _ => format!("invalid value: {}, expected usize", number.unexpected()),
+            };
+            self.fix(Self::data(message))
+        })
+    }
+
+    fn string(&mut self) -> Parsed<String> {
+        match self.whitespace() {
+            None => Err(self.peek_error("EOF while parsing a value")),
+            Some(b'"') => {
+                self.eat();
+                self.string_body()
+            }
+            Some(_) => Err(self.invalid_type("a string")),
+        }
+    }
+
+    /// `null`, or the value `some` reads.
+    fn option<T>(&mut self, some: impl FnOnce(&mut Self) -> Parsed<T>) -> Parsed<Option<T>> {
+        if self.whitespace() == Some(b'n') {
+            self.eat();
+            self.ident(b"ull")?;
+            return Ok(None);
+        }
+
+        some(self).map(Some)
+    }
+
+    /// A list, each element read with `element`.
+    fn list<T>(&mut self, mut element: impl FnMut(&mut Self) -> Parsed<T>) -> Parsed<Vec<T>> {
+        self.seq("a sequence", |reader| {
+            let mut elements = vec![];
+            let mut first = true;
+            while reader.has_next_element(&mut first)? {
+                elements.push(element(reader)?);
+            }
+            Ok(elements)
+        })
+    }
+
+    fn game(&mut self) -> Parsed<Game> {
+        let blank = || Game {
+            rows: 0,
+            cols: 0,
+            colors: vec![],
+            idx_to_color: vec![],
+            regions: None,
+            rules: Rules::default(),
+            adjacency: None,
+            cell_ids: None,
+            links: vec![],
+            strict: false,
+        };
+
+        self.structure(
+            "struct Game",
+            |reader| {
+                let mut game = blank();
+                let mut first = true;
+                for field in 0..GAME_FIELDS.len() {
+                    if !reader.has_next_element(&mut first)? {
+                        break;
+                    }
+                    reader.game_field(&mut game, field)?;
+                }
+                Ok(game)
+            },
+            |reader| {
+                let mut game = blank();
+                let mut seen = [false; GAME_FIELDS.len()];
+                let mut first = true;
+                while reader.has_next_key(&mut first)? {
+                    let key = reader.key()?;
+                    let Some(field) = GAME_FIELDS.iter().position(|&name| name == key) else {
+                        reader.colon()?;
+                        reader.skip()?;
+                        continue;
+                    };
+                    if std::mem::replace(&mut seen[field], true) {
+                        let message = format!("duplicate field `{}`", GAME_FIELDS[field]);
+                        return Err(Self::data(message));
+                    }
+                    reader.colon()?;
+                    reader.game_field(&mut game, field)?;
+                }
+                Ok(game)
+            },
+        )
+    }
+
+    /// The value of the field numbered `field` in `GAME_FIELDS`.
+    fn game_field(&mut self, game: &mut Game, field: usize) -> Parsed<()> {
+        match field {
+            0 => game.rows = self.index()?,
+            1 => game.cols = self.index()?,
+            2 => game.colors = self.list(Self::index)?,
+            3 => game.idx_to_color = self.list(Self::index)?,
+            4 => {
+                game.regions =
+                    self.option(|reader| reader.list(|reader| reader.list(Self::region_cell)))?
+            }
+            5 => game.rules = self.rules()?,
+            6 => {
+                game.adjacency =
+                    self.option(|reader| reader.list(|reader| reader.list(Self::index)))?
+            }
+            7 => game.cell_ids = self.option(|reader| reader.list(Self::string))?,
+            8 => game.links = self.list(Self::link)?,
+            _ => game.strict = self.boolean()?,
+        }
+
+        Ok(())
+    }
+
+    /// A cell of a region, as an index or a `[row, col]` pair. Like serde for an untagged enum,
+    /// the value is read whatever it is, and only then matched against either.
+    fn region_cell(&mut self) -> Parsed<RegionCell> {
+        let index = |content: &Content| match *content {
+            Content::Unsigned(n) => usize::try_from(n).ok(),
+            _ => None,
+        };
+
+        let content = self.content()?;
+        let cell = match &content {
+            Content::Unsigned(_) => index(&content).map(RegionCell::Idx),
+            Content::List(pair) => match &pair[..] {
+                [row, col] => index(row)
+                    .zip(index(col))
+                    .map(|(row, col)| RegionCell::At([row, col])),
+                _ => None,
+            },
+            Content::Other => None,
+        };
+
+        cell.ok_or_else(|| {
+            Self::data("data did not match any variant of untagged enum RegionCell".to_string())
+        })
+    }
+
+    /// A value of any shape, keeping what a region cell could be made of.
+    fn content(&mut self) -> Parsed<Content> {
+        let value = match self.whitespace() {
+            None => return Err(self.peek_error("EOF while parsing a value")),
+            Some(b'n') => {
+                self.eat();
+                self.ident(b"ull")?;
+                Ok(Content::Other)
+            }
+            Some(b't') => {
+                self.eat();
+                self.ident(b"rue")?;
+                Ok(Content::Other)
+            }
+            Some(b'f') => {
+                self.eat();
+                self.ident(b"alse")?;
+                Ok(Content::Other)
+            }
+            Some(b'-') => {
+                self.eat();
+                self.integer(false)?;
+                Ok(Content::Other)
+            }
+            Some(b'0'..=b'9') => match self.integer(true)? {
+                Number::U64(n) => Ok(Content::Unsigned(n)),
+                _ => Ok(Content::Other),
+            },
+            Some(b'"') => {
+                self.eat();
+                self.string_body()?;
+                Ok(Content::Other)
+            }
+            Some(b'[') => {
+                self.enter()?;
+                let mut elements = vec![];
+                let mut first = true;
+                let value = loop {
+                    match self.has_next_element(&mut first) {
+                        Ok(true) => match self.content() {
+                            Ok(element) => elements.push(element),
+                            Err(e) => break Err(e),
+                        },
+                        Ok(false) => break Ok(Content::List(elements)),
+                        Err(e) => break Err(e),
+                    }
+                };
+                self.end_seq(value)
+            }
+            Some(b'{') => {
+                self.enter()?;
+                let mut first = true;
+                let value = loop {
+                    match self.has_next_key(&mut first) {
+                        Ok(true) => {
+                            let entry = self.key().and_then(|_| self.colon());
+                            if let Err(e) = entry.and_then(|()| self.content()) {
+                                break Err(e);
+                            }
+                        }
+                        Ok(false) => break Ok(Content::Other),
+                        Err(e) => break Err(e),
+                    }
+                };
+                self.end_map(value)
+            }
+            Some(_) => Err(self.peek_error("expected value")),
+        };
+
+        value.map_err(|e| self.fix(e))
+    }
+
+    fn rules(&mut self) -> Parsed<Rules> {
+        self.structure(
+            "struct Rules",
+            |reader| {
+                let mut rules = Rules::default();
+                let mut first = true;
+                for field in 0..RULES_FIELDS.len() {
+                    if !reader.has_next_element(&mut first)? {
+                        break;
+                    }
+                    reader.rules_field(&mut rules, field)?;
+                }
+                Ok(rules)
+            },
+            |reader| {
+                let mut rules = Rules::default();
+                let mut seen = [false; RULES_FIELDS.len()];
+                let mut first = true;
+                while reader.has_next_key(&mut first)? {
+                    let key = reader.key()?;
+                    let field = Self::lookup(&RULES_FIELDS, "field", &key)?;
+                    if std::mem::replace(&mut seen[field], true) {
+                        let message = format!("duplicate field `{}`", RULES_FIELDS[field]);
+                        return Err(Self::data(message));
+                    }
+                    reader.colon()?;
+                    reader.rules_field(&mut rules, field)?;
+                }
+                Ok(rules)
+            },
+        )
+    }
+
+    /// The value of the field numbered `field` in `RULES_FIELDS`.
+    fn rules_field(&mut self, rules: &mut Rules, field: usize) -> Parsed<()> {
+        match field {
+            0 => rules.colorless = self.boolean()?,
+            1 => rules.all_colors_required = self.boolean()?,
+            _ => {
+                rules.topology = [Topology::Square, Topology::Hex, Topology::HexLines]
+                    [self.variant(&TOPOLOGIES)?]
+            }
+        }
+
+        Ok(())
+    }
+
+    fn link(&mut self) -> Parsed<Link> {
+        self.structure(
+            "struct Link",
+            |reader| {
+                let mut first = true;
+                let missing = |len| {
+                    let message = format!(
+                        "invalid length {}, expected struct Link with 2 elements",
+                        len
+                    );
+                    Err(Self::data(message))
+                };
+                if !reader.has_next_element(&mut first)? {
+                    return missing(0);
+                }
+                let kind = reader.link_kind()?;
+                if !reader.has_next_element(&mut first)? {
+                    return missing(1);
+                }
+                let cells = reader.pair()?;
+                Ok(Link { kind, cells })
+            },
+            |reader| {
+                let (mut kind, mut cells) = (None, None);
+                let mut first = true;
+                while reader.has_next_key(&mut first)? {
+                    let key = reader.key()?;
+                    let field = Self::lookup(&LINK_FIELDS, "field", &key)?;
+                    let duplicate = match field {
+                        0 => kind.is_some(),
+                        _ => cells.is_some(),
+                    };
+                    if duplicate {
+                        let message = format!("duplicate field `{}`", LINK_FIELDS[field]);
+                        return Err(Self::data(message));
+                    }
+                    reader.colon()?;
+                    match field {
+                        0 => kind = Some(reader.link_kind()?),
+                        _ => cells = Some(reader.pair()?),
+                    }
+                }
+
+                match (kind, cells) {
+                    (Some(kind), Some(cells)) => Ok(Link { kind, cells }),
+                    (None, _) => Err(Self::data("missing field `kind`".to_string())),
+                    (_, None) => Err(Self::data("missing field `cells`".to_string())),
+                }
+            },
+        )
+    }
+
+    fn link_kind(&mut self) -> Parsed<LinkKind> {
+        Ok([LinkKind::Same, LinkKind::AtMostOne][self.variant(&LINK_KINDS)?])
+    }
+
+    /// The two cells of a link.
+    fn pair(&mut self) -> Parsed<[usize; 2]> {
+        self.seq("an array of length 2", |reader| {
+            let mut cells = [0; 2];
+            let mut first = true;
+            for (len, cell) in cells.iter_mut().enumerate() {
+                if !reader.has_next_element(&mut first)? {
+                    let message = format!("invalid length {}, expected an array of length 2", len);
+                    return Err(Self::data(message));
+                }
+                *cell = reader.index()?;
+            }
+            Ok(cells)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::utils::Rng;
+
+    /// Games using every field, with the values each may take.
+    const GAMES: &[&str] = &[
+        r#"{}"#,
+        r#"{"rows":4,"cols":4}"#,
+        r#" { "rows" : 2 , "cols":3, "colors":[0,1], "idxToColor":[0,0,1,1,0,1] } "#,
+        r#"{"regions":[[0,1],[[1,0],[1,1]]],"strict":true,"unknown":{"a":[1,"b",null,-2.5e3]}}"#,
+        r#"{"regions":null,"adjacency":null,"cellIds":null,"links":[]}"#,
+        r#"{"rules":{"colorless":true,"allColorsRequired":false,"topology":"hexLines"}}"#,
+        r#"{"rules":{"topology":"hex"},"adjacency":[[1],[0],[]]}"#,
+        r#"{"cellIds":["a","b\"c\\d\n\té😀","\/\b\f\r"]}"#,
+        r#"{"links":[{"kind":"same","cells":[0,1]},{"cells":[2,3],"kind":"atMostOne"}]}"#,
+        "{\"rows\":1,\n\"cols\":1\r\n}",
+        r#"[]"#,
+        r#"[4,4,[0],[0],null,[true],null,null,[["same",[0,1]]],true]"#,
+        r#"{"rules":{"topology":{"hex":null}},"links":[{"kind":{"same":null},"cells":[0,1]}]}"#,
+    ];
+
+    /// Broken games, each failing for a different reason.
+    const BROKEN: &[&str] = &[
+        "",
+        "null",
+        r#"{"rows":-1}"#,
+        r#"{"rows":1.5}"#,
+        r#"{"rows":1e3}"#,
+        r#"{"rows":01}"#,
+        r#"{"rows":18446744073709551616}"#,
+        r#"{"rows":"4"}"#,
+        r#"{"rows":true}"#,
+        r#"{"rows":nul}"#,
+        r#"{"rows":tru"#,
+        r#"{"rows":x}"#,
+        r#"{"rows":4,"rows":4}"#,
+        r#"{"rows":4,}"#,
+        r#"{"rows":4 "cols":4}"#,
+        r#"{"rows" 4}"#,
+        r#"{rows:4}"#,
+        r#"{"rows":4"#,
+        r#"{"rows":4}}"#,
+        r#"{"colors":[0,]}"#,
+        r#"{"colors":[0 1]}"#,
+        r#"{"colors":[0"#,
+        r#"{"colors":{}}"#,
+        r#"{"colors":[null]}"#,
+        r#"{"regions":[[[0]]]}"#,
+        r#"{"regions":[["a"]]}"#,
+        r#"{"rules":{"topology":"round"}}"#,
+        r#"{"rules":{"colorless":1}}"#,
+        r#"{"rules":{"strict":true}}"#,
+        r#"{"rules":{"topology":"hex","topology":"hex"}}"#,
+        r#"{"links":[{"kind":"same"}]}"#,
+        r#"{"links":[{"cells":[0,1]}]}"#,
+        r#"{"links":[{"kind":"same","cells":[0,1,2]}]}"#,
+        r#"{"links":[{"kind":"other","cells":[0,1]}]}"#,
+        r#"{"links":[{"kind":"same","cells":[0,1],"why":0}]}"#,
+        r#"{"cellIds":["a\x"]}"#,
+        r#"{"cellIds":["\ud83d"]}"#,
+        r#"{"cellIds":["\ud83dx"]}"#,
+        r#"{"cellIds":["\ude00"]}"#,
+        r#"{"cellIds":["\u12"]}"#,
+        "{\"cellIds\":[\"a\tb\"]}",
+        r#"{"cellIds":["a"#,
+        r#"{"strict":null}"#,
+        r#"{"unknown":[1,}"#,
+        "{\"rows\":4}\n x",
+        r#"{"rows":-0}"#,
+        r#"{"rows":1e-7}"#,
+        r#"{"rows":2.5e20}"#,
+        r#"{"rows":123456789012345678901234567890}"#,
+        r#"{"rows":1.}"#,
+        r#"{"rows":1e}"#,
+        r#"{"rows":1e400}"#,
+        r#"{"rows":-9223372036854775809}"#,
+        r#"{"unknown":[01]}"#,
+        r#"{"unknown":-}"#,
+        r#"{"regions":[[[0,1,2]]]}"#,
+        r#"{"regions":[[-1]]}"#,
+        r#"{"regions":[[{"a":1}]]}"#,
+        r#"[4,4,[0],[0],null,{},null,null,[],true,1]"#,
+        r#"{"rules":[true,false,"hex",1]}"#,
+        r#"{"rules":{"topology":{"hex":1}}}"#,
+        r#"{"rules":{"topology":{"hex":null,"a":1}}}"#,
+        r#"{"rules":{"topology":{}}}"#,
+        r#"{"rules":{"topology":1}}"#,
+        r#"{"links":[["same"]]}"#,
+        r#"{"links":[[]]}"#,
+        r#"{"links":[{"kind":"same","cells":[0]}]}"#,
+    ];
+
+    fn with_serde(json: &str) -> Result<Game, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// Both readers must agree on whether `json` is a game, on the game read, and on what is
+    /// wrong with it otherwise. Returns whether it was one.
+    fn agree(json: &str) -> bool {
+        match (read_game(json), with_serde(json)) {
+            (Ok(tiny), Ok(serde)) => {
+                assert_eq!(format!("{:?}", tiny), format!("{:?}", serde), "{}", json);
+                assert_eq!(write_game(&tiny), serde_json::to_string(&serde).unwrap());
+                true
+            }
+            (Err(tiny), Err(serde)) => {
+                assert_eq!(tiny, serde, "{}", json);
+                false
+            }
+            (tiny, serde) => panic!("{} gives {:?} and {:?}", json, tiny, serde),
+        }
+    }
+
+    #[test]
+    fn reads_games_like_serde() {
+        assert!(GAMES.iter().all(|json| agree(json)));
+        assert!(CORPUS.iter().all(|entry| agree(entry.json)));
+    }
+
+    #[test]
+    fn rejects_broken_games_like_serde() {
+        assert!(BROKEN.iter().all(|json| !agree(json)));
+    }
+
+    /// serde_json gives up past 128 nested lists and objects where it keeps what it reads, but
+    /// not where it skips it.
+    #[test]
+    fn limits_nesting_like_serde() {
+        for depth in [125, 126, 127, 200] {
+            let deep = format!("{}0{}", "[".repeat(depth), "]".repeat(depth));
+            assert!(!agree(&format!(r#"{{"regions":[{}]}}"#, deep)));
+            assert!(agree(&format!(r#"{{"unknown":{}}}"#, deep)));
+        }
+    }
+
+    /// Random edits of valid games, mostly breaking them, with characters that matter to JSON.
+    #[test]
+    fn agrees_with_serde_on_edited_games() {
+        const CHARS: &[char] = &[
+            '{', '}', '[', ']', ',', ':', '"', '\\', '-', '.', 'e', '0', '7', ' ', '\n', 'n', 'u',
+            'l', 't',
+        ];
+
+        let mut rng = Rng::new(166);
+        let sources: Vec<_> = GAMES
+            .iter()
+            .copied()
+            .chain(CORPUS.iter().map(|entry| entry.json))
+            .collect();
+        let mut valid = 0;
+        for _ in 0..20_000 {
+            let mut json: Vec<char> = sources[rng.below(sources.len())].chars().collect();
+            for _ in 0..1 + rng.below(3) {
+                let pos = rng.below(json.len() + 1);
+                match rng.below(3) {
+                    0 if pos < json.len() => drop(json.remove(pos)),
+                    1 if pos < json.len() => json[pos] = CHARS[rng.below(CHARS.len())],
+                    _ => json.insert(pos, CHARS[rng.below(CHARS.len())]),
+                }
+            }
+
+            valid += agree(&json.into_iter().collect::<String>()) as usize;
+        }
+        // Some edits keep the game valid, such as those to whitespace and unknown fields.
+        assert!(valid > 0);
+    }
+
+    #[test]
+    fn writes_solutions_like_serde() {
+        for solution in [vec![], vec![0], vec![3, 14, 159, 2653]] {
+            assert_eq!(
+                write_solution(&solution),
+                serde_json::to_string(&solution).unwrap()
+            );
+        }
+    }
+}
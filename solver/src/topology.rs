@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+/// The shape of a board's cells, from the rules' optional `topology`, which decides what a queen
+/// threatens beyond its row and column.
+///
+/// Hex boards are rhombuses in axial coordinates, stored row by row like square ones: the cell
+/// at `row` and `col` is the hexagon with `r = row` and `q = col`. Rows and columns are then two
+/// of the three directions of lines through the grid, and hold one queen each as usual.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum Topology {
+    /// Square cells, whose queens threaten their diagonal neighbors.
+    Square,
+    /// Hexagons, whose queens threaten their six neighbors. All but two of those share the
+    /// queen's row or column.
+    Hex,
+    /// Hexagons, whose queens also threaten every cell on their line of the third direction,
+    /// where `q + r` is constant. Those lines outnumber the queens, so they hold at most one
+    /// each, and the six neighbors all lie on one of the queen's three lines.
+    HexLines,
+}
+
+impl Topology {
+    /// The cells a queen on `idx` threatens besides those in its row and column, on a `rows` by
+    /// `cols` board.
+    pub fn neighbors(self, rows: usize, cols: usize, idx: usize) -> Vec<usize> {
+        let (row, col) = ((idx / cols) as i32, (idx % cols) as i32);
+
+        let offsets = match self {
+            Topology::Square => vec![(-1, -1), (-1, 1), (1, -1), (1, 1)],
+            Topology::Hex => vec![(-1, 1), (1, -1)],
+            Topology::HexLines => (1..rows as i32)
+                .flat_map(|step| vec![(-step, step), (step, -step)])
+                .collect(),
+        };
+
+        offsets
+            .iter()
+            .map(|&(dr, dc)| (row + dr, col + dc))
+            .filter(|&(new_row, new_col)| {
+                new_row >= 0 && new_row < (rows as i32) && new_col >= 0 && new_col < (cols as i32)
+            })
+            .map(|(new_row, new_col)| (new_row as usize) * cols + (new_col as usize))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Options;
+    use crate::strategy::Status;
+    use crate::verify::verify_solution;
+    use crate::{enumerate_solutions, run_counter, run_solver, Game};
+
+    /// A hex puzzle with one solution, [4, 11, 12, 19, 26, 33], whose queens on neighboring rows
+    /// touch at the corners of square cells but are not neighboring hexagons.
+    const HEX: &str = r#"{"rows":6,"cols":6,"colors":[0,1,2,3,4,5],"idxToColor":[
+        2,2,2,4,0,1, 2,2,4,4,4,1, 2,2,4,4,4,4, 3,3,4,4,4,5, 4,4,4,4,5,5, 4,4,4,5,5,5],
+        "rules":{"topology":"hex"}}"#;
+
+    fn sorted(mut cells: Vec<usize>) -> Vec<usize> {
+        cells.sort_unstable();
+        cells
+    }
+
+    /// The axial distance between two cells of a hex board `cols` wide.
+    fn hex_distance(cols: usize, a: usize, b: usize) -> i32 {
+        let (r, q) = (
+            (a / cols) as i32 - (b / cols) as i32,
+            (a % cols) as i32 - (b % cols) as i32,
+        );
+        (r.abs() + q.abs() + (r + q).abs()) / 2
+    }
+
+    /// With the row and column neighbors added back, every hexagon has the six neighbors at
+    /// distance one that fit on the board, and those of `HexLines` add the rest of the third line.
+    #[test]
+    fn lists_the_neighbors_of_each_hexagon() {
+        let (rows, cols) = (4, 5);
+        for idx in 0..rows * cols {
+            let mut neighbors = Topology::Hex.neighbors(rows, cols, idx);
+            let (row, col) = (idx / cols, idx % cols);
+            neighbors.extend((0..rows * cols).filter(|&other| {
+                other != idx
+                    && (other / cols == row || other % cols == col)
+                    && hex_distance(cols, idx, other) == 1
+            }));
+            let expected: Vec<_> = (0..rows * cols)
+                .filter(|&other| hex_distance(cols, idx, other) == 1)
+                .collect();
+            assert_eq!(sorted(neighbors), expected, "cell {}", idx);
+
+            let line: Vec<_> = (0..rows * cols)
+                .filter(|&other| other != idx && other / cols + other % cols == row + col)
+                .collect();
+            let lines = sorted(Topology::HexLines.neighbors(rows, cols, idx));
+            assert_eq!(lines, line, "cell {}", idx);
+        }
+
+        // Only the two neighbors off the middle cell's row and column are listed.
+        assert_eq!(sorted(Topology::Hex.neighbors(3, 3, 4)), [2, 6]);
+        assert_eq!(sorted(Topology::Square.neighbors(3, 3, 4)), [0, 2, 6, 8]);
+    }
+
+    /// Every way of solving the fixture finds the one placement that brute force over the
+    /// permutations, checking hexagon distances directly, allows.
+    #[test]
+    fn solves_a_known_hex_puzzle() {
+        let game = Game::from_json(HEX).unwrap();
+        let mut brute = vec![];
+        let mut cols: Vec<usize> = (0..6).collect();
+        permutations(&mut cols, 0, &mut |cols| {
+            let cells: Vec<usize> = cols
+                .iter()
+                .enumerate()
+                .map(|(row, col)| row * 6 + col)
+                .collect();
+            let colors = cells.iter().map(|&idx| game.idx_to_color[idx]);
+            let apart = cells
+                .iter()
+                .all(|&a| cells.iter().all(|&b| a == b || hex_distance(6, a, b) > 1));
+            if apart && sorted(colors.collect()) == [0, 1, 2, 3, 4, 5] {
+                brute.push(cells);
+            }
+        });
+        assert_eq!(brute, [vec![4, 11, 12, 19, 26, 33]]);
+
+        assert_eq!(enumerate_solutions(&game, &[], &[], u64::MAX), brute);
+        assert_eq!(run_counter(&game, false), 1);
+        for options in [
+            Options::default(),
+            Options::for_deduction(),
+            Options::for_solve(),
+        ] {
+            let outcome = run_solver(&game, &options);
+            assert_eq!(outcome.status, Status::Solved);
+            assert_eq!(sorted(outcome.solution), brute[0]);
+        }
+        assert!(verify_solution(&game, &brute[0]).is_empty());
+
+        // On square cells the first two queens touch diagonally.
+        let mut square = game.clone();
+        square.rules.topology = Topology::Square;
+        assert!(!verify_solution(&square, &brute[0]).is_empty());
+        assert!(!enumerate_solutions(&square, &[], &[], u64::MAX).contains(&brute[0]));
+    }
+
+    fn permutations(items: &mut Vec<usize>, start: usize, visit: &mut impl FnMut(&[usize])) {
+        if start == items.len() {
+            visit(items);
+            return;
+        }
+        for i in start..items.len() {
+            items.swap(start, i);
+            permutations(items, start + 1, visit);
+            items.swap(start, i);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+// Debug logging of the search through `tracing`, with the `trace` feature. Without it the
+// macros below expand to nothing, so neither the calls nor their arguments are compiled.
+
+/// Emits a `tracing` event at debug level, taking the arguments of `tracing::debug!`.
+#[cfg(feature = "trace")]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        tracing::debug!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {};
+}
+
+/// Binds `$guard` to a `tracing` span at debug level entered until the guard drops, taking the
+/// arguments of `tracing::debug_span!` after it.
+#[cfg(feature = "trace")]
+macro_rules! trace_span {
+    ($guard:ident, $($arg:tt)*) => {
+        let $guard = tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! trace_span {
+    ($guard:ident, $($arg:tt)*) => {};
+}
+
+#[cfg(feature = "trace")]
+pub(crate) use subscriber::init_tracing;
+
+#[cfg(feature = "trace")]
+mod subscriber {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::fmt::{Debug, Write};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen::prelude::*;
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = console, js_name = debug)]
+        fn console_debug(message: &str);
+    }
+
+    thread_local! {
+        /// The spans entered on this thread, innermost last.
+        static ENTERED: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Writes every debug event as a line prefixed by the spans it happened in, to
+    /// `console.debug` in the browser and stderr natively.
+    struct LineSubscriber {
+        next_id: AtomicU64,
+        /// The name and fields of every open span.
+        spans: Mutex<HashMap<u64, String>>,
+    }
+
+    /// Appends the fields visited to a line, the message bare and the rest as `name=value`.
+    struct FieldWriter<'a>(&'a mut String);
+
+    impl Visit for FieldWriter<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                let _ = write!(self.0, " {:?}", value);
+            } else {
+                let _ = write!(self.0, " {}={:?}", field.name(), value);
+            }
+        }
+    }
+
+    impl Subscriber for LineSubscriber {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            *metadata.level() <= Level::DEBUG
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            let mut line = span.metadata().name().to_string();
+            span.record(&mut FieldWriter(&mut line));
+            self.spans.lock().unwrap().insert(id, line);
+
+            Id::from_u64(id)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            if let Some(line) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+                values.record(&mut FieldWriter(line));
+            }
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let spans = self.spans.lock().unwrap();
+            let mut line = ENTERED.with(|entered| {
+                let names: Vec<_> = entered
+                    .borrow()
+                    .iter()
+                    .filter_map(|id| spans.get(id).map(String::as_str))
+                    .collect();
+                names.join(": ")
+            });
+            drop(spans);
+
+            if !line.is_empty() {
+                line.push(':');
+            }
+            event.record(&mut FieldWriter(&mut line));
+
+            #[cfg(target_arch = "wasm32")]
+            console_debug(line.trim_start());
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("{}", line.trim_start());
+        }
+
+        fn enter(&self, span: &Id) {
+            ENTERED.with(|entered| entered.borrow_mut().push(span.into_u64()));
+        }
+
+        fn exit(&self, _span: &Id) {
+            ENTERED.with(|entered| entered.borrow_mut().pop());
+        }
+
+        fn try_close(&self, span: Id) -> bool {
+            self.spans.lock().unwrap().remove(&span.into_u64());
+            true
+        }
+    }
+
+    /// Sends the debug logs of the search to the console from now on. Only the first call
+    /// installs the subscriber; later ones do nothing.
+    pub(crate) fn init_tracing() {
+        let subscriber = LineSubscriber {
+            // Span ids must not be 0.
+            next_id: AtomicU64::new(1),
+            spans: Mutex::new(HashMap::new()),
+        };
+
+        let _ = tracing::subscriber::set_global_default(subscriber);
+    }
+}
@@ -0,0 +1,308 @@
+/// The position of a node in the arena of a `Trie`.
+pub(crate) type NodeId = u32;
+
+/// A node of a `Trie`. Its children are a range of the trie's edges, sorted by index.
+#[derive(Clone, Copy, Default)]
+struct Node {
+    /// Where the node's range of edges starts, how many of them are children, and how many the
+    /// range has room for, always zero or a power of two.
+    start: u32,
+    len: u32,
+    cap: u32,
+    is_leaf: bool,
+}
+
+/// A trie of sorted cell indices kept in two flat buffers: the nodes, and the edges from each
+/// node to its children, which take up one range per node.
+///
+/// Nodes and ranges that fall out of use are kept on free lists for reuse instead of being
+/// handed back, so the trie makes no allocation of its own once it has grown, and is dropped by
+/// freeing the two buffers. A full range moves to a free range twice its size.
+pub(crate) struct Trie {
+    nodes: Vec<Node>,
+    /// Every child as its index and node, fan-outs being small enough that binary search within
+    /// a range beats hashing.
+    edges: Vec<(u32, NodeId)>,
+    free_nodes: Vec<NodeId>,
+    /// The unused ranges of every capacity, by the capacity's power of two.
+    free_ranges: Vec<Vec<u32>>,
+    /// The nodes left to drop by `mark_leaf`.
+    stack: Vec<NodeId>,
+}
+
+impl Trie {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+            edges: vec![],
+            free_nodes: vec![],
+            free_ranges: vec![],
+            stack: vec![],
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_leaf(&self, node: NodeId) -> bool {
+        self.nodes[node as usize].is_leaf
+    }
+
+    #[inline(always)]
+    fn children(&self, node: NodeId) -> &[(u32, NodeId)] {
+        let Node { start, len, .. } = self.nodes[node as usize];
+        &self.edges[start as usize..(start + len) as usize]
+    }
+
+    #[inline(always)]
+    pub fn child(&self, node: NodeId, idx: usize) -> Option<NodeId> {
+        let children = self.children(node);
+        children
+            .binary_search_by_key(&(idx as u32), |&(i, _)| i)
+            .ok()
+            .map(|pos| children[pos].1)
+    }
+
+    #[inline(always)]
+    pub fn child_or_insert(&mut self, node: NodeId, idx: usize) -> NodeId {
+        let pos = match self
+            .children(node)
+            .binary_search_by_key(&(idx as u32), |&(i, _)| i)
+        {
+            Ok(pos) => return self.children(node)[pos].1,
+            Err(pos) => pos,
+        };
+
+        let child = self.alloc_node();
+        let Node { len, cap, .. } = self.nodes[node as usize];
+        if len == cap {
+            self.grow(node);
+        }
+
+        let start = self.nodes[node as usize].start as usize;
+        let end = start + len as usize;
+        self.edges.copy_within(start + pos..end, start + pos + 1);
+        self.edges[start + pos] = (idx as u32, child);
+        self.nodes[node as usize].len += 1;
+
+        child
+    }
+
+    /// Makes `node` a leaf, dropping everything below it. Returns how many leaves were below it,
+    /// counting the node itself if it already was one.
+    pub fn mark_leaf(&mut self, node: NodeId) -> usize {
+        let mut leaves = self.is_leaf(node) as usize;
+        let mut stack = std::mem::take(&mut self.stack);
+        stack.extend(self.children(node).iter().map(|&(_, child)| child));
+
+        while let Some(next) = stack.pop() {
+            leaves += self.is_leaf(next) as usize;
+            stack.extend(self.children(next).iter().map(|&(_, child)| child));
+            self.free_node(next);
+        }
+        self.stack = stack;
+
+        let node = &mut self.nodes[node as usize];
+        node.len = 0;
+        node.is_leaf = true;
+        leaves
+    }
+
+    /// Unmarks the leaf reached by `sorted_solution`, dropping nodes left without a purpose.
+    /// Returns whether it was a leaf.
+    pub fn remove(&mut self, sorted_solution: &[usize]) -> bool {
+        self.remove_below(Self::ROOT, sorted_solution)
+    }
+
+    fn remove_below(&mut self, node: NodeId, sorted_solution: &[usize]) -> bool {
+        let Some((&idx, rest)) = sorted_solution.split_first() else {
+            return std::mem::replace(&mut self.nodes[node as usize].is_leaf, false);
+        };
+
+        let Ok(pos) = self
+            .children(node)
+            .binary_search_by_key(&(idx as u32), |&(i, _)| i)
+        else {
+            return false;
+        };
+
+        let child = self.children(node)[pos].1;
+        let removed = self.remove_below(child, rest);
+
+        let Node { is_leaf, len, .. } = self.nodes[child as usize];
+        if !is_leaf && len == 0 {
+            let Node { start, len, .. } = self.nodes[node as usize];
+            let (start, end) = (start as usize, (start + len) as usize);
+            self.edges.copy_within(start + pos + 1..end, start + pos);
+            self.nodes[node as usize].len -= 1;
+            self.free_node(child);
+        }
+
+        removed
+    }
+
+    /// The nodes in use, and the bytes taken up by the buffers and free lists.
+    pub fn footprint(&self) -> (usize, usize) {
+        let free_ranges: usize = self.free_ranges.iter().map(Vec::capacity).sum();
+        let bytes = self.nodes.capacity() * std::mem::size_of::<Node>()
+            + self.edges.capacity() * std::mem::size_of::<(u32, NodeId)>()
+            + (self.free_nodes.capacity() + self.stack.capacity()) * std::mem::size_of::<NodeId>()
+            + self.free_ranges.capacity() * std::mem::size_of::<Vec<u32>>()
+            + free_ranges * std::mem::size_of::<u32>();
+
+        (self.nodes.len() - self.free_nodes.len(), bytes)
+    }
+
+    fn alloc_node(&mut self) -> NodeId {
+        match self.free_nodes.pop() {
+            Some(node) => node,
+            None => {
+                self.nodes.push(Node::default());
+                (self.nodes.len() - 1) as NodeId
+            }
+        }
+    }
+
+    /// Hands `node` and its range back to the free lists. Its children must be freed apart.
+    fn free_node(&mut self, node: NodeId) {
+        let Node { start, cap, .. } = std::mem::take(&mut self.nodes[node as usize]);
+        if cap > 0 {
+            self.free_ranges[cap.trailing_zeros() as usize].push(start);
+        }
+        self.free_nodes.push(node);
+    }
+
+    /// Moves the full range of `node` to one twice its size.
+    fn grow(&mut self, node: NodeId) {
+        let Node {
+            start, len, cap, ..
+        } = self.nodes[node as usize];
+        let new_cap = (cap * 2).max(1);
+        let class = new_cap.trailing_zeros() as usize;
+
+        if self.free_ranges.len() <= class {
+            self.free_ranges.resize(class + 1, vec![]);
+        }
+        let new_start = match self.free_ranges[class].pop() {
+            Some(new_start) => new_start,
+            None => {
+                let new_start = self.edges.len() as u32;
+                self.edges
+                    .resize(self.edges.len() + new_cap as usize, (0, 0));
+                new_start
+            }
+        };
+
+        self.edges
+            .copy_within(start as usize..(start + len) as usize, new_start as usize);
+        if cap > 0 {
+            self.free_ranges[cap.trailing_zeros() as usize].push(start);
+        }
+
+        let node = &mut self.nodes[node as usize];
+        node.start = new_start;
+        node.cap = new_cap;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Rng;
+    use std::collections::BTreeSet;
+
+    /// A random nogood: up to five distinct indices below 12, sorted, so that many share prefixes.
+    fn random_set(rng: &mut Rng) -> Vec<usize> {
+        let len = 1 + rng.below(5);
+        let mut set: Vec<_> = (0..len).map(|_| rng.below(12)).collect();
+        set.sort_unstable();
+        set.dedup();
+        set
+    }
+
+    fn insert(trie: &mut Trie, set: &[usize]) -> usize {
+        let node = set
+            .iter()
+            .fold(Trie::ROOT, |node, &idx| trie.child_or_insert(node, idx));
+        trie.mark_leaf(node)
+    }
+
+    fn contains(trie: &Trie, set: &[usize]) -> bool {
+        set.iter()
+            .try_fold(Trie::ROOT, |node, &idx| trie.child(node, idx))
+            .is_some_and(|node| trie.is_leaf(node))
+    }
+
+    /// Inserting a set drops the sets it is a prefix of, as `mark_leaf` drops the nodes below.
+    #[test]
+    fn matches_a_reference_set() {
+        let mut rng = Rng::new(165);
+        let mut trie = Trie::new();
+        let mut reference = BTreeSet::new();
+
+        for round in 0..5000 {
+            let set = random_set(&mut rng);
+
+            if round % 3 == 2 {
+                assert_eq!(
+                    trie.remove(&set),
+                    reference.remove(&set),
+                    "removing {:?}",
+                    set
+                );
+            } else {
+                let below: Vec<_> = reference
+                    .iter()
+                    .filter(|stored: &&Vec<usize>| stored.starts_with(&set))
+                    .cloned()
+                    .collect();
+                for stored in &below {
+                    reference.remove(stored);
+                }
+                reference.insert(set.clone());
+                assert_eq!(insert(&mut trie, &set), below.len(), "inserting {:?}", set);
+            }
+
+            let probe = random_set(&mut rng);
+            assert_eq!(
+                contains(&trie, &probe),
+                reference.contains(&probe),
+                "{:?}",
+                probe
+            );
+        }
+
+        for set in &reference {
+            assert!(contains(&trie, set), "{:?}", set);
+        }
+
+        // No node outlives the sets it leads to: the nodes are the root and every prefix.
+        let prefixes: BTreeSet<_> = reference
+            .iter()
+            .flat_map(|set| (1..=set.len()).map(move |len| &set[..len]))
+            .collect();
+        assert_eq!(trie.footprint().0, prefixes.len() + 1);
+    }
+
+    #[test]
+    fn reuses_freed_nodes() {
+        let mut trie = Trie::new();
+        let wide: Vec<_> = (0..10).map(|idx| vec![0, idx + 1, idx + 20]).collect();
+        let mut footprints = vec![];
+
+        // Once the free lists have grown, dropping everything and growing it back takes no new
+        // memory.
+        for _ in 0..3 {
+            for set in &wide {
+                insert(&mut trie, set);
+            }
+            footprints.push(trie.footprint());
+            assert_eq!(insert(&mut trie, &[0]), 10);
+            assert_eq!(trie.footprint().0, 2);
+            assert!(trie.remove(&[0]));
+            assert_eq!(trie.footprint().0, 1);
+        }
+        assert_eq!(footprints[0].0, 22);
+        assert_eq!(footprints[1], footprints[2]);
+    }
+}
@@ -8,3 +8,128 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(message: &str);
+
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// Reports a non-fatal problem with the input, on the console in the browser and on stderr
+/// natively.
+pub fn warn(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    console_warn(message);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", message);
+}
+
+/// Milliseconds since some fixed point in time, for measuring how long things take.
+pub fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        performance_now()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::sync::OnceLock;
+        use std::time::Instant;
+
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+/// A hash of `value` in hex, since JSON numbers would round it. FNV-1a, whose output is fixed
+/// once and for all, unlike the standard library's hasher, so hashes match across builds.
+pub fn hash_hex(value: &str) -> String {
+    let hash = value.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
+/// A small, fast pseudorandom generator (SplitMix64), so runs are reproducible from a seed on
+/// every platform.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..n`, for `n` well below 2^32, with negligible bias.
+    pub fn below(&mut self, n: usize) -> usize {
+        (((self.next_u64() >> 32) * n as u64) >> 32) as usize
+    }
+
+    /// A value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// The allocator of the `small-alloc` feature: lol_alloc's free list allocator, locked when
+/// wasm-threads builds share memory between threads.
+///
+/// Its methods are kept out of line. Inlined into every allocation of the crate, the free list
+/// would take up more of the binary than the default allocator it replaces.
+#[cfg(all(feature = "small-alloc", target_arch = "wasm32"))]
+pub struct SmallAlloc {
+    #[cfg(not(target_feature = "atomics"))]
+    inner: lol_alloc::AssumeSingleThreaded<lol_alloc::FreeListAllocator>,
+    #[cfg(target_feature = "atomics")]
+    inner: lol_alloc::LockedAllocator<lol_alloc::FreeListAllocator>,
+}
+
+#[cfg(all(feature = "small-alloc", target_arch = "wasm32"))]
+impl SmallAlloc {
+    pub const fn new() -> Self {
+        Self {
+            // SAFETY: without atomics, wasm runs the module on a single thread.
+            #[cfg(not(target_feature = "atomics"))]
+            inner: unsafe {
+                lol_alloc::AssumeSingleThreaded::new(lol_alloc::FreeListAllocator::new())
+            },
+            #[cfg(target_feature = "atomics")]
+            inner: lol_alloc::LockedAllocator::new(lol_alloc::FreeListAllocator::new()),
+        }
+    }
+}
+
+#[cfg(all(feature = "small-alloc", target_arch = "wasm32"))]
+unsafe impl std::alloc::GlobalAlloc for SmallAlloc {
+    #[inline(never)]
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        self.inner.alloc(layout)
+    }
+
+    #[inline(never)]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+
+    #[inline(never)]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
@@ -0,0 +1,335 @@
+use crate::difficulty::assess_difficulty;
+use crate::isomorphism::Form;
+use crate::links::Link;
+use crate::recolor::{judge, Recolor, Verdict};
+use crate::regions::disconnected_colors;
+use crate::symmetry::Transform;
+use crate::utils::Rng;
+use crate::{enumerate_solutions, Game};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// The most cells moved to another color in one variant, few enough to keep the look of the
+/// original.
+const MAX_RECOLORS: usize = 3;
+
+/// How many boards `derive_variants` tries for every variant asked for before settling for
+/// fewer.
+const ATTEMPTS_PER_VARIANT: usize = 40;
+
+/// One of the operations producing a variant, in the order they were applied.
+#[derive(Serialize, Debug)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub(crate) enum Operation {
+    /// A cell of the original board moved to the color of one of its neighbors.
+    Recolor(Recolor),
+    /// The board carried through a rotation or reflection.
+    Transform { transform: Transform },
+    /// The colors renamed, `colors` holding the new name of every color of the original.
+    Relabel { colors: Vec<usize> },
+}
+
+/// A board derived from another by `derive_variants`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Variant {
+    pub game: Game,
+    /// The cells of its only solution, sorted.
+    pub solution: Vec<usize>,
+    pub operations: Vec<Operation>,
+}
+
+/// Up to `count` boards like `game` for practice, reproducible from `seed`.
+///
+/// Each moves one to `MAX_RECOLORS` cells on the borders between colors to a neighboring color,
+/// keeping every color in one piece if it was, and is kept only if it still has the original's
+/// only solution and takes the same hardest technique to solve. It is then rotated or reflected
+/// and its colors renamed, which change how it looks but not how it plays. No two variants, and
+/// no variant and the original, are isomorphic. Fewer come back when the recolorings that keep
+/// all that run out.
+pub(crate) fn derive_variants(
+    game: &Game,
+    count: usize,
+    seed: u64,
+) -> Result<Vec<Variant>, String> {
+    if !game.has_color_rule() {
+        return Err("Colorless boards have no colors to vary".to_string());
+    }
+
+    let mut solutions = enumerate_solutions(game, &[], &[], 2);
+    if solutions.len() != 1 {
+        return Err("Only boards with exactly one solution have variants".to_string());
+    }
+    let solution = solutions.pop().unwrap();
+    let hardest = assess_difficulty(game)?.usage.hardest;
+    let contiguous = disconnected_colors(game).is_empty();
+
+    let mut rng = Rng::new(seed);
+    let mut seen = HashSet::new();
+    seen.insert(Form::canonical(game));
+    let mut variants = vec![];
+
+    for _ in 0..count.saturating_mul(ATTEMPTS_PER_VARIANT) {
+        if variants.len() == count {
+            break;
+        }
+
+        let mut mutated = game.clone();
+        let recolors = recolor_borders(&mut mutated, &solution, &mut rng);
+        if recolors.is_empty() {
+            continue;
+        }
+
+        let unchanged = match judge(&mutated, contiguous) {
+            Verdict::Unique {
+                solution: mutated_solution,
+            } => mutated_solution == solution,
+            _ => false,
+        };
+        let form = Form::canonical(&mutated);
+        if !unchanged || seen.contains(&form) {
+            continue;
+        }
+
+        let fitting: Vec<_> = Transform::ALL
+            .iter()
+            .copied()
+            .filter(|transform| transform.fits(&mutated))
+            .collect();
+        let transform = fitting[rng.below(fitting.len())];
+        let mut colors: Vec<_> = (0..mutated.colors.len()).collect();
+        for pos in (1..colors.len()).rev() {
+            colors.swap(pos, rng.below(pos + 1));
+        }
+
+        // The order deductions come in, and with it the hardest technique they reach for, changes
+        // with the orientation, so the difficulty is judged on the board as it will be played.
+        let variant = image(&mutated, transform, &colors);
+        if assess_difficulty(&variant)?.usage.hardest != hardest {
+            continue;
+        }
+        seen.insert(form);
+
+        let mut solution: Vec<_> = solution
+            .iter()
+            .map(|&idx| transform.apply(&mutated, idx))
+            .collect();
+        solution.sort_unstable();
+
+        let mut operations: Vec<_> = recolors.into_iter().map(Operation::Recolor).collect();
+        operations.push(Operation::Transform { transform });
+        operations.push(Operation::Relabel {
+            colors: colors.clone(),
+        });
+
+        variants.push(Variant {
+            game: variant,
+            solution,
+            operations,
+        });
+    }
+
+    Ok(variants)
+}
+
+/// Moves up to `MAX_RECOLORS` cells outside `solution`, each bordering a cell of another color,
+/// to the color of that neighbor. Returns the moves made, in order.
+fn recolor_borders(game: &mut Game, solution: &[usize], rng: &mut Rng) -> Vec<Recolor> {
+    let want = 1 + rng.below(MAX_RECOLORS);
+    let mut recolors = vec![];
+
+    for _ in 0..want {
+        let borders: Vec<_> = (0..game.rows * game.cols)
+            .filter(|idx| !solution.contains(idx))
+            .flat_map(|idx| {
+                let game = &*game;
+                orthogonal_neighbors(game, idx)
+                    .filter(move |&neighbor| game.idx_to_color[neighbor] != game.idx_to_color[idx])
+                    .map(move |neighbor| Recolor {
+                        idx,
+                        color: game.idx_to_color[neighbor],
+                    })
+            })
+            .collect();
+        if borders.is_empty() {
+            break;
+        }
+
+        let recolor = borders[rng.below(borders.len())];
+        game.idx_to_color[recolor.idx] = recolor.color;
+        recolors.push(recolor);
+    }
+
+    recolors
+}
+
+/// The cells sharing an edge with `idx` in the grid.
+fn orthogonal_neighbors(game: &Game, idx: usize) -> impl Iterator<Item = usize> {
+    let (rows, cols) = (game.rows, game.cols);
+    let (row, col) = (idx / cols, idx % cols);
+
+    IntoIterator::into_iter([
+        (row > 0).then(|| idx - cols),
+        (row + 1 < rows).then(|| idx + cols),
+        (col > 0).then(|| idx - 1),
+        (col + 1 < cols).then(|| idx + 1),
+    ])
+    .flatten()
+}
+
+/// The board `transform` carries `game` onto, which it must fit, with every color `color` renamed
+/// `colors[color]`. Cell ids are left out, since they name the cells of the original.
+fn image(game: &Game, transform: Transform, colors: &[usize]) -> Game {
+    let cells = game.rows * game.cols;
+    let carry = |idx: usize| transform.apply(game, idx);
+
+    let mut idx_to_color = vec![0; cells];
+    for idx in 0..cells {
+        idx_to_color[carry(idx)] = colors[game.idx_to_color[idx]];
+    }
+
+    let adjacency = game.adjacency.as_ref().map(|adjacency| {
+        let mut image = vec![vec![]; cells];
+        for (idx, neighbors) in adjacency.iter().enumerate() {
+            image[carry(idx)] = neighbors.iter().map(|&neighbor| carry(neighbor)).collect();
+        }
+        image
+    });
+
+    Game {
+        rows: game.rows,
+        cols: game.cols,
+        colors: game.colors.clone(),
+        idx_to_color,
+        regions: None,
+        rules: game.rules.clone(),
+        adjacency,
+        cell_ids: None,
+        links: game
+            .links
+            .iter()
+            .map(|link| Link {
+                kind: link.kind,
+                cells: link.cells.map(carry),
+            })
+            .collect(),
+        strict: game.strict,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+
+    /// Replays the operations of `variant` on `game`.
+    fn replay(game: &Game, variant: &Variant) -> Game {
+        let mut mutated = game.clone();
+        let mut transform = None;
+        let mut colors = None;
+        for operation in &variant.operations {
+            match operation {
+                Operation::Recolor(recolor) => {
+                    mutated.idx_to_color[recolor.idx] = recolor.color;
+                }
+                Operation::Transform { transform: t } => transform = Some(*t),
+                Operation::Relabel { colors: c } => colors = Some(c.clone()),
+            }
+        }
+
+        image(&mutated, transform.unwrap(), &colors.unwrap())
+    }
+
+    #[test]
+    fn keeps_the_solution_and_difficulty_of_corpus_boards() {
+        for entry in CORPUS.iter().filter(|entry| !entry.solution.is_empty()) {
+            let game = Game::from_json(entry.json).unwrap();
+            let hardest = assess_difficulty(&game).unwrap().usage.hardest;
+            let variants = derive_variants(&game, 3, 7).unwrap();
+            assert_eq!(variants.len(), 3, "{}", entry.name);
+
+            let mut forms = vec![Form::canonical(&game)];
+            for variant in &variants {
+                let recolors = variant
+                    .operations
+                    .iter()
+                    .filter_map(|operation| match operation {
+                        Operation::Recolor(recolor) => Some(recolor.idx),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                assert!(
+                    (1..=MAX_RECOLORS).contains(&recolors.len()),
+                    "{}",
+                    entry.name
+                );
+                assert!(
+                    recolors.iter().all(|idx| !entry.solution.contains(idx)),
+                    "{}",
+                    entry.name
+                );
+
+                assert_eq!(
+                    serde_json::to_value(replay(&game, variant)).unwrap(),
+                    serde_json::to_value(&variant.game).unwrap(),
+                    "{}",
+                    entry.name
+                );
+                assert_eq!(
+                    enumerate_solutions(&variant.game, &[], &[], 2),
+                    std::slice::from_ref(&variant.solution),
+                    "{}",
+                    entry.name
+                );
+                assert!(
+                    disconnected_colors(&variant.game).is_empty(),
+                    "{}",
+                    entry.name
+                );
+                assert_eq!(
+                    assess_difficulty(&variant.game).unwrap().usage.hardest,
+                    hardest,
+                    "{}",
+                    entry.name
+                );
+
+                let form = Form::canonical(&variant.game);
+                assert!(!forms.contains(&form), "{}", entry.name);
+                forms.push(form);
+            }
+        }
+    }
+
+    #[test]
+    fn reproduces_variants_from_the_seed() {
+        let game = Game::from_json(CORPUS[1].json).unwrap();
+        let derive =
+            |seed| serde_json::to_string(&derive_variants(&game, 2, seed).unwrap()).unwrap();
+
+        assert_eq!(derive(3), derive(3));
+        assert_ne!(derive(3), derive(4));
+        assert!(derive_variants(&game, 0, 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_boards_without_exactly_one_solution() {
+        let colorless = Game::from_json(r#"{"rows":6,"cols":6,"rules":{"colorless":true}}"#);
+        assert_eq!(
+            derive_variants(&colorless.unwrap(), 1, 0).err(),
+            Some("Colorless boards have no colors to vary".to_string())
+        );
+
+        let mut several = Game::from_json(CORPUS[0].json).unwrap();
+        several.idx_to_color[33] = 1;
+        let unsolvable = CORPUS
+            .iter()
+            .find(|entry| entry.solution.is_empty())
+            .unwrap();
+        for game in [several, Game::from_json(unsolvable.json).unwrap()] {
+            assert_eq!(
+                derive_variants(&game, 1, 0).err(),
+                Some("Only boards with exactly one solution have variants".to_string())
+            );
+        }
+    }
+}
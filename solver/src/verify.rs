@@ -0,0 +1,140 @@
+use crate::Game;
+use serde::Serialize;
+use std::fmt;
+
+/// A rule broken by a claimed solution, found by `verify_solution`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum Violation {
+    /// A queen on a cell that is not on the board.
+    OffBoard {
+        cell: usize,
+    },
+    RowEmpty {
+        row: usize,
+    },
+    RowTwice {
+        row: usize,
+    },
+    ColumnTwice {
+        col: usize,
+    },
+    ColorEmpty {
+        color: usize,
+    },
+    ColorTwice {
+        color: usize,
+    },
+    /// Two queens on neighboring cells.
+    Adjacent {
+        first: usize,
+        second: usize,
+    },
+    /// A queen on only one of two cells linked to hold both queens or neither.
+    Unlinked {
+        first: usize,
+        second: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OffBoard { cell } => write!(f, "cell {} is off the board", cell),
+            Self::RowEmpty { row } => write!(f, "row {} has no queen", row),
+            Self::RowTwice { row } => write!(f, "row {} has more than one queen", row),
+            Self::ColumnTwice { col } => write!(f, "column {} has more than one queen", col),
+            Self::ColorEmpty { color } => write!(f, "color {} has no queen", color),
+            Self::ColorTwice { color } => write!(f, "color {} has more than one queen", color),
+            Self::Adjacent { first, second } => {
+                write!(f, "cells {} and {} are neighbors", first, second)
+            }
+            Self::Unlinked { first, second } => write!(
+                f,
+                "cells {} and {} are linked, but only one holds a queen",
+                first, second
+            ),
+        }
+    }
+}
+
+/// Every rule of `game` that `solution` breaks, in no particular order, or nothing if it is a
+/// solution.
+///
+/// The checks only read the game itself, never the tables or trackers the search keeps, so that
+/// a bug in those cannot hide from them.
+pub(crate) fn verify_solution(game: &Game, solution: &[usize]) -> Vec<Violation> {
+    let cells = game.rows * game.cols;
+    let mut violations = vec![];
+
+    let (on_board, off_board): (Vec<usize>, Vec<usize>) =
+        solution.iter().partition(|&&idx| idx < cells);
+    violations.extend(
+        off_board
+            .into_iter()
+            .map(|cell| Violation::OffBoard { cell }),
+    );
+
+    let mut rows = vec![0; game.rows];
+    let mut cols = vec![0; game.cols];
+    let mut colors = vec![0; game.num_colors()];
+
+    for &idx in &on_board {
+        rows[idx / game.cols] += 1;
+        cols[idx % game.cols] += 1;
+        if game.has_color_rule() {
+            colors[game.idx_to_color[idx]] += 1;
+        }
+    }
+
+    for (row, &queens) in rows.iter().enumerate() {
+        match queens {
+            0 => violations.push(Violation::RowEmpty { row }),
+            1 => {}
+            _ => violations.push(Violation::RowTwice { row }),
+        }
+    }
+
+    violations.extend(
+        (0..game.cols)
+            .filter(|&col| cols[col] > 1)
+            .map(|col| Violation::ColumnTwice { col }),
+    );
+
+    for (color, &queens) in colors.iter().enumerate() {
+        if queens == 0 && game.rules.all_colors_required {
+            violations.push(Violation::ColorEmpty { color });
+        } else if queens > 1 {
+            violations.push(Violation::ColorTwice { color });
+        }
+    }
+
+    for (pos, &first) in on_board.iter().enumerate() {
+        for &second in &on_board[pos + 1..] {
+            if are_neighbors(game, first, second) {
+                violations.push(Violation::Adjacent { first, second });
+            }
+        }
+    }
+
+    violations.extend(
+        game.same_links()
+            .filter(|[first, second]| on_board.contains(first) != on_board.contains(second))
+            .map(|[first, second]| Violation::Unlinked { first, second }),
+    );
+
+    violations
+}
+
+/// Whether queens on `a` and `b` threaten each other through adjacency, by the game's custom
+/// lists when present and the neighbors of its topology otherwise.
+fn are_neighbors(game: &Game, a: usize, b: usize) -> bool {
+    match &game.adjacency {
+        Some(adjacency) => adjacency[a].contains(&b) || adjacency[b].contains(&a),
+        None => game
+            .rules
+            .topology
+            .neighbors(game.rows, game.cols, a)
+            .contains(&b),
+    }
+}
@@ -0,0 +1,464 @@
+use crate::availability::{Availability, CellAvailability};
+use crate::bitboard::BitAvailability;
+use crate::board::BoardIndex;
+use crate::constrained::find_solution;
+use crate::difficulty::{same_deduction, Technique};
+use crate::marks::BoardState;
+use crate::options::Options;
+use crate::propagation::{Lines, Step};
+use crate::regions::bits;
+use crate::search::{Search, Unit};
+use crate::Game;
+use serde::Serialize;
+
+/// One step of a walkthrough, from `walk_through`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WalkStep {
+    /// `guessing` for a queen tried when no deduction applies.
+    pub technique: Technique,
+    /// Names the sentence describing the step, for the extension to localize:
+    ///
+    /// - `singleInRow`, `singleInColumn`, `singleInColor`: `args` holds the line or color.
+    /// - `confinedToRow`, `confinedToColumn`: `args` holds the color, then the line.
+    /// - `pairInRows`, `pairInColumns`, `tripleInRows`, `tripleInColumns`: `args` holds the
+    ///   colors, then the lines, each in increasing order.
+    /// - `tryQueen`: `args` holds the row and column of the queen.
+    pub key: &'static str,
+    pub args: Vec<usize>,
+    /// The queen the step places, for singles and tries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queen: Option<usize>,
+    /// The cells the step rules out, for confinements and subsets, in increasing order.
+    pub marks: Vec<usize>,
+    /// The cells the reasoning rests on: the row, column, or color of a single, the spots left in
+    /// the unit of a try, or the spots left for the colors of a confinement or subset.
+    pub reason: Vec<usize>,
+    /// For a try, the other spots left in its unit, with why each fails.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<Alternative>,
+}
+
+/// The other choice for a tried queen.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Alternative {
+    pub cell: usize,
+    /// Whether the deductions alone run out of spots after a queen on `cell`. Otherwise it takes
+    /// deeper search to fail, or on boards with several solutions it may lead to another one.
+    pub refuted_by_deduction: bool,
+}
+
+/// A queen of a solution in the order the deductions find it, from `deduction_order`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Placement {
+    pub queen: usize,
+    /// The position of the step placing the queen in the walkthrough of the empty board.
+    pub step: usize,
+    /// The hardest technique of the steps since the queen before, so that a single found after a
+    /// confinement is tagged `confinement`. `guessing` for a queen tried once deductions run out.
+    pub technique: Technique,
+}
+
+/// The steps from `state` to a solution: every deduction the difficulty rater would make, in
+/// order, and whenever they run out, a try of the target solution's queen in the unit with the
+/// fewest spots. Fails when no solution agrees with the state.
+pub(crate) fn walk_through(game: &Game, state: &BoardState) -> Result<Vec<WalkStep>, String> {
+    state.validate(game)?;
+
+    let Some(target) = find_solution(game, &state.queens, &state.marks) else {
+        return Err("No solution agrees with the state".to_string());
+    };
+
+    Ok(if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        walk(game, state, &target, BitAvailability::new(game))
+    } else {
+        walk(game, state, &target, Availability::new(game))
+    })
+}
+
+/// The queens of `solution` in the order `walk_through` places them from the empty board: by
+/// deduction where it can, and otherwise trying the queen of `solution` in the unit with the
+/// fewest spots.
+pub(crate) fn deduction_order(game: &Game, solution: &[usize]) -> Vec<Placement> {
+    let state = BoardState::default();
+    let steps = if game.rows * game.cols <= BitAvailability::MAX_CELLS {
+        walk(game, &state, solution, BitAvailability::new(game))
+    } else {
+        walk(game, &state, solution, Availability::new(game))
+    };
+
+    let mut placements = vec![];
+    let mut hardest = Technique::Singles;
+    for (pos, step) in steps.iter().enumerate() {
+        hardest = hardest.max(step.technique);
+        if let Some(queen) = step.queen {
+            placements.push(Placement {
+                queen,
+                step: pos,
+                technique: hardest,
+            });
+            hardest = Technique::Singles;
+        }
+    }
+
+    placements
+}
+
+fn walk<A: CellAvailability>(
+    game: &Game,
+    state: &BoardState,
+    target: &[usize],
+    availability: A,
+) -> Vec<WalkStep> {
+    let options = Options::for_deduction();
+    let mut search = Search::new(game, &options, availability);
+    // The target agrees with the state, so its queens leave each other alone.
+    search.assume(&state.queens, &state.marks);
+
+    let mut available: Vec<_> = (0..game.rows * game.cols)
+        .map(|idx| search.availability.is_available(idx))
+        .collect();
+    let mut steps = vec![];
+
+    loop {
+        let start = search.trail.len();
+        if game.has_color_rule() {
+            search.propagate::<true>();
+        } else {
+            search.propagate::<false>();
+        }
+
+        let mut pos = start;
+        while pos < search.trail.len() {
+            let end = (pos + 1..search.trail.len())
+                .find(|&next| !same_deduction(search.trail[next - 1], search.trail[next]))
+                .unwrap_or(search.trail.len());
+            let group = &search.trail[pos..end];
+            steps.push(describe(game, &search.index, &available, group));
+
+            for &step in group {
+                match step {
+                    Step::Forced { idx, .. } => rule_out_around(&search, &mut available, idx),
+                    Step::Confined { idx, .. }
+                    | Step::Subset { idx, .. }
+                    | Step::Wipeout { idx, .. } => available[idx] = false,
+                }
+            }
+            pos = end;
+        }
+
+        if search.is_solved() {
+            return steps;
+        }
+
+        let tried = if game.has_color_rule() {
+            try_queen::<A, true>(&mut search, target)
+        } else {
+            try_queen::<A, false>(&mut search, target)
+        };
+        let queen = tried.queen.unwrap();
+        steps.push(tried);
+
+        search.push(queen);
+        search.set_queen(queen, true);
+        rule_out_around(&search, &mut available, queen);
+    }
+}
+
+/// The step for the deduction `group`, the trail entries of one rule firing, on the board left
+/// by the steps before it, where `available` tells the cells still open.
+fn describe(game: &Game, index: &BoardIndex, available: &[bool], group: &[Step]) -> WalkStep {
+    let mut marks: Vec<_> = group
+        .iter()
+        .map(|&step| match step {
+            Step::Forced { idx, .. }
+            | Step::Confined { idx, .. }
+            | Step::Subset { idx, .. }
+            | Step::Wipeout { idx, .. } => idx,
+        })
+        .collect();
+    marks.sort_unstable();
+
+    let (technique, key, args, reason) = match group[0] {
+        Step::Forced { idx, unit } => {
+            let (key, arg) = match unit {
+                Unit::Row(row) => ("singleInRow", row),
+                Unit::Col(col) => ("singleInColumn", col),
+                Unit::Color(color) => ("singleInColor", color),
+            };
+
+            return WalkStep {
+                technique: Technique::Singles,
+                key,
+                args: vec![arg],
+                queen: Some(idx),
+                marks: vec![],
+                reason: unit.cells(index).to_vec(),
+                alternatives: vec![],
+            };
+        }
+        Step::Confined { color, line, .. } => {
+            let (key, arg) = match line {
+                Unit::Row(row) => ("confinedToRow", row),
+                Unit::Col(col) => ("confinedToColumn", col),
+                Unit::Color(_) => unreachable!("colors are confined to lines"),
+            };
+            let spots = index.cells_by_color[color]
+                .iter()
+                .copied()
+                .filter(|&idx| available[idx])
+                .collect();
+            (Technique::Confinement, key, vec![color, arg], spots)
+        }
+        Step::Subset { colors, lines, .. } => {
+            let pair = colors.count_ones() == 2;
+            let (technique, key, mask) = match (pair, lines) {
+                (true, Lines::Rows(rows)) => (Technique::Pairs, "pairInRows", rows),
+                (true, Lines::Cols(cols)) => (Technique::Pairs, "pairInColumns", cols),
+                (false, Lines::Rows(rows)) => (Technique::Triples, "tripleInRows", rows),
+                (false, Lines::Cols(cols)) => (Technique::Triples, "tripleInColumns", cols),
+            };
+            let spots = (0..game.rows * game.cols)
+                .filter(|&idx| available[idx] && (colors >> game.idx_to_color[idx]) & 1 == 1)
+                .collect();
+            (
+                technique,
+                key,
+                bits(colors).into_iter().chain(bits(mask)).collect(),
+                spots,
+            )
+        }
+        Step::Wipeout { .. } => unreachable!("arc consistency is off"),
+    };
+
+    WalkStep {
+        technique,
+        key,
+        args,
+        queen: None,
+        marks,
+        reason,
+        alternatives: vec![],
+    }
+}
+
+/// The try of the target's queen in the unit with the fewest spots, once the deductions are
+/// stuck, with each other spot of the unit checked by deduction alone.
+fn try_queen<A: CellAvailability, const COLORS: bool>(
+    search: &mut Search<A>,
+    target: &[usize],
+) -> WalkStep {
+    let game = search.game;
+    // Some unit is missing its queen, or the board would be solved.
+    let (_, unit) = search.tightest_unit::<COLORS>().unwrap();
+    let spots: Vec<_> = unit
+        .cells(&search.index)
+        .iter()
+        .copied()
+        .filter(|&idx| search.availability.is_available(idx))
+        .collect();
+    let queen = *spots.iter().find(|idx| target.contains(idx)).unwrap();
+
+    let alternatives = spots
+        .iter()
+        .copied()
+        .filter(|&idx| idx != queen)
+        .map(|cell| {
+            let sorted_pos = search.push(cell);
+            search.set_queen(cell, true);
+            let steps = search.propagate::<COLORS>();
+            let refuted =
+                matches!(search.tightest_unit::<COLORS>(), Some((0, _))) || search.breaks_link();
+
+            search.unpropagate(steps);
+            search.set_queen(cell, false);
+            search.pop(sorted_pos);
+
+            Alternative {
+                cell,
+                refuted_by_deduction: refuted,
+            }
+        })
+        .collect();
+
+    WalkStep {
+        technique: Technique::Guessing,
+        key: "tryQueen",
+        args: vec![queen / game.cols, queen % game.cols],
+        queen: Some(queen),
+        marks: vec![],
+        reason: spots,
+        alternatives,
+    }
+}
+
+/// Rules out in `available` the cell of a new queen on `queen` and every cell it attacks.
+fn rule_out_around<A: CellAvailability>(search: &Search<A>, available: &mut [bool], queen: usize) {
+    let game = search.game;
+    let neighbors = search.availability.adj_lookup().neighbors(queen);
+
+    for (idx, open) in available.iter_mut().enumerate() {
+        if idx / game.cols == queen / game.cols
+            || idx % game.cols == queen % game.cols
+            || (game.has_color_rule() && game.idx_to_color[idx] == game.idx_to_color[queen])
+            || neighbors.contains(&(idx as u16))
+        {
+            *open = false;
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+    use crate::verify::verify_solution;
+    use crate::walkthrough;
+    use serde_json::{json, Value};
+
+    fn walked(entry: usize, state_json: &str) -> Value {
+        let json = walkthrough(CORPUS[entry].json.to_string(), state_json.to_string()).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /// The 8x8 board runs out of deductions once, where trying either spot of color 4 in column 7
+    /// works out, and the other is refuted by deduction.
+    #[test]
+    fn walks_through_the_8x8_board() {
+        let expected = json!([
+            {"technique": "singles", "key": "singleInColor", "args": [7], "queen": 56,
+                "marks": [], "reason": [56]},
+            {"technique": "singles", "key": "singleInColor", "args": [1], "queen": 9,
+                "marks": [], "reason": [8, 9, 16]},
+            {"technique": "singles", "key": "singleInColor", "args": [2], "queen": 19,
+                "marks": [], "reason": [10, 18, 19]},
+            {"technique": "singles", "key": "singleInColor", "args": [3], "queen": 29,
+                "marks": [], "reason": [21, 29]},
+            {"technique": "confinement", "key": "confinedToColumn", "args": [4, 7],
+                "marks": [7, 55], "reason": [39, 47]},
+            {"technique": "confinement", "key": "confinedToColumn", "args": [6, 6],
+                "marks": [6], "reason": [46, 54]},
+            {"technique": "singles", "key": "singleInRow", "args": [0], "queen": 4,
+                "marks": [], "reason": [0, 1, 2, 3, 4, 5, 6, 7]},
+            {"technique": "guessing", "key": "tryQueen", "args": [4, 7], "queen": 39,
+                "marks": [], "reason": [34, 39],
+                "alternatives": [{"cell": 34, "refutedByDeduction": true}]},
+            {"technique": "singles", "key": "singleInRow", "args": [5], "queen": 42,
+                "marks": [], "reason": [40, 41, 42, 43, 44, 45, 46, 47]},
+            {"technique": "singles", "key": "singleInRow", "args": [6], "queen": 54,
+                "marks": [], "reason": [48, 49, 50, 51, 52, 53, 54, 55]},
+        ]);
+        assert_eq!(walked(0, r#"{"queens":[],"marks":[]}"#), expected);
+    }
+
+    /// From a board with a queen and marks already on it, the walkthrough leaves them be and
+    /// needs no confinement.
+    #[test]
+    fn walks_through_the_9x9_board_from_a_state() {
+        let expected = json!([
+            {"technique": "singles", "key": "singleInColor", "args": [2], "queen": 26,
+                "marks": [], "reason": [17, 26]},
+            {"technique": "singles", "key": "singleInColor", "args": [6], "queen": 60,
+                "marks": [], "reason": [60]},
+            {"technique": "singles", "key": "singleInColor", "args": [8], "queen": 76,
+                "marks": [], "reason": [76]},
+            {"technique": "singles", "key": "singleInColor", "args": [4], "queen": 43,
+                "marks": [],
+                "reason": [25, 34, 35, 43, 44, 51, 52, 53, 61, 62, 69, 70, 71, 79, 80]},
+            {"technique": "singles", "key": "singleInColor", "args": [5], "queen": 48,
+                "marks": [], "reason": [48, 49, 57, 58, 59, 67, 68, 77, 78]},
+            {"technique": "singles", "key": "singleInRow", "args": [0], "queen": 5,
+                "marks": [], "reason": [0, 1, 2, 3, 4, 5, 6, 7, 8]},
+            {"technique": "singles", "key": "singleInRow", "args": [3], "queen": 27,
+                "marks": [], "reason": [27, 28, 29, 30, 31, 32, 33, 34, 35]},
+            {"technique": "singles", "key": "singleInRow", "args": [7], "queen": 65,
+                "marks": [], "reason": [63, 64, 65, 66, 67, 68, 69, 70, 71]},
+        ]);
+        assert_eq!(walked(1, r#"{"queens":[10],"marks":[0,1,2]}"#), expected);
+    }
+
+    /// From states made of some queens of the solution and marks off it, the queens placed make
+    /// up the solution, and no step marks a queen or places one on a mark.
+    #[test]
+    fn completes_the_state_on_every_board() {
+        for entry in CORPUS
+            .iter()
+            .filter(|entry| !entry.name.starts_with("unsolvable"))
+        {
+            let game = Game::from_json(entry.json).unwrap();
+            let off: Vec<_> = (0..game.rows * game.cols)
+                .filter(|idx| !entry.solution.contains(idx))
+                .collect();
+
+            for given in 0..entry.solution.len() {
+                let state = BoardState {
+                    queens: entry.solution[..given].to_vec(),
+                    marks: off.iter().copied().step_by(given + 2).collect(),
+                };
+                let steps = walk_through(&game, &state).unwrap();
+
+                let mut queens = state.queens.clone();
+                queens.extend(steps.iter().filter_map(|step| step.queen));
+                queens.sort_unstable();
+                assert_eq!(
+                    queens, entry.solution,
+                    "{} with {} given",
+                    entry.name, given
+                );
+                assert!(verify_solution(&game, &queens).is_empty());
+
+                for step in &steps {
+                    assert!(step.marks.iter().all(|idx| !queens.contains(idx)));
+                    assert!(step.queen.is_none_or(|queen| !state.marks.contains(&queen)));
+                    assert_eq!(step.alternatives.is_empty(), step.key != "tryQueen");
+                }
+            }
+
+            // Placed in the order of the walkthrough from the empty board.
+            let order = deduction_order(&game, entry.solution);
+            let mut queens: Vec<_> = order.iter().map(|placement| placement.queen).collect();
+            queens.sort_unstable();
+            assert_eq!(queens, entry.solution, "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn fails_without_a_solution_agreeing_with_the_state() {
+        let game = Game::from_json(CORPUS[0].json).unwrap();
+        // Both queens are in the first row.
+        let state = BoardState {
+            queens: vec![0, 4],
+            marks: vec![],
+        };
+        let error = walk_through(&game, &state).unwrap_err();
+        assert_eq!(error, "No solution agrees with the state");
+        // A mark on a queen of the only solution.
+        let state = BoardState {
+            queens: vec![],
+            marks: vec![56],
+        };
+        assert!(walk_through(&game, &state).is_err());
+
+        let unsolvable = CORPUS
+            .iter()
+            .find(|entry| entry.name == "unsolvable-9x9")
+            .unwrap();
+        let game = Game::from_json(unsolvable.json).unwrap();
+        assert!(walk_through(&game, &BoardState::default()).is_err());
+
+        let error = walkthrough(CORPUS[0].json.to_string(), r#"{"queens":[64]}"#.to_string());
+        assert_eq!(
+            error.unwrap_err(),
+            "Cell 64 is not on the board, which has cells 0..64"
+        );
+        let error = walkthrough(
+            CORPUS[0].json.to_string(),
+            r#"{"queens":[3],"marks":[3]}"#.to_string(),
+        );
+        assert_eq!(
+            error.unwrap_err(),
+            "Cell 3 appears more than once among the queens and marks"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{count_solutions, hints, solve, solve_with_options, validate_game};
+use std::convert::TryInto;
+
+/// The version of the envelope, the first byte of every request and reply, raised whenever the
+/// layout changes so that a worker and a page from different builds notice.
+pub(crate) const PROTOCOL_VERSION: u8 = 1;
+
+/// What a request asks the worker to do, the second byte of its envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// `solve`, or `solve_with_options` when options are given.
+    Solve = 1,
+    /// The first hint of `hints`, the options taking the board state, `{"queens", "marks"}`.
+    Hint = 2,
+    /// `validate_game`.
+    Validate = 3,
+    /// `count_solutions`.
+    Count = 4,
+}
+
+impl Kind {
+    const NAMES: &'static [(&'static str, Kind)] = &[
+        ("solve", Kind::Solve),
+        ("hint", Kind::Hint),
+        ("validate", Kind::Validate),
+        ("count", Kind::Count),
+    ];
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::NAMES
+            .iter()
+            .find(|&&(registered, _)| registered == name)
+            .map(|&(_, kind)| kind)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::NAMES.iter().map(|&(name, _)| name).collect();
+                format!(
+                    "Unknown request kind {:?}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .map(|&(_, kind)| kind)
+            .find(|&kind| kind as u8 == code)
+    }
+}
+
+/// How a request went, the third byte of a reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReplyStatus {
+    /// The payload is the JSON the entry point returned.
+    Ok = 0,
+    /// The payload is the error the entry point returned.
+    Failed = 1,
+    /// The request names a kind this build does not know, perhaps one of a newer build.
+    UnknownKind = 2,
+    /// The request is not an envelope of this version.
+    Malformed = 3,
+}
+
+/// Packs a request: the version, the kind, and the game and options JSON, each as UTF-8 after
+/// its length in bytes as a little-endian `u32`.
+pub(crate) fn encode_request(kind: Kind, game_json: &str, options_json: &str) -> Vec<u8> {
+    let mut bytes = vec![PROTOCOL_VERSION, kind as u8];
+    put_str(&mut bytes, game_json);
+    put_str(&mut bytes, options_json);
+    bytes
+}
+
+/// Runs the request packed in `bytes` and packs the reply: the version, the kind of the request
+/// or 0 if it could not be read, the status, and the payload, as in a request. Never fails, so
+/// that the page always hears back.
+pub(crate) fn handle_request(bytes: &[u8]) -> Vec<u8> {
+    let code = bytes.get(1).copied().unwrap_or(0);
+
+    let (status, payload) = match read_request(bytes) {
+        Err(message) => (ReplyStatus::Malformed, message),
+        Ok(None) => (
+            ReplyStatus::UnknownKind,
+            format!(
+                "Unknown request kind {}, expected one of 1 to {}",
+                code,
+                Kind::NAMES.len()
+            ),
+        ),
+        Ok(Some((kind, game_json, options_json))) => match run(kind, game_json, options_json) {
+            Ok(json) => (ReplyStatus::Ok, json),
+            Err(message) => (ReplyStatus::Failed, message),
+        },
+    };
+
+    let mut reply = vec![PROTOCOL_VERSION, code, status as u8];
+    put_str(&mut reply, &payload);
+    reply
+}
+
+/// The JSON of a reply from `handle_request`, or the error it carries.
+pub(crate) fn decode_reply(bytes: &[u8]) -> Result<String, String> {
+    let mut reader = Reader::new(bytes)?;
+    reader.byte()?;
+    let status = reader.byte()?;
+    let payload = reader.str()?.to_string();
+
+    if status == ReplyStatus::Ok as u8 {
+        Ok(payload)
+    } else {
+        Err(payload)
+    }
+}
+
+/// The kind and the game and options JSON of a request, or `None` for a kind this build does not
+/// know, whose rest is left unread since it may be laid out differently.
+fn read_request(bytes: &[u8]) -> Result<Option<(Kind, &str, &str)>, String> {
+    let mut reader = Reader::new(bytes)?;
+    let Some(kind) = Kind::from_code(reader.byte()?) else {
+        return Ok(None);
+    };
+    let game_json = reader.str()?;
+    let options_json = reader.str()?;
+
+    Ok(Some((kind, game_json, options_json)))
+}
+
+fn run(kind: Kind, game_json: &str, options_json: &str) -> Result<String, String> {
+    let game_json = game_json.to_string();
+
+    match kind {
+        Kind::Solve if options_json.is_empty() => solve(game_json),
+        Kind::Solve => solve_with_options(game_json, options_json.to_string()),
+        Kind::Hint => {
+            let state_json = if options_json.is_empty() {
+                r#"{"queens": [], "marks": []}"#
+            } else {
+                options_json
+            };
+            hints(game_json, state_json.to_string(), 1)
+        }
+        Kind::Validate => validate_game(game_json),
+        Kind::Count => count_solutions(game_json),
+    }
+}
+
+fn put_str(bytes: &mut Vec<u8>, s: &str) {
+    bytes.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+/// Reads an envelope front to back, past its version.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Checks the version before anything else, since the rest of a message of another version
+    /// may mean something else.
+    fn new(bytes: &'a [u8]) -> Result<Self, String> {
+        match bytes.first() {
+            None => Err("Message is empty".to_string()),
+            Some(&PROTOCOL_VERSION) => Ok(Self { bytes: &bytes[1..] }),
+            Some(&version) => Err(format!(
+                "Message has protocol version {}, but this build speaks version {}",
+                version, PROTOCOL_VERSION
+            )),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err(format!(
+                "Message ends early: expected {} more bytes, found {}",
+                len,
+                self.bytes.len()
+            ));
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn str(&mut self) -> Result<&'a str, String> {
+        let len = u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize;
+        std::str::from_utf8(self.take(len)?).map_err(|e| format!("Message is not UTF-8: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::CORPUS;
+
+    /// Through the envelope, every kind returns what calling its entry point does, errors
+    /// included.
+    #[test]
+    fn round_trips_every_kind() {
+        let game = CORPUS[0].json;
+        let state = r#"{"queens": [], "marks": []}"#;
+        let options = r#"{"verify":true}"#;
+        let cases = [
+            (Kind::Solve, game, "", solve(game.into())),
+            (
+                Kind::Solve,
+                game,
+                options,
+                solve_with_options(game.into(), options.into()),
+            ),
+            (Kind::Hint, game, "", hints(game.into(), state.into(), 1)),
+            (Kind::Validate, game, "", validate_game(game.into())),
+            (Kind::Count, game, "", count_solutions(game.into())),
+            (Kind::Count, "{", "", count_solutions("{".into())),
+        ];
+
+        for (kind, game, options, direct) in cases {
+            let reply = handle_request(&encode_request(kind, game, options));
+            let status = match direct {
+                Ok(_) => ReplyStatus::Ok,
+                Err(_) => ReplyStatus::Failed,
+            };
+            assert_eq!(reply[..3], [PROTOCOL_VERSION, kind as u8, status as u8]);
+            assert_eq!(decode_reply(&reply), direct);
+        }
+    }
+
+    /// Every request cut short gets a reply saying so, and every reply cut short is refused.
+    #[test]
+    fn rejects_truncated_messages() {
+        let request = encode_request(Kind::Count, CORPUS[0].json, "");
+        for len in 0..request.len() {
+            let reply = handle_request(&request[..len]);
+            assert_eq!(reply[2], ReplyStatus::Malformed as u8);
+            let message = decode_reply(&reply).unwrap_err();
+            if len == 0 {
+                assert_eq!(message, "Message is empty");
+            } else {
+                assert!(message.starts_with("Message ends early"), "{}", message);
+            }
+        }
+
+        let reply = handle_request(&request);
+        for len in 0..reply.len() {
+            assert!(decode_reply(&reply[..len]).is_err());
+        }
+    }
+
+    /// A kind this build does not know is answered without reading the rest, which a newer
+    /// build may lay out differently, and a version it does not speak is refused outright.
+    #[test]
+    fn rejects_unknown_kinds_and_versions() {
+        let reply = handle_request(&[PROTOCOL_VERSION, 9, 0xff]);
+        assert_eq!(
+            reply[..3],
+            [PROTOCOL_VERSION, 9, ReplyStatus::UnknownKind as u8]
+        );
+        assert_eq!(
+            decode_reply(&reply).unwrap_err(),
+            "Unknown request kind 9, expected one of 1 to 4"
+        );
+
+        let mut request = encode_request(Kind::Count, CORPUS[0].json, "");
+        request[0] = PROTOCOL_VERSION + 1;
+        let reply = handle_request(&request);
+        assert_eq!(reply[2], ReplyStatus::Malformed as u8);
+        assert_eq!(
+            decode_reply(&reply).unwrap_err(),
+            "Message has protocol version 2, but this build speaks version 1"
+        );
+
+        let mut reply = reply;
+        reply[0] = 0;
+        assert_eq!(
+            decode_reply(&reply).unwrap_err(),
+            "Message has protocol version 0, but this build speaks version 1"
+        );
+    }
+}
@@ -1,40 +1,618 @@
 /* tslint:disable */
 /* eslint-disable */
+
+/**
+ * A board being painted cell by cell, judged after every edit.
+ *
+ * Most edits leave the solutions away from the edited cells alone, since whether a set of
+ * queens solves the board only depends on the colors under them. So the editor keeps up to two
+ * solutions from the last status: if both still hold the board has several, and if they were
+ * all of them then only solutions with a queen on an edited cell need looking for. Adjacency
+ * tables are shared between searches by board size anyway.
+ */
+export class BoardEditor {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * The board as game JSON, as taken by `solve` and the other entry points.
+     */
+    get_board_json(): string;
+    /**
+     * An editor for a `rows` by `cols` board with one color per row, every cell starting out in
+     * color 0.
+     */
+    constructor(rows: number, cols: number);
+    /**
+     * Paints the cell at `row` and `col` with `color`, which can be undone.
+     */
+    set_color(row: number, col: number, color: number): void;
+    /**
+     * Judges the board as it stands: `invalid`, `unsolvable`, `unique` with the solution, or
+     * `multiple`, along with the colors that are empty or split into pieces.
+     */
+    status(): string;
+    /**
+     * Takes back the last edit not yet undone, returning whether there was one.
+     */
+    undo(): boolean;
+}
+
+/**
+ * A pack of puzzles for the practice mode, read from a pack file.
+ *
+ * A pack file is JSON with the format `version`, a `title`, and `entries`, each holding a
+ * `board` as game JSON, its `difficulty` as the hardest technique it takes, its `hash` as
+ * computed by the crate, and optionally its `solution`. Reading checks every board and that
+ * every hash matches its board, so that a pack edited by hand cannot pass off one board as
+ * another.
+ */
+export class PuzzlePack {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * The index of the first entry with hash `hash`, if any.
+     */
+    find_by_hash(hash: string): number | undefined;
+    /**
+     * The board of entry `index` as game JSON.
+     */
+    get(index: number): string;
+    is_empty(): boolean;
+    len(): number;
+    /**
+     * What the pack says about entry `index`: its `difficulty`, `hash`, size, and whether it
+     * comes with a solution.
+     */
+    meta(index: number): string;
+    constructor(pack_json: string);
+    /**
+     * Checks that every board has exactly one solution, and that the solution given with an
+     * entry, if any, keeps the rules and is that one. Returns whether every entry `passed`,
+     * and per entry whether it passed and what failed.
+     */
+    verify(): string;
+    readonly title: string;
+}
+
+/**
+ * The solutions of a game, found one at a time as they are asked for.
+ *
+ * Every call goes on with the same backtracking search from the solution found last, so asking
+ * for a few solutions only pays for those, and the solutions come in the order the search finds
+ * them, each once. The iterator owns its search, which is freed once the solutions run out or it
+ * is dropped, so other calls cannot disturb it.
+ */
+export class SolutionIterator {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * An iterator over the solutions of the game. Nothing is searched until `next`.
+     */
+    constructor(game_json: string);
+    /**
+     * The next solution as JSON, a list of its cells sorted, or `None` once there are no more.
+     */
+    next(): string | undefined;
+}
+
+/**
+ * A solve run a chunk of nodes at a time, so that a page can show progress, or give up, without
+ * blocking on one long search.
+ *
+ * Every chunk takes the search up exactly where the last one paused, with the same stack,
+ * nogoods, and statistics, so solving in chunks finds the same solution after the same number
+ * of nodes as solving in one go. Only backtracking can pause, so the options must leave out
+ * backjumping, restarts, and a node budget of their own, and name no other algorithm.
+ */
+export class SolveSession {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Searches for up to `extra_nodes` more nodes, at least one, returning what
+     * `solve_with_options` would with the statistics of every chunk so far. The status stays
+     * `unknown` until the search finds a solution or proves there is none, after which calling
+     * again returns the same result without searching.
+     */
+    continue_solve(extra_nodes: number): string;
+    /**
+     * A session solving the game, tuned by `options_json` as for `solve_with_options`. Nothing
+     * is searched until `continue_solve`.
+     */
+    constructor(game_json: string, options_json: string);
+    /**
+     * Changes the options of the session. The paused search only carries on if nothing but
+     * `verify` and `order` changed, since it would not visit the same nodes under any other
+     * options: it starts over, statistics and all, otherwise.
+     */
+    set_options(options_json: string): void;
+}
+
+/**
+ * Solves every game of `corpus_jsonl`, one per line, under each configuration of `configs_json`,
+ * a list of `{"name", "options"}` with options as for `solve_with_options`. With `null` the
+ * nogood cache, forward checking, and candidate ordering are each turned off alone and then
+ * together, the nogoods are kept in a hash set instead or screened by signature, and the
+ * heuristics are left to `"auto"`, every run giving up after two million nodes. Each solve is repeated `repeats` times
+ * to take the fastest. Returns per configuration the boards solved and given up on, the total
+ * nodes and milliseconds, and the boards it settled fastest, followed by every run.
+ */
+export function benchmark_options(corpus_jsonl: string, configs_json: string, repeats: number): string;
+
+/**
+ * Counts the solutions through every cell of one unit, given by `unit_json` as `{"row": 0}`,
+ * `{"col": 0}`, or `{"color": 0}`, or with `null` the tightest unit once forced queens are
+ * placed, the first the counter branches on. Returns the `unit` and its `cells`, each with its
+ * `idx` and `count`. The cells counting zero are the ones no solution uses.
+ */
+export function branch_census(game_json: string, unit_json: string): string;
+
+/**
+ * Checks the user's X marks in `state_json`, `{"queens", "marks"}` as lists of cells, against
+ * every solution. Returns the marks that are wrong, since the cell holds a queen in every
+ * solution, those the user's queens already imply, those the deductions from those queens
+ * reach, and the premature rest. Fails when the board has no solution.
+ */
+export function check_marks(game_json: string, state_json: string): string;
+
+/**
+ * Reports which rotations and reflections map the board, each of its solutions, and its whole
+ * set of solutions onto themselves. A board with several solutions can be symmetric as a set
+ * even when no single solution is.
+ */
+export function classify_symmetry(game_json: string): string;
+
+/**
+ * Forgets every solve in the activity log.
+ */
+export function clear_activity(): void;
+
+/**
+ * Turns the cells of `solution_json`, such as a solution from `solve`, into the points to click
+ * to place their queens, in the same order. `layout_json` gives the bounding box of the cells on
+ * the page, the gap between them, and optionally their size, the device pixel ratio, and whether
+ * queens take a double click. Returns each cell with the device-pixel center of its cell.
+ */
+export function click_plan(game_json: string, solution_json: string, layout_json: string): string;
+
+/**
+ * Lists every rule the queens in `placed_json`, a list of cells, break against each other: each
+ * pair sharing a row, column, or color, or placed on neighboring cells, as `{"first",
+ * "second", "rule"}`. Never looks at the solution, so it gives nothing away.
+ */
+export function conflicts(game_json: string, placed_json: string): string;
+
+/**
+ * Counts every solution of the game.
+ */
+export function count_solutions(game_json: string): string;
+
+/**
+ * Counts the solutions of the game up to its symmetries: solutions carried into each other by a
+ * rotation or reflection mapping the board onto itself count once.
+ */
+export function count_solutions_mod_symmetry(game_json: string): string;
+
+/**
+ * Solves the game like `solve_with_options` and returns a bundle for attaching to bug reports:
+ * the `bundleVersion` of its format, the crate version, features, and whether it is a debug
+ * build, the game normalized with its `boardHash`, the options searched with, every default
+ * spelled out and automatic heuristics replaced by their tier's, the solution, its status,
+ * whether it was `verified` to keep every rule with any `violations`, the statistics, and the
+ * milliseconds taken. Passing the board and options back to `solve_with_options` reproduces the
+ * solve. `solve --bundle out.json` writes the same bundle from the command line.
+ */
+export function debug_bundle(game_json: string, options_json: string): string;
+
+/**
+ * The JSON the request returned from a reply of `handle_request`, or the error it carries.
+ */
+export function decode_reply(bytes: Uint8Array): string;
+
+/**
+ * Derives up to `count` practice boards from the game, reproducibly from `seed`: each moves a
+ * few cells between neighboring colors, keeping the only solution and the hardest technique it
+ * takes, then is rotated or reflected and has its colors renamed. Each comes with its solution
+ * and the operations that produced it. No two are isomorphic, nor any to the game. Fails unless
+ * the game has exactly one solution.
+ */
+export function derive_variants(game_json: string, count: number, seed: bigint): string;
+
+/**
+ * Looks for signs that scraping `game_json` merged or split regions: a region count off from the
+ * rows, regions of outlying sizes, and regions in pieces. With `rgb_json`, the scraped `[r, g, b]`
+ * of every cell, also proposes fixes, each a list of `{"idx", "color"}` changes judged like
+ * `what_if_recolor_batch`, best first. Boards short of colors are read anyway, as they are what
+ * this is for.
+ */
+export function diagnose_scrape(game_json: string, rgb_json?: string | null): string;
+
+/**
+ * Compares two scrapes of a board, matching the colors of `a` with those of `b` by the most
+ * cells their regions share, so that numbering the same regions differently is not a
+ * difference. Returns the size of each board, the `alignment` giving the color of `b` matched
+ * with every color of `a`, the `cells` whose region differs with their color on each board,
+ * and whether the boards are the `same`.
+ */
+export function diff_boards(a_json: string, b_json: string): string;
+
+/**
+ * Reports which deduction techniques solving the game takes and how often, and whether it
+ * still needs guessing once they run out.
+ */
+export function difficulty_breakdown(game_json: string): string;
+
+/**
+ * Shows which cells of the user's board in `state_json`, `{"queens", "marks"}` as lists of
+ * cells, could still hold a queen once `level` has been applied: `basic` for what the queens
+ * attack and the marks rule out, then `singles`, `confinement`, `pairs`, and `triples`, each
+ * adding its technique to the ones before. Every ruled-out cell names the technique, or mark or
+ * queen, that ruled it out, and a unit left without any spot is named as `emptied`.
+ */
+export function domains(game_json: string, state_json: string, level: string): string;
+
+/**
+ * Packs a request for a worker running the solver, to post to it as is: `kind` is `"solve"`,
+ * `"hint"`, `"validate"`, or `"count"`, run as `solve_with_options` (or `solve` when
+ * `options_json` is empty), `hints` for the first hint (`options_json` then being the board
+ * state, the empty board when empty), `validate_game`, and `count_solutions`. The envelope starts
+ * with a protocol version, so that a worker from another build answers with an error instead of
+ * misreading it.
+ */
+export function encode_request(kind: string, game_json: string, options_json: string): Uint8Array;
+
+/**
+ * Estimates the number of solutions of boards too large to count, from `probes` random paths
+ * down the counting search's tree. The result holds the estimate with its variance and standard
+ * error, which are only rough guides since the estimate can be far off with few probes.
+ */
+export function estimate_solution_count(game_json: string, probes: number, seed: number): string;
+
+/**
+ * Lists the boards of `corpus_jsonl`, one game per line, that are the game rotated or reflected
+ * with its colors renamed, and how each maps onto it. Looking up several games in the same
+ * corpus indexes it only once.
+ */
+export function find_duplicates(corpus_jsonl: string, game_json: string): string;
+
+/**
+ * Points at one place `technique`, one of `singles`, `confinement`, `pairs`, and `triples`,
+ * applies to the user's board in `state_json`, `{"queens", "marks"}` as lists of cells, right
+ * now, without making it or any other deduction. Comes back `found` with the cells the
+ * reasoning rests on, the `units` it is about, and the `queen` it places or `eliminations` it
+ * makes; `absent` if the technique applies nowhere, even if an easier one does; or
+ * `contradiction` if no solution agrees with the board.
+ */
+export function find_technique(game_json: string, state_json: string, technique: string): string;
+
+/**
+ * Reads a board pasted in any format the crate knows, working out which from the input: game
+ * JSON as taken by `solve` (`game`), the same with `regions` listing the cells of every color
+ * (`regions`), LinkedIn's puzzle data with its `gridSize` and `colorGrid` (`linkedin`), or an
+ * ASCII grid of one letter or digit per cell, cells with the same symbol sharing a region
+ * (`grid`). Returns the `format` found and the `game`, normalized as by `normalize_game`. Fails
+ * when no format or several recognize the input, naming every format tried.
+ */
+export function game_from_any(input: string): string;
+
+/**
+ * Generates up to `count` square boards of `rows` rows for a lesson on `technique`, one of
+ * `singles`, `confinement`, `pairs`, `triples`, and `guessing`, reproducibly from `seed`. Every
+ * board has one solution and takes the technique but nothing harder: deductions up to it solve
+ * the board, or stall for `guessing`, while those below it alone stall. Each comes with its
+ * solution and, as proof, its `difficulty` as from `difficulty_breakdown`. Fewer come back when
+ * the boards grown run out, `attempts` telling how many were.
+ */
+export function generate_lesson(rows: number, technique: string, count: number, seed: bigint): string;
+
+/**
+ * Replays the user's moves in `events_json`, a list of `{"kind", "cell", "time"}` with `kind`
+ * one of `place`, `remove`, `mark`, and `unmark`, for a review of the game. Grades each queen
+ * and mark put down as `forced`, `safe`, `guess`, or `mistake` against the board it went on, or
+ * `unsolvable` once no solution agreed with the board, flags the queens later taken off as
+ * wasted, and gives the first event leaving the board unsolvable with a summary of the counts
+ * and accuracy. Events that do not fit the board come back with a warning and are skipped.
+ * Fails when the board has no solution.
+ */
+export function grade_replay(game_json: string, events_json: string): string;
+
+/**
+ * Runs a request from `encode_request` inside the worker and packs the reply to post back, for
+ * `decode_reply` on the other side. Never fails: requests of another protocol version, cut
+ * short, or of a kind this build does not know get a reply carrying the error.
+ */
+export function handle_request(bytes: Uint8Array): Uint8Array;
+
+/**
+ * Suggests up to `n` different next moves for the user's board in `state_json`, `{"queens",
+ * "marks"}` as lists of cells, simplest first: forced singles, then eliminations by confinement,
+ * pairs, and triples, then safe cells that take lookahead. Each names its technique, the queen
+ * to place or cells to mark, and the cells its reasoning rests on. Empty when no solution agrees
+ * with the state.
+ */
+export function hints(game_json: string, state_json: string, n: number): string;
+
+/**
+ * Whether board `b` is board `a` rotated or reflected, with its colors renamed. Returns the
+ * transform and color renaming carrying `a` onto `b`, or `null`.
+ */
+export function is_isomorphic(a_json: string, b_json: string): string;
+
+/**
+ * Writes the boards of `games_json`, a JSON array of games as taken by `solve`, as a pack file
+ * titled `title` for `PuzzlePack`, working out the difficulty, hash, and solution of each. Fails
+ * on a board without exactly one solution, which a pack has no use for.
+ */
+export function make_pack(title: string, games_json: string): string;
+
+/**
+ * Makes a drill from a deduction using `technique`, one of `singles`, `confinement`, `pairs`,
+ * and `triples`, among those solving the game, picked reproducibly from `seed`. Comes back
+ * `ready` with the `position` just before it, `{"queens", "marks"}` as taken by `hints`, and the
+ * `answer` as from `find_technique` to check the user's against, along with which `deduction`
+ * it is out of how many `deductions`; or `unused` if solving the game never takes the technique.
+ */
+export function make_quiz(game_json: string, technique: string, seed: bigint): string;
+
+/**
+ * Finds the two solutions of the game differing in the most cells among up to `cap` of them,
+ * at least 2, with the `distance` between them, the cells only each holds, how many solutions
+ * were `compared`, and whether they were all of them. The `status` is `multiple`, or `unique`
+ * with the only solution, or `unsolvable`.
+ */
+export function max_diff_solutions(game_json: string, cap: number): string;
+
+/**
+ * Finds the fewest queens to give away so that deductions with only the techniques listed in
+ * `techniques_json`, such as `["singles", "confinement"]`, finish the board. Returns the givens
+ * with the deductions that follow them.
+ */
+export function minimal_hints(game_json: string, techniques_json: string): string;
+
+/**
+ * Finds the solution closest to the queens in `placed_json`, a list of cells, with the user's
+ * queens to remove and the queens to add to reach it. Ties between solutions are broken the
+ * same way every time, so the correction is stable. Boards with many solutions are only
+ * compared against some of them, as `exact` tells.
+ */
+export function nearest_solution(game_json: string, placed_json: string): string;
+
+/**
+ * Rewrites `game_json` in one canonical form, for hashing, caching, or comparing boards: sizes
+ * filled in, unknown fields dropped, colors renamed by first appearance, neighbor lists and
+ * links sorted, and keys in a fixed order. Normalizing the result again returns it unchanged.
+ */
+export function normalize_game(game_json: string): string;
+
+/**
+ * Evens out the sizes of the colors, reproducibly from `seed`, by moving cells on their borders
+ * from larger colors to smaller ones until the largest has at most `target_spread` cells more
+ * than the smallest. The cells of the solution never move, and a move is only kept if the board
+ * stays valid, in one piece, and with the same only solution. Returns the `game`, the `shifts`
+ * made, the `spread` reached, and whether it `met` the target. Fails unless the game has exactly
+ * one solution.
+ */
+export function rebalance(game_json: string, seed: bigint, target_spread: number): string;
+
+/**
+ * Lists the last solves made by `solve`, `solve_with_options`, and finished `SolveSession`s,
+ * oldest first, for diagnosing slowness: for each the entry point, a hash of the board, its
+ * size, the status, nodes, and milliseconds, and the algorithm, heuristics tier, and a hash of
+ * the options. Kept in memory only, 32 solves unless `set_activity_capacity` says otherwise.
+ */
+export function recent_activity(): string;
+
+/**
+ * Describes where the cells of every color lie: their rows, columns, and bounding box, and
+ * whether they are confined to one or two rows or columns. A quick analysis for overlays, which
+ * does not search.
+ */
+export function region_analysis(game_json: string): string;
+
 /**
- * @param {string} game_json
- * @returns {string}
+ * Grows the square game by a row and a column at the corner `direction`, one of `topLeft`,
+ * `topRight`, `bottomLeft`, and `bottomRight`, reproducibly from `seed`. The new cells mostly
+ * extend the colors beside them, and a stretch of them makes up the new color. Returns the new
+ * `game` with its only `solution`, or fails when no extension tried has exactly one solution.
  */
+export function resize(game_json: string, direction: string, seed: bigint): string;
+
+/**
+ * Checks that the build answers right: solves every board of the corpus bundled with the crate
+ * like `solve` and counts its solutions, comparing against the known answers and checking every
+ * solution against the rules. Returns whether everything `passed`, the crate `version` and
+ * optional `features` of the build, and per board whether it passed, what failed, and the
+ * milliseconds taken. Takes a few milliseconds.
+ */
+export function run_self_tests(): string;
+
+/**
+ * Lists the empty cells of the user's board in `state_json`, `{"queens", "marks"}` as lists of
+ * cells, that hold a queen in every solution agreeing with those queens and marks. Each comes
+ * with whether filling in singles from the state finds it. Meant to run after every move: the
+ * solutions of the board are kept between calls.
+ */
+export function safe_cells(game_json: string, state_json: string): string;
+
+/**
+ * Keeps at most `capacity` solves in the activity log, dropping the oldest beyond that, or none
+ * with a capacity of 0.
+ */
+export function set_activity_capacity(capacity: number): void;
+
+/**
+ * Shrinks the square game by the row and column at the corner `direction`, as for `resize`,
+ * keeping most queens of its solution and the colors around them, and giving the cells cut off
+ * from their color to a neighboring one. Returns the new `game` with its only `solution`, or fails when no reduction
+ * tried has exactly one solution.
+ */
+export function shrink(game_json: string, direction: string, seed: bigint): string;
+
 export function solve(game_json: string): string;
 
+/**
+ * Solves the game with queens on every cell of `include_json` and none on `exclude_json`, both
+ * lists of cells. Returns the `status`, `solved` or `unsolvable`, and the `solution` with its
+ * cells sorted. When the constraints contradict each other the status is `unsolvable` without
+ * any search, listing the cells both included and excluded as `overlap` and the rules the
+ * included cells break together as `conflicts`.
+ */
+export function solve_constrained(game_json: string, include_json: string, exclude_json: string): string;
+
+/**
+ * Solves the game like `solve`, tuned by `options_json`, returning the solution together with
+ * statistics about the search.
+ */
+export function solve_with_options(game_json: string, options_json: string): string;
+
+/**
+ * Grid-searches the `orderingWeights` of `solve_with_options` for the fewest search nodes over
+ * the games of `corpus_jsonl`, one per line, with the other options left at their defaults.
+ * Returns the best table as `weights`, ready to pass as `orderingWeights`, with its total
+ * `nodes`, the `baselineNodes` without weights, the boards still given up on, and how many
+ * tables were tried.
+ */
+export function tune_ordering_weights(corpus_jsonl: string): string;
+
+/**
+ * Checks the game like every other entry point does, and lists what is suspicious about it
+ * without breaking the rules: under `splitRegions`, every color whose cells fall apart into
+ * several pieces through shared edges, with the cells of each piece for highlighting. A game
+ * with `"strict": true` is rejected for those instead.
+ */
+export function validate_game(game_json: string): string;
+
+/**
+ * Spells out a way from the user's board in `state_json`, `{"queens", "marks"}` as lists of
+ * cells, to a solution agreeing with it, one step at a time. Each step names its technique, a
+ * key and arguments for the sentence describing it, the queen it places or cells it marks, and
+ * the cells its reasoning rests on. Deductions come first; when they run out the step tries a
+ * queen of the solution, with whether deductions alone refute each alternative spot.
+ */
+export function walkthrough(game_json: string, state_json: string): string;
+
+/**
+ * Previews placing a queen on cell `idx` of the user's board in `state_json`, `{"queens",
+ * "marks"}` as lists of cells, without changing it: the queens singles would force, the cells
+ * deductions up to triples would newly rule out and why, and whether that leaves a row, column,
+ * or color without any spot. A cell the queens already hold or attack comes back `illegal` with
+ * the reason. With `deep`, also searches for whether a solution still agrees with the board.
+ */
+export function what_if(game_json: string, state_json: string, idx: number, deep: boolean): string;
+
+/**
+ * Judges the game with cell `idx` moved to color `new_color`: `invalid` with the reason, or
+ * `unsolvable`, `unique` with the solution, or `multiple`. With `contiguous`, a color split into
+ * pieces makes the board invalid.
+ */
+export function what_if_recolor(game_json: string, idx: number, new_color: number, contiguous: boolean): string;
+
+/**
+ * Like `what_if_recolor` for several tentative edits, each a list of `{"idx", "color"}` changes
+ * applied together, judged one by one against the unchanged game. Returns a verdict per edit.
+ */
+export function what_if_recolor_batch(game_json: string, edits_json: string, contiguous: boolean): string;
+
 export type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembly.Module;
 
 export interface InitOutput {
-  readonly memory: WebAssembly.Memory;
-  readonly solve: (a: number, b: number) => Array;
-  readonly __wbindgen_export_0: WebAssembly.Table;
-  readonly __wbindgen_malloc: (a: number, b: number) => number;
-  readonly __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
-  readonly __wbindgen_free: (a: number, b: number, c: number) => void;
-  readonly __wbindgen_start: () => void;
+    readonly memory: WebAssembly.Memory;
+    readonly __wbg_boardeditor_free: (a: number, b: number) => void;
+    readonly __wbg_puzzlepack_free: (a: number, b: number) => void;
+    readonly __wbg_solutioniterator_free: (a: number, b: number) => void;
+    readonly __wbg_solvesession_free: (a: number, b: number) => void;
+    readonly benchmark_options: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+    readonly boardeditor_get_board_json: (a: number) => [number, number];
+    readonly boardeditor_new: (a: number, b: number) => [number, number, number];
+    readonly boardeditor_set_color: (a: number, b: number, c: number, d: number) => [number, number];
+    readonly boardeditor_status: (a: number) => [number, number, number, number];
+    readonly boardeditor_undo: (a: number) => number;
+    readonly branch_census: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly check_marks: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly classify_symmetry: (a: number, b: number) => [number, number, number, number];
+    readonly clear_activity: () => void;
+    readonly click_plan: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+    readonly conflicts: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly count_solutions: (a: number, b: number) => [number, number, number, number];
+    readonly count_solutions_mod_symmetry: (a: number, b: number) => [number, number, number, number];
+    readonly debug_bundle: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly decode_reply: (a: number, b: number) => [number, number, number, number];
+    readonly derive_variants: (a: number, b: number, c: number, d: bigint) => [number, number, number, number];
+    readonly diagnose_scrape: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly diff_boards: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly difficulty_breakdown: (a: number, b: number) => [number, number, number, number];
+    readonly domains: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+    readonly encode_request: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+    readonly estimate_solution_count: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly find_duplicates: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly find_technique: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+    readonly game_from_any: (a: number, b: number) => [number, number, number, number];
+    readonly generate_lesson: (a: number, b: number, c: number, d: number, e: bigint) => [number, number, number, number];
+    readonly grade_replay: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly handle_request: (a: number, b: number) => [number, number];
+    readonly hints: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+    readonly is_isomorphic: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly make_pack: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly make_quiz: (a: number, b: number, c: number, d: number, e: bigint) => [number, number, number, number];
+    readonly max_diff_solutions: (a: number, b: number, c: number) => [number, number, number, number];
+    readonly minimal_hints: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly nearest_solution: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly normalize_game: (a: number, b: number) => [number, number, number, number];
+    readonly puzzlepack_find_by_hash: (a: number, b: number, c: number) => number;
+    readonly puzzlepack_get: (a: number, b: number) => [number, number, number, number];
+    readonly puzzlepack_is_empty: (a: number) => number;
+    readonly puzzlepack_len: (a: number) => number;
+    readonly puzzlepack_meta: (a: number, b: number) => [number, number, number, number];
+    readonly puzzlepack_new: (a: number, b: number) => [number, number, number];
+    readonly puzzlepack_title: (a: number) => [number, number];
+    readonly puzzlepack_verify: (a: number) => [number, number, number, number];
+    readonly rebalance: (a: number, b: number, c: bigint, d: number) => [number, number, number, number];
+    readonly recent_activity: () => [number, number];
+    readonly region_analysis: (a: number, b: number) => [number, number, number, number];
+    readonly resize: (a: number, b: number, c: number, d: number, e: bigint) => [number, number, number, number];
+    readonly run_self_tests: () => [number, number, number, number];
+    readonly safe_cells: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly set_activity_capacity: (a: number) => void;
+    readonly shrink: (a: number, b: number, c: number, d: number, e: bigint) => [number, number, number, number];
+    readonly solutioniterator_new: (a: number, b: number) => [number, number, number];
+    readonly solutioniterator_next: (a: number) => [number, number, number, number];
+    readonly solve: (a: number, b: number) => [number, number, number, number];
+    readonly solve_constrained: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+    readonly solve_with_options: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly solvesession_continue_solve: (a: number, b: number) => [number, number, number, number];
+    readonly solvesession_new: (a: number, b: number, c: number, d: number) => [number, number, number];
+    readonly solvesession_set_options: (a: number, b: number, c: number) => [number, number];
+    readonly tune_ordering_weights: (a: number, b: number) => [number, number, number, number];
+    readonly validate_game: (a: number, b: number) => [number, number, number, number];
+    readonly walkthrough: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+    readonly what_if: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+    readonly what_if_recolor: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+    readonly what_if_recolor_batch: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+    readonly __wbindgen_free: (a: number, b: number, c: number) => void;
+    readonly __wbindgen_malloc: (a: number, b: number) => number;
+    readonly __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
+    readonly __wbindgen_externrefs: WebAssembly.Table;
+    readonly __externref_table_dealloc: (a: number) => void;
+    readonly __wbindgen_start: () => void;
 }
 
 export type SyncInitInput = BufferSource | WebAssembly.Module;
+
 /**
-* Instantiates the given `module`, which can either be bytes or
-* a precompiled `WebAssembly.Module`.
-*
-* @param {{ module: SyncInitInput }} module - Passing `SyncInitInput` directly is deprecated.
-*
-* @returns {InitOutput}
-*/
+ * Instantiates the given `module`, which can either be bytes or
+ * a precompiled `WebAssembly.Module`.
+ *
+ * @param {{ module: SyncInitInput }} module - Passing `SyncInitInput` directly is deprecated.
+ *
+ * @returns {InitOutput}
+ */
 export function initSync(module: { module: SyncInitInput } | SyncInitInput): InitOutput;
 
 /**
-* If `module_or_path` is {RequestInfo} or {URL}, makes a request and
-* for everything else, calls `WebAssembly.instantiate` directly.
-*
-* @param {{ module_or_path: InitInput | Promise<InitInput> }} module_or_path - Passing `InitInput` directly is deprecated.
-*
-* @returns {Promise<InitOutput>}
-*/
+ * If `module_or_path` is {RequestInfo} or {URL}, makes a request and
+ * for everything else, calls `WebAssembly.instantiate` directly.
+ *
+ * @param {{ module_or_path: InitInput | Promise<InitInput> }} module_or_path - Passing `InitInput` directly is deprecated.
+ *
+ * @returns {Promise<InitOutput>}
+ */
 export default function __wbg_init (module_or_path?: { module_or_path: InitInput | Promise<InitInput> } | InitInput | Promise<InitInput>): Promise<InitOutput>;
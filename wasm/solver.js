@@ -1,9 +1,1934 @@
-let wasm;
+/* @ts-self-types="./solver.d.ts" */
 
-let WASM_VECTOR_LEN = 0;
+/**
+ * A board being painted cell by cell, judged after every edit.
+ *
+ * Most edits leave the solutions away from the edited cells alone, since whether a set of
+ * queens solves the board only depends on the colors under them. So the editor keeps up to two
+ * solutions from the last status: if both still hold the board has several, and if they were
+ * all of them then only solutions with a queen on an edited cell need looking for. Adjacency
+ * tables are shared between searches by board size anyway.
+ */
+export class BoardEditor {
+    __destroy_into_raw() {
+        const ptr = this.__wbg_ptr;
+        this.__wbg_ptr = 0;
+        BoardEditorFinalization.unregister(this);
+        return ptr;
+    }
+    free() {
+        const ptr = this.__destroy_into_raw();
+        wasm.__wbg_boardeditor_free(ptr, 0);
+    }
+    /**
+     * The board as game JSON, as taken by `solve` and the other entry points.
+     * @returns {string}
+     */
+    get_board_json() {
+        let deferred1_0;
+        let deferred1_1;
+        try {
+            const ret = wasm.boardeditor_get_board_json(this.__wbg_ptr);
+            deferred1_0 = ret[0];
+            deferred1_1 = ret[1];
+            return getStringFromWasm0(ret[0], ret[1]);
+        } finally {
+            wasm.__wbindgen_free(deferred1_0, deferred1_1, 1);
+        }
+    }
+    /**
+     * An editor for a `rows` by `cols` board with one color per row, every cell starting out in
+     * color 0.
+     * @param {number} rows
+     * @param {number} cols
+     */
+    constructor(rows, cols) {
+        const ret = wasm.boardeditor_new(rows, cols);
+        if (ret[2]) {
+            throw takeFromExternrefTable0(ret[1]);
+        }
+        this.__wbg_ptr = ret[0];
+        BoardEditorFinalization.register(this, this.__wbg_ptr, this);
+        return this;
+    }
+    /**
+     * Paints the cell at `row` and `col` with `color`, which can be undone.
+     * @param {number} row
+     * @param {number} col
+     * @param {number} color
+     */
+    set_color(row, col, color) {
+        const ret = wasm.boardeditor_set_color(this.__wbg_ptr, row, col, color);
+        if (ret[1]) {
+            throw takeFromExternrefTable0(ret[0]);
+        }
+    }
+    /**
+     * Judges the board as it stands: `invalid`, `unsolvable`, `unique` with the solution, or
+     * `multiple`, along with the colors that are empty or split into pieces.
+     * @returns {string}
+     */
+    status() {
+        let deferred2_0;
+        let deferred2_1;
+        try {
+            const ret = wasm.boardeditor_status(this.__wbg_ptr);
+            var ptr1 = ret[0];
+            var len1 = ret[1];
+            if (ret[3]) {
+                ptr1 = 0; len1 = 0;
+                throw takeFromExternrefTable0(ret[2]);
+            }
+            deferred2_0 = ptr1;
+            deferred2_1 = len1;
+            return getStringFromWasm0(ptr1, len1);
+        } finally {
+            wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+        }
+    }
+    /**
+     * Takes back the last edit not yet undone, returning whether there was one.
+     * @returns {boolean}
+     */
+    undo() {
+        const ret = wasm.boardeditor_undo(this.__wbg_ptr);
+        return ret !== 0;
+    }
+}
+if (Symbol.dispose) BoardEditor.prototype[Symbol.dispose] = BoardEditor.prototype.free;
+
+/**
+ * A pack of puzzles for the practice mode, read from a pack file.
+ *
+ * A pack file is JSON with the format `version`, a `title`, and `entries`, each holding a
+ * `board` as game JSON, its `difficulty` as the hardest technique it takes, its `hash` as
+ * computed by the crate, and optionally its `solution`. Reading checks every board and that
+ * every hash matches its board, so that a pack edited by hand cannot pass off one board as
+ * another.
+ */
+export class PuzzlePack {
+    __destroy_into_raw() {
+        const ptr = this.__wbg_ptr;
+        this.__wbg_ptr = 0;
+        PuzzlePackFinalization.unregister(this);
+        return ptr;
+    }
+    free() {
+        const ptr = this.__destroy_into_raw();
+        wasm.__wbg_puzzlepack_free(ptr, 0);
+    }
+    /**
+     * The index of the first entry with hash `hash`, if any.
+     * @param {string} hash
+     * @returns {number | undefined}
+     */
+    find_by_hash(hash) {
+        const ptr0 = passStringToWasm0(hash, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.puzzlepack_find_by_hash(this.__wbg_ptr, ptr0, len0);
+        return ret === Number.MAX_SAFE_INTEGER ? undefined : ret;
+    }
+    /**
+     * The board of entry `index` as game JSON.
+     * @param {number} index
+     * @returns {string}
+     */
+    get(index) {
+        let deferred2_0;
+        let deferred2_1;
+        try {
+            const ret = wasm.puzzlepack_get(this.__wbg_ptr, index);
+            var ptr1 = ret[0];
+            var len1 = ret[1];
+            if (ret[3]) {
+                ptr1 = 0; len1 = 0;
+                throw takeFromExternrefTable0(ret[2]);
+            }
+            deferred2_0 = ptr1;
+            deferred2_1 = len1;
+            return getStringFromWasm0(ptr1, len1);
+        } finally {
+            wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+        }
+    }
+    /**
+     * @returns {boolean}
+     */
+    is_empty() {
+        const ret = wasm.puzzlepack_is_empty(this.__wbg_ptr);
+        return ret !== 0;
+    }
+    /**
+     * @returns {number}
+     */
+    len() {
+        const ret = wasm.puzzlepack_len(this.__wbg_ptr);
+        return ret >>> 0;
+    }
+    /**
+     * What the pack says about entry `index`: its `difficulty`, `hash`, size, and whether it
+     * comes with a solution.
+     * @param {number} index
+     * @returns {string}
+     */
+    meta(index) {
+        let deferred2_0;
+        let deferred2_1;
+        try {
+            const ret = wasm.puzzlepack_meta(this.__wbg_ptr, index);
+            var ptr1 = ret[0];
+            var len1 = ret[1];
+            if (ret[3]) {
+                ptr1 = 0; len1 = 0;
+                throw takeFromExternrefTable0(ret[2]);
+            }
+            deferred2_0 = ptr1;
+            deferred2_1 = len1;
+            return getStringFromWasm0(ptr1, len1);
+        } finally {
+            wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+        }
+    }
+    /**
+     * @param {string} pack_json
+     */
+    constructor(pack_json) {
+        const ptr0 = passStringToWasm0(pack_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.puzzlepack_new(ptr0, len0);
+        if (ret[2]) {
+            throw takeFromExternrefTable0(ret[1]);
+        }
+        this.__wbg_ptr = ret[0];
+        PuzzlePackFinalization.register(this, this.__wbg_ptr, this);
+        return this;
+    }
+    /**
+     * @returns {string}
+     */
+    get title() {
+        let deferred1_0;
+        let deferred1_1;
+        try {
+            const ret = wasm.puzzlepack_title(this.__wbg_ptr);
+            deferred1_0 = ret[0];
+            deferred1_1 = ret[1];
+            return getStringFromWasm0(ret[0], ret[1]);
+        } finally {
+            wasm.__wbindgen_free(deferred1_0, deferred1_1, 1);
+        }
+    }
+    /**
+     * Checks that every board has exactly one solution, and that the solution given with an
+     * entry, if any, keeps the rules and is that one. Returns whether every entry `passed`,
+     * and per entry whether it passed and what failed.
+     * @returns {string}
+     */
+    verify() {
+        let deferred2_0;
+        let deferred2_1;
+        try {
+            const ret = wasm.puzzlepack_verify(this.__wbg_ptr);
+            var ptr1 = ret[0];
+            var len1 = ret[1];
+            if (ret[3]) {
+                ptr1 = 0; len1 = 0;
+                throw takeFromExternrefTable0(ret[2]);
+            }
+            deferred2_0 = ptr1;
+            deferred2_1 = len1;
+            return getStringFromWasm0(ptr1, len1);
+        } finally {
+            wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+        }
+    }
+}
+if (Symbol.dispose) PuzzlePack.prototype[Symbol.dispose] = PuzzlePack.prototype.free;
+
+/**
+ * The solutions of a game, found one at a time as they are asked for.
+ *
+ * Every call goes on with the same backtracking search from the solution found last, so asking
+ * for a few solutions only pays for those, and the solutions come in the order the search finds
+ * them, each once. The iterator owns its search, which is freed once the solutions run out or it
+ * is dropped, so other calls cannot disturb it.
+ */
+export class SolutionIterator {
+    __destroy_into_raw() {
+        const ptr = this.__wbg_ptr;
+        this.__wbg_ptr = 0;
+        SolutionIteratorFinalization.unregister(this);
+        return ptr;
+    }
+    free() {
+        const ptr = this.__destroy_into_raw();
+        wasm.__wbg_solutioniterator_free(ptr, 0);
+    }
+    /**
+     * An iterator over the solutions of the game. Nothing is searched until `next`.
+     * @param {string} game_json
+     */
+    constructor(game_json) {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.solutioniterator_new(ptr0, len0);
+        if (ret[2]) {
+            throw takeFromExternrefTable0(ret[1]);
+        }
+        this.__wbg_ptr = ret[0];
+        SolutionIteratorFinalization.register(this, this.__wbg_ptr, this);
+        return this;
+    }
+    /**
+     * The next solution as JSON, a list of its cells sorted, or `None` once there are no more.
+     * @returns {string | undefined}
+     */
+    next() {
+        const ret = wasm.solutioniterator_next(this.__wbg_ptr);
+        if (ret[3]) {
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        let v1;
+        if (ret[0] !== 0) {
+            v1 = getStringFromWasm0(ret[0], ret[1]);
+            wasm.__wbindgen_free(ret[0], ret[1] * 1, 1);
+        }
+        return v1;
+    }
+}
+if (Symbol.dispose) SolutionIterator.prototype[Symbol.dispose] = SolutionIterator.prototype.free;
+
+/**
+ * A solve run a chunk of nodes at a time, so that a page can show progress, or give up, without
+ * blocking on one long search.
+ *
+ * Every chunk takes the search up exactly where the last one paused, with the same stack,
+ * nogoods, and statistics, so solving in chunks finds the same solution after the same number
+ * of nodes as solving in one go. Only backtracking can pause, so the options must leave out
+ * backjumping, restarts, and a node budget of their own, and name no other algorithm.
+ */
+export class SolveSession {
+    __destroy_into_raw() {
+        const ptr = this.__wbg_ptr;
+        this.__wbg_ptr = 0;
+        SolveSessionFinalization.unregister(this);
+        return ptr;
+    }
+    free() {
+        const ptr = this.__destroy_into_raw();
+        wasm.__wbg_solvesession_free(ptr, 0);
+    }
+    /**
+     * Searches for up to `extra_nodes` more nodes, at least one, returning what
+     * `solve_with_options` would with the statistics of every chunk so far. The status stays
+     * `unknown` until the search finds a solution or proves there is none, after which calling
+     * again returns the same result without searching.
+     * @param {number} extra_nodes
+     * @returns {string}
+     */
+    continue_solve(extra_nodes) {
+        let deferred2_0;
+        let deferred2_1;
+        try {
+            const ret = wasm.solvesession_continue_solve(this.__wbg_ptr, extra_nodes);
+            var ptr1 = ret[0];
+            var len1 = ret[1];
+            if (ret[3]) {
+                ptr1 = 0; len1 = 0;
+                throw takeFromExternrefTable0(ret[2]);
+            }
+            deferred2_0 = ptr1;
+            deferred2_1 = len1;
+            return getStringFromWasm0(ptr1, len1);
+        } finally {
+            wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+        }
+    }
+    /**
+     * A session solving the game, tuned by `options_json` as for `solve_with_options`. Nothing
+     * is searched until `continue_solve`.
+     * @param {string} game_json
+     * @param {string} options_json
+     */
+    constructor(game_json, options_json) {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(options_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.solvesession_new(ptr0, len0, ptr1, len1);
+        if (ret[2]) {
+            throw takeFromExternrefTable0(ret[1]);
+        }
+        this.__wbg_ptr = ret[0];
+        SolveSessionFinalization.register(this, this.__wbg_ptr, this);
+        return this;
+    }
+    /**
+     * Changes the options of the session. The paused search only carries on if nothing but
+     * `verify` and `order` changed, since it would not visit the same nodes under any other
+     * options: it starts over, statistics and all, otherwise.
+     * @param {string} options_json
+     */
+    set_options(options_json) {
+        const ptr0 = passStringToWasm0(options_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.solvesession_set_options(this.__wbg_ptr, ptr0, len0);
+        if (ret[1]) {
+            throw takeFromExternrefTable0(ret[0]);
+        }
+    }
+}
+if (Symbol.dispose) SolveSession.prototype[Symbol.dispose] = SolveSession.prototype.free;
+
+/**
+ * Solves every game of `corpus_jsonl`, one per line, under each configuration of `configs_json`,
+ * a list of `{"name", "options"}` with options as for `solve_with_options`. With `null` the
+ * nogood cache, forward checking, and candidate ordering are each turned off alone and then
+ * together, the nogoods are kept in a hash set instead or screened by signature, and the
+ * heuristics are left to `"auto"`, every run giving up after two million nodes. Each solve is repeated `repeats` times
+ * to take the fastest. Returns per configuration the boards solved and given up on, the total
+ * nodes and milliseconds, and the boards it settled fastest, followed by every run.
+ * @param {string} corpus_jsonl
+ * @param {string} configs_json
+ * @param {number} repeats
+ * @returns {string}
+ */
+export function benchmark_options(corpus_jsonl, configs_json, repeats) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(corpus_jsonl, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(configs_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.benchmark_options(ptr0, len0, ptr1, len1, repeats);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Counts the solutions through every cell of one unit, given by `unit_json` as `{"row": 0}`,
+ * `{"col": 0}`, or `{"color": 0}`, or with `null` the tightest unit once forced queens are
+ * placed, the first the counter branches on. Returns the `unit` and its `cells`, each with its
+ * `idx` and `count`. The cells counting zero are the ones no solution uses.
+ * @param {string} game_json
+ * @param {string} unit_json
+ * @returns {string}
+ */
+export function branch_census(game_json, unit_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(unit_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.branch_census(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Checks the user's X marks in `state_json`, `{"queens", "marks"}` as lists of cells, against
+ * every solution. Returns the marks that are wrong, since the cell holds a queen in every
+ * solution, those the user's queens already imply, those the deductions from those queens
+ * reach, and the premature rest. Fails when the board has no solution.
+ * @param {string} game_json
+ * @param {string} state_json
+ * @returns {string}
+ */
+export function check_marks(game_json, state_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(state_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.check_marks(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Reports which rotations and reflections map the board, each of its solutions, and its whole
+ * set of solutions onto themselves. A board with several solutions can be symmetric as a set
+ * even when no single solution is.
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function classify_symmetry(game_json) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.classify_symmetry(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Forgets every solve in the activity log.
+ */
+export function clear_activity() {
+    wasm.clear_activity();
+}
+
+/**
+ * Turns the cells of `solution_json`, such as a solution from `solve`, into the points to click
+ * to place their queens, in the same order. `layout_json` gives the bounding box of the cells on
+ * the page, the gap between them, and optionally their size, the device pixel ratio, and whether
+ * queens take a double click. Returns each cell with the device-pixel center of its cell.
+ * @param {string} game_json
+ * @param {string} solution_json
+ * @param {string} layout_json
+ * @returns {string}
+ */
+export function click_plan(game_json, solution_json, layout_json) {
+    let deferred5_0;
+    let deferred5_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(solution_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ptr2 = passStringToWasm0(layout_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len2 = WASM_VECTOR_LEN;
+        const ret = wasm.click_plan(ptr0, len0, ptr1, len1, ptr2, len2);
+        var ptr4 = ret[0];
+        var len4 = ret[1];
+        if (ret[3]) {
+            ptr4 = 0; len4 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred5_0 = ptr4;
+        deferred5_1 = len4;
+        return getStringFromWasm0(ptr4, len4);
+    } finally {
+        wasm.__wbindgen_free(deferred5_0, deferred5_1, 1);
+    }
+}
+
+/**
+ * Lists every rule the queens in `placed_json`, a list of cells, break against each other: each
+ * pair sharing a row, column, or color, or placed on neighboring cells, as `{"first",
+ * "second", "rule"}`. Never looks at the solution, so it gives nothing away.
+ * @param {string} game_json
+ * @param {string} placed_json
+ * @returns {string}
+ */
+export function conflicts(game_json, placed_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(placed_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.conflicts(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Counts every solution of the game.
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function count_solutions(game_json) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.count_solutions(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Counts the solutions of the game up to its symmetries: solutions carried into each other by a
+ * rotation or reflection mapping the board onto itself count once.
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function count_solutions_mod_symmetry(game_json) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.count_solutions_mod_symmetry(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Solves the game like `solve_with_options` and returns a bundle for attaching to bug reports:
+ * the `bundleVersion` of its format, the crate version, features, and whether it is a debug
+ * build, the game normalized with its `boardHash`, the options searched with, every default
+ * spelled out and automatic heuristics replaced by their tier's, the solution, its status,
+ * whether it was `verified` to keep every rule with any `violations`, the statistics, and the
+ * milliseconds taken. Passing the board and options back to `solve_with_options` reproduces the
+ * solve. `solve --bundle out.json` writes the same bundle from the command line.
+ * @param {string} game_json
+ * @param {string} options_json
+ * @returns {string}
+ */
+export function debug_bundle(game_json, options_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(options_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.debug_bundle(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * The JSON the request returned from a reply of `handle_request`, or the error it carries.
+ * @param {Uint8Array} bytes
+ * @returns {string}
+ */
+export function decode_reply(bytes) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passArray8ToWasm0(bytes, wasm.__wbindgen_malloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.decode_reply(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Derives up to `count` practice boards from the game, reproducibly from `seed`: each moves a
+ * few cells between neighboring colors, keeping the only solution and the hardest technique it
+ * takes, then is rotated or reflected and has its colors renamed. Each comes with its solution
+ * and the operations that produced it. No two are isomorphic, nor any to the game. Fails unless
+ * the game has exactly one solution.
+ * @param {string} game_json
+ * @param {number} count
+ * @param {bigint} seed
+ * @returns {string}
+ */
+export function derive_variants(game_json, count, seed) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.derive_variants(ptr0, len0, count, seed);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Looks for signs that scraping `game_json` merged or split regions: a region count off from the
+ * rows, regions of outlying sizes, and regions in pieces. With `rgb_json`, the scraped `[r, g, b]`
+ * of every cell, also proposes fixes, each a list of `{"idx", "color"}` changes judged like
+ * `what_if_recolor_batch`, best first. Boards short of colors are read anyway, as they are what
+ * this is for.
+ * @param {string} game_json
+ * @param {string | null} [rgb_json]
+ * @returns {string}
+ */
+export function diagnose_scrape(game_json, rgb_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        var ptr1 = isLikeNone(rgb_json) ? 0 : passStringToWasm0(rgb_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        var len1 = WASM_VECTOR_LEN;
+        const ret = wasm.diagnose_scrape(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Compares two scrapes of a board, matching the colors of `a` with those of `b` by the most
+ * cells their regions share, so that numbering the same regions differently is not a
+ * difference. Returns the size of each board, the `alignment` giving the color of `b` matched
+ * with every color of `a`, the `cells` whose region differs with their color on each board,
+ * and whether the boards are the `same`.
+ * @param {string} a_json
+ * @param {string} b_json
+ * @returns {string}
+ */
+export function diff_boards(a_json, b_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(a_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(b_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.diff_boards(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Reports which deduction techniques solving the game takes and how often, and whether it
+ * still needs guessing once they run out.
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function difficulty_breakdown(game_json) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.difficulty_breakdown(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Shows which cells of the user's board in `state_json`, `{"queens", "marks"}` as lists of
+ * cells, could still hold a queen once `level` has been applied: `basic` for what the queens
+ * attack and the marks rule out, then `singles`, `confinement`, `pairs`, and `triples`, each
+ * adding its technique to the ones before. Every ruled-out cell names the technique, or mark or
+ * queen, that ruled it out, and a unit left without any spot is named as `emptied`.
+ * @param {string} game_json
+ * @param {string} state_json
+ * @param {string} level
+ * @returns {string}
+ */
+export function domains(game_json, state_json, level) {
+    let deferred5_0;
+    let deferred5_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(state_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ptr2 = passStringToWasm0(level, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len2 = WASM_VECTOR_LEN;
+        const ret = wasm.domains(ptr0, len0, ptr1, len1, ptr2, len2);
+        var ptr4 = ret[0];
+        var len4 = ret[1];
+        if (ret[3]) {
+            ptr4 = 0; len4 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred5_0 = ptr4;
+        deferred5_1 = len4;
+        return getStringFromWasm0(ptr4, len4);
+    } finally {
+        wasm.__wbindgen_free(deferred5_0, deferred5_1, 1);
+    }
+}
+
+/**
+ * Packs a request for a worker running the solver, to post to it as is: `kind` is `"solve"`,
+ * `"hint"`, `"validate"`, or `"count"`, run as `solve_with_options` (or `solve` when
+ * `options_json` is empty), `hints` for the first hint (`options_json` then being the board
+ * state, the empty board when empty), `validate_game`, and `count_solutions`. The envelope starts
+ * with a protocol version, so that a worker from another build answers with an error instead of
+ * misreading it.
+ * @param {string} kind
+ * @param {string} game_json
+ * @param {string} options_json
+ * @returns {Uint8Array}
+ */
+export function encode_request(kind, game_json, options_json) {
+    const ptr0 = passStringToWasm0(kind, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+    const len0 = WASM_VECTOR_LEN;
+    const ptr1 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+    const len1 = WASM_VECTOR_LEN;
+    const ptr2 = passStringToWasm0(options_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+    const len2 = WASM_VECTOR_LEN;
+    const ret = wasm.encode_request(ptr0, len0, ptr1, len1, ptr2, len2);
+    if (ret[3]) {
+        throw takeFromExternrefTable0(ret[2]);
+    }
+    var v4 = getArrayU8FromWasm0(ret[0], ret[1]).slice();
+    wasm.__wbindgen_free(ret[0], ret[1] * 1, 1);
+    return v4;
+}
+
+/**
+ * Estimates the number of solutions of boards too large to count, from `probes` random paths
+ * down the counting search's tree. The result holds the estimate with its variance and standard
+ * error, which are only rough guides since the estimate can be far off with few probes.
+ * @param {string} game_json
+ * @param {number} probes
+ * @param {number} seed
+ * @returns {string}
+ */
+export function estimate_solution_count(game_json, probes, seed) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.estimate_solution_count(ptr0, len0, probes, seed);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Lists the boards of `corpus_jsonl`, one game per line, that are the game rotated or reflected
+ * with its colors renamed, and how each maps onto it. Looking up several games in the same
+ * corpus indexes it only once.
+ * @param {string} corpus_jsonl
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function find_duplicates(corpus_jsonl, game_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(corpus_jsonl, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.find_duplicates(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Points at one place `technique`, one of `singles`, `confinement`, `pairs`, and `triples`,
+ * applies to the user's board in `state_json`, `{"queens", "marks"}` as lists of cells, right
+ * now, without making it or any other deduction. Comes back `found` with the cells the
+ * reasoning rests on, the `units` it is about, and the `queen` it places or `eliminations` it
+ * makes; `absent` if the technique applies nowhere, even if an easier one does; or
+ * `contradiction` if no solution agrees with the board.
+ * @param {string} game_json
+ * @param {string} state_json
+ * @param {string} technique
+ * @returns {string}
+ */
+export function find_technique(game_json, state_json, technique) {
+    let deferred5_0;
+    let deferred5_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(state_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ptr2 = passStringToWasm0(technique, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len2 = WASM_VECTOR_LEN;
+        const ret = wasm.find_technique(ptr0, len0, ptr1, len1, ptr2, len2);
+        var ptr4 = ret[0];
+        var len4 = ret[1];
+        if (ret[3]) {
+            ptr4 = 0; len4 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred5_0 = ptr4;
+        deferred5_1 = len4;
+        return getStringFromWasm0(ptr4, len4);
+    } finally {
+        wasm.__wbindgen_free(deferred5_0, deferred5_1, 1);
+    }
+}
+
+/**
+ * Reads a board pasted in any format the crate knows, working out which from the input: game
+ * JSON as taken by `solve` (`game`), the same with `regions` listing the cells of every color
+ * (`regions`), LinkedIn's puzzle data with its `gridSize` and `colorGrid` (`linkedin`), or an
+ * ASCII grid of one letter or digit per cell, cells with the same symbol sharing a region
+ * (`grid`). Returns the `format` found and the `game`, normalized as by `normalize_game`. Fails
+ * when no format or several recognize the input, naming every format tried.
+ * @param {string} input
+ * @returns {string}
+ */
+export function game_from_any(input) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(input, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.game_from_any(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Generates up to `count` square boards of `rows` rows for a lesson on `technique`, one of
+ * `singles`, `confinement`, `pairs`, `triples`, and `guessing`, reproducibly from `seed`. Every
+ * board has one solution and takes the technique but nothing harder: deductions up to it solve
+ * the board, or stall for `guessing`, while those below it alone stall. Each comes with its
+ * solution and, as proof, its `difficulty` as from `difficulty_breakdown`. Fewer come back when
+ * the boards grown run out, `attempts` telling how many were.
+ * @param {number} rows
+ * @param {string} technique
+ * @param {number} count
+ * @param {bigint} seed
+ * @returns {string}
+ */
+export function generate_lesson(rows, technique, count, seed) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(technique, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.generate_lesson(rows, ptr0, len0, count, seed);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Replays the user's moves in `events_json`, a list of `{"kind", "cell", "time"}` with `kind`
+ * one of `place`, `remove`, `mark`, and `unmark`, for a review of the game. Grades each queen
+ * and mark put down as `forced`, `safe`, `guess`, or `mistake` against the board it went on, or
+ * `unsolvable` once no solution agreed with the board, flags the queens later taken off as
+ * wasted, and gives the first event leaving the board unsolvable with a summary of the counts
+ * and accuracy. Events that do not fit the board come back with a warning and are skipped.
+ * Fails when the board has no solution.
+ * @param {string} game_json
+ * @param {string} events_json
+ * @returns {string}
+ */
+export function grade_replay(game_json, events_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(events_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.grade_replay(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Runs a request from `encode_request` inside the worker and packs the reply to post back, for
+ * `decode_reply` on the other side. Never fails: requests of another protocol version, cut
+ * short, or of a kind this build does not know get a reply carrying the error.
+ * @param {Uint8Array} bytes
+ * @returns {Uint8Array}
+ */
+export function handle_request(bytes) {
+    const ptr0 = passArray8ToWasm0(bytes, wasm.__wbindgen_malloc);
+    const len0 = WASM_VECTOR_LEN;
+    const ret = wasm.handle_request(ptr0, len0);
+    var v2 = getArrayU8FromWasm0(ret[0], ret[1]).slice();
+    wasm.__wbindgen_free(ret[0], ret[1] * 1, 1);
+    return v2;
+}
+
+/**
+ * Suggests up to `n` different next moves for the user's board in `state_json`, `{"queens",
+ * "marks"}` as lists of cells, simplest first: forced singles, then eliminations by confinement,
+ * pairs, and triples, then safe cells that take lookahead. Each names its technique, the queen
+ * to place or cells to mark, and the cells its reasoning rests on. Empty when no solution agrees
+ * with the state.
+ * @param {string} game_json
+ * @param {string} state_json
+ * @param {number} n
+ * @returns {string}
+ */
+export function hints(game_json, state_json, n) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(state_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.hints(ptr0, len0, ptr1, len1, n);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Whether board `b` is board `a` rotated or reflected, with its colors renamed. Returns the
+ * transform and color renaming carrying `a` onto `b`, or `null`.
+ * @param {string} a_json
+ * @param {string} b_json
+ * @returns {string}
+ */
+export function is_isomorphic(a_json, b_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(a_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(b_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.is_isomorphic(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Writes the boards of `games_json`, a JSON array of games as taken by `solve`, as a pack file
+ * titled `title` for `PuzzlePack`, working out the difficulty, hash, and solution of each. Fails
+ * on a board without exactly one solution, which a pack has no use for.
+ * @param {string} title
+ * @param {string} games_json
+ * @returns {string}
+ */
+export function make_pack(title, games_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(title, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(games_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.make_pack(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Makes a drill from a deduction using `technique`, one of `singles`, `confinement`, `pairs`,
+ * and `triples`, among those solving the game, picked reproducibly from `seed`. Comes back
+ * `ready` with the `position` just before it, `{"queens", "marks"}` as taken by `hints`, and the
+ * `answer` as from `find_technique` to check the user's against, along with which `deduction`
+ * it is out of how many `deductions`; or `unused` if solving the game never takes the technique.
+ * @param {string} game_json
+ * @param {string} technique
+ * @param {bigint} seed
+ * @returns {string}
+ */
+export function make_quiz(game_json, technique, seed) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(technique, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.make_quiz(ptr0, len0, ptr1, len1, seed);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Finds the two solutions of the game differing in the most cells among up to `cap` of them,
+ * at least 2, with the `distance` between them, the cells only each holds, how many solutions
+ * were `compared`, and whether they were all of them. The `status` is `multiple`, or `unique`
+ * with the only solution, or `unsolvable`.
+ * @param {string} game_json
+ * @param {number} cap
+ * @returns {string}
+ */
+export function max_diff_solutions(game_json, cap) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.max_diff_solutions(ptr0, len0, cap);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Finds the fewest queens to give away so that deductions with only the techniques listed in
+ * `techniques_json`, such as `["singles", "confinement"]`, finish the board. Returns the givens
+ * with the deductions that follow them.
+ * @param {string} game_json
+ * @param {string} techniques_json
+ * @returns {string}
+ */
+export function minimal_hints(game_json, techniques_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(techniques_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.minimal_hints(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Finds the solution closest to the queens in `placed_json`, a list of cells, with the user's
+ * queens to remove and the queens to add to reach it. Ties between solutions are broken the
+ * same way every time, so the correction is stable. Boards with many solutions are only
+ * compared against some of them, as `exact` tells.
+ * @param {string} game_json
+ * @param {string} placed_json
+ * @returns {string}
+ */
+export function nearest_solution(game_json, placed_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(placed_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.nearest_solution(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Rewrites `game_json` in one canonical form, for hashing, caching, or comparing boards: sizes
+ * filled in, unknown fields dropped, colors renamed by first appearance, neighbor lists and
+ * links sorted, and keys in a fixed order. Normalizing the result again returns it unchanged.
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function normalize_game(game_json) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.normalize_game(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Evens out the sizes of the colors, reproducibly from `seed`, by moving cells on their borders
+ * from larger colors to smaller ones until the largest has at most `target_spread` cells more
+ * than the smallest. The cells of the solution never move, and a move is only kept if the board
+ * stays valid, in one piece, and with the same only solution. Returns the `game`, the `shifts`
+ * made, the `spread` reached, and whether it `met` the target. Fails unless the game has exactly
+ * one solution.
+ * @param {string} game_json
+ * @param {bigint} seed
+ * @param {number} target_spread
+ * @returns {string}
+ */
+export function rebalance(game_json, seed, target_spread) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.rebalance(ptr0, len0, seed, target_spread);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Lists the last solves made by `solve`, `solve_with_options`, and finished `SolveSession`s,
+ * oldest first, for diagnosing slowness: for each the entry point, a hash of the board, its
+ * size, the status, nodes, and milliseconds, and the algorithm, heuristics tier, and a hash of
+ * the options. Kept in memory only, 32 solves unless `set_activity_capacity` says otherwise.
+ * @returns {string}
+ */
+export function recent_activity() {
+    let deferred1_0;
+    let deferred1_1;
+    try {
+        const ret = wasm.recent_activity();
+        deferred1_0 = ret[0];
+        deferred1_1 = ret[1];
+        return getStringFromWasm0(ret[0], ret[1]);
+    } finally {
+        wasm.__wbindgen_free(deferred1_0, deferred1_1, 1);
+    }
+}
+
+/**
+ * Describes where the cells of every color lie: their rows, columns, and bounding box, and
+ * whether they are confined to one or two rows or columns. A quick analysis for overlays, which
+ * does not search.
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function region_analysis(game_json) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.region_analysis(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Grows the square game by a row and a column at the corner `direction`, one of `topLeft`,
+ * `topRight`, `bottomLeft`, and `bottomRight`, reproducibly from `seed`. The new cells mostly
+ * extend the colors beside them, and a stretch of them makes up the new color. Returns the new
+ * `game` with its only `solution`, or fails when no extension tried has exactly one solution.
+ * @param {string} game_json
+ * @param {string} direction
+ * @param {bigint} seed
+ * @returns {string}
+ */
+export function resize(game_json, direction, seed) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(direction, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.resize(ptr0, len0, ptr1, len1, seed);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
 
-let cachedUint8ArrayMemory0 = null;
+/**
+ * Checks that the build answers right: solves every board of the corpus bundled with the crate
+ * like `solve` and counts its solutions, comparing against the known answers and checking every
+ * solution against the rules. Returns whether everything `passed`, the crate `version` and
+ * optional `features` of the build, and per board whether it passed, what failed, and the
+ * milliseconds taken. Takes a few milliseconds.
+ * @returns {string}
+ */
+export function run_self_tests() {
+    let deferred2_0;
+    let deferred2_1;
+    try {
+        const ret = wasm.run_self_tests();
+        var ptr1 = ret[0];
+        var len1 = ret[1];
+        if (ret[3]) {
+            ptr1 = 0; len1 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred2_0 = ptr1;
+        deferred2_1 = len1;
+        return getStringFromWasm0(ptr1, len1);
+    } finally {
+        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
+    }
+}
+
+/**
+ * Lists the empty cells of the user's board in `state_json`, `{"queens", "marks"}` as lists of
+ * cells, that hold a queen in every solution agreeing with those queens and marks. Each comes
+ * with whether filling in singles from the state finds it. Meant to run after every move: the
+ * solutions of the board are kept between calls.
+ * @param {string} game_json
+ * @param {string} state_json
+ * @returns {string}
+ */
+export function safe_cells(game_json, state_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(state_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.safe_cells(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Keeps at most `capacity` solves in the activity log, dropping the oldest beyond that, or none
+ * with a capacity of 0.
+ * @param {number} capacity
+ */
+export function set_activity_capacity(capacity) {
+    wasm.set_activity_capacity(capacity);
+}
+
+/**
+ * Shrinks the square game by the row and column at the corner `direction`, as for `resize`,
+ * keeping most queens of its solution and the colors around them, and giving the cells cut off
+ * from their color to a neighboring one. Returns the new `game` with its only `solution`, or fails when no reduction
+ * tried has exactly one solution.
+ * @param {string} game_json
+ * @param {string} direction
+ * @param {bigint} seed
+ * @returns {string}
+ */
+export function shrink(game_json, direction, seed) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(direction, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.shrink(ptr0, len0, ptr1, len1, seed);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function solve(game_json) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.solve(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Solves the game with queens on every cell of `include_json` and none on `exclude_json`, both
+ * lists of cells. Returns the `status`, `solved` or `unsolvable`, and the `solution` with its
+ * cells sorted. When the constraints contradict each other the status is `unsolvable` without
+ * any search, listing the cells both included and excluded as `overlap` and the rules the
+ * included cells break together as `conflicts`.
+ * @param {string} game_json
+ * @param {string} include_json
+ * @param {string} exclude_json
+ * @returns {string}
+ */
+export function solve_constrained(game_json, include_json, exclude_json) {
+    let deferred5_0;
+    let deferred5_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(include_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ptr2 = passStringToWasm0(exclude_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len2 = WASM_VECTOR_LEN;
+        const ret = wasm.solve_constrained(ptr0, len0, ptr1, len1, ptr2, len2);
+        var ptr4 = ret[0];
+        var len4 = ret[1];
+        if (ret[3]) {
+            ptr4 = 0; len4 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred5_0 = ptr4;
+        deferred5_1 = len4;
+        return getStringFromWasm0(ptr4, len4);
+    } finally {
+        wasm.__wbindgen_free(deferred5_0, deferred5_1, 1);
+    }
+}
+
+/**
+ * Solves the game like `solve`, tuned by `options_json`, returning the solution together with
+ * statistics about the search.
+ * @param {string} game_json
+ * @param {string} options_json
+ * @returns {string}
+ */
+export function solve_with_options(game_json, options_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(options_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.solve_with_options(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Grid-searches the `orderingWeights` of `solve_with_options` for the fewest search nodes over
+ * the games of `corpus_jsonl`, one per line, with the other options left at their defaults.
+ * Returns the best table as `weights`, ready to pass as `orderingWeights`, with its total
+ * `nodes`, the `baselineNodes` without weights, the boards still given up on, and how many
+ * tables were tried.
+ * @param {string} corpus_jsonl
+ * @returns {string}
+ */
+export function tune_ordering_weights(corpus_jsonl) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(corpus_jsonl, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.tune_ordering_weights(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Checks the game like every other entry point does, and lists what is suspicious about it
+ * without breaking the rules: under `splitRegions`, every color whose cells fall apart into
+ * several pieces through shared edges, with the cells of each piece for highlighting. A game
+ * with `"strict": true` is rejected for those instead.
+ * @param {string} game_json
+ * @returns {string}
+ */
+export function validate_game(game_json) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.validate_game(ptr0, len0);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Spells out a way from the user's board in `state_json`, `{"queens", "marks"}` as lists of
+ * cells, to a solution agreeing with it, one step at a time. Each step names its technique, a
+ * key and arguments for the sentence describing it, the queen it places or cells it marks, and
+ * the cells its reasoning rests on. Deductions come first; when they run out the step tries a
+ * queen of the solution, with whether deductions alone refute each alternative spot.
+ * @param {string} game_json
+ * @param {string} state_json
+ * @returns {string}
+ */
+export function walkthrough(game_json, state_json) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(state_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.walkthrough(ptr0, len0, ptr1, len1);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Previews placing a queen on cell `idx` of the user's board in `state_json`, `{"queens",
+ * "marks"}` as lists of cells, without changing it: the queens singles would force, the cells
+ * deductions up to triples would newly rule out and why, and whether that leaves a row, column,
+ * or color without any spot. A cell the queens already hold or attack comes back `illegal` with
+ * the reason. With `deep`, also searches for whether a solution still agrees with the board.
+ * @param {string} game_json
+ * @param {string} state_json
+ * @param {number} idx
+ * @param {boolean} deep
+ * @returns {string}
+ */
+export function what_if(game_json, state_json, idx, deep) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(state_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.what_if(ptr0, len0, ptr1, len1, idx, deep);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+
+/**
+ * Judges the game with cell `idx` moved to color `new_color`: `invalid` with the reason, or
+ * `unsolvable`, `unique` with the solution, or `multiple`. With `contiguous`, a color split into
+ * pieces makes the board invalid.
+ * @param {string} game_json
+ * @param {number} idx
+ * @param {number} new_color
+ * @param {boolean} contiguous
+ * @returns {string}
+ */
+export function what_if_recolor(game_json, idx, new_color, contiguous) {
+    let deferred3_0;
+    let deferred3_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ret = wasm.what_if_recolor(ptr0, len0, idx, new_color, contiguous);
+        var ptr2 = ret[0];
+        var len2 = ret[1];
+        if (ret[3]) {
+            ptr2 = 0; len2 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred3_0 = ptr2;
+        deferred3_1 = len2;
+        return getStringFromWasm0(ptr2, len2);
+    } finally {
+        wasm.__wbindgen_free(deferred3_0, deferred3_1, 1);
+    }
+}
+
+/**
+ * Like `what_if_recolor` for several tentative edits, each a list of `{"idx", "color"}` changes
+ * applied together, judged one by one against the unchanged game. Returns a verdict per edit.
+ * @param {string} game_json
+ * @param {string} edits_json
+ * @param {boolean} contiguous
+ * @returns {string}
+ */
+export function what_if_recolor_batch(game_json, edits_json, contiguous) {
+    let deferred4_0;
+    let deferred4_1;
+    try {
+        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len0 = WASM_VECTOR_LEN;
+        const ptr1 = passStringToWasm0(edits_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+        const len1 = WASM_VECTOR_LEN;
+        const ret = wasm.what_if_recolor_batch(ptr0, len0, ptr1, len1, contiguous);
+        var ptr3 = ret[0];
+        var len3 = ret[1];
+        if (ret[3]) {
+            ptr3 = 0; len3 = 0;
+            throw takeFromExternrefTable0(ret[2]);
+        }
+        deferred4_0 = ptr3;
+        deferred4_1 = len3;
+        return getStringFromWasm0(ptr3, len3);
+    } finally {
+        wasm.__wbindgen_free(deferred4_0, deferred4_1, 1);
+    }
+}
+function __wbg_get_imports() {
+    const import0 = {
+        __proto__: null,
+        __wbg___wbindgen_throw_41e9ee4f547fc59a: function(arg0, arg1) {
+            throw new Error(getStringFromWasm0(arg0, arg1));
+        },
+        __wbg_error_757e9472f8410341: function(arg0, arg1) {
+            let deferred0_0;
+            let deferred0_1;
+            try {
+                deferred0_0 = arg0;
+                deferred0_1 = arg1;
+                console.error(getStringFromWasm0(arg0, arg1));
+            } finally {
+                wasm.__wbindgen_free(deferred0_0, deferred0_1, 1);
+            }
+        },
+        __wbg_new_227d7c05414eb861: function() {
+            const ret = new Error();
+            return ret;
+        },
+        __wbg_now_73a197c6281b97e9: function() {
+            const ret = performance.now();
+            return ret;
+        },
+        __wbg_stack_3b0d974bbf31e44f: function(arg0, arg1) {
+            const ret = arg1.stack;
+            const ptr1 = passStringToWasm0(ret, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+            const len1 = WASM_VECTOR_LEN;
+            getDataViewMemory0().setInt32(arg0 + 4 * 1, len1, true);
+            getDataViewMemory0().setInt32(arg0 + 4 * 0, ptr1, true);
+        },
+        __wbg_warn_2073bac08d473bd7: function(arg0, arg1) {
+            console.warn(getStringFromWasm0(arg0, arg1));
+        },
+        __wbindgen_generic_0000000000000001: function(arg0, arg1) {
+            // Cast intrinsic for `Ref(String) -> Externref`.
+            const ret = getStringFromWasm0(arg0, arg1);
+            return ret;
+        },
+        __wbindgen_init_externref_table: function() {
+            const table = wasm.__wbindgen_externrefs;
+            const offset = table.grow(4);
+            table.set(0, undefined);
+            table.set(offset + 0, undefined);
+            table.set(offset + 1, null);
+            table.set(offset + 2, true);
+            table.set(offset + 3, false);
+        },
+    };
+    return {
+        __proto__: null,
+        "./solver_bg.js": import0,
+    };
+}
+
+const BoardEditorFinalization = (typeof FinalizationRegistry === 'undefined')
+    ? { register: () => {}, unregister: () => {} }
+    : new FinalizationRegistry(ptr => wasm.__wbg_boardeditor_free(ptr, 1));
+const PuzzlePackFinalization = (typeof FinalizationRegistry === 'undefined')
+    ? { register: () => {}, unregister: () => {} }
+    : new FinalizationRegistry(ptr => wasm.__wbg_puzzlepack_free(ptr, 1));
+const SolutionIteratorFinalization = (typeof FinalizationRegistry === 'undefined')
+    ? { register: () => {}, unregister: () => {} }
+    : new FinalizationRegistry(ptr => wasm.__wbg_solutioniterator_free(ptr, 1));
+const SolveSessionFinalization = (typeof FinalizationRegistry === 'undefined')
+    ? { register: () => {}, unregister: () => {} }
+    : new FinalizationRegistry(ptr => wasm.__wbg_solvesession_free(ptr, 1));
+
+function getArrayU8FromWasm0(ptr, len) {
+    ptr = ptr >>> 0;
+    return getUint8ArrayMemory0().subarray(ptr / 1, ptr / 1 + len);
+}
+
+let cachedDataViewMemory0 = null;
+function getDataViewMemory0() {
+    if (cachedDataViewMemory0 === null || cachedDataViewMemory0.buffer.detached === true || (cachedDataViewMemory0.buffer.detached === undefined && cachedDataViewMemory0.buffer !== wasm.memory.buffer)) {
+        cachedDataViewMemory0 = new DataView(wasm.memory.buffer);
+    }
+    return cachedDataViewMemory0;
+}
+
+function getStringFromWasm0(ptr, len) {
+    return decodeText(ptr >>> 0, len);
+}
 
+let cachedUint8ArrayMemory0 = null;
 function getUint8ArrayMemory0() {
     if (cachedUint8ArrayMemory0 === null || cachedUint8ArrayMemory0.byteLength === 0) {
         cachedUint8ArrayMemory0 = new Uint8Array(wasm.memory.buffer);
@@ -11,23 +1936,18 @@ function getUint8ArrayMemory0() {
     return cachedUint8ArrayMemory0;
 }
 
-const cachedTextEncoder = (typeof TextEncoder !== 'undefined' ? new TextEncoder('utf-8') : { encode: () => { throw Error('TextEncoder not available') } } );
+function isLikeNone(x) {
+    return x === undefined || x === null;
+}
 
-const encodeString = (typeof cachedTextEncoder.encodeInto === 'function'
-    ? function (arg, view) {
-    return cachedTextEncoder.encodeInto(arg, view);
+function passArray8ToWasm0(arg, malloc) {
+    const ptr = malloc(arg.length * 1, 1) >>> 0;
+    getUint8ArrayMemory0().set(arg, ptr / 1);
+    WASM_VECTOR_LEN = arg.length;
+    return ptr;
 }
-    : function (arg, view) {
-    const buf = cachedTextEncoder.encode(arg);
-    view.set(buf);
-    return {
-        read: arg.length,
-        written: buf.length
-    };
-});
 
 function passStringToWasm0(arg, malloc, realloc) {
-
     if (realloc === undefined) {
         const buf = cachedTextEncoder.encode(arg);
         const ptr = malloc(buf.length, 1) >>> 0;
@@ -48,14 +1968,13 @@ function passStringToWasm0(arg, malloc, realloc) {
         if (code > 0x7F) break;
         mem[ptr + offset] = code;
     }
-
     if (offset !== len) {
         if (offset !== 0) {
             arg = arg.slice(offset);
         }
         ptr = realloc(ptr, len, len = offset + arg.length * 3, 1) >>> 0;
         const view = getUint8ArrayMemory0().subarray(ptr + offset, ptr + len);
-        const ret = encodeString(arg, view);
+        const ret = cachedTextEncoder.encodeInto(arg, view);
 
         offset += ret.written;
         ptr = realloc(ptr, len, offset, 1) >>> 0;
@@ -65,132 +1984,96 @@ function passStringToWasm0(arg, malloc, realloc) {
     return ptr;
 }
 
-const cachedTextDecoder = (typeof TextDecoder !== 'undefined' ? new TextDecoder('utf-8', { ignoreBOM: true, fatal: true }) : { decode: () => { throw Error('TextDecoder not available') } } );
-
-if (typeof TextDecoder !== 'undefined') { cachedTextDecoder.decode(); };
+function takeFromExternrefTable0(idx) {
+    const value = wasm.__wbindgen_externrefs.get(idx);
+    wasm.__externref_table_dealloc(idx);
+    return value;
+}
 
-function getStringFromWasm0(ptr, len) {
-    ptr = ptr >>> 0;
+let cachedTextDecoder = new TextDecoder('utf-8', { ignoreBOM: true, fatal: true });
+cachedTextDecoder.decode();
+const MAX_SAFARI_DECODE_BYTES = 2146435072;
+let numBytesDecoded = 0;
+function decodeText(ptr, len) {
+    numBytesDecoded += len;
+    if (numBytesDecoded >= MAX_SAFARI_DECODE_BYTES) {
+        cachedTextDecoder = new TextDecoder('utf-8', { ignoreBOM: true, fatal: true });
+        cachedTextDecoder.decode();
+        numBytesDecoded = len;
+    }
     return cachedTextDecoder.decode(getUint8ArrayMemory0().subarray(ptr, ptr + len));
 }
-/**
- * @param {string} game_json
- * @returns {string}
- */
-export function solve(game_json) {
-    let deferred2_0;
-    let deferred2_1;
-    try {
-        const ptr0 = passStringToWasm0(game_json, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
-        const len0 = WASM_VECTOR_LEN;
-        const ret = wasm.solve(ptr0, len0);
-        deferred2_0 = ret[0];
-        deferred2_1 = ret[1];
-        return getStringFromWasm0(ret[0], ret[1]);
-    } finally {
-        wasm.__wbindgen_free(deferred2_0, deferred2_1, 1);
-    }
+
+const cachedTextEncoder = new TextEncoder();
+
+if (!('encodeInto' in cachedTextEncoder)) {
+    cachedTextEncoder.encodeInto = function (arg, view) {
+        const buf = cachedTextEncoder.encode(arg);
+        view.set(buf);
+        return {
+            read: arg.length,
+            written: buf.length
+        };
+    };
 }
 
-let cachedDataViewMemory0 = null;
+let WASM_VECTOR_LEN = 0;
 
-function getDataViewMemory0() {
-    if (cachedDataViewMemory0 === null || cachedDataViewMemory0.buffer.detached === true || (cachedDataViewMemory0.buffer.detached === undefined && cachedDataViewMemory0.buffer !== wasm.memory.buffer)) {
-        cachedDataViewMemory0 = new DataView(wasm.memory.buffer);
-    }
-    return cachedDataViewMemory0;
+let wasmModule, wasmInstance, wasm;
+function __wbg_finalize_init(instance, module) {
+    wasmInstance = instance;
+    wasm = instance.exports;
+    wasmModule = module;
+    cachedDataViewMemory0 = null;
+    cachedUint8ArrayMemory0 = null;
+    wasm.__wbindgen_start();
+    return wasm;
 }
 
 async function __wbg_load(module, imports) {
     if (typeof Response === 'function' && module instanceof Response) {
+        if (!module.ok) {
+            throw new Error(`failed to fetch Wasm: ${module.status} ${module.statusText} fetching '${module.url}'`);
+        }
+
         if (typeof WebAssembly.instantiateStreaming === 'function') {
             try {
                 return await WebAssembly.instantiateStreaming(module, imports);
-
             } catch (e) {
-                if (module.headers.get('Content-Type') != 'application/wasm') {
+                const validResponse = expectedResponseType(module.type);
+
+                if (validResponse && module.headers.get('Content-Type') !== 'application/wasm') {
                     console.warn("`WebAssembly.instantiateStreaming` failed because your server does not serve Wasm with `application/wasm` MIME type. Falling back to `WebAssembly.instantiate` which is slower. Original error:\n", e);
 
-                } else {
-                    throw e;
-                }
+                } else { throw e; }
             }
         }
 
         const bytes = await module.arrayBuffer();
         return await WebAssembly.instantiate(bytes, imports);
-
     } else {
         const instance = await WebAssembly.instantiate(module, imports);
 
         if (instance instanceof WebAssembly.Instance) {
             return { instance, module };
-
         } else {
             return instance;
         }
     }
-}
 
-function __wbg_get_imports() {
-    const imports = {};
-    imports.wbg = {};
-    imports.wbg.__wbg_new_abda76e883ba8a5f = function() {
-        const ret = new Error();
-        return ret;
-    };
-    imports.wbg.__wbg_stack_658279fe44541cf6 = function(arg0, arg1) {
-        const ret = arg1.stack;
-        const ptr1 = passStringToWasm0(ret, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
-        const len1 = WASM_VECTOR_LEN;
-        getDataViewMemory0().setInt32(arg0 + 4 * 1, len1, true);
-        getDataViewMemory0().setInt32(arg0 + 4 * 0, ptr1, true);
-    };
-    imports.wbg.__wbg_error_f851667af71bcfc6 = function(arg0, arg1) {
-        let deferred0_0;
-        let deferred0_1;
-        try {
-            deferred0_0 = arg0;
-            deferred0_1 = arg1;
-            console.error(getStringFromWasm0(arg0, arg1));
-        } finally {
-            wasm.__wbindgen_free(deferred0_0, deferred0_1, 1);
+    function expectedResponseType(type) {
+        switch (type) {
+            case 'basic': case 'cors': case 'default': return true;
         }
-    };
-    imports.wbg.__wbindgen_init_externref_table = function() {
-        const table = wasm.__wbindgen_export_0;
-        const offset = table.grow(4);
-        table.set(0, undefined);
-        table.set(offset + 0, undefined);
-        table.set(offset + 1, null);
-        table.set(offset + 2, true);
-        table.set(offset + 3, false);
-        ;
-    };
-
-    return imports;
-}
-
-function __wbg_init_memory(imports, memory) {
-
-}
-
-function __wbg_finalize_init(instance, module) {
-    wasm = instance.exports;
-    __wbg_init.__wbindgen_wasm_module = module;
-    cachedDataViewMemory0 = null;
-    cachedUint8ArrayMemory0 = null;
-
-
-    wasm.__wbindgen_start();
-    return wasm;
+        return false;
+    }
 }
 
 function initSync(module) {
     if (wasm !== undefined) return wasm;
 
 
-    if (typeof module !== 'undefined') {
+    if (module !== undefined) {
         if (Object.getPrototypeOf(module) === Object.prototype) {
             ({module} = module)
         } else {
@@ -199,15 +2082,10 @@ function initSync(module) {
     }
 
     const imports = __wbg_get_imports();
-
-    __wbg_init_memory(imports);
-
     if (!(module instanceof WebAssembly.Module)) {
         module = new WebAssembly.Module(module);
     }
-
     const instance = new WebAssembly.Instance(module, imports);
-
     return __wbg_finalize_init(instance, module);
 }
 
@@ -215,7 +2093,7 @@ async function __wbg_init(module_or_path) {
     if (wasm !== undefined) return wasm;
 
 
-    if (typeof module_or_path !== 'undefined') {
+    if (module_or_path !== undefined) {
         if (Object.getPrototypeOf(module_or_path) === Object.prototype) {
             ({module_or_path} = module_or_path)
         } else {
@@ -223,7 +2101,7 @@ async function __wbg_init(module_or_path) {
         }
     }
 
-    if (typeof module_or_path === 'undefined') {
+    if (module_or_path === undefined) {
         module_or_path = new URL('solver_bg.wasm', import.meta.url);
     }
     const imports = __wbg_get_imports();
@@ -232,12 +2110,9 @@ async function __wbg_init(module_or_path) {
         module_or_path = fetch(module_or_path);
     }
 
-    __wbg_init_memory(imports);
-
     const { instance, module } = await __wbg_load(await module_or_path, imports);
 
     return __wbg_finalize_init(instance, module);
 }
 
-export { initSync };
-export default __wbg_init;
+export { initSync, __wbg_init as default };
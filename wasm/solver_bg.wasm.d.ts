@@ -1,9 +1,80 @@
 /* tslint:disable */
 /* eslint-disable */
 export const memory: WebAssembly.Memory;
-export function solve(a: number, b: number): Array;
-export const __wbindgen_export_0: WebAssembly.Table;
-export function __wbindgen_malloc(a: number, b: number): number;
-export function __wbindgen_realloc(a: number, b: number, c: number, d: number): number;
-export function __wbindgen_free(a: number, b: number, c: number): void;
-export function __wbindgen_start(): void;
+export const __wbg_boardeditor_free: (a: number, b: number) => void;
+export const __wbg_puzzlepack_free: (a: number, b: number) => void;
+export const __wbg_solutioniterator_free: (a: number, b: number) => void;
+export const __wbg_solvesession_free: (a: number, b: number) => void;
+export const benchmark_options: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+export const boardeditor_get_board_json: (a: number) => [number, number];
+export const boardeditor_new: (a: number, b: number) => [number, number, number];
+export const boardeditor_set_color: (a: number, b: number, c: number, d: number) => [number, number];
+export const boardeditor_status: (a: number) => [number, number, number, number];
+export const boardeditor_undo: (a: number) => number;
+export const branch_census: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const check_marks: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const classify_symmetry: (a: number, b: number) => [number, number, number, number];
+export const clear_activity: () => void;
+export const click_plan: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+export const conflicts: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const count_solutions: (a: number, b: number) => [number, number, number, number];
+export const count_solutions_mod_symmetry: (a: number, b: number) => [number, number, number, number];
+export const debug_bundle: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const decode_reply: (a: number, b: number) => [number, number, number, number];
+export const derive_variants: (a: number, b: number, c: number, d: bigint) => [number, number, number, number];
+export const diagnose_scrape: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const diff_boards: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const difficulty_breakdown: (a: number, b: number) => [number, number, number, number];
+export const domains: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+export const encode_request: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+export const estimate_solution_count: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const find_duplicates: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const find_technique: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+export const game_from_any: (a: number, b: number) => [number, number, number, number];
+export const generate_lesson: (a: number, b: number, c: number, d: number, e: bigint) => [number, number, number, number];
+export const grade_replay: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const handle_request: (a: number, b: number) => [number, number];
+export const hints: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+export const is_isomorphic: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const make_pack: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const make_quiz: (a: number, b: number, c: number, d: number, e: bigint) => [number, number, number, number];
+export const max_diff_solutions: (a: number, b: number, c: number) => [number, number, number, number];
+export const minimal_hints: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const nearest_solution: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const normalize_game: (a: number, b: number) => [number, number, number, number];
+export const puzzlepack_find_by_hash: (a: number, b: number, c: number) => number;
+export const puzzlepack_get: (a: number, b: number) => [number, number, number, number];
+export const puzzlepack_is_empty: (a: number) => number;
+export const puzzlepack_len: (a: number) => number;
+export const puzzlepack_meta: (a: number, b: number) => [number, number, number, number];
+export const puzzlepack_new: (a: number, b: number) => [number, number, number];
+export const puzzlepack_title: (a: number) => [number, number];
+export const puzzlepack_verify: (a: number) => [number, number, number, number];
+export const rebalance: (a: number, b: number, c: bigint, d: number) => [number, number, number, number];
+export const recent_activity: () => [number, number];
+export const region_analysis: (a: number, b: number) => [number, number, number, number];
+export const resize: (a: number, b: number, c: number, d: number, e: bigint) => [number, number, number, number];
+export const run_self_tests: () => [number, number, number, number];
+export const safe_cells: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const set_activity_capacity: (a: number) => void;
+export const shrink: (a: number, b: number, c: number, d: number, e: bigint) => [number, number, number, number];
+export const solutioniterator_new: (a: number, b: number) => [number, number, number];
+export const solutioniterator_next: (a: number) => [number, number, number, number];
+export const solve: (a: number, b: number) => [number, number, number, number];
+export const solve_constrained: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+export const solve_with_options: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const solvesession_continue_solve: (a: number, b: number) => [number, number, number, number];
+export const solvesession_new: (a: number, b: number, c: number, d: number) => [number, number, number];
+export const solvesession_set_options: (a: number, b: number, c: number) => [number, number];
+export const tune_ordering_weights: (a: number, b: number) => [number, number, number, number];
+export const validate_game: (a: number, b: number) => [number, number, number, number];
+export const walkthrough: (a: number, b: number, c: number, d: number) => [number, number, number, number];
+export const what_if: (a: number, b: number, c: number, d: number, e: number, f: number) => [number, number, number, number];
+export const what_if_recolor: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+export const what_if_recolor_batch: (a: number, b: number, c: number, d: number, e: number) => [number, number, number, number];
+export const __wbindgen_free: (a: number, b: number, c: number) => void;
+export const __wbindgen_malloc: (a: number, b: number) => number;
+export const __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
+export const __wbindgen_externrefs: WebAssembly.Table;
+export const __externref_table_dealloc: (a: number) => void;
+export const __wbindgen_start: () => void;